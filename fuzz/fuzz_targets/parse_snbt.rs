@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustnbt::{ snbt, tag::Tag };
+
+fuzz_target!(|text: &str| {
+	if let Ok(tag) = Tag::parse(text) {
+		// Anything that parses must parse again to the same SNBT after being written.
+		let written = snbt::to_string(&tag);
+		let again = Tag::parse(&written).expect("Written SNBT parses.");
+		assert_eq!(snbt::to_string(&again), written);
+	}
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustnbt::{ io::*, tag::NamedTag };
+
+// Length prefixes are trusted by the reader, so inputs with huge lengths show up as out of memory reports.
+fuzz_target!(|data: &[u8]| {
+	let mut reader = data;
+	if let Ok(named) = reader.read_nbt::<NamedTag>() {
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&named).expect("Writing to a Vec does not fail.");
+	}
+	let _ = rustnbt::io::carve(data);
+});
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustnbt::region::Region;
+
+fuzz_target!(|data: &[u8]| {
+	// Regions are read from files, so each input is written to a file of its own process.
+	let path = std::env::temp_dir().join(format!("rustnbt-fuzz-{}", std::process::id())).join("r.0.0.mca");
+	std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+	std::fs::write(&path, data).unwrap();
+	if let Ok(mut region) = Region::open(&path) {
+		let chunks: Vec<_> = region.chunks().collect();
+		for chunk in chunks {
+			let _ = region.read_chunk(chunk);
+		}
+	}
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustnbt::{ io::*, tag::NamedTag };
+
+fuzz_target!(|named: NamedTag| {
+	// Compare bytes rather than tags, since NaN values are not equal to themselves.
+	let mut bytes = Vec::new();
+	bytes.write_nbt(&named).expect("Writing to a Vec does not fail.");
+	let read: NamedTag = bytes.as_slice().read_nbt().expect("Written NBT reads back.");
+	let mut again = Vec::new();
+	again.write_nbt(&read).expect("Writing to a Vec does not fail.");
+	assert_eq!(bytes, again);
+});
@@ -30,6 +30,22 @@ use syn::{
     parenthesized,
 };
 
+mod snbt;
+
+/// Parses an SNBT string literal at compile time into an expression that builds the `rustnbt::tag::Tag` it stands
+/// for, so embedded templates cost nothing to parse at run time and invalid SNBT is a compile error.
+///
+/// The syntax is the same as `Tag::parse`. Later values of duplicate keys replace earlier ones, and Lists with
+/// elements of different types are an error.
+#[proc_macro]
+pub fn snbt(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as syn::LitStr);
+    match snbt::parse(&literal.value()) {
+        Ok(value) => value.tag().into(),
+        Err(message) => syn::Error::new(literal.span(), message).to_compile_error().into(),
+    }
+}
+
 /*
 struct Whatever {
 	name: String,
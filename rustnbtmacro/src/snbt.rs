@@ -0,0 +1,392 @@
+// Reads SNBT for the snbt! macro. This crate cannot depend on rustnbt, so this is a copy of the single-pass reader
+// in rustnbt's snbt module that builds [Value]s instead of tags. It must accept the same syntax: rustnbt's tests
+// compare the two.
+
+use proc_macro2::TokenStream;
+use quote::{ quote, format_ident };
+
+/// A tag read from SNBT.
+pub enum Value {
+	Byte(i8),
+	Short(i16),
+	Int(i32),
+	Long(i64),
+	Float(f32),
+	Double(f64),
+	ByteArray(Vec<i8>),
+	String(String),
+	List(Vec<Value>),
+	Compound(Vec<(String, Value)>),
+	IntArray(Vec<i32>),
+	LongArray(Vec<i64>),
+}
+
+impl Value {
+	/// The name of the variant of `Tag` and `ListTag` for this value.
+	fn variant(&self) -> &'static str {
+		match self {
+			Value::Byte(_) => "Byte",
+			Value::Short(_) => "Short",
+			Value::Int(_) => "Int",
+			Value::Long(_) => "Long",
+			Value::Float(_) => "Float",
+			Value::Double(_) => "Double",
+			Value::ByteArray(_) => "ByteArray",
+			Value::String(_) => "String",
+			Value::List(_) => "List",
+			Value::Compound(_) => "Compound",
+			Value::IntArray(_) => "IntArray",
+			Value::LongArray(_) => "LongArray",
+		}
+	}
+
+	/// An expression that builds this value as a `Tag`.
+	pub fn tag(&self) -> TokenStream {
+		let variant = format_ident!("{}", self.variant());
+		let payload = self.payload();
+		quote!(::rustnbt::tag::Tag::#variant(#payload))
+	}
+
+	/// An expression that builds the payload of this value, such as an `i8` for a Byte or a `ListTag` for a List.
+	fn payload(&self) -> TokenStream {
+		match self {
+			Value::Byte(value) => quote!(#value),
+			Value::Short(value) => quote!(#value),
+			Value::Int(value) => quote!(#value),
+			Value::Long(value) => quote!(#value),
+			// Bits keep infinities and every digit of the value.
+			Value::Float(value) => {
+				let bits = value.to_bits();
+				quote!(f32::from_bits(#bits))
+			}
+			Value::Double(value) => {
+				let bits = value.to_bits();
+				quote!(f64::from_bits(#bits))
+			}
+			Value::ByteArray(values) => quote!(::std::vec![#(#values),*]),
+			Value::IntArray(values) => quote!(::std::vec![#(#values),*]),
+			Value::LongArray(values) => quote!(::std::vec![#(#values),*]),
+			Value::String(value) => quote!(::std::string::String::from(#value)),
+			Value::List(values) => match values.first() {
+				None => quote!(::rustnbt::tag::ListTag::Empty),
+				Some(first) => {
+					let variant = format_ident!("{}", first.variant());
+					let payloads = values.iter().map(Value::payload);
+					quote!(::rustnbt::tag::ListTag::#variant(::std::vec![#(#payloads),*]))
+				}
+			},
+			Value::Compound(entries) if entries.is_empty() => quote!(<::rustnbt::Map as ::std::default::Default>::default()),
+			Value::Compound(entries) => {
+				let entries = entries.iter().map(|(key, value)| {
+					let tag = value.tag();
+					quote!((::std::string::String::from(#key), #tag))
+				});
+				quote!(<::rustnbt::Map as ::std::iter::FromIterator<_>>::from_iter([#(#entries),*]))
+			}
+		}
+	}
+}
+
+/// Reads SNBT into a [Value], or returns the error message.
+pub fn parse(source: &str) -> Result<Value, String> {
+	let mut reader = Reader { source, position: 0 };
+	let value = reader.value()?;
+	match reader.skip_whitespace() {
+		None => Ok(value),
+		found => Err(reader.unexpected(reader.position, found, "the end of the input")),
+	}
+}
+
+fn is_ident_char(c: char) -> bool {
+	c.is_ascii_alphanumeric() || ['_', '-', '+', '.'].contains(&c)
+}
+
+struct Reader<'a> {
+	source: &'a str,
+	/// The byte offset of the next character.
+	position: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn rest(&self) -> &'a str {
+		&self.source[self.position..]
+	}
+
+	fn skip_whitespace(&mut self) -> Option<char> {
+		let rest = self.rest();
+		let trimmed = rest.trim_start();
+		self.position += rest.len() - trimmed.len();
+		trimmed.chars().next()
+	}
+
+	fn error(&self, position: usize, message: String) -> String {
+		format!("Invalid SNBT at character {}: {message}", self.source[..position].chars().count())
+	}
+
+	fn unexpected(&self, position: usize, found: Option<char>, expected: &str) -> String {
+		match found {
+			Some(c) => self.error(position, format!("Expected {expected}, found {c:?}.")),
+			None => self.error(position, format!("Expected {expected}, found the end of the input.")),
+		}
+	}
+
+	fn eat(&mut self, expected: char) -> bool {
+		if self.skip_whitespace() == Some(expected) {
+			self.position += expected.len_utf8();
+			true
+		} else {
+			false
+		}
+	}
+
+	fn separator(&mut self, close: char, trailing: bool) -> Result<bool, String> {
+		if self.eat(',') {
+			Ok(trailing && self.eat(close))
+		} else if self.eat(close) {
+			Ok(true)
+		} else {
+			Err(self.unexpected(self.position, self.rest().chars().next(), &format!("',' or '{close}'")))
+		}
+	}
+
+	fn value(&mut self) -> Result<Value, String> {
+		match self.skip_whitespace() {
+			Some('{') => self.compound(),
+			Some('[') => self.list(),
+			Some(quote @ ('"' | '\'')) => Ok(Value::String(self.quoted(quote)?)),
+			_ => {
+				let position = self.position;
+				let word = self.word()?;
+				word_value(word).map_err(|message| self.error(position, message))
+			}
+		}
+	}
+
+	fn compound(&mut self) -> Result<Value, String> {
+		self.position += 1;
+		let mut entries: Vec<(String, Value)> = Vec::new();
+		if self.eat('}') {
+			return Ok(Value::Compound(entries));
+		}
+		loop {
+			let key = match self.skip_whitespace() {
+				Some(quote @ ('"' | '\'')) => self.quoted(quote)?,
+				_ => self.word()?.to_owned(),
+			};
+			if !self.eat(':') {
+				return Err(self.unexpected(self.position, self.rest().chars().next(), "':'"));
+			}
+			let value = self.value()?;
+			// Later values replace earlier ones, as they do when SNBT is parsed at run time.
+			match entries.iter_mut().find(|(existing, _)| *existing == key) {
+				Some((_, existing)) => *existing = value,
+				None => entries.push((key, value)),
+			}
+			if self.separator('}', true)? {
+				return Ok(Value::Compound(entries));
+			}
+		}
+	}
+
+	fn list(&mut self) -> Result<Value, String> {
+		let start = self.position;
+		self.position += 1;
+		let mut chars = self.rest().chars();
+		if let (Some(kind), Some(';')) = (chars.next(), chars.next()) {
+			if matches!(kind.to_ascii_uppercase(), 'B' | 'I' | 'L') {
+				self.position += 2;
+				return self.array(kind.to_ascii_uppercase());
+			}
+		}
+		let mut values: Vec<Value> = Vec::new();
+		if !self.eat(']') {
+			loop {
+				values.push(self.value()?);
+				if self.separator(']', true)? {
+					break;
+				}
+			}
+		}
+		if let Some(other) = values.iter().find(|value| value.variant() != values[0].variant()) {
+			return Err(self.error(start, format!("A List cannot have both {} and {} elements.", values[0].variant(), other.variant())));
+		}
+		Ok(Value::List(values))
+	}
+
+	fn array(&mut self, kind: char) -> Result<Value, String> {
+		let mut values = Vec::new();
+		if !self.eat(']') {
+			loop {
+				let position = self.position;
+				values.push((position, word_value(self.word()?).map_err(|message| self.error(position, message))?));
+				if self.separator(']', false)? {
+					break;
+				}
+			}
+		}
+		macro_rules! elements {
+			($name:literal, $variant:ident) => {
+				values.into_iter().map(|(position, value)| match value {
+					Value::$variant(value) => Ok(value),
+					_ => Err(self.error(position, format!("Expected {} in {} Array.", $name, stringify!($variant)))),
+				}).collect::<Result<Vec<_>, String>>()
+			};
+		}
+		Ok(match kind {
+			'B' => Value::ByteArray(elements!("a Byte", Byte)?),
+			'I' => Value::IntArray(elements!("an Int", Int)?),
+			_ => Value::LongArray(elements!("a Long", Long)?),
+		})
+	}
+
+	fn word(&mut self) -> Result<&'a str, String> {
+		let found = self.skip_whitespace();
+		let rest = self.rest();
+		let length = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+		if length == 0 {
+			return Err(self.unexpected(self.position, found, "a value"));
+		}
+		self.position += length;
+		Ok(&rest[..length])
+	}
+
+	fn quoted(&mut self, quote: char) -> Result<String, String> {
+		let start = self.position;
+		self.position += 1;
+		let mut value = String::new();
+		loop {
+			let rest = self.rest();
+			let Some(length) = rest.find([quote, '\\']) else {
+				return Err(self.error(start, "The String is not closed.".to_owned()));
+			};
+			value.push_str(&rest[..length]);
+			self.position += length + 1;
+			if rest[length..].starts_with(quote) {
+				return Ok(value);
+			}
+			if self.rest().starts_with('u') {
+				value.push(self.unicode_escape()?);
+				continue;
+			}
+			let escaped = match self.rest().chars().next() {
+				Some(c @ ('\\' | '/' | '"' | '\'')) => c,
+				Some('b') => '\x08',
+				Some('f') => '\x0C',
+				Some('n') => '\n',
+				Some('r') => '\r',
+				Some('t') => '\t',
+				found => return Err(self.unexpected(self.position, found, "an escape sequence")),
+			};
+			value.push(escaped);
+			self.position += 1;
+		}
+	}
+
+	/// Reads a `\u` escape, from the `u` after the backslash, along with the escape of the low surrogate after a high
+	/// one. Unpaired surrogates are errors.
+	fn unicode_escape(&mut self) -> Result<char, String> {
+		let start = self.position - 1;
+		let unit = self.code_unit()?;
+		if (0xD800..0xDC00).contains(&unit) && self.rest().starts_with("\\u") {
+			let position = self.position;
+			self.position += 1;
+			let low = self.code_unit()?;
+			if let Some(Ok(c)) = char::decode_utf16([unit, low]).next() {
+				return Ok(c);
+			}
+			self.position = position;
+		}
+		char::from_u32(unit.into())
+			.ok_or_else(|| self.error(start, format!("{} is an unpaired surrogate, which can not be in a String.", &self.source[start..self.position])))
+	}
+
+	/// Reads the `u` and the four hexadecimal digits of a `\u` escape.
+	fn code_unit(&mut self) -> Result<u16, String> {
+		let digits = self.rest().get(1..5).filter(|digits| digits.bytes().all(|digit| digit.is_ascii_hexdigit()));
+		let Some(unit) = digits.and_then(|digits| u16::from_str_radix(digits, 16).ok()) else {
+			return Err(self.error(self.position, "Expected four hexadecimal digits after \\u.".to_owned()));
+		};
+		self.position += 5;
+		Ok(unit)
+	}
+}
+
+/// Returns the value an unquoted word stands for: a number if it has one of the forms of numbers of the game, a Byte
+/// for `true` and `false` in any case, and a String otherwise. This is `word_kind` in rustnbt's snbt module.
+fn word_value(word: &str) -> Result<Value, String> {
+	if word.eq_ignore_ascii_case("true") {
+		return Ok(Value::Byte(1));
+	}
+	if word.eq_ignore_ascii_case("false") {
+		return Ok(Value::Byte(0));
+	}
+	let unsigned = word.strip_prefix(['+', '-']).unwrap_or(word);
+	let (body, suffix) = match unsigned.as_bytes().last() {
+		Some(suffix) if suffix.is_ascii_alphabetic() => (&unsigned[..unsigned.len() - 1], Some(suffix.to_ascii_lowercase())),
+		_ => (unsigned, None),
+	};
+	let digits = &word[..word.len() - unsigned.len() + body.len()];
+	macro_rules! number {
+		($variant:ident, $rank:literal) => {
+			digits.parse().map(Value::$variant).map_err(|_| out_of_range(word, digits, $rank))
+		};
+	}
+	// Floats and Doubles that are too large parse as infinity, so only integers can fail.
+	match suffix {
+		Some(b'f') if is_decimal(body, false) => number!(Float, 0),
+		Some(b'd') if is_decimal(body, false) => number!(Double, 0),
+		Some(b'b') if is_integer(body) => number!(Byte, 0),
+		Some(b's') if is_integer(body) => number!(Short, 1),
+		Some(b'l') if is_integer(body) => number!(Long, 3),
+		None if is_integer(body) => number!(Int, 2),
+		None if is_decimal(body, true) => number!(Double, 0),
+		_ => Ok(Value::String(word.to_owned())),
+	}
+}
+
+/// The message for an integer word that is out of the range of the integer type at `rank` (Byte, Short, Int, Long),
+/// suggesting the suffix of the narrowest wider type that it fits. This is the message of rustnbt's snbt module.
+fn out_of_range(word: &str, digits: &str, rank: usize) -> String {
+	const TYPES: [(&str, &str, i128, i128); 4] = [
+		("Byte", "b", i8::MIN as i128, i8::MAX as i128),
+		("Short", "s", i16::MIN as i128, i16::MAX as i128),
+		("Int", "", i32::MIN as i128, i32::MAX as i128),
+		("Long", "L", i64::MIN as i128, i64::MAX as i128),
+	];
+	let name = TYPES[rank].0;
+	let suggestion = digits.parse::<i128>().ok().and_then(|value| TYPES[rank + 1..].iter().find(|(_, _, min, max)| (*min..=*max).contains(&value)));
+	match suggestion {
+		Some((_, suffix, ..)) => format!("{word} exceeds {name}; did you mean {digits}{suffix}?"),
+		None => format!("{word} exceeds {name}, and no integer type is wider."),
+	}
+}
+
+/// Whether text is the digits of an integer, `0|[1-9][0-9]*`.
+fn is_integer(text: &str) -> bool {
+	match text.as_bytes() {
+		[b'0'] => true,
+		[b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+		_ => false,
+	}
+}
+
+/// Whether text is the digits of a decimal, `[0-9]+[.]?|[0-9]*[.][0-9]+` followed by an optional exponent,
+/// `e[-+]?[0-9]+`.
+fn is_decimal(text: &str, point_required: bool) -> bool {
+	let digits = |text: &str| text.bytes().take_while(u8::is_ascii_digit).count();
+	let integer = digits(text);
+	let (length, point) = match text[integer..].strip_prefix('.') {
+		Some(fraction) => (integer + 1 + digits(fraction), true),
+		None => (integer, false),
+	};
+	if length == usize::from(point) || (point_required && !point) {
+		return false;
+	}
+	match text[length..].strip_prefix(['e', 'E']) {
+		Some(exponent) => {
+			let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+			!exponent.is_empty() && digits(exponent) == exponent.len()
+		}
+		None => length == text.len(),
+	}
+}
@@ -0,0 +1,505 @@
+#![doc = r#"
+Scrubbing of personal data from tags and worlds, so that they can be shared in bug reports.
+
+[Anonymizer] replaces player UUIDs, player names, IP addresses, and chat text (sign lines, book pages, and custom names)
+with placeholders. Placeholders are derived from a hash of the original value and a salt, so the same value is always
+replaced with the same placeholder and references between files stay intact. Anyone who knows the salt can test whether a
+placeholder belongs to a guessed value, so use a random salt when the mapping must not be recoverable.
+
+Values are recognized by the keys the game stores them under:
+- UUIDs are IntArrays of 4 integers under keys such as `UUID`, `Owner`, or `Trusted`, `...UUIDMost`/`...UUIDLeast` Long pairs,
+  and Strings in the hyphenated UUID form.
+- Player names are the `Name` of `SkullOwner` and `profile` Compounds, along with `author` and `OwnerName`.
+  The `Properties` of those profiles hold skin textures which contain the name, so they are removed.
+  Names given with [Anonymizer::player_name] are also replaced wherever they appear inside other Strings.
+- IP addresses are IPv4 addresses anywhere inside Strings.
+- Chat text is every String under the keys in [TEXT_KEYS] or those given with [Anonymizer::text_key].
+
+```
+# use rustnbt::{ compound, tag::Tag, anonymize::Anonymizer };
+let anonymizer = Anonymizer::new(7).player_name("Notch");
+let mut tag = compound!(
+    ("UUID", Tag::IntArray(vec![1, 2, 3, 4])),
+    ("Text1", "{\"text\":\"Notch was here\"}"),
+    ("LastServer", "Notch@192.168.0.10")
+);
+let report = anonymizer.scrub(&mut tag);
+assert_eq!(report.uuids, 1);
+assert_eq!(report.texts, 1);
+let Tag::Compound(map) = &tag else { unreachable!() };
+assert_eq!(map["LastServer"], Tag::String(format!("{}@{}", anonymizer.name("Notch"), anonymizer.address([192, 168, 0, 10].into()))));
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	region::{
+		Region,
+		RawChunk,
+	},
+	tag::{
+		Tag,
+		ListTag,
+	},
+	world::{
+		World,
+		RegionKind,
+		read_gzip_nbt,
+		write_gzip_nbt,
+	},
+};
+use std::{
+	collections::HashSet,
+	net::Ipv4Addr,
+};
+
+/// Keys under which Compounds hold UUIDs that are not named `UUID`.
+pub const UUID_KEYS: [&str; 9] = ["Owner", "Thrower", "Target", "Trusted", "LoveCause", "AngryAt", "angry_at", "Id", "id"];
+/// Keys of the Compounds that describe a player profile.
+pub const PROFILE_KEYS: [&str; 3] = ["SkullOwner", "profile", "SkullProfile"];
+/// Keys that hold a player name directly.
+pub const NAME_KEYS: [&str; 3] = ["author", "OwnerName", "LastKnownName"];
+/// Keys whose Strings, including those nested in Lists and Compounds below them, are chat text.
+pub const TEXT_KEYS: [&str; 11] = ["Text1", "Text2", "Text3", "Text4", "messages", "filtered_messages", "pages", "filtered_pages", "title", "CustomName", "minecraft:custom_name"];
+
+/// The number of values that were replaced.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct AnonymizeReport {
+	pub uuids: usize,
+	pub names: usize,
+	pub addresses: usize,
+	pub texts: usize,
+	/// Files that were rewritten by [Anonymizer::scrub_world], including chunks in region files.
+	pub files: usize,
+}
+
+impl AnonymizeReport {
+	/// The total number of replaced values.
+	pub fn total(&self) -> usize {
+		self.uuids + self.names + self.addresses + self.texts
+	}
+}
+
+impl std::ops::AddAssign for AnonymizeReport {
+	fn add_assign(&mut self, other: Self) {
+		self.uuids += other.uuids;
+		self.names += other.names;
+		self.addresses += other.addresses;
+		self.texts += other.texts;
+		self.files += other.files;
+	}
+}
+
+/// Replaces personal data with deterministic placeholders. See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Anonymizer {
+	salt: u64,
+	player_names: Vec<String>,
+	text_keys: HashSet<String>,
+}
+
+/// Where a value is in the tree.
+#[derive(Clone, Copy)]
+struct Context<'a> {
+	key: Option<&'a str>,
+	parent: Option<&'a str>,
+	in_text: bool,
+}
+
+impl Anonymizer {
+	/// Creates an anonymizer whose placeholders are derived with `salt`.
+	pub fn new(salt: u64) -> Self {
+		Self {
+			salt,
+			player_names: Vec::new(),
+			text_keys: TEXT_KEYS.iter().map(|key| (*key).to_owned()).collect(),
+		}
+	}
+
+	/// Adds a player name that is replaced wherever it appears inside a String.
+	pub fn player_name<S: Into<String>>(mut self, name: S) -> Self {
+		let name = name.into();
+		if !name.is_empty() {
+			self.player_names.push(name);
+		}
+		self
+	}
+
+	/// Adds a key whose Strings are chat text.
+	pub fn text_key<S: Into<String>>(mut self, key: S) -> Self {
+		self.text_keys.insert(key.into());
+		self
+	}
+
+	/// FNV-1a over the salt, the kind of value, and the value.
+	fn hash(&self, kind: &str, value: &[u8]) -> u64 {
+		let mut hash = 0xCBF2_9CE4_8422_2325u64;
+		for byte in self.salt.to_le_bytes().iter().chain(kind.as_bytes()).chain([0u8].iter()).chain(value) {
+			hash ^= *byte as u64;
+			hash = hash.wrapping_mul(0x0100_0000_01B3);
+		}
+		hash
+	}
+
+	/// The placeholder for a UUID, which is a valid version 4 UUID.
+	pub fn uuid(&self, uuid: u128) -> u128 {
+		let bytes = uuid.to_be_bytes();
+		let value = (self.hash("uuid", &bytes) as u128) << 64 | self.hash("uuid low", &bytes) as u128;
+		value & !(0xFu128 << 76) & !(0x3u128 << 62) | 0x4u128 << 76 | 0x2u128 << 62
+	}
+
+	/// The placeholder for a player name, which is a valid player name.
+	pub fn name(&self, name: &str) -> String {
+		format!("Player_{:08x}", self.hash("name", name.as_bytes()) as u32)
+	}
+
+	/// The placeholder for an IPv4 address, which is in the private `10.0.0.0/8` range.
+	pub fn address(&self, address: Ipv4Addr) -> Ipv4Addr {
+		let [_, a, b, c, ..] = self.hash("address", &address.octets()).to_be_bytes();
+		Ipv4Addr::new(10, a, b, c)
+	}
+
+	/// The placeholder for chat text.
+	/// Text that looks like a JSON text component is replaced with a JSON string, so that it stays valid JSON.
+	pub fn text(&self, text: &str) -> String {
+		let placeholder = format!("Text_{:08x}", self.hash("text", text.as_bytes()) as u32);
+		match text.trim_start().chars().next() {
+			Some('{' | '[' | '"') => format!("\"{placeholder}\""),
+			_ => placeholder,
+		}
+	}
+
+	/// Replaces the personal data in a tag.
+	pub fn scrub(&self, tag: &mut Tag) -> AnonymizeReport {
+		let mut report = AnonymizeReport::default();
+		self.tag(tag, Context { key: None, parent: None, in_text: false }, &mut report);
+		report
+	}
+
+	/// Replaces the personal data in `level.dat`, the player data files, and every chunk of every dimension of a world.
+	/// Player files are renamed after the placeholder of their UUID, along with their statistics and advancements.
+	/// Chunks keep their timestamps, and only files and chunks that changed are written.
+	pub fn scrub_world(&self, world: &World) -> Result<AnonymizeReport, NbtError> {
+		let mut report = AnonymizeReport::default();
+		let level = world.root().join("level.dat");
+		if level.is_file() {
+			let mut root = read_gzip_nbt(&level)?;
+			let changes = self.scrub(root.tag_mut());
+			if changes.total() > 0 {
+				write_gzip_nbt(&level, &root)?;
+				report += changes;
+				report.files += 1;
+			}
+		}
+		for uuid in world.player_uuids()? {
+			let mut root = read_gzip_nbt(world.player_path(&uuid))?;
+			report += self.scrub(root.tag_mut());
+			let placeholder = parse_uuid(&uuid).map_or_else(|| uuid.clone(), |value| format_uuid(self.uuid(value)));
+			write_gzip_nbt(world.player_path(&placeholder), &root)?;
+			report.files += 1;
+			if placeholder != uuid {
+				report.uuids += 1;
+				std::fs::remove_file(world.player_path(&uuid))?;
+				for (from, to) in [
+					(world.stats_path(&uuid), world.stats_path(&placeholder)),
+					(world.advancements_path(&uuid), world.advancements_path(&placeholder)),
+				] {
+					if from.is_file() {
+						std::fs::rename(from, to)?;
+					}
+				}
+			}
+		}
+		for dimension in world.dimensions()? {
+			for kind in RegionKind::ALL {
+				for position in world.regions(&dimension, kind)? {
+					let mut region = Region::open(world.region_path(&dimension, kind, position))?;
+					let chunks: Vec<_> = region.chunks().collect();
+					for chunk in chunks {
+						let Some(mut data) = region.read_chunk(chunk)? else {
+							continue;
+						};
+						let changes = self.scrub(data.tag_mut());
+						if changes.total() > 0 {
+							let timestamp = region.timestamp(chunk).unwrap_or(0);
+							region.write_raw_chunk(chunk, &RawChunk::encode(&data)?, timestamp)?;
+							report += changes;
+							report.files += 1;
+						}
+					}
+				}
+			}
+		}
+		Ok(report)
+	}
+
+	fn tag(&self, tag: &mut Tag, context: Context, report: &mut AnonymizeReport) {
+		match tag {
+			Tag::IntArray(values) => self.int_array(values, context, report),
+			Tag::String(value) => self.string(value, context, report),
+			Tag::List(list) => self.list(list, context, report),
+			Tag::Compound(map) => self.map(map, context, report),
+			_ => (),
+		}
+	}
+
+	fn list(&self, list: &mut ListTag, context: Context, report: &mut AnonymizeReport) {
+		match list {
+			ListTag::IntArray(arrays) => arrays.iter_mut().for_each(|values| self.int_array(values, context, report)),
+			ListTag::String(values) => values.iter_mut().for_each(|value| self.string(value, context, report)),
+			ListTag::List(lists) => lists.iter_mut().for_each(|list| self.list(list, context, report)),
+			ListTag::Compound(maps) => maps.iter_mut().for_each(|map| self.map(map, context, report)),
+			_ => (),
+		}
+	}
+
+	fn map(&self, map: &mut Map, context: Context, report: &mut AnonymizeReport) {
+		let pairs: Vec<String> = map.keys()
+			.filter_map(|key| key.strip_suffix("Most"))
+			.filter(|base| is_uuid_key(base))
+			.filter(|base| matches!((map.get(&format!("{base}Most")), map.get(&format!("{base}Least"))), (Some(Tag::Long(_)), Some(Tag::Long(_)))))
+			.map(str::to_owned)
+			.collect();
+		for base in pairs {
+			let (Some(Tag::Long(most)), Some(Tag::Long(least))) = (map.get(&format!("{base}Most")), map.get(&format!("{base}Least"))) else {
+				continue;
+			};
+			let uuid = self.uuid((*most as u64 as u128) << 64 | *least as u64 as u128);
+			map.insert(format!("{base}Most"), Tag::Long((uuid >> 64) as u64 as i64));
+			map.insert(format!("{base}Least"), Tag::Long(uuid as u64 as i64));
+			report.uuids += 1;
+		}
+		let is_profile = context.key.is_some_and(|key| PROFILE_KEYS.contains(&key));
+		if is_profile {
+			for key in ["Properties", "properties"] {
+				if remove(map, key).is_some() {
+					report.names += 1;
+				}
+			}
+		}
+		for (key, tag) in map.iter_mut() {
+			let in_text = context.in_text || self.text_keys.contains(key);
+			self.tag(tag, Context { key: Some(key), parent: context.key, in_text }, report);
+		}
+	}
+
+	fn int_array(&self, values: &mut [i32], context: Context, report: &mut AnonymizeReport) {
+		let &mut [a, b, c, d] = values else {
+			return;
+		};
+		if !context.key.is_some_and(is_uuid_key) {
+			return;
+		}
+		let uuid = self.uuid((a as u32 as u128) << 96 | (b as u32 as u128) << 64 | (c as u32 as u128) << 32 | d as u32 as u128);
+		for (index, value) in values.iter_mut().enumerate() {
+			*value = (uuid >> (96 - 32 * index)) as u32 as i32;
+		}
+		report.uuids += 1;
+	}
+
+	fn string(&self, value: &mut String, context: Context, report: &mut AnonymizeReport) {
+		if let Some(uuid) = parse_uuid(value) {
+			*value = format_uuid(self.uuid(uuid));
+			report.uuids += 1;
+			return;
+		}
+		let key = context.key.unwrap_or_default();
+		let is_name = NAME_KEYS.contains(&key)
+			|| (key == "SkullOwner")
+			|| (matches!(key, "Name" | "name") && context.parent.is_some_and(|parent| PROFILE_KEYS.contains(&parent)));
+		if is_name {
+			if !value.is_empty() {
+				*value = self.name(value);
+				report.names += 1;
+			}
+			return;
+		}
+		if context.in_text {
+			if !is_blank_text(value) {
+				*value = self.text(value);
+				report.texts += 1;
+			}
+			return;
+		}
+		let mut replaced_name = false;
+		for name in &self.player_names {
+			if value.contains(name.as_str()) {
+				*value = value.replace(name.as_str(), &self.name(name));
+				replaced_name = true;
+			}
+		}
+		if replaced_name {
+			report.names += 1;
+		}
+		if let Some((text, count)) = self.replace_addresses(value) {
+			*value = text;
+			report.addresses += count;
+		}
+	}
+
+	/// Replaces every IPv4 address in `text`, returning the new text and the number of addresses, or `None` if there were none.
+	fn replace_addresses(&self, text: &str) -> Option<(String, usize)> {
+		let bytes = text.as_bytes();
+		let mut output = String::with_capacity(text.len());
+		let mut count = 0;
+		let mut copied = 0;
+		let mut start = 0;
+		while start < bytes.len() {
+			let is_run = |byte: u8| byte.is_ascii_digit() || byte == b'.';
+			if !bytes[start].is_ascii_digit() || (start > 0 && (bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'.')) {
+				start += 1;
+				continue;
+			}
+			let mut end = start;
+			while end < bytes.len() && is_run(bytes[end]) {
+				end += 1;
+			}
+			let mut run_end = end;
+			while bytes[run_end - 1] == b'.' {
+				run_end -= 1;
+			}
+			let followed_by_word = end < bytes.len() && bytes[end].is_ascii_alphanumeric();
+			if let (false, Some(address)) = (followed_by_word, parse_address(&text[start..run_end])) {
+				output.push_str(&text[copied..start]);
+				output.push_str(&self.address(address).to_string());
+				copied = run_end;
+				count += 1;
+			}
+			start = end;
+		}
+		if count == 0 {
+			return None;
+		}
+		output.push_str(&text[copied..]);
+		Some((output, count))
+	}
+}
+
+fn is_uuid_key(key: &str) -> bool {
+	key.to_ascii_lowercase().contains("uuid") || UUID_KEYS.contains(&key)
+}
+
+/// Empty sign lines are stored as empty Strings or empty JSON text components, which are not personal data.
+fn is_blank_text(text: &str) -> bool {
+	matches!(text.trim(), "" | "\"\"" | r#"{"text":""}"#)
+}
+
+fn remove(map: &mut Map, key: &str) -> Option<Tag> {
+	#[cfg(feature = "preserve_order")]
+	return map.shift_remove(key);
+	#[cfg(not(feature = "preserve_order"))]
+	return map.remove(key);
+}
+
+fn parse_address(text: &str) -> Option<Ipv4Addr> {
+	let mut address = [0u8; 4];
+	let mut parts = text.split('.');
+	for octet in address.iter_mut() {
+		let part = parts.next()?;
+		if part.is_empty() || part.len() > 3 {
+			return None;
+		}
+		*octet = part.parse().ok()?;
+	}
+	parts.next().is_none().then_some(address.into())
+}
+
+/// Parses a UUID in the hyphenated form, as in `069a79f4-44e9-4726-a5be-fca90e38aaf5`.
+fn parse_uuid(text: &str) -> Option<u128> {
+	let bytes = text.as_bytes();
+	if bytes.len() != 36 || [8, 13, 18, 23].iter().any(|index| bytes[*index] != b'-') {
+		return None;
+	}
+	let digits: String = text.split('-').collect();
+	if digits.len() != 32 || !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+		return None;
+	}
+	u128::from_str_radix(&digits, 16).ok()
+}
+
+fn format_uuid(uuid: u128) -> String {
+	let digits = format!("{uuid:032x}");
+	format!("{}-{}-{}-{}-{}", &digits[..8], &digits[8..12], &digits[12..16], &digits[16..20], &digits[20..])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn scrub_test() {
+		let anonymizer = Anonymizer::new(1).player_name("Notch");
+		let uuid = Tag::IntArray(vec![1, 2, 3, 4]);
+		let mut tag = compound!(
+			("UUID", uuid.clone()),
+			("Trusted", Tag::List(ListTag::IntArray(vec![vec![1, 2, 3, 4]]))),
+			("Pos", Tag::IntArray(vec![1, 2, 3, 4])),
+			("OwnerUUIDMost", Tag::Long(1)),
+			("OwnerUUIDLeast", Tag::Long(2)),
+			("SkullOwner", compound!(("Name", "Notch"), ("Properties", compound!(("textures", list!("abc")))))),
+			("pages", list!("page one", "")),
+			("Text1", r#"{"text":"hello"}"#),
+			("Text2", r#"{"text":""}"#),
+			("id", "069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+			("log", "Notch joined from 127.0.0.1:25565 on 1.20.4."),
+			("Name", "minecraft:stone")
+		);
+		let original = tag.clone();
+		let report = anonymizer.scrub(&mut tag);
+		assert_eq!(report, AnonymizeReport { uuids: 4, names: 3, addresses: 1, texts: 2, files: 0 });
+		let Tag::Compound(map) = &tag else { unreachable!() };
+		assert_ne!(map["UUID"], uuid);
+		assert_eq!(map["Trusted"], Tag::List(ListTag::IntArray(vec![match &map["UUID"] { Tag::IntArray(values) => values.clone(), _ => unreachable!() }])));
+		assert_eq!(map["Pos"], Tag::IntArray(vec![1, 2, 3, 4]));
+		assert_eq!(map["SkullOwner"], compound!(("Name", anonymizer.name("Notch"))));
+		assert_eq!(map["pages"], list!(anonymizer.text("page one"), String::new()));
+		assert_eq!(map["Text1"], Tag::String(anonymizer.text(r#"{"text":"hello"}"#)));
+		assert!(matches!(&map["Text1"], Tag::String(text) if text.starts_with("\"Text_")));
+		assert_eq!(map["Name"], Tag::from("minecraft:stone"));
+		let Tag::String(id) = &map["id"] else { unreachable!() };
+		assert_eq!(&id[14..15], "4");
+		assert_eq!(map["log"], Tag::String(format!("{} joined from {}:25565 on 1.20.4.", anonymizer.name("Notch"), anonymizer.address([127, 0, 0, 1].into()))));
+
+		let mut again = original.clone();
+		Anonymizer::new(1).player_name("Notch").scrub(&mut again);
+		assert_eq!(again, tag);
+		let mut salted = original;
+		Anonymizer::new(2).player_name("Notch").scrub(&mut salted);
+		assert_ne!(salted, tag);
+		assert_eq!(format_uuid(parse_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap()), "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+		assert_eq!(parse_address("256.1.1.1"), None);
+	}
+
+	#[test]
+	fn world_test() -> Result<(), NbtError> {
+		use crate::{ pos::ChunkPos, tag::NamedTag, world::{ OVERWORLD, tests::temp_world } };
+		let path = temp_world("anonymize");
+		let world = World::open(&path)?;
+		let uuid = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+		write_gzip_nbt(world.player_path(uuid), &NamedTag::new(compound!(("UUID", Tag::IntArray(vec![1, 2, 3, 4])))))?;
+		for kind in RegionKind::ALL {
+			std::fs::create_dir_all(world.region_dir(OVERWORLD, kind))?;
+		}
+		let chunk = ChunkPos::new(0, 0);
+		let mut region = Region::create(world.region_path(OVERWORLD, RegionKind::Entities, chunk.region()))?;
+		region.write_chunk(chunk, &NamedTag::new(compound!(("Entities", Tag::List(ListTag::Compound(vec![
+			Map::from_iter([("Owner".to_owned(), Tag::IntArray(vec![1, 2, 3, 4]))]),
+		]))))))?;
+		region.set_timestamp(chunk, 100)?;
+		drop(region);
+
+		let anonymizer = Anonymizer::new(3);
+		let report = anonymizer.scrub_world(&world)?;
+		assert_eq!(report, AnonymizeReport { uuids: 3, files: 2, ..Default::default() });
+		let placeholder = format_uuid(anonymizer.uuid(parse_uuid(uuid).unwrap()));
+		assert_eq!(world.player_uuids()?, [placeholder]);
+		let mut region = Region::open(world.region_path(OVERWORLD, RegionKind::Entities, chunk.region()))?;
+		assert_eq!(region.timestamp(chunk), Some(100));
+		let mut entities = region.read_chunk(chunk)?.unwrap().take_tag();
+		assert_eq!(anonymizer.scrub(&mut entities).uuids, 1);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+}
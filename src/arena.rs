@@ -0,0 +1,493 @@
+#![doc = r#"
+Tag trees that are allocated in a [bumpalo] arena.
+
+A [Tag] tree owns a `Vec`, `String`, or [Map](crate::Map) for every array, String, and Compound in it, which are all
+allocated and freed one at a time. An [ArenaTag] keeps them in a [Bump] instead, so reading a tree is a few large
+allocations, and the whole tree is freed at once when the arena is reset or dropped. This suits pipelines that read
+and throw away many trees, such as the chunks of a world, with one arena that is reset after each chunk.
+
+[read] reads binary NBT straight into the arena, and [ArenaTag::encode_to] writes a tree to any
+[NbtSink](crate::sink::NbtSink), which [write] uses for binary NBT. [ArenaTag::to_tag] and [ArenaTag::from_tag] convert
+to and from the global allocator.
+
+```
+# use rustnbt::{ arena, compound, io::write_named_tag, tag::Tag };
+use bumpalo::Bump;
+let mut bytes = Vec::new();
+write_named_tag(&mut bytes, &compound!(("DataVersion", 3465), ("Status", "minecraft:full")), "")?;
+let mut bump = Bump::new();
+for _ in 0..3 {
+	let (_, chunk) = arena::read(&mut bytes.as_slice(), &bump)?;
+	assert_eq!(chunk.get("Status").and_then(|status| status.as_str()), Some("minecraft:full"));
+	drop(chunk);
+	// Frees the whole tree.
+	bump.reset();
+}
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	frames::{ self, Tree },
+	io::{ BinarySink, NbtRead, NbtWrite },
+	sink::{ NbtSink, Scalar },
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+	},
+};
+use bumpalo::{ Bump, collections::Vec as BumpVec };
+use std::io::{ Read, Write };
+
+/// A tag whose arrays, Strings, Lists, and Compounds are allocated in a [Bump]. See the [module](self) documentation.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ArenaTag<'b> {
+	Byte(i8),
+	Short(i16),
+	Int(i32),
+	Long(i64),
+	Float(f32),
+	Double(f64),
+	ByteArray(BumpVec<'b, i8>),
+	String(&'b str),
+	List(ArenaList<'b>),
+	Compound(ArenaMap<'b>),
+	IntArray(BumpVec<'b, i32>),
+	LongArray(BumpVec<'b, i64>),
+}
+
+/// The entries of a Compound in a [Bump], in the order that they were read or inserted. Keys are looked up by going
+/// through the entries, which is fast for the small Compounds that most NBT is made of. Reading adds entries without
+/// looking for repeated keys, so it takes linear time, and a Compound with a repeated key keeps both entries, like the
+/// file; lookups and [ArenaTag::to_tag] use the last one.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ArenaMap<'b> {
+	entries: BumpVec<'b, (&'b str, ArenaTag<'b>)>,
+}
+
+/// The elements of a List in a [Bump], which all have the same type. Elements can only be replaced by
+/// [ArenaList::set], which checks their type.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ArenaList<'b> {
+	element: Option<TagID>,
+	items: BumpVec<'b, ArenaTag<'b>>,
+}
+
+impl<'b> ArenaTag<'b> {
+	pub fn id(&self) -> TagID {
+		match self {
+			ArenaTag::Byte(_) => TagID::Byte,
+			ArenaTag::Short(_) => TagID::Short,
+			ArenaTag::Int(_) => TagID::Int,
+			ArenaTag::Long(_) => TagID::Long,
+			ArenaTag::Float(_) => TagID::Float,
+			ArenaTag::Double(_) => TagID::Double,
+			ArenaTag::ByteArray(_) => TagID::ByteArray,
+			ArenaTag::String(_) => TagID::String,
+			ArenaTag::List(_) => TagID::List,
+			ArenaTag::Compound(_) => TagID::Compound,
+			ArenaTag::IntArray(_) => TagID::IntArray,
+			ArenaTag::LongArray(_) => TagID::LongArray,
+		}
+	}
+
+	/// Returns the value of an entry, if this is a Compound that has it.
+	pub fn get(&self, key: &str) -> Option<&ArenaTag<'b>> {
+		match self {
+			ArenaTag::Compound(map) => map.get(key),
+			_ => None,
+		}
+	}
+
+	/// Returns the text of a String.
+	pub fn as_str(&self) -> Option<&'b str> {
+		match self {
+			ArenaTag::String(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	/// Copies a tag into `bump`.
+	pub fn from_tag(tag: &Tag, bump: &'b Bump) -> Self {
+		match tag {
+			Tag::Byte(value) => ArenaTag::Byte(*value),
+			Tag::Short(value) => ArenaTag::Short(*value),
+			Tag::Int(value) => ArenaTag::Int(*value),
+			Tag::Long(value) => ArenaTag::Long(*value),
+			Tag::Float(value) => ArenaTag::Float(*value),
+			Tag::Double(value) => ArenaTag::Double(*value),
+			Tag::ByteArray(values) => ArenaTag::ByteArray(BumpVec::from_iter_in(values.iter().copied(), bump)),
+			Tag::String(value) => ArenaTag::String(bump.alloc_str(value)),
+			Tag::List(list) => {
+				let tags = Vec::from(list.clone());
+				let items = BumpVec::from_iter_in(tags.iter().map(|tag| ArenaTag::from_tag(tag, bump)), bump);
+				ArenaTag::List(ArenaList { element: list.element_id(), items })
+			}
+			Tag::Compound(map) => {
+				let entries = map.iter().map(|(key, tag)| (&*bump.alloc_str(key), ArenaTag::from_tag(tag, bump)));
+				ArenaTag::Compound(ArenaMap { entries: BumpVec::from_iter_in(entries, bump) })
+			}
+			Tag::IntArray(values) => ArenaTag::IntArray(BumpVec::from_iter_in(values.iter().copied(), bump)),
+			Tag::LongArray(values) => ArenaTag::LongArray(BumpVec::from_iter_in(values.iter().copied(), bump)),
+		}
+	}
+
+	/// Copies this tag out of its arena, into the global allocator. Fails if a List has elements of different types.
+	pub fn to_tag(&self) -> Result<Tag, NbtError> {
+		Ok(match self {
+			ArenaTag::Byte(value) => Tag::Byte(*value),
+			ArenaTag::Short(value) => Tag::Short(*value),
+			ArenaTag::Int(value) => Tag::Int(*value),
+			ArenaTag::Long(value) => Tag::Long(*value),
+			ArenaTag::Float(value) => Tag::Float(*value),
+			ArenaTag::Double(value) => Tag::Double(*value),
+			ArenaTag::ByteArray(values) => Tag::ByteArray(values.to_vec()),
+			ArenaTag::String(value) => Tag::String((*value).to_owned()),
+			ArenaTag::List(list) => Tag::List(match list.items.is_empty() {
+				true => ListTag::empty_of(list.element),
+				false => {
+					let tags = list.iter().map(|item| list.check(item).and_then(|_| item.to_tag())).collect::<Result<Vec<_>, _>>()?;
+					ListTag::try_from(tags).map_err(|_| invalid_data("List elements must all have the same type."))?
+				}
+			}),
+			ArenaTag::Compound(map) => Tag::Compound(
+				map.iter().map(|(key, tag)| Ok((key.to_owned(), tag.to_tag()?))).collect::<Result<Map, NbtError>>()?,
+			),
+			ArenaTag::IntArray(values) => Tag::IntArray(values.to_vec()),
+			ArenaTag::LongArray(values) => Tag::LongArray(values.to_vec()),
+		})
+	}
+
+	/// Writes this tag to a sink, like [Tag::encode_to]. Fails if a List has elements of different types.
+	pub fn encode_to<S: NbtSink + ?Sized>(&self, sink: &mut S) -> Result<(), NbtError> {
+		match self {
+			ArenaTag::Byte(value) => sink.scalar(Scalar::Byte(*value)),
+			ArenaTag::Short(value) => sink.scalar(Scalar::Short(*value)),
+			ArenaTag::Int(value) => sink.scalar(Scalar::Int(*value)),
+			ArenaTag::Long(value) => sink.scalar(Scalar::Long(*value)),
+			ArenaTag::Float(value) => sink.scalar(Scalar::Float(*value)),
+			ArenaTag::Double(value) => sink.scalar(Scalar::Double(*value)),
+			ArenaTag::ByteArray(values) => sink.scalar(Scalar::ByteArray(values)),
+			ArenaTag::String(value) => sink.scalar(Scalar::String(value)),
+			ArenaTag::IntArray(values) => sink.scalar(Scalar::IntArray(values)),
+			ArenaTag::LongArray(values) => sink.scalar(Scalar::LongArray(values)),
+			ArenaTag::List(list) => {
+				sink.begin_list(list.element, list.len())?;
+				for item in list.iter() {
+					list.check(item)?;
+					item.encode_to(sink)?;
+				}
+				sink.end_list()
+			}
+			ArenaTag::Compound(map) => {
+				sink.begin_compound(map.len())?;
+				let mut entries: Vec<_> = map.iter().collect();
+				if sink.sorted_keys() {
+					entries.sort_by_key(|(key, _)| *key);
+				}
+				for (key, tag) in entries {
+					sink.key(key, tag.id())?;
+					tag.encode_to(sink)?;
+				}
+				sink.end_compound()
+			}
+		}
+	}
+}
+
+impl<'b> ArenaMap<'b> {
+	pub fn new_in(bump: &'b Bump) -> Self {
+		Self { entries: BumpVec::new_in(bump) }
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns the value of the last entry with the key.
+	pub fn get(&self, key: &str) -> Option<&ArenaTag<'b>> {
+		self.entries.iter().rev().find(|(entry, _)| *entry == key).map(|(_, tag)| tag)
+	}
+
+	/// Mutably borrows the value of the last entry with the key.
+	pub fn get_mut(&mut self, key: &str) -> Option<&mut ArenaTag<'b>> {
+		self.entries.iter_mut().rev().find(|(entry, _)| *entry == key).map(|(_, tag)| tag)
+	}
+
+	/// Sets the value of an entry, returning the old value. New entries are added at the end.
+	pub fn insert(&mut self, key: &'b str, tag: ArenaTag<'b>) -> Option<ArenaTag<'b>> {
+		match self.get_mut(key) {
+			Some(old) => Some(std::mem::replace(old, tag)),
+			None => {
+				self.entries.push((key, tag));
+				None
+			}
+		}
+	}
+
+	/// Removes every entry with the key, returning the value of the last one. The other entries keep their order.
+	pub fn remove(&mut self, key: &str) -> Option<ArenaTag<'b>> {
+		let mut removed = None;
+		let mut index = 0;
+		while index < self.entries.len() {
+			if self.entries[index].0 == key {
+				removed = Some(self.entries.remove(index).1);
+			} else {
+				index += 1;
+			}
+		}
+		removed
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&'b str, &ArenaTag<'b>)> + '_ {
+		self.entries.iter().map(|(key, tag)| (*key, tag))
+	}
+}
+
+impl<'b> ArenaList<'b> {
+	/// An empty List of End tags, which takes the type of the first element that is pushed.
+	pub fn new_in(bump: &'b Bump) -> Self {
+		Self { element: None, items: BumpVec::new_in(bump) }
+	}
+
+	/// The type of the elements, or None for a List of End tags.
+	pub fn element_id(&self) -> Option<TagID> {
+		self.element
+	}
+
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+
+	pub fn get(&self, index: usize) -> Option<&ArenaTag<'b>> {
+		self.items.get(index)
+	}
+
+	/// Replaces an element, returning the old one. Fails if the index is out of range, or the tag has another type
+	/// than the elements.
+	pub fn set(&mut self, index: usize, tag: ArenaTag<'b>) -> Result<ArenaTag<'b>, NbtError> {
+		self.check(&tag)?;
+		let length = self.items.len();
+		let item = self.items.get_mut(index)
+			.ok_or_else(|| invalid_data(&format!("Index {index} is out of range for a List of {length} elements.")))?;
+		Ok(std::mem::replace(item, tag))
+	}
+
+	/// Mutably borrows an element that is a Compound. Its entries can be changed, but not its type.
+	pub fn compound_mut(&mut self, index: usize) -> Option<&mut ArenaMap<'b>> {
+		match self.items.get_mut(index)? {
+			ArenaTag::Compound(map) => Some(map),
+			_ => None,
+		}
+	}
+
+	/// Mutably borrows an element that is a List. Its elements can be changed, but not its type.
+	pub fn list_mut(&mut self, index: usize) -> Option<&mut ArenaList<'b>> {
+		match self.items.get_mut(index)? {
+			ArenaTag::List(list) => Some(list),
+			_ => None,
+		}
+	}
+
+	/// Adds an element at the end. Fails if the List has elements of another type.
+	pub fn push(&mut self, tag: ArenaTag<'b>) -> Result<(), NbtError> {
+		if !self.items.is_empty() {
+			self.check(&tag)?;
+		}
+		self.element = Some(tag.id());
+		self.items.push(tag);
+		Ok(())
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &ArenaTag<'b>> + '_ {
+		self.items.iter()
+	}
+
+	/// Fails if a tag does not have the type of the elements.
+	fn check(&self, tag: &ArenaTag) -> Result<(), NbtError> {
+		match self.element {
+			Some(element) if element == tag.id() => Ok(()),
+			Some(element) => Err(invalid_data(&format!("A List of {element} can not hold a {}.", tag.id()))),
+			None => Err(invalid_data(&format!("A List of End tags can not hold a {}.", tag.id()))),
+		}
+	}
+}
+
+/// Reads [ArenaTag]s into a [Bump] for [frames::read].
+struct ArenaTree<'b>(&'b Bump);
+
+impl<'b> Tree for ArenaTree<'b> {
+	type Tag = ArenaTag<'b>;
+	type Key = &'b str;
+	type Map = ArenaMap<'b>;
+	type Items = BumpVec<'b, ArenaTag<'b>>;
+
+	fn key<R: Read>(&mut self, reader: &mut R) -> Result<&'b str, NbtError> {
+		read_str(reader, self.0)
+	}
+
+	fn leaf<R: Read>(&mut self, reader: &mut R, id: TagID) -> Result<ArenaTag<'b>, NbtError> {
+		Ok(match id {
+			TagID::Byte => ArenaTag::Byte(i8::nbt_read(reader)?),
+			TagID::Short => ArenaTag::Short(i16::nbt_read(reader)?),
+			TagID::Int => ArenaTag::Int(i32::nbt_read(reader)?),
+			TagID::Long => ArenaTag::Long(i64::nbt_read(reader)?),
+			TagID::Float => ArenaTag::Float(f32::nbt_read(reader)?),
+			TagID::Double => ArenaTag::Double(f64::nbt_read(reader)?),
+			TagID::ByteArray => ArenaTag::ByteArray(read_array(reader, self.0)?),
+			TagID::String => ArenaTag::String(read_str(reader, self.0)?),
+			TagID::IntArray => ArenaTag::IntArray(read_array(reader, self.0)?),
+			TagID::LongArray => ArenaTag::LongArray(read_array(reader, self.0)?),
+			TagID::List | TagID::Compound => unreachable!("Lists and Compounds are read by frames::read."),
+		})
+	}
+
+	fn map(&mut self) -> ArenaMap<'b> {
+		ArenaMap::new_in(self.0)
+	}
+
+	fn entry(map: &mut ArenaMap<'b>, key: &'b str, tag: ArenaTag<'b>) {
+		map.entries.push((key, tag));
+	}
+
+	fn compound(map: ArenaMap<'b>) -> ArenaTag<'b> {
+		ArenaTag::Compound(map)
+	}
+
+	fn items(&mut self, capacity: usize) -> BumpVec<'b, ArenaTag<'b>> {
+		BumpVec::with_capacity_in(capacity, self.0)
+	}
+
+	fn item(items: &mut BumpVec<'b, ArenaTag<'b>>, tag: ArenaTag<'b>) {
+		items.push(tag);
+	}
+
+	fn list(element: Option<TagID>, items: BumpVec<'b, ArenaTag<'b>>) -> ArenaTag<'b> {
+		ArenaTag::List(ArenaList { element, items })
+	}
+}
+
+/// Reads a named root tag of binary NBT into `bump`, returning its name and the tag. Tags may be nested up to
+/// [DEFAULT_MAX_DEPTH](crate::io::DEFAULT_MAX_DEPTH) deep, and are read with a stack on the heap instead of by
+/// recursion.
+pub fn read<'b, R: Read>(reader: &mut R, bump: &'b Bump) -> Result<(&'b str, ArenaTag<'b>), NbtError> {
+	frames::read(reader, &mut ArenaTree(bump))
+}
+
+fn read_str<'b, R: Read>(reader: &mut R, bump: &'b Bump) -> Result<&'b str, NbtError> {
+	let length = u16::nbt_read(reader)? as usize;
+	let bytes = bump.alloc_slice_fill_copy(length, 0u8);
+	reader.read_exact(bytes)?;
+	std::str::from_utf8(bytes).map_err(|_| invalid_data("String is not valid UTF-8."))
+}
+
+fn read_array<'b, R: Read, T: NbtRead>(reader: &mut R, bump: &'b Bump) -> Result<BumpVec<'b, T>, NbtError> {
+	let length = u32::nbt_read(reader)? as usize;
+	let mut values = BumpVec::with_capacity_in(length.min(1 << 16), bump);
+	for _ in 0..length {
+		values.push(T::nbt_read(reader)?);
+	}
+	Ok(values)
+}
+
+/// Writes a named root tag as binary NBT, returning the number of bytes that were written. Fails if a List has elements
+/// of different types.
+pub fn write<W: Write>(writer: &mut W, name: &str, tag: &ArenaTag) -> Result<usize, NbtError> {
+	let header = tag.id().nbt_write(writer)? + name.nbt_write(writer)?;
+	let mut sink = BinarySink::new(writer);
+	tag.encode_to(&mut sink)?;
+	Ok(header + sink.size())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, io::{ read_named_tag, write_named_tag, DEFAULT_MAX_DEPTH } };
+
+	#[test]
+	fn arena_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("Bytes", Tag::ByteArray(vec![1, -2])),
+			("Longs", Tag::LongArray(vec![i64::MIN])),
+			("Pos", list!(0.5, 64.0, 0.5)),
+			("Items", Tag::List(ListTag::Compound(vec![Map::from_iter([("id".to_owned(), Tag::from("minecraft:stone"))])]))),
+			("Empty", Tag::List(ListTag::Empty)),
+			("Nested", Tag::List(ListTag::List(vec![ListTag::Short(vec![1]), ListTag::Int(Vec::new())]))),
+			("Name", "é")
+		);
+		let mut bytes = Vec::new();
+		write_named_tag(&mut bytes, &tag, "root")?;
+		let bump = Bump::new();
+		let (name, read_tag) = read(&mut bytes.as_slice(), &bump)?;
+		assert_eq!((name, read_tag.to_tag()?), ("root", tag.clone()));
+		assert_eq!(ArenaTag::from_tag(&tag, &bump), read_tag);
+
+		let mut written = Vec::new();
+		let size = write(&mut written, "root", &read_tag)?;
+		assert_eq!(size, written.len());
+		assert_eq!(read_named_tag(&mut written.as_slice())?, ("root".to_owned(), tag));
+
+		let mut list = ArenaList::new_in(&bump);
+		list.push(ArenaTag::Int(1))?;
+		assert!(list.push(ArenaTag::String("a")).is_err());
+		let mut map = ArenaMap::new_in(&bump);
+		assert!(map.insert("a", ArenaTag::List(list)).is_none());
+		assert!(map.insert("a", ArenaTag::Byte(1)).is_some());
+		assert_eq!(map.len(), 1);
+		assert_eq!(map.remove("a"), Some(ArenaTag::Byte(1)));
+
+		let mut deep = Vec::new();
+		for _ in 0..=DEFAULT_MAX_DEPTH {
+			deep.extend([9, 0, 0, 0, 1]);
+		}
+		assert!(read(&mut [&[9, 0, 0][..], &deep].concat().as_slice(), &bump).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn arena_edit_test() -> Result<(), NbtError> {
+		let tag = compound!(("Items", Tag::List(ListTag::Compound(vec![Map::from_iter([("Slot".to_owned(), Tag::Byte(0))])]))));
+		let mut bytes = Vec::new();
+		write_named_tag(&mut bytes, &tag, "")?;
+		let bump = Bump::new();
+		let (_, mut read_tag) = read(&mut bytes.as_slice(), &bump)?;
+		let ArenaTag::Compound(map) = &mut read_tag else { panic!("The root is a Compound.") };
+		let Some(ArenaTag::List(items)) = map.get_mut("Items") else { panic!("Items is a List.") };
+		// Elements can not be replaced with tags of another type, so the List can always be written.
+		assert!(items.set(0, ArenaTag::Int(1)).is_err());
+		assert!(items.set(1, ArenaTag::Compound(ArenaMap::new_in(&bump))).is_err());
+		items.compound_mut(0).expect("The element is a Compound.").insert("Slot", ArenaTag::Byte(3));
+		let mut written = Vec::new();
+		write(&mut written, "", &read_tag)?;
+		let expected = compound!(("Items", Tag::List(ListTag::Compound(vec![Map::from_iter([("Slot".to_owned(), Tag::Byte(3))])]))));
+		assert_eq!(read_named_tag(&mut written.as_slice())?.1, expected);
+
+		// A Compound with the key "a" twice keeps both, and lookups use the last.
+		let bytes = [10, 0, 0, 1, 0, 1, b'a', 1, 1, 0, 1, b'a', 2, 0];
+		let (_, repeated) = read(&mut bytes.as_slice(), &bump)?;
+		assert_eq!(repeated.get("a"), Some(&ArenaTag::Byte(2)));
+		assert_eq!(repeated.to_tag()?, compound!(("a", 2i8)));
+		let mut written = Vec::new();
+		write(&mut written, "", &repeated)?;
+		assert_eq!(written, bytes);
+
+		// Reading takes linear time in the number of entries.
+		let many = Tag::Compound((0..100_000).map(|key| (key.to_string(), Tag::Int(key))).collect());
+		let mut bytes = Vec::new();
+		write_named_tag(&mut bytes, &many, "")?;
+		assert_eq!(read(&mut bytes.as_slice(), &bump)?.1.to_tag()?, many);
+		Ok(())
+	}
+}
@@ -0,0 +1,536 @@
+#![doc = r#"
+An experimental content-addressed store for incremental world snapshots.
+
+A [Store] is a folder of objects, each named by the SHA-256 hash of its contents, and a manifest for each snapshot.
+[Store::snapshot] splits a world into objects at chunk boundaries: every chunk of every region file is stored as its own
+object, exactly as it is stored in the region (compressed), and every other file is stored whole. Objects that the store
+already has are not written again, so a snapshot of a world where a few chunks changed only adds those chunks.
+[Store::restore] rebuilds the files of a snapshot byte for byte, apart from the padding of region files.
+
+With the `encryption` feature, [Store::open_encrypted] opens a store whose objects and manifests are encrypted. Objects
+are still named by the hash of their unencrypted contents, so the names reveal whether the store holds a known chunk.
+
+```no_run
+# use rustnbt::{ backup::Store, world::World };
+let world = World::open("saves/New World").expect("Failed to open world.");
+let store = Store::open("backups/New World").expect("Failed to open store.");
+let stats = store.snapshot(&world, "2024-01-01").expect("Failed to take snapshot.");
+println!("{} new objects, {} reused", stats.objects_written, stats.objects_reused);
+store.restore("2024-01-01", "restored/New World").expect("Failed to restore snapshot.");
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	pos::{ ChunkPos, RegionPos },
+	region::{ Region, RawChunk },
+	tag::{
+		Tag,
+		ListTag,
+		NamedTag,
+	},
+	world::{ World, gzip_nbt, gunzip_nbt },
+};
+use std::collections::{ BTreeMap, HashSet };
+use std::path::{ Path, PathBuf };
+
+/// The file of an encrypted store that holds its key.
+#[cfg(feature = "encryption")]
+const KEY_FILE: &str = "key";
+
+/// The name of an object in a [Store], which is the SHA-256 hash of its contents.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct ObjectId(pub [u8; 32]);
+
+impl ObjectId {
+	/// Hashes the contents of an object.
+	pub fn of(bytes: &[u8]) -> Self {
+		Self(crate::sha256::digest(bytes))
+	}
+}
+
+/// Formats the hash as lowercase hexadecimal.
+impl std::fmt::Display for ObjectId {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+	}
+}
+
+impl std::str::FromStr for ObjectId {
+	type Err = NbtError;
+
+	fn from_str(text: &str) -> Result<Self, NbtError> {
+		let invalid = || invalid_data(&format!("Invalid object ID: {text}"));
+		if text.len() != 64 || !text.is_ascii() {
+			return Err(invalid());
+		}
+		let mut hash = [0; 32];
+		for (byte, pair) in hash.iter_mut().zip(text.as_bytes().chunks_exact(2)) {
+			*byte = u8::from_str_radix(std::str::from_utf8(pair).map_err(|_| invalid())?, 16).map_err(|_| invalid())?;
+		}
+		Ok(Self(hash))
+	}
+}
+
+/// A file of a snapshot that is stored whole.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ManifestFile {
+	/// The path of the file in the world folder, with `/` separators.
+	pub path: String,
+	pub object: ObjectId,
+}
+
+/// A chunk of a snapshot. The object holds the compression byte of the chunk followed by its compressed data.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ManifestChunk {
+	/// The path of the region file in the world folder, with `/` separators.
+	pub region: String,
+	pub chunk: ChunkPos,
+	/// The timestamp of the chunk in the region file.
+	pub timestamp: u32,
+	pub object: ObjectId,
+}
+
+/// The contents of a snapshot.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Manifest {
+	/// When the snapshot was taken, in seconds since the unix epoch.
+	pub created: i64,
+	pub files: Vec<ManifestFile>,
+	pub chunks: Vec<ManifestChunk>,
+}
+
+impl Manifest {
+	/// Every object that the snapshot refers to.
+	pub fn objects(&self) -> impl Iterator<Item = ObjectId> + '_ {
+		self.files.iter().map(|file| file.object).chain(self.chunks.iter().map(|chunk| chunk.object))
+	}
+
+	fn to_tag(&self) -> Tag {
+		let files = self.files.iter().map(|file| Map::from_iter([
+			("Path".to_owned(), Tag::String(file.path.clone())),
+			("Object".to_owned(), Tag::String(file.object.to_string())),
+		])).collect();
+		let chunks = self.chunks.iter().map(|chunk| Map::from_iter([
+			("Region".to_owned(), Tag::String(chunk.region.clone())),
+			("X".to_owned(), Tag::Int(chunk.chunk.x)),
+			("Z".to_owned(), Tag::Int(chunk.chunk.z)),
+			("Timestamp".to_owned(), Tag::Int(chunk.timestamp as i32)),
+			("Object".to_owned(), Tag::String(chunk.object.to_string())),
+		])).collect();
+		Tag::Compound(Map::from_iter([
+			("Created".to_owned(), Tag::Long(self.created)),
+			("Files".to_owned(), Tag::List(ListTag::Compound(files))),
+			("Chunks".to_owned(), Tag::List(ListTag::Compound(chunks))),
+		]))
+	}
+
+	fn from_tag(tag: &Tag) -> Result<Self, NbtError> {
+		let invalid = || invalid_data("Invalid snapshot manifest.");
+		let Tag::Compound(root) = tag else {
+			return Err(invalid());
+		};
+		let entries = |key: &str| match root.get(key) {
+			Some(Tag::List(ListTag::Compound(entries))) => entries.as_slice(),
+			_ => &[],
+		};
+		let string = |entry: &Map, key: &str| match entry.get(key) {
+			Some(Tag::String(value)) => Ok(value.clone()),
+			_ => Err(invalid()),
+		};
+		let int = |entry: &Map, key: &str| match entry.get(key) {
+			Some(Tag::Int(value)) => Ok(*value),
+			_ => Err(invalid()),
+		};
+		let files = entries("Files").iter().map(|entry| Ok(ManifestFile {
+			path: string(entry, "Path")?,
+			object: string(entry, "Object")?.parse()?,
+		})).collect::<Result<_, NbtError>>()?;
+		let chunks = entries("Chunks").iter().map(|entry| Ok(ManifestChunk {
+			region: string(entry, "Region")?,
+			chunk: ChunkPos::new(int(entry, "X")?, int(entry, "Z")?),
+			timestamp: int(entry, "Timestamp")? as u32,
+			object: string(entry, "Object")?.parse()?,
+		})).collect::<Result<_, NbtError>>()?;
+		let created = match root.get("Created") {
+			Some(Tag::Long(created)) => *created,
+			_ => 0,
+		};
+		Ok(Self { created, files, chunks })
+	}
+}
+
+/// What [Store::snapshot] wrote.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SnapshotStats {
+	/// The number of objects that were new to the store.
+	pub objects_written: usize,
+	/// The number of objects that the store already had.
+	pub objects_reused: usize,
+	/// The size of the new objects.
+	pub bytes_written: u64,
+}
+
+/// A content-addressed store of world snapshots. See the [module](self) documentation.
+#[derive(Clone, Debug)]
+pub struct Store {
+	root: PathBuf,
+	#[cfg(feature = "encryption")]
+	key: Option<crate::encryption::Key>,
+}
+
+impl Store {
+	/// Opens the store at `path`, creating its folders if needed.
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, NbtError> {
+		let root = path.as_ref().to_path_buf();
+		std::fs::create_dir_all(root.join("objects"))?;
+		std::fs::create_dir_all(root.join("snapshots"))?;
+		#[cfg(feature = "encryption")]
+		if root.join(KEY_FILE).exists() {
+			return Err(invalid_data("Store is encrypted. Open it with Store::open_encrypted."));
+		}
+		Ok(Self {
+			root,
+			#[cfg(feature = "encryption")]
+			key: None,
+		})
+	}
+
+	/// Opens the encrypted store at `path`, creating it if needed. The objects are encrypted with a random key, which is
+	/// stored in the `key` file encrypted with the passphrase.<br>
+	/// Fails with [NbtError::DecryptionFailed] if the passphrase is wrong, and fails if the store exists but is not encrypted.
+	#[cfg(feature = "encryption")]
+	pub fn open_encrypted<P: AsRef<Path>>(path: P, passphrase: &str) -> Result<Self, NbtError> {
+		use crate::encryption::{ Key, seal_with_passphrase, open_with_passphrase };
+		let root = path.as_ref().to_path_buf();
+		let key_path = root.join(KEY_FILE);
+		let key = if key_path.exists() {
+			let key: [u8; 32] = open_with_passphrase(passphrase, &std::fs::read(key_path)?)?
+				.try_into()
+				.map_err(|_| invalid_data("Invalid store key."))?;
+			Key::from_bytes(key)
+		} else {
+			if root.join("snapshots").read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+				return Err(invalid_data("Store exists and is not encrypted."));
+			}
+			std::fs::create_dir_all(&root)?;
+			let key = Key::generate()?;
+			std::fs::write(key_path, seal_with_passphrase(passphrase, key.as_bytes())?)?;
+			key
+		};
+		std::fs::create_dir_all(root.join("objects"))?;
+		std::fs::create_dir_all(root.join("snapshots"))?;
+		Ok(Self { root, key: Some(key) })
+	}
+
+	/// Returns true if the objects of the store are encrypted.
+	#[cfg(feature = "encryption")]
+	pub fn is_encrypted(&self) -> bool {
+		self.key.is_some()
+	}
+
+	/// Encrypts the contents of a file of the store, if the store is encrypted.
+	fn seal(&self, bytes: &[u8]) -> Result<Vec<u8>, NbtError> {
+		#[cfg(feature = "encryption")]
+		if let Some(key) = &self.key {
+			return crate::encryption::seal(key, bytes);
+		}
+		Ok(bytes.to_vec())
+	}
+
+	/// Decrypts the contents of a file of the store, if the store is encrypted.
+	fn unseal(&self, bytes: Vec<u8>) -> Result<Vec<u8>, NbtError> {
+		#[cfg(feature = "encryption")]
+		if let Some(key) = &self.key {
+			return crate::encryption::open(key, &bytes);
+		}
+		Ok(bytes)
+	}
+
+	/// The folder of the store.
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	fn object_path(&self, id: ObjectId) -> PathBuf {
+		let hex = id.to_string();
+		self.root.join("objects").join(&hex[..2]).join(&hex[2..])
+	}
+
+	fn manifest_path(&self, name: &str) -> PathBuf {
+		self.root.join("snapshots").join(format!("{name}.dat"))
+	}
+
+	/// Returns true if the store has the object.
+	pub fn contains(&self, id: ObjectId) -> bool {
+		self.object_path(id).is_file()
+	}
+
+	/// Stores an object, unless the store already has it. Returns its ID, and whether it was written.<br>
+	/// Objects are written to a temporary file first and then renamed, so an interrupted write never leaves a damaged object.
+	pub fn put(&self, bytes: &[u8]) -> Result<(ObjectId, bool), NbtError> {
+		let id = ObjectId::of(bytes);
+		let path = self.object_path(id);
+		if path.is_file() {
+			return Ok((id, false));
+		}
+		let dir = path.parent().expect("Object paths have a parent.");
+		std::fs::create_dir_all(dir)?;
+		let temp = dir.join(format!("{id}.tmp"));
+		std::fs::write(&temp, self.seal(bytes)?)?;
+		std::fs::rename(temp, path)?;
+		Ok((id, true))
+	}
+
+	/// Reads an object, checking that its contents still match its ID.
+	pub fn get(&self, id: ObjectId) -> Result<Vec<u8>, NbtError> {
+		let bytes = self.unseal(std::fs::read(self.object_path(id))?)?;
+		if ObjectId::of(&bytes) != id {
+			return Err(invalid_data(&format!("Object {id} is damaged.")));
+		}
+		Ok(bytes)
+	}
+
+	/// The names of the snapshots, in sorted order.
+	pub fn snapshots(&self) -> Result<Vec<String>, NbtError> {
+		let mut names = Vec::new();
+		for entry in std::fs::read_dir(self.root.join("snapshots"))? {
+			let name = entry?.file_name().to_string_lossy().into_owned();
+			if let Some(name) = name.strip_suffix(".dat") {
+				names.push(name.to_owned());
+			}
+		}
+		names.sort();
+		Ok(names)
+	}
+
+	/// Reads the manifest of a snapshot.
+	pub fn manifest(&self, name: &str) -> Result<Manifest, NbtError> {
+		let bytes = self.unseal(std::fs::read(self.manifest_path(name))?)?;
+		Manifest::from_tag(gunzip_nbt(&bytes)?.tag())
+	}
+
+	/// Takes a snapshot of a world, replacing any snapshot with the same name.<br>
+	/// `session.lock` and external chunk (`.mcc`) files are skipped, since the chunks in them are stored with their region.
+	pub fn snapshot(&self, world: &World, name: &str) -> Result<SnapshotStats, NbtError> {
+		let mut stats = SnapshotStats::default();
+		let mut put = |bytes: &[u8]| -> Result<ObjectId, NbtError> {
+			let (id, written) = self.put(bytes)?;
+			if written {
+				stats.objects_written += 1;
+				stats.bytes_written += bytes.len() as u64;
+			} else {
+				stats.objects_reused += 1;
+			}
+			Ok(id)
+		};
+		let created = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|time| time.as_secs() as i64).unwrap_or(0);
+		let mut manifest = Manifest { created, ..Manifest::default() };
+		for path in world_files(world.root())? {
+			let relative = relative_path(world.root(), &path);
+			let name = path.file_name().unwrap_or_default().to_string_lossy();
+			if name == "session.lock" || name.ends_with(".mcc") {
+				continue;
+			}
+			if RegionPos::from_file_name(&name).is_none() {
+				manifest.files.push(ManifestFile { object: put(&std::fs::read(&path)?)?, path: relative });
+				continue;
+			}
+			let mut region = Region::open(&path)?;
+			let chunks: Vec<ChunkPos> = region.chunks().collect();
+			for chunk in chunks {
+				let Some(raw) = region.read_raw_chunk(chunk)? else {
+					continue;
+				};
+				let mut bytes = Vec::with_capacity(raw.data.len() + 1);
+				bytes.push(raw.compression);
+				bytes.extend_from_slice(&raw.data);
+				manifest.chunks.push(ManifestChunk {
+					region: relative.clone(),
+					chunk,
+					timestamp: region.timestamp(chunk).unwrap_or(0),
+					object: put(&bytes)?,
+				});
+			}
+		}
+		std::fs::write(self.manifest_path(name), self.seal(&gzip_nbt(&NamedTag::new(manifest.to_tag()))?)?)?;
+		Ok(stats)
+	}
+
+	/// Writes the files of a snapshot into `destination`. Region files in the snapshot are recreated from scratch.
+	pub fn restore<P: AsRef<Path>>(&self, name: &str, destination: P) -> Result<(), NbtError> {
+		let destination = destination.as_ref();
+		let manifest = self.manifest(name)?;
+		let prepare = |relative: &str| -> Result<PathBuf, NbtError> {
+			if relative.split('/').any(|part| part == ".." || part.is_empty()) {
+				return Err(invalid_data(&format!("Invalid path in snapshot: {relative}")));
+			}
+			let path = destination.join(relative);
+			if let Some(parent) = path.parent() {
+				std::fs::create_dir_all(parent)?;
+			}
+			Ok(path)
+		};
+		for file in &manifest.files {
+			std::fs::write(prepare(&file.path)?, self.get(file.object)?)?;
+		}
+		let mut regions: BTreeMap<&str, Vec<&ManifestChunk>> = BTreeMap::new();
+		for chunk in &manifest.chunks {
+			regions.entry(&chunk.region).or_default().push(chunk);
+		}
+		for (relative, chunks) in regions {
+			let path = prepare(relative)?;
+			if path.exists() {
+				std::fs::remove_file(&path)?;
+			}
+			let mut region = Region::create(path)?;
+			for chunk in chunks {
+				let bytes = self.get(chunk.object)?;
+				let Some((compression, data)) = bytes.split_first() else {
+					return Err(invalid_data(&format!("Chunk object {} is empty.", chunk.object)));
+				};
+				region.write_raw_chunk(chunk.chunk, &RawChunk { compression: *compression, data: data.to_vec() }, chunk.timestamp)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Deletes the manifest of a snapshot. Its objects stay until [prune](Self::prune) is called.
+	pub fn remove_snapshot(&self, name: &str) -> Result<(), NbtError> {
+		std::fs::remove_file(self.manifest_path(name))?;
+		Ok(())
+	}
+
+	/// Deletes the objects that no snapshot refers to, and returns the number deleted.
+	pub fn prune(&self) -> Result<usize, NbtError> {
+		let mut referenced = HashSet::new();
+		for name in self.snapshots()? {
+			referenced.extend(self.manifest(&name)?.objects());
+		}
+		let mut removed = 0;
+		for dir in std::fs::read_dir(self.root.join("objects"))? {
+			let dir = dir?.path();
+			let Some(prefix) = dir.file_name().map(|name| name.to_string_lossy().into_owned()) else {
+				continue;
+			};
+			for entry in std::fs::read_dir(&dir)? {
+				let path = entry?.path();
+				let rest = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+				match format!("{prefix}{rest}").parse::<ObjectId>() {
+					Ok(id) if referenced.contains(&id) => {}
+					_ => {
+						std::fs::remove_file(path)?;
+						removed += 1;
+					}
+				}
+			}
+		}
+		Ok(removed)
+	}
+}
+
+/// Every file under `dir`, in sorted order.
+fn world_files(dir: &Path) -> Result<Vec<PathBuf>, NbtError> {
+	let mut files = Vec::new();
+	let mut dirs = vec![dir.to_path_buf()];
+	while let Some(dir) = dirs.pop() {
+		for entry in std::fs::read_dir(dir)? {
+			let entry = entry?;
+			if entry.file_type()?.is_dir() {
+				dirs.push(entry.path());
+			} else {
+				files.push(entry.path());
+			}
+		}
+	}
+	files.sort();
+	Ok(files)
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+	let relative = path.strip_prefix(root).unwrap_or(path);
+	relative.components().map(|part| part.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, world::{ OVERWORLD, RegionKind, tests::temp_world } };
+
+	#[test]
+	fn snapshot_test() -> Result<(), NbtError> {
+		let dir = temp_world("backup");
+		std::fs::create_dir_all(dir.join("world"))?;
+		let world = World::open(dir.join("world"))?;
+		std::fs::write(world.root().join("level.dat"), b"level")?;
+		std::fs::write(world.root().join("session.lock"), b"lock")?;
+		let (first, second) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0));
+		let region_path = world.region_path(OVERWORLD, RegionKind::Chunks, first.region());
+		std::fs::create_dir_all(region_path.parent().expect("Region has a folder."))?;
+		let mut region = Region::create(&region_path)?;
+		region.write_chunk(first, &NamedTag::new(compound!(("xPos", 0))))?;
+		region.write_chunk(second, &NamedTag::new(compound!(("xPos", 1))))?;
+		region.set_timestamp(first, 1234)?;
+		drop(region);
+
+		let store = Store::open(dir.join("store"))?;
+		let stats = store.snapshot(&world, "a")?;
+		assert_eq!((stats.objects_written, stats.objects_reused), (3, 0));
+		let mut region = Region::open(&region_path)?;
+		region.write_chunk(second, &NamedTag::new(compound!(("xPos", 1), ("Changed", 1))))?;
+		drop(region);
+		let stats = store.snapshot(&world, "b")?;
+		assert_eq!((stats.objects_written, stats.objects_reused), (1, 2));
+		assert_eq!(store.snapshots()?, ["a", "b"]);
+
+		store.restore("a", dir.join("restored"))?;
+		let restored = World::open(dir.join("restored"))?;
+		assert_eq!(std::fs::read(restored.root().join("level.dat"))?, b"level");
+		assert!(!restored.root().join("session.lock").exists());
+		let mut region = Region::open(restored.region_path(OVERWORLD, RegionKind::Chunks, first.region()))?;
+		assert_eq!(region.timestamp(first), Some(1234));
+		assert_eq!(region.read_chunk(second)?.map(NamedTag::take_tag), Some(compound!(("xPos", 1))));
+
+		assert_eq!(store.prune()?, 0);
+		store.remove_snapshot("a")?;
+		assert_eq!(store.prune()?, 1);
+		let manifest = store.manifest("b")?;
+		assert!(manifest.objects().all(|id| store.get(id).is_ok()));
+		assert_eq!(manifest.files[0].path, "level.dat");
+		std::fs::remove_dir_all(dir)?;
+		Ok(())
+	}
+
+	#[cfg(feature = "encryption")]
+	#[test]
+	fn encrypted_test() -> Result<(), NbtError> {
+		let dir = temp_world("backup-encrypted");
+		std::fs::create_dir_all(dir.join("world"))?;
+		std::fs::write(dir.join("world").join("level.dat"), b"level")?;
+		let world = World::open(dir.join("world"))?;
+		let store = Store::open_encrypted(dir.join("store"), "passphrase")?;
+		assert!(store.is_encrypted());
+		store.snapshot(&world, "a")?;
+		let id = ObjectId::of(b"level");
+		assert_ne!(std::fs::read(store.object_path(id))?, b"level");
+		assert_eq!(store.get(id)?, b"level");
+		assert_eq!(store.manifest("a")?.files[0].object, id);
+		store.restore("a", dir.join("restored"))?;
+		assert_eq!(std::fs::read(dir.join("restored").join("level.dat"))?, b"level");
+		assert!(Store::open(dir.join("store")).is_err());
+		assert!(matches!(Store::open_encrypted(dir.join("store"), "wrong"), Err(NbtError::DecryptionFailed)));
+		std::fs::remove_dir_all(dir)?;
+		Ok(())
+	}
+
+	#[test]
+	fn object_id_test() -> Result<(), NbtError> {
+		let id = ObjectId::of(b"abc");
+		assert_eq!(id.to_string(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+		assert_eq!(id.to_string().parse::<ObjectId>()?, id);
+		assert!("ba78".parse::<ObjectId>().is_err());
+		Ok(())
+	}
+}
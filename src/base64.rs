@@ -0,0 +1,78 @@
+#![doc = r#"
+Standard base64 (RFC 4648, with `+` and `/`), which the game uses for server icons.
+"#]
+
+use crate::{ NbtError, tag::Tag };
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes, with `=` padding.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+	let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+	for chunk in bytes.chunks(3) {
+		let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| group | (*byte as u32) << (16 - index * 8));
+		for index in 0..4 {
+			if index <= chunk.len() {
+				text.push(ALPHABET[(group >> (18 - index * 6)) as usize & 63] as char);
+			} else {
+				text.push('=');
+			}
+		}
+	}
+	text
+}
+
+/// Decodes text, with or without `=` padding. Whitespace is not allowed.
+pub(crate) fn decode(text: &str) -> Result<Vec<u8>, NbtError> {
+	let invalid = || crate::invalid_data("Invalid base64.");
+	let text = text.trim_end_matches('=').as_bytes();
+	if text.len() % 4 == 1 {
+		return Err(invalid());
+	}
+	let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+	for chunk in text.chunks(4) {
+		let mut group = 0u32;
+		for (index, char) in chunk.iter().enumerate() {
+			let value = ALPHABET.iter().position(|letter| letter == char).ok_or_else(invalid)?;
+			group |= (value as u32) << (18 - index * 6);
+		}
+		bytes.extend(group.to_be_bytes()[1..chunk.len()].iter());
+	}
+	Ok(bytes)
+}
+
+impl Tag {
+	/// Decodes base64 text, such as the icon of a server, into a ByteArray.
+	pub fn byte_array_from_base64(text: &str) -> Result<Tag, NbtError> {
+		Ok(Tag::ByteArray(bytemuck::cast_vec(decode(text)?)))
+	}
+
+	/// Encodes a ByteArray as base64 text, with `=` padding. Returns [None] for other tags.
+	pub fn to_base64(&self) -> Option<String> {
+		match self {
+			Tag::ByteArray(values) => Some(encode(bytemuck::cast_slice(values))),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn base64_test() -> Result<(), NbtError> {
+		for (bytes, text) in [(&b""[..], ""), (b"f", "Zg=="), (b"fo", "Zm8="), (b"foo", "Zm9v"), (b"foobar", "Zm9vYmFy"), (&[0xFB, 0xFF], "+/8=")] {
+			assert_eq!(encode(bytes), text);
+			assert_eq!(decode(text)?, bytes);
+		}
+		assert_eq!(decode("Zm8")?, b"fo");
+		assert!(decode("Zm9vY").is_err());
+		assert!(decode("Zm9v!A==").is_err());
+		let tag = Tag::byte_array_from_base64("+/8=")?;
+		assert_eq!(tag, Tag::ByteArray(vec![-5, -1]));
+		assert_eq!(tag.to_base64().as_deref(), Some("+/8="));
+		assert_eq!(Tag::Int(1).to_base64(), None);
+		Ok(())
+	}
+}
@@ -0,0 +1,160 @@
+#![doc = r#"
+Bit set views over LongArray tags.
+
+Some data is stored as a set of bits packed into a LongArray, in the layout of Java's `BitSet.toLongArray`: bit `i` is
+bit `i % 64` of long `i / 64`, counting from the least significant bit. Carving masks of proto-chunks are stored
+like this, and so is data of some mods. [Tag::bits] and [Tag::bits_mut] view a LongArray as such a set, so the shifts
+and masks do not have to be written by hand.
+
+```
+# use rustnbt::tag::Tag;
+let mut mask = Tag::LongArray(vec![]);
+let mut bits = mask.bits_mut().unwrap();
+bits.set(3, true);
+bits.set(70, true);
+assert_eq!(mask, Tag::LongArray(vec![0b1000, 0b1000000]));
+let bits = mask.bits().unwrap();
+assert!(bits.get(70) && !bits.get(71));
+assert_eq!(bits.ones().collect::<Vec<_>>(), [3, 70]);
+```
+"#]
+
+use crate::tag::Tag;
+
+/// A read-only bit set over the longs of a LongArray. See the [module](self) documentation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BitSet<'a> {
+	words: &'a [i64],
+}
+
+/// A bit set over the longs of a LongArray that can be changed. See the [module](self) documentation.
+#[derive(PartialEq, Eq, Debug)]
+pub struct BitSetMut<'a> {
+	words: &'a mut Vec<i64>,
+}
+
+impl Tag {
+	/// Views a LongArray as a bit set, or returns None for other tags.
+	pub fn bits(&self) -> Option<BitSet<'_>> {
+		match self {
+			Tag::LongArray(words) => Some(BitSet { words }),
+			_ => None,
+		}
+	}
+
+	/// Views a LongArray as a bit set that can be changed, or returns None for other tags.
+	pub fn bits_mut(&mut self) -> Option<BitSetMut<'_>> {
+		match self {
+			Tag::LongArray(words) => Some(BitSetMut { words }),
+			_ => None,
+		}
+	}
+}
+
+/// The long that holds bit `index`, and the mask of the bit in it.
+fn position(index: usize) -> (usize, i64) {
+	(index / 64, 1 << (index % 64))
+}
+
+impl<'a> BitSet<'a> {
+	/// Views longs as a bit set.
+	pub fn new(words: &'a [i64]) -> Self {
+		Self { words }
+	}
+
+	/// The number of bits that the longs hold, which is 64 for every long.
+	pub fn capacity(self) -> usize {
+		self.words.len() * 64
+	}
+
+	/// Returns whether a bit is set. Bits past the end are not set.
+	pub fn get(self, index: usize) -> bool {
+		let (word, mask) = position(index);
+		self.words.get(word).is_some_and(|word| word & mask != 0)
+	}
+
+	/// The number of bits that are set.
+	pub fn count_ones(self) -> usize {
+		self.words.iter().map(|word| word.count_ones() as usize).sum()
+	}
+
+	/// Iterates over the indices of the bits that are set, in increasing order.
+	pub fn ones(self) -> impl Iterator<Item = usize> + 'a {
+		self.words.iter().enumerate().flat_map(|(index, word)| {
+			let mut word = *word as u64;
+			std::iter::from_fn(move || {
+				if word == 0 {
+					return None;
+				}
+				let bit = word.trailing_zeros() as usize;
+				word &= word - 1;
+				Some(index * 64 + bit)
+			})
+		})
+	}
+}
+
+impl<'a> BitSetMut<'a> {
+	/// Views longs as a bit set that can be changed.
+	pub fn new(words: &'a mut Vec<i64>) -> Self {
+		Self { words }
+	}
+
+	/// A read-only view of the same bits.
+	pub fn as_bits(&self) -> BitSet<'_> {
+		BitSet { words: self.words }
+	}
+
+	/// Returns whether a bit is set. Bits past the end are not set.
+	pub fn get(&self, index: usize) -> bool {
+		self.as_bits().get(index)
+	}
+
+	/// Sets or clears a bit. Setting a bit past the end appends longs of zeros to hold it.
+	pub fn set(&mut self, index: usize, value: bool) {
+		let (word, mask) = position(index);
+		if value {
+			if word >= self.words.len() {
+				self.words.resize(word + 1, 0);
+			}
+			self.words[word] |= mask;
+		} else if let Some(word) = self.words.get_mut(word) {
+			*word &= !mask;
+		}
+	}
+
+	/// Clears every bit, keeping the number of longs.
+	pub fn clear(&mut self) {
+		self.words.fill(0);
+	}
+
+	/// Removes the longs of zeros at the end, like `BitSet.toLongArray` does.
+	pub fn trim(&mut self) {
+		let length = self.words.iter().rposition(|word| *word != 0).map_or(0, |index| index + 1);
+		self.words.truncate(length);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn bits_test() {
+		let mut tag = Tag::LongArray(vec![0, 0]);
+		let mut bits = tag.bits_mut().unwrap();
+		bits.set(63, true);
+		bits.set(64, true);
+		bits.set(200, false);
+		assert!(bits.get(63) && bits.get(64) && !bits.get(62) && !bits.get(1000));
+		assert_eq!(bits.as_bits().capacity(), 128);
+		bits.set(64, false);
+		bits.trim();
+		assert_eq!(tag, Tag::LongArray(vec![i64::MIN]));
+
+		let bits = BitSet::new(&[-1, 5]);
+		assert_eq!(bits.count_ones(), 66);
+		assert_eq!(bits.ones().skip(63).collect::<Vec<_>>(), [63, 64, 66]);
+		assert!(Tag::IntArray(vec![]).bits().is_none());
+	}
+}
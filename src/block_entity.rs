@@ -0,0 +1,225 @@
+// https://minecraft.wiki/w/Block_entity_format
+
+#![doc = r#"
+Recognizing block entities across versions, with uniform access to their ID and position.
+
+Block entities are the Compounds in a chunk's `block_entities` List (`TileEntities` before 1.18). Every one of them
+has an `id` String and its block position as the Ints `x`, `y`, and `z`. Before 1.11 (data version 819), the IDs
+were CamelCase names without a namespace, such as `Chest` and `MobSpawner`; since then they are namespaced, such as
+`minecraft:chest` and `minecraft:mob_spawner`, and a few of them changed names along the way.
+
+[TYPES] lists the vanilla block entity types with both of their IDs. [id] reads the ID of a block entity in the
+current form whatever version wrote it, and [convert] rewrites it for the [IdStyle] of a version.
+
+```
+# use rustnbt::{ compound, tag::Tag, pos::BlockPos, block_entity::* };
+let Tag::Compound(mut spawner) = compound!(("id", "MobSpawner"), ("x", 1), ("y", 64), ("z", -2)) else { unreachable!() };
+assert!(is_block_entity(&spawner));
+assert_eq!(id(&spawner).as_deref(), Some("minecraft:mob_spawner"));
+assert_eq!(position(&spawner), Some(BlockPos::new(1, 64, -2)));
+convert(&mut spawner, IdStyle::for_data_version(3465));
+assert_eq!(spawner["id"], Tag::from("minecraft:mob_spawner"));
+```
+"#]
+
+use crate::{
+	Map,
+	pos::BlockPos,
+	tag::Tag,
+};
+use std::borrow::Cow;
+
+/// The first data version with namespaced block entity IDs (16w32a, a snapshot for 1.11).
+pub const NAMESPACED_SINCE: i32 = 819;
+
+/// How a version writes block entity IDs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IdStyle {
+	/// CamelCase names without a namespace, such as `Chest`, before 1.11.
+	Legacy,
+	/// Namespaced IDs, such as `minecraft:chest`, since 1.11.
+	Namespaced,
+}
+
+impl IdStyle {
+	/// The style of the version that has the data version.
+	pub fn for_data_version(data_version: i32) -> Self {
+		if data_version < NAMESPACED_SINCE { IdStyle::Legacy } else { IdStyle::Namespaced }
+	}
+}
+
+/// A vanilla block entity type.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BlockEntityType {
+	/// The namespaced ID.
+	pub id: &'static str,
+	/// The ID before 1.11, or None for types that were added later.
+	pub legacy_id: Option<&'static str>,
+}
+
+impl BlockEntityType {
+	/// The ID of this type in a style, or None if it did not exist in that style.
+	pub fn id_in(self, style: IdStyle) -> Option<&'static str> {
+		match style {
+			IdStyle::Legacy => self.legacy_id,
+			IdStyle::Namespaced => Some(self.id),
+		}
+	}
+}
+
+macro_rules! types {
+	($($id:literal $(= $legacy:literal)?,)+) => {
+		/// The vanilla block entity types, by namespaced ID.
+		pub const TYPES: &[BlockEntityType] = &[
+			$(BlockEntityType { id: concat!("minecraft:", $id), legacy_id: types!(@legacy $($legacy)?) },)+
+		];
+	};
+	(@legacy $legacy:literal) => { Some($legacy) };
+	(@legacy) => { None };
+}
+
+types!{
+	"banner" = "Banner",
+	"barrel",
+	"beacon" = "Beacon",
+	"bed",
+	"beehive",
+	"bell",
+	"blast_furnace",
+	"brewing_stand" = "Cauldron",
+	"brushable_block",
+	"calibrated_sculk_sensor",
+	"campfire",
+	"chest" = "Chest",
+	"chiseled_bookshelf",
+	"command_block" = "Control",
+	"comparator" = "Comparator",
+	"conduit",
+	"crafter",
+	"creaking_heart",
+	"daylight_detector" = "DLDetector",
+	"decorated_pot",
+	"dispenser" = "Trap",
+	"dropper" = "Dropper",
+	"enchanting_table" = "EnchantTable",
+	"end_gateway" = "EndGateway",
+	"end_portal" = "Airportal",
+	"ender_chest" = "EnderChest",
+	"flower_pot" = "FlowerPot",
+	"furnace" = "Furnace",
+	"hanging_sign",
+	"hopper" = "Hopper",
+	"jigsaw",
+	"jukebox" = "RecordPlayer",
+	"lectern",
+	"mob_spawner" = "MobSpawner",
+	"noteblock" = "Music",
+	"piston" = "Piston",
+	"sculk_catalyst",
+	"sculk_sensor",
+	"sculk_shrieker",
+	"shulker_box",
+	"sign" = "Sign",
+	"skull" = "Skull",
+	"smoker",
+	"structure_block" = "Structure",
+	"trapped_chest",
+	"trial_spawner",
+	"vault",
+}
+
+/// Finds the type that has an ID, in either style. IDs without a namespace are in the `minecraft` namespace.
+pub fn lookup(id: &str) -> Option<&'static BlockEntityType> {
+	let namespaced = id.strip_prefix("minecraft:").unwrap_or(id);
+	TYPES.iter().find(|kind| kind.legacy_id == Some(id) || &kind.id["minecraft:".len()..] == namespaced)
+}
+
+/// Returns an ID in the namespaced style. Unknown IDs without a namespace are put in the `minecraft` namespace.
+pub fn normalize_id(id: &str) -> Cow<'_, str> {
+	match lookup(id) {
+		Some(kind) => Cow::Borrowed(kind.id),
+		None if id.contains(':') => Cow::Borrowed(id),
+		None => Cow::Owned(format!("minecraft:{id}")),
+	}
+}
+
+/// Returns true if a Compound has the `id` String and the `x`, `y`, and `z` Ints that every block entity has.
+pub fn is_block_entity(entity: &Map) -> bool {
+	matches!(entity.get("id"), Some(Tag::String(_))) && position(entity).is_some()
+}
+
+/// Returns the ID of a block entity in the namespaced style, whatever version wrote it.
+pub fn id(entity: &Map) -> Option<Cow<'_, str>> {
+	match entity.get("id") {
+		Some(Tag::String(id)) => Some(normalize_id(id)),
+		_ => None,
+	}
+}
+
+/// Returns the position of a block entity.
+pub fn position(entity: &Map) -> Option<BlockPos> {
+	match (entity.get("x"), entity.get("y"), entity.get("z")) {
+		(Some(Tag::Int(x)), Some(Tag::Int(y)), Some(Tag::Int(z))) => Some(BlockPos::new(*x, *y, *z)),
+		_ => None,
+	}
+}
+
+/// Sets the position of a block entity.
+pub fn set_position(entity: &mut Map, pos: BlockPos) {
+	entity.insert("x".to_owned(), Tag::Int(pos.x));
+	entity.insert("y".to_owned(), Tag::Int(pos.y));
+	entity.insert("z".to_owned(), Tag::Int(pos.z));
+}
+
+/// Rewrites the ID of a block entity in a style, and returns true if it changed.<br>
+/// IDs of types that do not exist in the style, and unknown IDs when converting to [IdStyle::Legacy], are kept.
+pub fn convert(entity: &mut Map, style: IdStyle) -> bool {
+	let Some(Tag::String(id)) = entity.get_mut("id") else {
+		return false;
+	};
+	let converted = match style {
+		IdStyle::Namespaced => normalize_id(id).into_owned(),
+		IdStyle::Legacy => match lookup(id).and_then(|kind| kind.legacy_id) {
+			Some(legacy) => legacy.to_owned(),
+			None => return false,
+		},
+	};
+	if *id == converted {
+		return false;
+	}
+	*id = converted;
+	true
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::compound;
+
+	#[test]
+	fn registry_test() {
+		assert_eq!(lookup("Cauldron").map(|kind| kind.id), Some("minecraft:brewing_stand"));
+		assert_eq!(lookup("minecraft:chest").and_then(|kind| kind.legacy_id), Some("Chest"));
+		assert_eq!(lookup("barrel").map(|kind| kind.id_in(IdStyle::Legacy)), Some(None));
+		assert_eq!(lookup("minecraft:Chest"), None);
+		assert_eq!(normalize_id("Control"), "minecraft:command_block");
+		assert_eq!(normalize_id("mymod:machine"), "mymod:machine");
+		assert_eq!(normalize_id("machine"), "minecraft:machine");
+		assert_eq!(IdStyle::for_data_version(512), IdStyle::Legacy);
+
+		let Tag::Compound(mut chest) = compound!(("id", "minecraft:chest"), ("x", 0), ("y", -64), ("z", 0)) else { unreachable!() };
+		assert!(is_block_entity(&chest));
+		assert!(convert(&mut chest, IdStyle::Legacy));
+		assert_eq!(chest["id"], Tag::from("Chest"));
+		assert!(!convert(&mut chest, IdStyle::Legacy));
+		assert!(convert(&mut chest, IdStyle::Namespaced));
+		assert_eq!(id(&chest).as_deref(), Some("minecraft:chest"));
+		set_position(&mut chest, BlockPos::new(1, 2, 3));
+		assert_eq!(position(&chest), Some(BlockPos::new(1, 2, 3)));
+
+		let Tag::Compound(mut item) = compound!(("id", "minecraft:chest"), ("Count", 1i8)) else { unreachable!() };
+		assert!(!is_block_entity(&item));
+		item.insert("id".to_owned(), Tag::from("minecraft:vault"));
+		assert!(!convert(&mut item, IdStyle::Legacy));
+	}
+}
@@ -0,0 +1,159 @@
+#![doc = r#"
+What bulk operations over a world do when one of their items fails.
+
+[merge_with](crate::world::merge_with), [prune_chunks_with](crate::world::prune_chunks_with),
+[Index::build_with](crate::world::Index::build_with), and [fingerprint_with](crate::fingerprint::fingerprint_with) go
+through every region file and chunk of a world, which can take an hour. Without a policy they stop at the first error,
+so one corrupt chunk loses the whole run. With an [ErrorPolicy] that [skips](ErrorPolicy::skip) failed items, they
+carry on past the region files and chunks that fail, and return an [ItemError] for each, with where it is and what
+went wrong. Transient io errors, such as an interrupted read or a timeout on a network drive, can be
+[retried](ErrorPolicy::retries) before an item counts as failed.
+
+```no_run
+# use rustnbt::{ bulk::ErrorPolicy, world::* };
+let world = World::open("saves/New World").expect("Failed to open world.");
+let (index, errors) = Index::build_with(&world, ErrorPolicy::skip_and_collect().retries(3)).expect("Failed to list regions.");
+for error in errors {
+	eprintln!("Skipped {error}");
+}
+```
+"#]
+
+use crate::{
+	NbtError,
+	context::ErrorContext,
+};
+use std::fmt::{ self, Display };
+use std::io::ErrorKind;
+
+/// What a bulk operation does when an item fails. See the [module](self) documentation.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct ErrorPolicy {
+	/// Whether items that fail are skipped and reported, instead of stopping the operation with their error. `false`
+	/// by default.
+	pub skip: bool,
+	/// How many times an item is tried again after a [transient](is_transient) error. 0 by default.
+	pub retries: u32,
+}
+
+impl ErrorPolicy {
+	/// Stops at the first error, like the operations without a policy.
+	pub fn fail_fast() -> Self {
+		Self::default()
+	}
+
+	/// Skips the items that fail, and reports them.
+	pub fn skip_and_collect() -> Self {
+		Self { skip: true, retries: 0 }
+	}
+
+	pub fn skip(mut self, skip: bool) -> Self {
+		self.skip = skip;
+		self
+	}
+
+	pub fn retries(mut self, retries: u32) -> Self {
+		self.retries = retries;
+		self
+	}
+
+	/// Runs an item, and tries it again after transient errors. Returns [None] if it failed and was skipped, in which
+	/// case its error is added to `errors`. The context is added to the error either way.
+	pub(crate) fn run<T, C, F>(&self, errors: &mut Vec<ItemError>, context: C, mut item: F) -> Result<Option<T>, NbtError>
+	where
+		C: FnOnce() -> ErrorContext,
+		F: FnMut() -> Result<T, NbtError>,
+	{
+		let mut attempts = 1;
+		loop {
+			match item() {
+				Ok(value) => return Ok(Some(value)),
+				Err(err) if attempts <= self.retries && is_transient(&err) => attempts += 1,
+				Err(err) => {
+					let err = err.with_context(context());
+					if !self.skip {
+						return Err(err);
+					}
+					errors.push(ItemError {
+						context: err.context().cloned().unwrap_or_default(),
+						error: err.root().to_string(),
+						attempts,
+					});
+					return Ok(None);
+				}
+			}
+		}
+	}
+}
+
+/// Whether an error may go away if the same thing is tried again: io errors of the kinds
+/// [Interrupted](ErrorKind::Interrupted), [WouldBlock](ErrorKind::WouldBlock), [TimedOut](ErrorKind::TimedOut), and
+/// [ResourceBusy](ErrorKind::ResourceBusy).
+pub fn is_transient(err: &NbtError) -> bool {
+	matches!(err.root(), NbtError::IoError(err) if matches!(err.kind(), ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::ResourceBusy))
+}
+
+/// An item that a bulk operation skipped.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ItemError {
+	/// Where the item is.
+	pub context: ErrorContext,
+	/// The error, without its context.
+	pub error: String,
+	/// How many times the item was tried.
+	pub attempts: u32,
+}
+
+/// Formats the error like its [NbtError::Context], with the number of attempts if it was retried.
+impl Display for ItemError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: {}", self.context, self.error)?;
+		if self.attempts > 1 {
+			write!(f, " ({} attempts)", self.attempts)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::pos::ChunkPos;
+
+	#[test]
+	fn policy_test() {
+		let busy = || -> Result<(), NbtError> { Err(std::io::Error::from(ErrorKind::TimedOut).into()) };
+		let context = || ErrorContext::default().chunk(ChunkPos::new(1, 2));
+		let mut errors = Vec::new();
+		let err = ErrorPolicy::fail_fast().run(&mut errors, context, busy).unwrap_err();
+		assert_eq!(err.context(), Some(&context()));
+		assert!(errors.is_empty());
+
+		// Transient errors are tried again, and other errors are not.
+		let mut calls = 0;
+		let skipped = ErrorPolicy::skip_and_collect().retries(2).run(&mut errors, context, || {
+			calls += 1;
+			busy()
+		});
+		assert_eq!((skipped.unwrap(), calls), (None, 3));
+		let mut calls = 0;
+		let result = ErrorPolicy::skip_and_collect().retries(2).run(&mut errors, context, || {
+			calls += 1;
+			match calls {
+				1 => busy().map(|_| 0),
+				_ => Ok(calls),
+			}
+		});
+		assert_eq!(result.unwrap(), Some(2));
+		let mut calls = 0;
+		ErrorPolicy::skip_and_collect().retries(2).run(&mut errors, context, || -> Result<(), NbtError> {
+			calls += 1;
+			Err(crate::invalid_data("Corrupt chunk."))
+		}).unwrap();
+		assert_eq!(calls, 1);
+		assert_eq!(errors.iter().map(ToString::to_string).collect::<Vec<_>>(), [
+			"region 0 0, chunk 1 2: timed out (3 attempts)",
+			"region 0 0, chunk 1 2: Corrupt chunk.",
+		]);
+	}
+}
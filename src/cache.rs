@@ -0,0 +1,335 @@
+#![doc = r#"
+A cache of the chunks of a world, for reading and writing many blocks at random positions.
+
+[World::get_block] and [World::set_block] read the whole chunk of a block for every call. A [ChunkCache] from
+[World::chunk_cache] keeps the chunks it has read, up to a [CacheLimit] of a number of chunks or of bytes counted with
+[Chunk::memory_usage], and drops the chunk that was used the longest time ago when it is full. Chunks that have not
+been generated are remembered too, so queries outside of the world do not open region files again.
+
+Changed chunks are written to their region files as they change with [WritePolicy::WriteThrough], or when they are
+dropped from the cache and on [ChunkCache::flush] with [WritePolicy::WriteBack], which writes a chunk once for many
+changes. The cache flushes when it is dropped, but errors are lost then, so call [flush](ChunkCache::flush) to see
+them.
+
+```no_run
+# use rustnbt::{ cache::*, chunk::BlockState, pos::BlockPos, world::{ World, OVERWORLD } };
+let world = World::open("saves/New World").expect("Failed to open world.");
+let mut cache = world.chunk_cache(CacheLimit::Bytes(256 << 20), WritePolicy::WriteBack);
+for x in 0..256 {
+	let pos = BlockPos::new(x, 64, x * 3);
+	if cache.get_block(OVERWORLD, pos).expect("Failed to read block.").is_some_and(|block| block.name == "minecraft:stone") {
+		cache.set_block(OVERWORLD, pos, BlockState::new("minecraft:glass")).expect("Failed to write block.");
+	}
+}
+cache.flush().expect("Failed to write chunks.");
+```
+"#]
+
+use crate::{
+	NbtError,
+	chunk::{ BlockState, Chunk },
+	pos::{ BlockPos, ChunkPos, RegionPos },
+	region::Region,
+	tag::{ Tag, NamedTag },
+	world::{ World, RegionKind },
+};
+use std::collections::{ BTreeMap, HashMap };
+
+/// How much a [ChunkCache] holds before it drops chunks. The cache always holds the chunk that was used last, even if
+/// it is larger than the limit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheLimit {
+	/// The number of chunks, including chunks that have not been generated.
+	Chunks(usize),
+	/// The bytes of memory of the chunks, from [Chunk::memory_usage].
+	Bytes(usize),
+}
+
+/// When a [ChunkCache] writes the chunks that were changed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WritePolicy {
+	/// A chunk is written every time it is changed, so the files are always up to date.
+	WriteThrough,
+	/// A chunk is written when it is dropped from the cache, and on [ChunkCache::flush].
+	WriteBack,
+}
+
+/// Counts of what a [ChunkCache] did.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CacheStats {
+	/// The chunks that were found in the cache.
+	pub hits: usize,
+	/// The chunks that were read from region files, or found to not be generated.
+	pub misses: usize,
+	/// The chunks that were dropped to stay under the limit.
+	pub evictions: usize,
+	/// The chunks that were written to region files.
+	pub writes: usize,
+}
+
+/// A cached chunk, or [None] for a chunk that has not been generated.
+struct Entry {
+	chunk: Option<Chunk>,
+	/// Whether the chunk was changed since it was read or written.
+	dirty: bool,
+	/// When the chunk was used last, as the key of the entry in [ChunkCache::recent].
+	used: u64,
+	/// The bytes counted for the chunk.
+	size: usize,
+}
+
+/// A bounded cache of the chunks of a world, from [World::chunk_cache]. See the [module](self) documentation.
+pub struct ChunkCache {
+	world: World,
+	limit: CacheLimit,
+	policy: WritePolicy,
+	entries: HashMap<(String, ChunkPos), Entry>,
+	/// The keys of the entries by when they were used, with the one used the longest time ago first.
+	recent: BTreeMap<u64, (String, ChunkPos)>,
+	clock: u64,
+	bytes: usize,
+	stats: CacheStats,
+}
+
+impl World {
+	/// Creates a cache of the chunks of this world. See the [cache](crate::cache) module.
+	pub fn chunk_cache(&self, limit: CacheLimit, policy: WritePolicy) -> ChunkCache {
+		ChunkCache {
+			world: self.clone(),
+			limit,
+			policy,
+			entries: HashMap::new(),
+			recent: BTreeMap::new(),
+			clock: 0,
+			bytes: 0,
+			stats: CacheStats::default(),
+		}
+	}
+}
+
+/// The bytes counted for a cached chunk.
+fn entry_size(chunk: &Option<Chunk>) -> usize {
+	std::mem::size_of::<Entry>() + chunk.as_ref().map_or(0, Chunk::memory_usage)
+}
+
+impl ChunkCache {
+	/// The world that the chunks are read from and written to.
+	pub fn world(&self) -> &World {
+		&self.world
+	}
+
+	pub fn limit(&self) -> CacheLimit {
+		self.limit
+	}
+
+	pub fn policy(&self) -> WritePolicy {
+		self.policy
+	}
+
+	pub fn stats(&self) -> CacheStats {
+		self.stats
+	}
+
+	/// The number of chunks in the cache, including chunks that have not been generated.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// The bytes of memory that the cached chunks take, as counted for [CacheLimit::Bytes].
+	pub fn memory_usage(&self) -> usize {
+		self.bytes
+	}
+
+	/// Makes a chunk the one used last, reading it if it is not in the cache, and drops other chunks to stay under
+	/// the limit.
+	fn load(&mut self, dimension: &str, pos: ChunkPos) -> Result<&mut Entry, NbtError> {
+		let key = (dimension.to_owned(), pos);
+		self.clock += 1;
+		if let Some(entry) = self.entries.get_mut(&key) {
+			self.stats.hits += 1;
+			self.recent.remove(&entry.used);
+			entry.used = self.clock;
+		} else {
+			self.stats.misses += 1;
+			let chunk = self.world.chunk(dimension, pos)?;
+			let size = entry_size(&chunk);
+			self.bytes += size;
+			self.entries.insert(key.clone(), Entry { chunk, dirty: false, used: self.clock, size });
+		}
+		self.recent.insert(self.clock, key.clone());
+		self.evict()?;
+		Ok(self.entries.get_mut(&key).expect("The chunk used last is never evicted."))
+	}
+
+	fn over_limit(&self) -> bool {
+		match self.limit {
+			CacheLimit::Chunks(chunks) => self.entries.len() > chunks,
+			CacheLimit::Bytes(bytes) => self.bytes > bytes,
+		}
+	}
+
+	/// Drops the chunks used the longest time ago until the cache is under its limit, writing those that changed.
+	fn evict(&mut self) -> Result<(), NbtError> {
+		while self.over_limit() && self.entries.len() > 1 {
+			let Some((&used, key)) = self.recent.iter().next() else {
+				break;
+			};
+			let key = key.clone();
+			let entry = &self.entries[&key];
+			if entry.dirty {
+				if let Some(chunk) = &entry.chunk {
+					self.world.save_chunk(&key.0, key.1, chunk)?;
+					self.stats.writes += 1;
+				}
+			}
+			self.recent.remove(&used);
+			let entry = self.entries.remove(&key).expect("Every key in recent has an entry.");
+			self.bytes -= entry.size;
+			self.stats.evictions += 1;
+		}
+		Ok(())
+	}
+
+	/// Borrows a chunk, reading it if it is not in the cache. Returns [None] if the chunk has not been generated.
+	pub fn chunk(&mut self, dimension: &str, pos: ChunkPos) -> Result<Option<&Chunk>, NbtError> {
+		Ok(self.load(dimension, pos)?.chunk.as_ref())
+	}
+
+	/// Changes a chunk with `change`, and writes it or marks it to be written, depending on the [WritePolicy].
+	/// Returns what `change` returned, or [None] without calling it if the chunk has not been generated.
+	pub fn modify<T, F: FnOnce(&mut Chunk) -> T>(&mut self, dimension: &str, pos: ChunkPos, change: F) -> Result<Option<T>, NbtError> {
+		let entry = self.load(dimension, pos)?;
+		let Some(chunk) = &mut entry.chunk else {
+			return Ok(None);
+		};
+		let result = change(chunk);
+		let size = entry_size(&entry.chunk);
+		let old_size = std::mem::replace(&mut entry.size, size);
+		entry.dirty = true;
+		self.bytes = self.bytes - old_size + size;
+		if self.policy == WritePolicy::WriteThrough {
+			self.write(dimension, pos)?;
+		}
+		self.evict()?;
+		Ok(Some(result))
+	}
+
+	/// Writes a cached chunk if it was changed.
+	fn write(&mut self, dimension: &str, pos: ChunkPos) -> Result<(), NbtError> {
+		let Some(entry) = self.entries.get_mut(&(dimension.to_owned(), pos)) else {
+			return Ok(());
+		};
+		if let (true, Some(chunk)) = (entry.dirty, &entry.chunk) {
+			self.world.save_chunk(dimension, pos, chunk)?;
+			entry.dirty = false;
+			self.stats.writes += 1;
+		}
+		Ok(())
+	}
+
+	/// The block at a position, like [World::get_block].
+	pub fn get_block(&mut self, dimension: &str, pos: BlockPos) -> Result<Option<BlockState>, NbtError> {
+		match self.chunk(dimension, pos.chunk())? {
+			Some(chunk) => chunk.block_at(pos),
+			None => Ok(None),
+		}
+	}
+
+	/// Sets the block at a position, like [World::set_block]. The chunk is only changed if it has a section there.
+	pub fn set_block(&mut self, dimension: &str, pos: BlockPos, state: BlockState) -> Result<Option<BlockState>, NbtError> {
+		let Some(chunk) = self.chunk(dimension, pos.chunk())? else {
+			return Ok(None);
+		};
+		if chunk.section(pos.section().y).is_none() {
+			return Ok(None);
+		}
+		Ok(self.modify(dimension, pos.chunk(), |chunk| chunk.set_block(pos, state))?.transpose()?.flatten())
+	}
+
+	/// Writes every chunk that was changed, opening each region file once. Returns the number of chunks written.
+	pub fn flush(&mut self) -> Result<usize, NbtError> {
+		let mut dirty: BTreeMap<(String, RegionPos), Vec<ChunkPos>> = BTreeMap::new();
+		for ((dimension, pos), entry) in &self.entries {
+			if entry.dirty && entry.chunk.is_some() {
+				dirty.entry((dimension.clone(), pos.region())).or_default().push(*pos);
+			}
+		}
+		let mut written = 0;
+		for ((dimension, region_pos), chunks) in dirty {
+			std::fs::create_dir_all(self.world.region_dir(&dimension, RegionKind::Chunks))?;
+			let mut region = Region::create(self.world.region_path(&dimension, RegionKind::Chunks, region_pos))?;
+			for pos in chunks {
+				let entry = self.entries.get_mut(&(dimension.clone(), pos)).expect("The dirty chunks were collected from the entries.");
+				if let Some(chunk) = &entry.chunk {
+					region.write_chunk(pos, &NamedTag::new(Tag::Compound(chunk.data().clone())))?;
+				}
+				entry.dirty = false;
+				written += 1;
+			}
+		}
+		self.stats.writes += written;
+		Ok(written)
+	}
+
+	/// Forgets every chunk without writing the ones that were changed.
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.recent.clear();
+		self.bytes = 0;
+	}
+}
+
+/// Writes the chunks that were changed, ignoring errors.
+impl Drop for ChunkCache {
+	fn drop(&mut self) {
+		let _ = self.flush();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::world::{ OVERWORLD, tests::temp_world };
+
+	#[test]
+	fn cache_test() -> Result<(), NbtError> {
+		let path = temp_world("cache");
+		let world = World::open(&path)?;
+		for x in 0..3 {
+			let Tag::Compound(data) = Tag::parse(format!("{{xPos: {x}, zPos: 0, sections: [{{Y: 4b}}]}}")).unwrap() else { unreachable!() };
+			world.save_chunk(OVERWORLD, ChunkPos::new(x, 0), &Chunk::new(data))?;
+		}
+		let stone = BlockState::new("minecraft:stone");
+
+		let mut cache = world.chunk_cache(CacheLimit::Chunks(2), WritePolicy::WriteBack);
+		assert_eq!(cache.set_block(OVERWORLD, BlockPos::new(1, 70, 1), stone.clone())?, Some(BlockState::air()));
+		assert_eq!(cache.set_block(OVERWORLD, BlockPos::new(1, 0, 1), stone.clone())?, None);
+		assert_eq!(cache.get_block(OVERWORLD, BlockPos::new(1, 70, 1))?, Some(stone.clone()));
+		// Not written until the chunk is dropped from the cache.
+		assert_eq!(world.get_block(OVERWORLD, BlockPos::new(1, 70, 1))?, Some(BlockState::air()));
+		cache.get_block(OVERWORLD, BlockPos::new(17, 70, 1))?;
+		assert_eq!(cache.get_block(OVERWORLD, BlockPos::new(-100, 70, 1))?, None);
+		assert_eq!(world.get_block(OVERWORLD, BlockPos::new(1, 70, 1))?, Some(stone.clone()));
+		assert_eq!(cache.len(), 2);
+		assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 3, evictions: 1, writes: 1 });
+
+		cache.set_block(OVERWORLD, BlockPos::new(33, 70, 1), stone.clone())?;
+		assert_eq!(cache.flush()?, 1);
+		assert_eq!(world.get_block(OVERWORLD, BlockPos::new(33, 70, 1))?, Some(stone.clone()));
+		assert_eq!(cache.flush()?, 0);
+		drop(cache);
+
+		let mut cache = world.chunk_cache(CacheLimit::Bytes(0), WritePolicy::WriteThrough);
+		cache.set_block(OVERWORLD, BlockPos::new(17, 70, 1), stone.clone())?;
+		assert_eq!(world.get_block(OVERWORLD, BlockPos::new(17, 70, 1))?, Some(stone));
+		cache.get_block(OVERWORLD, BlockPos::new(1, 70, 1))?;
+		assert_eq!(cache.len(), 1);
+		assert!(cache.memory_usage() > 0);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,249 @@
+#![doc = r#"
+Conversion between [Tag] and [CBOR](https://cbor.io) values from [ciborium](https://docs.rs/ciborium), enabled with the `cbor` feature.
+
+| NBT | CBOR |
+|-----|------|
+| Byte | integer with tag [TYPE_TAG_BASE]` + 1` |
+| Short | integer with tag [TYPE_TAG_BASE]` + 2` |
+| Int | integer |
+| Long | integer, with tag [TYPE_TAG_BASE]` + 4` if it fits in an Int |
+| Float | float with tag [TYPE_TAG_BASE]` + 5` |
+| Double | float |
+| ByteArray | byte string with tag 72 (RFC 8746 sint8 typed array) |
+| String | text string |
+| List | array |
+| Compound | map with text string keys |
+| IntArray | byte string with tag 74 (RFC 8746 sint32 big endian typed array) |
+| LongArray | byte string with tag 75 (RFC 8746 sint64 big endian typed array) |
+
+The tags only add type information, so tools that ignore them still see plain numbers and arrays of bytes,
+and converting a [Tag] to CBOR and back gives the same [Tag].<br>
+When converting values that did not come from NBT, untagged integers become Int or Long depending on their size,
+untagged floats become Double, booleans become Byte, and untagged byte strings become ByteArray.
+Other tags are ignored. `null`, and maps with keys that are not text strings, can not be converted.
+
+```
+# use rustnbt::{ compound, tag::Tag };
+let tag = compound!(("Health", Tag::Float(20.0)), ("Pos", Tag::IntArray(vec![1, 2, 3])));
+let mut bytes = Vec::new();
+rustnbt::cbor::write(&mut bytes, &tag).unwrap();
+assert_eq!(rustnbt::cbor::read(bytes.as_slice()).unwrap(), tag);
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	parse::{ ParseOptions, ParseOutcome },
+	path::{ PathSegment, TagPath },
+	tag::{
+		Tag,
+		ListTag,
+	},
+};
+use ciborium::value::{ Integer, Value };
+use std::io::{ Read, Write };
+
+/// Base of the CBOR tag numbers that mark the NBT type of a number. The NBT type ID is added to this value.
+pub const TYPE_TAG_BASE: u64 = 0x4E42_5400;
+/// RFC 8746 tag for an array of signed bytes.
+const SINT8_ARRAY: u64 = 72;
+/// RFC 8746 tag for an array of unsigned bytes.
+const UINT8_ARRAY: u64 = 64;
+/// RFC 8746 tag for an array of big endian 32-bit signed integers.
+const SINT32_ARRAY: u64 = 74;
+/// RFC 8746 tag for an array of big endian 64-bit signed integers.
+const SINT64_ARRAY: u64 = 75;
+
+fn type_tag(id: u64, value: Value) -> Value {
+	Value::Tag(TYPE_TAG_BASE + id, Box::new(value))
+}
+
+/// Converts a [Tag] to a CBOR value.
+pub fn to_value(tag: &Tag) -> Value {
+	match tag {
+		Tag::Byte(value) => type_tag(1, Value::Integer(Integer::from(*value))),
+		Tag::Short(value) => type_tag(2, Value::Integer(Integer::from(*value))),
+		Tag::Int(value) => Value::Integer(Integer::from(*value)),
+		Tag::Long(value) if i32::try_from(*value).is_ok() => type_tag(4, Value::Integer(Integer::from(*value))),
+		Tag::Long(value) => Value::Integer(Integer::from(*value)),
+		Tag::Float(value) => type_tag(5, Value::Float(*value as f64)),
+		Tag::Double(value) => Value::Float(*value),
+		Tag::ByteArray(values) => Value::Tag(SINT8_ARRAY, Box::new(Value::Bytes(bytemuck::cast_slice(values).to_vec()))),
+		Tag::String(value) => Value::Text(value.clone()),
+		Tag::List(list) => Value::Array(list_values(list)),
+		Tag::Compound(map) => Value::Map(map.iter().map(|(key, tag)| (Value::Text(key.clone()), to_value(tag))).collect()),
+		Tag::IntArray(values) => Value::Tag(SINT32_ARRAY, Box::new(Value::Bytes(values.iter().flat_map(|value| value.to_be_bytes()).collect()))),
+		Tag::LongArray(values) => Value::Tag(SINT64_ARRAY, Box::new(Value::Bytes(values.iter().flat_map(|value| value.to_be_bytes()).collect()))),
+	}
+}
+
+fn list_values(list: &ListTag) -> Vec<Value> {
+	macro_rules! convert {
+		($($title:ident)+) => {
+			match list {
+				ListTag::Empty => Vec::new(),
+				$(
+					ListTag::$title(values) => values.iter().map(|value| to_value(&Tag::$title(value.clone()))).collect(),
+				)+
+			}
+		};
+	}
+	convert!(Byte Short Int Long Float Double ByteArray String List Compound IntArray LongArray)
+}
+
+fn integer<T: TryFrom<i128>>(value: Integer) -> Result<T, NbtError> {
+	T::try_from(i128::from(value)).map_err(|_| invalid_data("CBOR integer is out of range for its NBT type."))
+}
+
+/// Splits big endian bytes into values of `N` bytes each.
+fn chunks<T, const N: usize>(bytes: &[u8], from_be_bytes: fn([u8; N]) -> T) -> Result<Vec<T>, NbtError> {
+	if !bytes.len().is_multiple_of(N) {
+		return Err(invalid_data("CBOR typed array has a partial element."));
+	}
+	Ok(bytes.chunks_exact(N).map(|chunk| from_be_bytes(chunk.try_into().expect("Chunk has N bytes."))).collect())
+}
+
+/// Converts a CBOR value to a [Tag].
+pub fn from_value(value: Value) -> Result<Tag, NbtError> {
+	from_value_with(value, &ParseOptions::default()).map(ParseOutcome::into_value)
+}
+
+/// Converts a CBOR value to a [Tag], normalizing map keys and handling keys that collide with the [ParseOptions].
+pub fn from_value_with(value: Value, options: &ParseOptions) -> Result<ParseOutcome<Tag>, NbtError> {
+	let mut options = options.clone();
+	let tag = convert(value, &mut options, &mut TagPath::new())?;
+	Ok(options.finish(tag))
+}
+
+fn convert(value: Value, options: &mut ParseOptions, path: &mut TagPath) -> Result<Tag, NbtError> {
+	Ok(match value {
+		Value::Integer(value) => match i32::try_from(value) {
+			Ok(value) => Tag::Int(value),
+			Err(_) => Tag::Long(integer(value)?),
+		},
+		Value::Float(value) => Tag::Double(value),
+		Value::Bytes(bytes) => Tag::ByteArray(bytemuck::cast_vec(bytes)),
+		Value::Text(value) => Tag::String(value),
+		Value::Bool(value) => Tag::from(value),
+		Value::Null => return Err(invalid_data("CBOR null can not be converted to NBT.")),
+		Value::Tag(tag, inner) => match (tag, *inner) {
+			(tag, Value::Integer(value)) if tag == TYPE_TAG_BASE + 1 => Tag::Byte(integer(value)?),
+			(tag, Value::Integer(value)) if tag == TYPE_TAG_BASE + 2 => Tag::Short(integer(value)?),
+			(tag, Value::Integer(value)) if tag == TYPE_TAG_BASE + 4 => Tag::Long(integer(value)?),
+			(tag, Value::Float(value)) if tag == TYPE_TAG_BASE + 5 => Tag::Float(value as f32),
+			(SINT8_ARRAY | UINT8_ARRAY, Value::Bytes(bytes)) => Tag::ByteArray(bytemuck::cast_vec(bytes)),
+			(SINT32_ARRAY, Value::Bytes(bytes)) => Tag::IntArray(chunks(&bytes, i32::from_be_bytes)?),
+			(SINT64_ARRAY, Value::Bytes(bytes)) => Tag::LongArray(chunks(&bytes, i64::from_be_bytes)?),
+			(_, inner) => convert(inner, options, path)?,
+		},
+		Value::Array(values) => {
+			let mut tags = Vec::with_capacity(values.len());
+			for (index, value) in values.into_iter().enumerate() {
+				path.push(PathSegment::Index(index));
+				tags.push(convert(value, options, path)?);
+				path.pop();
+			}
+			Tag::List(ListTag::try_from(tags).map_err(|_| invalid_data("CBOR array elements must all convert to the same NBT type."))?)
+		}
+		Value::Map(entries) => {
+			let mut map = Map::default();
+			for (key, value) in entries {
+				let Value::Text(key) = key else {
+					return Err(invalid_data("CBOR map keys must be text strings."));
+				};
+				path.push(PathSegment::Key(key.clone()));
+				let value = convert(value, options, path);
+				path.pop();
+				options.insert(&mut map, || path.clone(), key, value?)?;
+			}
+			Tag::Compound(map)
+		}
+		_ => return Err(invalid_data("Unsupported CBOR value.")),
+	})
+}
+
+/// Writes a [Tag] to a writer as CBOR.
+pub fn write<W: Write>(writer: W, tag: &Tag) -> Result<(), NbtError> {
+	ciborium::into_writer(&to_value(tag), writer).map_err(|err| match err {
+		ciborium::ser::Error::Io(err) => err.into(),
+		ciborium::ser::Error::Value(message) => invalid_data(&message),
+	})
+}
+
+/// Reads a [Tag] from CBOR.
+pub fn read<R: Read>(reader: R) -> Result<Tag, NbtError> {
+	read_with(reader, &ParseOptions::default()).map(ParseOutcome::into_value)
+}
+
+/// Reads a [Tag] from CBOR with [ParseOptions]. See [from_value_with].
+pub fn read_with<R: Read>(reader: R, options: &ParseOptions) -> Result<ParseOutcome<Tag>, NbtError> {
+	let value = ciborium::from_reader(reader).map_err(|err| match err {
+		ciborium::de::Error::Io(err) => err.into(),
+		other => invalid_data(&other.to_string()),
+	})?;
+	from_value_with(value, options)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, parse::KeyCollisionPolicy };
+
+	#[test]
+	fn roundtrip_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("byte", Tag::Byte(-3)),
+			("short", Tag::Short(300)),
+			("int", 7),
+			("small long", Tag::Long(5)),
+			("long", Tag::Long(i64::MIN)),
+			("float", Tag::Float(0.1)),
+			("double", Tag::Double(0.1)),
+			("bytes", Tag::ByteArray(vec![-1, 2])),
+			("ints", Tag::IntArray(vec![-1, i32::MAX])),
+			("longs", Tag::LongArray(vec![i64::MAX])),
+			("list", list!(1i8, 2i8)),
+			("empty", list!()),
+			("nested", Tag::List(ListTag::Compound(vec![Map::default()])))
+		);
+		let mut bytes = Vec::new();
+		write(&mut bytes, &tag)?;
+		assert_eq!(read(bytes.as_slice())?, tag);
+		Ok(())
+	}
+
+	#[test]
+	fn foreign_test() -> Result<(), NbtError> {
+		let value = Value::Map(vec![
+			(Value::Text("small".to_owned()), Value::Integer(Integer::from(1))),
+			(Value::Text("large".to_owned()), Value::Integer(Integer::from(u32::MAX))),
+			(Value::Text("flag".to_owned()), Value::Bool(true)),
+			(Value::Text("date".to_owned()), Value::Tag(0, Box::new(Value::Text("2022-11-11".to_owned())))),
+		]);
+		assert_eq!(from_value(value)?, compound!(
+			("small", 1),
+			("large", Tag::Long(u32::MAX as i64)),
+			("flag", Tag::Byte(1)),
+			("date", "2022-11-11")
+		));
+		assert!(from_value(Value::Null).is_err());
+		assert!(from_value(Value::Array(vec![Value::Integer(Integer::from(1)), Value::Text("a".to_owned())])).is_err());
+		assert!(from_value(Value::Map(vec![(Value::Integer(Integer::from(1)), Value::Null)])).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn collision_test() -> Result<(), NbtError> {
+		let value = Value::Map(vec![
+			(Value::Text("name".to_owned()), Value::Integer(Integer::from(1))),
+			(Value::Text("name".to_owned()), Value::Integer(Integer::from(2))),
+		]);
+		assert_eq!(from_value(value.clone())?, compound!(("name", 2)));
+		let outcome = from_value_with(value, &ParseOptions::new().key_collisions(KeyCollisionPolicy::KeepFirst))?;
+		assert_eq!(outcome.value, compound!(("name", 1)));
+		assert_eq!(outcome.warnings.len(), 1);
+		Ok(())
+	}
+}
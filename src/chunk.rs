@@ -0,0 +1,1004 @@
+// https://minecraft.fandom.com/wiki/Chunk_format
+
+#![doc = r#"
+Typed access to chunk data, and reading and writing blocks by their coordinates.
+
+[Chunk] wraps the root compound of a chunk in the 1.18+ format, where each entry of `sections` stores its blocks in a
+`block_states` compound as a palette of [BlockState]s and a LongArray of packed palette indices, and its biomes in a
+`biomes` compound as a palette of biome names for each 4x4x4 cell.
+[PalettedContainer] decodes and encodes that layout.
+
+[World::get_block] and [World::set_block] find, read, and (for `set_block`) rewrite the chunk that contains a block.
+Both read the whole chunk, so to work with many blocks, read the [Chunk] once with [World::chunk] and save it with [World::save_chunk],
+or keep the chunks in a [ChunkCache](crate::cache::ChunkCache).
+
+[Chunk::structure_starts] and [Chunk::structure_references] read the structures that start in, or have pieces in, a chunk.
+[Chunk::ticks] reads the pending block and fluid updates, and [Chunk::purge_foreign_ticks] and [Chunk::rebase_ticks]
+keep them in place when chunks are copied or moved. A tick left at a block that was replaced still runs if the new
+block has the same type, so [Chunk::set_block] removes them.
+
+```no_run
+# use rustnbt::{ chunk::BlockState, pos::BlockPos, world::{ World, OVERWORLD } };
+let world = World::open("saves/New World").expect("Failed to open world.");
+let pos = BlockPos::new(10, 64, -3);
+println!("{:?}", world.get_block(OVERWORLD, pos).expect("Failed to read block."));
+let log = BlockState::new("minecraft:oak_log").with("axis", "y");
+world.set_block(OVERWORLD, pos, log).expect("Failed to write block.");
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	context::{ ErrorContext, WithContext },
+	invalid_data,
+	path::TagPath,
+	pos::{ BlockPos, ChunkPos },
+	region::Region,
+	tag::{
+		Tag,
+		ListTag,
+		NamedTag,
+	},
+	world::{ World, RegionKind },
+};
+use std::collections::BTreeMap;
+
+/// The name of the air block, which fills sections that have not been built in.
+pub const AIR: &str = "minecraft:air";
+
+/// The number of blocks in a chunk section.
+pub const SECTION_BLOCKS: usize = 4096;
+
+/// The number of biome cells in a chunk section. Each cell is 4x4x4 blocks.
+pub const SECTION_BIOMES: usize = 64;
+
+/// A block and its properties, as stored in a block palette.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BlockState {
+	/// The namespaced block ID, such as `minecraft:stone`.
+	pub name: String,
+	/// The block's properties, such as `axis` = `y`. Values are always strings in a palette.
+	pub properties: BTreeMap<String, String>,
+}
+
+impl BlockState {
+	/// A block state without properties.
+	pub fn new<S: Into<String>>(name: S) -> Self {
+		Self { name: name.into(), properties: BTreeMap::new() }
+	}
+
+	/// Air.
+	pub fn air() -> Self {
+		Self::new(AIR)
+	}
+
+	/// Sets a property.
+	pub fn with<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+		self.properties.insert(key.into(), value.into());
+		self
+	}
+
+	/// The value of a property.
+	pub fn property(&self, key: &str) -> Option<&str> {
+		self.properties.get(key).map(String::as_str)
+	}
+
+	/// Reads a palette entry, which has a `Name` string and an optional `Properties` compound of strings.
+	pub fn from_map(map: &Map) -> Option<Self> {
+		let Some(Tag::String(name)) = map.get("Name") else {
+			return None;
+		};
+		let mut state = Self::new(name.clone());
+		if let Some(Tag::Compound(properties)) = map.get("Properties") {
+			for (key, value) in properties {
+				let Tag::String(value) = value else {
+					return None;
+				};
+				state.properties.insert(key.clone(), value.clone());
+			}
+		}
+		Some(state)
+	}
+
+	/// Writes a palette entry. `Properties` is left out when there are none, like the game does.
+	pub fn to_map(&self) -> Map {
+		let mut map = Map::from_iter([("Name".to_owned(), Tag::String(self.name.clone()))]);
+		if !self.properties.is_empty() {
+			let properties = self.properties.iter().map(|(key, value)| (key.clone(), Tag::String(value.clone())));
+			map.insert("Properties".to_owned(), Tag::Compound(Map::from_iter(properties)));
+		}
+		map
+	}
+}
+
+/// Formats the state like a command argument, such as `minecraft:oak_log[axis=y]`.
+impl std::fmt::Display for BlockState {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.name)?;
+		if !self.properties.is_empty() {
+			let properties: Vec<String> = self.properties.iter().map(|(key, value)| format!("{key}={value}")).collect();
+			write!(f, "[{}]", properties.join(","))?;
+		}
+		Ok(())
+	}
+}
+
+/// Parses the format written by [Display](std::fmt::Display), such as `minecraft:oak_log[axis=y]`.
+impl std::str::FromStr for BlockState {
+	type Err = NbtError;
+
+	fn from_str(text: &str) -> Result<Self, NbtError> {
+		let invalid = || invalid_data(&format!("Invalid block state: {text}"));
+		let (name, properties) = match text.split_once('[') {
+			Some((name, rest)) => (name, Some(rest.strip_suffix(']').ok_or_else(invalid)?)),
+			None => (text, None),
+		};
+		if name.is_empty() {
+			return Err(invalid());
+		}
+		let mut state = Self::new(name);
+		for property in properties.into_iter().flat_map(|properties| properties.split(',')).filter(|property| !property.is_empty()) {
+			let (key, value) = property.split_once('=').ok_or_else(invalid)?;
+			state.properties.insert(key.trim().to_owned(), value.trim().to_owned());
+		}
+		Ok(state)
+	}
+}
+
+/// The number of bits that each index of a palette of `len` values takes, which is at least `min_bits`.
+fn index_bits(len: usize, min_bits: u32) -> usize {
+	(usize::BITS - len.saturating_sub(1).leading_zeros()).max(min_bits) as usize
+}
+
+/// The most values that a palette can hold, since palette indices are u16.
+const MAX_PALETTE: usize = u16::MAX as usize + 1;
+
+/// Values stored as a palette and one palette index per entry, like the blocks of a chunk section.<br>
+/// The palette holds at most 65536 values.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PalettedContainer<T> {
+	palette: Vec<T>,
+	indices: Vec<u16>,
+}
+
+impl<T: Clone + PartialEq> PalettedContainer<T> {
+	/// A container of `size` entries that are all `value`.
+	pub fn filled(value: T, size: usize) -> Self {
+		Self { palette: vec![value], indices: vec![0; size] }
+	}
+
+	/// Decodes a palette and its packed indices.<br>
+	/// Indices are packed into longs from the lowest bits up, and do not span two longs. Like the game, each index
+	/// takes as many bits as the palette needs, and at least `min_bits` (4 for blocks, 1 for biomes). A palette with
+	/// a single value has no `data`. Fails if the palette has more than 65536 values, or indices take more than 16 bits.
+	pub fn unpack(palette: Vec<T>, data: &[i64], size: usize, min_bits: u32) -> Result<Self, NbtError> {
+		if palette.is_empty() {
+			return Err(invalid_data("Palette is empty."));
+		}
+		if palette.len() > MAX_PALETTE {
+			return Err(invalid_data(&format!("Palette has {} values, but at most {MAX_PALETTE} are supported.", palette.len())));
+		}
+		if data.is_empty() {
+			if palette.len() > 1 {
+				return Err(invalid_data("Palette has more than one entry, but there is no data."));
+			}
+			return Ok(Self { palette, indices: vec![0; size] });
+		}
+		let bits = index_bits(palette.len(), min_bits);
+		if bits > 16 {
+			return Err(invalid_data(&format!("Indices of {bits} bits are not supported, at most 16 are.")));
+		}
+		let per_long = 64 / bits;
+		if size.div_ceil(per_long) != data.len() {
+			return Err(invalid_data(&format!("Packed data has {} longs, but {size} entries of {bits} bits take {}.", data.len(), size.div_ceil(per_long))));
+		}
+		let mask = (1u64 << bits) - 1;
+		let indices: Vec<u16> = (0..size)
+			.map(|index| ((data[index / per_long] as u64 >> (index % per_long * bits)) & mask) as u16)
+			.collect();
+		if indices.iter().any(|index| *index as usize >= palette.len()) {
+			return Err(invalid_data("Packed data has an index outside of the palette."));
+		}
+		Ok(Self { palette, indices })
+	}
+
+	/// Encodes the container as a palette and packed indices, removing palette entries that are not used.<br>
+	/// Indices take at least `min_bits` bits (4 for blocks, 1 for biomes). A single value is written without data.
+	pub fn pack(&self, min_bits: u32) -> (Vec<T>, Vec<i64>) {
+		let mut remap = vec![None; self.palette.len()];
+		let mut palette = Vec::new();
+		let indices: Vec<u16> = self.indices.iter().map(|index| {
+			*remap[*index as usize].get_or_insert_with(|| {
+				palette.push(self.palette[*index as usize].clone());
+				(palette.len() - 1) as u16
+			})
+		}).collect();
+		if palette.len() <= 1 {
+			return (palette, Vec::new());
+		}
+		let bits = index_bits(palette.len(), min_bits);
+		let per_long = 64 / bits;
+		let data = indices.chunks(per_long).map(|chunk| {
+			chunk.iter().enumerate().fold(0u64, |long, (offset, index)| long | (*index as u64) << (offset * bits)) as i64
+		}).collect();
+		(palette, data)
+	}
+
+	/// The number of entries.
+	pub fn len(&self) -> usize {
+		self.indices.len()
+	}
+
+	/// Returns true if there are no entries.
+	pub fn is_empty(&self) -> bool {
+		self.indices.is_empty()
+	}
+
+	/// The palette, which may hold values that are no longer used.
+	pub fn palette(&self) -> &[T] {
+		&self.palette
+	}
+
+	/// The value of an entry.
+	pub fn get(&self, index: usize) -> &T {
+		&self.palette[self.indices[index] as usize]
+	}
+
+	/// Sets the value of an entry, adding it to the palette if needed. Returns the old value.<br>
+	/// Fails if the value is new and the palette already holds 65536 values, in which case nothing is changed.
+	pub fn set(&mut self, index: usize, value: T) -> Result<T, NbtError> {
+		let old = self.get(index).clone();
+		let palette_index = match self.palette.iter().position(|entry| *entry == value) {
+			Some(position) => position,
+			None if self.palette.len() >= MAX_PALETTE => {
+				return Err(invalid_data(&format!("Palette already holds {MAX_PALETTE} values.")));
+			}
+			None => {
+				self.palette.push(value);
+				self.palette.len() - 1
+			}
+		};
+		self.indices[index] = palette_index as u16;
+		Ok(old)
+	}
+
+	/// Iterates over the values of all entries.
+	pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+		self.indices.iter().map(|index| &self.palette[*index as usize])
+	}
+}
+
+/// An axis-aligned box of blocks, which includes both corners.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BoundingBox {
+	pub min: BlockPos,
+	pub max: BlockPos,
+}
+
+impl BoundingBox {
+	/// Reads a `BB` IntArray, which holds the minimum X, Y, Z followed by the maximum X, Y, Z.
+	pub fn from_array(values: &[i32]) -> Option<Self> {
+		match values {
+			&[min_x, min_y, min_z, max_x, max_y, max_z] => Some(Self {
+				min: BlockPos::new(min_x, min_y, min_z),
+				max: BlockPos::new(max_x, max_y, max_z),
+			}),
+			_ => None,
+		}
+	}
+
+	/// The values of a `BB` IntArray.
+	pub fn to_array(self) -> [i32; 6] {
+		[self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z]
+	}
+
+	/// Returns true if the box contains the block.
+	pub fn contains(self, pos: BlockPos) -> bool {
+		(self.min.x..=self.max.x).contains(&pos.x)
+			&& (self.min.y..=self.max.y).contains(&pos.y)
+			&& (self.min.z..=self.max.z).contains(&pos.z)
+	}
+
+	/// The smallest box that contains both boxes.
+	pub fn union(self, other: Self) -> Self {
+		Self {
+			min: BlockPos::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z)),
+			max: BlockPos::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z)),
+		}
+	}
+}
+
+/// A piece of a structure (an entry of `Children` in a structure start).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StructurePiece {
+	/// The type of the piece, such as `minecraft:jigsaw`.
+	pub id: String,
+	pub bounds: BoundingBox,
+}
+
+/// A structure that starts in a chunk, from `structures.starts`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StructureStart {
+	/// The structure, such as `minecraft:village_plains`.
+	pub id: String,
+	/// The chunk that the structure starts in (`ChunkX` and `ChunkZ`).
+	pub chunk: ChunkPos,
+	/// The number of times the structure has been referenced by a locate, such as by an explorer map (`references`).
+	pub references: i32,
+	/// The pieces of the structure, which may span many chunks.
+	pub pieces: Vec<StructurePiece>,
+}
+
+impl StructureStart {
+	fn from_map(map: &Map) -> Option<Self> {
+		let Some(Tag::String(id)) = map.get("id") else {
+			return None;
+		};
+		// Chunks that were checked for a structure that was not placed store an INVALID start.
+		if id == "INVALID" {
+			return None;
+		}
+		let (Some(Tag::Int(x)), Some(Tag::Int(z))) = (map.get("ChunkX"), map.get("ChunkZ")) else {
+			return None;
+		};
+		let references = match map.get("references") {
+			Some(Tag::Int(references)) => *references,
+			_ => 0,
+		};
+		let pieces = match map.get("Children") {
+			Some(Tag::List(ListTag::Compound(children))) => children.iter().filter_map(|child| {
+				match (child.get("id"), child.get("BB")) {
+					(Some(Tag::String(id)), Some(Tag::IntArray(bounds))) => Some(StructurePiece {
+						id: id.clone(),
+						bounds: BoundingBox::from_array(bounds)?,
+					}),
+					_ => None,
+				}
+			}).collect(),
+			_ => Vec::new(),
+		};
+		Some(Self { id: id.clone(), chunk: ChunkPos::new(*x, *z), references, pieces })
+	}
+
+	/// The box that contains every piece, or [None] if there are no pieces.
+	pub fn bounds(&self) -> Option<BoundingBox> {
+		self.pieces.iter().map(|piece| piece.bounds).reduce(BoundingBox::union)
+	}
+}
+
+/// The two lists of scheduled ticks in a chunk.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TickKind {
+	/// Block updates, such as redstone and falling blocks (`block_ticks`).
+	Block,
+	/// Fluid updates, for flowing water and lava (`fluid_ticks`).
+	Fluid,
+}
+
+impl TickKind {
+	pub const ALL: [TickKind; 2] = [TickKind::Block, TickKind::Fluid];
+
+	/// The key of the list in the chunk compound.
+	pub const fn key(self) -> &'static str {
+		match self {
+			TickKind::Block => "block_ticks",
+			TickKind::Fluid => "fluid_ticks",
+		}
+	}
+}
+
+/// A pending block or fluid update.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ScheduledTick {
+	/// The block or fluid that is updated (`i`). The update is dropped if the block has a different type when it runs.
+	pub id: String,
+	/// The position of the block, in world coordinates.
+	pub pos: BlockPos,
+	/// The number of game ticks until the update (`t`).
+	pub delay: i32,
+	/// The order of updates scheduled for the same tick, where lower runs first (`p`).
+	pub priority: i32,
+}
+
+impl ScheduledTick {
+	/// Reads a tick list entry.
+	pub fn from_map(map: &Map) -> Option<Self> {
+		let (Some(Tag::String(id)), Some(pos), Some(Tag::Int(delay))) = (map.get("i"), entry_pos(map), map.get("t")) else {
+			return None;
+		};
+		let priority = match map.get("p") {
+			Some(Tag::Int(priority)) => *priority,
+			_ => 0,
+		};
+		Some(Self { id: id.clone(), pos, delay: *delay, priority })
+	}
+
+	/// Writes a tick list entry.
+	pub fn to_map(&self) -> Map {
+		Map::from_iter([
+			("i".to_owned(), Tag::String(self.id.clone())),
+			("x".to_owned(), Tag::Int(self.pos.x)),
+			("y".to_owned(), Tag::Int(self.pos.y)),
+			("z".to_owned(), Tag::Int(self.pos.z)),
+			("t".to_owned(), Tag::Int(self.delay)),
+			("p".to_owned(), Tag::Int(self.priority)),
+		])
+	}
+}
+
+/// Wrapper around the root compound of a chunk in the 1.18+ format.<br>
+/// The wrapper only interprets the keys that its accessors touch, so any other
+/// data in the compound is left untouched.
+#[derive(Clone, Debug, Default)]
+pub struct Chunk {
+	data: Map,
+}
+
+impl Chunk {
+	/// Wraps the root compound of a chunk.
+	pub fn new(data: Map) -> Self {
+		Self { data }
+	}
+
+	/// Immutably borrow the underlying compound.
+	pub fn data(&self) -> &Map {
+		&self.data
+	}
+
+	/// Mutably borrow the underlying compound.
+	pub fn data_mut(&mut self) -> &mut Map {
+		&mut self.data
+	}
+
+	/// An estimate of the bytes of memory that the chunk takes, like [Tag::memory_usage].
+	pub fn memory_usage(&self) -> usize {
+		crate::tag::map_memory_usage(&self.data)
+	}
+
+	/// Irreversibly take the underlying compound.
+	pub fn take_data(self) -> Map {
+		self.data
+	}
+
+	/// The position of the chunk (`xPos` and `zPos`).
+	pub fn position(&self) -> Option<ChunkPos> {
+		match (self.data.get("xPos"), self.data.get("zPos")) {
+			(Some(Tag::Int(x)), Some(Tag::Int(z))) => Some(ChunkPos::new(*x, *z)),
+			_ => None,
+		}
+	}
+
+	/// The Y coordinates of the sections that the chunk has.
+	pub fn section_ys(&self) -> Vec<i32> {
+		self.sections().filter_map(section_y).collect()
+	}
+
+	/// The section with the given section Y coordinate.
+	pub fn section(&self, y: i32) -> Option<&Map> {
+		self.sections().find(|section| section_y(section) == Some(y))
+	}
+
+	/// Mutably borrow the section with the given section Y coordinate.
+	pub fn section_mut(&mut self, y: i32) -> Option<&mut Map> {
+		match self.data.get_mut("sections") {
+			Some(Tag::List(ListTag::Compound(sections))) => sections.iter_mut().find(|section| section_y(section) == Some(y)),
+			_ => None,
+		}
+	}
+
+	fn sections(&self) -> impl Iterator<Item = &Map> {
+		match self.data.get("sections") {
+			Some(Tag::List(ListTag::Compound(sections))) => sections.iter(),
+			_ => [].iter(),
+		}
+	}
+
+	/// The chunk and path of a compound of a section, for the errors of decoding it.
+	fn section_context(&self, y: i32, key: &str) -> ErrorContext {
+		let index = self.sections().position(|section| section_y(section) == Some(y)).unwrap_or_default();
+		let context = ErrorContext::default().path(TagPath::new().key("sections").index(index).key(key));
+		match self.position() {
+			Some(pos) => context.chunk(pos),
+			None => context,
+		}
+	}
+
+	/// Decodes the blocks of a section. Returns [None] if the chunk has no such section.<br>
+	/// A section without `block_states` is all air.
+	pub fn block_states(&self, section_y: i32) -> Result<Option<PalettedContainer<BlockState>>, NbtError> {
+		let Some(section) = self.section(section_y) else {
+			return Ok(None);
+		};
+		let Some(Tag::Compound(states)) = section.get("block_states") else {
+			return Ok(Some(PalettedContainer::filled(BlockState::air(), SECTION_BLOCKS)));
+		};
+		let context = || self.section_context(section_y, "block_states");
+		let palette = match states.get("palette") {
+			Some(Tag::List(ListTag::Compound(entries))) => entries.iter()
+				.map(|entry| BlockState::from_map(entry).ok_or_else(|| invalid_data("Invalid block palette entry.")))
+				.collect::<Result<Vec<_>, _>>()
+				.with_context(context)?,
+			_ => return Err(invalid_data("Block states have no palette.").with_context(context())),
+		};
+		PalettedContainer::unpack(palette, packed_data(states), SECTION_BLOCKS, 4).map(Some).with_context(context)
+	}
+
+	/// Encodes the blocks of a section into its `block_states`. Returns false if the chunk has no such section.
+	pub fn set_block_states(&mut self, section_y: i32, blocks: &PalettedContainer<BlockState>) -> bool {
+		let Some(section) = self.section_mut(section_y) else {
+			return false;
+		};
+		let (palette, data) = blocks.pack(4);
+		let palette = ListTag::Compound(palette.iter().map(BlockState::to_map).collect());
+		section.insert("block_states".to_owned(), container_tag(palette, data));
+		true
+	}
+
+	/// Decodes the biomes of a section, which are stored for each 4x4x4 cell.
+	/// Returns [None] if the chunk has no such section, or the section has no `biomes`.
+	pub fn biomes(&self, section_y: i32) -> Result<Option<PalettedContainer<String>>, NbtError> {
+		let Some(Tag::Compound(biomes)) = self.section(section_y).and_then(|section| section.get("biomes")) else {
+			return Ok(None);
+		};
+		let context = || self.section_context(section_y, "biomes");
+		let palette = match biomes.get("palette") {
+			Some(Tag::List(ListTag::String(entries))) => entries.clone(),
+			_ => return Err(invalid_data("Biomes have no palette.").with_context(context())),
+		};
+		PalettedContainer::unpack(palette, packed_data(biomes), SECTION_BIOMES, 1).map(Some).with_context(context)
+	}
+
+	/// Encodes the biomes of a section into its `biomes`. Returns false if the chunk has no such section.
+	pub fn set_biomes(&mut self, section_y: i32, biomes: &PalettedContainer<String>) -> bool {
+		let Some(section) = self.section_mut(section_y) else {
+			return false;
+		};
+		let (palette, data) = biomes.pack(1);
+		section.insert("biomes".to_owned(), container_tag(ListTag::String(palette), data));
+		true
+	}
+
+	/// The biome at a position, such as `minecraft:plains`. Like [block_at](Self::block_at), world and
+	/// chunk-local positions both work. Returns [None] if there are no biomes stored there.
+	pub fn biome_at(&self, pos: BlockPos) -> Result<Option<String>, NbtError> {
+		Ok(self.biomes(pos.section().y)?.map(|biomes| biomes.get(pos.biome_index()).clone()))
+	}
+
+	/// Sets the biome of the 4x4x4 cell that contains a position, and returns the biome that was there,
+	/// or [None] if there are no biomes stored there, in which case nothing is changed.
+	pub fn set_biome<S: Into<String>>(&mut self, pos: BlockPos, biome: S) -> Result<Option<String>, NbtError> {
+		let Some(mut biomes) = self.biomes(pos.section().y)? else {
+			return Ok(None);
+		};
+		let old = biomes.set(pos.biome_index(), biome.into())?;
+		self.set_biomes(pos.section().y, &biomes);
+		Ok(Some(old))
+	}
+
+	/// The block at a position. Only the section Y coordinate and the position within the section are used,
+	/// so world and chunk-local positions both work. Returns [None] if the chunk has no section there.
+	pub fn block_at(&self, pos: BlockPos) -> Result<Option<BlockState>, NbtError> {
+		Ok(self.block_states(pos.section().y)?.map(|blocks| blocks.get(pos.section_index()).clone()))
+	}
+
+	/// Sets the block at a position, and returns the block that was there, or [None] if the
+	/// chunk has no section there, in which case nothing is changed. Like [block_at](Self::block_at), world and
+	/// chunk-local positions both work.<br>
+	/// A block entity and scheduled ticks at the position are removed, since they belonged to the old block,
+	/// and the chunk is [marked dirty](Self::mark_dirty).
+	pub fn set_block(&mut self, pos: BlockPos, state: BlockState) -> Result<Option<BlockState>, NbtError> {
+		let Some(mut blocks) = self.block_states(pos.section().y)? else {
+			return Ok(None);
+		};
+		let old = blocks.set(pos.section_index(), state)?;
+		self.set_block_states(pos.section().y, &blocks);
+		// Block entities and ticks are stored with world positions.
+		let pos = self.world_pos(pos);
+		if let Some(Tag::List(ListTag::Compound(entities))) = self.data.get_mut("block_entities") {
+			entities.retain(|entity| entry_pos(entity) != Some(pos));
+		}
+		self.remove_ticks_at(pos);
+		self.mark_dirty();
+		Ok(Some(old))
+	}
+
+	/// A position within this chunk as a world position. The position is returned as it is if the chunk has none.
+	fn world_pos(&self, pos: BlockPos) -> BlockPos {
+		match self.position() {
+			Some(chunk) => BlockPos::new(chunk.x << 4 | pos.x & 15, pos.y, chunk.z << 4 | pos.z & 15),
+			None => pos,
+		}
+	}
+
+	/// The scheduled ticks of a kind. Entries that are not valid ticks are skipped.
+	pub fn ticks(&self, kind: TickKind) -> Vec<ScheduledTick> {
+		match self.data.get(kind.key()) {
+			Some(Tag::List(ListTag::Compound(ticks))) => ticks.iter().filter_map(ScheduledTick::from_map).collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Replaces the scheduled ticks of a kind.
+	pub fn set_ticks(&mut self, kind: TickKind, ticks: &[ScheduledTick]) {
+		let ticks = ticks.iter().map(ScheduledTick::to_map).collect();
+		self.data.insert(kind.key().to_owned(), Tag::List(ListTag::Compound(ticks)));
+	}
+
+	/// Keeps the scheduled ticks for which `keep` returns true, in both lists, and returns the number removed.<br>
+	/// Entries are filtered in place, so anything the game adds to them is kept.
+	pub fn retain_ticks<F: FnMut(TickKind, BlockPos) -> bool>(&mut self, mut keep: F) -> usize {
+		let mut removed = 0;
+		for kind in TickKind::ALL {
+			if let Some(Tag::List(ListTag::Compound(ticks))) = self.data.get_mut(kind.key()) {
+				let before = ticks.len();
+				ticks.retain(|tick| entry_pos(tick).is_some_and(|pos| keep(kind, pos)));
+				removed += before - ticks.len();
+			}
+		}
+		removed
+	}
+
+	/// Removes the scheduled ticks at a block, which should be done whenever the block is replaced.
+	/// Returns the number removed.
+	pub fn remove_ticks_at(&mut self, pos: BlockPos) -> usize {
+		self.retain_ticks(|_, tick| tick != pos)
+	}
+
+	/// Removes the scheduled ticks that are not inside this chunk, which cause updates in the wrong place
+	/// after a chunk is copied or moved. Returns the number removed, or [None] if the chunk has no position.
+	pub fn purge_foreign_ticks(&mut self) -> Option<usize> {
+		let chunk = self.position()?;
+		Some(self.retain_ticks(|_, pos| pos.chunk() == chunk))
+	}
+
+	/// Moves the scheduled ticks along with a chunk that was moved from `from` to `to`, so that they keep
+	/// the same position relative to the chunk. The chunk's own position is not changed.
+	pub fn rebase_ticks(&mut self, from: ChunkPos, to: ChunkPos) {
+		let (dx, dz) = ((to.x - from.x) << 4, (to.z - from.z) << 4);
+		for kind in TickKind::ALL {
+			if let Some(Tag::List(ListTag::Compound(ticks))) = self.data.get_mut(kind.key()) {
+				for tick in ticks {
+					if let Some(Tag::Int(x)) = tick.get_mut("x") {
+						*x = x.wrapping_add(dx);
+					}
+					if let Some(Tag::Int(z)) = tick.get_mut("z") {
+						*z = z.wrapping_add(dz);
+					}
+				}
+			}
+		}
+	}
+
+	/// The `structures` compound, or `Level.Structures` in chunks from before 1.18.
+	fn structures(&self) -> Option<&Map> {
+		match (self.data.get("structures"), self.data.get("Level")) {
+			(Some(Tag::Compound(structures)), _) => Some(structures),
+			(None, Some(Tag::Compound(level))) => match level.get("Structures") {
+				Some(Tag::Compound(structures)) => Some(structures),
+				_ => None,
+			},
+			_ => None,
+		}
+	}
+
+	/// The structures that have pieces in this chunk, and the chunks that they start in (`structures.References`).
+	pub fn structure_references(&self) -> BTreeMap<String, Vec<ChunkPos>> {
+		let Some(Tag::Compound(references)) = self.structures().and_then(|structures| structures.get("References")) else {
+			return BTreeMap::new();
+		};
+		references.iter().filter_map(|(structure, chunks)| match chunks {
+			Tag::LongArray(chunks) => Some((structure.clone(), chunks.iter().map(|chunk| ChunkPos::from_long(*chunk)).collect())),
+			_ => None,
+		}).collect()
+	}
+
+	/// The structures that start in this chunk, by structure name (`structures.starts`).
+	pub fn structure_starts(&self) -> BTreeMap<String, StructureStart> {
+		let starts = self.structures().and_then(|structures| structures.get("starts").or_else(|| structures.get("Starts")));
+		let Some(Tag::Compound(starts)) = starts else {
+			return BTreeMap::new();
+		};
+		starts.iter().filter_map(|(structure, start)| match start {
+			Tag::Compound(start) => Some((structure.clone(), StructureStart::from_map(start)?)),
+			_ => None,
+		}).collect()
+	}
+
+	/// Makes the game recompute the chunk's light and heightmaps when it is next loaded,
+	/// by clearing `isLightOn` and removing `Heightmaps`.
+	pub fn mark_dirty(&mut self) {
+		self.data.insert("isLightOn".to_owned(), Tag::Byte(0));
+		self.data.retain(|key, _| key != "Heightmaps");
+	}
+}
+
+fn section_y(section: &Map) -> Option<i32> {
+	match section.get("Y") {
+		Some(Tag::Byte(y)) => Some(*y as i32),
+		Some(Tag::Int(y)) => Some(*y),
+		_ => None,
+	}
+}
+
+fn packed_data(container: &Map) -> &[i64] {
+	match container.get("data") {
+		Some(Tag::LongArray(data)) => data,
+		_ => &[],
+	}
+}
+
+fn container_tag(palette: ListTag, data: Vec<i64>) -> Tag {
+	let mut container = Map::from_iter([("palette".to_owned(), Tag::List(palette))]);
+	if !data.is_empty() {
+		container.insert("data".to_owned(), Tag::LongArray(data));
+	}
+	Tag::Compound(container)
+}
+
+/// The `x`, `y`, and `z` of a block entity or scheduled tick.
+fn entry_pos(entity: &Map) -> Option<BlockPos> {
+	match (entity.get("x"), entity.get("y"), entity.get("z")) {
+		(Some(Tag::Int(x)), Some(Tag::Int(y)), Some(Tag::Int(z))) => Some(BlockPos::new(*x, *y, *z)),
+		_ => None,
+	}
+}
+
+impl World {
+	/// Reads a chunk from its region file. Returns [None] if the chunk has not been generated.
+	pub fn chunk(&self, dimension: &str, pos: ChunkPos) -> Result<Option<Chunk>, NbtError> {
+		let path = self.region_path(dimension, RegionKind::Chunks, pos.region());
+		if !path.is_file() {
+			return Ok(None);
+		}
+		let chunk = Region::open(path).and_then(|mut region| region.read_chunk(pos));
+		match chunk.with_context(|| ErrorContext::default().dimension(dimension).chunk(pos))? {
+			Some(chunk) => match chunk.take_tag() {
+				Tag::Compound(data) => Ok(Some(Chunk::new(data))),
+				_ => Err(invalid_data("Chunk root is not a Compound.").with_context(ErrorContext::default().dimension(dimension).chunk(pos))),
+			},
+			None => Ok(None),
+		}
+	}
+
+	/// Writes a chunk to its region file, creating the file if needed.
+	pub fn save_chunk(&self, dimension: &str, pos: ChunkPos, chunk: &Chunk) -> Result<(), NbtError> {
+		let dir = self.region_dir(dimension, RegionKind::Chunks);
+		std::fs::create_dir_all(dir)?;
+		Region::create(self.region_path(dimension, RegionKind::Chunks, pos.region()))
+			.and_then(|mut region| region.write_chunk(pos, &NamedTag::new(Tag::Compound(chunk.data().clone()))))
+			.with_context(|| ErrorContext::default().dimension(dimension).chunk(pos))
+	}
+
+	/// The block at a position. Returns [None] if the chunk has not been generated, or has no section there.
+	pub fn get_block(&self, dimension: &str, pos: BlockPos) -> Result<Option<BlockState>, NbtError> {
+		match self.chunk(dimension, pos.chunk())? {
+			Some(chunk) => chunk.block_at(pos).with_context(|| ErrorContext::default().dimension(dimension)),
+			None => Ok(None),
+		}
+	}
+
+	/// Sets the block at a position with [Chunk::set_block], and saves the chunk. Returns the block that was there,
+	/// or [None] if the chunk has not been generated or has no section there, in which case nothing is written.
+	pub fn set_block(&self, dimension: &str, pos: BlockPos, state: BlockState) -> Result<Option<BlockState>, NbtError> {
+		let Some(mut chunk) = self.chunk(dimension, pos.chunk())? else {
+			return Ok(None);
+		};
+		let old = chunk.set_block(pos, state).with_context(|| ErrorContext::default().dimension(dimension).chunk(pos.chunk()))?;
+		if old.is_some() {
+			self.save_chunk(dimension, pos.chunk(), &chunk)?;
+		}
+		Ok(old)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, world::{ OVERWORLD, tests::temp_world } };
+
+	#[test]
+	fn palette_test() -> Result<(), NbtError> {
+		let mut container = PalettedContainer::filled(0u8, SECTION_BLOCKS);
+		assert_eq!(container.pack(4), (vec![0], Vec::new()));
+		for value in 1..=16 {
+			container.set(value as usize * 100, value)?;
+		}
+		// 17 values take 5 bits, so 12 fit in each long.
+		let (palette, data) = container.pack(4);
+		assert_eq!((palette.len(), data.len()), (17, SECTION_BLOCKS.div_ceil(12)));
+		let unpacked = PalettedContainer::unpack(palette, &data, SECTION_BLOCKS, 4)?;
+		assert!(unpacked.iter().eq(container.iter()));
+		assert_eq!(*unpacked.get(1600), 16);
+		// Values that are overwritten are dropped from the palette when packing.
+		container.set(1600, 0)?;
+		assert_eq!(container.palette().len(), 17);
+		assert_eq!(container.pack(4).0.len(), 16);
+		assert!(PalettedContainer::unpack(vec![0u8, 1], &[], 64, 1).is_err());
+		assert!(PalettedContainer::unpack(vec![0u8, 1, 2], &[-1, -1], 64, 1).is_err());
+		assert!(PalettedContainer::unpack(vec![0u8, 1], &[0; 3], 64, 1).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn palette_bits_test() -> Result<(), NbtError> {
+		// 64 biome cells take 4 longs at both 3 and 4 bits, and 4096 blocks take 820 longs at both 11 and 12 bits, so
+		// the bits come from the palette and not from the length of the data.
+		for (values, size, min_bits) in [(5, SECTION_BIOMES, 1), (8, SECTION_BIOMES, 1), (9, SECTION_BIOMES, 1), (1025, SECTION_BLOCKS, 4), (2049, SECTION_BLOCKS, 4)] {
+			let mut container = PalettedContainer::filled(0u16, size);
+			for value in 1..values {
+				container.set(value as usize % size, value)?;
+			}
+			let (palette, data) = container.pack(min_bits);
+			assert_eq!(palette.len(), values as usize);
+			let unpacked = PalettedContainer::unpack(palette, &data, size, min_bits)?;
+			assert!(unpacked.iter().eq(container.iter()), "{values} values");
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn palette_limit_test() -> Result<(), NbtError> {
+		let mut full = PalettedContainer { palette: (0..MAX_PALETTE as u32).collect(), indices: vec![0; 4] };
+		assert!(full.set(0, MAX_PALETTE as u32).is_err());
+		assert_eq!(full.set(0, 5)?, 0);
+		assert_eq!((full.palette().len(), *full.get(0)), (MAX_PALETTE, 5));
+		let (palette, data) = full.pack(16);
+		assert_eq!(palette.len(), 2);
+		assert!(PalettedContainer::unpack(palette.clone(), &data, 4, 16)?.iter().eq(full.iter()));
+		assert!(PalettedContainer::unpack(palette, &data, 4, 17).is_err());
+		let palette: Vec<u32> = (0..=MAX_PALETTE as u32).collect();
+		assert!(PalettedContainer::unpack(palette, &[0; 4], 4, 4).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn block_state_test() -> Result<(), NbtError> {
+		let log = BlockState::new("minecraft:oak_log").with("axis", "y");
+		assert_eq!(log.to_string(), "minecraft:oak_log[axis=y]");
+		assert_eq!("minecraft:oak_log[axis=y]".parse::<BlockState>()?, log);
+		assert_eq!(BlockState::from_map(&log.to_map()), Some(log));
+		assert!(!BlockState::air().to_map().contains_key("Properties"));
+		assert!("minecraft:stone[axis".parse::<BlockState>().is_err());
+		Ok(())
+	}
+
+	fn section(y: i8) -> Map {
+		let stone = Map::from_iter([("Name".to_owned(), Tag::from("minecraft:stone"))]);
+		Map::from_iter([
+			("Y".to_owned(), Tag::Byte(y)),
+			("block_states".to_owned(), compound!(("palette", Tag::List(ListTag::Compound(vec![stone]))))),
+		])
+	}
+
+	#[test]
+	fn biome_test() -> Result<(), NbtError> {
+		let mut with_biomes = section(0);
+		with_biomes.insert("biomes".to_owned(), compound!(("palette", Tag::List(ListTag::String(vec!["minecraft:plains".to_owned()])))));
+		let mut chunk = Chunk::new(Map::from_iter([
+			("sections".to_owned(), Tag::List(ListTag::Compound(vec![with_biomes, section(1)]))),
+		]));
+		let pos = BlockPos::new(5, 9, 14);
+		assert_eq!(chunk.biome_at(pos)?.as_deref(), Some("minecraft:plains"));
+		assert_eq!(chunk.biome_at(BlockPos::new(5, 16, 14))?, None);
+		assert_eq!(chunk.set_biome(pos, "minecraft:desert")?.as_deref(), Some("minecraft:plains"));
+		assert_eq!(chunk.set_biome(BlockPos::new(5, 16, 14), "minecraft:desert")?, None);
+		assert_eq!(chunk.biome_at(BlockPos::new(7, 11, 12))?.as_deref(), Some("minecraft:desert"));
+		assert_eq!(chunk.biome_at(BlockPos::new(8, 11, 12))?.as_deref(), Some("minecraft:plains"));
+		// Two biomes take a single bit per cell, so the 64 cells fit in one long.
+		let Some(Tag::Compound(biomes)) = chunk.section(0).and_then(|section| section.get("biomes")) else {
+			panic!("Section has no biomes.");
+		};
+		assert_eq!(biomes.get("data"), Some(&Tag::LongArray(vec![1 << pos.biome_index()])));
+
+		// Five biomes take 3 bits per cell, which is 4 longs, the same as 4 bits.
+		let names = ["minecraft:plains", "minecraft:desert", "minecraft:forest", "minecraft:taiga", "minecraft:swamp"];
+		let cells: Vec<BlockPos> = (0..4).flat_map(|y| (0..4).flat_map(move |z| (0..4).map(move |x| BlockPos::new(x * 4, y * 4, z * 4)))).collect();
+		for (i, cell) in cells.iter().enumerate() {
+			chunk.set_biome(*cell, names[i % names.len()])?;
+		}
+		for (i, cell) in cells.iter().enumerate() {
+			assert_eq!(chunk.biome_at(*cell)?.as_deref(), Some(names[i % names.len()]));
+		}
+		assert_eq!(chunk.biomes(0)?.map(|biomes| biomes.pack(1).1.len()), Some(4));
+		Ok(())
+	}
+
+	#[test]
+	fn structure_test() {
+		let piece = |id: &str, bounds: [i32; 6]| Map::from_iter([
+			("id".to_owned(), Tag::from(id)),
+			("BB".to_owned(), Tag::IntArray(bounds.to_vec())),
+		]);
+		let village = compound!(
+			("id", "minecraft:village_plains"),
+			("ChunkX", 2),
+			("ChunkZ", -1),
+			("references", 1),
+			("Children", Tag::List(ListTag::Compound(vec![
+				piece("minecraft:jigsaw", [32, 60, -16, 40, 70, -8]),
+				piece("minecraft:jigsaw", [28, 64, -20, 34, 72, -12]),
+			])))
+		);
+		let chunk = Chunk::new(Map::from_iter([("structures".to_owned(), compound!(
+			("References", compound!(("minecraft:village_plains", Tag::LongArray(vec![ChunkPos::new(2, -1).as_long()])))),
+			("starts", compound!(("minecraft:village_plains", village), ("minecraft:mineshaft", compound!(("id", "INVALID")))))
+		))]));
+		let references = chunk.structure_references();
+		assert_eq!(references.get("minecraft:village_plains").map(Vec::as_slice), Some([ChunkPos::new(2, -1)].as_slice()));
+		let starts = chunk.structure_starts();
+		assert_eq!(starts.len(), 1);
+		let start = &starts["minecraft:village_plains"];
+		assert_eq!((start.chunk, start.references, start.pieces.len()), (ChunkPos::new(2, -1), 1, 2));
+		let bounds = start.bounds().expect("Start has pieces.");
+		assert_eq!(bounds.to_array(), [28, 60, -20, 40, 72, -8]);
+		assert!(bounds.contains(BlockPos::new(28, 72, -8)) && !bounds.contains(BlockPos::new(27, 72, -8)));
+	}
+
+	#[test]
+	fn tick_test() -> Result<(), NbtError> {
+		let tick = |id: &str, x: i32, z: i32| ScheduledTick { id: id.to_owned(), pos: BlockPos::new(x, 64, z), delay: 2, priority: 0 };
+		let mut chunk = Chunk::new(Map::from_iter([
+			("xPos".to_owned(), Tag::Int(1)),
+			("zPos".to_owned(), Tag::Int(0)),
+			("sections".to_owned(), Tag::List(ListTag::Compound(vec![section(4)]))),
+		]));
+		chunk.set_ticks(TickKind::Block, &[tick("minecraft:repeater", 16, 0), tick("minecraft:sand", 17, 0), tick("minecraft:sand", 0, 0)]);
+		chunk.set_ticks(TickKind::Fluid, &[tick("minecraft:water", 17, 0)]);
+		assert_eq!(chunk.ticks(TickKind::Block)[1], tick("minecraft:sand", 17, 0));
+
+		assert_eq!(chunk.purge_foreign_ticks(), Some(1));
+		chunk.set_block(BlockPos::new(17, 64, 0), BlockState::air())?;
+		assert_eq!(chunk.ticks(TickKind::Block), [tick("minecraft:repeater", 16, 0)]);
+		assert!(chunk.ticks(TickKind::Fluid).is_empty());
+
+		chunk.rebase_ticks(ChunkPos::new(1, 0), ChunkPos::new(-2, 3));
+		assert_eq!(chunk.ticks(TickKind::Block), [tick("minecraft:repeater", -32, 48)]);
+		Ok(())
+	}
+
+	#[test]
+	fn world_block_test() -> Result<(), NbtError> {
+		let path = temp_world("blocks");
+		let world = World::open(&path)?;
+		let pos = BlockPos::new(-5, 70, 33);
+		assert_eq!(world.get_block(OVERWORLD, pos)?, None);
+
+		let chest = Map::from_iter([
+			("id".to_owned(), Tag::from("minecraft:chest")),
+			("x".to_owned(), Tag::Int(-5)),
+			("y".to_owned(), Tag::Int(70)),
+			("z".to_owned(), Tag::Int(33)),
+		]);
+		let chunk = Chunk::new(Map::from_iter([
+			("xPos".to_owned(), Tag::Int(-1)),
+			("zPos".to_owned(), Tag::Int(2)),
+			("isLightOn".to_owned(), Tag::Byte(1)),
+			("Heightmaps".to_owned(), Tag::Compound(Map::default())),
+			("sections".to_owned(), Tag::List(ListTag::Compound(vec![section(3), section(4)]))),
+			("block_entities".to_owned(), Tag::List(ListTag::Compound(vec![chest]))),
+		]));
+		// Chunk-local positions find the block entity too.
+		let stone = BlockState::new("minecraft:stone");
+		let mut local = chunk.clone();
+		assert_eq!(local.set_block(BlockPos::new(11, 70, 1), BlockState::air())?, Some(stone.clone()));
+		assert_eq!(local.data().get("block_entities"), Some(&Tag::List(ListTag::Compound(Vec::new()))));
+		world.save_chunk(OVERWORLD, pos.chunk(), &chunk)?;
+		assert_eq!(world.get_block(OVERWORLD, pos)?, Some(stone.clone()));
+		assert_eq!(world.get_block(OVERWORLD, BlockPos::new(-5, 100, 33))?, None);
+
+		let log = BlockState::new("minecraft:oak_log").with("axis", "x");
+		assert_eq!(world.set_block(OVERWORLD, pos, log.clone())?, Some(stone.clone()));
+		assert_eq!(world.set_block(OVERWORLD, BlockPos::new(-5, 100, 33), log.clone())?, None);
+		assert_eq!(world.get_block(OVERWORLD, pos)?, Some(log));
+		assert_eq!(world.get_block(OVERWORLD, BlockPos::new(-4, 70, 33))?, Some(stone));
+
+		let chunk = world.chunk(OVERWORLD, pos.chunk())?.expect("Chunk was saved.");
+		assert_eq!(chunk.section_ys(), [3, 4]);
+		assert_eq!(chunk.data().get("isLightOn"), Some(&Tag::Byte(0)));
+		assert!(!chunk.data().contains_key("Heightmaps"));
+		assert_eq!(chunk.data().get("block_entities"), Some(&Tag::List(ListTag::Compound(Vec::new()))));
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,454 @@
+#![doc = r#"
+Files in the `.minecraft` folder that belong to the game client rather than to a world.
+
+[Hotbars] wraps `hotbar.nbt`, which holds the nine saved creative mode hotbars as uncompressed NBT.
+[ServerList] reads and edits the multiplayer server list in `servers.dat`, which is also uncompressed.
+[Options] reads and edits `options.txt`, whose lines are `key:value` pairs. Values are written in a mix of
+formats (`true`, `0.5`, `["vanilla"]`, `key.mouse.left`), and [Options::value] reads them leniently as SNBT.
+
+```
+# use rustnbt::{ tag::Tag, client::Options };
+let mut options: Options = "fov:0.25\nlang:en_us\nresourcePacks:[\"vanilla\"]".parse()?;
+assert_eq!(options.value("fov"), Some(Tag::Double(0.25)));
+assert_eq!(options.value("lang"), Some(Tag::from("en_us")));
+options.set("lang", "de_de");
+assert_eq!(options.to_string(), "fov:0.25\nlang:de_de\nresourcePacks:[\"vanilla\"]\n");
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	io::*,
+	tag::{
+		Tag,
+		ListTag,
+		NamedTag,
+	},
+};
+use std::fs::File;
+use std::io::{ BufReader, BufWriter };
+use std::path::Path;
+
+/// The number of saved hotbars.
+pub const HOTBAR_COUNT: usize = 9;
+
+/// The number of slots in a hotbar.
+pub const HOTBAR_SIZE: usize = 9;
+
+/// Wrapper around the root compound of `hotbar.nbt`, which stores each hotbar as a List of
+/// items under its index (`"0"` to `"8"`).<br>
+/// The wrapper only interprets the keys that its accessors touch, so any other
+/// data in the compound is left untouched.
+#[derive(Clone, Debug, Default)]
+pub struct Hotbars {
+	data: Map,
+}
+
+impl Hotbars {
+	/// Wraps the root compound of `hotbar.nbt`.
+	pub fn new(data: Map) -> Self {
+		Self { data }
+	}
+
+	/// Reads `hotbar.nbt`, which is not compressed.
+	pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, NbtError> {
+		let root: NamedTag = BufReader::new(File::open(path)?).read_nbt()?;
+		match root.take_tag() {
+			Tag::Compound(data) => Ok(Self::new(data)),
+			_ => Err(crate::invalid_data("Hotbar file root is not a Compound.")),
+		}
+	}
+
+	/// Writes `hotbar.nbt`, without compression.
+	pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<usize, NbtError> {
+		let mut writer = BufWriter::new(File::create(path)?);
+		writer.write_nbt(&NamedTag::new(Tag::Compound(self.data.clone())))
+	}
+
+	/// Immutably borrow the underlying compound.
+	pub fn data(&self) -> &Map {
+		&self.data
+	}
+
+	/// Mutably borrow the underlying compound.
+	pub fn data_mut(&mut self) -> &mut Map {
+		&mut self.data
+	}
+
+	/// Irreversibly take the underlying compound.
+	pub fn take_data(self) -> Map {
+		self.data
+	}
+
+	/// The `DataVersion` of the game that saved the file.
+	pub fn data_version(&self) -> Option<i32> {
+		match self.data.get("DataVersion") {
+			Some(Tag::Int(version)) => Some(*version),
+			_ => None,
+		}
+	}
+
+	/// The items of a hotbar, including empty slots. Returns an empty slice if the hotbar has not been saved.
+	pub fn hotbar(&self, index: usize) -> &[Map] {
+		match self.data.get(&index.to_string()) {
+			Some(Tag::List(ListTag::Compound(items))) => items,
+			_ => &[],
+		}
+	}
+
+	/// The item in a slot of a hotbar, or [None] if the slot is empty.
+	/// Empty slots are stored as an empty compound, or as air in older versions.
+	pub fn item(&self, index: usize, slot: usize) -> Option<&Map> {
+		self.hotbar(index).get(slot).filter(|item| !is_empty_item(item))
+	}
+
+	/// Puts an item into a slot of a hotbar, or empties it with [None], and returns the item that was there.<br>
+	/// A hotbar that has not been saved is created with [HOTBAR_SIZE] empty slots.
+	pub fn set_item(&mut self, index: usize, slot: usize, item: Option<Map>) -> Option<Map> {
+		let items = self.data.entry(index.to_string()).or_insert_with(|| Tag::List(ListTag::Compound(Vec::new())));
+		if !matches!(items, Tag::List(ListTag::Compound(_))) {
+			*items = Tag::List(ListTag::Compound(Vec::new()));
+		}
+		let Tag::List(ListTag::Compound(items)) = items else {
+			unreachable!("The hotbar was just made a List of Compounds.");
+		};
+		if items.len() < HOTBAR_SIZE.max(slot + 1) {
+			items.resize_with(HOTBAR_SIZE.max(slot + 1), Map::default);
+		}
+		let old = std::mem::replace(&mut items[slot], item.unwrap_or_default());
+		Some(old).filter(|old| !is_empty_item(old))
+	}
+}
+
+fn is_empty_item(item: &Map) -> bool {
+	match (item.get("id"), item.get("Count").or_else(|| item.get("count"))) {
+		(None, _) => true,
+		(Some(Tag::String(id)), _) if id == "minecraft:air" => true,
+		(_, Some(Tag::Byte(count))) => *count <= 0,
+		(_, Some(Tag::Int(count))) => *count <= 0,
+		_ => false,
+	}
+}
+
+/// A server in the multiplayer server list.<br>
+/// The wrapper only interprets the keys that its accessors touch, so any other
+/// data in the compound is left untouched.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Server {
+	data: Map,
+}
+
+impl Server {
+	/// A server with a name and address, such as `mc.example.com:25566`.
+	pub fn new<N: Into<String>, A: Into<String>>(name: N, ip: A) -> Self {
+		Self::from_map(Map::from_iter([
+			("name".to_owned(), Tag::String(name.into())),
+			("ip".to_owned(), Tag::String(ip.into())),
+		]))
+	}
+
+	/// Wraps an entry of the `servers` list.
+	pub fn from_map(data: Map) -> Self {
+		Self { data }
+	}
+
+	/// Immutably borrow the underlying compound.
+	pub fn data(&self) -> &Map {
+		&self.data
+	}
+
+	/// Mutably borrow the underlying compound.
+	pub fn data_mut(&mut self) -> &mut Map {
+		&mut self.data
+	}
+
+	/// Irreversibly take the underlying compound.
+	pub fn take_data(self) -> Map {
+		self.data
+	}
+
+	fn string(&self, key: &str) -> Option<&str> {
+		match self.data.get(key) {
+			Some(Tag::String(value)) => Some(value),
+			_ => None,
+		}
+	}
+
+	/// The name shown in the server list.
+	pub fn name(&self) -> Option<&str> {
+		self.string("name")
+	}
+
+	/// Sets the name shown in the server list.
+	pub fn set_name<S: Into<String>>(&mut self, name: S) {
+		self.data.insert("name".to_owned(), Tag::String(name.into()));
+	}
+
+	/// The address of the server (`ip`), which may include a port.
+	pub fn ip(&self) -> Option<&str> {
+		self.string("ip")
+	}
+
+	/// Sets the address of the server.
+	pub fn set_ip<S: Into<String>>(&mut self, ip: S) {
+		self.data.insert("ip".to_owned(), Tag::String(ip.into()));
+	}
+
+	/// Whether the server's resource pack is accepted (`acceptTextures`).
+	/// [None] means that the player is asked each time.
+	pub fn accept_textures(&self) -> Option<bool> {
+		match self.data.get("acceptTextures") {
+			Some(Tag::Byte(accept)) => Some(*accept != 0),
+			_ => None,
+		}
+	}
+
+	/// Sets whether the server's resource pack is accepted, or removes the choice with [None].
+	pub fn set_accept_textures(&mut self, accept: Option<bool>) {
+		match accept {
+			Some(accept) => { self.data.insert("acceptTextures".to_owned(), Tag::Byte(accept as i8)); }
+			None => self.data.retain(|key, _| key != "acceptTextures"),
+		}
+	}
+
+	/// The server's icon as base64 encoded PNG data, which is how it is stored.
+	pub fn icon(&self) -> Option<&str> {
+		self.string("icon")
+	}
+
+	/// The server's icon as PNG data.
+	pub fn icon_png(&self) -> Result<Option<Vec<u8>>, NbtError> {
+		self.icon().map(crate::base64::decode).transpose()
+	}
+
+	/// Sets the server's icon from PNG data, which should be a 64x64 image, or removes it with [None].
+	/// The game replaces the icon with the one that the server sends when the list is refreshed.
+	pub fn set_icon_png(&mut self, png: Option<&[u8]>) {
+		match png {
+			Some(png) => { self.data.insert("icon".to_owned(), Tag::String(crate::base64::encode(png))); }
+			None => self.data.retain(|key, _| key != "icon"),
+		}
+	}
+}
+
+/// The multiplayer server list in `servers.dat`, in the order it is shown.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ServerList {
+	servers: Vec<Server>,
+}
+
+impl ServerList {
+	/// Reads `servers.dat`, which is not compressed.
+	pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, NbtError> {
+		let root: NamedTag = BufReader::new(File::open(path)?).read_nbt()?;
+		let servers = match root.tag() {
+			Tag::Compound(root) => match root.get("servers") {
+				Some(Tag::List(ListTag::Compound(servers))) => servers.iter().cloned().map(Server::from_map).collect(),
+				Some(Tag::List(ListTag::Empty)) | None => Vec::new(),
+				Some(_) => return Err(crate::invalid_data("Server list is not a List of Compounds.")),
+			},
+			_ => return Err(crate::invalid_data("Server list root is not a Compound.")),
+		};
+		Ok(Self { servers })
+	}
+
+	/// Writes `servers.dat`, without compression.
+	pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<usize, NbtError> {
+		let servers = self.servers.iter().map(|server| server.data.clone()).collect();
+		let root = Map::from_iter([("servers".to_owned(), Tag::List(ListTag::Compound(servers)))]);
+		BufWriter::new(File::create(path)?).write_nbt(&NamedTag::new(Tag::Compound(root)))
+	}
+
+	/// The servers, in order.
+	pub fn servers(&self) -> &[Server] {
+		&self.servers
+	}
+
+	/// Mutably borrow the servers.
+	pub fn servers_mut(&mut self) -> &mut Vec<Server> {
+		&mut self.servers
+	}
+
+	/// The number of servers.
+	pub fn len(&self) -> usize {
+		self.servers.len()
+	}
+
+	/// Returns true if there are no servers.
+	pub fn is_empty(&self) -> bool {
+		self.servers.is_empty()
+	}
+
+	/// The index of the first server with the address.
+	pub fn position(&self, ip: &str) -> Option<usize> {
+		self.servers.iter().position(|server| server.ip() == Some(ip))
+	}
+
+	/// Adds a server to the end of the list.
+	pub fn add(&mut self, server: Server) {
+		self.servers.push(server);
+	}
+
+	/// Removes the server at an index, returning it, or [None] if the index is out of bounds.
+	pub fn remove(&mut self, index: usize) -> Option<Server> {
+		(index < self.servers.len()).then(|| self.servers.remove(index))
+	}
+
+	/// Moves the server at `from` to `to`, shifting the servers in between.
+	/// Returns false if either index is out of bounds.
+	pub fn move_server(&mut self, from: usize, to: usize) -> bool {
+		if from >= self.servers.len() || to >= self.servers.len() {
+			return false;
+		}
+		let server = self.servers.remove(from);
+		self.servers.insert(to, server);
+		true
+	}
+}
+
+/// The lines of `options.txt`, in order.<br>
+/// Values are kept as the text that follows the first `:` of each line, so settings that this crate
+/// does not understand are written back unchanged.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Options {
+	entries: Vec<(String, String)>,
+}
+
+impl Options {
+	/// Reads `options.txt`.
+	pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, NbtError> {
+		std::fs::read_to_string(path)?.parse()
+	}
+
+	/// Writes `options.txt`.
+	pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), NbtError> {
+		std::fs::write(path, self.to_string())?;
+		Ok(())
+	}
+
+	/// Iterates over the keys and raw values.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.entries.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+	}
+
+	/// The raw value of a key.
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.entries.iter().find(|(entry, _)| entry == key).map(|(_, value)| value.as_str())
+	}
+
+	/// Sets the raw value of a key, adding the key at the end if it is new.
+	pub fn set<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+		let (key, value) = (key.into(), value.into());
+		match self.entries.iter_mut().find(|(entry, _)| *entry == key) {
+			Some((_, old)) => *old = value,
+			None => self.entries.push((key, value)),
+		}
+	}
+
+	/// Removes a key, returning its raw value.
+	pub fn remove(&mut self, key: &str) -> Option<String> {
+		let index = self.entries.iter().position(|(entry, _)| entry == key)?;
+		Some(self.entries.remove(index).1)
+	}
+
+	/// The value of a key, parsed as SNBT. `true` and `false` become Bytes, `0.5` a Double, and `["a"]` a List.
+	/// A value that is not valid SNBT, such as an empty value, is returned as a String.
+	pub fn value(&self, key: &str) -> Option<Tag> {
+		let value = self.get(key)?;
+		Some(Tag::parse(value).unwrap_or_else(|_| Tag::String(value.to_owned())))
+	}
+}
+
+/// Parses `options.txt`. Lines without a `:` are skipped, since the game ignores them.
+impl std::str::FromStr for Options {
+	type Err = NbtError;
+
+	fn from_str(text: &str) -> Result<Self, NbtError> {
+		let entries = text.lines()
+			.filter_map(|line| line.split_once(':'))
+			.map(|(key, value)| (key.to_owned(), value.to_owned()))
+			.collect();
+		Ok(Self { entries })
+	}
+}
+
+/// Writes the lines of `options.txt`, each ending with a newline.
+impl std::fmt::Display for Options {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.entries.iter().try_for_each(|(key, value)| writeln!(f, "{key}:{value}"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::world::tests::temp_world;
+
+	#[test]
+	fn hotbar_test() -> Result<(), NbtError> {
+		let path = temp_world("hotbar");
+		let stone = Map::from_iter([("id".to_owned(), Tag::from("minecraft:stone")), ("count".to_owned(), Tag::Int(64))]);
+		let mut hotbars = Hotbars::new(Map::from_iter([("DataVersion".to_owned(), Tag::Int(3700))]));
+		assert_eq!(hotbars.set_item(2, 4, Some(stone.clone())), None);
+		assert_eq!(hotbars.hotbar(2).len(), HOTBAR_SIZE);
+		hotbars.write(path.join("hotbar.nbt"))?;
+
+		let mut hotbars = Hotbars::read(path.join("hotbar.nbt"))?;
+		assert_eq!(hotbars.data_version(), Some(3700));
+		assert_eq!(hotbars.item(2, 4), Some(&stone));
+		assert_eq!(hotbars.item(2, 3), None);
+		assert!(hotbars.hotbar(0).is_empty());
+		assert_eq!(hotbars.set_item(2, 4, None), Some(stone));
+		let air = Map::from_iter([("id".to_owned(), Tag::from("minecraft:air")), ("Count".to_owned(), Tag::Byte(0))]);
+		hotbars.set_item(2, 5, Some(air));
+		assert_eq!(hotbars.item(2, 5), None);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+
+	#[test]
+	fn server_list_test() -> Result<(), NbtError> {
+		let path = temp_world("servers");
+		let mut servers = ServerList::default();
+		let mut local = Server::new("Local", "localhost:25566");
+		local.set_accept_textures(Some(true));
+		local.set_icon_png(Some(b"\x89PNG"));
+		servers.add(Server::new("Hub", "hub.example.com"));
+		servers.add(local.clone());
+		servers.add(Server::new("Old", "old.example.com"));
+		assert!(servers.move_server(1, 0));
+		assert!(!servers.move_server(3, 0));
+		assert_eq!(servers.remove(2).and_then(|server| server.name().map(str::to_owned)).as_deref(), Some("Old"));
+		servers.write(path.join("servers.dat"))?;
+
+		let servers = ServerList::read(path.join("servers.dat"))?;
+		assert_eq!(servers.len(), 2);
+		assert_eq!(servers.servers()[0], local);
+		assert_eq!(servers.position("hub.example.com"), Some(1));
+		assert_eq!(servers.servers()[0].icon(), Some("iVBORw=="));
+		assert_eq!(servers.servers()[0].icon_png()?.as_deref(), Some(&b"\x89PNG"[..]));
+		assert_eq!(servers.servers()[0].accept_textures(), Some(true));
+		assert_eq!(servers.servers()[1].accept_textures(), None);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+
+	#[test]
+	fn options_test() -> Result<(), NbtError> {
+		let text = "version:3700\nautoJump:false\nlastServer:\nkey_key.attack:key.mouse.left\nresourcePacks:[\"vanilla\",\"file/pack.zip\"]\nbroken line\n";
+		let mut options: Options = text.parse()?;
+		assert_eq!(options.value("version"), Some(Tag::Int(3700)));
+		assert_eq!(options.value("autoJump"), Some(Tag::Byte(0)));
+		assert_eq!(options.value("lastServer"), Some(Tag::from("")));
+		assert_eq!(options.value("key_key.attack"), Some(Tag::from("key.mouse.left")));
+		assert_eq!(options.value("resourcePacks"), Some(Tag::List(ListTag::String(vec!["vanilla".to_owned(), "file/pack.zip".to_owned()]))));
+		assert_eq!(options.value("missing"), None);
+		options.set("autoJump", "true");
+		options.set("fov", "0.5");
+		assert_eq!(options.remove("lastServer").as_deref(), Some(""));
+		assert_eq!(options.to_string(), "version:3700\nautoJump:true\nkey_key.attack:key.mouse.left\nresourcePacks:[\"vanilla\",\"file/pack.zip\"]\nfov:0.5\n");
+		Ok(())
+	}
+}
@@ -0,0 +1,127 @@
+#![doc = r#"
+Reading and writing the snippets that NBT editors put on the clipboard when copying a node.
+
+Editors copy nodes either as text or as a small binary blob:
+- Text snippets are SNBT. When a Compound entry is copied, the snippet may include its key, as in `Health: 20f`.
+- Binary snippets are a named tag (type ID, name, and payload), as at the root of an NBT file.
+  NBTExplorer copies nodes this way. Some tools compress the blob with gzip or zlib.
+
+[read] accepts any of these, so an editor can paste whatever is on the clipboard.
+
+```
+# use rustnbt::{ clipboard, tag::{ Tag, NamedTag } };
+let node = NamedTag::with_name("Health", Tag::Float(20.0));
+assert_eq!(clipboard::to_snbt(&node), "Health:20f");
+assert_eq!(clipboard::read(clipboard::to_snbt(&node).as_bytes()).unwrap(), node);
+assert_eq!(clipboard::read(&clipboard::to_binary(&node)).unwrap(), node);
+```
+"#]
+
+use crate::{
+	NbtError,
+	invalid_data,
+	io::*,
+	snbt,
+	tag::{
+		Tag,
+		NamedTag,
+	},
+};
+use std::io::Read;
+
+/// Writes a node as an SNBT snippet. Named nodes are written as a Compound entry without the braces, as in `Health:20f`.
+pub fn to_snbt(node: &NamedTag) -> String {
+	if node.name().is_empty() {
+		return snbt::to_string(node.tag());
+	}
+	let entry = snbt::to_string(&Tag::compound([(node.name(), node.tag().clone())]));
+	entry[1..entry.len() - 1].to_owned()
+}
+
+/// Reads an SNBT snippet, which may be a plain value or a single Compound entry without the braces.
+/// Plain values are given an empty name.
+pub fn from_snbt(text: &str) -> Result<NamedTag, NbtError> {
+	let text = text.trim();
+	match Tag::parse(format!("{{{text}}}")) {
+		Ok(Tag::Compound(map)) if map.len() == 1 => Ok(map.into_iter().next().expect("Map has one entry.").into()),
+		_ => Ok(NamedTag::new(Tag::parse(text)?)),
+	}
+}
+
+/// Writes a node as an uncompressed binary snippet.
+pub fn to_binary(node: &NamedTag) -> Vec<u8> {
+	let mut bytes = Vec::with_capacity(node.nbt_size());
+	bytes.write_nbt(node).expect("Writing to a Vec does not fail.");
+	bytes
+}
+
+/// Reads a binary snippet, which may be compressed with gzip or zlib.
+pub fn from_binary(bytes: &[u8]) -> Result<NamedTag, NbtError> {
+	let node = match bytes {
+		[0x1F, 0x8B, ..] => flate2::read::GzDecoder::new(bytes).read_nbt()?,
+		[0x78, ..] => flate2::read::ZlibDecoder::new(bytes).read_nbt()?,
+		mut bytes => {
+			let node = bytes.read_nbt()?;
+			if !bytes.is_empty() {
+				return Err(invalid_data("Binary snippet has trailing bytes."));
+			}
+			node
+		}
+	};
+	Ok(node)
+}
+
+/// Reads a snippet in any of the supported formats.
+/// Data is read as binary if it starts with a tag type ID or a compression header, and as SNBT otherwise,
+/// or if reading it as binary fails.
+pub fn read(data: &[u8]) -> Result<NamedTag, NbtError> {
+	let binary = matches!(data.first(), Some(1..=12 | 0x1F | 0x78));
+	match (binary.then(|| from_binary(data)), std::str::from_utf8(data)) {
+		(Some(Ok(node)), _) => Ok(node),
+		(_, Ok(text)) => from_snbt(text),
+		(Some(Err(err)), Err(_)) => Err(err),
+		(None, Err(_)) => Err(invalid_data("Clipboard data is neither NBT nor UTF-8 text.")),
+	}
+}
+
+/// Reads a snippet in any of the supported formats from a reader. See [read].
+pub fn read_from<R: Read>(reader: &mut R) -> Result<NamedTag, NbtError> {
+	let mut data = Vec::new();
+	reader.read_to_end(&mut data)?;
+	read(&data)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+	use std::io::Write;
+
+	#[test]
+	fn snbt_test() -> Result<(), NbtError> {
+		let node = NamedTag::with_name("Items", list!("a", "b"));
+		assert_eq!(to_snbt(&node), r#"Items:["a","b"]"#);
+		assert_eq!(from_snbt(&to_snbt(&node))?, node);
+		assert_eq!(from_snbt(r#""odd key" : 5b"#)?, NamedTag::with_name("odd key", Tag::Byte(5)));
+		assert_eq!(from_snbt("{a: 1}")?, NamedTag::new(compound!(("a", 1))));
+		assert_eq!(to_snbt(&NamedTag::new(Tag::Int(3))), "3");
+		assert!(from_snbt("a: 1, b: 2").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn binary_test() -> Result<(), NbtError> {
+		let node = NamedTag::with_name("Pos", Tag::IntArray(vec![1, 2, 3]));
+		let binary = to_binary(&node);
+		assert_eq!(read(&binary)?, node);
+		let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+		gzip.write_all(&binary)?;
+		assert_eq!(read(&gzip.finish()?)?, node);
+		let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+		zlib.write_all(&binary)?;
+		assert_eq!(read_from(&mut zlib.finish()?.as_slice())?, node);
+		assert!(from_binary(&[binary.as_slice(), &[0]].concat()).is_err());
+		assert!(read(&[0xFF, 0xFE]).is_err());
+		Ok(())
+	}
+}
@@ -0,0 +1,374 @@
+#![doc = r#"
+Columnar export of values selected from many chunks, for analysis in tools such as pandas, DataFusion, or DuckDB.
+
+Each column is a [TagPath] that is looked up in every chunk, and each chunk becomes one CSV row that starts with
+the `chunk_x` and `chunk_z` coordinates of the chunk. Missing values are left empty.
+
+| Value | Cell |
+|-------|------|
+| Numbers | the number, such as `-4` or `0.5` |
+| String | the string |
+| ByteArray, IntArray, LongArray, List | the elements, separated by spaces |
+| Compound | the number of entries |
+
+Cells that contain a comma, a double quote, or a line break are quoted as described by RFC 4180.
+
+```no_run
+# use rustnbt::{ columns::*, world::* };
+let world = World::open("saves/New World").expect("Failed to open world.");
+let columns = Columns::from_paths(&["Status", "InhabitedTime", "sections[0].Y"]).expect("Invalid path.");
+let file = std::fs::File::create("chunks.csv").expect("Failed to create file.");
+let mut csv = CsvWriter::new(file, columns).expect("Failed to write header.");
+csv.write_world(&world, OVERWORLD, RegionKind::Chunks).expect("Failed to export chunks.");
+```
+
+[write_flat] exports a single tag instead, with one row for every value from [Tag::flatten] and `path`, `type`, and
+`value` columns, as CSV or TSV. Arrays are joined into one cell, expanded into a row per element, or truncated
+([ArrayCells]).
+
+```
+# use rustnbt::{ compound, list, columns::* };
+let tag = compound!(("Pos", list!(1.5, 64.0)));
+let mut csv = Vec::new();
+write_flat(&mut csv, &tag, &FlatOptions::csv())?;
+assert_eq!(String::from_utf8(csv).unwrap(), "path,type,value\r\nPos[0],Double,1.5\r\nPos[1],Double,64\r\n");
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	NbtError,
+	path::{ PathError, TagPath },
+	pos::ChunkPos,
+	region::Region,
+	tag::{
+		Tag,
+		ListTag,
+	},
+	world::{ RegionKind, World },
+};
+use std::{
+	borrow::Cow,
+	io::Write,
+};
+
+/// Named paths to select from each chunk.
+#[derive(Clone, Default, Debug)]
+pub struct Columns {
+	columns: Vec<(String, TagPath)>,
+}
+
+impl Columns {
+	/// Creates an empty set of columns.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Parses each path, using the path itself as the column name.
+	pub fn from_paths<S: AsRef<str>>(paths: &[S]) -> Result<Self, PathError> {
+		let mut columns = Self::new();
+		for path in paths {
+			let path = path.as_ref();
+			columns = columns.column(path, path.parse()?);
+		}
+		Ok(columns)
+	}
+
+	/// Returns these columns with another column appended.
+	pub fn column<S: Into<String>>(mut self, name: S, path: TagPath) -> Self {
+		self.columns.push((name.into(), path));
+		self
+	}
+
+	/// The number of columns.
+	pub fn len(&self) -> usize {
+		self.columns.len()
+	}
+
+	/// Returns `true` if there are no columns.
+	pub fn is_empty(&self) -> bool {
+		self.columns.is_empty()
+	}
+
+	/// The names of the columns, in order.
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.columns.iter().map(|(name, _)| name.as_str())
+	}
+
+	/// Looks up every column in `tag`.
+	pub fn select<'a>(&self, tag: &'a Tag) -> Vec<Option<Cow<'a, Tag>>> {
+		self.columns.iter().map(|(_, path)| path.get(tag)).collect()
+	}
+}
+
+/// Writes one CSV row per chunk.
+pub struct CsvWriter<W: Write> {
+	writer: W,
+	columns: Columns,
+}
+
+impl<W: Write> CsvWriter<W> {
+	/// Creates a writer for the given columns and writes the header row.
+	pub fn new(mut writer: W, columns: Columns) -> Result<Self, NbtError> {
+		let header = ["chunk_x", "chunk_z"].into_iter().chain(columns.names()).map(Cow::Borrowed);
+		write_record(&mut writer, header)?;
+		Ok(Self { writer, columns })
+	}
+
+	/// Writes the row for a single chunk.
+	pub fn write_chunk(&mut self, chunk: ChunkPos, tag: &Tag) -> Result<(), NbtError> {
+		let position = [Cow::Owned(chunk.x.to_string()), Cow::Owned(chunk.z.to_string())];
+		let cells = self.columns.select(tag).into_iter()
+			.map(|value| value.map_or(Cow::Borrowed(""), |value| Cow::Owned(cell(&value))));
+		write_record(&mut self.writer, position.into_iter().chain(cells))
+	}
+
+	/// Writes a row for every chunk in a region, returning the number of rows written.
+	pub fn write_region(&mut self, region: &mut Region) -> Result<usize, NbtError> {
+		let chunks: Vec<ChunkPos> = region.chunks().collect();
+		let mut rows = 0;
+		for chunk in chunks {
+			if let Some(data) = region.read_chunk(chunk)? {
+				self.write_chunk(chunk, data.tag())?;
+				rows += 1;
+			}
+		}
+		Ok(rows)
+	}
+
+	/// Writes a row for every chunk in the region files of the given kind in a dimension,
+	/// returning the number of rows written.
+	pub fn write_world(&mut self, world: &World, dimension: &str, kind: RegionKind) -> Result<usize, NbtError> {
+		let mut rows = 0;
+		for region in world.regions(dimension, kind)? {
+			let mut region = Region::open(world.region_path(dimension, kind, region))?;
+			rows += self.write_region(&mut region)?;
+		}
+		Ok(rows)
+	}
+
+	/// Flushes and returns the underlying writer.
+	pub fn into_inner(mut self) -> Result<W, NbtError> {
+		self.writer.flush()?;
+		Ok(self.writer)
+	}
+}
+
+/// How [write_flat] writes the elements of ByteArrays, IntArrays, and LongArrays.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ArrayCells {
+	/// Every element in one cell, separated by spaces.
+	#[default]
+	Join,
+	/// A row for every element, with the index in its path, such as `UUID[0]`.
+	Expand,
+	/// At most this many elements in one cell, followed by `...` if there were more.
+	Truncate(usize),
+}
+
+/// Options for [write_flat].
+#[derive(Clone, Debug)]
+pub struct FlatOptions {
+	/// The character between cells, `,` for CSV and a tab for TSV.
+	pub delimiter: char,
+	pub arrays: ArrayCells,
+	/// Whether to write the `path,type,value` header row.
+	pub header: bool,
+}
+
+impl Default for FlatOptions {
+	fn default() -> Self {
+		Self::csv()
+	}
+}
+
+impl FlatOptions {
+	/// Comma separated cells with a header row.
+	pub fn csv() -> Self {
+		Self { delimiter: ',', arrays: ArrayCells::Join, header: true }
+	}
+
+	/// Tab separated cells with a header row.
+	pub fn tsv() -> Self {
+		Self { delimiter: '\t', ..Self::csv() }
+	}
+
+	/// Returns these options with a different [ArrayCells].
+	pub fn arrays(mut self, arrays: ArrayCells) -> Self {
+		self.arrays = arrays;
+		self
+	}
+
+	/// Returns these options with or without the header row.
+	pub fn header(mut self, header: bool) -> Self {
+		self.header = header;
+		self
+	}
+}
+
+/// Writes a row with the path, type, and value of every value of a tag from [Tag::flatten], returning the number of
+/// rows written, not counting the header. Cells are formatted like those of [CsvWriter], and are quoted when they
+/// contain the delimiter, a double quote, or a line break.
+pub fn write_flat<W: Write>(writer: &mut W, tag: &Tag, options: &FlatOptions) -> Result<usize, NbtError> {
+	let delimiter = options.delimiter;
+	if options.header {
+		write_delimited(writer, delimiter, ["path", "type", "value"].into_iter().map(Cow::Borrowed))?;
+	}
+	let mut rows = 0;
+	for (path, value) in tag.flatten() {
+		let elements = match (&*value, options.arrays) {
+			(Tag::ByteArray(values), ArrayCells::Expand) => Some(values.iter().map(|value| Tag::Byte(*value)).collect::<Vec<_>>()),
+			(Tag::IntArray(values), ArrayCells::Expand) => Some(values.iter().map(|value| Tag::Int(*value)).collect()),
+			(Tag::LongArray(values), ArrayCells::Expand) => Some(values.iter().map(|value| Tag::Long(*value)).collect()),
+			_ => None,
+		};
+		let Some(elements) = elements else {
+			let text = match (&*value, options.arrays) {
+				(Tag::ByteArray(values), ArrayCells::Truncate(limit)) => truncated(values, limit),
+				(Tag::IntArray(values), ArrayCells::Truncate(limit)) => truncated(values, limit),
+				(Tag::LongArray(values), ArrayCells::Truncate(limit)) => truncated(values, limit),
+				(value, _) => cell(value),
+			};
+			let row = [Cow::Owned(path.to_string()), Cow::Borrowed(value.title()), Cow::Owned(text)];
+			write_delimited(writer, delimiter, row.into_iter())?;
+			rows += 1;
+			continue;
+		};
+		for (index, element) in elements.iter().enumerate() {
+			let row = [Cow::Owned(path.clone().index(index).to_string()), Cow::Borrowed(element.title()), Cow::Owned(cell(element))];
+			write_delimited(writer, delimiter, row.into_iter())?;
+			rows += 1;
+		}
+	}
+	Ok(rows)
+}
+
+/// The first `limit` values separated by spaces, followed by `...` if there were more.
+fn truncated<T: ToString>(values: &[T], limit: usize) -> String {
+	let mut text = join(&values[..values.len().min(limit)]);
+	if values.len() > limit {
+		if !text.is_empty() {
+			text.push(' ');
+		}
+		text.push_str("...");
+	}
+	text
+}
+
+fn write_record<'a, W: Write, I: Iterator<Item = Cow<'a, str>>>(writer: &mut W, fields: I) -> Result<(), NbtError> {
+	write_delimited(writer, ',', fields)
+}
+
+fn write_delimited<'a, W: Write, I: Iterator<Item = Cow<'a, str>>>(writer: &mut W, delimiter: char, fields: I) -> Result<(), NbtError> {
+	for (i, field) in fields.enumerate() {
+		if i > 0 {
+			write!(writer, "{delimiter}")?;
+		}
+		if field.contains([delimiter, '"', '\n', '\r']) {
+			write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+		} else {
+			writer.write_all(field.as_bytes())?;
+		}
+	}
+	writer.write_all(b"\r\n")?;
+	Ok(())
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+	values.iter().map(T::to_string).collect::<Vec<_>>().join(" ")
+}
+
+/// Formats a value as the text of a cell.
+fn cell(tag: &Tag) -> String {
+	match tag {
+		Tag::Byte(value) => value.to_string(),
+		Tag::Short(value) => value.to_string(),
+		Tag::Int(value) => value.to_string(),
+		Tag::Long(value) => value.to_string(),
+		Tag::Float(value) => value.to_string(),
+		Tag::Double(value) => value.to_string(),
+		Tag::String(value) => value.clone(),
+		Tag::ByteArray(values) => join(values),
+		Tag::IntArray(values) => join(values),
+		Tag::LongArray(values) => join(values),
+		Tag::Compound(map) => map.len().to_string(),
+		Tag::List(list) => match list {
+			ListTag::Empty => String::new(),
+			ListTag::Byte(values) => join(values),
+			ListTag::Short(values) => join(values),
+			ListTag::Int(values) => join(values),
+			ListTag::Long(values) => join(values),
+			ListTag::Float(values) => join(values),
+			ListTag::Double(values) => join(values),
+			ListTag::String(values) => values.join(" "),
+			ListTag::ByteArray(values) => values.iter().map(|values| join(values)).collect::<Vec<_>>().join(" "),
+			ListTag::IntArray(values) => values.iter().map(|values| join(values)).collect::<Vec<_>>().join(" "),
+			ListTag::LongArray(values) => values.iter().map(|values| join(values)).collect::<Vec<_>>().join(" "),
+			ListTag::List(values) => values.iter().map(|list| cell(&Tag::List(list.clone()))).collect::<Vec<_>>().join(" "),
+			ListTag::Compound(values) => values.iter().map(|map| map.len().to_string()).collect::<Vec<_>>().join(" "),
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn csv_test() -> Result<(), NbtError> {
+		let columns = Columns::from_paths(&["Status", "InhabitedTime", "Heightmaps", "sections[0].Y", "missing"])
+			.map_err(|err| crate::invalid_data(&err.to_string()))?
+			.column("note", TagPath::new().key("note"));
+		let sections = Tag::List(ListTag::Compound(vec![
+			crate::Map::from_iter([("Y".to_owned(), Tag::Byte(-4))]),
+		]));
+		let chunk = compound!(
+			("Status", "minecraft:full"),
+			("InhabitedTime", Tag::Long(1200)),
+			("Heightmaps", compound!(("A", list!(1, 2)), ("B", list!(3)))),
+			("sections", sections),
+			("note", "say \"hi\", then leave")
+		);
+		let mut csv = CsvWriter::new(Vec::new(), columns)?;
+		csv.write_chunk(ChunkPos::new(-1, 2), &chunk)?;
+		csv.write_chunk(ChunkPos::new(0, 0), &compound!(("InhabitedTime", Tag::Long(5))))?;
+		let text = String::from_utf8(csv.into_inner()?)?;
+		assert_eq!(text, concat!(
+			"chunk_x,chunk_z,Status,InhabitedTime,Heightmaps,sections[0].Y,missing,note\r\n",
+			"-1,2,minecraft:full,1200,2,-4,,\"say \"\"hi\"\", then leave\"\r\n",
+			"0,0,,5,,,,\r\n",
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn cell_test() {
+		assert_eq!(cell(&Tag::Float(0.5)), "0.5");
+		assert_eq!(cell(&Tag::IntArray(vec![1, -2])), "1 -2");
+		assert_eq!(cell(&list!("a", "b")), "a b");
+		assert_eq!(cell(&list!()), "");
+	}
+
+	#[test]
+	fn flat_test() -> Result<(), NbtError> {
+		let tag = compound!(("UUID", Tag::IntArray(vec![1, 2, 3, 4])), ("Name", "a\tb, c"));
+		let export = |options: FlatOptions| -> Result<String, NbtError> {
+			let mut output = Vec::new();
+			write_flat(&mut output, &tag, &options.header(false))?;
+			let mut rows: Vec<String> = String::from_utf8(output)?.split_terminator("\r\n").map(str::to_owned).collect();
+			rows.sort();
+			Ok(rows.join("|"))
+		};
+		assert_eq!(export(FlatOptions::csv())?, "Name,String,\"a\tb, c\"|UUID,IntArray,1 2 3 4");
+		assert_eq!(export(FlatOptions::tsv())?, "Name\tString\t\"a\tb, c\"|UUID\tIntArray\t1 2 3 4");
+		assert_eq!(export(FlatOptions::csv().arrays(ArrayCells::Truncate(2)))?, "Name,String,\"a\tb, c\"|UUID,IntArray,1 2 ...");
+		assert_eq!(export(FlatOptions::csv().arrays(ArrayCells::Truncate(0)))?, "Name,String,\"a\tb, c\"|UUID,IntArray,...");
+		assert_eq!(
+			export(FlatOptions::csv().arrays(ArrayCells::Expand))?,
+			"Name,String,\"a\tb, c\"|UUID[0],Int,1|UUID[1],Int,2|UUID[2],Int,3|UUID[3],Int,4",
+		);
+		assert_eq!(write_flat(&mut Vec::new(), &tag, &FlatOptions::csv().arrays(ArrayCells::Expand))?, 5);
+		Ok(())
+	}
+}
@@ -0,0 +1,225 @@
+#![doc = r#"
+A corpus of NBT from several versions of the game, and checks that a reader understands all of it.
+
+[CORPUS] holds a [Sample] of each kind of file whose layout changed between versions: items with numeric and
+flattened IDs, UUIDs as two longs and as an IntArray, chunks before and after the 1.18 height change, item
+components, and a file of edge cases, such as extreme numbers, empty arrays, and characters outside of the Basic
+Multilingual Plane. The samples are written by hand in the layout of their version, with arrays shortened, so they
+are small enough to ship in the crate. They are not copies of files made by the game.
+
+[verify] runs a reader, such as a pipeline that decodes NBT through this crate and converts it, over the binary form
+of every sample, and returns a row of the compatibility matrix for each. [check_round_trips] checks this crate
+against files of your own, by reading them and writing them back as binary NBT and SNBT.
+
+```
+# use rustnbt::{ compat, io::* };
+let results = compat::verify(|mut bytes: &[u8]| bytes.read_nbt::<rustnbt::tag::NamedTag>().map(|root| root.take_tag()));
+assert!(results.iter().all(compat::CompatResult::passed));
+assert!(compat::check_round_trips(&compat::CORPUS[0].bytes()).is_empty());
+```
+"#]
+
+use crate::{
+	NbtError,
+	io::*,
+	snbt,
+	testing::differences,
+	tag::{ Tag, NamedTag },
+};
+use std::fmt::Display;
+
+/// A tag in the layout of a version of the game. See the [module](self) documentation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Sample {
+	/// What the sample is, such as `chunk`.
+	pub name: &'static str,
+	/// The version of the game whose layout the sample has.
+	pub version: &'static str,
+	/// The `DataVersion` of that version, for versions that have one.
+	pub data_version: Option<i32>,
+	/// The sample as SNBT.
+	pub snbt: &'static str,
+}
+
+impl Sample {
+	/// The sample as a tag.
+	pub fn tag(&self) -> Tag {
+		Tag::parse(self.snbt).expect("The samples are valid SNBT.")
+	}
+
+	/// The sample as an uncompressed binary file, with an empty root name.
+	pub fn bytes(&self) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&NamedTag::new(self.tag())).expect("Writing to a Vec does not fail.");
+		bytes
+	}
+}
+
+/// The samples of the corpus, oldest first.
+pub const CORPUS: &[Sample] = &[
+	Sample {
+		name: "item with a numeric ID",
+		version: "1.7.10",
+		data_version: None,
+		snbt: r#"{id: 35s, Count: 12b, Damage: 14s, Slot: 0b, tag: {display: {Name: "Red Wool", Lore: ["Soft"]}, ench: [{id: 34s, lvl: 3s}]}}"#,
+	},
+	Sample {
+		name: "player with a UUID in two longs",
+		version: "1.12.2",
+		data_version: Some(1343),
+		snbt: r#"{DataVersion: 1343, Pos: [0.5d, 64d, -12.5d], Rotation: [90f, -15.5f], Health: 20f, foodLevel: 20, OnGround: 1b, Dimension: 0, UUIDMost: 8385736523398612325L, UUIDLeast: -6354787812466702874L, Inventory: [{id: "minecraft:wool", Count: 12b, Damage: 14s, Slot: 0b}, {id: "minecraft:diamond_sword", Count: 1b, Damage: 3s, Slot: 1b, tag: {ench: [{id: 16s, lvl: 5s}]}}]}"#,
+	},
+	Sample {
+		name: "chunk after the flattening",
+		version: "1.13.2",
+		data_version: Some(1631),
+		snbt: r#"{DataVersion: 1631, Level: {xPos: -3, zPos: 7, LastUpdate: 123456L, InhabitedTime: 42L, Status: "postprocessed", Biomes: [I; 1, 1, 4, 4], Sections: [{Y: 0b, Palette: [{Name: "minecraft:bedrock"}, {Name: "minecraft:stone"}, {Name: "minecraft:oak_log", Properties: {axis: "y"}}], BlockStates: [L; 0L, 1229782938247303441L, -8608480567731124088L], BlockLight: [B; 0b, 0b, 0b, 0b], SkyLight: [B; -1b, -1b, -1b, -1b]}], TileEntities: [], Entities: [], Heightmaps: {WORLD_SURFACE: [L; 0L, 0L]}}}"#,
+	},
+	Sample {
+		name: "player with an IntArray UUID",
+		version: "1.16.5",
+		data_version: Some(2586),
+		snbt: r#"{DataVersion: 2586, UUID: [I; 1952476523, -1090171699, -1479573419, 1387924581], Pos: [0.5d, 64d, -12.5d], Dimension: "minecraft:overworld", Attributes: [{Name: "minecraft:generic.max_health", Base: 20d}], Inventory: [{id: "minecraft:white_wool", Count: 12b, Slot: 0b}]}"#,
+	},
+	Sample {
+		name: "chunk with negative sections",
+		version: "1.18.2",
+		data_version: Some(2975),
+		snbt: r#"{DataVersion: 2975, xPos: -3, yPos: -4, zPos: 7, Status: "full", LastUpdate: 123456L, InhabitedTime: 42L, isLightOn: 1b, sections: [{Y: -4b, block_states: {palette: [{Name: "minecraft:bedrock"}, {Name: "minecraft:deepslate", Properties: {axis: "y"}}], data: [L; 1229782938247303441L, 0L]}, biomes: {palette: ["minecraft:plains"]}}, {Y: 19b, block_states: {palette: [{Name: "minecraft:air"}]}, biomes: {palette: ["minecraft:plains"]}}], block_entities: [{id: "minecraft:chest", x: -48, y: -60, z: 112, keepPacked: 0b, Items: []}], Heightmaps: {MOTION_BLOCKING: [L; 0L, 0L]}, structures: {starts: {}, References: {}}}"#,
+	},
+	Sample {
+		name: "level.dat",
+		version: "1.20.1",
+		data_version: Some(3465),
+		snbt: r#"{Data: {DataVersion: 3465, LevelName: "New World", version: 19133, GameType: 0, hardcore: 0b, allowCommands: 1b, Time: 240500L, DayTime: 6000L, WorldGenSettings: {seed: -1234567890123456789L, bonus_chest: 0b, generate_features: 1b, dimensions: {"minecraft:overworld": {type: "minecraft:overworld", generator: {type: "minecraft:noise", settings: "minecraft:overworld", biome_source: {type: "minecraft:multi_noise", preset: "minecraft:overworld"}}}}}, Version: {Id: 3465, Name: "1.20.1", Series: "main", Snapshot: 0b}, GameRules: {doDaylightCycle: "true", keepInventory: "false"}}}"#,
+	},
+	Sample {
+		name: "item with components",
+		version: "1.20.5",
+		data_version: Some(3837),
+		snbt: r#"{id: "minecraft:diamond_sword", count: 1, components: {"minecraft:damage": 5, "minecraft:custom_name": '{"text":"Blade","italic":false}', "minecraft:enchantments": {levels: {"minecraft:sharpness": 5}}, "minecraft:unbreakable": {}}}"#,
+	},
+	Sample {
+		name: "edge cases",
+		version: "any",
+		data_version: None,
+		snbt: r#"{empty: [], lists: [[], [1b], ["text"]], compounds: [{}, {a: {}}], numbers: {byte: -128b, short: 32767s, int: -2147483648, long: 9223372036854775807L, float: 3.4028235E38f, small: 1.4E-45f, double: -4.9E-324d}, arrays: {bytes: [B;], ints: [I;], longs: [L;]}, text: "§6Gold ✓ 𝄞", "key with spaces": "quoted \"text\" and 'quotes'", "": "empty key"}"#,
+	},
+];
+
+/// A row of the compatibility matrix that [verify] returns.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CompatResult {
+	pub sample: &'static str,
+	pub version: &'static str,
+	/// The error of the reader, if it failed.
+	pub error: Option<String>,
+	/// The differences between what the reader returned and the sample, as from
+	/// [differences](crate::testing::differences).
+	pub differences: Vec<String>,
+}
+
+impl CompatResult {
+	/// Whether the reader returned the sample.
+	pub fn passed(&self) -> bool {
+		self.error.is_none() && self.differences.is_empty()
+	}
+}
+
+/// Runs `reader` over the uncompressed binary form of every sample of the [CORPUS], and compares what it returns
+/// to the sample.
+pub fn verify<E: Display, F: FnMut(&[u8]) -> Result<Tag, E>>(mut reader: F) -> Vec<CompatResult> {
+	CORPUS.iter().map(|sample| {
+		let (error, differences) = match reader(&sample.bytes()) {
+			Ok(tag) => (None, differences(&sample.tag(), &tag)),
+			Err(err) => (Some(err.to_string()), Vec::new()),
+		};
+		CompatResult { sample: sample.name, version: sample.version, error, differences }
+	}).collect()
+}
+
+/// Reads uncompressed binary NBT, and describes every way in which writing it back does not give the same data: the
+/// bytes that binary NBT is written to, its [size](NbtSize::nbt_size), and the tag that its SNBT is read back as.
+/// Returns nothing if the round trips are exact.<br>
+/// The bytes are only compared with the `preserve_order` feature, since Compounds are written in another order
+/// without it. Otherwise, the bytes written are read back and compared as tags.
+pub fn check_round_trips(bytes: &[u8]) -> Vec<String> {
+	let mut problems = Vec::new();
+	let root = match (&mut &bytes[..]).read_nbt::<NamedTag>() {
+		Ok(root) => root,
+		Err(err) => return vec![format!("Failed to read: {err}")],
+	};
+	let mut written = Vec::new();
+	if let Err(err) = written.write_nbt(&root) {
+		problems.push(format!("Failed to write: {err}"));
+	} else if written.len() != bytes.len() {
+		problems.push(format!("Binary: {} bytes were written for {} bytes read.", written.len(), bytes.len()));
+	} else if cfg!(feature = "preserve_order") {
+		if let Some(at) = written.iter().zip(bytes).position(|(left, right)| left != right) {
+			problems.push(format!("Binary: the bytes written differ from the bytes read at byte {at}."));
+		}
+	} else {
+		// Without `preserve_order`, the entries of Compounds are written in another order.
+		match (&mut written.as_slice()).read_nbt::<NamedTag>() {
+			Ok(again) => problems.extend(differences(root.tag(), again.tag()).into_iter().map(|difference| format!("Binary: {difference}"))),
+			Err(err) => problems.push(format!("Binary: failed to read back: {err}")),
+		}
+	}
+	let size = root.nbt_size();
+	if size != bytes.len() {
+		problems.push(format!("Size: {size} bytes were counted for {} bytes.", bytes.len()));
+	}
+	match Tag::parse(snbt::to_string(root.tag())) {
+		Ok(tag) => problems.extend(differences(root.tag(), &tag).into_iter().map(|difference| format!("SNBT: {difference}"))),
+		Err(err) => problems.push(format!("SNBT: failed to read back: {err}")),
+	}
+	problems
+}
+
+/// Runs [check_round_trips] over a file, which may be compressed with gzip or zlib.
+pub fn check_file<P: AsRef<std::path::Path>>(path: P) -> Result<Vec<String>, NbtError> {
+	use std::io::Read;
+	let bytes = std::fs::read(path)?;
+	let bytes = match detect_compression(&bytes) {
+		crate::region::ChunkCompression::GZip => {
+			let mut inflated = Vec::new();
+			flate2::read::GzDecoder::new(bytes.as_slice()).read_to_end(&mut inflated)?;
+			inflated
+		}
+		crate::region::ChunkCompression::Zlib => {
+			let mut inflated = Vec::new();
+			flate2::read::ZlibDecoder::new(bytes.as_slice()).read_to_end(&mut inflated)?;
+			inflated
+		}
+		_ => bytes,
+	};
+	Ok(check_round_trips(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn compat_test() {
+		for sample in CORPUS {
+			assert_eq!(check_round_trips(&sample.bytes()), Vec::<String>::new(), "{} ({})", sample.name, sample.version);
+		}
+		// A reader that drops UUIDs fails the samples that have them.
+		let results = verify(|mut bytes: &[u8]| -> Result<Tag, NbtError> {
+			let mut tag = bytes.read_nbt::<NamedTag>()?.take_tag();
+			if let Tag::Compound(map) = &mut tag {
+				map.retain(|key, _| !key.starts_with("UUID"));
+			}
+			Ok(tag)
+		});
+		let failed: Vec<&str> = results.iter().filter(|result| !result.passed()).map(|result| result.version).collect();
+		assert_eq!(failed, ["1.12.2", "1.16.5"]);
+		assert!(results[3].differences[0].starts_with("UUID: only on the left"));
+		let results = verify(|_: &[u8]| Err("unsupported"));
+		assert_eq!(results[0].error.as_deref(), Some("unsupported"));
+
+		// Trailing data is not read, so it is neither written nor counted.
+		assert_eq!(check_round_trips(&[10, 0, 0, 1, 0, 1, b'a', 5, 0, 0]), ["Binary: 9 bytes were written for 10 bytes read.", "Size: 9 bytes were counted for 10 bytes."]);
+	}
+}
@@ -0,0 +1,166 @@
+#![doc = r#"
+Where in a world an error happened.
+
+An error from deep in a scan of a world, such as a palette index outside of the palette, says little on its own. The
+readers of [World](crate::world::World), [Region](crate::region::Region), and [Chunk](crate::chunk::Chunk) wrap
+their errors in [NbtError::Context], with an [ErrorContext] of the dimension, region, chunk, and [TagPath] that they
+were reading. Each layer adds what it knows to the context of the layers below it, so an error is only wrapped once,
+and reads like `dimension minecraft:overworld, region 0 -1, chunk 3 -17, at sections[2].block_states: Palette is
+empty.`
+
+[WithContext] adds context to errors of your own code the same way. [NbtError::root] is the error without its
+context, for matching on what went wrong.
+
+```
+# use rustnbt::{ NbtError, context::*, pos::ChunkPos };
+let result: Result<(), NbtError> = Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+let err = result
+	.with_context(|| ErrorContext::default().chunk(ChunkPos::new(3, -17)))
+	.with_context(|| ErrorContext::default().dimension("minecraft:overworld"))
+	.unwrap_err();
+assert_eq!(err.to_string(), "dimension minecraft:overworld, region 0 -1, chunk 3 -17: entity not found");
+assert!(matches!(err.root(), NbtError::IoError(_)));
+```
+"#]
+
+use crate::{
+	NbtError,
+	path::TagPath,
+	pos::{ ChunkPos, RegionPos },
+};
+use std::fmt::{ self, Display };
+
+/// Where an error happened. Every part is optional, and only the known parts are shown.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct ErrorContext {
+	pub dimension: Option<String>,
+	pub region: Option<RegionPos>,
+	pub chunk: Option<ChunkPos>,
+	/// The path of the value inside of the chunk or file.
+	pub path: Option<TagPath>,
+}
+
+impl ErrorContext {
+	pub fn dimension<S: Into<String>>(mut self, dimension: S) -> Self {
+		self.dimension = Some(dimension.into());
+		self
+	}
+
+	pub fn region(mut self, region: RegionPos) -> Self {
+		self.region = Some(region);
+		self
+	}
+
+	/// Sets the chunk, and the region that contains it if it is not set.
+	pub fn chunk(mut self, chunk: ChunkPos) -> Self {
+		self.chunk = Some(chunk);
+		self.region.get_or_insert(chunk.region());
+		self
+	}
+
+	pub fn path(mut self, path: TagPath) -> Self {
+		self.path = Some(path);
+		self
+	}
+
+	/// Adds the context of an outer layer: its parts fill the ones that are not set, and its path is put in front of
+	/// this path.
+	fn merge(&mut self, outer: ErrorContext) {
+		if self.dimension.is_none() {
+			self.dimension = outer.dimension;
+		}
+		self.region = self.region.or(outer.region);
+		self.chunk = self.chunk.or(outer.chunk);
+		self.path = match (outer.path, self.path.take()) {
+			(Some(outer), Some(inner)) => Some(TagPath::from([outer.segments(), inner.segments()].concat())),
+			(outer, inner) => inner.or(outer),
+		};
+	}
+}
+
+/// Formats the known parts, such as `dimension minecraft:overworld, chunk 3 -17, at Level.Sections[0]`.
+impl Display for ErrorContext {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut parts = Vec::new();
+		if let Some(dimension) = &self.dimension {
+			parts.push(format!("dimension {dimension}"));
+		}
+		if let Some(region) = self.region {
+			parts.push(format!("region {} {}", region.x, region.z));
+		}
+		if let Some(chunk) = self.chunk {
+			parts.push(format!("chunk {} {}", chunk.x, chunk.z));
+		}
+		if let Some(path) = &self.path {
+			parts.push(format!("at {path}"));
+		}
+		f.write_str(&parts.join(", "))
+	}
+}
+
+impl NbtError {
+	/// Wraps this error in [NbtError::Context], or adds to its context if it has one.
+	pub fn with_context(self, context: ErrorContext) -> NbtError {
+		match self {
+			NbtError::Context { context: mut inner, source } => {
+				inner.merge(context);
+				NbtError::Context { context: inner, source }
+			}
+			err => NbtError::Context { context: Box::new(context), source: Box::new(err) },
+		}
+	}
+
+	/// Where this error happened, if a reader added it.
+	pub fn context(&self) -> Option<&ErrorContext> {
+		match self {
+			NbtError::Context { context, .. } => Some(context),
+			_ => None,
+		}
+	}
+
+	/// This error without its context.
+	pub fn root(&self) -> &NbtError {
+		match self {
+			NbtError::Context { source, .. } => source.root(),
+			err => err,
+		}
+	}
+}
+
+/// Adds an [ErrorContext] to the error of a [Result]. See the [module](self) documentation.
+pub trait WithContext<T> {
+	/// Adds the context from `context`, which is only called if there is an error.
+	fn with_context<F: FnOnce() -> ErrorContext>(self, context: F) -> Result<T, NbtError>;
+}
+
+impl<T> WithContext<T> for Result<T, NbtError> {
+	fn with_context<F: FnOnce() -> ErrorContext>(self, context: F) -> Result<T, NbtError> {
+		self.map_err(|err| err.with_context(context()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ chunk::Chunk, pos::BlockPos, tag::Tag, world::{ World, OVERWORLD, tests::temp_world } };
+
+	#[test]
+	fn context_test() -> Result<(), NbtError> {
+		let path = temp_world("context");
+		let world = World::open(&path)?;
+		let Tag::Compound(data) = Tag::parse("{xPos: 33, zPos: -1, sections: [{Y: 3b}, {Y: 4b, block_states: {palette: []}}]}").unwrap() else { unreachable!() };
+		world.save_chunk(OVERWORLD, ChunkPos::new(33, -1), &Chunk::new(data))?;
+		let err = world.get_block(OVERWORLD, BlockPos::new(530, 70, -5)).unwrap_err();
+		assert_eq!(err.to_string(), "dimension minecraft:overworld, region 1 -1, chunk 33 -1, at sections[1].block_states: Block states have no palette.");
+		assert_eq!(err.context().and_then(|context| context.path.as_ref()).map(ToString::to_string).as_deref(), Some("sections[1].block_states"));
+		assert!(matches!(err.root(), NbtError::IoError(_)));
+
+		// Outer paths go in front of inner ones.
+		let err = crate::invalid_data("Bad value.")
+			.with_context(ErrorContext::default().path(TagPath::new().key("data")))
+			.with_context(ErrorContext::default().path(TagPath::new().key("Level")));
+		assert_eq!(err.to_string(), "at Level.data: Bad value.");
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+}
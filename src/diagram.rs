@@ -0,0 +1,222 @@
+#![doc = r#"
+Diagrams of the structure of a tag, as [Graphviz](https://graphviz.org/) DOT or [Mermaid](https://mermaid.js.org/)
+flowcharts.
+
+[Tag::to_dot] and [Tag::to_mermaid] draw every Compound, List, and value as a box that shows its type and either
+its size or its value, with an arrow to each child labeled with its key or index. Long values are truncated, and
+only the first children of large Compounds and Lists are drawn, so the diagram of an unfamiliar chunk or player file
+shows its layout without drawing every block. [DiagramOptions] sets both limits.
+
+```
+# use rustnbt::{ compound, list, tag::Tag };
+let tag = compound!(("Pos", list!(1.5, 64.0)));
+let mermaid = tag.to_mermaid();
+assert!(mermaid.starts_with("flowchart TD\n\tn0[\"Compound (1 entry)\"]\n\tn1[\"List of Double (2)\"]\n\tn0 -->|\"Pos\"| n1\n"));
+assert!(mermaid.ends_with("\tn3[\"Double: 64d\"]\n\tn1 -->|\"[1]\"| n3\n"));
+```
+"#]
+
+use crate::{
+	Map,
+	snbt,
+	tag::{ Tag, ListTag },
+};
+use std::fmt::Write;
+
+/// Limits on what a diagram draws.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DiagramOptions {
+	/// The number of children of each Compound or List that are drawn. A box with the number of the rest is drawn
+	/// after them. 8 by default.
+	pub children: usize,
+	/// The number of characters of values that are shown, after which they end with `...`. 24 by default.
+	pub value_length: usize,
+}
+
+impl Default for DiagramOptions {
+	fn default() -> Self {
+		Self { children: 8, value_length: 24 }
+	}
+}
+
+impl DiagramOptions {
+	pub fn children(mut self, children: usize) -> Self {
+		self.children = children;
+		self
+	}
+
+	pub fn value_length(mut self, value_length: usize) -> Self {
+		self.value_length = value_length;
+		self
+	}
+}
+
+/// A box of a diagram. Elements of Lists are not [Tag]s, so Compounds and Lists in them are borrowed as they are,
+/// and other elements are copied into tags.
+enum Value<'a> {
+	Tag(&'a Tag),
+	Owned(Tag),
+	Map(&'a Map),
+	List(&'a ListTag),
+	/// The number of children that are not drawn.
+	More(usize),
+}
+
+/// The first `count` elements of a List.
+fn elements(list: &ListTag, count: usize) -> Vec<Value<'_>> {
+	let count = count.min(list.len());
+	match list {
+		ListTag::Byte(values) => values[..count].iter().map(|value| Value::Owned(Tag::Byte(*value))).collect(),
+		ListTag::Short(values) => values[..count].iter().map(|value| Value::Owned(Tag::Short(*value))).collect(),
+		ListTag::Int(values) => values[..count].iter().map(|value| Value::Owned(Tag::Int(*value))).collect(),
+		ListTag::Long(values) => values[..count].iter().map(|value| Value::Owned(Tag::Long(*value))).collect(),
+		ListTag::Float(values) => values[..count].iter().map(|value| Value::Owned(Tag::Float(*value))).collect(),
+		ListTag::Double(values) => values[..count].iter().map(|value| Value::Owned(Tag::Double(*value))).collect(),
+		ListTag::ByteArray(values) => values[..count].iter().map(|value| Value::Owned(Tag::ByteArray(value.clone()))).collect(),
+		ListTag::String(values) => values[..count].iter().map(|value| Value::Owned(Tag::String(value.clone()))).collect(),
+		ListTag::List(values) => values[..count].iter().map(Value::List).collect(),
+		ListTag::Compound(values) => values[..count].iter().map(Value::Map).collect(),
+		ListTag::IntArray(values) => values[..count].iter().map(|value| Value::Owned(Tag::IntArray(value.clone()))).collect(),
+		ListTag::LongArray(values) => values[..count].iter().map(|value| Value::Owned(Tag::LongArray(value.clone()))).collect(),
+		ListTag::Empty => Vec::new(),
+	}
+}
+
+/// The label of a value that has no children: its type, its length if it is an array, and its SNBT, cut to
+/// `length` characters.
+fn leaf(tag: &Tag, length: usize) -> String {
+	let text = snbt::to_string(tag);
+	let text = match text.char_indices().nth(length) {
+		Some((end, _)) => format!("{}...", &text[..end]),
+		None => text,
+	};
+	match tag {
+		Tag::ByteArray(values) => format!("ByteArray ({}): {text}", values.len()),
+		Tag::IntArray(values) => format!("IntArray ({}): {text}", values.len()),
+		Tag::LongArray(values) => format!("LongArray ({}): {text}", values.len()),
+		tag => format!("{}: {text}", tag.title()),
+	}
+}
+
+/// Draws the boxes of a diagram in pre-order, without recursion. `draw` is called with the number of each box, its
+/// label, and the number and label of the arrow from its parent, if it has one.
+fn walk<F: FnMut(usize, &str, Option<(usize, &str)>)>(tag: &Tag, options: &DiagramOptions, mut draw: F) {
+	let mut stack: Vec<(Option<(usize, String)>, Value)> = vec![(None, Value::Tag(tag))];
+	let mut node = 0;
+	while let Some((parent, value)) = stack.pop() {
+		let mut children = Vec::new();
+		let (label, len) = match value {
+			Value::Tag(Tag::Compound(map)) | Value::Map(map) => {
+				children.extend(map.iter().take(options.children).map(|(key, tag)| (key.clone(), Value::Tag(tag))));
+				let label = match map.len() {
+					1 => "Compound (1 entry)".to_owned(),
+					len => format!("Compound ({len} entries)"),
+				};
+				(label, map.len())
+			}
+			Value::Tag(Tag::List(list)) | Value::List(list) => {
+				children.extend(elements(list, options.children).into_iter().enumerate().map(|(index, value)| (format!("[{index}]"), value)));
+				let label = match list.element_id() {
+					Some(id) => format!("List of {} ({})", id.title(), list.len()),
+					None => "List (empty)".to_owned(),
+				};
+				(label, list.len())
+			}
+			Value::Tag(tag) => (leaf(tag, options.value_length), 0),
+			Value::Owned(tag) => (leaf(&tag, options.value_length), 0),
+			Value::More(count) => (format!("... {count} more"), 0),
+		};
+		if len > options.children {
+			children.push((String::new(), Value::More(len - options.children)));
+		}
+		draw(node, &label, parent.as_ref().map(|(parent, edge)| (*parent, edge.as_str())));
+		stack.extend(children.into_iter().rev().map(|(edge, value)| (Some((node, edge)), value)));
+		node += 1;
+	}
+}
+
+impl Tag {
+	/// A Graphviz DOT diagram of the structure of this tag, with the default [DiagramOptions].
+	pub fn to_dot(&self) -> String {
+		self.to_dot_with(&DiagramOptions::default())
+	}
+
+	/// A Graphviz DOT diagram of the structure of this tag.
+	pub fn to_dot_with(&self, options: &DiagramOptions) -> String {
+		let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+		let mut dot = String::from("digraph nbt {\n\tnode [shape=box, fontname=\"monospace\"];\n");
+		walk(self, options, |node, label, parent| {
+			writeln!(dot, "\tn{node} [label=\"{}\"];", escape(label)).unwrap();
+			match parent {
+				Some((parent, "")) => writeln!(dot, "\tn{parent} -> n{node};").unwrap(),
+				Some((parent, edge)) => writeln!(dot, "\tn{parent} -> n{node} [label=\"{}\"];", escape(edge)).unwrap(),
+				None => {}
+			}
+		});
+		dot.push_str("}\n");
+		dot
+	}
+
+	/// A Mermaid flowchart of the structure of this tag, with the default [DiagramOptions].
+	pub fn to_mermaid(&self) -> String {
+		self.to_mermaid_with(&DiagramOptions::default())
+	}
+
+	/// A Mermaid flowchart of the structure of this tag.
+	pub fn to_mermaid_with(&self, options: &DiagramOptions) -> String {
+		// Quoted Mermaid text has no backslash escapes, only entity codes like `#quot;`.
+		let escape = |text: &str| text.replace('&', "#amp;").replace('"', "#quot;");
+		let mut mermaid = String::from("flowchart TD\n");
+		walk(self, options, |node, label, parent| {
+			writeln!(mermaid, "\tn{node}[\"{}\"]", escape(label)).unwrap();
+			match parent {
+				Some((parent, "")) => writeln!(mermaid, "\tn{parent} --> n{node}").unwrap(),
+				Some((parent, edge)) => writeln!(mermaid, "\tn{parent} -->|\"{}\"| n{node}", escape(edge)).unwrap(),
+				None => {}
+			}
+		});
+		mermaid
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{ compound, list, tag::Tag };
+	use super::*;
+
+	#[test]
+	fn diagram_test() {
+		let items = Tag::parse("[{id: stone}, {id: dirt}, {id: sand}]").unwrap();
+		let tag = compound!(
+			("name", "a \"long\" name"),
+			("heights", Tag::IntArray((0..100).collect())),
+			("items", items.clone()),
+			("empty", Tag::List(ListTag::Empty))
+		);
+		let options = DiagramOptions::default().children(2).value_length(10);
+		let dot = tag.to_dot_with(&options);
+		assert!(dot.starts_with("digraph nbt {\n\tnode [shape=box, fontname=\"monospace\"];\n\tn0 [label=\"Compound (4 entries)\"];\n"));
+		assert!(dot.ends_with("}\n"));
+		// Every box but the root has one arrow to it.
+		let boxes = dot.lines().filter(|line| line.contains(" [label=") && !line.contains("->")).count();
+		assert_eq!(dot.matches(" -> ").count(), boxes - 1);
+		assert!(dot.contains("[label=\"... 2 more\"]"));
+
+		let mermaid = items.to_mermaid_with(&options);
+		assert_eq!(mermaid, r#"flowchart TD
+	n0["List of Compound (3)"]
+	n1["Compound (1 entry)"]
+	n0 -->|"[0]"| n1
+	n2["String: #quot;stone#quot;"]
+	n1 -->|"id"| n2
+	n3["Compound (1 entry)"]
+	n0 -->|"[1]"| n3
+	n4["String: #quot;dirt#quot;"]
+	n3 -->|"id"| n4
+	n5["... 1 more"]
+	n0 --> n5
+"#);
+		assert_eq!(Tag::IntArray((0..100).collect()).to_mermaid_with(&options), "flowchart TD\n\tn0[\"IntArray (100): [I;0,1,2,3...\"]\n");
+		assert_eq!(Tag::List(ListTag::Empty).to_dot(), "digraph nbt {\n\tnode [shape=box, fontname=\"monospace\"];\n\tn0 [label=\"List (empty)\"];\n}\n");
+	}
+}
@@ -0,0 +1,216 @@
+#![doc = r#"
+Optional authenticated encryption for exported data, so that files containing player data can be shared or stored safely.
+
+Data is encrypted with AES-256-GCM, which also detects any change to the encrypted bytes. A [Key] is either random
+([Key::generate]) or derived from a passphrase with PBKDF2-HMAC-SHA256 ([Key::derive]). [seal] and [open] encrypt with
+a key, and [seal_with_passphrase] and [open_with_passphrase] store the salt of the key in the output, so only the
+passphrase is needed to decrypt it. [write_nbt] and [read_nbt] do the same for gzip compressed NBT files, and a
+[backup](crate::backup) store can be opened with [Store::open_encrypted](crate::backup::Store::open_encrypted).
+
+Encrypted data starts with `RNBTENC` followed by a version byte, so [is_encrypted] can tell it apart from NBT.
+
+```
+# use rustnbt::encryption::{ Key, seal, open };
+let key = Key::generate().unwrap();
+let sealed = seal(&key, b"player data").unwrap();
+assert_eq!(open(&key, &sealed).unwrap(), b"player data");
+assert!(open(&Key::generate().unwrap(), &sealed).is_err());
+```
+"#]
+
+use crate::{
+	NbtError,
+	invalid_data,
+	tag::NamedTag,
+	world::{ gzip_nbt, gunzip_nbt },
+};
+use aes_gcm::{ Aes256Gcm, KeyInit, aead::Aead };
+use std::io::Write;
+use std::path::Path;
+
+/// The magic bytes of data sealed with a [Key].
+const KEY_MAGIC: &[u8; 8] = b"RNBTENC\x01";
+/// The magic bytes of data sealed with a passphrase.
+const PASSPHRASE_MAGIC: &[u8; 8] = b"RNBTENC\x02";
+const NONCE_LENGTH: usize = 12;
+/// The length of the salt used to derive a key from a passphrase.
+pub const SALT_LENGTH: usize = 16;
+/// The number of PBKDF2 iterations used by [seal_with_passphrase].
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// A 256-bit AES key. Its [Debug] output does not show the key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Key([u8; 32]);
+
+impl Key {
+	pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+		Self(bytes)
+	}
+
+	pub const fn as_bytes(&self) -> &[u8; 32] {
+		&self.0
+	}
+
+	/// Creates a random key.
+	pub fn generate() -> Result<Self, NbtError> {
+		let mut key = [0; 32];
+		fill_random(&mut key)?;
+		Ok(Self(key))
+	}
+
+	/// Derives a key from a passphrase with PBKDF2-HMAC-SHA256.
+	pub fn derive(passphrase: &str, salt: &[u8; SALT_LENGTH], iterations: u32) -> Self {
+		Self(pbkdf2::pbkdf2_hmac_array::<sha2::Sha256, 32>(passphrase.as_bytes(), salt, iterations))
+	}
+}
+
+impl std::fmt::Debug for Key {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("Key(..)")
+	}
+}
+
+/// Creates a random salt for [Key::derive].
+pub fn generate_salt() -> Result<[u8; SALT_LENGTH], NbtError> {
+	let mut salt = [0; SALT_LENGTH];
+	fill_random(&mut salt)?;
+	Ok(salt)
+}
+
+fn fill_random(bytes: &mut [u8]) -> Result<(), NbtError> {
+	getrandom::getrandom(bytes).map_err(|err| NbtError::IoError(err.into()))
+}
+
+/// Returns true if the bytes start with the magic bytes of encrypted data.
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+	bytes.starts_with(KEY_MAGIC) || bytes.starts_with(PASSPHRASE_MAGIC)
+}
+
+fn encrypt(key: &Key, plain: &[u8], output: &mut Vec<u8>) -> Result<(), NbtError> {
+	let mut nonce = [0; NONCE_LENGTH];
+	fill_random(&mut nonce)?;
+	let cipher = Aes256Gcm::new(&key.0.into());
+	let encrypted = cipher.encrypt(&nonce.into(), plain).map_err(|_| invalid_data("Data is too large to encrypt."))?;
+	output.extend_from_slice(&nonce);
+	output.extend_from_slice(&encrypted);
+	Ok(())
+}
+
+fn decrypt(key: &Key, sealed: &[u8]) -> Result<Vec<u8>, NbtError> {
+	if sealed.len() < NONCE_LENGTH {
+		return Err(invalid_data("Encrypted data is truncated."));
+	}
+	let (nonce, encrypted) = sealed.split_at(NONCE_LENGTH);
+	let nonce: [u8; NONCE_LENGTH] = nonce.try_into().expect("Nonce has the right length.");
+	let cipher = Aes256Gcm::new(&key.0.into());
+	cipher.decrypt(&nonce.into(), encrypted).map_err(|_| NbtError::DecryptionFailed)
+}
+
+/// Encrypts bytes with a key and a random nonce.
+pub fn seal(key: &Key, plain: &[u8]) -> Result<Vec<u8>, NbtError> {
+	let mut output = KEY_MAGIC.to_vec();
+	encrypt(key, plain, &mut output)?;
+	Ok(output)
+}
+
+/// Decrypts bytes written by [seal].<br>
+/// Fails with [NbtError::DecryptionFailed] if the key is wrong or the bytes were modified.
+pub fn open(key: &Key, sealed: &[u8]) -> Result<Vec<u8>, NbtError> {
+	let Some(sealed) = sealed.strip_prefix(KEY_MAGIC) else {
+		return Err(invalid_data("Data was not encrypted with a key."));
+	};
+	decrypt(key, sealed)
+}
+
+/// Encrypts bytes with a key derived from a passphrase and a random salt, which is stored in the output along with the
+/// number of iterations.
+pub fn seal_with_passphrase(passphrase: &str, plain: &[u8]) -> Result<Vec<u8>, NbtError> {
+	seal_with_iterations(passphrase, plain, DEFAULT_ITERATIONS)
+}
+
+/// Like [seal_with_passphrase], with a different number of PBKDF2 iterations.
+/// Fewer iterations make a wrong passphrase faster to try.
+pub fn seal_with_iterations(passphrase: &str, plain: &[u8], iterations: u32) -> Result<Vec<u8>, NbtError> {
+	let salt = generate_salt()?;
+	let key = Key::derive(passphrase, &salt, iterations);
+	let mut output = PASSPHRASE_MAGIC.to_vec();
+	output.extend_from_slice(&iterations.to_be_bytes());
+	output.extend_from_slice(&salt);
+	encrypt(&key, plain, &mut output)?;
+	Ok(output)
+}
+
+/// Decrypts bytes written by [seal_with_passphrase].<br>
+/// Fails with [NbtError::DecryptionFailed] if the passphrase is wrong or the bytes were modified.
+pub fn open_with_passphrase(passphrase: &str, sealed: &[u8]) -> Result<Vec<u8>, NbtError> {
+	let Some(sealed) = sealed.strip_prefix(PASSPHRASE_MAGIC) else {
+		return Err(invalid_data("Data was not encrypted with a passphrase."));
+	};
+	if sealed.len() < 4 + SALT_LENGTH {
+		return Err(invalid_data("Encrypted data is truncated."));
+	}
+	let (iterations, sealed) = sealed.split_at(4);
+	let (salt, sealed) = sealed.split_at(SALT_LENGTH);
+	let iterations = u32::from_be_bytes(iterations.try_into().expect("Iterations have the right length."));
+	let key = Key::derive(passphrase, salt.try_into().expect("Salt has the right length."), iterations);
+	decrypt(&key, sealed)
+}
+
+/// Writes gzip compressed NBT encrypted with a passphrase, creating the parent directory if needed.
+pub fn write_nbt<P: AsRef<Path>>(path: P, root: &NamedTag, passphrase: &str) -> Result<(), NbtError> {
+	if let Some(parent) = path.as_ref().parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	let sealed = seal_with_passphrase(passphrase, &gzip_nbt(root)?)?;
+	std::fs::File::create(path)?.write_all(&sealed)?;
+	Ok(())
+}
+
+/// Reads a file written by [write_nbt].
+pub fn read_nbt<P: AsRef<Path>>(path: P, passphrase: &str) -> Result<NamedTag, NbtError> {
+	gunzip_nbt(&open_with_passphrase(passphrase, &std::fs::read(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, world::tests::temp_world };
+
+	#[test]
+	fn seal_test() -> Result<(), NbtError> {
+		let key = Key::generate()?;
+		let sealed = seal(&key, b"player data")?;
+		assert!(is_encrypted(&sealed));
+		assert!(!is_encrypted(b"\x0a\x00\x00"));
+		assert_eq!(open(&key, &sealed)?, b"player data");
+		assert!(matches!(open(&Key::generate()?, &sealed), Err(NbtError::DecryptionFailed)));
+		let mut modified = sealed.clone();
+		*modified.last_mut().unwrap() ^= 1;
+		assert!(matches!(open(&key, &modified), Err(NbtError::DecryptionFailed)));
+		assert!(open(&key, &sealed[..10]).is_err());
+		assert_ne!(seal(&key, b"player data")?, sealed);
+		assert_eq!(format!("{key:?}"), "Key(..)");
+		Ok(())
+	}
+
+	#[test]
+	fn passphrase_test() -> Result<(), NbtError> {
+		let salt = [7; SALT_LENGTH];
+		assert_eq!(Key::derive("passphrase", &salt, 2), Key::derive("passphrase", &salt, 2));
+		assert_ne!(Key::derive("passphrase", &salt, 2), Key::derive("passphrase", &salt, 3));
+
+		let dir = temp_world("encryption");
+		let path = dir.join("export.dat");
+		let root = NamedTag::new(compound!(("Name", "Steve"), ("Health", 20.0f32)));
+		write_nbt(&path, &root, "correct horse")?;
+		assert!(is_encrypted(&std::fs::read(&path)?));
+		assert_eq!(read_nbt(&path, "correct horse")?, root);
+		assert!(open(&Key::generate()?, &std::fs::read(&path)?).is_err());
+		let sealed = seal_with_iterations("correct horse", b"player data", 10)?;
+		assert_eq!(open_with_passphrase("correct horse", &sealed)?, b"player data");
+		assert!(matches!(open_with_passphrase("wrong", &sealed), Err(NbtError::DecryptionFailed)));
+		assert!(open_with_passphrase("correct horse", &sealed[..20]).is_err());
+		std::fs::remove_dir_all(dir)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,238 @@
+// https://minecraft.wiki/w/Entity_format
+
+#![doc = r#"
+Typed access to the position, motion, and rotation of entities.
+
+Every entity stores `Pos` and `Motion` as Lists of three Doubles and `Rotation` as a List of two Floats (yaw, then
+pitch). [Pos], [Motion], and [Rotation] convert to and from those Lists, checking their types, lengths, and that every
+value is finite, which the game requires when it loads an entity. [EntityField] reads and writes them in an entity
+Compound.
+
+```
+# use rustnbt::{ compound, list, tag::Tag, entity::* };
+let Tag::Compound(mut zombie) = compound!(("Pos", list!(0.5, 64.0, -3.5)), ("Rotation", list!(90.0f32, 0.0f32))) else { unreachable!() };
+let mut pos = Pos::get(&zombie)?.expect("The zombie has a position.");
+pos.y += 1.0;
+pos.set(&mut zombie);
+assert_eq!(zombie["Pos"], list!(0.5, 65.0, -3.5));
+assert_eq!(Rotation::get(&zombie)?.map(|rotation| rotation.yaw), Some(90.0));
+assert_eq!(Motion::get(&zombie)?, None);
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	pos::BlockPos,
+	tag::{ Tag, ListTag },
+};
+
+/// The position of an entity (`Pos`), in blocks.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Pos {
+	pub x: f64,
+	pub y: f64,
+	pub z: f64,
+}
+
+/// The velocity of an entity (`Motion`), in blocks per tick.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Motion {
+	pub dx: f64,
+	pub dy: f64,
+	pub dz: f64,
+}
+
+/// The rotation of an entity (`Rotation`), in degrees.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Rotation {
+	/// The clockwise rotation around the Y axis, where `0` faces south.
+	pub yaw: f32,
+	/// The rotation above or below the horizon, where `-90` faces up and `90` faces down.
+	pub pitch: f32,
+}
+
+/// A value that entities store under a fixed key. See the [module](self) documentation.
+pub trait EntityField: Sized + Into<Tag> + for<'a> TryFrom<&'a Tag, Error = NbtError> {
+	const KEY: &'static str;
+
+	/// Reads the value from an entity, or returns None if the entity does not have it.<br>
+	/// Errors if the value does not have the right type and length, or is not finite.
+	fn get(entity: &Map) -> Result<Option<Self>, NbtError> {
+		entity.get(Self::KEY).map(Self::try_from).transpose()
+	}
+
+	/// Writes the value to an entity, replacing what was there.
+	fn set(self, entity: &mut Map) {
+		entity.insert(Self::KEY.to_owned(), self.into());
+	}
+}
+
+impl Pos {
+	pub const fn new(x: f64, y: f64, z: f64) -> Self {
+		Self { x, y, z }
+	}
+
+	/// The block that contains this position.
+	pub fn block(self) -> BlockPos {
+		BlockPos::new(self.x.floor() as i32, self.y.floor() as i32, self.z.floor() as i32)
+	}
+
+	/// Returns this position moved by `motion` for one tick.
+	pub fn offset(self, motion: Motion) -> Self {
+		Self::new(self.x + motion.dx, self.y + motion.dy, self.z + motion.dz)
+	}
+}
+
+impl Motion {
+	pub const fn new(dx: f64, dy: f64, dz: f64) -> Self {
+		Self { dx, dy, dz }
+	}
+}
+
+impl Rotation {
+	pub const fn new(yaw: f32, pitch: f32) -> Self {
+		Self { yaw, pitch }
+	}
+
+	/// Returns this rotation with the yaw wrapped to `-180..180` and the pitch clamped to `-90..=90`.
+	pub fn normalized(self) -> Self {
+		Self::new((self.yaw + 180.0).rem_euclid(360.0) - 180.0, self.pitch.clamp(-90.0, 90.0))
+	}
+}
+
+impl EntityField for Pos {
+	const KEY: &'static str = "Pos";
+}
+
+impl EntityField for Motion {
+	const KEY: &'static str = "Motion";
+}
+
+impl EntityField for Rotation {
+	const KEY: &'static str = "Rotation";
+}
+
+/// Returns the values of a List of `N` finite numbers of one type.
+fn finite<T: Copy + Into<f64>, const N: usize>(key: &str, values: Option<&Vec<T>>, kind: &str) -> Result<[T; N], NbtError> {
+	let values: [T; N] = values
+		.and_then(|values| values.as_slice().try_into().ok())
+		.ok_or_else(|| invalid_data(&format!("{key} must be a List of {N} {kind}s.")))?;
+	if values.iter().any(|value| !(*value).into().is_finite()) {
+		return Err(invalid_data(&format!("{key} has a value that is not finite.")));
+	}
+	Ok(values)
+}
+
+impl TryFrom<&Tag> for Pos {
+	type Error = NbtError;
+
+	fn try_from(tag: &Tag) -> Result<Self, NbtError> {
+		let values = match tag {
+			Tag::List(ListTag::Double(values)) => Some(values),
+			_ => None,
+		};
+		let [x, y, z] = finite(Self::KEY, values, "Double")?;
+		Ok(Self::new(x, y, z))
+	}
+}
+
+impl TryFrom<&Tag> for Motion {
+	type Error = NbtError;
+
+	fn try_from(tag: &Tag) -> Result<Self, NbtError> {
+		let values = match tag {
+			Tag::List(ListTag::Double(values)) => Some(values),
+			_ => None,
+		};
+		let [dx, dy, dz] = finite(Self::KEY, values, "Double")?;
+		Ok(Self::new(dx, dy, dz))
+	}
+}
+
+impl TryFrom<&Tag> for Rotation {
+	type Error = NbtError;
+
+	fn try_from(tag: &Tag) -> Result<Self, NbtError> {
+		let values = match tag {
+			Tag::List(ListTag::Float(values)) => Some(values),
+			_ => None,
+		};
+		let [yaw, pitch] = finite(Self::KEY, values, "Float")?;
+		Ok(Self::new(yaw, pitch))
+	}
+}
+
+impl From<Pos> for Tag {
+	fn from(pos: Pos) -> Self {
+		Tag::List(ListTag::Double(vec![pos.x, pos.y, pos.z]))
+	}
+}
+
+impl From<Motion> for Tag {
+	fn from(motion: Motion) -> Self {
+		Tag::List(ListTag::Double(vec![motion.dx, motion.dy, motion.dz]))
+	}
+}
+
+impl From<Rotation> for Tag {
+	fn from(rotation: Rotation) -> Self {
+		Tag::List(ListTag::Float(vec![rotation.yaw, rotation.pitch]))
+	}
+}
+
+impl From<[f64; 3]> for Pos {
+	fn from([x, y, z]: [f64; 3]) -> Self {
+		Self::new(x, y, z)
+	}
+}
+
+impl From<Pos> for [f64; 3] {
+	fn from(pos: Pos) -> Self {
+		[pos.x, pos.y, pos.z]
+	}
+}
+
+impl From<[f64; 3]> for Motion {
+	fn from([dx, dy, dz]: [f64; 3]) -> Self {
+		Self::new(dx, dy, dz)
+	}
+}
+
+impl From<Motion> for [f64; 3] {
+	fn from(motion: Motion) -> Self {
+		[motion.dx, motion.dy, motion.dz]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::list;
+
+	#[test]
+	fn field_test() -> Result<(), NbtError> {
+		let mut entity = Map::default();
+		Pos::new(-0.5, 70.25, 3.0).set(&mut entity);
+		Motion::new(0.0, -0.08, 0.0).set(&mut entity);
+		Rotation::new(450.0, -120.0).normalized().set(&mut entity);
+		assert_eq!(entity["Pos"], list!(-0.5, 70.25, 3.0));
+		assert_eq!(entity["Rotation"], list!(90.0f32, -90.0f32));
+		let pos = Pos::get(&entity)?.expect("Pos was set.");
+		assert_eq!(pos.block(), BlockPos::new(-1, 70, 3));
+		assert_eq!(pos.offset(Motion::get(&entity)?.expect("Motion was set.")).y, 70.17);
+		assert_eq!(<[f64; 3]>::from(pos), [-0.5, 70.25, 3.0]);
+
+		assert!(Pos::try_from(&list!(1.0, 2.0)).is_err());
+		assert!(Pos::try_from(&list!(1.0f32, 2.0f32, 3.0f32)).is_err());
+		assert!(Motion::try_from(&list!(1.0, f64::NAN, 3.0)).is_err());
+		assert!(Rotation::try_from(&list!(f32::INFINITY, 0.0f32)).is_err());
+		entity.insert("Rotation".to_owned(), Tag::Int(0));
+		assert!(Rotation::get(&entity).is_err());
+		assert_eq!(Rotation::new(-180.0, 0.0).normalized(), Rotation::new(-180.0, 0.0));
+		assert_eq!(Rotation::new(180.0, 0.0).normalized(), Rotation::new(-180.0, 0.0));
+		Ok(())
+	}
+}
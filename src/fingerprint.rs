@@ -0,0 +1,186 @@
+#![doc = r#"
+Fingerprints of what players built in a world, which do not depend on its seed.
+
+[fingerprint] hashes the blocks that were not generated, and the block entities of every chunk. Which blocks are
+natural is decided by a predicate from the caller, such as "stone, dirt, grass, water, and ores below sea level", so
+the terrain that the seed generated does not change the fingerprint. Two copies of a world have the same
+[WorldFingerprint::total], and [WorldFingerprint::changed_chunks] points at the chunks where two versions differ,
+which is where to look after griefing.
+
+Block entities are hashed by their type and position only. Their contents, such as the progress of a furnace, change
+while the world runs, and would make every fingerprint of a running server different. Only chunks in the 1.18+
+format, with a `sections` List, have their blocks hashed.
+
+```no_run
+# use rustnbt::{ fingerprint::*, world::World };
+let natural = |_dimension: &str, pos: rustnbt::pos::BlockPos, block: &rustnbt::chunk::BlockState| {
+	pos.y < 0 || matches!(block.name.as_str(), "minecraft:air" | "minecraft:stone" | "minecraft:dirt" | "minecraft:grass_block" | "minecraft:water")
+};
+let before = fingerprint(&World::open("backups/monday").expect("Failed to open world."), natural).expect("Failed to read world.");
+let after = fingerprint(&World::open("saves/server").expect("Failed to open world."), natural).expect("Failed to read world.");
+for (dimension, chunk) in before.changed_chunks(&after) {
+	println!("{dimension} {chunk:?} changed");
+}
+```
+"#]
+
+use crate::{
+	NbtError,
+	block_entity,
+	bulk::{ ErrorPolicy, ItemError },
+	context::{ ErrorContext, WithContext },
+	chunk::{ BlockState, Chunk },
+	pos::{ BlockPos, ChunkPos },
+	region::Region,
+	tag::{ Tag, ListTag },
+	world::{ World, RegionKind },
+};
+use std::collections::BTreeMap;
+
+/// A SHA-256 hash of player-built content. It is the same on every platform and in every version of this crate.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct Fingerprint(pub [u8; 32]);
+
+/// Formats the hash as lowercase hexadecimal.
+impl std::fmt::Display for Fingerprint {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+	}
+}
+
+/// The fingerprints of a world, from [fingerprint].
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct WorldFingerprint {
+	/// The fingerprint of every chunk that has blocks that are not natural or block entities, by dimension and
+	/// position.
+	pub chunks: BTreeMap<(String, ChunkPos), Fingerprint>,
+	/// The fingerprint of the whole world, made from those of its chunks.
+	pub total: Fingerprint,
+}
+
+impl WorldFingerprint {
+	/// The chunks whose player-built content differs between two fingerprints, including chunks that only one of them
+	/// has, in order.
+	pub fn changed_chunks<'a>(&'a self, other: &'a WorldFingerprint) -> Vec<(&'a str, ChunkPos)> {
+		let mut changed: Vec<(&str, ChunkPos)> = self.chunks.iter()
+			.filter(|(key, fingerprint)| other.chunks.get(*key) != Some(fingerprint))
+			.chain(other.chunks.iter().filter(|(key, _)| !self.chunks.contains_key(*key)))
+			.map(|((dimension, chunk), _)| (dimension.as_str(), *chunk))
+			.collect();
+		changed.sort_unstable();
+		changed
+	}
+}
+
+/// The lines that are hashed for a chunk: every block that is not natural, in the order of the sections and blocks,
+/// followed by every block entity, sorted. Returns an empty string if the chunk has neither.
+fn chunk_content<F: FnMut(&str, BlockPos, &BlockState) -> bool>(dimension: &str, chunk: &Chunk, natural: &mut F) -> Result<String, NbtError> {
+	let mut content = String::new();
+	let Some(pos) = chunk.position() else {
+		return Ok(content);
+	};
+	let mut section_ys = chunk.section_ys();
+	section_ys.sort_unstable();
+	for section_y in section_ys {
+		let Some(blocks) = chunk.block_states(section_y)? else {
+			continue;
+		};
+		for (index, block) in blocks.iter().enumerate() {
+			let block_pos = BlockPos::new(pos.x * 16 + (index & 15) as i32, section_y * 16 + (index >> 8) as i32, pos.z * 16 + (index >> 4 & 15) as i32);
+			if !natural(dimension, block_pos, block) {
+				content.push_str(&format!("block {} {} {} {block}\n", block_pos.x, block_pos.y, block_pos.z));
+			}
+		}
+	}
+	if let Some(Tag::List(ListTag::Compound(entities))) = chunk.data().get("block_entities") {
+		let mut lines: Vec<String> = entities.iter()
+			.filter_map(|entity| match (entity.get("id"), block_entity::position(entity)) {
+				(Some(Tag::String(id)), Some(pos)) => Some(format!("block_entity {} {} {} {id}\n", pos.x, pos.y, pos.z)),
+				_ => None,
+			})
+			.collect();
+		lines.sort_unstable();
+		lines.iter().for_each(|line| content.push_str(line));
+	}
+	Ok(content)
+}
+
+/// Fingerprints the player-built content of every chunk of a world. `natural` is called with the dimension, position,
+/// and state of every block, and returns whether the block was generated with the world. See the
+/// [module](self) documentation.
+pub fn fingerprint<F: FnMut(&str, BlockPos, &BlockState) -> bool>(world: &World, natural: F) -> Result<WorldFingerprint, NbtError> {
+	Ok(fingerprint_with(world, natural, ErrorPolicy::fail_fast())?.0)
+}
+
+/// Like [fingerprint], with a policy for the region files and chunks that fail. Also returns the items that were
+/// skipped. Skipped chunks are left out of the fingerprint, so they show up as changed when it is compared.
+pub fn fingerprint_with<F: FnMut(&str, BlockPos, &BlockState) -> bool>(world: &World, mut natural: F, on_error: ErrorPolicy) -> Result<(WorldFingerprint, Vec<ItemError>), NbtError> {
+	let mut fingerprint = WorldFingerprint::default();
+	let mut errors = Vec::new();
+	for dimension in world.dimensions()? {
+		for position in world.regions(&dimension, RegionKind::Chunks).with_context(|| ErrorContext::default().dimension(&dimension))? {
+			let opened = on_error.run(&mut errors, || ErrorContext::default().dimension(&dimension).region(position), || {
+				Region::open(world.region_path(&dimension, RegionKind::Chunks, position))
+			})?;
+			let Some(mut region) = opened else {
+				continue;
+			};
+			let chunks: Vec<ChunkPos> = region.chunks().collect();
+			for pos in chunks {
+				let content = on_error.run(&mut errors, || ErrorContext::default().dimension(&dimension).chunk(pos), || {
+					let Some(root) = region.read_chunk(pos)? else {
+						return Ok(String::new());
+					};
+					let Tag::Compound(data) = root.take_tag() else {
+						return Ok(String::new());
+					};
+					chunk_content(&dimension, &Chunk::new(data), &mut natural)
+				})?;
+				if let Some(content) = content.filter(|content| !content.is_empty()) {
+					fingerprint.chunks.insert((dimension.clone(), pos), Fingerprint(crate::sha256::digest(content.as_bytes())));
+				}
+			}
+		}
+	}
+	let total: String = fingerprint.chunks.iter()
+		.map(|((dimension, chunk), hash)| format!("{dimension} {} {} {hash}\n", chunk.x, chunk.z))
+		.collect();
+	fingerprint.total = Fingerprint(crate::sha256::digest(total.as_bytes()));
+	Ok((fingerprint, errors))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::world::{ OVERWORLD, tests::temp_world };
+
+	#[test]
+	fn fingerprint_test() -> Result<(), NbtError> {
+		let path = temp_world("fingerprint");
+		std::fs::create_dir_all(path.join("region"))?;
+		let world = World::open(&path)?;
+		let pos = ChunkPos::new(1, -1);
+		let Tag::Compound(data) = Tag::parse("{xPos: 1, zPos: -1, sections: [{Y: 4b}]}").unwrap() else { unreachable!() };
+		let mut chunk = Chunk::new(data);
+		let natural = |_: &str, _: BlockPos, block: &BlockState| block.name == "minecraft:air";
+		let write = |chunk: &Chunk| -> Result<WorldFingerprint, NbtError> {
+			let mut region = Region::create(world.region_path(OVERWORLD, RegionKind::Chunks, pos.region()))?;
+			region.write_chunk(pos, &crate::tag::NamedTag::new(Tag::Compound(chunk.data().clone())))?;
+			fingerprint(&world, natural)
+		};
+		let empty = write(&chunk)?;
+		assert!(empty.chunks.is_empty());
+
+		chunk.set_block(BlockPos::new(17, 70, -5), BlockState::new("minecraft:oak_planks"))?;
+		let built = write(&chunk)?;
+		assert_eq!(built.changed_chunks(&empty), [(OVERWORLD, pos)]);
+		assert_ne!(built.total, empty.total);
+		assert_eq!(write(&chunk)?, built);
+
+		chunk.set_block(BlockPos::new(17, 70, -5), BlockState::air())?;
+		assert_eq!(write(&chunk)?.total, empty.total);
+		assert_eq!(built.total.to_string().len(), 64);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,121 @@
+#![doc = r#"
+The iterative reader of binary NBT that the trees of [lazy](crate::lazy) and `arena` are read with.
+
+A [Tree] says how the tags of a tree are made, and [read] reads a named root tag into it with a stack of frames on the
+heap instead of by recursion, so hostile input can not overflow the thread stack.
+"#]
+
+use crate::{
+	NbtError,
+	invalid_data,
+	io::{ NbtRead, DEFAULT_MAX_DEPTH },
+	tag::TagID,
+};
+use std::io::Read;
+
+/// How the tags of a tree are made from what [read] reads.
+pub(crate) trait Tree {
+	type Tag;
+	/// The names of root tags, and the keys of Compound entries.
+	type Key;
+	/// The entries of a Compound that is being read.
+	type Map;
+	/// The elements of a List that is being read.
+	type Items;
+
+	fn key<R: Read>(&mut self, reader: &mut R) -> Result<Self::Key, NbtError>;
+	/// Reads the payload of a tag that is not a Compound or a List.
+	fn leaf<R: Read>(&mut self, reader: &mut R, id: TagID) -> Result<Self::Tag, NbtError>;
+	fn map(&mut self) -> Self::Map;
+	/// Adds an entry to a Compound. Entries are added in the order that they were read, without looking for an
+	/// earlier entry with the same key, so reading a Compound takes linear time.
+	fn entry(map: &mut Self::Map, key: Self::Key, tag: Self::Tag);
+	fn compound(map: Self::Map) -> Self::Tag;
+	fn items(&mut self, capacity: usize) -> Self::Items;
+	fn item(items: &mut Self::Items, tag: Self::Tag);
+	/// Finishes a List. The elements all have the type `element`, which is None for an empty List of End tags.
+	fn list(element: Option<TagID>, items: Self::Items) -> Self::Tag;
+}
+
+/// A tag that was read whole, with its key in its parent Compound.
+type Finished<T> = Option<(Option<<T as Tree>::Key>, <T as Tree>::Tag)>;
+
+/// A Compound or List that is being read, with its key in its parent Compound.
+enum Frame<T: Tree> {
+	Compound(Option<T::Key>, T::Map),
+	List(Option<T::Key>, TagID, usize, T::Items),
+}
+
+/// Reads a named root tag of binary NBT into a tree, returning its name and the tag. Tags may be nested up to
+/// [DEFAULT_MAX_DEPTH] deep.
+pub(crate) fn read<T: Tree, R: Read>(reader: &mut R, tree: &mut T) -> Result<(T::Key, T::Tag), NbtError> {
+	let id = TagID::nbt_read(reader)?;
+	let name = tree.key(reader)?;
+	let mut stack = Vec::new();
+	let mut finished = begin_read(reader, tree, id, None, &mut stack)?;
+	loop {
+		if let Some((key, tag)) = finished.take() {
+			match stack.last_mut() {
+				None => return Ok((name, tag)),
+				Some(Frame::Compound(_, map)) => T::entry(map, key.expect("Compound entries have keys."), tag),
+				Some(Frame::List(_, _, _, items)) => T::item(items, tag),
+			}
+		}
+		finished = match stack.last_mut().expect("A frame is open until the root tag is finished.") {
+			Frame::Compound(..) => match u8::nbt_read(reader)? {
+				0 => match stack.pop() {
+					Some(Frame::Compound(key, map)) => Some((key, T::compound(map))),
+					_ => unreachable!(),
+				},
+				id => {
+					let id = TagID::try_from(id)?;
+					let key = tree.key(reader)?;
+					begin_read(reader, tree, id, Some(key), &mut stack)?
+				}
+			},
+			Frame::List(_, _, 0, _) => match stack.pop() {
+				Some(Frame::List(key, element, _, items)) => Some((key, T::list(Some(element), items))),
+				_ => unreachable!(),
+			},
+			Frame::List(_, element, remaining, _) => {
+				*remaining -= 1;
+				let element = *element;
+				begin_read(reader, tree, element, None, &mut stack)?
+			}
+		};
+	}
+}
+
+/// Starts reading a tag. Compounds and Lists are pushed onto the stack, and other tags are read whole.
+fn begin_read<T: Tree, R: Read>(
+	reader: &mut R,
+	tree: &mut T,
+	id: TagID,
+	key: Option<T::Key>,
+	stack: &mut Vec<Frame<T>>,
+) -> Result<Finished<T>, NbtError> {
+	if matches!(id, TagID::Compound | TagID::List) && stack.len() >= DEFAULT_MAX_DEPTH {
+		return Err(invalid_data(&format!("Tags are nested deeper than {DEFAULT_MAX_DEPTH}.")));
+	}
+	let tag = match id {
+		TagID::Compound => {
+			stack.push(Frame::Compound(key, tree.map()));
+			return Ok(None);
+		}
+		TagID::List => {
+			let element = u8::nbt_read(reader)?;
+			let length = u32::nbt_read(reader)? as usize;
+			match TagID::list_element(element)? {
+				None if length == 0 => T::list(None, tree.items(0)),
+				None => return Err(invalid_data("List of End tags has elements.")),
+				Some(element) => {
+					// The length is not trusted to reserve more than a little.
+					stack.push(Frame::List(key, element, length, tree.items(length.min(1024))));
+					return Ok(None);
+				}
+			}
+		}
+		id => tree.leaf(reader, id)?,
+	};
+	Ok(Some((key, tag)))
+}
@@ -0,0 +1,281 @@
+#![doc = r#"
+Helpers for fuzzing the decoders of this crate.
+
+The `fuzz` folder of the repository holds [cargo-fuzz](https://github.com/rust-fuzz/cargo-fuzz) targets for the binary
+reader, the SNBT parser, and the region reader, along with a target that writes and reads back arbitrary tags.
+Run them with `cargo fuzz run <target>` from the repository root.
+
+- [write_corpus] seeds a corpus folder with tags from the [generator](crate::generator), in binary or SNBT form.
+- [minimize] shrinks a tag that makes a test fail, by removing entries and elements and simplifying values for as long as the test
+  still fails. Use `cargo fuzz tmin` and `cargo fuzz cmin` to minimize raw inputs and whole corpora.
+- With the `arbitrary` feature, [Tag], [ListTag], and [NamedTag] implement `arbitrary::Arbitrary`, for structured fuzzing.
+
+```
+# use rustnbt::{ compound, list, tag::Tag, fuzz::minimize };
+let failing = compound!(("a", 1), ("b", list!("x", "boom", "y")), ("c", "text"));
+let contains_boom = |tag: &Tag| rustnbt::snbt::to_string(tag).contains("boom");
+assert_eq!(minimize(failing, contains_boom), compound!(("b", list!("boom"))));
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	generator::{ Generator, GeneratorConfig },
+	io::*,
+	snbt,
+	tag::{
+		Tag,
+		ListTag,
+		NamedTag,
+	},
+};
+use std::path::Path;
+
+/// The form of the files that [write_corpus] writes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CorpusFormat {
+	/// Uncompressed named tags, as read by the binary reader.
+	Binary,
+	/// Compact SNBT, as read by the SNBT parser.
+	Snbt,
+}
+
+/// Writes `count` generated tags into `dir` as `seed-<n>.nbt` or `seed-<n>.snbt`, creating the folder if needed.
+pub fn write_corpus<P: AsRef<Path>>(dir: P, config: &GeneratorConfig, count: usize, format: CorpusFormat) -> Result<(), NbtError> {
+	let dir = dir.as_ref();
+	std::fs::create_dir_all(dir)?;
+	let mut generator = Generator::new(config.clone());
+	for index in 0..count {
+		let tag = generator.next_tag();
+		match format {
+			CorpusFormat::Binary => {
+				let mut bytes = Vec::new();
+				bytes.write_nbt(&NamedTag::new(tag))?;
+				std::fs::write(dir.join(format!("seed-{index}.nbt")), bytes)?;
+			}
+			CorpusFormat::Snbt => std::fs::write(dir.join(format!("seed-{index}.snbt")), snbt::to_string(&tag))?,
+		}
+	}
+	Ok(())
+}
+
+/// Shrinks a tag for as long as `still_fails` returns `true` for the smaller tag, and returns the smallest failing tag found.<br>
+/// Compound entries and List elements are removed, Strings and arrays are shortened, and numbers are set to zero.
+/// Each step makes the tag strictly smaller, so this always finishes, although it tries many candidates for large tags.
+pub fn minimize<F: FnMut(&Tag) -> bool>(tag: Tag, mut still_fails: F) -> Tag {
+	let mut current = tag;
+	'search: loop {
+		for candidate in candidates(&current) {
+			if still_fails(&candidate) {
+				current = candidate;
+				continue 'search;
+			}
+		}
+		return current;
+	}
+}
+
+/// All tags that are one step smaller than `tag`, with the largest steps first.
+fn candidates(tag: &Tag) -> Vec<Tag> {
+	fn shorter<T: Clone>(values: &[T], wrap: fn(Vec<T>) -> Tag) -> Vec<Tag> {
+		match values.len() {
+			0 => Vec::new(),
+			1 => vec![wrap(Vec::new())],
+			length => vec![wrap(Vec::new()), wrap(values[..length / 2].to_vec()), wrap(values[..length - 1].to_vec())],
+		}
+	}
+	match tag {
+		Tag::Byte(value) if *value != 0 => vec![Tag::Byte(0)],
+		Tag::Short(value) if *value != 0 => vec![Tag::Short(0)],
+		Tag::Int(value) if *value != 0 => vec![Tag::Int(0)],
+		Tag::Long(value) if *value != 0 => vec![Tag::Long(0)],
+		Tag::Float(value) if *value != 0.0 => vec![Tag::Float(0.0)],
+		Tag::Double(value) if *value != 0.0 => vec![Tag::Double(0.0)],
+		Tag::ByteArray(values) => shorter(values, Tag::ByteArray),
+		Tag::IntArray(values) => shorter(values, Tag::IntArray),
+		Tag::LongArray(values) => shorter(values, Tag::LongArray),
+		Tag::String(value) => {
+			let chars: Vec<char> = value.chars().collect();
+			shorter(&chars, |chars| Tag::String(chars.into_iter().collect()))
+		}
+		Tag::List(list) => {
+			let tags: Vec<Tag> = list.clone().into();
+			let mut lists = Vec::new();
+			for index in 0..tags.len() {
+				let mut removed = tags.clone();
+				removed.remove(index);
+				lists.push(removed);
+			}
+			for (index, element) in tags.iter().enumerate() {
+				for candidate in candidates(element) {
+					let mut replaced = tags.clone();
+					replaced[index] = candidate;
+					lists.push(replaced);
+				}
+			}
+			// Smaller elements may no longer have the type of the others, such as a List of Compounds that became Empty.
+			lists.into_iter().filter_map(|tags| ListTag::try_from(tags).ok().map(Tag::List)).collect()
+		}
+		Tag::Compound(map) => {
+			let mut maps = Vec::new();
+			for key in map.keys() {
+				let mut removed = map.clone();
+				remove(&mut removed, key);
+				maps.push(removed);
+			}
+			for (key, value) in map.iter() {
+				for candidate in candidates(value) {
+					let mut replaced = map.clone();
+					replaced.insert(key.clone(), candidate);
+					maps.push(replaced);
+				}
+			}
+			maps.into_iter().map(Tag::Compound).collect()
+		}
+		_ => Vec::new(),
+	}
+}
+
+fn remove(map: &mut Map, key: &str) -> Option<Tag> {
+	#[cfg(feature = "preserve_order")]
+	return map.shift_remove(key);
+	#[cfg(not(feature = "preserve_order"))]
+	return map.remove(key);
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+	use super::*;
+	use crate::tag::TagID;
+	use arbitrary::{ Arbitrary, Result, Unstructured };
+
+	/// How deep arbitrary Compounds and Lists are nested, so that fuzz inputs can not overflow the stack.
+	const MAX_DEPTH: usize = 16;
+
+	fn id(u: &mut Unstructured, depth: usize) -> Result<TagID> {
+		let max = if depth < MAX_DEPTH { 12 } else { 10 };
+		let id = u.int_in_range(1..=max)?;
+		// At the depth limit, the IDs of List and Compound stand for IntArray and LongArray instead.
+		let id = match (depth < MAX_DEPTH, id) {
+			(false, 9) => 11,
+			(false, 10) => 12,
+			(_, id) => id,
+		};
+		Ok(TagID::try_from(id).expect("ID is in range."))
+	}
+
+	/// Strings are limited to the 65535 bytes that their length prefix can hold.
+	fn string(u: &mut Unstructured) -> Result<String> {
+		let mut value = String::arbitrary(u)?;
+		let mut end = value.len().min(u16::MAX as usize);
+		while !value.is_char_boundary(end) {
+			end -= 1;
+		}
+		value.truncate(end);
+		Ok(value)
+	}
+
+	fn map(u: &mut Unstructured, depth: usize) -> Result<Map> {
+		let mut map = Map::default();
+		for _ in 0..u.arbitrary_len::<(u8, u8)>()? {
+			let id = id(u, depth)?;
+			map.insert(string(u)?, tag(u, id, depth)?);
+		}
+		Ok(map)
+	}
+
+	fn list(u: &mut Unstructured, depth: usize) -> Result<ListTag> {
+		let id = id(u, depth)?;
+		let length = u.arbitrary_len::<u8>()?;
+		let tags = (0..length).map(|_| tag(u, id, depth)).collect::<Result<Vec<_>>>()?;
+		Ok(ListTag::try_from(tags).expect("Elements have the same type."))
+	}
+
+	fn tag(u: &mut Unstructured, id: TagID, depth: usize) -> Result<Tag> {
+		Ok(match id {
+			TagID::Byte => Tag::Byte(u.arbitrary()?),
+			TagID::Short => Tag::Short(u.arbitrary()?),
+			TagID::Int => Tag::Int(u.arbitrary()?),
+			TagID::Long => Tag::Long(u.arbitrary()?),
+			TagID::Float => Tag::Float(u.arbitrary()?),
+			TagID::Double => Tag::Double(u.arbitrary()?),
+			TagID::ByteArray => Tag::ByteArray(u.arbitrary()?),
+			TagID::String => Tag::String(string(u)?),
+			TagID::List => Tag::List(list(u, depth + 1)?),
+			TagID::Compound => Tag::Compound(map(u, depth + 1)?),
+			TagID::IntArray => Tag::IntArray(u.arbitrary()?),
+			TagID::LongArray => Tag::LongArray(u.arbitrary()?),
+		})
+	}
+
+	impl<'a> Arbitrary<'a> for Tag {
+		fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+			let id = id(u, 0)?;
+			tag(u, id, 0)
+		}
+	}
+
+	impl<'a> Arbitrary<'a> for ListTag {
+		fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+			list(u, 0)
+		}
+	}
+
+	impl<'a> Arbitrary<'a> for NamedTag {
+		fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+			Ok(NamedTag::with_name(string(u)?, Tag::arbitrary(u)?))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn minimize_test() {
+		let tag = compound!(
+			("keep", Tag::IntArray(vec![5, 6, 7, 8])),
+			("drop", list!(1, 2, 3)),
+			("nested", compound!(("x", Tag::Long(9))))
+		);
+		let has_five = |tag: &Tag| matches!(tag, Tag::Compound(map) if matches!(map.get("keep"), Some(Tag::IntArray(values)) if values.contains(&5)));
+		assert_eq!(minimize(tag, has_five), compound!(("keep", Tag::IntArray(vec![5]))));
+		assert_eq!(minimize(Tag::List(ListTag::Compound(vec![Map::from_iter([("a".to_owned(), Tag::Int(1))])])), |tag| matches!(tag, Tag::List(_))), Tag::List(ListTag::Empty));
+	}
+
+	#[test]
+	fn corpus_test() -> Result<(), NbtError> {
+		let dir = std::env::temp_dir().join(format!("rustnbt-corpus-{}", std::process::id()));
+		let config = GeneratorConfig { seed: 3, ..Default::default() };
+		write_corpus(dir.join("binary"), &config, 3, CorpusFormat::Binary)?;
+		write_corpus(dir.join("snbt"), &config, 3, CorpusFormat::Snbt)?;
+		for index in 0..3 {
+			let binary: NamedTag = std::fs::read(dir.join(format!("binary/seed-{index}.nbt")))?.as_slice().read_nbt()?;
+			let text = std::fs::read_to_string(dir.join(format!("snbt/seed-{index}.snbt")))?;
+			// Compare as SNBT, since an empty List is read back from SNBT as an empty List of Bytes.
+			assert_eq!(snbt::to_string_canonical(binary.tag()), snbt::to_string_canonical(&Tag::parse(text)?));
+		}
+		std::fs::remove_dir_all(dir)?;
+		Ok(())
+	}
+
+	#[cfg(feature = "arbitrary")]
+	#[test]
+	fn arbitrary_test() -> Result<(), NbtError> {
+		use arbitrary::{ Arbitrary, Unstructured };
+		let data: Vec<u8> = (0..4096u32).map(|value| (value.wrapping_mul(2654435761) >> 13) as u8).collect();
+		let mut u = Unstructured::new(&data);
+		while !u.is_empty() {
+			let Ok(named) = NamedTag::arbitrary(&mut u) else { break };
+			let mut bytes = Vec::new();
+			bytes.write_nbt(&named)?;
+			let mut again = Vec::new();
+			again.write_nbt(&bytes.as_slice().read_nbt::<NamedTag>()?)?;
+			assert_eq!(bytes, again);
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,232 @@
+#![doc = r#"
+Generation of random tags, for load testing and for seeding fuzzing corpora.
+
+Tags are generated from a seed with a small built-in random number generator, so the same [GeneratorConfig] always generates
+the same tags, on every platform and with every version of this crate that has the same generator.
+
+```
+# use rustnbt::{ tag::Tag, generator::* };
+let config = GeneratorConfig { seed: 42, max_depth: 3, ..Default::default() };
+let tag = random_tag(&config);
+assert!(matches!(tag, Tag::Compound(_)));
+assert_eq!(tag, random_tag(&config));
+```
+"#]
+
+use crate::{
+	Map,
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+	},
+};
+
+/// The shape of the tags that a [Generator] creates.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GeneratorConfig {
+	pub seed: u64,
+	/// The deepest that Compounds and Lists are nested. The root Compound is at depth 0.
+	pub max_depth: usize,
+	/// The most entries in a Compound or elements in a List.
+	pub max_entries: usize,
+	/// The most elements in a ByteArray, IntArray, or LongArray.
+	pub max_array_len: usize,
+	/// The most characters in a String or key.
+	pub max_string_len: usize,
+	/// How often each tag type is chosen relative to the others, indexed by type ID minus one.
+	/// A weight of 0 disables the type. Compounds and Lists are not chosen at `max_depth`.
+	pub weights: [u32; 12],
+	/// The characters that keys are made of.
+	pub key_alphabet: Vec<char>,
+	/// The characters that String values are made of.
+	pub string_alphabet: Vec<char>,
+}
+
+impl Default for GeneratorConfig {
+	fn default() -> Self {
+		Self {
+			seed: 0,
+			max_depth: 4,
+			max_entries: 8,
+			max_array_len: 64,
+			max_string_len: 16,
+			//        B   S   I   L  F  D  BA  Str Lst Cmp IA LA
+			weights: [10, 5, 10, 5, 5, 5, 1, 10, 4, 6, 1, 1],
+			key_alphabet: ('a'..='z').chain('A'..='Z').chain(['_']).collect(),
+			string_alphabet: ('a'..='z').chain('0'..='9').chain([' ', ':', '_', '.', 'é', '§']).collect(),
+		}
+	}
+}
+
+impl GeneratorConfig {
+	/// Sets the weight of a tag type.
+	pub fn weight(mut self, id: TagID, weight: u32) -> Self {
+		self.weights[id as usize - 1] = weight;
+		self
+	}
+}
+
+/// Creates a random Compound from the configuration.
+pub fn random_tag(config: &GeneratorConfig) -> Tag {
+	Generator::new(config.clone()).next_tag()
+}
+
+/// Generates a sequence of random tags from one seed.
+#[derive(Clone, Debug)]
+pub struct Generator {
+	config: GeneratorConfig,
+	state: u64,
+}
+
+impl Generator {
+	/// Creates a generator that starts from the configured seed.
+	pub fn new(config: GeneratorConfig) -> Self {
+		let state = config.seed;
+		Self { config, state }
+	}
+
+	/// Creates the next random Compound.
+	pub fn next_tag(&mut self) -> Tag {
+		Tag::Compound(self.map(0))
+	}
+
+	/// SplitMix64, which is small and fast, and whose output does not change between versions of a dependency.
+	fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut value = self.state;
+		value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		value ^ (value >> 31)
+	}
+
+	/// A number in `0..=max`.
+	fn up_to(&mut self, max: usize) -> usize {
+		(self.next_u64() % (max as u64 + 1)) as usize
+	}
+
+	/// Picks a tag type by weight, or [None] if every allowed type has a weight of 0.
+	fn pick(&mut self, depth: usize) -> Option<TagID> {
+		let nested = depth < self.config.max_depth;
+		let weights: Vec<(TagID, u64)> = (1..=12u8)
+			.filter_map(|id| TagID::try_from(id).ok())
+			.filter(|id| nested || !matches!(id, TagID::List | TagID::Compound))
+			.map(|id| (id, self.config.weights[id as usize - 1] as u64))
+			.collect();
+		let total: u64 = weights.iter().map(|(_, weight)| weight).sum();
+		if total == 0 {
+			return None;
+		}
+		let mut roll = self.next_u64() % total;
+		weights.into_iter().find_map(|(id, weight)| {
+			if roll < weight {
+				return Some(id);
+			}
+			roll -= weight;
+			None
+		})
+	}
+
+	fn string(&mut self, key: bool, min: usize) -> String {
+		let length = min.max(self.up_to(self.config.max_string_len));
+		let alphabet_len = if key { self.config.key_alphabet.len() } else { self.config.string_alphabet.len() };
+		if alphabet_len == 0 {
+			return String::new();
+		}
+		(0..length).map(|_| {
+			let index = self.up_to(alphabet_len - 1);
+			if key { self.config.key_alphabet[index] } else { self.config.string_alphabet[index] }
+		}).collect()
+	}
+
+	fn array<T>(&mut self, value: impl Fn(u64) -> T) -> Vec<T> {
+		let length = self.up_to(self.config.max_array_len);
+		(0..length).map(|_| value(self.next_u64())).collect()
+	}
+
+	fn tag(&mut self, id: TagID, depth: usize) -> Tag {
+		let bits = self.next_u64();
+		match id {
+			TagID::Byte => Tag::Byte(bits as i8),
+			TagID::Short => Tag::Short(bits as i16),
+			TagID::Int => Tag::Int(bits as i32),
+			TagID::Long => Tag::Long(bits as i64),
+			// Floats are made from a fraction and a small range, since random bits are mostly huge numbers or NaN.
+			TagID::Float => Tag::Float((bits >> 40) as f32 / (1u64 << 24) as f32 * 256.0 - 128.0),
+			TagID::Double => Tag::Double((bits >> 11) as f64 / (1u64 << 53) as f64 * 65536.0 - 32768.0),
+			TagID::ByteArray => Tag::ByteArray(self.array(|bits| bits as i8)),
+			TagID::String => Tag::String(self.string(false, 0)),
+			TagID::List => Tag::List(self.list(depth + 1)),
+			TagID::Compound => Tag::Compound(self.map(depth + 1)),
+			TagID::IntArray => Tag::IntArray(self.array(|bits| bits as i32)),
+			TagID::LongArray => Tag::LongArray(self.array(|bits| bits as i64)),
+		}
+	}
+
+	fn map(&mut self, depth: usize) -> Map {
+		let mut map = Map::default();
+		let entries = self.up_to(self.config.max_entries);
+		for _ in 0..entries {
+			let Some(id) = self.pick(depth) else {
+				break;
+			};
+			let key = self.string(true, 1);
+			let tag = self.tag(id, depth);
+			map.insert(key, tag);
+		}
+		map
+	}
+
+	fn list(&mut self, depth: usize) -> ListTag {
+		let length = self.up_to(self.config.max_entries);
+		let Some(id) = self.pick(depth).filter(|_| length > 0) else {
+			return ListTag::Empty;
+		};
+		let tags: Vec<Tag> = (0..length).map(|_| self.tag(id, depth)).collect();
+		ListTag::try_from(tags).expect("Elements have the same type.")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ NbtError, io::*, tag::NamedTag };
+
+	fn depth(tag: &Tag) -> usize {
+		fn list_depth(list: &ListTag) -> usize {
+			match list {
+				ListTag::List(lists) => 1 + lists.iter().map(list_depth).max().unwrap_or(0),
+				ListTag::Compound(maps) => 1 + maps.iter().flat_map(|map| map.values().map(depth)).max().unwrap_or(0),
+				_ => 1,
+			}
+		}
+		match tag {
+			Tag::Compound(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+			Tag::List(list) => list_depth(list),
+			_ => 0,
+		}
+	}
+
+	#[test]
+	fn generator_test() -> Result<(), NbtError> {
+		let config = GeneratorConfig { seed: 7, max_depth: 2, ..Default::default() };
+		let mut generator = Generator::new(config.clone());
+		let first = generator.next_tag();
+		assert_eq!(first, random_tag(&config));
+		assert_ne!(first, generator.next_tag());
+		for _ in 0..50 {
+			let tag = generator.next_tag();
+			assert!(depth(&tag) <= 3);
+			let named = NamedTag::new(tag);
+			let mut bytes = Vec::new();
+			bytes.write_nbt(&named)?;
+			assert_eq!(bytes.as_slice().read_nbt::<NamedTag>()?, named);
+		}
+
+		let strings = GeneratorConfig { seed: 1, weights: [0; 12], ..Default::default() }.weight(TagID::String, 1);
+		let Tag::Compound(map) = random_tag(&strings) else { unreachable!() };
+		assert!(map.values().all(|tag| matches!(tag, Tag::String(_))));
+		assert_eq!(random_tag(&GeneratorConfig { weights: [0; 12], ..Default::default() }), Tag::Compound(Map::default()));
+		Ok(())
+	}
+}
@@ -0,0 +1,211 @@
+#![doc = r#"
+Helpers for using binary NBT files with git.
+
+[textconv] renders an NBT file as canonical SNBT, so `git diff` can show readable changes.
+Add `*.dat diff=nbt` to `.gitattributes` and point `diff.nbt.textconv` at a program that prints [textconv] of its argument.
+
+[merge_files] is a three-way merge driver. Changes made on only one side are merged key by key through Compounds.
+When both sides changed the same value differently, the merged file keeps our value and the conflict is returned,
+so the driver can print [render_conflicts] and exit with a failure code. Point `merge.nbt.driver` at a program that calls
+[merge_files] with `%O %A %B`.
+
+```
+# use rustnbt::{ compound, tag::Tag, git::* };
+let base = compound!(("Health", Tag::Float(20.0)), ("Level", 1));
+let ours = compound!(("Health", Tag::Float(10.0)), ("Level", 1));
+let theirs = compound!(("Health", Tag::Float(20.0)), ("Level", 2));
+let merged = merge(&base, &ours, &theirs);
+assert!(merged.conflicts.is_empty());
+assert_eq!(merged.tag, compound!(("Health", Tag::Float(10.0)), ("Level", 2)));
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	io::*,
+	path::{ PathSegment, TagPath },
+	snbt,
+	tag::{
+		Tag,
+		NamedTag,
+	},
+};
+use std::{
+	collections::BTreeSet,
+	fmt::Write as _,
+	io::Write,
+	path::Path,
+};
+
+/// A value that both sides of a merge changed differently. `None` means the value was absent on that side.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MergeConflict {
+	pub path: TagPath,
+	pub base: Option<Tag>,
+	pub ours: Option<Tag>,
+	pub theirs: Option<Tag>,
+}
+
+/// The result of [merge]. Conflicting values are taken from our side.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MergeResult {
+	pub tag: Tag,
+	pub conflicts: Vec<MergeConflict>,
+}
+
+/// Reads an NBT file, which may be compressed with gzip or zlib, and renders its root tag as canonical SNBT.
+/// The root name is not included.
+pub fn textconv<P: AsRef<Path>>(path: P) -> Result<String, NbtError> {
+	let root = crate::clipboard::from_binary(&std::fs::read(path)?)?;
+	let mut text = snbt::to_string_canonical(root.tag());
+	text.push('\n');
+	Ok(text)
+}
+
+/// Merges the changes that `ours` and `theirs` made to `base`.
+pub fn merge(base: &Tag, ours: &Tag, theirs: &Tag) -> MergeResult {
+	let mut conflicts = Vec::new();
+	let tag = merge_values(&mut TagPath::new(), Some(base), Some(ours), Some(theirs), &mut conflicts)
+		.unwrap_or_else(|| ours.clone());
+	MergeResult { tag, conflicts }
+}
+
+fn merge_values(path: &mut TagPath, base: Option<&Tag>, ours: Option<&Tag>, theirs: Option<&Tag>, conflicts: &mut Vec<MergeConflict>) -> Option<Tag> {
+	if ours == theirs || theirs == base {
+		return ours.cloned();
+	}
+	if ours == base {
+		return theirs.cloned();
+	}
+	let empty = Map::default();
+	let base_map = match base {
+		Some(Tag::Compound(map)) => Some(map),
+		None => Some(&empty),
+		Some(_) => None,
+	};
+	match (base_map, ours, theirs) {
+		(Some(base), Some(Tag::Compound(ours)), Some(Tag::Compound(theirs))) => {
+			let keys: BTreeSet<&String> = base.keys().chain(ours.keys()).chain(theirs.keys()).collect();
+			let mut merged = Map::default();
+			for key in keys {
+				path.push(PathSegment::Key(key.clone()));
+				if let Some(tag) = merge_values(path, base.get(key), ours.get(key), theirs.get(key), conflicts) {
+					merged.insert(key.clone(), tag);
+				}
+				path.pop();
+			}
+			Some(Tag::Compound(merged))
+		}
+		_ => {
+			conflicts.push(MergeConflict {
+				path: path.clone(),
+				base: base.cloned(),
+				ours: ours.cloned(),
+				theirs: theirs.cloned(),
+			});
+			ours.cloned()
+		}
+	}
+}
+
+/// Renders conflicts with git style conflict markers, with each side written as `path: <SNBT>`.
+pub fn render_conflicts(conflicts: &[MergeConflict]) -> String {
+	let mut text = String::new();
+	let mut side = |text: &mut String, path: &TagPath, value: &Option<Tag>| {
+		let value = value.as_ref().map_or_else(|| "<absent>".to_owned(), snbt::to_string);
+		writeln!(text, "{path}: {value}").unwrap();
+	};
+	for conflict in conflicts {
+		text.push_str("<<<<<<< ours\n");
+		side(&mut text, &conflict.path, &conflict.ours);
+		text.push_str("||||||| base\n");
+		side(&mut text, &conflict.path, &conflict.base);
+		text.push_str("=======\n");
+		side(&mut text, &conflict.path, &conflict.theirs);
+		text.push_str(">>>>>>> theirs\n");
+	}
+	text
+}
+
+/// Merges three NBT files and writes the result to `ours`, using the same compression that `ours` had.
+/// The root name is taken from `ours`. Returns the conflicts, which were resolved by keeping our values.
+pub fn merge_files<P1, P2, P3>(base: P1, ours: P2, theirs: P3) -> Result<Vec<MergeConflict>, NbtError>
+where
+	P1: AsRef<Path>,
+	P2: AsRef<Path>,
+	P3: AsRef<Path>,
+{
+	let base = crate::clipboard::from_binary(&std::fs::read(base)?)?;
+	let ours_bytes = std::fs::read(ours.as_ref())?;
+	let our_root = crate::clipboard::from_binary(&ours_bytes)?;
+	let theirs = crate::clipboard::from_binary(&std::fs::read(theirs)?)?;
+	let merged = merge(base.tag(), our_root.tag(), theirs.tag());
+	let root = NamedTag::with_name(our_root.name(), merged.tag);
+	let mut output = Vec::new();
+	match ours_bytes.as_slice() {
+		[0x1F, 0x8B, ..] => {
+			let mut encoder = flate2::write::GzEncoder::new(&mut output, flate2::Compression::default());
+			encoder.write_nbt(&root)?;
+			encoder.finish()?;
+		}
+		[0x78, ..] => {
+			let mut encoder = flate2::write::ZlibEncoder::new(&mut output, flate2::Compression::default());
+			encoder.write_nbt(&root)?;
+			encoder.finish()?;
+		}
+		_ => {
+			output.write_nbt(&root)?;
+		}
+	}
+	std::fs::File::create(ours)?.write_all(&output)?;
+	Ok(merged.conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn merge_test() {
+		let base = compound!(("a", 1), ("b", 1), ("c", 1), ("nested", compound!(("x", 1))), ("list", list!(1)));
+		let ours = compound!(("a", 2), ("b", 1), ("c", 2), ("nested", compound!(("x", 1), ("y", 1))), ("list", list!(2)));
+		let theirs = compound!(("a", 1), ("b", 3), ("nested", compound!(("x", 2))), ("list", list!(3)), ("new", 1));
+		let merged = merge(&base, &ours, &theirs);
+		assert_eq!(merged.tag, compound!(("a", 2), ("b", 3), ("c", 2), ("nested", compound!(("x", 2), ("y", 1))), ("list", list!(2)), ("new", 1)));
+		assert_eq!(merged.conflicts, vec![
+			MergeConflict { path: TagPath::new().key("c"), base: Some(Tag::Int(1)), ours: Some(Tag::Int(2)), theirs: None },
+			MergeConflict { path: TagPath::new().key("list"), base: Some(list!(1)), ours: Some(list!(2)), theirs: Some(list!(3)) },
+		]);
+		assert_eq!(render_conflicts(&merged.conflicts[..1]), concat!(
+			"<<<<<<< ours\n",
+			"c: 2\n",
+			"||||||| base\n",
+			"c: 1\n",
+			"=======\n",
+			"c: <absent>\n",
+			">>>>>>> theirs\n",
+		));
+	}
+
+	#[test]
+	fn merge_files_test() -> Result<(), NbtError> {
+		let dir = std::env::temp_dir().join("rustnbt_git_merge_test");
+		std::fs::create_dir_all(&dir)?;
+		let write = |name: &str, tag: Tag| -> Result<(), NbtError> {
+			let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(dir.join(name))?, flate2::Compression::default());
+			encoder.write_nbt(&NamedTag::with_name("Data", tag))?;
+			encoder.finish()?;
+			Ok(())
+		};
+		write("base.dat", compound!(("a", 1), ("b", 1)))?;
+		write("ours.dat", compound!(("a", 2), ("b", 1)))?;
+		write("theirs.dat", compound!(("a", 1), ("b", 2)))?;
+		let conflicts = merge_files(dir.join("base.dat"), dir.join("ours.dat"), dir.join("theirs.dat"))?;
+		assert!(conflicts.is_empty());
+		assert_eq!(textconv(dir.join("ours.dat"))?, "{\n    a: 2,\n    b: 2\n}\n");
+		std::fs::remove_dir_all(&dir)?;
+		Ok(())
+	}
+}
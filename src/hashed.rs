@@ -0,0 +1,239 @@
+#![doc = r#"
+Content hashes and fast equality for large trees.
+
+[Tag::content_hash] hashes a tag by its contents. The hash of a Compound does not depend on the order of its entries,
+and tags that are equal always have the same hash, so it can be compared before the tags themselves.
+[HashedTag] shares a tag through an [Arc] and remembers its hash, which makes comparing and deduplicating
+millions of tags cheap: tags with different hashes are never compared deeply.
+
+```
+# use rustnbt::{ compound, hashed::HashedTag };
+# use std::collections::HashSet;
+let items = [
+	compound!(("id", "minecraft:stone"), ("Count", 64)),
+	compound!(("id", "minecraft:dirt"), ("Count", 1)),
+	compound!(("Count", 64), ("id", "minecraft:stone")),
+];
+let unique: HashSet<HashedTag> = items.into_iter().map(HashedTag::new).collect();
+assert_eq!(unique.len(), 2);
+```
+"#]
+
+use crate::{
+	Map,
+	tag::{
+		Tag,
+		ListTag,
+	},
+};
+use std::{
+	collections::hash_map::DefaultHasher,
+	hash::{ Hash, Hasher },
+	ops::Deref,
+	sync::Arc,
+};
+
+impl Tag {
+	/// Hashes the contents of this tag. Equal tags have equal hashes, regardless of the order of Compound entries.<br>
+	/// The hash uses fixed keys, so it is the same on every run, but it may change between versions of Rust.
+	pub fn content_hash(&self) -> u64 {
+		let mut state = DefaultHasher::new();
+		hash_tag(self, &mut state);
+		state.finish()
+	}
+
+	/// Compares two tags, checking that they are the same tag, then their types and lengths, before comparing their contents.
+	pub fn fast_eq(&self, other: &Tag) -> bool {
+		if std::ptr::eq(self, other) {
+			return true;
+		}
+		let same_shape = match (self, other) {
+			(Tag::Compound(a), Tag::Compound(b)) => a.len() == b.len(),
+			(Tag::List(a), Tag::List(b)) => a.id() == b.id() && a.len() == b.len(),
+			(Tag::String(a), Tag::String(b)) => a.len() == b.len(),
+			(Tag::ByteArray(a), Tag::ByteArray(b)) => a.len() == b.len(),
+			(Tag::IntArray(a), Tag::IntArray(b)) => a.len() == b.len(),
+			(Tag::LongArray(a), Tag::LongArray(b)) => a.len() == b.len(),
+			(a, b) => a.id() == b.id(),
+		};
+		same_shape && self == other
+	}
+}
+
+// Zeroes are hashed as positive, since `0.0 == -0.0`.
+fn hash_f32<H: Hasher>(value: f32, state: &mut H) {
+	state.write_u32(if value == 0.0 { 0 } else { value.to_bits() });
+}
+
+fn hash_f64<H: Hasher>(value: f64, state: &mut H) {
+	state.write_u64(if value == 0.0 { 0 } else { value.to_bits() });
+}
+
+fn hash_tag<H: Hasher>(tag: &Tag, state: &mut H) {
+	state.write_u8(tag.id() as u8);
+	match tag {
+		Tag::Byte(value) => value.hash(state),
+		Tag::Short(value) => value.hash(state),
+		Tag::Int(value) => value.hash(state),
+		Tag::Long(value) => value.hash(state),
+		Tag::Float(value) => hash_f32(*value, state),
+		Tag::Double(value) => hash_f64(*value, state),
+		Tag::ByteArray(values) => values.hash(state),
+		Tag::String(value) => value.hash(state),
+		Tag::List(list) => hash_list(list, state),
+		Tag::Compound(map) => hash_map(map, state),
+		Tag::IntArray(values) => values.hash(state),
+		Tag::LongArray(values) => values.hash(state),
+	}
+}
+
+fn hash_list<H: Hasher>(list: &ListTag, state: &mut H) {
+	// Empty has the Byte type, and hashes the same as an empty List of Bytes.
+	state.write_u8(list.id() as u8);
+	state.write_usize(list.len());
+	if list.is_empty() {
+		return;
+	}
+	match list {
+		ListTag::Empty => {}
+		ListTag::Byte(values) => values.hash(state),
+		ListTag::Short(values) => values.hash(state),
+		ListTag::Int(values) => values.hash(state),
+		ListTag::Long(values) => values.hash(state),
+		ListTag::Float(values) => values.iter().for_each(|value| hash_f32(*value, state)),
+		ListTag::Double(values) => values.iter().for_each(|value| hash_f64(*value, state)),
+		ListTag::ByteArray(values) => values.hash(state),
+		ListTag::String(values) => values.hash(state),
+		ListTag::List(lists) => lists.iter().for_each(|list| hash_list(list, state)),
+		ListTag::Compound(maps) => maps.iter().for_each(|map| hash_map(map, state)),
+		ListTag::IntArray(values) => values.hash(state),
+		ListTag::LongArray(values) => values.hash(state),
+	}
+}
+
+/// Entries are hashed on their own and summed, so that the order they are stored in does not matter.
+fn hash_map<H: Hasher>(map: &Map, state: &mut H) {
+	let sum = map.iter().fold(0u64, |sum, (key, value)| {
+		let mut entry = DefaultHasher::new();
+		key.hash(&mut entry);
+		hash_tag(value, &mut entry);
+		sum.wrapping_add(entry.finish())
+	});
+	state.write_usize(map.len());
+	state.write_u64(sum);
+}
+
+/// A shared [Tag] with its [content hash](Tag::content_hash), which is computed once.<br>
+/// Clones share the tag. Equality compares the hashes before the tags, and [Hash] uses the stored hash.
+/// Like [Tag], a tag that holds a NaN is only equal to itself through a clone of the same [HashedTag].
+#[derive(Clone)]
+pub struct HashedTag {
+	tag: Arc<Tag>,
+	hash: u64,
+}
+
+impl HashedTag {
+	/// Hashes a tag.
+	pub fn new(tag: Tag) -> Self {
+		Self::from_arc(Arc::new(tag))
+	}
+
+	/// Hashes a shared tag.
+	pub fn from_arc(tag: Arc<Tag>) -> Self {
+		let hash = tag.content_hash();
+		Self { tag, hash }
+	}
+
+	/// The tag.
+	pub fn tag(&self) -> &Tag {
+		&self.tag
+	}
+
+	/// The shared tag.
+	pub fn arc(&self) -> &Arc<Tag> {
+		&self.tag
+	}
+
+	/// The stored content hash.
+	pub fn content_hash(&self) -> u64 {
+		self.hash
+	}
+
+	/// Returns the shared tag.
+	pub fn into_arc(self) -> Arc<Tag> {
+		self.tag
+	}
+}
+
+impl PartialEq for HashedTag {
+	fn eq(&self, other: &Self) -> bool {
+		Arc::ptr_eq(&self.tag, &other.tag) || (self.hash == other.hash && self.tag.fast_eq(&other.tag))
+	}
+}
+
+impl Eq for HashedTag {}
+
+impl Hash for HashedTag {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		state.write_u64(self.hash);
+	}
+}
+
+impl Deref for HashedTag {
+	type Target = Tag;
+
+	fn deref(&self) -> &Tag {
+		&self.tag
+	}
+}
+
+impl From<Tag> for HashedTag {
+	fn from(tag: Tag) -> Self {
+		Self::new(tag)
+	}
+}
+
+impl std::fmt::Debug for HashedTag {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_tuple("HashedTag").field(&self.tag).finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn hash_test() {
+		let mut forward = Map::default();
+		let mut backward = Map::default();
+		for index in 0..32 {
+			forward.insert(format!("key{index}"), Tag::Int(index));
+			backward.insert(format!("key{}", 31 - index), Tag::Int(31 - index));
+		}
+		let (forward, backward) = (Tag::Compound(forward), Tag::Compound(backward));
+		assert_eq!(forward.content_hash(), backward.content_hash());
+		assert!(forward.fast_eq(&backward));
+		assert_eq!(Tag::Float(0.0).content_hash(), Tag::Float(-0.0).content_hash());
+		assert_eq!(Tag::List(ListTag::Empty).content_hash(), Tag::List(ListTag::Byte(Vec::new())).content_hash());
+		assert_ne!(Tag::Int(1).content_hash(), Tag::Long(1).content_hash());
+		assert_ne!(list!(1, 2).content_hash(), list!(2, 1).content_hash());
+		assert_ne!(compound!(("a", 1), ("b", 2)).content_hash(), compound!(("a", 2), ("b", 1)).content_hash());
+		assert!(!Tag::from("ab").fast_eq(&Tag::from("abc")));
+		assert!(!list!(1, 2).fast_eq(&list!(1, 3)));
+	}
+
+	#[test]
+	fn hashed_tag_test() {
+		let stone = HashedTag::new(compound!(("id", "minecraft:stone")));
+		let copy = HashedTag::new(compound!(("id", "minecraft:stone")));
+		let nan = HashedTag::new(Tag::Double(f64::NAN));
+		assert_eq!(stone, copy);
+		assert_eq!(stone.content_hash(), copy.content_hash());
+		assert_ne!(stone, HashedTag::new(compound!(("id", "minecraft:dirt"))));
+		assert_eq!(nan, nan.clone());
+		assert_ne!(nan, HashedTag::new(Tag::Double(f64::NAN)));
+		assert_eq!(stone.id(), crate::tag::TagID::Compound);
+	}
+}
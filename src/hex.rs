@@ -0,0 +1,59 @@
+#![doc = r#"
+Hexadecimal text of bytes, two digits per byte, which mods often use for binary payloads.
+"#]
+
+use crate::{ NbtError, tag::Tag };
+use std::fmt::Write;
+
+/// Encodes bytes as lowercase digits.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+	let mut text = String::with_capacity(bytes.len() * 2);
+	for byte in bytes {
+		write!(text, "{byte:02x}").unwrap();
+	}
+	text
+}
+
+/// Decodes digits in either case. Whitespace and prefixes such as `0x` are not allowed.
+pub(crate) fn decode(text: &str) -> Result<Vec<u8>, NbtError> {
+	let invalid = || crate::invalid_data("Invalid hexadecimal.");
+	if text.len() % 2 == 1 {
+		return Err(invalid());
+	}
+	let digit = |digit: u8| (digit as char).to_digit(16).ok_or_else(invalid);
+	text.as_bytes().chunks_exact(2).map(|pair| Ok((digit(pair[0])? << 4 | digit(pair[1])?) as u8)).collect()
+}
+
+impl Tag {
+	/// Decodes hexadecimal text into a ByteArray.
+	pub fn byte_array_from_hex(text: &str) -> Result<Tag, NbtError> {
+		Ok(Tag::ByteArray(bytemuck::cast_vec(decode(text)?)))
+	}
+
+	/// Encodes a ByteArray as lowercase hexadecimal text. Returns [None] for other tags.
+	pub fn to_hex(&self) -> Option<String> {
+		match self {
+			Tag::ByteArray(values) => Some(encode(bytemuck::cast_slice(values))),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn hex_test() -> Result<(), NbtError> {
+		assert_eq!(encode(&[0x00, 0x7F, 0xAB, 0xFF]), "007fabff");
+		assert_eq!(decode("007FabfF")?, [0x00, 0x7F, 0xAB, 0xFF]);
+		assert!(decode("abc").is_err());
+		assert!(decode("0x").is_err());
+		assert!(decode("+1").is_err());
+		let tag = Tag::byte_array_from_hex("80ff01")?;
+		assert_eq!(tag, Tag::ByteArray(vec![-128, -1, 1]));
+		assert_eq!(tag.to_hex().as_deref(), Some("80ff01"));
+		assert_eq!(Tag::from("80ff01").to_hex(), None);
+		Ok(())
+	}
+}
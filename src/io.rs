@@ -11,6 +11,10 @@ use crate::{
 		NamedTag,
 	},
 	family::*,
+	parse::{ ParseOptions, ParseOutcome, Warning },
+	path::{ PathSegment, TagPath },
+	region::ChunkCompression,
+	sink::{ NbtSink, Node, Scalar, encode },
 	tag_info_table,
 };
 use std::io::{ Read, Write };
@@ -40,6 +44,8 @@ impl<Reader: Read> ReadNbt for Reader {
 pub trait WriteNbt: Write {
 	/// Write NBT (anything that implements NbtWrite).
 	fn write_nbt<T: NbtWrite>(&mut self, value: &T) -> Result<usize, NbtError>;
+	/// Write a [NamedTag] with [WriteOptions].
+	fn write_nbt_with(&mut self, value: &NamedTag, options: &WriteOptions) -> Result<usize, NbtError>;
 }
 
 // std::io::Write extension method write_nbt implementation.
@@ -48,6 +54,11 @@ impl<Writer: Write> WriteNbt for Writer {
 	fn write_nbt<T: NbtWrite>(&mut self, value: &T) -> Result<usize, NbtError> {
 		value.nbt_write(self)
 	}
+
+	/// Write a [NamedTag] with [WriteOptions].
+	fn write_nbt_with(&mut self, value: &NamedTag, options: &WriteOptions) -> Result<usize, NbtError> {
+		options.write(self, value)
+	}
 }
 
 /// A trait for reading values from readers.
@@ -59,6 +70,11 @@ impl<Writer: Write> WriteNbt for Writer {
 pub trait NbtRead: Sized {
 	/// Attempt to read a value from a reader.
 	fn nbt_read<R: Read>(reader: &mut R) -> Result<Self, NbtError>;
+
+	/// Reads a number of values that follow each other, such as the elements of a List or an array.
+	fn nbt_read_many<R: Read>(reader: &mut R, length: usize) -> Result<Vec<Self>, NbtError> {
+		(0..length).map(|_| Self::nbt_read(reader)).collect()
+	}
 }
 
 /// A trait for writing values to writers.
@@ -70,6 +86,14 @@ pub trait NbtRead: Sized {
 pub trait NbtWrite {
 	/// Write a value to a writer.
 	fn nbt_write<W: Write>(&self, writer: &mut W) -> Result<usize, NbtError>;
+
+	/// Writes values one after another, such as the elements of a List or an array.
+	fn nbt_write_many<W: Write>(values: &[Self], writer: &mut W) -> Result<usize, NbtError>
+	where
+		Self: Sized,
+	{
+		values.iter().map(|value| value.nbt_write(writer)).sum()
+	}
 }
 
 macro_rules! tag_io {
@@ -102,18 +126,10 @@ macro_rules! tag_io {
 		After reading the name, it will read the tag itself, using the [Tag] ID that was read to
 		determine which [Tag] type to read. Typically this will be a Compound tag (ID: 10), or a List tag (ID: 9).
 		There is no restriction on what type this tag can be, though.
+		Tags may be nested up to [DEFAULT_MAX_DEPTH] deep. Use [read_named_tag_with_stats] for another limit.
 		"]
 		pub fn read_named_tag<R: Read>(reader: &mut R) -> Result<(String, Tag), NbtError> {
-			let id = TagID::nbt_read(reader)?;
-			let name = String::nbt_read(reader)?;
-			let tag = match id {
-				$(
-					TagID::$title => {
-						Tag::$title(<$type>::nbt_read(reader)?)
-					}
-				)+
-			};
-			Ok((name, tag))
+			read_named_tag_with_stats(reader, DEFAULT_MAX_DEPTH).map(|(name, tag, _)| (name, tag))
 		}
 
 		impl NbtSize for Tag {
@@ -139,91 +155,690 @@ macro_rules! tag_io {
 			}
 		}
 
-		impl NbtRead for ListTag {
-			#[doc = "Attempt to read a [ListTag] from a reader."]
-			fn nbt_read<R: Read>(reader: &mut R) -> Result<Self, NbtError> {
-				let id = TagID::nbt_read(reader);
-				if matches!(id, Err($crate::NbtError::End)) {
-					u32::nbt_read(reader)?;
-					return Ok(ListTag::Empty);
-				}
-				match id {
-					$(
-						Ok(TagID::$title) => {
-							let length = u32::nbt_read(reader)?;
-							Ok(ListTag::$title(
-								read_array(reader, length as usize)?
-							))
-						},
-					)+
-					Err($crate::NbtError::End) => {
-						u32::nbt_read(reader)?;
-						Ok(ListTag::Empty)
-					},
-					Err(err) => {
-						Err(err)
-					},
-				}
+		/// Reads the payload of a tag that is not a Compound or List.
+		fn read_flat_tag<R: Read>(reader: &mut R, id: TagID) -> Result<Tag, NbtError> {
+			Ok(match id {
+				$(
+					TagID::$title => Tag::$title(<$type>::nbt_read(reader)?),
+				)+
+			})
+		}
+
+		/// Reads the elements of a List whose elements are not Compounds or Lists.
+		fn read_flat_list<R: Read>(reader: &mut R, id: TagID, length: usize) -> Result<ListTag, NbtError> {
+			Ok(match id {
+				$(
+					TagID::$title => ListTag::$title(read_array(reader, length)?),
+				)+
+			})
+		}
+
+		/// Writes a List whose elements are not Compounds or Lists.
+		fn write_flat_list<W: Write>(list: &ListTag, writer: &mut W) -> Result<usize, NbtError> {
+			match list {
+				$(
+					ListTag::$title(list) => {
+						TagID::$title.nbt_write(writer)?;
+						list.nbt_write(writer).map(|size| size + 1)
+					}
+				)+
+				ListTag::Empty => {
+					0u8.nbt_write(writer)?;
+					0u32.nbt_write(writer)?;
+					Ok(5)
+				},
 			}
 		}
 
-		impl NbtWrite for ListTag {
-			#[doc = "Attmept to write a [ListTag] to a writer."]
-			fn nbt_write<W: Write>(&self, writer: &mut W) -> Result<usize,NbtError> {
-				match self {
-					$(
-						ListTag::$title(list) => {
-							TagID::$title.nbt_write(writer)?;
-							list.nbt_write(writer).map(|size| size + 1)
-						}
-					)+
-					ListTag::Empty => {
-						0u8.nbt_write(writer)?;
-						0u32.nbt_write(writer)?;
-						Ok(5)
+		/// Writes the payload of a tag that is not a Compound or List.
+		fn write_flat_tag<W: Write>(tag: &Tag, writer: &mut W) -> Result<usize, NbtError> {
+			match tag {
+				$(
+					Tag::$title(tag) => tag.nbt_write(writer),
+				)+
+			}
+		}
+	};
+}
+
+/// How deeply Compounds and Lists may be nested when reading, which is the limit that Minecraft uses.
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// Statistics gathered while reading binary NBT, from [read_named_tag_with_stats].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct ParseStats {
+	/// The deepest nesting of Compounds and Lists. A root Compound holding only numbers has a depth of 1.
+	pub max_depth: usize,
+	/// The number of tags read, counting the root and every List element.
+	pub tags: usize,
+}
+
+/// Like [read_named_tag], with a limit on how deeply tags may be nested, returning statistics about what was read.<br>
+/// Tags are read with a stack on the heap instead of by recursion, so hostile input can not overflow the thread stack
+/// with any limit. Note that dropping or comparing a tag is still recursive, so very deep trees should be avoided.
+pub fn read_named_tag_with_stats<R: Read>(reader: &mut R, max_depth: usize) -> Result<(String, Tag, ParseStats), NbtError> {
+	let id = TagID::nbt_read(reader)?;
+	let name = String::nbt_read(reader)?;
+	let mut stats = ParseStats::default();
+	let tag = read_payload(reader, id, max_depth, &mut stats, &mut ParseOptions::default())?;
+	Ok((name, tag, stats))
+}
+
+/// Reads a whole buffer of binary NBT leniently, with [ParseOptions].<br>
+/// Duplicate keys are handled by the key collision policy of the options, and bytes after the root tag are an error
+/// unless [ParseOptions::allow_trailing_data] is set.
+pub fn read_named_tag_with(bytes: &[u8], options: &ParseOptions) -> Result<ParseOutcome<NamedTag>, NbtError> {
+	let mut options = options.clone();
+	let mut reader = bytes;
+	let id = TagID::nbt_read(&mut reader)?;
+	let name = String::nbt_read(&mut reader)?;
+	let tag = read_payload(&mut reader, id, options.max_depth, &mut ParseStats::default(), &mut options)?;
+	if !reader.is_empty() {
+		if !options.allow_trailing_data {
+			return Err(crate::invalid_data(&format!("Found {} bytes after the root tag.", reader.len())));
+		}
+		options.warn(Warning::TrailingData { offset: bytes.len() - reader.len(), length: reader.len() });
+	}
+	Ok(options.finish(NamedTag::with_name(name, tag)))
+}
+
+/// The compression of binary NBT from its first bytes: [GZip](ChunkCompression::GZip) and
+/// [Zlib](ChunkCompression::Zlib) by their headers, and [Uncompressed](ChunkCompression::Uncompressed) otherwise,
+/// since no tag ID is `0x1F` or `0x78`.
+pub fn detect_compression(bytes: &[u8]) -> ChunkCompression {
+	match bytes {
+		[0x1f, 0x8b, ..] => ChunkCompression::GZip,
+		[0x78, _, ..] => ChunkCompression::Zlib,
+		_ => ChunkCompression::Uncompressed,
+	}
+}
+
+/// Reads binary NBT that may be compressed with gzip or zlib, such as `level.dat`, a player file, or a structure.
+/// The compression is found with [detect_compression].
+/// ### Example
+/// ```no_run
+/// # use rustnbt::io::read_compressed;
+/// let level = read_compressed(std::fs::File::open("saves/New World/level.dat").unwrap()).unwrap();
+/// ```
+pub fn read_compressed<R: Read>(reader: R) -> Result<NamedTag, NbtError> {
+	use std::io::BufRead;
+	let mut reader = std::io::BufReader::new(reader);
+	match detect_compression(reader.fill_buf()?) {
+		ChunkCompression::GZip => flate2::read::GzDecoder::new(reader).read_nbt(),
+		ChunkCompression::Zlib => flate2::read::ZlibDecoder::new(reader).read_nbt(),
+		_ => reader.read_nbt(),
+	}
+}
+
+/// Writes binary NBT compressed with gzip, zlib, or not at all. Returns the number of bytes of NBT before
+/// compression.<br>
+/// The game writes `level.dat` and player files with gzip. LZ4 is only used in region files, and is an error here.
+pub fn write_compressed<W: Write>(writer: W, root: &NamedTag, compression: ChunkCompression) -> Result<usize, NbtError> {
+	let mut writer = std::io::BufWriter::new(writer);
+	let written = match compression {
+		ChunkCompression::GZip => {
+			let mut encoder = flate2::write::GzEncoder::new(&mut writer, flate2::Compression::default());
+			let written = encoder.write_nbt(root)?;
+			encoder.finish()?;
+			written
+		}
+		ChunkCompression::Zlib => {
+			let mut encoder = flate2::write::ZlibEncoder::new(&mut writer, flate2::Compression::default());
+			let written = encoder.write_nbt(root)?;
+			encoder.finish()?;
+			written
+		}
+		ChunkCompression::Uncompressed => writer.write_nbt(root)?,
+		ChunkCompression::Lz4 => return Err(crate::invalid_data("LZ4 compressed NBT is not supported.")),
+	};
+	writer.flush()?;
+	Ok(written)
+}
+
+/// The result of [IncrementalParser::poll_parse].
+#[derive(Clone, PartialEq, Debug)]
+pub enum ParsePoll {
+	/// The budget ran out before the root tag was finished.
+	Pending,
+	/// The root tag was finished.
+	Done(NamedTag),
+}
+
+/// Reads binary NBT in slices of bounded work, so that reading a large file can be spread over the ticks of a game
+/// server or the steps of a cooperative scheduler.<br>
+/// Each call of [poll_parse](Self::poll_parse) reads about as many tags as its budget, counted like [ParseStats::tags].
+/// Lists of numbers and arrays are read whole, so a slice can read more tags than its budget.
+/// ### Example
+/// ```
+/// # use rustnbt::{ compound, io::*, tag::NamedTag };
+/// let mut bytes = Vec::new();
+/// bytes.write_nbt(&NamedTag::new(compound!(("a", 1), ("b", 2), ("c", 3)))).unwrap();
+/// let mut parser = IncrementalParser::new(bytes.as_slice());
+/// let mut slices = 1;
+/// while parser.poll_parse(2).unwrap() == ParsePoll::Pending {
+///     slices += 1;
+/// }
+/// assert!(slices > 1);
+/// ```
+pub struct IncrementalParser<R> {
+	reader: R,
+	max_depth: usize,
+	stats: ParseStats,
+	state: ParserState,
+}
+
+enum ParserState {
+	Header,
+	Payload(String, PayloadReader),
+	Finished,
+}
+
+impl<R: Read> IncrementalParser<R> {
+	pub fn new(reader: R) -> Self {
+		Self::with_max_depth(reader, DEFAULT_MAX_DEPTH)
+	}
+
+	/// Creates a parser with a limit on how deeply tags may be nested.
+	pub fn with_max_depth(reader: R, max_depth: usize) -> Self {
+		Self { reader, max_depth, stats: ParseStats::default(), state: ParserState::Header }
+	}
+
+	/// Statistics about what has been read so far.
+	pub fn stats(&self) -> ParseStats {
+		self.stats
+	}
+
+	pub fn into_inner(self) -> R {
+		self.reader
+	}
+
+	/// Reads about `budget` more tags, returning [ParsePoll::Done] with the root tag once it is finished.<br>
+	/// A budget of 0 is treated as 1, so every call makes progress. After the root tag is finished or reading fails,
+	/// every call fails.
+	pub fn poll_parse(&mut self, budget: usize) -> Result<ParsePoll, NbtError> {
+		let limit = self.stats.tags.saturating_add(budget.max(1));
+		let (name, mut payload) = match std::mem::replace(&mut self.state, ParserState::Finished) {
+			ParserState::Header => {
+				let id = TagID::nbt_read(&mut self.reader)?;
+				let name = String::nbt_read(&mut self.reader)?;
+				(name, PayloadReader::start(&mut self.reader, id, Vec::new(), self.max_depth, &mut self.stats, &mut Pool::default())?)
+			}
+			ParserState::Payload(name, payload) => (name, payload),
+			ParserState::Finished => return Err(crate::invalid_data("The parser has already finished.")),
+		};
+		match payload.resume(&mut self.reader, self.max_depth, &mut self.stats, &mut ParseOptions::default(), limit, &mut Pool::default())? {
+			Some(tag) => Ok(ParsePoll::Done(NamedTag::with_name(name, tag))),
+			None => {
+				self.state = ParserState::Payload(name, payload);
+				Ok(ParsePoll::Pending)
+			}
+		}
+	}
+}
+
+/// A Compound or List that is being read. `key` is the key of the tag in its parent Compound.
+enum ReadFrame {
+	Compound { map: Map, key: Option<String> },
+	Compounds { maps: Vec<Map>, remaining: usize, key: Option<String> },
+	Lists { lists: Vec<ListTag>, remaining: usize, key: Option<String> },
+}
+
+impl ReadFrame {
+	fn key(&self) -> &Option<String> {
+		match self {
+			ReadFrame::Compound { key, .. } | ReadFrame::Compounds { key, .. } | ReadFrame::Lists { key, .. } => key,
+		}
+	}
+}
+
+/// Returns the path of the tag with the given key that is being read inside of the open frames.
+fn frame_path(frames: &[ReadFrame], key: &Option<String>) -> TagPath {
+	let mut path = TagPath::new();
+	let keys = frames.iter().skip(1).map(ReadFrame::key).chain([key]);
+	for (frame, key) in frames.iter().zip(keys) {
+		path.push(match frame {
+			ReadFrame::Compound { .. } => PathSegment::Key(key.clone().expect("Compound entries have keys.")),
+			ReadFrame::Compounds { maps, .. } => PathSegment::Index(maps.len()),
+			ReadFrame::Lists { lists, .. } => PathSegment::Index(lists.len()),
+		});
+	}
+	path
+}
+
+/// Reads the payload of a tag with the given type, keeping the Compounds and Lists that are being read on a stack.
+fn read_payload<R: Read>(
+	reader: &mut R,
+	id: TagID,
+	max_depth: usize,
+	stats: &mut ParseStats,
+	options: &mut ParseOptions,
+) -> Result<Tag, NbtError> {
+	let pool = &mut Pool::default();
+	let mut payload = PayloadReader::start(reader, id, Vec::new(), max_depth, stats, pool)?;
+	Ok(payload.resume(reader, max_depth, stats, options, usize::MAX, pool)?.expect("Reading without a limit finishes the tag."))
+}
+
+/// The payload of a tag that is being read, which can be paused between tags.
+struct PayloadReader {
+	stack: Vec<ReadFrame>,
+	finished: Option<(Option<String>, Tag)>,
+}
+
+impl PayloadReader {
+	/// Starts reading, using `stack` for the frames. The stack must be empty.
+	fn start<R: Read>(
+		reader: &mut R,
+		id: TagID,
+		mut stack: Vec<ReadFrame>,
+		max_depth: usize,
+		stats: &mut ParseStats,
+		pool: &mut Pool,
+	) -> Result<Self, NbtError> {
+		let finished = begin_read(reader, id, None, &mut stack, max_depth, stats, pool)?;
+		Ok(Self { stack, finished })
+	}
+
+	/// Reads until the tag is finished, returning it, or until `stats.tags` reaches `limit`, returning None.
+	fn resume<R: Read>(
+		&mut self,
+		reader: &mut R,
+		max_depth: usize,
+		stats: &mut ParseStats,
+		options: &mut ParseOptions,
+		limit: usize,
+		pool: &mut Pool,
+	) -> Result<Option<Tag>, NbtError> {
+		let Self { stack, finished } = self;
+		loop {
+			// A finished tag is added to the Compound or List it is in.
+			if let Some((key, tag)) = finished.take() {
+				let Some((frame, parents)) = stack.split_last_mut() else {
+					return Ok(Some(tag));
+				};
+				match (frame, tag) {
+					(ReadFrame::Compound { map, key: frame_key }, tag) => {
+						let parents = &*parents;
+						options.insert(map, || frame_path(parents, frame_key), key.expect("Compound entries have keys."), tag)?;
+					}
+					(ReadFrame::Compounds { maps, .. }, Tag::Compound(map)) => maps.push(map),
+					(ReadFrame::Lists { lists, .. }, Tag::List(list)) => lists.push(list),
+					_ => unreachable!("List elements are read with the type of the List."),
+				}
+			}
+			if stats.tags >= limit {
+				return Ok(None);
+			}
+			*finished = match stack.last_mut().expect("A frame is open until the root tag is finished.") {
+				ReadFrame::Compound { .. } => match u8::nbt_read(reader)? {
+					0 => match stack.pop() {
+						Some(ReadFrame::Compound { map, key }) => Some((key, Tag::Compound(map))),
+						_ => unreachable!(),
 					},
+					id => {
+						let id = TagID::try_from(id)?;
+						let name = pool.string(reader)?;
+						begin_read(reader, id, Some(name), stack, max_depth, stats, pool)?
+					}
+				},
+				ReadFrame::Compounds { remaining: 0, .. } | ReadFrame::Lists { remaining: 0, .. } => match stack.pop() {
+					Some(ReadFrame::Compounds { maps, key, .. }) => Some((key, Tag::List(ListTag::Compound(maps)))),
+					Some(ReadFrame::Lists { lists, key, .. }) => Some((key, Tag::List(ListTag::List(lists)))),
+					_ => unreachable!(),
+				},
+				ReadFrame::Compounds { remaining, .. } => {
+					*remaining -= 1;
+					begin_read(reader, TagID::Compound, None, stack, max_depth, stats, pool)?
 				}
+				ReadFrame::Lists { remaining, .. } => {
+					*remaining -= 1;
+					begin_read(reader, TagID::List, None, stack, max_depth, stats, pool)?
+				}
+			};
+		}
+	}
+}
+
+/// Starts reading a tag. Compounds and Lists of Compounds or Lists are pushed onto the stack, and other tags are read whole.
+fn begin_read<R: Read>(
+	reader: &mut R,
+	id: TagID,
+	key: Option<String>,
+	stack: &mut Vec<ReadFrame>,
+	max_depth: usize,
+	stats: &mut ParseStats,
+	pool: &mut Pool,
+) -> Result<Option<(Option<String>, Tag)>, NbtError> {
+	stats.tags += 1;
+	if matches!(id, TagID::Compound | TagID::List) {
+		let depth = stack.len() + 1;
+		if depth > max_depth {
+			return Err(crate::invalid_data(&format!("Tags are nested deeper than {max_depth}.")));
+		}
+		stats.max_depth = stats.max_depth.max(depth);
+	}
+	let tag = match id {
+		TagID::Compound => {
+			stack.push(ReadFrame::Compound { map: pool.map(), key });
+			return Ok(None);
+		}
+		TagID::List => {
+			let element = u8::nbt_read(reader)?;
+			let length = u32::nbt_read(reader)? as usize;
+			// Lists of End tags are read as empty, and can not have elements.
+			let list = match TagID::list_element(element)? {
+				None if length == 0 => ListTag::Empty,
+				None => return Err(crate::invalid_data("List of End tags has elements.")),
+				Some(element) => match element {
+					TagID::Compound => {
+						stack.push(ReadFrame::Compounds { maps: pool.maps(), remaining: length, key });
+						return Ok(None);
+					}
+					TagID::List => {
+						stack.push(ReadFrame::Lists { lists: pool.lists(), remaining: length, key });
+						return Ok(None);
+					}
+					TagID::String => ListTag::String((0..length).map(|_| pool.string(reader)).collect::<Result<_, _>>()?),
+					element => read_flat_list(reader, element, length)?,
+				},
+			};
+			stats.tags += list.len();
+			Tag::List(list)
+		}
+		TagID::String => Tag::String(pool.string(reader)?),
+		id => read_flat_tag(reader, id)?,
+	};
+	Ok(Some((key, tag)))
+}
+
+/// Statistics about how a [Decoder] reuses allocations.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct PoolStats {
+	/// The Strings, Compounds, and Lists of Compounds or Lists that were read into reused allocations.
+	pub reused: usize,
+	/// The ones that were read into new allocations, because there were none to reuse.
+	pub allocated: usize,
+	/// The allocations waiting to be reused.
+	pub pooled: usize,
+}
+
+/// The allocations of recycled tags, which are cleared and reused for the next tags that are read.
+#[derive(Default)]
+struct Pool {
+	strings: Vec<Vec<u8>>,
+	maps: Vec<Map>,
+	compound_lists: Vec<Vec<Map>>,
+	list_lists: Vec<Vec<ListTag>>,
+	reused: usize,
+	allocated: usize,
+}
+
+impl Pool {
+	fn take<T: Default>(items: &mut Vec<T>, reused: &mut usize, allocated: &mut usize) -> T {
+		match items.pop() {
+			Some(item) => {
+				*reused += 1;
+				item
+			}
+			None => {
+				*allocated += 1;
+				T::default()
 			}
 		}
+	}
+
+	/// Reads a String like [String::nbt_read], into a reused buffer if there is one.
+	fn string<R: Read>(&mut self, reader: &mut R) -> Result<String, NbtError> {
+		let length = u16::nbt_read(reader)? as usize;
+		let mut bytes = Self::take(&mut self.strings, &mut self.reused, &mut self.allocated);
+		bytes.resize(length, 0);
+		reader.read_exact(&mut bytes)?;
+		Ok(String::from_utf8(bytes)?)
+	}
+
+	fn map(&mut self) -> Map {
+		Self::take(&mut self.maps, &mut self.reused, &mut self.allocated)
+	}
+
+	fn maps(&mut self) -> Vec<Map> {
+		Self::take(&mut self.compound_lists, &mut self.reused, &mut self.allocated)
+	}
+
+	fn lists(&mut self) -> Vec<ListTag> {
+		Self::take(&mut self.list_lists, &mut self.reused, &mut self.allocated)
+	}
+
+	fn pooled(&self) -> usize {
+		self.strings.len() + self.maps.len() + self.compound_lists.len() + self.list_lists.len()
+	}
+}
+
+/// Reads binary NBT over and over with the same buffers, for services that decode many messages.<br>
+/// The stack of the reader is kept between reads, and tags that are done with can be given back with
+/// [recycle](Self::recycle) or [recycle_named](Self::recycle_named), so that the Strings, Compounds, and Lists of Compounds or Lists of the next tags are read
+/// into their allocations. Lists of numbers and arrays are not pooled.
+/// ### Example
+/// ```
+/// # use rustnbt::{ compound, io::*, tag::NamedTag };
+/// let mut message = Vec::new();
+/// message.write_nbt(&NamedTag::new(compound!(("id", "minecraft:stone"), ("Count", 1i8)))).unwrap();
+/// let mut decoder = Decoder::new();
+/// for _ in 0..3 {
+///     let tag = decoder.decode(&mut message.as_slice()).unwrap();
+///     decoder.recycle_named(tag);
+/// }
+/// // The name, the Compound, its keys, and the String are only allocated for the first read.
+/// let stats = decoder.pool_stats();
+/// assert_eq!((stats.allocated, stats.reused), (5, 10));
+/// ```
+pub struct Decoder {
+	max_depth: usize,
+	stack: Vec<ReadFrame>,
+	pool: Pool,
+	/// The tags that are being taken apart by [recycle](Self::recycle).
+	recycling: Vec<Tag>,
+	stats: ParseStats,
+}
+
+impl Default for Decoder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
 
-		impl NbtRead for Map {
-			#[doc = "Attempt to read a [Map] from a reader."]
-			fn nbt_read<R: Read>(reader: &mut R) -> Result<Self, NbtError> {
-				// Reading goes like this:
-				// Read TagID
-				// if TagID is not End or Unsupported,
-				//     Read string for name
-				//     Read tag
-				//     read next id
-				//     repeat until id is End or Unsupported
-				let mut map = Map::new();
-				let mut id = TagID::nbt_read(reader);
-				while !matches!(id, Err($crate::NbtError::End)) {
-					let name = String::nbt_read(reader)?;
-					let tag = match id {
-						$(
-							Ok(TagID::$title) => Tag::$title(<$type>::nbt_read(reader)?),
-						)+
-						Err(err) => return Err(err),
-					};
-					map.insert(name, tag);
-					id = TagID::nbt_read(reader);
+impl Decoder {
+	pub fn new() -> Self {
+		Self::with_max_depth(DEFAULT_MAX_DEPTH)
+	}
+
+	/// Creates a decoder with a limit on how deeply tags may be nested.
+	pub fn with_max_depth(max_depth: usize) -> Self {
+		Self { max_depth, stack: Vec::new(), pool: Pool::default(), recycling: Vec::new(), stats: ParseStats::default() }
+	}
+
+	/// Reads a named tag, like [read_named_tag].
+	pub fn decode<R: Read>(&mut self, reader: &mut R) -> Result<NamedTag, NbtError> {
+		self.stats = ParseStats::default();
+		let id = TagID::nbt_read(reader)?;
+		let name = self.pool.string(reader)?;
+		let mut stack = std::mem::take(&mut self.stack);
+		// A read that failed leaves its frames behind.
+		stack.clear();
+		let mut payload = PayloadReader::start(reader, id, stack, self.max_depth, &mut self.stats, &mut self.pool)?;
+		let tag = payload.resume(reader, self.max_depth, &mut self.stats, &mut ParseOptions::default(), usize::MAX, &mut self.pool);
+		self.stack = payload.stack;
+		Ok(NamedTag::with_name(name, tag?.expect("Reading without a limit finishes the tag.")))
+	}
+
+	/// Statistics about the last tag that was read.
+	pub fn stats(&self) -> ParseStats {
+		self.stats
+	}
+
+	/// Statistics about the allocations that were reused since the decoder was created or cleared.
+	pub fn pool_stats(&self) -> PoolStats {
+		PoolStats { reused: self.pool.reused, allocated: self.pool.allocated, pooled: self.pool.pooled() }
+	}
+
+	/// Takes a tag apart, keeping its allocations for the next reads.
+	pub fn recycle(&mut self, tag: Tag) {
+		let Self { pool, recycling, .. } = self;
+		recycling.push(tag);
+		while let Some(tag) = recycling.pop() {
+			match tag {
+				Tag::String(value) => pool.strings.push(recycle_string(value)),
+				Tag::Compound(mut map) => {
+					#[cfg(feature = "preserve_order")]
+					let entries = map.drain(..);
+					#[cfg(not(feature = "preserve_order"))]
+					let entries = map.drain();
+					for (key, value) in entries {
+						pool.strings.push(recycle_string(key));
+						recycling.push(value);
+					}
+					pool.maps.push(map);
 				}
-				Ok(map)
+				Tag::List(ListTag::String(values)) => pool.strings.extend(values.into_iter().map(recycle_string)),
+				Tag::List(ListTag::Compound(mut maps)) => {
+					recycling.extend(maps.drain(..).map(Tag::Compound));
+					pool.compound_lists.push(maps);
+				}
+				Tag::List(ListTag::List(mut lists)) => {
+					recycling.extend(lists.drain(..).map(Tag::List));
+					pool.list_lists.push(lists);
+				}
+				_ => {}
 			}
 		}
+	}
 
-		impl NbtWrite for Tag {
-			#[doc = "Attempt to write a [Tag]"]
-			fn nbt_write<W: Write>(&self, writer: &mut W) -> Result<usize, NbtError> {
-				match self {
-					$(
-						Tag::$title(tag) => tag.nbt_write(writer),
-					)+
-				}
+	/// Takes a named tag apart, keeping the allocations of its name and tag for the next reads.
+	pub fn recycle_named(&mut self, tag: NamedTag) {
+		let (name, tag) = <(String, Tag)>::from(tag);
+		self.pool.strings.push(recycle_string(name));
+		self.recycle(tag);
+	}
+
+	/// Drops the allocations that are waiting to be reused, and resets the [PoolStats].
+	pub fn clear(&mut self) {
+		self.pool = Pool::default();
+		self.recycling = Vec::new();
+	}
+}
+
+fn recycle_string(value: String) -> Vec<u8> {
+	let mut bytes = value.into_bytes();
+	bytes.clear();
+	bytes
+}
+
+/// Writes a payload with a [BinarySink].
+fn write_payload<W: Write>(writer: &mut W, node: Node) -> Result<usize, NbtError> {
+	let mut sink = BinarySink::new(writer);
+	encode(node, &mut sink)?;
+	Ok(sink.size())
+}
+
+/// Writes the payloads of tags as binary NBT. This is what [NbtWrite] uses for [Tag], [ListTag], and [Map].
+pub struct BinarySink<'w, W> {
+	writer: &'w mut W,
+	size: usize,
+}
+
+impl<'w, W: Write> BinarySink<'w, W> {
+	pub fn new(writer: &'w mut W) -> Self {
+		Self { writer, size: 0 }
+	}
+
+	/// The number of bytes that have been written.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+}
+
+impl<W: Write> NbtSink for BinarySink<'_, W> {
+	fn scalar(&mut self, value: Scalar) -> Result<(), NbtError> {
+		let writer = &mut *self.writer;
+		self.size += match value {
+			Scalar::Byte(value) => value.nbt_write(writer)?,
+			Scalar::Short(value) => value.nbt_write(writer)?,
+			Scalar::Int(value) => value.nbt_write(writer)?,
+			Scalar::Long(value) => value.nbt_write(writer)?,
+			Scalar::Float(value) => value.nbt_write(writer)?,
+			Scalar::Double(value) => value.nbt_write(writer)?,
+			Scalar::ByteArray(values) => (values.len() as u32).nbt_write(writer)? + write_bytes(writer, bytemuck::cast_slice(values))?,
+			Scalar::String(value) => value.nbt_write(writer)?,
+			Scalar::IntArray(values) => (values.len() as u32).nbt_write(writer)? + write_array(writer, values)?,
+			Scalar::LongArray(values) => (values.len() as u32).nbt_write(writer)? + write_array(writer, values)?,
+		};
+		Ok(())
+	}
+
+	fn begin_compound(&mut self, _: usize) -> Result<(), NbtError> {
+		Ok(())
+	}
+
+	fn key(&mut self, key: &str, id: TagID) -> Result<(), NbtError> {
+		self.size += id.nbt_write(self.writer)? + key.nbt_write(self.writer)?;
+		Ok(())
+	}
+
+	fn end_compound(&mut self) -> Result<(), NbtError> {
+		self.size += 0u8.nbt_write(self.writer)?;
+		Ok(())
+	}
+
+	fn begin_list(&mut self, element: Option<TagID>, len: usize) -> Result<(), NbtError> {
+		self.size += element.map_or(0, |id| id.value() as u8).nbt_write(self.writer)? + (len as u32).nbt_write(self.writer)?;
+		Ok(())
+	}
+
+	fn end_list(&mut self) -> Result<(), NbtError> {
+		Ok(())
+	}
+
+	fn flat_list(&mut self, list: &ListTag) -> Result<(), NbtError> {
+		match list {
+			ListTag::Compound(_) | ListTag::List(_) => encode(Node::List(list), self),
+			list => {
+				self.size += write_flat_list(list, self.writer)?;
+				Ok(())
 			}
 		}
-	};
+	}
+}
+
+impl NbtRead for ListTag {
+	/// Attempt to read a [ListTag] from a reader.
+	fn nbt_read<R: Read>(reader: &mut R) -> Result<Self, NbtError> {
+		match read_payload(reader, TagID::List, DEFAULT_MAX_DEPTH, &mut ParseStats::default(), &mut ParseOptions::default())? {
+			Tag::List(list) => Ok(list),
+			_ => unreachable!(),
+		}
+	}
+}
+
+impl NbtWrite for ListTag {
+	/// Attempt to write a [ListTag] to a writer.
+	fn nbt_write<W: Write>(&self, writer: &mut W) -> Result<usize, NbtError> {
+		write_payload(writer, Node::List(self))
+	}
+}
+
+impl NbtRead for Map {
+	/// Attempt to read a [Map] from a reader.
+	fn nbt_read<R: Read>(reader: &mut R) -> Result<Self, NbtError> {
+		match read_payload(reader, TagID::Compound, DEFAULT_MAX_DEPTH, &mut ParseStats::default(), &mut ParseOptions::default())? {
+			Tag::Compound(map) => Ok(map),
+			_ => unreachable!(),
+		}
+	}
+}
+
+impl NbtWrite for Tag {
+	/// Attempt to write a [Tag]
+	fn nbt_write<W: Write>(&self, writer: &mut W) -> Result<usize, NbtError> {
+		write_payload(writer, Node::Tag(self))
+	}
 }
 
 /// Blanket implementations for reading and writing primitives (scalar types).
@@ -237,18 +852,53 @@ macro_rules! primitive_io {
 					reader.read_exact(&mut buf)?;
 					Ok(Self::from_be_bytes(buf))
 				}
+
+				#[doc = "Reads the bytes of all of the values at once, then converts them from Big-Endian in place."]
+				fn nbt_read_many<R: Read>(reader: &mut R, length: usize) -> Result<Vec<Self>, NbtError> {
+					let mut values: Vec<$primitive> = Vec::new();
+					// The values are read in chunks, so a length from a damaged file can not allocate much more than
+					// the file holds.
+					while values.len() < length {
+						let start = values.len();
+						values.resize(start + (length - start).min(BULK_CHUNK), 0 as $primitive);
+						reader.read_exact(bytemuck::cast_slice_mut(&mut values[start..]))?;
+					}
+					// This does nothing on Big-Endian targets, and is a single pass of byte swaps elsewhere.
+					for value in &mut values {
+						*value = Self::from_be_bytes(value.to_ne_bytes());
+					}
+					Ok(values)
+				}
 			}
 
 			impl NbtWrite for $primitive {
 				#[doc = "Attempts to write primitive to writer. This will write in Big-Endian byte-order."]
 				fn nbt_write<W: Write>(&self, writer: &mut W) -> Result<usize, NbtError> {
-					Ok(writer.write(self.to_be_bytes().as_slice())?)
+					writer.write_all(self.to_be_bytes().as_slice())?;
+					Ok(std::mem::size_of::<$primitive>())
+				}
+
+				#[doc = "Converts the values to Big-Endian in chunks, writing each chunk at once."]
+				fn nbt_write_many<W: Write>(values: &[Self], writer: &mut W) -> Result<usize, NbtError> {
+					const SIZE: usize = std::mem::size_of::<$primitive>();
+					let mut buffer = [0u8; BULK_CHUNK];
+					for chunk in values.chunks(BULK_CHUNK / SIZE) {
+						let bytes = &mut buffer[..chunk.len() * SIZE];
+						for (value, out) in chunk.iter().zip(bytes.chunks_exact_mut(SIZE)) {
+							out.copy_from_slice(&value.to_be_bytes());
+						}
+						writer.write_all(bytes)?;
+					}
+					Ok(values.len() * SIZE)
 				}
 			}
 		)+
 	};
 }
 
+/// How many values, and how many bytes, the bulk reads and writes of primitives handle at a time.
+pub(crate) const BULK_CHUNK: usize = 4096;
+
 /// These are the primitive types that will be read and written in Big-Endian order.
 primitive_io![
 	i8 u8
@@ -278,7 +928,7 @@ where
 	R: Read,
 	T: NbtRead,
 {
-	(0..length).map(|_| T::nbt_read(reader)).collect()
+	T::nbt_read_many(reader, length)
 }
 
 /// Writes elements to a writer, returning the total number of bytes written.
@@ -287,7 +937,7 @@ where
 	W: Write,
 	T: NbtWrite,
 {
-	data.iter().map(|item| item.nbt_write(writer)).sum()
+	T::nbt_write_many(data, writer)
 }
 
 impl<T: Primitive + Sized> NbtSize for T {
@@ -378,13 +1028,7 @@ impl NbtRead for Vec<i8> {
 	/// Read a bytearray from a reader.
 	fn nbt_read<R: Read>(reader: &mut R) -> Result<Self, NbtError> {
 		let length = u32::nbt_read(reader)?;
-		let bytes = read_bytes(reader, length as usize)?;
-		// Use compiler magic to convert Vec<u8> to Vec<i8>
-		Ok(
-			bytes.into_iter()
-				.map(|x| x as i8)
-				.collect()
-		)
+		i8::nbt_read_many(reader, length as usize)
 	}
 }
 
@@ -472,17 +1116,7 @@ impl NbtWrite for Vec<i8> {
 impl NbtWrite for Map {
 	/// Write a [Map] to a writer.
 	fn nbt_write<W: Write>(&self, writer: &mut W) -> Result<usize, NbtError> {
-		// Writing goes like this:
-		// for each key/value pair, write:
-		//     TagID of value
-		//     name string
-		//     Payload
-		// After iteration, write TagID::End (0u8)
-		let write_size = self.iter().try_fold(0usize, |size, (key, tag)| {
-			write_named_tag(writer, tag, key)
-				.map(|written| written + size)
-		})?;
-		0u8.nbt_write(writer).map(|size| write_size + size)
+		write_payload(writer, Node::Map(self))
 	}
 }
 
@@ -501,10 +1135,473 @@ impl NbtSize for NamedTag {
 	}
 }
 
+/// What [WriteOptions] writes in place of a redacted value.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Redaction {
+	/// The value is left out. Omitting the root writes an empty Compound.
+	Omit,
+	/// The value is written as this tag instead. List elements must be replaced with a tag of the List's type.
+	Replace(Tag),
+}
+
+/// Options for writing a [NamedTag], used with [WriteNbt::write_nbt_with].<br>
+/// Redacted paths are left out or replaced while writing, without changing the tag in memory, which is useful
+/// for exporting data that holds secrets. Paths lead to Compound entries or List elements, and paths that lead
+/// nowhere are ignored. When several redactions have the same path, the last one is used.
+/// ```
+/// # use rustnbt::{ compound, tag::{ Tag, NamedTag }, io::*, path::TagPath };
+/// let config = compound!(("host", "example.com"), ("token", "hunter2"));
+/// let options = WriteOptions::new().redact(["token".parse::<TagPath>().unwrap()]);
+/// let mut bytes = Vec::new();
+/// bytes.write_nbt_with(&NamedTag::new(config), &options).unwrap();
+/// let written: NamedTag = bytes.as_slice().read_nbt().unwrap();
+/// assert_eq!(written.tag(), &compound!(("host", "example.com")));
+/// ```
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct WriteOptions {
+	redactions: Vec<(TagPath, Redaction)>,
+}
+
+impl WriteOptions {
+	/// Creates options that write tags unchanged.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Leaves out the values at these paths.
+	pub fn redact<I: IntoIterator<Item = TagPath>>(mut self, paths: I) -> Self {
+		self.redactions.extend(paths.into_iter().map(|path| (path, Redaction::Omit)));
+		self
+	}
+
+	/// Writes `replacement` in place of the value at this path.
+	pub fn redact_with(mut self, path: TagPath, replacement: Tag) -> Self {
+		self.redactions.push((path, Redaction::Replace(replacement)));
+		self
+	}
+
+	/// The redactions, in the order they were added.
+	pub fn redactions(&self) -> &[(TagPath, Redaction)] {
+		&self.redactions
+	}
+
+	/// Writes a [NamedTag], returning the number of bytes written.
+	pub fn write<W: Write>(&self, writer: &mut W, value: &NamedTag) -> Result<usize, NbtError> {
+		let mut path = TagPath::new();
+		match self.redaction(&path) {
+			Some(Redaction::Omit) => write_named_tag(writer, &Tag::Compound(Map::default()), value.name()),
+			Some(Redaction::Replace(tag)) => write_named_tag(writer, tag, value.name()),
+			None => {
+				value.tag().id().nbt_write(writer)?;
+				let name_size = value.name().nbt_write(writer)?;
+				Ok(1 + name_size + self.write_tag(writer, value.tag(), &mut path)?)
+			}
+		}
+	}
+
+	fn redaction(&self, path: &TagPath) -> Option<&Redaction> {
+		self.redactions.iter().rev().find(|(redacted, _)| redacted == path).map(|(_, redaction)| redaction)
+	}
+
+	/// Returns `true` if a redaction is nested below `path`.
+	fn redacts_below(&self, path: &TagPath) -> bool {
+		self.redactions.iter().any(|(redacted, _)| {
+			redacted.segments().len() > path.segments().len() && redacted.segments().starts_with(path.segments())
+		})
+	}
+
+	fn write_tag<W: Write>(&self, writer: &mut W, tag: &Tag, path: &mut TagPath) -> Result<usize, NbtError> {
+		if !self.redacts_below(path) {
+			return tag.nbt_write(writer);
+		}
+		match tag {
+			Tag::Compound(map) => self.write_map(writer, map, path),
+			Tag::List(list) => self.write_list(writer, list, path),
+			tag => tag.nbt_write(writer),
+		}
+	}
+
+	fn write_map<W: Write>(&self, writer: &mut W, map: &Map, path: &mut TagPath) -> Result<usize, NbtError> {
+		if !self.redacts_below(path) {
+			return map.nbt_write(writer);
+		}
+		let mut size = 0;
+		for (key, tag) in map.iter() {
+			path.push(PathSegment::Key(key.clone()));
+			size += match self.redaction(path) {
+				Some(Redaction::Omit) => 0,
+				Some(Redaction::Replace(replacement)) => write_named_tag(writer, replacement, key)?,
+				None => {
+					tag.id().nbt_write(writer)?;
+					1 + key.nbt_write(writer)? + self.write_tag(writer, tag, path)?
+				}
+			};
+			path.pop();
+		}
+		Ok(size + 0u8.nbt_write(writer)?)
+	}
+
+	fn write_list<W: Write>(&self, writer: &mut W, list: &ListTag, path: &mut TagPath) -> Result<usize, NbtError> {
+		if !self.redacts_below(path) {
+			return list.nbt_write(writer);
+		}
+		let mut elements = Vec::with_capacity(list.len());
+		for index in 0..list.len() {
+			path.push(PathSegment::Index(index));
+			match self.redaction(path) {
+				Some(Redaction::Omit) => {}
+				Some(Redaction::Replace(replacement)) if replacement.id() != list.id() => {
+					return Err(crate::invalid_data(&format!("Replacement for {path} does not have the type of its List.")));
+				}
+				Some(Redaction::Replace(replacement)) => elements.push((index, Some(replacement))),
+				None => elements.push((index, None)),
+			}
+			path.pop();
+		}
+		if elements.is_empty() {
+			return ListTag::Empty.nbt_write(writer);
+		}
+		list.id().nbt_write(writer)?;
+		let mut size = 1 + (elements.len() as u32).nbt_write(writer)?;
+		for (index, replacement) in elements {
+			path.push(PathSegment::Index(index));
+			size += match replacement {
+				Some(replacement) => replacement.nbt_write(writer)?,
+				None => self.write_element(writer, list, index, path)?,
+			};
+			path.pop();
+		}
+		Ok(size)
+	}
+
+	fn write_element<W: Write>(&self, writer: &mut W, list: &ListTag, index: usize, path: &mut TagPath) -> Result<usize, NbtError> {
+		macro_rules! element {
+			($($title:ident)+) => {
+				match list {
+					ListTag::Empty => Ok(0),
+					ListTag::Compound(maps) => self.write_map(writer, &maps[index], path),
+					ListTag::List(lists) => self.write_list(writer, &lists[index], path),
+					$(
+						ListTag::$title(values) => values[index].nbt_write(writer),
+					)+
+				}
+			};
+		}
+		element!(Byte Short Int Long Float Double ByteArray String IntArray LongArray)
+	}
+}
+
+/// The size in bytes of a tag under each compression scheme that region files support. See [suggest_compression].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompressionSizes {
+	pub uncompressed: usize,
+	pub gzip: usize,
+	pub zlib: usize,
+	/// The size of the LZ4 block stream that the game writes for compression scheme 4, or [None] without the `lz4` feature.
+	pub lz4: Option<usize>,
+}
+
+impl CompressionSizes {
+	/// The scheme with the smallest size. Ties go to zlib, which is what the game uses by default.
+	pub fn best(&self) -> ChunkCompression {
+		[
+			(ChunkCompression::Zlib, Some(self.zlib)),
+			(ChunkCompression::GZip, Some(self.gzip)),
+			(ChunkCompression::Lz4, self.lz4),
+			(ChunkCompression::Uncompressed, Some(self.uncompressed)),
+		].into_iter()
+			.filter_map(|(compression, size)| Some((compression, size?)))
+			.min_by_key(|(_, size)| *size)
+			.map(|(compression, _)| compression)
+			.expect("Zlib always has a size.")
+	}
+
+	/// The size under the given scheme, or [None] if it was not measured.
+	pub fn size(&self, compression: ChunkCompression) -> Option<usize> {
+		match compression {
+			ChunkCompression::GZip => Some(self.gzip),
+			ChunkCompression::Zlib => Some(self.zlib),
+			ChunkCompression::Uncompressed => Some(self.uncompressed),
+			ChunkCompression::Lz4 => self.lz4,
+		}
+	}
+}
+
+/// Writes a tag with an empty name, as at the root of a chunk, and measures how large it is under each compression scheme,
+/// so that the smallest one can be chosen for each chunk. The tag is only serialized once.
+pub fn suggest_compression(tag: &Tag) -> CompressionSizes {
+	let mut raw = Vec::with_capacity(tag.nbt_size() + 3);
+	write_named_tag(&mut raw, tag, "").expect("Writing to a Vec does not fail.");
+	let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+	gzip.write_all(&raw).expect("Writing to a Vec does not fail.");
+	let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+	zlib.write_all(&raw).expect("Writing to a Vec does not fail.");
+	CompressionSizes {
+		uncompressed: raw.len(),
+		gzip: gzip.finish().expect("Writing to a Vec does not fail.").len(),
+		zlib: zlib.finish().expect("Writing to a Vec does not fail.").len(),
+		lz4: lz4_block_stream_size(&raw),
+	}
+}
+
+/// The size of the data as written by lz4-java's `LZ4BlockOutputStream` with its default 64 KiB blocks.
+/// Every block has a 21 byte header and is stored raw if compressing does not make it smaller,
+/// and the stream ends with an empty block.
+#[cfg(feature = "lz4")]
+fn lz4_block_stream_size(data: &[u8]) -> Option<usize> {
+	const HEADER: usize = 21;
+	let blocks: usize = data.chunks(1 << 16)
+		.map(|block| HEADER + lz4_flex::block::compress(block).len().min(block.len()))
+		.sum();
+	Some(blocks + HEADER)
+}
+
+#[cfg(not(feature = "lz4"))]
+fn lz4_block_stream_size(_data: &[u8]) -> Option<usize> {
+	None
+}
+
+/// A named tag that [carve] found in a blob.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CarvedTag {
+	/// The position of the tag type ID in the blob.
+	pub offset: usize,
+	/// The number of bytes that the named tag spans.
+	pub length: usize,
+	pub tag: NamedTag,
+}
+
+/// The nesting depth at which [carve] stops following Compounds and Lists, which is the limit that the game uses.
+const CARVE_MAX_DEPTH: usize = 512;
+
+/// Scans arbitrary binary data, such as a damaged disk image or a corrupted file, for NBT that is still intact.<br>
+/// Every offset is tried as the start of a named Compound with at least one entry.
+/// A candidate is only read after all of its type IDs, lengths, and strings have been checked against the data,
+/// so garbage lengths never cause large allocations. Tags are returned in the order they appear and do not overlap;
+/// scanning continues after the end of each tag that was found.<br>
+/// Only uncompressed big endian NBT, as written by Java Edition, is recognized. Compressed data must be decompressed first.
+pub fn carve(bytes: &[u8]) -> Vec<CarvedTag> {
+	let mut found = Vec::new();
+	let mut offset = 0;
+	while offset < bytes.len() {
+		let Some(length) = carve_candidate(&bytes[offset..]) else {
+			offset += 1;
+			continue;
+		};
+		let mut reader = &bytes[offset..offset + length];
+		match reader.read_nbt::<NamedTag>() {
+			Ok(tag) => {
+				found.push(CarvedTag { offset, length, tag });
+				offset += length;
+			}
+			Err(_) => offset += 1,
+		}
+	}
+	found
+}
+
+/// Returns the length of the named Compound at the start of `bytes`, if it is structurally valid and not empty.
+fn carve_candidate(bytes: &[u8]) -> Option<usize> {
+	if bytes.first() != Some(&(TagID::Compound as u8)) {
+		return None;
+	}
+	let name = carve_string(bytes, 1)?;
+	// Names of root tags are identifiers, so control characters mean that this is not a tag.
+	if std::str::from_utf8(&bytes[3..name]).ok()?.chars().any(char::is_control) {
+		return None;
+	}
+	// An empty Compound is only 4 bytes and shows up by chance in any blob.
+	if bytes.get(name).is_none_or(|id| *id == 0) {
+		return None;
+	}
+	carve_payload(bytes, name, TagID::Compound, 0)
+}
+
+/// Checks a string at `position` and returns the position after it.
+fn carve_string(bytes: &[u8], position: usize) -> Option<usize> {
+	let length = u16::from_be_bytes(bytes.get(position..position + 2)?.try_into().ok()?) as usize;
+	let end = position + 2 + length;
+	std::str::from_utf8(bytes.get(position + 2..end)?).ok()?;
+	Some(end)
+}
+
+/// Checks an array of `length` elements that are `size` bytes each at `position`, and returns the position after it.
+fn carve_array(bytes: &[u8], position: usize, size: usize) -> Option<usize> {
+	let length = u32::from_be_bytes(bytes.get(position..position + 4)?.try_into().ok()?) as usize;
+	let end = (position + 4).checked_add(length.checked_mul(size)?)?;
+	(end <= bytes.len()).then_some(end)
+}
+
+/// Checks the payload of a tag at `position` and returns the position after it.
+fn carve_payload(bytes: &[u8], position: usize, id: TagID, depth: usize) -> Option<usize> {
+	if depth > CARVE_MAX_DEPTH {
+		return None;
+	}
+	let fixed = |size: usize| (position + size <= bytes.len()).then_some(position + size);
+	match id {
+		TagID::Byte => fixed(1),
+		TagID::Short => fixed(2),
+		TagID::Int | TagID::Float => fixed(4),
+		TagID::Long | TagID::Double => fixed(8),
+		TagID::ByteArray => carve_array(bytes, position, 1),
+		TagID::IntArray => carve_array(bytes, position, 4),
+		TagID::LongArray => carve_array(bytes, position, 8),
+		TagID::String => carve_string(bytes, position),
+		TagID::List => {
+			let element = *bytes.get(position)?;
+			let length = u32::from_be_bytes(bytes.get(position + 1..position + 5)?.try_into().ok()?);
+			let mut position = position + 5;
+			if element == 0 {
+				return (length == 0).then_some(position);
+			}
+			let element = TagID::try_from(element).ok()?;
+			for _ in 0..length {
+				position = carve_payload(bytes, position, element, depth + 1)?;
+			}
+			Some(position)
+		}
+		TagID::Compound => {
+			let mut position = position;
+			loop {
+				let id = *bytes.get(position)?;
+				if id == 0 {
+					return Some(position + 1);
+				}
+				let id = TagID::try_from(id).ok()?;
+				position = carve_string(bytes, position + 1)?;
+				position = carve_payload(bytes, position, id, depth + 1)?;
+			}
+		}
+	}
+}
+
+/// The number of bytes on each line of an [annotated_dump].
+const DUMP_WIDTH: usize = 16;
+
+/// Writes lines of hex and ASCII for `bytes`, which start at `offset`.
+fn dump_lines(output: &mut String, bytes: &[u8], offset: usize, indent: usize) {
+	use std::fmt::Write;
+	for (row, chunk) in bytes.chunks(DUMP_WIDTH).enumerate() {
+		let hex: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+		let ascii: String = chunk.iter().map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' }).collect();
+		writeln!(output, "{:indent$}{:08x}  {:<width$}  |{ascii}|", "", offset + row * DUMP_WIDTH, hex.join(" "), width = DUMP_WIDTH * 3 - 1).unwrap();
+	}
+}
+
+/// Describes binary NBT as a hexdump in which the bytes of every tag follow a line with its name, type, and value,
+/// indented by how deeply it is nested.<br>
+/// Compounds and Lists are followed by the bytes of their header only, and then by their contents. Damaged data is
+/// described up to the point where it could not be read, followed by the error and the rest of the bytes. This is
+/// built on [reflect](crate::reflect::reflect), so the tag is never built.
+/// ```
+/// # use rustnbt::{ compound, io::*, tag::NamedTag };
+/// let mut bytes = Vec::new();
+/// bytes.write_nbt(&NamedTag::new(compound!(("Health", 20.0f32))))?;
+/// assert_eq!(annotated_dump(&bytes), r#""": Compound (1 entry)
+///   00000000  0a 00 00                                         |...|
+///   Health: Float = 20f
+///     00000003  05 00 06 48 65 61 6c 74 68 41 a0 00 00           |...HealthA...|
+///   End
+///     00000010  00                                               |.|
+/// "#);
+/// # Ok::<(), rustnbt::NbtError>(())
+/// ```
+pub fn annotated_dump(bytes: &[u8]) -> String {
+	use std::fmt::Write;
+	let mut spans = Vec::new();
+	let result = crate::reflect::reflect(bytes, &mut |span: &crate::reflect::TagSpan| spans.push(span.clone()));
+	// Compounds in Lists start at the same offset as their first entry, so they are put before it by their depth.
+	spans.sort_by_key(|span| (span.header, span.depth()));
+	// The number of entries of every Compound, found by keeping the Compounds and Lists that hold each span on a stack.
+	let mut entries = vec![0; spans.len()];
+	let mut parents: Vec<usize> = Vec::new();
+	for (index, span) in spans.iter().enumerate() {
+		while parents.last().is_some_and(|parent| spans[*parent].end() <= span.header) {
+			parents.pop();
+		}
+		if let Some(parent) = parents.last() {
+			entries[*parent] += 1;
+		}
+		if matches!(span.id, TagID::Compound | TagID::List) {
+			parents.push(index);
+		}
+	}
+
+	let mut output = String::new();
+	// The end and depth of every Compound that holds the position, innermost last.
+	let mut compounds: Vec<(usize, usize)> = Vec::new();
+	let mut position = 0;
+	let close = |output: &mut String, position: &mut usize, compounds: &mut Vec<(usize, usize)>, before: usize| {
+		while let Some(&(end, depth)) = compounds.last().filter(|(end, _)| *end <= before) {
+			compounds.pop();
+			writeln!(output, "{:indent$}End", "", indent = depth * 2 + 2).unwrap();
+			dump_lines(output, &bytes[end - 1..end], end - 1, depth * 2 + 4);
+			*position = end;
+		}
+	};
+	for (span, entries) in spans.iter().zip(entries) {
+		close(&mut output, &mut position, &mut compounds, span.header);
+		let indent = span.depth() * 2;
+		if span.header > position {
+			// Compounds and Lists that did not end are not reported, so their headers are only dumped.
+			writeln!(output, "{:indent$}(Headers of unfinished tags)", "").unwrap();
+			dump_lines(&mut output, &bytes[position..span.header], position, indent + 2);
+		}
+		let name = match span.path.segments().last() {
+			Some(PathSegment::Key(key)) => key.clone(),
+			Some(PathSegment::Index(index)) => format!("[{index}]"),
+			None => format!("{:?}", String::from_utf8_lossy(&bytes[span.header + 3..span.payload])),
+		};
+		let payload = &bytes[span.payload_range()];
+		let (description, end) = match span.id {
+			TagID::Compound => {
+				compounds.push((span.end(), span.depth()));
+				(if entries == 1 { "(1 entry)".to_owned() } else { format!("({entries} entries)") }, span.payload)
+			}
+			TagID::List => {
+				let length = u32::from_be_bytes(payload[1..5].try_into().unwrap());
+				let element = TagID::try_from(payload[0]).map_or("End", |id| id.title());
+				(format!("of {element} ({length})"), span.payload + 5)
+			}
+			id => {
+				let value = match read_payload(&mut &payload[..], id, 0, &mut ParseStats::default(), &mut ParseOptions::default()) {
+					Ok(tag) => {
+						let text = crate::snbt::to_string(&tag);
+						match text.char_indices().nth(64) {
+							Some((end, _)) => format!("{}...", &text[..end]),
+							None => text,
+						}
+					}
+					Err(err) => format!("(unreadable: {err})"),
+				};
+				(format!("= {value}"), span.end())
+			}
+		};
+		writeln!(output, "{:indent$}{name}: {} {description}", "", span.id.title()).unwrap();
+		dump_lines(&mut output, &bytes[span.header..end], span.header, indent + 2);
+		position = end;
+	}
+	close(&mut output, &mut position, &mut compounds, usize::MAX);
+	match result {
+		Ok(end) if end < bytes.len() => {
+			writeln!(output, "(Trailing data)").unwrap();
+			dump_lines(&mut output, &bytes[end..], end, 2);
+		}
+		Ok(_) => {}
+		Err(err) => {
+			writeln!(output, "Error: {err}").unwrap();
+			dump_lines(&mut output, &bytes[position..], position, 2);
+		}
+	}
+	output
+}
+
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
 	use crate::*;
 	use crate::io::*;
+	use crate::region::ChunkCompression;
 	use crate::tag::*;
 
 	fn test_tag() -> Tag {
@@ -519,7 +1616,7 @@ mod tests {
 		let list = Tag::List(ListTag::from(vec![1i32,2,3,4]));
 		let intarray = Tag::IntArray(vec![1,1,2,3,5,8,13,21,34,55,89,144]);
 		let longarray = Tag::LongArray(vec![1,3,3,7, 1337, 13,37, 1,3,37,1,337, 133,7, 1,33,7,13,3,7]);
-		let mut compound = Map::from([
+		let mut compound = Map::from_iter([
 			("Byte".to_owned(), byte),
 			("Short".to_owned(), short),
 			("Int".to_owned(), int),
@@ -549,6 +1646,53 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn carve_test() -> Result<(), NbtError> {
+		let first = NamedTag::with_name("Level", test_tag());
+		let second = NamedTag::with_name("", Tag::Compound(Map::from_iter([("Health".to_owned(), Tag::Float(20.0))])));
+		let mut blob = vec![0xFFu8, 10, 0, 0, 0, 10, 0, 1, b'x', 7, 0xFF, 0xFF, 0xFF, 0xFF];
+		let first_offset = blob.len();
+		blob.write_nbt(&first)?;
+		blob.extend_from_slice(&[1, 2, 3]);
+		let second_offset = blob.len();
+		blob.write_nbt(&second)?;
+		// A copy of the first tag that was cut off.
+		blob.extend_from_slice(&crate::clipboard::to_binary(&first)[..20]);
+		let found = io::carve(&blob);
+		assert_eq!(found, vec![
+			CarvedTag { offset: first_offset, length: first.nbt_size(), tag: first },
+			CarvedTag { offset: second_offset, length: second.nbt_size(), tag: second },
+		]);
+		Ok(())
+	}
+
+	#[test]
+	fn suggest_compression_test() {
+		let sizes = io::suggest_compression(&test_tag());
+		assert_eq!(sizes.uncompressed, NamedTag::new(test_tag()).nbt_size());
+		assert!(sizes.zlib < sizes.uncompressed);
+		assert!(sizes.gzip > sizes.zlib);
+		assert_eq!(sizes.lz4.is_some(), cfg!(feature = "lz4"));
+		assert_eq!(sizes.best(), ChunkCompression::Zlib);
+		assert_eq!(sizes.size(sizes.best()), Some(sizes.zlib));
+		let tiny = io::suggest_compression(&Tag::Compound(Map::default()));
+		assert_eq!(tiny.best(), ChunkCompression::Uncompressed);
+	}
+
+	#[test]
+	fn compressed_test() -> Result<(), NbtError> {
+		let root = NamedTag::with_name("Data", test_tag());
+		for compression in [ChunkCompression::GZip, ChunkCompression::Zlib, ChunkCompression::Uncompressed] {
+			let mut bytes = Vec::new();
+			assert_eq!(io::write_compressed(&mut bytes, &root, compression)?, root.nbt_size());
+			assert_eq!(io::detect_compression(&bytes), compression);
+			assert_eq!(io::read_compressed(bytes.as_slice())?, root);
+		}
+		assert!(io::write_compressed(Vec::new(), &root, ChunkCompression::Lz4).is_err());
+		assert_eq!(io::detect_compression(&[]), ChunkCompression::Uncompressed);
+		Ok(())
+	}
+
 	#[test]
 	fn read_test() -> Result<(), NbtError> {
 		let file = include_bytes!("../test_nbt.nbt");
@@ -557,4 +1701,239 @@ mod tests {
 		println!("Tag: {:#?}", named);
 		Ok(())
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn end_list_test() -> Result<(), NbtError> {
+		// A root Compound with a List of End tags, the way the game writes empty Lists.
+		let end_list = |length: u8| [10, 0, 0, 9, 0, 1, b'a', TagID::END, 0, 0, 0, length, 0];
+		let bytes = end_list(0);
+		let named: NamedTag = bytes.as_slice().read_nbt()?;
+		assert_eq!(named.tag(), &compound!(("a", Tag::List(ListTag::Empty))));
+		let mut written = Vec::new();
+		written.write_nbt(&named)?;
+		assert_eq!(written, bytes);
+		assert!(end_list(2).as_slice().read_nbt::<NamedTag>().is_err());
+		assert!(NamedTag::decode_from(&mut crate::stream::NbtReader::new(end_list(2).as_slice())).is_err());
+
+		// Empty Lists of other types keep their type through every reader.
+		let typed = NamedTag::new(compound!(
+			("Items", Tag::List(ListTag::empty_of(Some(TagID::Compound)))),
+			("Empty", Tag::List(ListTag::empty_of(None))),
+			("Nested", Tag::List(ListTag::List(vec![ListTag::empty_of(Some(TagID::String))])))
+		));
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&typed)?;
+		assert_eq!(bytes.as_slice().read_nbt::<NamedTag>()?, typed);
+		assert_eq!(NamedTag::decode_from(&mut crate::stream::NbtReader::new(bytes.as_slice()))?, typed);
+		assert_eq!(ListTag::Compound(Vec::new()).element_id(), Some(TagID::Compound));
+		assert_eq!(ListTag::Empty.element_id(), None);
+		assert_eq!(TagID::list_element(TagID::END)?, None);
+		Ok(())
+	}
+
+	#[test]
+	fn bulk_array_test() -> Result<(), NbtError> {
+		let values: Vec<i64> = (0..BULK_CHUNK as i64 * 2 + 3).map(|value| value * -0x0102_0304_0506).collect();
+		let mut bytes = Vec::new();
+		values.nbt_write(&mut bytes)?;
+		assert_eq!(&bytes[12..20], &values[1].to_be_bytes());
+		assert_eq!(<Vec<i64>>::nbt_read(&mut bytes.as_slice())?, values);
+		let floats = vec![0.5f32, -1.0, f32::INFINITY];
+		bytes.clear();
+		floats.nbt_write(&mut bytes)?;
+		assert_eq!(<Vec<f32>>::nbt_read(&mut bytes.as_slice())?, floats);
+		// A damaged length is only read as far as the data goes.
+		assert!(<Vec<i32>>::nbt_read(&mut [0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 1].as_slice()).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn float_bits_test() -> Result<(), NbtError> {
+		let floats = [0.0, -0.0, f32::from_bits(1), f32::MIN_POSITIVE, f32::MAX, f32::NEG_INFINITY, f32::from_bits(0x7FC0_1234), f32::from_bits(0xFF80_0001)];
+		let doubles = [0.0, -0.0, f64::from_bits(1), f64::MIN_POSITIVE, f64::MAX, f64::INFINITY, f64::from_bits(0x7FF8_0000_DEAD_BEEF), f64::from_bits(0xFFF0_0000_0000_0001)];
+		let mut map = Map::default();
+		map.insert("Floats".to_owned(), Tag::List(ListTag::Float(floats.to_vec())));
+		map.insert("Doubles".to_owned(), Tag::List(ListTag::Double(doubles.to_vec())));
+		for (index, (float, double)) in floats.iter().zip(doubles).enumerate() {
+			map.insert(format!("Float{index}"), Tag::Float(*float));
+			map.insert(format!("Double{index}"), Tag::Double(double));
+		}
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&NamedTag::new(Tag::Compound(map)))?;
+		// The signs of zeros and NaN, and the payloads of NaN, are kept by every reader.
+		for read in [bytes.as_slice().read_nbt::<NamedTag>()?, Decoder::new().decode(&mut bytes.as_slice())?] {
+			let Tag::Compound(map) = read.tag() else { panic!("The root is a Compound.") };
+			let Some(Tag::List(ListTag::Float(read_floats))) = map.get("Floats") else { panic!("Floats is a List of Floats.") };
+			let Some(Tag::List(ListTag::Double(read_doubles))) = map.get("Doubles") else { panic!("Doubles is a List of Doubles.") };
+			assert!(read_floats.iter().zip(floats).all(|(read, value)| read.to_bits() == value.to_bits()));
+			assert!(read_doubles.iter().zip(doubles).all(|(read, value)| read.to_bits() == value.to_bits()));
+			for (index, (float, double)) in floats.iter().zip(doubles).enumerate() {
+				assert!(matches!(map.get(&format!("Float{index}")), Some(Tag::Float(read)) if read.to_bits() == float.to_bits()));
+				assert!(matches!(map.get(&format!("Double{index}")), Some(Tag::Double(read)) if read.to_bits() == double.to_bits()));
+			}
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn decoder_test() -> Result<(), NbtError> {
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&NamedTag::new(test_tag()))?;
+		let mut decoder = Decoder::new();
+		assert!(decoder.decode(&mut &bytes[..bytes.len() / 2]).is_err());
+		let first = decoder.decode(&mut bytes.as_slice())?;
+		assert_eq!(first, bytes.as_slice().read_nbt::<NamedTag>()?);
+		assert_eq!(decoder.stats(), read_named_tag_with_stats(&mut bytes.as_slice(), DEFAULT_MAX_DEPTH)?.2);
+		let allocated = decoder.pool_stats().allocated;
+		decoder.recycle_named(first);
+		let pooled = decoder.pool_stats().pooled;
+		assert!(pooled > 0);
+		let second = decoder.decode(&mut bytes.as_slice())?;
+		assert_eq!(second.tag(), &test_tag());
+		assert_eq!(decoder.pool_stats(), PoolStats { reused: pooled, allocated, pooled: 0 });
+		decoder.recycle_named(second);
+		decoder.clear();
+		assert_eq!(decoder.pool_stats(), PoolStats::default());
+		Ok(())
+	}
+
+	#[cfg(any(feature = "stable_hash", feature = "preserve_order"))]
+	#[test]
+	fn stable_order_test() -> Result<(), NbtError> {
+		let write = || -> Result<Vec<u8>, NbtError> {
+			let mut bytes = Vec::new();
+			bytes.write_nbt(&NamedTag::new(test_tag()))?;
+			Ok(bytes)
+		};
+		let first = write()?;
+		for _ in 0..8 {
+			assert_eq!(write()?, first);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn redact_test() -> Result<(), NbtError> {
+		let account = Map::from_iter([("token".to_owned(), Tag::from("secret")), ("user".to_owned(), Tag::from("alex"))]);
+		let tag = compound!(
+			("config", compound!(("token", "hunter2"), ("port", 25565))),
+			("accounts", Tag::List(ListTag::Compound(vec![account.clone(), account]))),
+			("names", list!("a", "b", "c"))
+		);
+		let original = tag.clone();
+		let options = WriteOptions::new()
+			.redact(["config.token".parse::<path::TagPath>().unwrap(), "names[1]".parse().unwrap(), "accounts[1]".parse().unwrap()])
+			.redact_with("accounts[0].token".parse().unwrap(), Tag::from("<redacted>"));
+		let mut bytes = Vec::new();
+		let size = bytes.write_nbt_with(&NamedTag::new(tag.clone()), &options)?;
+		assert_eq!(size, bytes.len());
+		let written: NamedTag = bytes.as_slice().read_nbt()?;
+		let redacted_account = Map::from_iter([("token".to_owned(), Tag::from("<redacted>")), ("user".to_owned(), Tag::from("alex"))]);
+		assert_eq!(written.tag(), &compound!(
+			("config", compound!(("port", 25565))),
+			("accounts", Tag::List(ListTag::Compound(vec![redacted_account]))),
+			("names", list!("a", "c"))
+		));
+		assert_eq!(tag, original);
+
+		let mut unchanged = Vec::new();
+		unchanged.write_nbt_with(&NamedTag::new(tag.clone()), &WriteOptions::new().redact(["missing.path".parse().unwrap()]))?;
+		assert_eq!(unchanged.as_slice().read_nbt::<NamedTag>()?.tag(), &tag);
+		let wrong_type = WriteOptions::new().redact_with("names[0]".parse().unwrap(), Tag::Int(0));
+		assert!(Vec::new().write_nbt_with(&NamedTag::new(tag), &wrong_type).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn depth_test() -> Result<(), NbtError> {
+		// A root List holding a List, and so on, `depth` Lists deep.
+		let nested = |depth: usize| {
+			let mut bytes = vec![9, 0, 0];
+			for _ in 1..depth {
+				bytes.extend([9, 0, 0, 0, 1]);
+			}
+			bytes.extend([0, 0, 0, 0, 0]);
+			bytes
+		};
+		let (_, tag, stats) = read_named_tag_with_stats(&mut nested(3).as_slice(), 3)?;
+		assert_eq!(tag, Tag::List(ListTag::List(vec![ListTag::List(vec![ListTag::Empty])])));
+		assert_eq!(stats, ParseStats { max_depth: 3, tags: 3 });
+		assert!(read_named_tag_with_stats(&mut nested(3).as_slice(), 2).is_err());
+		assert!(read_named_tag(&mut nested(1_000_000).as_slice()).is_err());
+
+		let bytes = nested(5000);
+		let (name, tag, stats) = read_named_tag_with_stats(&mut bytes.as_slice(), usize::MAX)?;
+		assert_eq!(stats.max_depth, 5000);
+		let mut written = Vec::new();
+		write_named_tag(&mut written, &tag, name)?;
+		assert_eq!(written, bytes);
+		assert_eq!(snbt::to_string(&tag).matches('[').count(), 5000);
+
+		let (_, tag, stats) = read_named_tag_with_stats(&mut std::io::BufReader::new(include_bytes!("../test_nbt.nbt").as_slice()), DEFAULT_MAX_DEPTH)?;
+		assert!(stats.max_depth >= 1 && stats.tags > 1);
+		let mut again = Vec::new();
+		again.write_nbt(&NamedTag::new(tag.clone()))?;
+		assert_eq!(again.as_slice().read_nbt::<NamedTag>()?.tag(), &tag);
+		Ok(())
+	}
+
+	#[test]
+	fn read_named_tag_with_test() -> Result<(), NbtError> {
+		use crate::parse::{ KeyCollisionPolicy, ParseOptions, Warning };
+		// {"": {list: [{a: 1b, a: 2b}]}} followed by two bytes.
+		let mut bytes = vec![10, 0, 0, 9, 0, 4, b'l', b'i', b's', b't', 10, 0, 0, 0, 1];
+		bytes.extend([1, 0, 1, b'a', 1, 1, 0, 1, b'a', 2, 0, 0]);
+		assert_eq!(read_named_tag(&mut bytes.as_slice())?.1, compound!(("list", Tag::List(ListTag::Compound(vec![
+			Map::from_iter([("a".to_owned(), Tag::Byte(2))]),
+		])))));
+		let options = ParseOptions::new().key_collisions(KeyCollisionPolicy::KeepFirst);
+		let outcome = read_named_tag_with(&bytes, &options)?;
+		assert_eq!(outcome.value.tag(), &compound!(("list", Tag::List(ListTag::Compound(vec![
+			Map::from_iter([("a".to_owned(), Tag::Byte(1))]),
+		])))));
+		assert!(matches!(&outcome.warnings[..], [Warning::KeyCollision { path, .. }] if *path == TagPath::new().key("list").index(0)));
+
+		bytes.extend([0xFF, 0xFF]);
+		assert!(read_named_tag_with(&bytes, &options).is_err());
+		let outcome = read_named_tag_with(&bytes, &options.allow_trailing_data(true))?;
+		assert_eq!(outcome.warnings[1], Warning::TrailingData { offset: 27, length: 2 });
+		Ok(())
+	}
+	#[test]
+	fn incremental_parser_test() -> Result<(), NbtError> {
+		let bytes = include_bytes!("../test_nbt.nbt");
+		let (name, tag, stats) = read_named_tag_with_stats(&mut std::io::BufReader::new(bytes.as_slice()), DEFAULT_MAX_DEPTH)?;
+		let mut parser = IncrementalParser::new(std::io::BufReader::new(bytes.as_slice()));
+		let mut slices = 1;
+		let root = loop {
+			match parser.poll_parse(0)? {
+				ParsePoll::Pending => slices += 1,
+				ParsePoll::Done(root) => break root,
+			}
+		};
+		assert_eq!(root, NamedTag::with_name(name, tag));
+		assert_eq!(parser.stats(), stats);
+		assert!(slices > 1 && slices <= stats.tags);
+		assert!(parser.poll_parse(1).is_err());
+
+		let mut parser = IncrementalParser::new(&bytes[..bytes.len() / 2]);
+		assert!(std::iter::repeat_with(|| parser.poll_parse(4)).find(|poll| !matches!(poll, Ok(ParsePoll::Pending))).unwrap().is_err());
+		Ok(())
+	}
+	#[test]
+	fn annotated_dump_test() {
+		// {list: [{a: 1b}]}
+		let mut bytes = vec![10, 0, 0, 9, 0, 4, b'l', b'i', b's', b't', 10, 0, 0, 0, 1, 1, 0, 1, b'a', 1, 0, 0];
+		let dump = annotated_dump(&bytes);
+		let lines: Vec<&str> = dump.lines().filter(|line| !line.trim_start().starts_with('0')).collect();
+		assert_eq!(lines, ["\"\": Compound (1 entry)", "  list: List of Compound (1)", "    [0]: Compound (1 entry)", "      a: Byte = 1b", "      End", "  End"]);
+		assert!(dump.contains("    00000003  09 00 04 6c 69 73 74 0a 00 00 00 01"));
+
+		bytes.push(0xFF);
+		assert!(annotated_dump(&bytes).contains("(Trailing data)\n  00000016  ff"));
+		// The Compounds and the List do not end, so only their header bytes are dumped before `a`.
+		let dump = annotated_dump(&bytes[..20]);
+		assert!(dump.starts_with("      (Headers of unfinished tags)\n        00000000  0a 00 00 09"));
+		assert!(dump.ends_with("      a: Byte = 1b\n        0000000f  01 00 01 61 01                                   |...a.|\nError: Unexpected end of data. (at offset 20, in `list[0]`)\n"));
+	}
+}
@@ -0,0 +1,122 @@
+#![doc = r#"
+Finding the keys of a Compound that are spelled differently than expected.
+
+Files written by different versions and mods do not always agree on the case of a key (`Id` and `id`, `UUID` and
+`Uuid`), and hand-edited files have typos. [KeyLookup] adds lookups to [Map] that tolerate both:
+[get_ignore_case](KeyLookup::get_ignore_case) ignores case, and [get_fuzzy](KeyLookup::get_fuzzy) returns every key
+within a small edit distance as a candidate, closest first.
+
+```
+# use rustnbt::{ compound, tag::Tag, keys::KeyLookup };
+let Tag::Compound(item) = compound!(("Id", "minecraft:stone"), ("Cont", 1i8)) else { unreachable!() };
+assert_eq!(item.get_ignore_case("id"), Some(&Tag::from("minecraft:stone")));
+let candidates = item.get_fuzzy("Count");
+assert_eq!(candidates[0].key, "Cont");
+assert_eq!(candidates[0].distance, 1);
+```
+"#]
+
+use crate::{
+	Map,
+	tag::Tag,
+};
+
+/// The furthest edit distance of the candidates from [KeyLookup::get_fuzzy] for a key with `len` characters: none for
+/// keys shorter than 3 characters, one edit for keys shorter than 6, and two for longer keys.
+pub fn max_fuzzy_distance(len: usize) -> usize {
+	(len / 3).min(2)
+}
+
+/// A key that is close to the one that was looked up.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Candidate<'a> {
+	pub key: &'a str,
+	pub tag: &'a Tag,
+	/// The number of characters that must be inserted, removed, or replaced to turn one key into the other,
+	/// ignoring case.
+	pub distance: usize,
+}
+
+/// Lookups that tolerate differences in the spelling of keys. See the [module](self) documentation.
+pub trait KeyLookup {
+	/// Returns the value of a key, ignoring case. An exact match is preferred, and when several keys only differ in
+	/// case, the least of them is used, so the result does not depend on the order of the Compound.
+	fn get_ignore_case(&self, key: &str) -> Option<&Tag>;
+
+	/// Returns the keys within [max_fuzzy_distance] edits of `key`, ignoring case, ordered by distance and then by
+	/// key. An exact match is first.
+	fn get_fuzzy(&self, key: &str) -> Vec<Candidate<'_>>;
+}
+
+impl KeyLookup for Map {
+	fn get_ignore_case(&self, key: &str) -> Option<&Tag> {
+		if let Some(tag) = self.get(key) {
+			return Some(tag);
+		}
+		let key = key.to_lowercase();
+		self.iter()
+			.filter(|(other, _)| other.to_lowercase() == key)
+			.min_by(|(left, _), (right, _)| left.cmp(right))
+			.map(|(_, tag)| tag)
+	}
+
+	fn get_fuzzy(&self, key: &str) -> Vec<Candidate<'_>> {
+		let lowercase: Vec<char> = key.to_lowercase().chars().collect();
+		let max = max_fuzzy_distance(lowercase.len());
+		let mut candidates: Vec<Candidate> = self.iter()
+			.filter_map(|(other, tag)| {
+				let distance = edit_distance(&lowercase, &other.to_lowercase().chars().collect::<Vec<_>>(), max)?;
+				Some(Candidate { key: other, tag, distance })
+			})
+			.collect();
+		candidates.sort_by(|left, right| {
+			(left.distance, left.key != key, left.key).cmp(&(right.distance, right.key != key, right.key))
+		});
+		candidates
+	}
+}
+
+/// The Levenshtein distance between two strings, or None if it is more than `max`.
+fn edit_distance(left: &[char], right: &[char], max: usize) -> Option<usize> {
+	if left.len().abs_diff(right.len()) > max {
+		return None;
+	}
+	let mut previous: Vec<usize> = (0..=right.len()).collect();
+	let mut current = vec![0; right.len() + 1];
+	for (i, l) in left.iter().enumerate() {
+		current[0] = i + 1;
+		for (j, r) in right.iter().enumerate() {
+			let replace = previous[j] + usize::from(l != r);
+			current[j + 1] = replace.min(previous[j + 1] + 1).min(current[j] + 1);
+		}
+		if current.iter().all(|distance| *distance > max) {
+			return None;
+		}
+		std::mem::swap(&mut previous, &mut current);
+	}
+	Some(previous[right.len()]).filter(|distance| *distance <= max)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::compound;
+
+	#[test]
+	fn lookup_test() {
+		let Tag::Compound(map) = compound!(("id", 1), ("ID", 2), ("UUID", 3), ("CustomName", 4), ("Count", 5)) else { unreachable!() };
+		assert_eq!(map.get_ignore_case("ID"), Some(&Tag::Int(2)));
+		assert_eq!(map.get_ignore_case("Id"), Some(&Tag::Int(2)));
+		assert_eq!(map.get_ignore_case("uuid"), Some(&Tag::Int(3)));
+		assert_eq!(map.get_ignore_case("Name"), None);
+
+		let keys = |key: &str| map.get_fuzzy(key).iter().map(|candidate| (candidate.key, candidate.distance)).collect::<Vec<_>>();
+		assert_eq!(keys("id"), [("id", 0), ("ID", 0)]);
+		assert_eq!(keys("Ids"), [("ID", 1), ("id", 1)]);
+		assert_eq!(keys("Cout"), [("Count", 1)]);
+		assert_eq!(keys("customname"), [("CustomName", 0)]);
+		assert_eq!(keys("Display"), []);
+		assert_eq!(edit_distance(&['a', 'b', 'c'], &['c', 'a'], 3), Some(3));
+		assert_eq!(edit_distance(&['a'; 8], &['b'; 8], 2), None);
+	}
+}
@@ -0,0 +1,656 @@
+#![doc = r#"
+Tag trees whose Int and Long arrays stay in the file's byte order until they are used.
+
+Reading a [Tag] converts every element of every array from Big-Endian, and writing it converts them back, even when
+nothing looked at them. A [FileOrderTag] keeps the arrays it reads as [LazyArray]s, which hold the bytes as they were in
+the file and remember that they have not been converted yet. They are converted in place the first time they are borrowed,
+and arrays that are still in file order are written back with a single copy. Read-modify-write flows that only change
+a few scalars, such as setting the `Status` of a chunk, skip both passes over the block states and heightmaps.
+
+The entries of Compounds keep the order that they were read in, so a tree that is written back unchanged has the same
+bytes as the file. Entries are added without looking for repeated keys, so reading takes linear time, and a Compound
+with a repeated key keeps both entries, like the file; lookups and [FileOrderTag::to_tag] use the last one, like
+[read_named_tag](crate::io::read_named_tag).
+
+```
+# use rustnbt::{ compound, lazy, io::write_named_tag, tag::Tag };
+let mut bytes = Vec::new();
+write_named_tag(&mut bytes, &compound!(("Status", "minecraft:features"), ("Heights", Tag::LongArray(vec![7; 37]))), "")?;
+let (name, mut chunk) = lazy::read(&mut bytes.as_slice())?;
+if let lazy::FileOrderTag::Compound(map) = &mut chunk {
+	map.insert("Status", lazy::FileOrderTag::String("minecraft:full".to_owned()));
+	if let Some(lazy::FileOrderTag::LongArray(heights)) = map.get("Heights") {
+		assert!(heights.is_file_order());
+	}
+}
+let mut written = Vec::new();
+lazy::write(&mut written, &name, &chunk)?;
+assert_eq!(lazy::read(&mut written.as_slice())?, (name, chunk));
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	frames::{ self, Tree },
+	io::{ NbtRead, NbtWrite, BULK_CHUNK },
+	sink::{ NbtSink, Scalar },
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+	},
+};
+use std::io::{ Read, Write };
+
+/// The elements of a [LazyArray], which are converted between Big-Endian and native byte order.
+pub trait LazyElement: bytemuck::Pod + PartialEq + NbtWrite {
+	/// Converts a value from Big-Endian to native byte order. This is its own inverse.
+	fn swap_order(self) -> Self;
+}
+
+impl LazyElement for i32 {
+	fn swap_order(self) -> Self {
+		i32::from_be(self)
+	}
+}
+
+impl LazyElement for i64 {
+	fn swap_order(self) -> Self {
+		i64::from_be(self)
+	}
+}
+
+/// An array that may still be in the Big-Endian byte order of the file it was read from. See the [module](self)
+/// documentation.
+#[derive(Clone)]
+pub struct LazyArray<T: LazyElement> {
+	values: Vec<T>,
+	/// Whether `values` are still in file order, and have to be converted before they are used.
+	file_order: bool,
+}
+
+impl<T: LazyElement> LazyArray<T> {
+	/// An array of values in native byte order.
+	pub fn new(values: Vec<T>) -> Self {
+		Self { values, file_order: false }
+	}
+
+	/// Returns true until the values are converted to native byte order.
+	pub fn is_file_order(&self) -> bool {
+		self.file_order
+	}
+
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+
+	/// Returns a single value, converting only that value.
+	pub fn get(&self, index: usize) -> Option<T> {
+		let value = *self.values.get(index)?;
+		Some(if self.file_order { value.swap_order() } else { value })
+	}
+
+	/// Borrows the values, converting them all in place the first time.
+	pub fn as_slice(&mut self) -> &[T] {
+		self.as_mut_slice()
+	}
+
+	/// Mutably borrows the values, converting them all in place the first time.
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		if self.file_order {
+			for value in &mut self.values {
+				*value = value.swap_order();
+			}
+			self.file_order = false;
+		}
+		&mut self.values
+	}
+
+	/// Copies the values in native byte order, leaving the array as it is.
+	pub fn to_vec(&self) -> Vec<T> {
+		(0..self.len()).filter_map(|index| self.get(index)).collect()
+	}
+
+	pub fn into_vec(mut self) -> Vec<T> {
+		self.as_mut_slice();
+		self.values
+	}
+
+	/// Reads the length and the values of an array, without converting them.
+	fn read<R: Read>(reader: &mut R) -> Result<Self, NbtError> {
+		let length = u32::nbt_read(reader)? as usize;
+		let mut values: Vec<T> = Vec::new();
+		// Like the bulk reads in io, the values are read in chunks so that a damaged length can not allocate much
+		// more than the file holds.
+		while values.len() < length {
+			let start = values.len();
+			values.resize(start + (length - start).min(BULK_CHUNK), T::zeroed());
+			reader.read_exact(bytemuck::cast_slice_mut(&mut values[start..]))?;
+		}
+		Ok(Self { values, file_order: true })
+	}
+
+	/// Writes the length and the values of an array. Values in file order are written as they are.
+	fn write<W: Write>(&self, writer: &mut W) -> Result<usize, NbtError> {
+		let header = (self.values.len() as u32).nbt_write(writer)?;
+		if self.file_order {
+			let bytes: &[u8] = bytemuck::cast_slice(&self.values);
+			writer.write_all(bytes)?;
+			Ok(header + bytes.len())
+		} else {
+			Ok(header + T::nbt_write_many(&self.values, writer)?)
+		}
+	}
+}
+
+impl<T: LazyElement> From<Vec<T>> for LazyArray<T> {
+	fn from(values: Vec<T>) -> Self {
+		Self::new(values)
+	}
+}
+
+impl<T: LazyElement> PartialEq for LazyArray<T> {
+	/// Compares the values, whichever byte order they are in.
+	fn eq(&self, other: &Self) -> bool {
+		match self.file_order == other.file_order {
+			true => self.values == other.values,
+			false => self.len() == other.len() && (0..self.len()).all(|index| self.get(index) == other.get(index)),
+		}
+	}
+}
+
+impl<T: LazyElement + std::fmt::Debug> std::fmt::Debug for LazyArray<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_list().entries(self.to_vec()).finish()
+	}
+}
+
+/// A tag whose Int and Long arrays are [LazyArray]s. See the [module](self) documentation.
+#[derive(Clone, PartialEq, Debug)]
+pub enum FileOrderTag {
+	Byte(i8),
+	Short(i16),
+	Int(i32),
+	Long(i64),
+	Float(f32),
+	Double(f64),
+	ByteArray(Vec<i8>),
+	String(String),
+	List(FileOrderList),
+	Compound(FileOrderMap),
+	IntArray(LazyArray<i32>),
+	LongArray(LazyArray<i64>),
+}
+
+/// The entries of a Compound, in the order that they were read or inserted.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct FileOrderMap {
+	entries: Vec<(String, FileOrderTag)>,
+}
+
+/// The elements of a List, which all have the same type. Elements can only be replaced by [FileOrderList::set], which
+/// checks their type.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct FileOrderList {
+	element: Option<TagID>,
+	items: Vec<FileOrderTag>,
+}
+
+impl FileOrderTag {
+	pub fn id(&self) -> TagID {
+		match self {
+			FileOrderTag::Byte(_) => TagID::Byte,
+			FileOrderTag::Short(_) => TagID::Short,
+			FileOrderTag::Int(_) => TagID::Int,
+			FileOrderTag::Long(_) => TagID::Long,
+			FileOrderTag::Float(_) => TagID::Float,
+			FileOrderTag::Double(_) => TagID::Double,
+			FileOrderTag::ByteArray(_) => TagID::ByteArray,
+			FileOrderTag::String(_) => TagID::String,
+			FileOrderTag::List(_) => TagID::List,
+			FileOrderTag::Compound(_) => TagID::Compound,
+			FileOrderTag::IntArray(_) => TagID::IntArray,
+			FileOrderTag::LongArray(_) => TagID::LongArray,
+		}
+	}
+
+	/// Returns the value of an entry, if this is a Compound that has it.
+	pub fn get(&self, key: &str) -> Option<&FileOrderTag> {
+		match self {
+			FileOrderTag::Compound(map) => map.get(key),
+			_ => None,
+		}
+	}
+
+	/// Mutably borrows the value of an entry, if this is a Compound that has it.
+	pub fn get_mut(&mut self, key: &str) -> Option<&mut FileOrderTag> {
+		match self {
+			FileOrderTag::Compound(map) => map.get_mut(key),
+			_ => None,
+		}
+	}
+
+	/// Copies a tag. Its arrays are in native byte order.
+	pub fn from_tag(tag: &Tag) -> Self {
+		match tag {
+			Tag::Byte(value) => FileOrderTag::Byte(*value),
+			Tag::Short(value) => FileOrderTag::Short(*value),
+			Tag::Int(value) => FileOrderTag::Int(*value),
+			Tag::Long(value) => FileOrderTag::Long(*value),
+			Tag::Float(value) => FileOrderTag::Float(*value),
+			Tag::Double(value) => FileOrderTag::Double(*value),
+			Tag::ByteArray(values) => FileOrderTag::ByteArray(values.clone()),
+			Tag::String(value) => FileOrderTag::String(value.clone()),
+			Tag::List(list) => FileOrderTag::List(FileOrderList {
+				element: list.element_id(),
+				items: Vec::from(list.clone()).iter().map(FileOrderTag::from_tag).collect(),
+			}),
+			Tag::Compound(map) => FileOrderTag::Compound(FileOrderMap {
+				entries: map.iter().map(|(key, tag)| (key.clone(), FileOrderTag::from_tag(tag))).collect(),
+			}),
+			Tag::IntArray(values) => FileOrderTag::IntArray(LazyArray::new(values.clone())),
+			Tag::LongArray(values) => FileOrderTag::LongArray(LazyArray::new(values.clone())),
+		}
+	}
+
+	/// Copies this tag into a [Tag], converting the copies of arrays that are still in file order. Fails if a List has
+	/// elements of different types.
+	pub fn to_tag(&self) -> Result<Tag, NbtError> {
+		Ok(match self {
+			FileOrderTag::Byte(value) => Tag::Byte(*value),
+			FileOrderTag::Short(value) => Tag::Short(*value),
+			FileOrderTag::Int(value) => Tag::Int(*value),
+			FileOrderTag::Long(value) => Tag::Long(*value),
+			FileOrderTag::Float(value) => Tag::Float(*value),
+			FileOrderTag::Double(value) => Tag::Double(*value),
+			FileOrderTag::ByteArray(values) => Tag::ByteArray(values.clone()),
+			FileOrderTag::String(value) => Tag::String(value.clone()),
+			FileOrderTag::List(list) => Tag::List(match list.items.is_empty() {
+				true => ListTag::empty_of(list.element),
+				false => {
+					let tags = list.iter().map(|item| list.check(item).and_then(|_| item.to_tag())).collect::<Result<Vec<_>, _>>()?;
+					ListTag::try_from(tags).map_err(|_| invalid_data("List elements must all have the same type."))?
+				}
+			}),
+			FileOrderTag::Compound(map) => Tag::Compound(
+				map.iter().map(|(key, tag)| Ok((key.to_owned(), tag.to_tag()?))).collect::<Result<Map, NbtError>>()?,
+			),
+			FileOrderTag::IntArray(values) => Tag::IntArray(values.to_vec()),
+			FileOrderTag::LongArray(values) => Tag::LongArray(values.to_vec()),
+		})
+	}
+
+	/// Writes this tag to a sink, like [Tag::encode_to]. Arrays in file order are copied to native byte order for the
+	/// sink; use [write] to write binary NBT without converting them. Fails if a List has elements of different types.
+	pub fn encode_to<S: NbtSink + ?Sized>(&self, sink: &mut S) -> Result<(), NbtError> {
+		match self {
+			FileOrderTag::Byte(value) => sink.scalar(Scalar::Byte(*value)),
+			FileOrderTag::Short(value) => sink.scalar(Scalar::Short(*value)),
+			FileOrderTag::Int(value) => sink.scalar(Scalar::Int(*value)),
+			FileOrderTag::Long(value) => sink.scalar(Scalar::Long(*value)),
+			FileOrderTag::Float(value) => sink.scalar(Scalar::Float(*value)),
+			FileOrderTag::Double(value) => sink.scalar(Scalar::Double(*value)),
+			FileOrderTag::ByteArray(values) => sink.scalar(Scalar::ByteArray(values)),
+			FileOrderTag::String(value) => sink.scalar(Scalar::String(value)),
+			FileOrderTag::IntArray(values) => sink.scalar(Scalar::IntArray(&values.to_vec())),
+			FileOrderTag::LongArray(values) => sink.scalar(Scalar::LongArray(&values.to_vec())),
+			FileOrderTag::List(list) => {
+				sink.begin_list(list.element, list.len())?;
+				for item in list.iter() {
+					list.check(item)?;
+					item.encode_to(sink)?;
+				}
+				sink.end_list()
+			}
+			FileOrderTag::Compound(map) => {
+				sink.begin_compound(map.len())?;
+				let mut entries: Vec<_> = map.iter().collect();
+				if sink.sorted_keys() {
+					entries.sort_by_key(|(key, _)| *key);
+				}
+				for (key, tag) in entries {
+					sink.key(key, tag.id())?;
+					tag.encode_to(sink)?;
+				}
+				sink.end_compound()
+			}
+		}
+	}
+
+	/// Writes the payload of this tag as binary NBT, returning the number of bytes that were written.
+	fn write_payload<W: Write>(&self, writer: &mut W) -> Result<usize, NbtError> {
+		Ok(match self {
+			FileOrderTag::Byte(value) => value.nbt_write(writer)?,
+			FileOrderTag::Short(value) => value.nbt_write(writer)?,
+			FileOrderTag::Int(value) => value.nbt_write(writer)?,
+			FileOrderTag::Long(value) => value.nbt_write(writer)?,
+			FileOrderTag::Float(value) => value.nbt_write(writer)?,
+			FileOrderTag::Double(value) => value.nbt_write(writer)?,
+			FileOrderTag::ByteArray(values) => values.nbt_write(writer)?,
+			FileOrderTag::String(value) => value.nbt_write(writer)?,
+			FileOrderTag::IntArray(values) => values.write(writer)?,
+			FileOrderTag::LongArray(values) => values.write(writer)?,
+			FileOrderTag::List(list) => {
+				let mut size = list.element.map_or(0, |id| id.value() as u8).nbt_write(writer)?;
+				size += (list.len() as u32).nbt_write(writer)?;
+				for item in list.iter() {
+					list.check(item)?;
+					size += item.write_payload(writer)?;
+				}
+				size
+			}
+			FileOrderTag::Compound(map) => {
+				let mut size = 0;
+				for (key, tag) in map.iter() {
+					size += tag.id().nbt_write(writer)? + key.nbt_write(writer)? + tag.write_payload(writer)?;
+				}
+				size + 0u8.nbt_write(writer)?
+			}
+		})
+	}
+}
+
+impl FileOrderMap {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns the value of the last entry with the key.
+	pub fn get(&self, key: &str) -> Option<&FileOrderTag> {
+		self.entries.iter().rev().find(|(entry, _)| entry == key).map(|(_, tag)| tag)
+	}
+
+	/// Mutably borrows the value of the last entry with the key.
+	pub fn get_mut(&mut self, key: &str) -> Option<&mut FileOrderTag> {
+		self.entries.iter_mut().rev().find(|(entry, _)| entry == key).map(|(_, tag)| tag)
+	}
+
+	/// Sets the value of an entry, returning the old value. New entries are added at the end.
+	pub fn insert<S: Into<String> + AsRef<str>>(&mut self, key: S, tag: FileOrderTag) -> Option<FileOrderTag> {
+		match self.get_mut(key.as_ref()) {
+			Some(old) => Some(std::mem::replace(old, tag)),
+			None => {
+				self.entries.push((key.into(), tag));
+				None
+			}
+		}
+	}
+
+	/// Removes every entry with the key, returning the value of the last one. The other entries keep their order.
+	pub fn remove(&mut self, key: &str) -> Option<FileOrderTag> {
+		let mut removed = None;
+		let mut index = 0;
+		while index < self.entries.len() {
+			if self.entries[index].0 == key {
+				removed = Some(self.entries.remove(index).1);
+			} else {
+				index += 1;
+			}
+		}
+		removed
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &FileOrderTag)> + '_ {
+		self.entries.iter().map(|(key, tag)| (key.as_str(), tag))
+	}
+}
+
+impl FileOrderList {
+	/// An empty List of End tags, which takes the type of the first element that is pushed.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The type of the elements, or None for a List of End tags.
+	pub fn element_id(&self) -> Option<TagID> {
+		self.element
+	}
+
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+
+	pub fn get(&self, index: usize) -> Option<&FileOrderTag> {
+		self.items.get(index)
+	}
+
+	/// Replaces an element, returning the old one. Fails if the index is out of range, or the tag has another type
+	/// than the elements.
+	pub fn set(&mut self, index: usize, tag: FileOrderTag) -> Result<FileOrderTag, NbtError> {
+		self.check(&tag)?;
+		let length = self.items.len();
+		let item = self.items.get_mut(index)
+			.ok_or_else(|| invalid_data(&format!("Index {index} is out of range for a List of {length} elements.")))?;
+		Ok(std::mem::replace(item, tag))
+	}
+
+	/// Mutably borrows an element that is a Compound. Its entries can be changed, but not its type.
+	pub fn compound_mut(&mut self, index: usize) -> Option<&mut FileOrderMap> {
+		match self.items.get_mut(index)? {
+			FileOrderTag::Compound(map) => Some(map),
+			_ => None,
+		}
+	}
+
+	/// Mutably borrows an element that is a List. Its elements can be changed, but not its type.
+	pub fn list_mut(&mut self, index: usize) -> Option<&mut FileOrderList> {
+		match self.items.get_mut(index)? {
+			FileOrderTag::List(list) => Some(list),
+			_ => None,
+		}
+	}
+
+	/// Adds an element at the end. Fails if the List has elements of another type.
+	pub fn push(&mut self, tag: FileOrderTag) -> Result<(), NbtError> {
+		if !self.items.is_empty() {
+			self.check(&tag)?;
+		}
+		self.element = Some(tag.id());
+		self.items.push(tag);
+		Ok(())
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &FileOrderTag> + '_ {
+		self.items.iter()
+	}
+
+	/// Fails if a tag does not have the type of the elements.
+	fn check(&self, tag: &FileOrderTag) -> Result<(), NbtError> {
+		match self.element {
+			Some(element) if element == tag.id() => Ok(()),
+			Some(element) => Err(invalid_data(&format!("A List of {element} can not hold a {}.", tag.id()))),
+			None => Err(invalid_data(&format!("A List of End tags can not hold a {}.", tag.id()))),
+		}
+	}
+}
+
+/// Reads [FileOrderTag]s for [frames::read].
+struct FileOrderTree;
+
+impl Tree for FileOrderTree {
+	type Tag = FileOrderTag;
+	type Key = String;
+	type Map = FileOrderMap;
+	type Items = Vec<FileOrderTag>;
+
+	fn key<R: Read>(&mut self, reader: &mut R) -> Result<String, NbtError> {
+		String::nbt_read(reader)
+	}
+
+	fn leaf<R: Read>(&mut self, reader: &mut R, id: TagID) -> Result<FileOrderTag, NbtError> {
+		Ok(match id {
+			TagID::Byte => FileOrderTag::Byte(i8::nbt_read(reader)?),
+			TagID::Short => FileOrderTag::Short(i16::nbt_read(reader)?),
+			TagID::Int => FileOrderTag::Int(i32::nbt_read(reader)?),
+			TagID::Long => FileOrderTag::Long(i64::nbt_read(reader)?),
+			TagID::Float => FileOrderTag::Float(f32::nbt_read(reader)?),
+			TagID::Double => FileOrderTag::Double(f64::nbt_read(reader)?),
+			TagID::ByteArray => FileOrderTag::ByteArray(Vec::<i8>::nbt_read(reader)?),
+			TagID::String => FileOrderTag::String(String::nbt_read(reader)?),
+			TagID::IntArray => FileOrderTag::IntArray(LazyArray::read(reader)?),
+			TagID::LongArray => FileOrderTag::LongArray(LazyArray::read(reader)?),
+			TagID::List | TagID::Compound => unreachable!("Lists and Compounds are read by frames::read."),
+		})
+	}
+
+	fn map(&mut self) -> FileOrderMap {
+		FileOrderMap::new()
+	}
+
+	fn entry(map: &mut FileOrderMap, key: String, tag: FileOrderTag) {
+		map.entries.push((key, tag));
+	}
+
+	fn compound(map: FileOrderMap) -> FileOrderTag {
+		FileOrderTag::Compound(map)
+	}
+
+	fn items(&mut self, capacity: usize) -> Vec<FileOrderTag> {
+		Vec::with_capacity(capacity)
+	}
+
+	fn item(items: &mut Vec<FileOrderTag>, tag: FileOrderTag) {
+		items.push(tag);
+	}
+
+	fn list(element: Option<TagID>, items: Vec<FileOrderTag>) -> FileOrderTag {
+		FileOrderTag::List(FileOrderList { element, items })
+	}
+}
+
+/// Reads a named root tag of binary NBT, returning its name and the tag. Int and Long arrays are left in file order.
+/// Tags may be nested up to [DEFAULT_MAX_DEPTH](crate::io::DEFAULT_MAX_DEPTH) deep, and are read with a stack on the
+/// heap instead of by recursion.
+pub fn read<R: Read>(reader: &mut R) -> Result<(String, FileOrderTag), NbtError> {
+	frames::read(reader, &mut FileOrderTree)
+}
+
+/// Writes a named root tag as binary NBT, returning the number of bytes that were written. Arrays that are still in
+/// file order are written without converting them. Fails if a List has elements of different types.
+pub fn write<W: Write>(writer: &mut W, name: &str, tag: &FileOrderTag) -> Result<usize, NbtError> {
+	Ok(tag.id().nbt_write(writer)? + name.nbt_write(writer)? + tag.write_payload(writer)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, io::{ read_named_tag, write_named_tag, DEFAULT_MAX_DEPTH } };
+
+	#[test]
+	fn lazy_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("Bytes", Tag::ByteArray(vec![1, -2])),
+			("Ints", Tag::IntArray(vec![1, -2, i32::MAX])),
+			("Longs", Tag::LongArray(vec![i64::MIN, 0x0102030405060708])),
+			("Pos", list!(0.5, 64.0, 0.5)),
+			("Items", Tag::List(ListTag::Compound(vec![Map::from_iter([("id".to_owned(), Tag::from("minecraft:stone"))])]))),
+			("Empty", Tag::List(ListTag::Empty)),
+			("Nested", Tag::List(ListTag::List(vec![ListTag::Short(vec![1]), ListTag::Int(Vec::new())]))),
+			("Name", "é")
+		);
+		let mut bytes = Vec::new();
+		write_named_tag(&mut bytes, &tag, "root")?;
+		let (name, mut read_tag) = read(&mut bytes.as_slice())?;
+		assert_eq!((name.as_str(), read_tag.to_tag()?), ("root", tag.clone()));
+		assert_eq!(FileOrderTag::from_tag(&tag), read_tag);
+
+		// Unchanged trees are written back byte for byte, without converting the arrays.
+		let mut written = Vec::new();
+		let size = write(&mut written, "root", &read_tag)?;
+		assert_eq!(size, written.len());
+		assert_eq!(written, bytes);
+
+		let Some(FileOrderTag::LongArray(longs)) = read_tag.get_mut("Longs") else { panic!("Longs is a LongArray.") };
+		assert!(longs.is_file_order());
+		assert_eq!(longs.get(1), Some(0x0102030405060708));
+		assert!(longs.is_file_order());
+		longs.as_mut_slice()[0] = 5;
+		assert!(!longs.is_file_order());
+		let mut written = Vec::new();
+		write(&mut written, "root", &read_tag)?;
+		let (_, written_tag) = read_named_tag(&mut written.as_slice())?;
+		let mut expected = tag.clone();
+		if let Tag::Compound(map) = &mut expected {
+			map.insert("Longs".to_owned(), Tag::LongArray(vec![5, 0x0102030405060708]));
+		}
+		assert_eq!(written_tag, expected);
+
+		let mut list = FileOrderList::new();
+		list.push(FileOrderTag::Int(1))?;
+		assert!(list.push(FileOrderTag::String("a".to_owned())).is_err());
+		let mut map = FileOrderMap::new();
+		assert!(map.insert("a", FileOrderTag::List(list)).is_none());
+		assert!(map.insert("a", FileOrderTag::Byte(1)).is_some());
+		assert_eq!(map.len(), 1);
+		assert_eq!(map.remove("a"), Some(FileOrderTag::Byte(1)));
+
+		let mut deep = Vec::new();
+		for _ in 0..=DEFAULT_MAX_DEPTH {
+			deep.extend([9, 0, 0, 0, 1]);
+		}
+		assert!(read(&mut [&[9, 0, 0][..], &deep].concat().as_slice()).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn list_types_test() -> Result<(), NbtError> {
+		let tag = compound!(("Items", Tag::List(ListTag::Compound(vec![Map::from_iter([("Slot".to_owned(), Tag::Byte(0))])]))));
+		let mut bytes = Vec::new();
+		write_named_tag(&mut bytes, &tag, "")?;
+		let (_, mut read_tag) = read(&mut bytes.as_slice())?;
+		let Some(FileOrderTag::List(items)) = read_tag.get_mut("Items") else { panic!("Items is a List.") };
+		// Elements can not be replaced with tags of another type, so the List can always be written.
+		assert!(items.set(0, FileOrderTag::Int(1)).is_err());
+		assert!(items.set(1, FileOrderTag::Compound(FileOrderMap::new())).is_err());
+		assert!(items.list_mut(0).is_none());
+		items.compound_mut(0).expect("The element is a Compound.").insert("Slot", FileOrderTag::Byte(3));
+		let mut written = Vec::new();
+		write(&mut written, "", &read_tag)?;
+		let expected = compound!(("Items", Tag::List(ListTag::Compound(vec![Map::from_iter([("Slot".to_owned(), Tag::Byte(3))])]))));
+		assert_eq!(read_named_tag(&mut written.as_slice())?.1, expected);
+		assert_eq!(read_tag.to_tag()?, expected);
+		Ok(())
+	}
+
+	#[test]
+	fn repeated_keys_test() -> Result<(), NbtError> {
+		// A Compound with the key "a" twice, which is read in linear time like any other.
+		let bytes = [10, 0, 0, 1, 0, 1, b'a', 1, 1, 0, 1, b'a', 2, 0];
+		let (_, mut tag) = read(&mut bytes.as_slice())?;
+		assert_eq!(tag.get("a"), Some(&FileOrderTag::Byte(2)));
+		assert_eq!(tag.to_tag()?, compound!(("a", 2i8)));
+		let mut written = Vec::new();
+		write(&mut written, "", &tag)?;
+		assert_eq!(written, bytes);
+		let FileOrderTag::Compound(map) = &mut tag else { panic!("The root is a Compound.") };
+		assert_eq!(map.len(), 2);
+		assert_eq!(map.remove("a"), Some(FileOrderTag::Byte(2)));
+		assert!(map.is_empty());
+
+		let many = Tag::Compound((0..100_000).map(|key| (key.to_string(), Tag::Int(key))).collect());
+		let mut bytes = Vec::new();
+		write_named_tag(&mut bytes, &many, "")?;
+		let (_, read_many) = read(&mut bytes.as_slice())?;
+		assert_eq!(read_many.to_tag()?, many);
+		Ok(())
+	}
+}
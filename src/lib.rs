@@ -1,13 +1,96 @@
 #![allow(unused)]
+pub mod anonymize;
+#[cfg(feature = "bumpalo")]
+pub mod arena;
+pub mod backup;
+pub mod bits;
+pub mod block_entity;
+pub mod bulk;
+pub mod cache;
+pub(crate) mod base64;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod chunk;
+pub mod client;
+pub mod clipboard;
+pub mod columns;
+#[cfg(feature = "compat")]
+pub mod compat;
+pub mod context;
+pub mod diagram;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod entity;
 pub mod family;
+pub mod fingerprint;
+pub(crate) mod frames;
+pub mod fuzz;
+pub mod generator;
+pub mod git;
+pub mod hashed;
+pub(crate) mod hex;
 pub mod io;
+pub mod keys;
+pub mod lazy;
+pub mod map;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+pub mod order;
+pub mod packed;
+pub mod parse;
+pub mod path;
+pub mod pixels;
+pub mod player;
+pub mod pos;
+pub mod reflect;
+pub mod region;
+pub mod schema;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod sink;
+pub mod stats;
+pub mod source;
+pub(crate) mod sha256;
 pub(crate) mod table;
 pub mod tag;
+pub mod template;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod time;
+pub mod tree;
+pub mod vfs;
+pub mod view;
 pub mod macros;
 pub mod snbt;
+pub mod stream;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod world;
 // format is incomplete, and I have no need to finish it, so it will remain incomplete until it is needed.
 // pub mod format;
 
+// Lets the expansions of the proc-macros, which name this crate as `::rustnbt`, work inside it too.
+extern crate self as rustnbt;
+
+/// Parses an SNBT string literal at compile time into a [tag::Tag]. Invalid SNBT is a compile error.
+/// ### Example
+/// ```
+/// # use rustnbt::{ snbt, compound, list };
+/// let item = snbt!("{id: 'minecraft:diamond_sword', Count: 1b, tag: {Damage: 0, Lore: ['Sharp']}}");
+/// assert_eq!(item, compound!(
+///     ("id", "minecraft:diamond_sword"),
+///     ("Count", 1i8),
+///     ("tag", compound!(("Damage", 0), ("Lore", list!("Sharp"))))
+/// ));
+/// ```
+/// Lists with elements of different types do not compile:
+/// ```compile_fail
+/// let mixed = rustnbt::snbt!("[1b, 2s]");
+/// ```
+pub use rustnbtmacro::snbt;
+
 /// This is the Error type returned from NbtRead and NbtWrite operations that fail.
 #[derive(thiserror::Error, Debug)]
 pub enum NbtError {
@@ -23,8 +106,56 @@ pub enum NbtError {
 	/// End marker (0x00) was encountered.
 	#[error("Encountered the End tag ID marker.")]
 	End,
+	/// Failure to parse SNBT.
+	#[error("{0}")]
+	SnbtError(#[from] snbt::ParseError),
+	/// Error from serde_json while reading or writing a JSON file.
+	#[cfg(feature = "json")]
+	#[error("{0}")]
+	JsonError(#[from] serde_json::Error),
+	/// Error raised by a `Serialize` or `Deserialize` implementation, or by a type mismatch during serde conversion.
+	#[cfg(feature = "serde")]
+	#[error("{0}")]
+	Message(String),
+	/// Error from rusqlite while storing or loading tags in a SQLite database.
+	#[cfg(feature = "sqlite")]
+	#[error("{0}")]
+	SqliteError(#[from] rusqlite::Error),
+	/// Decryption failed, because the key or passphrase is wrong or the encrypted data was modified.
+	#[cfg(feature = "encryption")]
+	#[error("Failed to decrypt: the key is wrong or the data was modified.")]
+	DecryptionFailed,
+	/// An error with where in a world it happened. See the [context] module.
+	#[error("{context}: {source}")]
+	Context { context: Box<context::ErrorContext>, source: Box<NbtError> },
+}
+
+/// Creates an [NbtError::IoError] of kind [std::io::ErrorKind::InvalidData] for malformed files.
+pub(crate) fn invalid_data(message: &str) -> NbtError {
+	std::io::Error::new(std::io::ErrorKind::InvalidData, message).into()
 }
 
+// Tags, the errors, and the readers and files over Send + Sync sources can be moved to other threads and shared
+// between them, so that one thread can parse while another consumes. This stops compiling if a change breaks that.
+const _: () = {
+	fn send_sync<T: Send + Sync>() {}
+	fn assert_send_sync() {
+		send_sync::<tag::Tag>();
+		send_sync::<tag::ListTag>();
+		send_sync::<tag::NamedTag>();
+		send_sync::<Map>();
+		send_sync::<NbtError>();
+		send_sync::<path::TagPath>();
+		send_sync::<path::Flatten<'_>>();
+		send_sync::<path::IntoFlatten>();
+		send_sync::<io::Decoder>();
+		send_sync::<io::IncrementalParser<std::fs::File>>();
+		send_sync::<stream::NbtReader<std::fs::File>>();
+		send_sync::<region::Region>();
+		send_sync::<world::World>();
+	}
+};
+
 // indexmap preserves the insertion order of elements.
 // Minecraft does not care what order elements are in, one thing to note
 // is that without having insertion order preserved, the order of elements
@@ -36,6 +167,13 @@ use indexmap::IndexMap;
 /// The mapping type used for Tag::Compound.
 pub type Map = IndexMap<String, tag::Tag>;
 // Fallback to HashMap.
-#[cfg(not(feature = "preserve_order"))]
+#[cfg(all(not(feature = "preserve_order"), not(feature = "stable_hash")))]
+/// The mapping type used for Tag::Compound.
+pub type Map = std::collections::HashMap<String, tag::Tag>;
+// A HashMap with fixed hash keys iterates in the same order on every run.
+#[cfg(all(not(feature = "preserve_order"), feature = "stable_hash"))]
 /// The mapping type used for Tag::Compound.
-pub type Map = std::collections::HashMap<String, tag::Tag>;
\ No newline at end of file
+pub type Map = std::collections::HashMap<String, tag::Tag, MapHasher>;
+/// The hasher that [Map] uses with the `stable_hash` feature. It hashes with fixed keys, so the iteration order of a [Map]
+/// only depends on its contents and the order they were inserted in. The order may still change between Rust versions.
+pub type MapHasher = std::hash::BuildHasherDefault<std::collections::hash_map::DefaultHasher>;
\ No newline at end of file
@@ -1,16 +1,17 @@
 /// Shorthand way to create a Tag::Compound.
 /// Example:
 /// ```no_run
+/// # use rustnbt::{compound, tag::Tag};
 /// compound!{
 ///     ("Item One", 0i8),
 ///     (String::from("Item Two"), 2i32),
 ///     ("Item Three", Tag::Byte(1))
-/// }
+/// };
 /// ```
 #[macro_export]
 macro_rules! compound {
 	($(($name:expr, $value:expr)),+) => {
-		$crate::tag::Tag::Compound($crate::Map::from([
+		$crate::tag::Tag::Compound($crate::Map::from_iter([
 			$(
 				($crate::list!(@literal_to_owned;$name), $crate::tag::Tag::from($value)),
 			)+
@@ -21,6 +22,7 @@ macro_rules! compound {
 /// Shorthand way to create a Tag::List.
 /// Example:
 /// ```no_run
+/// # use rustnbt::list;
 /// list!{ 1, 2, 3, 4, 5, 6, 7, 8, 9, 10 };
 /// list![
 ///     "One",
@@ -43,7 +45,7 @@ macro_rules! list {
 		$crate::tag::Tag::List($crate::tag::ListTag::from(std::vec![$crate::list!(@literal_to_owned;$value); $repititions]))
 	};
 	() => {
-		$crate::tag::Tag::List($crate::tag::ListTag::Empty);
+		$crate::tag::Tag::List($crate::tag::ListTag::Empty)
 	};
 	(@literal_to_owned;$lit:literal) => {
 		$lit.to_owned()
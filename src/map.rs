@@ -0,0 +1,169 @@
+// https://minecraft.fandom.com/wiki/Map_item_format
+
+#![doc = r#"
+Utilities for map items (the `map_#.dat` files found in a world's `data` folder).
+
+A map's pixels are stored as a 128x128 [Tag::ByteArray] at `data.colors`.
+Each byte is a color ID, where the upper six bits select one of the base colors
+and the lower two bits select the shade of that color.
+"#]
+
+use crate::{
+	tag::Tag,
+};
+
+/// Width of a map in pixels.
+pub const WIDTH: usize = 128;
+/// Height of a map in pixels.
+pub const HEIGHT: usize = 128;
+
+/// The vanilla map base colors, indexed by base color ID.
+/// Base color `0` is transparent, so its RGB value is never used.
+pub const BASE_COLORS: [[u8; 3]; 62] = [
+	[0, 0, 0],       // NONE
+	[127, 178, 56],  // GRASS
+	[247, 233, 163], // SAND
+	[199, 199, 199], // WOOL
+	[255, 0, 0],     // FIRE
+	[160, 160, 255], // ICE
+	[167, 167, 167], // METAL
+	[0, 124, 0],     // PLANT
+	[255, 255, 255], // SNOW
+	[164, 168, 184], // CLAY
+	[151, 109, 77],  // DIRT
+	[112, 112, 112], // STONE
+	[64, 64, 255],   // WATER
+	[143, 119, 72],  // WOOD
+	[255, 252, 245], // QUARTZ
+	[216, 127, 51],  // COLOR_ORANGE
+	[178, 76, 216],  // COLOR_MAGENTA
+	[102, 153, 216], // COLOR_LIGHT_BLUE
+	[229, 229, 51],  // COLOR_YELLOW
+	[127, 204, 25],  // COLOR_LIGHT_GREEN
+	[242, 127, 165], // COLOR_PINK
+	[76, 76, 76],    // COLOR_GRAY
+	[153, 153, 153], // COLOR_LIGHT_GRAY
+	[76, 127, 153],  // COLOR_CYAN
+	[127, 63, 178],  // COLOR_PURPLE
+	[51, 76, 178],   // COLOR_BLUE
+	[102, 76, 51],   // COLOR_BROWN
+	[102, 127, 51],  // COLOR_GREEN
+	[153, 51, 51],   // COLOR_RED
+	[25, 25, 25],    // COLOR_BLACK
+	[250, 238, 77],  // GOLD
+	[92, 219, 213],  // DIAMOND
+	[74, 128, 255],  // LAPIS
+	[0, 217, 58],    // EMERALD
+	[129, 86, 49],   // PODZOL
+	[112, 2, 0],     // NETHER
+	[209, 177, 161], // TERRACOTTA_WHITE
+	[159, 82, 36],   // TERRACOTTA_ORANGE
+	[149, 87, 108],  // TERRACOTTA_MAGENTA
+	[112, 108, 138], // TERRACOTTA_LIGHT_BLUE
+	[186, 133, 36],  // TERRACOTTA_YELLOW
+	[103, 117, 53],  // TERRACOTTA_LIGHT_GREEN
+	[160, 77, 78],   // TERRACOTTA_PINK
+	[57, 41, 35],    // TERRACOTTA_GRAY
+	[135, 107, 98],  // TERRACOTTA_LIGHT_GRAY
+	[87, 92, 92],    // TERRACOTTA_CYAN
+	[122, 73, 88],   // TERRACOTTA_PURPLE
+	[76, 62, 92],    // TERRACOTTA_BLUE
+	[76, 50, 35],    // TERRACOTTA_BROWN
+	[76, 82, 42],    // TERRACOTTA_GREEN
+	[142, 60, 46],   // TERRACOTTA_RED
+	[37, 22, 16],    // TERRACOTTA_BLACK
+	[189, 48, 49],   // CRIMSON_NYLIUM
+	[148, 63, 97],   // CRIMSON_STEM
+	[92, 25, 29],    // CRIMSON_HYPHAE
+	[22, 126, 134],  // WARPED_NYLIUM
+	[58, 142, 140],  // WARPED_STEM
+	[86, 44, 62],    // WARPED_HYPHAE
+	[20, 180, 133],  // WARPED_WART_BLOCK
+	[100, 100, 100], // DEEPSLATE
+	[216, 175, 147], // RAW_IRON
+	[127, 167, 150], // GLOW_LICHEN
+];
+
+/// The brightness multipliers for each of the four shades, out of 255.
+pub const SHADE_MULTIPLIERS: [u16; 4] = [180, 220, 255, 135];
+
+/// Converts a single map color byte into an RGBA pixel.
+/// Base color `0` and unknown base colors are fully transparent.
+pub fn color_rgba(color: i8) -> [u8; 4] {
+	let color = color as u8;
+	let base = (color >> 2) as usize;
+	let shade = SHADE_MULTIPLIERS[(color & 3) as usize];
+	match BASE_COLORS.get(base) {
+		Some(&[r, g, b]) if base != 0 => {
+			let apply = |channel: u8| (channel as u16 * shade / 255) as u8;
+			[apply(r), apply(g), apply(b), 255]
+		}
+		_ => [0, 0, 0, 0],
+	}
+}
+
+/// Converts map color bytes into a flat buffer of RGBA pixels (4 bytes per pixel).
+pub fn colors_to_rgba(colors: &[i8]) -> Vec<u8> {
+	colors.iter().flat_map(|&color| color_rgba(color)).collect()
+}
+
+/// Finds the `colors` array of a map.<br>
+/// Accepts either the root tag of a `map_#.dat` file, or the `data` compound within it.
+/// Returns [None] if there is no `colors` [Tag::ByteArray] or if it is not 128x128.
+pub fn colors(map: &Tag) -> Option<&[i8]> {
+	let Tag::Compound(root) = map else {
+		return None;
+	};
+	let data = match root.get("data") {
+		Some(Tag::Compound(data)) => data,
+		_ => root,
+	};
+	match data.get("colors") {
+		Some(Tag::ByteArray(colors)) if colors.len() == WIDTH * HEIGHT => Some(colors),
+		_ => None,
+	}
+}
+
+/// Renders a map into an RGBA image using the vanilla color palette.<br>
+/// Accepts either the root tag of a `map_#.dat` file, or the `data` compound within it.
+/// Returns [None] if the map does not have a valid `colors` array.
+#[cfg(feature = "image")]
+pub fn render(map: &Tag) -> Option<image::RgbaImage> {
+	let colors = colors(map)?;
+	image::RgbaImage::from_raw(WIDTH as u32, HEIGHT as u32, colors_to_rgba(colors))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::compound;
+
+	#[test]
+	fn color_test() {
+		// Transparent
+		assert_eq!(color_rgba(0), [0, 0, 0, 0]);
+		assert_eq!(color_rgba(3), [0, 0, 0, 0]);
+		// GRASS at full brightness.
+		assert_eq!(color_rgba(1 << 2 | 2), [127, 178, 56, 255]);
+		// GRASS at the darkest shade.
+		assert_eq!(color_rgba(1 << 2 | 3), [67, 94, 29, 255]);
+		// GLOW_LICHEN, shade 0, which has the high bit set.
+		assert_eq!(color_rgba((61 << 2) as u8 as i8), [89, 117, 105, 255]);
+		// Unknown base color.
+		assert_eq!(color_rgba(-1), [0, 0, 0, 0]);
+	}
+
+	#[test]
+	fn colors_test() {
+		let map = compound!(
+			("data", compound!(
+				("scale", 0i8),
+				("colors", vec![2i8 << 2 | 2; WIDTH * HEIGHT])
+			)),
+			("DataVersion", 3120)
+		);
+		let colors = colors(&map).expect("Failed to find colors.");
+		assert_eq!(colors_to_rgba(colors)[..4], [247, 233, 163, 255]);
+		assert!(super::colors(&compound!(("colors", vec![0i8; 16]))).is_none());
+	}
+}
@@ -0,0 +1,295 @@
+#![doc = r#"
+Conversion between [Tag] and [MessagePack](https://msgpack.org), enabled with the `msgpack` feature.
+
+| NBT | MessagePack |
+|-----|-------------|
+| Byte | int 8 |
+| Short | int 16 |
+| Int | int 32 |
+| Long | int 64 |
+| Float | float 32 |
+| Double | float 64 |
+| ByteArray | bin |
+| String | str |
+| List | array |
+| Compound | map with str keys |
+| IntArray | ext type 11, big endian 32-bit integers |
+| LongArray | ext type 12, big endian 64-bit integers |
+
+Numbers are always written with the format that matches their NBT type, so writing a [Tag] and reading it back gives the same [Tag].<br>
+When reading MessagePack that did not come from NBT, the smaller formats (positive and negative fixint, and the unsigned formats)
+become Int, or Long if the value does not fit in an Int. Booleans become Byte.
+`nil`, extension types other than 11 and 12, unsigned values that do not fit in a Long, and maps with keys that are not strings can not be read.
+
+```
+# use rustnbt::{ compound, tag::Tag };
+let tag = compound!(("Health", Tag::Float(20.0)), ("Pos", Tag::IntArray(vec![1, 2, 3])));
+let mut bytes = Vec::new();
+rustnbt::msgpack::write(&mut bytes, &tag).unwrap();
+assert_eq!(rustnbt::msgpack::read(bytes.as_slice()).unwrap(), tag);
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	io::NbtRead,
+	parse::{ ParseOptions, ParseOutcome },
+	path::{ PathSegment, TagPath },
+	tag::{
+		Tag,
+		ListTag,
+	},
+};
+use rmp::{ Marker, encode };
+use std::io::{ Read, Write };
+
+/// Extension type used for IntArray.
+pub const INT_ARRAY_EXT: i8 = 11;
+/// Extension type used for LongArray.
+pub const LONG_ARRAY_EXT: i8 = 12;
+
+fn encoded<T>(result: Result<T, encode::ValueWriteError>) -> Result<T, NbtError> {
+	result.map_err(|err| std::io::Error::from(err).into())
+}
+
+fn length(value: usize) -> Result<u32, NbtError> {
+	u32::try_from(value).map_err(|_| invalid_data("Value is too long for MessagePack."))
+}
+
+/// Writes a [Tag] to a writer as MessagePack.
+pub fn write<W: Write>(mut writer: W, tag: &Tag) -> Result<(), NbtError> {
+	write_tag(&mut writer, tag)
+}
+
+fn write_tag<W: Write>(writer: &mut W, tag: &Tag) -> Result<(), NbtError> {
+	match tag {
+		Tag::Byte(value) => encoded(encode::write_i8(writer, *value)),
+		Tag::Short(value) => encoded(encode::write_i16(writer, *value)),
+		Tag::Int(value) => encoded(encode::write_i32(writer, *value)),
+		Tag::Long(value) => encoded(encode::write_i64(writer, *value)),
+		Tag::Float(value) => encoded(encode::write_f32(writer, *value)),
+		Tag::Double(value) => encoded(encode::write_f64(writer, *value)),
+		Tag::ByteArray(values) => encoded(encode::write_bin(writer, bytemuck::cast_slice(values))),
+		Tag::String(value) => encoded(encode::write_str(writer, value)),
+		Tag::List(list) => write_list(writer, list),
+		Tag::Compound(map) => {
+			encoded(encode::write_map_len(writer, length(map.len())?))?;
+			for (key, tag) in map.iter() {
+				encoded(encode::write_str(writer, key))?;
+				write_tag(writer, tag)?;
+			}
+			Ok(())
+		}
+		Tag::IntArray(values) => {
+			encoded(encode::write_ext_meta(writer, length(values.len() * 4)?, INT_ARRAY_EXT))?;
+			values.iter().try_for_each(|value| writer.write_all(&value.to_be_bytes()))?;
+			Ok(())
+		}
+		Tag::LongArray(values) => {
+			encoded(encode::write_ext_meta(writer, length(values.len() * 8)?, LONG_ARRAY_EXT))?;
+			values.iter().try_for_each(|value| writer.write_all(&value.to_be_bytes()))?;
+			Ok(())
+		}
+	}
+}
+
+fn write_list<W: Write>(writer: &mut W, list: &ListTag) -> Result<(), NbtError> {
+	macro_rules! write_items {
+		($($title:ident)+) => {
+			match list {
+				ListTag::Empty => encoded(encode::write_array_len(writer, 0)).map(|_| ()),
+				$(
+					ListTag::$title(values) => {
+						encoded(encode::write_array_len(writer, length(values.len())?))?;
+						values.iter().try_for_each(|value| write_tag(writer, &Tag::$title(value.clone())))
+					}
+				)+
+			}
+		};
+	}
+	write_items!(Byte Short Int Long Float Double ByteArray String List Compound IntArray LongArray)
+}
+
+/// Reads a [Tag] from MessagePack.
+pub fn read<R: Read>(reader: R) -> Result<Tag, NbtError> {
+	read_with(reader, &ParseOptions::default()).map(ParseOutcome::into_value)
+}
+
+/// Reads a [Tag] from MessagePack, normalizing map keys and handling keys that collide with the [ParseOptions].
+pub fn read_with<R: Read>(mut reader: R, options: &ParseOptions) -> Result<ParseOutcome<Tag>, NbtError> {
+	let mut options = options.clone();
+	let tag = read_tag(&mut reader, &mut options, &mut TagPath::new())?;
+	Ok(options.finish(tag))
+}
+
+fn read_bytes<R: Read>(reader: &mut R, length: usize) -> Result<Vec<u8>, NbtError> {
+	let mut buf = vec![0u8; length];
+	reader.read_exact(&mut buf)?;
+	Ok(buf)
+}
+
+/// Converts an integer that was not written with an exact NBT type to Int, or Long if it does not fit.
+fn integer<T: Into<i128>>(value: T) -> Result<Tag, NbtError> {
+	let value = value.into();
+	if let Ok(value) = i32::try_from(value) {
+		Ok(Tag::Int(value))
+	} else {
+		i64::try_from(value).map(Tag::Long).map_err(|_| invalid_data("MessagePack integer is too large for NBT."))
+	}
+}
+
+fn read_tag<R: Read>(reader: &mut R, options: &mut ParseOptions, path: &mut TagPath) -> Result<Tag, NbtError> {
+	let marker = rmp::decode::read_marker(reader).map_err(|err| NbtError::from(err.0))?;
+	let length = match marker {
+		Marker::FixStr(length) | Marker::FixArray(length) | Marker::FixMap(length) => length as usize,
+		Marker::FixExt1 => 1,
+		Marker::FixExt2 => 2,
+		Marker::FixExt4 => 4,
+		Marker::FixExt8 => 8,
+		Marker::FixExt16 => 16,
+		Marker::Bin8 | Marker::Str8 | Marker::Ext8 => u8::nbt_read(reader)? as usize,
+		Marker::Bin16 | Marker::Str16 | Marker::Array16 | Marker::Map16 | Marker::Ext16 => u16::nbt_read(reader)? as usize,
+		Marker::Bin32 | Marker::Str32 | Marker::Array32 | Marker::Map32 | Marker::Ext32 => u32::nbt_read(reader)? as usize,
+		_ => 0,
+	};
+	Ok(match marker {
+		Marker::I8 => Tag::Byte(i8::nbt_read(reader)?),
+		Marker::I16 => Tag::Short(i16::nbt_read(reader)?),
+		Marker::I32 => Tag::Int(i32::nbt_read(reader)?),
+		Marker::I64 => Tag::Long(i64::nbt_read(reader)?),
+		Marker::F32 => Tag::Float(f32::nbt_read(reader)?),
+		Marker::F64 => Tag::Double(f64::nbt_read(reader)?),
+		Marker::FixPos(value) => integer(value)?,
+		Marker::FixNeg(value) => integer(value)?,
+		Marker::U8 => integer(u8::nbt_read(reader)?)?,
+		Marker::U16 => integer(u16::nbt_read(reader)?)?,
+		Marker::U32 => integer(u32::nbt_read(reader)?)?,
+		Marker::U64 => integer(u64::nbt_read(reader)?)?,
+		Marker::True => Tag::Byte(1),
+		Marker::False => Tag::Byte(0),
+		Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => read_bin(reader, length)?,
+		Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32 => read_str(reader, length)?,
+		Marker::FixArray(_) | Marker::Array16 | Marker::Array32 => read_list(reader, length, options, path)?,
+		Marker::FixMap(_) | Marker::Map16 | Marker::Map32 => read_map(reader, length, options, path)?,
+		Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 | Marker::FixExt16
+		| Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => read_ext(reader, length)?,
+		Marker::Null => return Err(invalid_data("MessagePack nil can not be converted to NBT.")),
+		Marker::Reserved => return Err(invalid_data("Encountered reserved MessagePack marker.")),
+	})
+}
+
+fn read_bin<R: Read>(reader: &mut R, length: usize) -> Result<Tag, NbtError> {
+	Ok(Tag::ByteArray(bytemuck::cast_vec(read_bytes(reader, length)?)))
+}
+
+fn read_str<R: Read>(reader: &mut R, length: usize) -> Result<Tag, NbtError> {
+	Ok(Tag::String(String::from_utf8(read_bytes(reader, length)?)?))
+}
+
+fn read_list<R: Read>(reader: &mut R, length: usize, options: &mut ParseOptions, path: &mut TagPath) -> Result<Tag, NbtError> {
+	let mut tags = Vec::new();
+	for index in 0..length {
+		path.push(PathSegment::Index(index));
+		tags.push(read_tag(reader, options, path)?);
+		path.pop();
+	}
+	Ok(Tag::List(ListTag::try_from(tags).map_err(|_| invalid_data("MessagePack array elements must all have the same NBT type."))?))
+}
+
+fn read_map<R: Read>(reader: &mut R, length: usize, options: &mut ParseOptions, path: &mut TagPath) -> Result<Tag, NbtError> {
+	let mut map = Map::default();
+	for _ in 0..length {
+		let Tag::String(key) = read_tag(reader, options, path)? else {
+			return Err(invalid_data("MessagePack map keys must be strings."));
+		};
+		path.push(PathSegment::Key(key.clone()));
+		let value = read_tag(reader, options, path);
+		path.pop();
+		options.insert(&mut map, || path.clone(), key, value?)?;
+	}
+	Ok(Tag::Compound(map))
+}
+
+fn read_ext<R: Read>(reader: &mut R, length: usize) -> Result<Tag, NbtError> {
+	let ext = i8::nbt_read(reader)?;
+	let (size, bytes) = match ext {
+		INT_ARRAY_EXT => (4, read_bytes(reader, length)?),
+		LONG_ARRAY_EXT => (8, read_bytes(reader, length)?),
+		_ => return Err(invalid_data("Unsupported MessagePack extension type.")),
+	};
+	if !length.is_multiple_of(size) {
+		return Err(invalid_data("MessagePack array extension has a partial element."));
+	}
+	let chunks = bytes.chunks_exact(size);
+	Ok(if ext == INT_ARRAY_EXT {
+		Tag::IntArray(chunks.map(|chunk| i32::from_be_bytes(chunk.try_into().expect("Chunk has 4 bytes."))).collect())
+	} else {
+		Tag::LongArray(chunks.map(|chunk| i64::from_be_bytes(chunk.try_into().expect("Chunk has 8 bytes."))).collect())
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, parse::{ KeyCollisionPolicy, KeyNormalization, Warning } };
+
+	#[test]
+	fn roundtrip_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("byte", Tag::Byte(-3)),
+			("short", Tag::Short(300)),
+			("int", 7),
+			("long", Tag::Long(5)),
+			("float", Tag::Float(0.1)),
+			("double", Tag::Double(0.1)),
+			("bytes", Tag::ByteArray(vec![-1, 2])),
+			("ints", Tag::IntArray(vec![-1, i32::MAX])),
+			("longs", Tag::LongArray(vec![i64::MIN])),
+			("list", list!(1i8, 2i8)),
+			("empty", list!()),
+			("nested", Tag::List(ListTag::Compound(vec![Map::default()])))
+		);
+		let mut bytes = Vec::new();
+		write(&mut bytes, &tag)?;
+		assert_eq!(read(bytes.as_slice())?, tag);
+		Ok(())
+	}
+
+	#[test]
+	fn foreign_test() -> Result<(), NbtError> {
+		// {"a": 1, "b": 4294967295, "c": true}
+		let bytes = [0x83, 0xA1, b'a', 0x01, 0xA1, b'b', 0xCE, 0xFF, 0xFF, 0xFF, 0xFF, 0xA1, b'c', 0xC3];
+		assert_eq!(read(bytes.as_slice())?, compound!(
+			("a", 1),
+			("b", Tag::Long(u32::MAX as i64)),
+			("c", Tag::Byte(1))
+		));
+		assert!(read([0xC0].as_slice()).is_err());
+		assert!(read([0xCF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF].as_slice()).is_err());
+		assert!(read([0x92, 0x01, 0xA1, b'a'].as_slice()).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn collision_test() -> Result<(), NbtError> {
+		// {"a": [{"Id": 1, "ID": 2}]}
+		let bytes = [0x81, 0xA1, b'a', 0x91, 0x82, 0xA2, b'I', b'd', 0x01, 0xA2, b'I', b'D', 0x02];
+		assert_eq!(read(bytes.as_slice())?, compound!(("a", Tag::List(ListTag::Compound(vec![
+			Map::from_iter([("Id".to_owned(), Tag::Int(1)), ("ID".to_owned(), Tag::Int(2))]),
+		])))));
+		let options = ParseOptions::new().normalize_keys(KeyNormalization::Lowercase).key_collisions(KeyCollisionPolicy::Rename);
+		let outcome = read_with(bytes.as_slice(), &options)?;
+		assert_eq!(outcome.value, compound!(("a", Tag::List(ListTag::Compound(vec![
+			Map::from_iter([("id".to_owned(), Tag::Int(1)), ("id_2".to_owned(), Tag::Int(2))]),
+		])))));
+		let [Warning::KeyCollision { path, original, .. }] = &outcome.warnings[..] else {
+			panic!("Expected one key collision.");
+		};
+		assert_eq!(*path, TagPath::new().key("a").index(0));
+		assert_eq!(original, "ID");
+		assert!(read_with(bytes.as_slice(), &options.key_collisions(KeyCollisionPolicy::Error)).is_err());
+		Ok(())
+	}
+}
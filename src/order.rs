@@ -0,0 +1,267 @@
+#![doc = r#"
+A total order over tags, and sorting and deduplicating Lists with it.
+
+[Tag] only implements `PartialEq`, because Floats and Doubles can be NaN. [Tag::total_cmp] orders any two tags anyway,
+like [f64::total_cmp] does for floats:
+
+- Tags of different types are ordered by their type ID, so every Byte is less than every Short, and so on.
+- Integers are ordered by value. Floats and Doubles are ordered by [f32::total_cmp] and [f64::total_cmp], so
+  `-0.0 < 0.0` and NaN is ordered too.
+- Strings are ordered by their UTF-8 bytes, which is the same as ordering by characters.
+- Arrays and Lists are ordered lexicographically by their elements, like slices. Lists of different element types
+  are ordered by the type ID of their elements, and an empty List is less than any other.
+- Compounds are ordered lexicographically by their entries sorted by key, comparing the key of an entry and then its
+  value. The order does not depend on the order of the entries in the [Map].
+
+Two tags are `Equal` exactly when they are the same, except that NaNs with the same bits are equal to each other.
+
+[ListTag::sort_by_key_path] sorts a List by the value at a path in each element, such as the `Slot` of every item
+in an inventory, and [ListTag::dedup_by_key_path] keeps the first element for every value, such as the first
+enchantment with each `id`.
+
+```
+# use rustnbt::{ compound, path::TagPath, tag::{ Tag, ListTag } };
+let Tag::List(mut items) = Tag::parse("[{Slot: 2b, id: 'b'}, {Slot: 0b, id: 'a'}, {Slot: 1b, id: 'a'}]")? else { unreachable!() };
+items.sort_by_key_path(&TagPath::new().key("Slot"));
+items.dedup_by_key_path(&TagPath::new().key("id"));
+assert_eq!(Tag::List(items), Tag::parse("[{Slot: 0b, id: 'a'}, {Slot: 2b, id: 'b'}]")?);
+# Ok::<(), rustnbt::snbt::ParseError>(())
+```
+"#]
+
+use crate::{
+	Map,
+	path::TagPath,
+	tag::{ Tag, ListTag },
+	tag_info_table,
+};
+use std::{
+	borrow::Cow,
+	cmp::Ordering,
+};
+
+/// Values that have a total order. See the [module](self) documentation.
+trait TotalOrd {
+	fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! ord_total {
+	($($type:ty)+) => {
+		$(
+			impl TotalOrd for $type {
+				fn total_cmp(&self, other: &Self) -> Ordering {
+					self.cmp(other)
+				}
+			}
+		)+
+	};
+}
+
+ord_total!(i8 i16 i32 i64 String);
+
+impl TotalOrd for f32 {
+	fn total_cmp(&self, other: &Self) -> Ordering {
+		f32::total_cmp(self, other)
+	}
+}
+
+impl TotalOrd for f64 {
+	fn total_cmp(&self, other: &Self) -> Ordering {
+		f64::total_cmp(self, other)
+	}
+}
+
+impl<T: TotalOrd> TotalOrd for Vec<T> {
+	fn total_cmp(&self, other: &Self) -> Ordering {
+		self.iter().zip(other)
+			.map(|(left, right)| left.total_cmp(right))
+			.find(|ordering| ordering.is_ne())
+			.unwrap_or_else(|| self.len().cmp(&other.len()))
+	}
+}
+
+impl TotalOrd for Map {
+	fn total_cmp(&self, other: &Self) -> Ordering {
+		fn sorted(map: &Map) -> Vec<(&String, &Tag)> {
+			let mut entries: Vec<(&String, &Tag)> = map.iter().collect();
+			entries.sort_unstable_by(|left, right| left.0.cmp(right.0));
+			entries
+		}
+		let (left, right) = (sorted(self), sorted(other));
+		left.iter().zip(&right)
+			.map(|((left_key, left), (right_key, right))| left_key.cmp(right_key).then_with(|| left.total_cmp(right)))
+			.find(|ordering| ordering.is_ne())
+			.unwrap_or_else(|| left.len().cmp(&right.len()))
+	}
+}
+
+macro_rules! total_order {
+	($($id:literal $title:ident $type:path [$($impl:path)?])+) => {
+		impl TotalOrd for ListTag {
+			fn total_cmp(&self, other: &Self) -> Ordering {
+				match (self, other) {
+					$(
+						(ListTag::$title(left), ListTag::$title(right)) => left.total_cmp(right),
+					)+
+					_ => self.element_id().cmp(&other.element_id()),
+				}
+			}
+		}
+
+		impl Tag {
+			/// Compares two tags in the total order described in the [module](self) documentation.
+			pub fn total_cmp(&self, other: &Tag) -> Ordering {
+				match (self, other) {
+					$(
+						(Tag::$title(left), Tag::$title(right)) => TotalOrd::total_cmp(left, right),
+					)+
+					_ => self.id().cmp(&other.id()),
+				}
+			}
+		}
+	};
+}
+
+tag_info_table!(total_order);
+
+impl ListTag {
+	/// Compares two Lists in the total order described in the [module](self) documentation.
+	pub fn total_cmp(&self, other: &ListTag) -> Ordering {
+		TotalOrd::total_cmp(self, other)
+	}
+
+	/// Sorts the elements by the value at `path` in each of them, in the total order of tags. The sort is stable, and
+	/// elements that have no value at the path go last. An empty path sorts by the elements themselves.
+	pub fn sort_by_key_path(&mut self, path: &TagPath) {
+		self.rebuild(|tags| {
+			let mut keyed: Vec<(Option<Tag>, Tag)> = tags.into_iter().map(|tag| (path.get(&tag).map(Cow::into_owned), tag)).collect();
+			keyed.sort_by(|(left, _), (right, _)| compare_keys(left, right));
+			keyed.into_iter().map(|(_, tag)| tag).collect()
+		});
+	}
+
+	/// Removes every element whose value at `path` is equal to that of an earlier element, keeping the order of the
+	/// rest. Elements that have no value at the path are kept. An empty path removes elements equal to earlier ones.
+	pub fn dedup_by_key_path(&mut self, path: &TagPath) {
+		self.rebuild(|tags| {
+			let keys: Vec<Option<Tag>> = tags.iter().map(|tag| path.get(tag).map(Cow::into_owned)).collect();
+			// Sorting the indices by key puts equal keys next to each other, with the earliest first.
+			let mut order: Vec<usize> = (0..tags.len()).collect();
+			order.sort_by(|left, right| compare_keys(&keys[*left], &keys[*right]));
+			let mut keep = vec![true; tags.len()];
+			for pair in order.windows(2) {
+				if keys[pair[1]].is_some() && compare_keys(&keys[pair[0]], &keys[pair[1]]).is_eq() {
+					keep[pair[1]] = false;
+				}
+			}
+			tags.into_iter().zip(keep).filter_map(|(tag, keep)| keep.then_some(tag)).collect()
+		});
+	}
+
+	/// Removes every element that is equal to an earlier one, keeping the order of the rest.
+	pub fn dedup(&mut self) {
+		self.dedup_by_key_path(&TagPath::new());
+	}
+
+	/// Replaces the elements with the tags that `f` makes of them, which must have the same type.
+	fn rebuild<F: FnOnce(Vec<Tag>) -> Vec<Tag>>(&mut self, f: F) {
+		let tags = f(std::mem::replace(self, ListTag::Empty).into());
+		*self = ListTag::try_from(tags).expect("The elements still have the same type.");
+	}
+}
+
+/// Orders optional keys with the missing ones last.
+fn compare_keys(left: &Option<Tag>, right: &Option<Tag>) -> Ordering {
+	match (left, right) {
+		(Some(left), Some(right)) => left.total_cmp(right),
+		(left, right) => right.is_some().cmp(&left.is_some()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn total_cmp_test() {
+		let ascending = [
+			Tag::Byte(-1),
+			Tag::Byte(3),
+			Tag::Short(-100),
+			Tag::Float(f32::NEG_INFINITY),
+			Tag::Float(-0.0),
+			Tag::Float(0.0),
+			Tag::Float(f32::NAN),
+			Tag::ByteArray(vec![]),
+			Tag::ByteArray(vec![1]),
+			Tag::ByteArray(vec![1, 0]),
+			Tag::from("B"),
+			Tag::from("a"),
+			Tag::from("ab"),
+			Tag::List(ListTag::Empty),
+			list!(1, 2),
+			list!(2),
+			list!("a"),
+			compound!(("a", 1)),
+			compound!(("a", 1), ("b", 0)),
+			compound!(("a", 2)),
+			compound!(("b", 0)),
+			Tag::IntArray(vec![0]),
+		];
+		for (i, left) in ascending.iter().enumerate() {
+			for (j, right) in ascending.iter().enumerate() {
+				assert_eq!(left.total_cmp(right), i.cmp(&j), "{left} and {right}");
+			}
+		}
+		assert!(Tag::Double(f64::NAN).total_cmp(&Tag::Double(f64::NAN)).is_eq());
+		// The order of the entries of a Compound does not matter.
+		let forward = Tag::Compound(Map::from_iter([("a".to_owned(), Tag::Int(1)), ("b".to_owned(), Tag::Int(2))]));
+		let backward = Tag::Compound(Map::from_iter([("b".to_owned(), Tag::Int(2)), ("a".to_owned(), Tag::Int(1))]));
+		assert!(forward.total_cmp(&backward).is_eq());
+	}
+
+	#[test]
+	fn sort_dedup_test() {
+		let enchantment = |id: &str, level: i16| match compound!(("id", id), ("lvl", level)) {
+			Tag::Compound(map) => map,
+			_ => unreachable!(),
+		};
+		let mut list = ListTag::Compound(vec![
+			enchantment("sharpness", 5),
+			Map::default(),
+			enchantment("unbreaking", 3),
+			enchantment("sharpness", 2),
+			enchantment("mending", 1),
+			Map::default(),
+		]);
+		let id = TagPath::new().key("id");
+		list.sort_by_key_path(&TagPath::new().key("lvl"));
+		assert_eq!(list, ListTag::Compound(vec![
+			enchantment("mending", 1),
+			enchantment("sharpness", 2),
+			enchantment("unbreaking", 3),
+			enchantment("sharpness", 5),
+			Map::default(),
+			Map::default(),
+		]));
+		list.dedup_by_key_path(&id);
+		assert_eq!(list, ListTag::Compound(vec![
+			enchantment("mending", 1),
+			enchantment("sharpness", 2),
+			enchantment("unbreaking", 3),
+			Map::default(),
+			Map::default(),
+		]));
+		list.dedup();
+		assert_eq!(list.len(), 4);
+
+		let mut numbers = ListTag::Double(vec![3.0, f64::NAN, -0.0, 0.0, 3.0, 1.5]);
+		numbers.sort_by_key_path(&TagPath::new());
+		numbers.dedup();
+		assert_eq!(format!("{:?}", numbers), "Double([-0.0, 0.0, 1.5, 3.0, NaN])");
+		let mut empty = ListTag::Empty;
+		empty.dedup();
+		assert_eq!(empty, ListTag::Empty);
+	}
+}
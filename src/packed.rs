@@ -0,0 +1,187 @@
+#![doc = r#"
+Codecs for storing float arrays in ByteArray and IntArray tags.
+
+NBT has no array of floats, and a List of Floats takes 4 bytes per element. Some mods pack floats into arrays instead,
+either as raw bits or at a lower precision. [FloatPacking] converts a `&[f32]` to such an array and back:
+
+| Packing | Tag | Bytes per value | Range |
+|---|---|---|---|
+| [F32](FloatPacking::F32) | IntArray | 4 | lossless |
+| [F16](FloatPacking::F16) | ByteArray | 2 | half precision |
+| [Unorm8](FloatPacking::Unorm8) | ByteArray | 1 | `0.0..=1.0` |
+| [Snorm8](FloatPacking::Snorm8) | ByteArray | 1 | `-1.0..=1.0` |
+| [Unorm16](FloatPacking::Unorm16) | ByteArray | 2 | `0.0..=1.0` |
+| [Snorm16](FloatPacking::Snorm16) | ByteArray | 2 | `-1.0..=1.0` |
+
+Values with two bytes are stored big-endian, like the rest of NBT. Normalized values outside of their range are clamped.
+
+```
+# use rustnbt::{ tag::Tag, packed::FloatPacking };
+let values = [0.0, 0.5, -2.25, 65504.0];
+let tag = FloatPacking::F16.pack(&values);
+assert!(matches!(&tag, Tag::ByteArray(bytes) if bytes.len() == 8));
+assert_eq!(FloatPacking::F16.unpack(&tag)?, values);
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	NbtError,
+	tag::{
+		Tag,
+		TagID,
+	},
+};
+
+/// How floats are packed into an array tag. See the [module](self) documentation.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FloatPacking {
+	/// The bits of each float in an IntArray.
+	F32,
+	/// IEEE 754 half precision floats in a ByteArray.
+	F16,
+	/// `0.0..=1.0` as `0..=255` in a ByteArray.
+	Unorm8,
+	/// `-1.0..=1.0` as `-127..=127` in a ByteArray.
+	Snorm8,
+	/// `0.0..=1.0` as `0..=65535` in a ByteArray.
+	Unorm16,
+	/// `-1.0..=1.0` as `-32767..=32767` in a ByteArray.
+	Snorm16,
+}
+
+impl FloatPacking {
+	/// The type of the array tag that values are packed into.
+	pub const fn array_type(self) -> TagID {
+		match self {
+			FloatPacking::F32 => TagID::IntArray,
+			_ => TagID::ByteArray,
+		}
+	}
+
+	/// Packs values into an array tag.
+	pub fn pack(self, values: &[f32]) -> Tag {
+		let wide = |encode: fn(f32) -> u16| Tag::ByteArray(values.iter()
+			.flat_map(|value| encode(*value).to_be_bytes())
+			.map(|byte| byte as i8)
+			.collect());
+		match self {
+			FloatPacking::F32 => Tag::IntArray(values.iter().map(|value| value.to_bits() as i32).collect()),
+			FloatPacking::F16 => wide(f16_from_f32),
+			FloatPacking::Unorm8 => Tag::ByteArray(values.iter().map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8 as i8).collect()),
+			FloatPacking::Snorm8 => Tag::ByteArray(values.iter().map(|value| (value.clamp(-1.0, 1.0) * 127.0).round() as i8).collect()),
+			FloatPacking::Unorm16 => wide(|value| (value.clamp(0.0, 1.0) * 65535.0).round() as u16),
+			FloatPacking::Snorm16 => wide(|value| (value.clamp(-1.0, 1.0) * 32767.0).round() as i16 as u16),
+		}
+	}
+
+	/// Unpacks the values of an array tag. Fails if the tag is not of the [array type](Self::array_type),
+	/// or if a ByteArray of two byte values has an odd length.
+	pub fn unpack(self, tag: &Tag) -> Result<Vec<f32>, NbtError> {
+		match (self, tag) {
+			(FloatPacking::F32, Tag::IntArray(values)) => Ok(values.iter().map(|bits| f32::from_bits(*bits as u32)).collect()),
+			(FloatPacking::Unorm8, Tag::ByteArray(bytes)) => Ok(bytes.iter().map(|byte| *byte as u8 as f32 / 255.0).collect()),
+			(FloatPacking::Snorm8, Tag::ByteArray(bytes)) => Ok(bytes.iter().map(|byte| (*byte as f32 / 127.0).max(-1.0)).collect()),
+			(FloatPacking::F16 | FloatPacking::Unorm16 | FloatPacking::Snorm16, Tag::ByteArray(bytes)) => {
+				if !bytes.len().is_multiple_of(2) {
+					return Err(crate::invalid_data("Packed ByteArray has an odd length."));
+				}
+				let decode = match self {
+					FloatPacking::F16 => f16_to_f32,
+					FloatPacking::Unorm16 => |value: u16| value as f32 / 65535.0,
+					_ => |value: u16| (value as i16 as f32 / 32767.0).max(-1.0),
+				};
+				Ok(bytes.chunks_exact(2).map(|pair| decode(u16::from_be_bytes([pair[0] as u8, pair[1] as u8]))).collect())
+			}
+			_ => Err(crate::invalid_data(&format!("Expected {} for {self:?} packing, found {}.", self.array_type().title(), tag.id().title()))),
+		}
+	}
+}
+
+/// Converts a float to the bits of the nearest half precision float, rounding ties to even.
+/// Values too large for a half become infinity, and NaN stays NaN.
+pub fn f16_from_f32(value: f32) -> u16 {
+	let bits = value.to_bits();
+	let sign = ((bits >> 16) & 0x8000) as u16;
+	let exponent = ((bits >> 23) & 0xFF) as i32;
+	let mantissa = bits & 0x7F_FFFF;
+	if exponent == 0xFF {
+		let nan = if mantissa != 0 { 0x200 | (mantissa >> 13) as u16 } else { 0 };
+		return sign | 0x7C00 | nan;
+	}
+	let exponent = exponent - 127 + 15;
+	if exponent >= 0x1F {
+		return sign | 0x7C00;
+	}
+	// Rounds away the lowest `shift` bits, to even on ties. A carry out of the mantissa correctly
+	// increments the exponent, up to infinity.
+	let round = |value: u32, shift: u32| {
+		let kept = value >> shift;
+		let rest = value & ((1 << shift) - 1);
+		let half = 1 << (shift - 1);
+		if rest > half || (rest == half && kept & 1 == 1) { kept + 1 } else { kept }
+	};
+	if exponent <= 0 {
+		// Subnormal halves, or zero if even the largest subnormal would round away.
+		if exponent < -10 {
+			return sign;
+		}
+		return sign | round(mantissa | 0x80_0000, (14 - exponent) as u32) as u16;
+	}
+	sign | round(((exponent as u32) << 23) | mantissa, 13) as u16
+}
+
+/// Converts the bits of a half precision float to a float, which is exact.
+pub fn f16_to_f32(bits: u16) -> f32 {
+	let sign = ((bits & 0x8000) as u32) << 16;
+	let exponent = ((bits >> 10) & 0x1F) as u32;
+	let mantissa = (bits & 0x3FF) as u32;
+	match exponent {
+		0 => {
+			let magnitude = mantissa as f32 * 2f32.powi(-24);
+			if sign != 0 { -magnitude } else { magnitude }
+		}
+		0x1F => f32::from_bits(sign | 0x7F80_0000 | (mantissa << 13)),
+		_ => f32::from_bits(sign | ((exponent + 112) << 23) | (mantissa << 13)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn f16_test() {
+		assert_eq!(f16_from_f32(1.0), 0x3C00);
+		assert_eq!(f16_from_f32(-2.0), 0xC000);
+		assert_eq!(f16_from_f32(65504.0), 0x7BFF);
+		assert_eq!(f16_from_f32(65520.0), 0x7C00);
+		assert_eq!(f16_from_f32(f32::NEG_INFINITY), 0xFC00);
+		assert_eq!(f16_from_f32(2f32.powi(-24)), 0x0001);
+		assert_eq!(f16_from_f32(2f32.powi(-26)), 0x0000);
+		// 1 + 2^-11 is halfway between two halves, and rounds to the even one.
+		assert_eq!(f16_from_f32(1.0 + 2f32.powi(-11)), 0x3C00);
+		assert_eq!(f16_from_f32(1.0 + 3.0 * 2f32.powi(-11)), 0x3C02);
+		assert!(f16_to_f32(f16_from_f32(f32::NAN)).is_nan());
+		for bits in (0..=u16::MAX).filter(|bits| bits & 0x7C00 != 0x7C00) {
+			assert_eq!(f16_from_f32(f16_to_f32(bits)), bits);
+		}
+	}
+
+	#[test]
+	fn packing_test() -> Result<(), NbtError> {
+		let values = [0.0, 0.25, 1.0, -1.0, 3.5];
+		assert_eq!(FloatPacking::F32.unpack(&FloatPacking::F32.pack(&values))?, values);
+		assert_eq!(FloatPacking::F16.unpack(&FloatPacking::F16.pack(&values))?, values);
+		assert_eq!(FloatPacking::Unorm8.unpack(&FloatPacking::Unorm8.pack(&values))?, [0.0, 64.0 / 255.0, 1.0, 0.0, 1.0]);
+		assert_eq!(FloatPacking::Snorm8.unpack(&FloatPacking::Snorm8.pack(&values))?, [0.0, 32.0 / 127.0, 1.0, -1.0, 1.0]);
+		for packing in [FloatPacking::Unorm16, FloatPacking::Snorm16] {
+			let unpacked = packing.unpack(&packing.pack(&[0.25, 1.0]))?;
+			assert!((unpacked[0] - 0.25).abs() < 1e-4 && unpacked[1] == 1.0);
+		}
+		assert_eq!(FloatPacking::Snorm16.pack(&[-1.0]), Tag::ByteArray(vec![-128, 1]));
+		assert!(FloatPacking::F16.unpack(&Tag::ByteArray(vec![0, 0, 0])).is_err());
+		assert!(FloatPacking::F32.unpack(&Tag::ByteArray(Vec::new())).is_err());
+		Ok(())
+	}
+}
@@ -0,0 +1,409 @@
+#![doc = r#"
+Options for reading data leniently, and the warnings that report what was tolerated.
+
+Readers that take [ParseOptions] return a [ParseOutcome], which holds the value that was read along with a [Warning]
+for every quirk that was tolerated, so lenient reading can succeed while still telling the caller what was unusual.
+Anything the options do not allow is an error instead.
+
+- Compounds can have the same key twice in SNBT, binary NBT, and formats that are not NBT, and keys can also collide
+  when they are normalized while importing, such as when data from a case-insensitive source is lowercased.
+  [ParseOptions] chooses how keys are normalized ([KeyNormalization]) and what happens when two keys collide
+  ([KeyCollisionPolicy]).
+- SNBT Lists with elements of different types are rejected unless [ParseOptions::allow_mixed_lists] is set, in which
+  case they are read the way the game reads them since 1.21.5: every element is wrapped in a Compound with an empty key.
+- Data after the root tag is rejected unless [ParseOptions::allow_trailing_data] is set.
+- SNBT integers that are out of the range of their type are rejected unless [ParseOptions::number_range] is set to
+  wrap them or read them as Strings ([NumberRange]).
+- Unquoted SNBT Strings and keys can only have ASCII letters, digits, and `_-.+`, like in the game, unless
+  [ParseOptions::unquoted_text] is set to also allow letters and digits of other scripts ([UnquotedText]).
+- SNBT Strings can have `\uXXXX` escapes of UTF-16 code units, and surrogate pairs in two escapes. Unpaired
+  surrogates, which Java strings can hold but Rust strings can not, are rejected unless [ParseOptions::surrogates] is
+  set to replace them ([Surrogates]).
+- [ParseOptions::vanilla_strict] reads SNBT exactly like the game did before 1.21.5, for tools that must accept and
+  reject the same commands as the game. See [crate::snbt::WriteOptions::vanilla] for writing it.
+
+```
+# #[cfg(feature = "cbor")] {
+# use rustnbt::{ compound, parse::* };
+# use ciborium::value::Value;
+let value = Value::Map(vec![
+	(Value::Text("Name".to_owned()), Value::Text("a".to_owned())),
+	(Value::Text("name".to_owned()), Value::Text("b".to_owned())),
+]);
+let options = ParseOptions::new()
+	.normalize_keys(KeyNormalization::Lowercase)
+	.key_collisions(KeyCollisionPolicy::KeepFirst);
+let outcome = rustnbt::cbor::from_value_with(value, &options).unwrap();
+assert_eq!(outcome.value, compound!(("name", "a")));
+assert_eq!(outcome.warnings.len(), 1);
+# }
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	io::DEFAULT_MAX_DEPTH,
+	path::TagPath,
+	tag::{ Tag, TagID },
+};
+use std::fmt;
+
+/// How keys are changed while importing, before they are inserted into a Compound.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum KeyNormalization {
+	/// Keys are kept as they are.
+	#[default]
+	None,
+	/// Keys are lowercased, for data from case-insensitive sources.
+	Lowercase,
+	/// Keys are changed by a function.
+	Custom(fn(&str) -> String),
+}
+
+impl KeyNormalization {
+	pub fn apply(self, key: &str) -> String {
+		match self {
+			KeyNormalization::None => key.to_owned(),
+			KeyNormalization::Lowercase => key.to_lowercase(),
+			KeyNormalization::Custom(normalize) => normalize(key),
+		}
+	}
+}
+
+/// What happens when a key is inserted into a Compound that already has it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum KeyCollisionPolicy {
+	/// The later value replaces the earlier one, which is what the game does with duplicate keys in SNBT.
+	#[default]
+	KeepLast,
+	/// The earlier value is kept and the later one is dropped.
+	KeepFirst,
+	/// The later value is inserted with `_2`, `_3`, and so on appended to its key, whichever is free first.
+	Rename,
+	/// Reading fails.
+	Error,
+}
+
+/// What happens to an SNBT integer that is out of the range of its type, such as `300b`.<br>
+/// Floats and Doubles are never out of range: values that are too large become infinite, as they do in Java.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NumberRange {
+	/// Reading fails, with the span of the number. The game does this since 1.21.5.
+	#[default]
+	Error,
+	/// The number is wrapped to the width of its type, like a narrowing cast in Java, so `300b` is `44b` and
+	/// `2147483648` is `-2147483648`.
+	Wrap,
+	/// The number is read as a String, so `300b` is `"300b"`. The game did this before 1.21.5, when numbers that did
+	/// not fit their type were read as unquoted Strings.
+	String,
+}
+
+/// Which characters SNBT Strings and keys can have without quotes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum UnquotedText {
+	/// ASCII letters, digits, `_`, `-`, `.`, and `+`, which is all the game allows. Any other letter is an error that
+	/// says the String must be quoted.
+	#[default]
+	Ascii,
+	/// Also letters and digits of any script, so `{name: Grüße}` can be read. The game cannot read such SNBT, so every
+	/// String that needs this is reported with a warning, and the SNBT writers still quote them.
+	Unicode,
+}
+
+/// What happens to an unpaired UTF-16 surrogate in a `\u` escape of an SNBT String, such as `"\ud83c"`. Java strings
+/// can hold them, so text that Java wrote can have them, but Rust strings can not.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Surrogates {
+	/// Reading fails, with the offset of the escape.
+	#[default]
+	Error,
+	/// The surrogate is replaced with U+FFFD, the replacement character, like [String::from_utf16_lossy] does.
+	Replace,
+}
+
+/// Something unusual that was tolerated while reading.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Warning {
+	/// Two keys of a Compound were the same after normalization.
+	KeyCollision {
+		/// The path of the Compound.
+		path: TagPath,
+		/// The key that both entries had after normalization.
+		key: String,
+		/// The key of the later entry as it was in the input.
+		original: String,
+		/// The key the later entry was inserted with, if it was renamed.
+		renamed: Option<String>,
+		/// What was done with the later entry.
+		policy: KeyCollisionPolicy,
+	},
+	/// The elements of a List had different types, so each of them was wrapped in a Compound with an empty key.
+	MixedList {
+		/// The path of the List.
+		path: TagPath,
+	},
+	/// An SNBT integer was out of the range of its type, so it was handled by the [NumberRange] of the options.
+	NumberOutOfRange {
+		/// Where the number starts, in characters.
+		offset: usize,
+		/// How long the number is, in characters.
+		length: usize,
+		/// The type the number had.
+		id: TagID,
+		/// What was done with it.
+		range: NumberRange,
+	},
+	/// An unquoted SNBT String or key had letters or digits that are not ASCII, which [UnquotedText::Unicode] allows.
+	UnquotedUnicode {
+		/// Where the String starts, in characters.
+		offset: usize,
+		/// How long the String is, in characters.
+		length: usize,
+	},
+	/// A `\u` escape in an SNBT String was an unpaired surrogate, so it was replaced with U+FFFD by
+	/// [Surrogates::Replace].
+	UnpairedSurrogate {
+		/// Where the escape starts, in characters.
+		offset: usize,
+	},
+	/// There was more data after the root tag, which was ignored.
+	TrailingData {
+		/// Where the data starts, in bytes for binary NBT and in characters for SNBT.
+		offset: usize,
+		/// How long the data is, in the same unit as `offset`.
+		length: usize,
+	},
+}
+
+fn describe(path: &TagPath) -> String {
+	if path.is_empty() { "the root".to_owned() } else { path.to_string() }
+}
+
+impl fmt::Display for Warning {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Warning::KeyCollision { path, key, original, renamed, policy } => {
+				write!(f, "Key {original:?} collides with {key:?} in {}", describe(path))?;
+				match (policy, renamed) {
+					(_, Some(renamed)) => write!(f, "; it was renamed to {renamed:?}."),
+					(KeyCollisionPolicy::KeepFirst, _) => f.write_str("; the later value was dropped."),
+					_ => f.write_str("; the earlier value was replaced."),
+				}
+			}
+			Warning::MixedList { path } => write!(f, "The elements of the List at {} have different types.", describe(path)),
+			Warning::NumberOutOfRange { offset, length, id, range } => {
+				write!(f, "The {} at character {offset} (length {length}) is out of range", id.title())?;
+				match range {
+					NumberRange::String => f.write_str("; it was read as a String."),
+					_ => f.write_str("; it was wrapped."),
+				}
+			}
+			Warning::UnquotedUnicode { offset, length } => write!(f, "The unquoted String at character {offset} (length {length}) has characters that the game only reads in quotes."),
+			Warning::UnpairedSurrogate { offset } => write!(f, "The escape at character {offset} is an unpaired surrogate; it was replaced with U+FFFD."),
+			Warning::TrailingData { offset, length } => write!(f, "Data after the root tag was ignored (offset {offset}, length {length})."),
+		}
+	}
+}
+
+/// A value that was read, with the warnings about what was tolerated while reading it, in the order they happened.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParseOutcome<T> {
+	pub value: T,
+	pub warnings: Vec<Warning>,
+}
+
+impl<T> ParseOutcome<T> {
+	/// Returns true if nothing was tolerated.
+	pub fn is_clean(&self) -> bool {
+		self.warnings.is_empty()
+	}
+
+	/// Discards the warnings.
+	pub fn into_value(self) -> T {
+		self.value
+	}
+
+	pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> ParseOutcome<U> {
+		ParseOutcome { value: f(self.value), warnings: self.warnings }
+	}
+}
+
+/// Options for lenient reading. See the [module](self) documentation.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+	pub normalize_keys: KeyNormalization,
+	pub key_collisions: KeyCollisionPolicy,
+	pub allow_mixed_lists: bool,
+	pub allow_trailing_data: bool,
+	pub number_range: NumberRange,
+	pub unquoted_text: UnquotedText,
+	pub surrogates: Surrogates,
+	/// Reads SNBT with the rules of the game before 1.21.5, instead of the more lenient rules of this crate:
+	/// - Integers that are out of the range of their type are read as Strings, whatever the [NumberRange] is.
+	/// - The type of an array must be an uppercase `B`, `I`, or `L`, and arrays may end with a separator.
+	/// - Only backslashes and the quote that a String is in can be escaped, so there are no `\u` escapes.
+	pub vanilla_strict: bool,
+	/// How deeply Compounds, Lists, and arrays may be nested. Deeper input is an error instead of overflowing the
+	/// stack. The default is [DEFAULT_MAX_DEPTH].
+	pub max_depth: usize,
+	/// The warnings of the read in progress.
+	pub(crate) warnings: Vec<Warning>,
+}
+
+impl Default for ParseOptions {
+	fn default() -> Self {
+		Self {
+			normalize_keys: KeyNormalization::default(),
+			key_collisions: KeyCollisionPolicy::default(),
+			allow_mixed_lists: false,
+			allow_trailing_data: false,
+			number_range: NumberRange::default(),
+			unquoted_text: UnquotedText::default(),
+			surrogates: Surrogates::default(),
+			vanilla_strict: false,
+			max_depth: DEFAULT_MAX_DEPTH,
+			warnings: Vec::new(),
+		}
+	}
+}
+
+impl ParseOptions {
+	/// The default options, which keep keys as they are and let later values replace earlier ones.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns these options with a different [KeyNormalization].
+	pub fn normalize_keys(mut self, normalize_keys: KeyNormalization) -> Self {
+		self.normalize_keys = normalize_keys;
+		self
+	}
+
+	/// Returns these options with a different [KeyCollisionPolicy].
+	pub fn key_collisions(mut self, key_collisions: KeyCollisionPolicy) -> Self {
+		self.key_collisions = key_collisions;
+		self
+	}
+
+	/// Returns these options with mixed Lists allowed or not.
+	pub fn allow_mixed_lists(mut self, allow_mixed_lists: bool) -> Self {
+		self.allow_mixed_lists = allow_mixed_lists;
+		self
+	}
+
+	/// Returns these options with data after the root tag allowed or not.
+	pub fn allow_trailing_data(mut self, allow_trailing_data: bool) -> Self {
+		self.allow_trailing_data = allow_trailing_data;
+		self
+	}
+
+	/// Returns these options with a different [NumberRange].
+	pub fn number_range(mut self, number_range: NumberRange) -> Self {
+		self.number_range = number_range;
+		self
+	}
+
+	/// Returns these options with a different [UnquotedText].
+	pub fn unquoted_text(mut self, unquoted_text: UnquotedText) -> Self {
+		self.unquoted_text = unquoted_text;
+		self
+	}
+
+	/// Returns these options with a different [Surrogates].
+	pub fn surrogates(mut self, surrogates: Surrogates) -> Self {
+		self.surrogates = surrogates;
+		self
+	}
+
+	/// Returns these options with the rules of the game turned on or off. See [ParseOptions::vanilla_strict].
+	pub fn vanilla_strict(mut self, vanilla_strict: bool) -> Self {
+		self.vanilla_strict = vanilla_strict;
+		self
+	}
+
+	/// Returns these options with a different [ParseOptions::max_depth].
+	pub fn max_depth(mut self, max_depth: usize) -> Self {
+		self.max_depth = max_depth;
+		self
+	}
+
+	/// Adds a warning to the read in progress.
+	pub(crate) fn warn(&mut self, warning: Warning) {
+		self.warnings.push(warning);
+	}
+
+	/// Finishes the read in progress, moving its warnings into the outcome.
+	pub(crate) fn finish<T>(&mut self, value: T) -> ParseOutcome<T> {
+		ParseOutcome { value, warnings: std::mem::take(&mut self.warnings) }
+	}
+
+	/// Inserts an entry into a Compound, normalizing its key and handling a collision by the policy.
+	/// `path` gives the path of the Compound, and is only called if the key collides.
+	pub(crate) fn insert<P: FnOnce() -> TagPath>(&mut self, map: &mut Map, path: P, key: String, value: Tag) -> Result<(), NbtError> {
+		let (normalized, original) = match self.normalize_keys {
+			KeyNormalization::None => (key, None),
+			normalization => (normalization.apply(&key), Some(key)),
+		};
+		let Some(existing) = map.get_mut(&normalized) else {
+			map.insert(normalized, value);
+			return Ok(());
+		};
+		let key = original.unwrap_or_else(|| normalized.clone());
+		let mut renamed = None;
+		match self.key_collisions {
+			KeyCollisionPolicy::KeepLast => *existing = value,
+			KeyCollisionPolicy::KeepFirst => {}
+			KeyCollisionPolicy::Rename => {
+				let free = (2..).map(|suffix| format!("{normalized}_{suffix}")).find(|key| !map.contains_key(key)).expect("Some suffix is free.");
+				map.insert(free.clone(), value);
+				renamed = Some(free);
+			}
+			KeyCollisionPolicy::Error => {
+				return Err(invalid_data(&format!("Key {key:?} collides with {normalized:?} in {}.", describe(&path()))));
+			}
+		}
+		self.warn(Warning::KeyCollision { path: path(), key: normalized, original: key, renamed, policy: self.key_collisions });
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn insert_test() -> Result<(), NbtError> {
+		let path = TagPath::new().key("display");
+		let mut collide = |policy: KeyCollisionPolicy| -> Result<(Map, Vec<Warning>), NbtError> {
+			let mut options = ParseOptions::new().normalize_keys(KeyNormalization::Lowercase).key_collisions(policy);
+			let mut map = Map::default();
+			options.insert(&mut map, || path.clone(), "Name".to_owned(), Tag::Int(1))?;
+			options.insert(&mut map, || path.clone(), "NAME".to_owned(), Tag::Int(2))?;
+			options.insert(&mut map, || path.clone(), "name_2".to_owned(), Tag::Int(3))?;
+			Ok((map, options.finish(()).warnings))
+		};
+		let (map, warnings) = collide(KeyCollisionPolicy::KeepLast)?;
+		assert_eq!(map, Map::from_iter([("name".to_owned(), Tag::Int(2)), ("name_2".to_owned(), Tag::Int(3))]));
+		assert_eq!(warnings, [Warning::KeyCollision {
+			path: path.clone(),
+			key: "name".to_owned(),
+			original: "NAME".to_owned(),
+			renamed: None,
+			policy: KeyCollisionPolicy::KeepLast,
+		}]);
+		assert_eq!(warnings[0].to_string(), r#"Key "NAME" collides with "name" in display; the earlier value was replaced."#);
+		assert_eq!(collide(KeyCollisionPolicy::KeepFirst)?.0.get("name"), Some(&Tag::Int(1)));
+		let (map, warnings) = collide(KeyCollisionPolicy::Rename)?;
+		assert_eq!(map, Map::from_iter([
+			("name".to_owned(), Tag::Int(1)),
+			("name_2".to_owned(), Tag::Int(2)),
+			("name_2_2".to_owned(), Tag::Int(3)),
+		]));
+		assert_eq!(warnings.len(), 2);
+		assert!(collide(KeyCollisionPolicy::Error).is_err());
+		Ok(())
+	}
+}
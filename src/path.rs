@@ -0,0 +1,632 @@
+#![doc = r#"
+Paths to values nested inside of a [Tag].
+
+A path is written as keys separated by dots, with list or array indices in square brackets,
+such as `Level.Sections[0].Y`. Keys that contain `.`, `[`, `]`, `"`, or `\`, or that are empty,
+are written in double quotes, with `\"` and `\\` as escapes.
+
+```
+# use rustnbt::{ compound, tag::Tag, path::TagPath };
+# use rustnbt::{ Map, tag::ListTag };
+let item = Map::from_iter([("Slot".to_owned(), Tag::Byte(3)), ("id".to_owned(), Tag::from("minecraft:stone"))]);
+let tag = compound!(("Inventory", Tag::List(ListTag::Compound(vec![item]))));
+let path: TagPath = "Inventory[0].id".parse().unwrap();
+assert_eq!(path.get(&tag).as_deref(), Some(&Tag::from("minecraft:stone")));
+assert_eq!(path.to_string(), "Inventory[0].id");
+```
+
+[Tag::flatten] lists every value of a tree with its path, and [Tag::from_flat] builds the tree back, for exports and
+comparisons that work with flat key-value pairs.
+"#]
+
+use crate::{
+	Map,
+	tag_info_table,
+	tag::{
+		Tag,
+		ListTag,
+	},
+};
+use std::{
+	borrow::Cow,
+	fmt::{ self, Display, Write },
+	iter::Peekable,
+	str::{ CharIndices, FromStr },
+};
+
+/// A single step in a [TagPath].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PathSegment {
+	/// Key of a Compound entry.
+	Key(String),
+	/// Index of a List or array element.
+	Index(usize),
+}
+
+/// A path to a value nested inside of a [Tag]. See the [module](self) documentation for the syntax.
+#[derive(Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct TagPath(Vec<PathSegment>);
+
+/// Error returned when parsing a [TagPath] fails. Positions are byte offsets into the source.
+#[derive(Debug, thiserror::Error)]
+pub enum PathError {
+	#[error("Expected a key at position {0}.")]
+	ExpectedKey(usize),
+	#[error("Unterminated quoted key starting at position {0}.")]
+	UnterminatedQuote(usize),
+	#[error("Invalid index at position {0}.")]
+	InvalidIndex(usize),
+	#[error("Unexpected character {0:?} at position {1}.")]
+	Unexpected(char, usize),
+}
+
+/// A node reached while walking a path. List elements are not stored as [Tag]s, so they are tracked by what they are.
+enum Node<'a> {
+	Tag(&'a Tag),
+	Map(&'a Map),
+	List(&'a ListTag),
+	Owned(Tag),
+}
+
+impl TagPath {
+	/// Creates an empty path, which refers to the root tag.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns this path with a key appended.
+	pub fn key<S: Into<String>>(mut self, key: S) -> Self {
+		self.0.push(PathSegment::Key(key.into()));
+		self
+	}
+
+	/// Returns this path with an index appended.
+	pub fn index(mut self, index: usize) -> Self {
+		self.0.push(PathSegment::Index(index));
+		self
+	}
+
+	/// Appends a segment to this path.
+	pub fn push(&mut self, segment: PathSegment) {
+		self.0.push(segment);
+	}
+
+	/// Removes the last segment of this path.
+	pub fn pop(&mut self) -> Option<PathSegment> {
+		self.0.pop()
+	}
+
+	/// The segments of this path, from the root.
+	pub fn segments(&self) -> &[PathSegment] {
+		&self.0
+	}
+
+	/// Returns `true` if this path refers to the root tag.
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Finds the value at this path.<br>
+	/// The value is borrowed when it is stored as a [Tag], which is the case for the root and for Compound entries.
+	/// Elements of Lists and arrays are not stored as [Tag]s, so they are cloned.
+	pub fn get<'a>(&self, tag: &'a Tag) -> Option<Cow<'a, Tag>> {
+		let mut node = Node::Tag(tag);
+		for segment in &self.0 {
+			node = match (segment, node) {
+				(PathSegment::Key(key), Node::Tag(Tag::Compound(map)) | Node::Map(map)) => Node::Tag(map.get(key)?),
+				(PathSegment::Key(key), Node::Owned(Tag::Compound(mut map))) => Node::Owned(map.remove(key)?),
+				(PathSegment::Index(index), Node::Tag(Tag::List(list)) | Node::List(list)) => list_element(list, *index)?,
+				(PathSegment::Index(index), Node::Owned(Tag::List(list))) => match list_element(&list, *index)? {
+					Node::Map(map) => Node::Owned(Tag::Compound(map.clone())),
+					Node::List(list) => Node::Owned(Tag::List(list.clone())),
+					Node::Tag(tag) => Node::Owned(tag.clone()),
+					Node::Owned(tag) => Node::Owned(tag),
+				},
+				(PathSegment::Index(index), Node::Tag(tag)) => Node::Owned(array_element(tag, *index)?),
+				(PathSegment::Index(index), Node::Owned(tag)) => Node::Owned(array_element(&tag, *index)?),
+				_ => return None,
+			};
+		}
+		Some(match node {
+			Node::Tag(tag) => Cow::Borrowed(tag),
+			Node::Map(map) => Cow::Owned(Tag::Compound(map.clone())),
+			Node::List(list) => Cow::Owned(Tag::List(list.clone())),
+			Node::Owned(tag) => Cow::Owned(tag),
+		})
+	}
+
+	/// Stores `value` at this path, returning the value that was there before.<br>
+	/// A Compound entry is inserted if the key does not exist, and an index one past the end of a List or array appends to it.
+	/// List and array elements must have the same type as the other elements.
+	/// If the path does not lead to a place that can hold `value`, it is returned as the error.
+	pub fn set(&self, tag: &mut Tag, value: Tag) -> Result<Option<Tag>, Tag> {
+		let Some((last, parents)) = self.0.split_last() else {
+			return Ok(Some(std::mem::replace(tag, value)));
+		};
+		match (last, parent_mut(parents, tag)) {
+			(PathSegment::Key(key), Some(Place::Tag(Tag::Compound(map)) | Place::Map(map))) => Ok(map.insert(key.clone(), value)),
+			(PathSegment::Index(index), Some(Place::Tag(Tag::List(list)) | Place::List(list))) => set_list_element(list, *index, value),
+			(PathSegment::Index(index), Some(Place::Tag(tag))) => set_array_element(tag, *index, value),
+			_ => Err(value),
+		}
+	}
+
+	/// Removes the value at this path from its Compound, List, or array, and returns it.
+	/// The root can not be removed.
+	pub fn remove(&self, tag: &mut Tag) -> Option<Tag> {
+		let (last, parents) = self.0.split_last()?;
+		match (last, parent_mut(parents, tag)?) {
+			(PathSegment::Key(key), Place::Tag(Tag::Compound(map)) | Place::Map(map)) => remove_key(map, key),
+			(PathSegment::Index(index), Place::Tag(Tag::List(list)) | Place::List(list)) => remove_list_element(list, *index),
+			(PathSegment::Index(index), Place::Tag(tag)) => remove_array_element(tag, *index),
+			_ => None,
+		}
+	}
+}
+
+/// A mutable place reached while walking a path.
+enum Place<'a> {
+	Tag(&'a mut Tag),
+	Map(&'a mut Map),
+	List(&'a mut ListTag),
+}
+
+/// Walks `segments`, which must only pass through Compounds and Lists.
+fn parent_mut<'a>(segments: &[PathSegment], tag: &'a mut Tag) -> Option<Place<'a>> {
+	let mut place = Place::Tag(tag);
+	for segment in segments {
+		place = match (segment, place) {
+			(PathSegment::Key(key), Place::Tag(Tag::Compound(map)) | Place::Map(map)) => Place::Tag(map.get_mut(key)?),
+			(PathSegment::Index(index), Place::Tag(Tag::List(list)) | Place::List(list)) => match list {
+				ListTag::Compound(maps) => Place::Map(maps.get_mut(*index)?),
+				ListTag::List(lists) => Place::List(lists.get_mut(*index)?),
+				_ => return None,
+			},
+			_ => return None,
+		};
+	}
+	Some(place)
+}
+
+#[cfg(feature = "preserve_order")]
+fn remove_key(map: &mut Map, key: &str) -> Option<Tag> {
+	map.shift_remove(key)
+}
+
+#[cfg(not(feature = "preserve_order"))]
+fn remove_key(map: &mut Map, key: &str) -> Option<Tag> {
+	map.remove(key)
+}
+
+macro_rules! list_element_access {
+	($($id:literal $title:ident $type:path [$($impl:path)?])+) => {
+		fn set_list_element(list: &mut ListTag, index: usize, value: Tag) -> Result<Option<Tag>, Tag> {
+			match (list, value) {
+				(list @ ListTag::Empty, value) if index == 0 => {
+					*list = ListTag::try_from(vec![value]).expect("A single element is always a valid list.");
+					Ok(None)
+				}
+				$(
+					(ListTag::$title(values), Tag::$title(value)) if index < values.len() => {
+						Ok(Some(Tag::$title(std::mem::replace(&mut values[index], value))))
+					}
+					(ListTag::$title(values), Tag::$title(value)) if index == values.len() => {
+						values.push(value);
+						Ok(None)
+					}
+				)+
+				(_, value) => Err(value),
+			}
+		}
+
+		fn remove_list_element(list: &mut ListTag, index: usize) -> Option<Tag> {
+			match list {
+				ListTag::Empty => None,
+				$(
+					ListTag::$title(values) => (index < values.len()).then(|| Tag::$title(values.remove(index))),
+				)+
+			}
+		}
+	};
+}
+
+tag_info_table!(list_element_access);
+
+fn set_array_element(tag: &mut Tag, index: usize, value: Tag) -> Result<Option<Tag>, Tag> {
+	macro_rules! set {
+		($($array:ident $element:ident)+) => {
+			match (tag, value) {
+				$(
+					(Tag::$array(values), Tag::$element(value)) if index < values.len() => {
+						Ok(Some(Tag::$element(std::mem::replace(&mut values[index], value))))
+					}
+					(Tag::$array(values), Tag::$element(value)) if index == values.len() => {
+						values.push(value);
+						Ok(None)
+					}
+				)+
+				(_, value) => Err(value),
+			}
+		};
+	}
+	set!(ByteArray Byte IntArray Int LongArray Long)
+}
+
+fn remove_array_element(tag: &mut Tag, index: usize) -> Option<Tag> {
+	match tag {
+		Tag::ByteArray(values) => (index < values.len()).then(|| Tag::Byte(values.remove(index))),
+		Tag::IntArray(values) => (index < values.len()).then(|| Tag::Int(values.remove(index))),
+		Tag::LongArray(values) => (index < values.len()).then(|| Tag::Long(values.remove(index))),
+		_ => None,
+	}
+}
+
+fn list_element(list: &ListTag, index: usize) -> Option<Node<'_>> {
+	macro_rules! element {
+		($($title:ident)+) => {
+			match list {
+				ListTag::Empty => None,
+				ListTag::Compound(maps) => maps.get(index).map(Node::Map),
+				ListTag::List(lists) => lists.get(index).map(Node::List),
+				$(
+					ListTag::$title(values) => values.get(index).map(|value| Node::Owned(Tag::$title(value.clone()))),
+				)+
+			}
+		};
+	}
+	element!(Byte Short Int Long Float Double ByteArray String IntArray LongArray)
+}
+
+fn array_element(tag: &Tag, index: usize) -> Option<Tag> {
+	match tag {
+		Tag::ByteArray(values) => values.get(index).copied().map(Tag::Byte),
+		Tag::IntArray(values) => values.get(index).copied().map(Tag::Int),
+		Tag::LongArray(values) => values.get(index).copied().map(Tag::Long),
+		_ => None,
+	}
+}
+
+/// An iterator over the values of a tree with their paths, from [Tag::flatten].
+pub struct Flatten<'a> {
+	/// The nodes that are left, with the next one last.
+	stack: Vec<(TagPath, Node<'a>)>,
+}
+
+impl<'a> Iterator for Flatten<'a> {
+	type Item = (TagPath, Cow<'a, Tag>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let (path, node) = self.stack.pop()?;
+			match node {
+				Node::Tag(Tag::Compound(map)) | Node::Map(map) if !map.is_empty() => {
+					let start = self.stack.len();
+					self.stack.extend(map.iter().map(|(key, tag)| (path.clone().key(key.as_str()), Node::Tag(tag))));
+					self.stack[start..].reverse();
+				}
+				Node::Tag(Tag::List(list)) | Node::List(list) if !list.is_empty() => {
+					self.stack.extend((0..list.len()).rev().filter_map(|index| Some((path.clone().index(index), list_element(list, index)?))));
+				}
+				Node::Tag(tag) => return Some((path, Cow::Borrowed(tag))),
+				Node::Map(_) => return Some((path, Cow::Owned(Tag::Compound(Map::default())))),
+				Node::List(_) => return Some((path, Cow::Owned(Tag::List(ListTag::Empty)))),
+				Node::Owned(tag) => return Some((path, Cow::Owned(tag))),
+			}
+		}
+	}
+}
+
+/// An iterator over the values of a tree with their paths that owns the tree, from [Tag::into_flatten].<br>
+/// It borrows nothing, so it can be sent to another thread, or kept after the tree it was made from is gone.
+pub struct IntoFlatten {
+	/// The tags that are left, with the next one last.
+	stack: Vec<(TagPath, Tag)>,
+}
+
+impl Iterator for IntoFlatten {
+	type Item = (TagPath, Tag);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let (path, tag) = self.stack.pop()?;
+			match tag {
+				Tag::Compound(map) if !map.is_empty() => {
+					let start = self.stack.len();
+					self.stack.extend(map.into_iter().map(|(key, tag)| (path.clone().key(key), tag)));
+					self.stack[start..].reverse();
+				}
+				Tag::List(list) if !list.is_empty() => {
+					let start = self.stack.len();
+					self.stack.extend(list.into_iter().enumerate().map(|(index, tag)| (path.clone().index(index), tag)));
+					self.stack[start..].reverse();
+				}
+				tag => return Some((path, tag)),
+			}
+		}
+	}
+}
+
+/// Error returned by [Tag::from_flat] when a value does not fit into the tree built from the values before it.
+#[derive(Debug, thiserror::Error)]
+#[error("The value at `{path}` does not fit into the values before it.")]
+pub struct FlatError {
+	pub path: TagPath,
+	pub value: Tag,
+}
+
+impl Tag {
+	/// Returns every value in this tree with its path, in the order the tree is written.<br>
+	/// The values are the numbers, Strings, and arrays, and the Compounds and Lists that are empty, so that
+	/// [Tag::from_flat] can build the same tree back. Elements of Lists are cloned, like with [TagPath::get].
+	/// ### Example
+	/// ```
+	/// # use rustnbt::{ compound, list, tag::Tag };
+	/// let tag = compound!(("Pos", list!(1.5, 64.0)));
+	/// let flat: Vec<(String, Tag)> = tag.flatten().map(|(path, value)| (path.to_string(), value.into_owned())).collect();
+	/// assert_eq!(flat, [("Pos[0]".to_owned(), Tag::Double(1.5)), ("Pos[1]".to_owned(), Tag::Double(64.0))]);
+	/// ```
+	pub fn flatten(&self) -> Flatten<'_> {
+		Flatten { stack: vec![(TagPath::new(), Node::Tag(self))] }
+	}
+
+	/// Returns every value in this tree with its path, like [Tag::flatten], but moves the values out of the tree
+	/// instead of borrowing them.
+	pub fn into_flatten(self) -> IntoFlatten {
+		IntoFlatten { stack: vec![(TagPath::new(), self)] }
+	}
+
+	/// Builds a tree from values and their paths, such as those from [Tag::flatten].<br>
+	/// Compounds and Lists are created where the paths need them, and the elements of a List must come in order,
+	/// starting from 0, and have the same type. Without any values, the tree is an empty Compound.
+	pub fn from_flat<I: IntoIterator<Item = (TagPath, Tag)>>(values: I) -> Result<Tag, FlatError> {
+		let mut root: Option<Tag> = None;
+		for (path, value) in values {
+			let Some(first) = path.0.first() else {
+				root = Some(value);
+				continue;
+			};
+			let root = root.get_or_insert_with(|| container(first));
+			// Creates the Compounds and Lists on the way that do not exist yet.
+			for end in 1..path.0.len() {
+				if parent_mut(&path.0[..end], root).is_none() {
+					let parent = TagPath(path.0[..end].to_vec());
+					if parent.set(root, container(&path.0[end])).is_err() {
+						return Err(FlatError { path, value });
+					}
+				}
+			}
+			if let Err(value) = path.set(root, value) {
+				return Err(FlatError { path, value });
+			}
+		}
+		Ok(root.unwrap_or_else(|| Tag::Compound(Map::default())))
+	}
+}
+
+/// An empty Compound or List that can hold the value at `segment`.
+fn container(segment: &PathSegment) -> Tag {
+	match segment {
+		PathSegment::Key(_) => Tag::Compound(Map::default()),
+		PathSegment::Index(_) => Tag::List(ListTag::Empty),
+	}
+}
+
+fn read_key(chars: &mut Peekable<CharIndices>, end: usize) -> Result<String, PathError> {
+	let mut key = String::new();
+	match chars.peek().copied() {
+		Some((start, '"')) => {
+			chars.next();
+			loop {
+				match chars.next() {
+					Some((_, '"')) => return Ok(key),
+					Some((_, '\\')) => match chars.next() {
+						Some((_, c @ ('"' | '\\'))) => key.push(c),
+						Some((position, c)) => return Err(PathError::Unexpected(c, position)),
+						None => return Err(PathError::UnterminatedQuote(start)),
+					},
+					Some((_, c)) => key.push(c),
+					None => return Err(PathError::UnterminatedQuote(start)),
+				}
+			}
+		}
+		start => {
+			while let Some((_, c)) = chars.next_if(|(_, c)| !matches!(c, '.' | '[' | ']' | '"' | '\\')) {
+				key.push(c);
+			}
+			if key.is_empty() {
+				return Err(PathError::ExpectedKey(start.map_or(end, |(position, _)| position)));
+			}
+			Ok(key)
+		}
+	}
+}
+
+fn read_index(chars: &mut Peekable<CharIndices>, start: usize) -> Result<usize, PathError> {
+	let mut digits = String::new();
+	while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+		digits.push(c);
+	}
+	match chars.next() {
+		Some((_, ']')) => digits.parse().map_err(|_| PathError::InvalidIndex(start)),
+		_ => Err(PathError::InvalidIndex(start)),
+	}
+}
+
+impl FromStr for TagPath {
+	type Err = PathError;
+
+	fn from_str(source: &str) -> Result<Self, Self::Err> {
+		let mut segments = Vec::new();
+		let mut chars = source.char_indices().peekable();
+		let mut expect_key = !source.is_empty() && !source.starts_with('[');
+		loop {
+			if expect_key {
+				segments.push(PathSegment::Key(read_key(&mut chars, source.len())?));
+			}
+			match chars.next() {
+				None => break,
+				Some((_, '.')) => expect_key = true,
+				Some((position, '[')) => {
+					segments.push(PathSegment::Index(read_index(&mut chars, position)?));
+					expect_key = false;
+				}
+				Some((position, c)) => return Err(PathError::Unexpected(c, position)),
+			}
+		}
+		Ok(Self(segments))
+	}
+}
+
+impl Display for TagPath {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (i, segment) in self.0.iter().enumerate() {
+			match segment {
+				PathSegment::Index(index) => write!(f, "[{index}]")?,
+				PathSegment::Key(key) => {
+					if i > 0 {
+						f.write_char('.')?;
+					}
+					if key.is_empty() || key.contains(['.', '[', ']', '"', '\\']) {
+						f.write_char('"')?;
+						for c in key.chars() {
+							if matches!(c, '"' | '\\') {
+								f.write_char('\\')?;
+							}
+							f.write_char(c)?;
+						}
+						f.write_char('"')?;
+					} else {
+						f.write_str(key)?;
+					}
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl From<Vec<PathSegment>> for TagPath {
+	fn from(segments: Vec<PathSegment>) -> Self {
+		Self(segments)
+	}
+}
+
+impl From<TagPath> for Vec<PathSegment> {
+	fn from(path: TagPath) -> Self {
+		path.0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn parse_test() -> Result<(), PathError> {
+		let path: TagPath = r#"Level.Sections[2]."odd.key"[0].Y"#.parse()?;
+		assert_eq!(path, TagPath::new().key("Level").key("Sections").index(2).key("odd.key").index(0).key("Y"));
+		assert_eq!(path.to_string(), r#"Level.Sections[2]."odd.key"[0].Y"#);
+		assert_eq!("[1][2]".parse::<TagPath>()?, TagPath::new().index(1).index(2));
+		assert_eq!(r#""a\"b""#.parse::<TagPath>()?.to_string(), r#""a\"b""#);
+		assert!("".parse::<TagPath>()?.is_empty());
+		assert!(matches!("a.".parse::<TagPath>(), Err(PathError::ExpectedKey(2))));
+		assert!(matches!("a[x]".parse::<TagPath>(), Err(PathError::InvalidIndex(1))));
+		assert!(matches!("\"a".parse::<TagPath>(), Err(PathError::UnterminatedQuote(0))));
+		assert!(matches!("a]".parse::<TagPath>(), Err(PathError::Unexpected(']', 1))));
+		Ok(())
+	}
+
+	#[test]
+	fn get_test() -> Result<(), PathError> {
+		let tag = compound!(
+			("name", "root"),
+			("sections", Tag::List(ListTag::Compound(vec![
+				Map::from_iter([("Y".to_owned(), Tag::Byte(-4)), ("data".to_owned(), Tag::LongArray(vec![1, 2, 3]))]),
+			]))),
+			("nested", Tag::List(ListTag::List(vec![ListTag::Int(vec![5, 6])])))
+		);
+		let get = |path: &str| path.parse::<TagPath>().map(|path| path.get(&tag).map(Cow::into_owned));
+		assert!(matches!(TagPath::new().get(&tag), Some(Cow::Borrowed(_))));
+		assert_eq!(get("name")?, Some(Tag::from("root")));
+		assert_eq!(get("sections[0].Y")?, Some(Tag::Byte(-4)));
+		assert_eq!(get("sections[0].data[2]")?, Some(Tag::Long(3)));
+		assert_eq!(get("nested[0][1]")?, Some(Tag::Int(6)));
+		assert_eq!(get("sections[1]")?, None);
+		assert_eq!(get("name.length")?, None);
+		Ok(())
+	}
+
+	#[test]
+	fn set_remove_test() -> Result<(), PathError> {
+		let mut tag = compound!(("list", Tag::List(ListTag::Compound(vec![Map::default()]))), ("ints", Tag::IntArray(vec![1])));
+		let path = |path: &str| path.parse::<TagPath>();
+		assert_eq!(path("list[0].id")?.set(&mut tag, Tag::from("stone")), Ok(None));
+		assert_eq!(path("list[0].id")?.set(&mut tag, Tag::from("dirt")), Ok(Some(Tag::from("stone"))));
+		assert_eq!(path("ints[1]")?.set(&mut tag, Tag::Int(2)), Ok(None));
+		assert_eq!(path("ints[0]")?.set(&mut tag, Tag::Long(2)), Err(Tag::Long(2)));
+		assert_eq!(path("list[1]")?.set(&mut tag, Tag::Int(2)), Err(Tag::Int(2)));
+		assert_eq!(path("empty")?.set(&mut tag, Tag::List(ListTag::Empty)), Ok(None));
+		assert_eq!(path("empty[0]")?.set(&mut tag, Tag::Byte(1)), Ok(None));
+		assert_eq!(path("missing.key")?.set(&mut tag, Tag::Byte(1)), Err(Tag::Byte(1)));
+		assert_eq!(path("empty")?.get(&tag).map(Cow::into_owned), Some(Tag::List(ListTag::Byte(vec![1]))));
+		assert_eq!(path("ints")?.get(&tag).map(Cow::into_owned), Some(Tag::IntArray(vec![1, 2])));
+
+		assert_eq!(path("ints[0]")?.remove(&mut tag), Some(Tag::Int(1)));
+		assert_eq!(path("list[0].id")?.remove(&mut tag), Some(Tag::from("dirt")));
+		assert_eq!(path("list[0]")?.remove(&mut tag), Some(Tag::Compound(Map::default())));
+		assert_eq!(path("list[0]")?.remove(&mut tag), None);
+		assert_eq!(TagPath::new().remove(&mut tag), None);
+		assert_eq!(tag, compound!(("list", Tag::List(ListTag::Compound(Vec::new()))), ("ints", Tag::IntArray(vec![2])), ("empty", list!(1i8))));
+		Ok(())
+	}
+
+	#[test]
+	fn flatten_test() -> Result<(), FlatError> {
+		let item = Map::from_iter([("id".to_owned(), Tag::from("minecraft:stone")), ("tag".to_owned(), compound!(("Lore", list!("a", "b"))))]);
+		let tag = compound!(
+			("Inventory", Tag::List(ListTag::Compound(vec![item, Map::default()]))),
+			("Pos", list!(1.5, 64.0)),
+			("Nested", Tag::List(ListTag::List(vec![ListTag::Empty, ListTag::Int(vec![7])]))),
+			("UUID", Tag::IntArray(vec![1, 2, 3, 4])),
+			("Empty", Tag::Compound(Map::default()))
+		);
+		let mut flat: Vec<(String, Tag)> = tag.flatten().map(|(path, value)| (path.to_string(), value.into_owned())).collect();
+		flat.sort_by(|left, right| left.0.cmp(&right.0));
+		assert_eq!(flat, [
+			("Empty".to_owned(), Tag::Compound(Map::default())),
+			("Inventory[0].id".to_owned(), Tag::from("minecraft:stone")),
+			("Inventory[0].tag.Lore[0]".to_owned(), Tag::from("a")),
+			("Inventory[0].tag.Lore[1]".to_owned(), Tag::from("b")),
+			("Inventory[1]".to_owned(), Tag::Compound(Map::default())),
+			("Nested[0]".to_owned(), Tag::List(ListTag::Empty)),
+			("Nested[1][0]".to_owned(), Tag::Int(7)),
+			("Pos[0]".to_owned(), Tag::Double(1.5)),
+			("Pos[1]".to_owned(), Tag::Double(64.0)),
+			("UUID".to_owned(), Tag::IntArray(vec![1, 2, 3, 4])),
+		]);
+		// The order of flatten builds the same tree back.
+		assert_eq!(Tag::from_flat(tag.flatten().map(|(path, value)| (path, value.into_owned())))?, tag);
+		assert_eq!(Tag::from_flat([(TagPath::new(), Tag::Int(1))])?, Tag::Int(1));
+		assert_eq!(Tag::from_flat([])?, Tag::Compound(Map::default()));
+		assert_eq!(Tag::from_flat([("[0].a".parse().unwrap(), Tag::Int(1))])?, Tag::List(ListTag::Compound(vec![Map::from_iter([("a".to_owned(), Tag::Int(1))])])));
+
+		// Elements out of order, of different types, or inside of values that are not Compounds or Lists do not fit.
+		assert!(Tag::from_flat([("a[1]".parse().unwrap(), Tag::Int(1))]).is_err());
+		assert!(Tag::from_flat([("a[0]".parse().unwrap(), Tag::Int(1)), ("a[1]".parse().unwrap(), Tag::Byte(1))]).is_err());
+		let err = Tag::from_flat([("a".parse().unwrap(), Tag::Int(1)), ("a.b".parse().unwrap(), Tag::Int(2))]).unwrap_err();
+		assert_eq!(err.path.to_string(), "a.b");
+		Ok(())
+	}
+
+	#[test]
+	fn into_flatten_test() {
+		let tag = Tag::parse("{Inventory: [{id: stone, Count: 1b}, {}], Pos: [1.5d, 64d], Nested: [[], [7]], UUID: [I; 1, 2, 3, 4]}").unwrap();
+		let borrowed: Vec<(TagPath, Tag)> = tag.flatten().map(|(path, value)| (path, value.into_owned())).collect();
+		// The tree is parsed on one thread and its values are taken on another.
+		let values = tag.into_flatten();
+		let owned = std::thread::spawn(move || values.collect::<Vec<_>>()).join().unwrap();
+		assert_eq!(owned, borrowed);
+		assert_eq!(ListTag::Int(vec![1, 2]).into_iter().collect::<Vec<_>>(), [Tag::Int(1), Tag::Int(2)]);
+	}
+}
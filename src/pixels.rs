@@ -0,0 +1,151 @@
+#![doc = r#"
+Reading ByteArray tags as images, and writing images into them.
+
+Map colors, textures that mods store in NBT, and the results of analyses, such as a heat map of where players spend
+their time, are all images with one byte per pixel. A [Palette] gives the color of every byte: [Palette::Gray] reads
+bytes as unsigned levels of gray, [Palette::Map] reads them as map colors, and [Palette::Colors] holds any list of
+colors. The width and height are not stored in the array, so they are passed in.
+
+[to_rgba] converts the bytes to RGBA pixels. With the `image` feature, [to_image] and [encode_png] make an
+[image](https://docs.rs/image/latest/image/) `RgbaImage` or a PNG file of them, and [from_image] converts an image
+back to bytes, using the color of the palette that is closest to each pixel.
+
+```
+# use rustnbt::{ tag::Tag, pixels::* };
+let heat = Tag::ByteArray(vec![0, -1, 127, 64]);
+let rgba = to_rgba(&heat, 2, 2, &Palette::Gray).unwrap();
+assert_eq!(&rgba[4..8], [255, 255, 255, 255]);
+assert_eq!(Palette::Gray.nearest([130, 120, 125, 255]), 125u8 as i8);
+```
+"#]
+
+use crate::{
+	NbtError,
+	map,
+	tag::Tag,
+};
+
+/// The colors of the bytes of an image. See the [module](self) documentation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Palette {
+	/// Each byte is a level of gray from 0 to 255, reading its bits as unsigned.
+	Gray,
+	/// Each byte is a map color, as in [map::color_rgba].
+	Map,
+	/// Each byte, read as unsigned, is an index into these RGBA colors. Bytes past the end are transparent.
+	Colors(Vec<[u8; 4]>),
+}
+
+impl Palette {
+	/// The RGBA color of a byte.
+	pub fn rgba(&self, value: i8) -> [u8; 4] {
+		match self {
+			Palette::Gray => {
+				let level = value as u8;
+				[level, level, level, 255]
+			}
+			Palette::Map => map::color_rgba(value),
+			Palette::Colors(colors) => colors.get(value as u8 as usize).copied().unwrap_or([0; 4]),
+		}
+	}
+
+	/// The byte whose color is closest to an RGBA color. Transparent colors are matched to transparent bytes, if the
+	/// palette has any.
+	pub fn nearest(&self, rgba: [u8; 4]) -> i8 {
+		if let Palette::Gray = self {
+			let [r, g, b, _] = rgba.map(u32::from);
+			return ((r + g + b + 1) / 3) as u8 as i8;
+		}
+		let distance = |color: [u8; 4]| -> u32 {
+			if (color[3] == 0) != (rgba[3] == 0) {
+				return u32::MAX;
+			}
+			if color[3] == 0 {
+				return 0;
+			}
+			color.iter().zip(rgba).map(|(left, right)| u32::from(left.abs_diff(right)).pow(2)).sum()
+		};
+		let count = match self {
+			Palette::Colors(colors) => colors.len().min(256),
+			_ => 256,
+		};
+		(0..count)
+			.map(|index| index as u8 as i8)
+			.min_by_key(|value| distance(self.rgba(*value)))
+			.unwrap_or(0)
+	}
+}
+
+/// The bytes of a ByteArray that holds an image of `width` by `height` pixels, or an error if the tag is not a ByteArray
+/// of that size.
+fn pixels(tag: &Tag, width: u32, height: u32) -> Result<&[i8], NbtError> {
+	match tag {
+		Tag::ByteArray(bytes) if bytes.len() as u64 == u64::from(width) * u64::from(height) => Ok(bytes),
+		Tag::ByteArray(bytes) => Err(crate::invalid_data(&format!("A {width}x{height} image has {} pixels, but the ByteArray has {}.", u64::from(width) * u64::from(height), bytes.len()))),
+		_ => Err(crate::invalid_data("Images are stored in ByteArrays.")),
+	}
+}
+
+/// Converts a ByteArray that holds an image of `width` by `height` pixels, row by row, to RGBA pixels with 4 bytes
+/// each.
+pub fn to_rgba(tag: &Tag, width: u32, height: u32, palette: &Palette) -> Result<Vec<u8>, NbtError> {
+	Ok(pixels(tag, width, height)?.iter().flat_map(|value| palette.rgba(*value)).collect())
+}
+
+/// Converts a ByteArray that holds an image of `width` by `height` pixels to an `RgbaImage`.
+#[cfg(feature = "image")]
+pub fn to_image(tag: &Tag, width: u32, height: u32, palette: &Palette) -> Result<image::RgbaImage, NbtError> {
+	let rgba = to_rgba(tag, width, height, palette)?;
+	Ok(image::RgbaImage::from_raw(width, height, rgba).expect("The buffer has a pixel for every position."))
+}
+
+/// Encodes a ByteArray that holds an image of `width` by `height` pixels as a PNG file.
+#[cfg(feature = "image")]
+pub fn encode_png(tag: &Tag, width: u32, height: u32, palette: &Palette) -> Result<Vec<u8>, NbtError> {
+	let image = to_image(tag, width, height, palette)?;
+	let mut png = std::io::Cursor::new(Vec::new());
+	image.write_to(&mut png, image::ImageOutputFormat::Png).map_err(|err| match err {
+		image::ImageError::IoError(err) => NbtError::IoError(err),
+		err => crate::invalid_data(&err.to_string()),
+	})?;
+	Ok(png.into_inner())
+}
+
+/// Converts an image to a ByteArray with the byte of the closest color of the palette for each pixel, row by row.
+#[cfg(feature = "image")]
+pub fn from_image(image: &image::RgbaImage, palette: &Palette) -> Tag {
+	let mut nearest = std::collections::HashMap::new();
+	Tag::ByteArray(image.pixels().map(|pixel| *nearest.entry(pixel.0).or_insert_with(|| palette.nearest(pixel.0))).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pixels_test() -> Result<(), NbtError> {
+		let palette = Palette::Colors(vec![[0, 0, 0, 0], [255, 0, 0, 255], [0, 0, 255, 255]]);
+		let tag = Tag::ByteArray(vec![0, 1, 2, 3, 1, 0]);
+		assert_eq!(to_rgba(&tag, 3, 2, &palette)?[4..12], [255, 0, 0, 255, 0, 0, 255, 255]);
+		assert_eq!(to_rgba(&tag, 3, 2, &palette)?[12..16], [0; 4]);
+		assert!(to_rgba(&tag, 2, 2, &palette).is_err());
+		assert!(to_rgba(&Tag::IntArray(vec![]), 0, 0, &palette).is_err());
+
+		assert_eq!(palette.nearest([200, 30, 10, 255]), 1);
+		assert_eq!(palette.nearest([10, 10, 10, 0]), 0);
+		// GRASS at full brightness.
+		assert_eq!(Palette::Map.nearest([127, 178, 56, 255]), 1 << 2 | 2);
+		assert_eq!(Palette::Map.nearest([0, 0, 0, 0]), 0);
+
+		#[cfg(feature = "image")]
+		{
+			let image = to_image(&tag, 3, 2, &palette)?;
+			assert_eq!(from_image(&image, &palette), Tag::ByteArray(vec![0, 1, 2, 0, 1, 0]));
+			let png = encode_png(&tag, 3, 2, &palette)?;
+			assert_eq!(&png[1..4], b"PNG");
+			let decoded = image::load_from_memory(&png).map_err(|err| crate::invalid_data(&err.to_string()))?.to_rgba8();
+			assert_eq!(decoded, image);
+		}
+		Ok(())
+	}
+}
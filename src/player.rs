@@ -0,0 +1,350 @@
+// https://minecraft.fandom.com/wiki/Player.dat_format
+
+#![doc = r#"
+Typed access to player data (the `<uuid>.dat` files in a world's `playerdata` folder,
+or the `Player` compound in `level.dat` for singleplayer worlds).
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	tag::{
+		Tag,
+		ListTag,
+	},
+};
+
+/// Inventory slot numbers used by the `Slot` byte of items in a player's `Inventory`.
+pub mod slot {
+	/// First hotbar slot.
+	pub const HOTBAR_START: i8 = 0;
+	/// Last hotbar slot.
+	pub const HOTBAR_END: i8 = 8;
+	/// First slot of the main inventory (excluding the hotbar).
+	pub const MAIN_START: i8 = 9;
+	/// Last slot of the main inventory.
+	pub const MAIN_END: i8 = 35;
+	/// Boots armor slot.
+	pub const FEET: i8 = 100;
+	/// Leggings armor slot.
+	pub const LEGS: i8 = 101;
+	/// Chestplate armor slot.
+	pub const CHEST: i8 = 102;
+	/// Helmet armor slot.
+	pub const HEAD: i8 = 103;
+	/// Offhand slot.
+	pub const OFFHAND: i8 = -106;
+	/// Number of slots in the ender chest. Ender chest slots are numbered from `0`.
+	pub const ENDER_CHEST_SIZE: i8 = 27;
+}
+
+/// Wrapper around the root compound of a player data file.<br>
+/// The wrapper only interprets the keys that its accessors touch, so any other
+/// data in the compound is left untouched.
+#[derive(Clone, Debug, Default)]
+pub struct PlayerData {
+	data: Map,
+}
+
+impl PlayerData {
+	/// Wraps the root compound of a player data file.
+	pub fn new(data: Map) -> Self {
+		Self { data }
+	}
+
+	/// Immutably borrow the underlying compound.
+	pub fn data(&self) -> &Map {
+		&self.data
+	}
+
+	/// Mutably borrow the underlying compound.
+	pub fn data_mut(&mut self) -> &mut Map {
+		&mut self.data
+	}
+
+	/// Irreversibly take the underlying compound.
+	pub fn take_data(self) -> Map {
+		self.data
+	}
+
+	/// Returns the item in the given `Inventory` slot. See [slot] for slot numbers.
+	pub fn inventory_item(&self, slot: i8) -> Option<&Map> {
+		find_slot(&self.data, "Inventory", slot)
+	}
+
+	/// Iterates over all items in the `Inventory`.
+	pub fn inventory(&self) -> impl Iterator<Item = &Map> {
+		items(&self.data, "Inventory")
+	}
+
+	/// Puts an item into the given `Inventory` slot, replacing the item that was there.<br>
+	/// The `Slot` byte of the item is set to `slot`, and the inventory is kept ordered by slot.
+	/// Returns the replaced item. Fails if `slot` is not a hotbar, main inventory, armor, or offhand slot, or if
+	/// `Inventory` is not a List of Compounds, in which case it is left as it is.
+	pub fn set_inventory_item(&mut self, slot: i8, item: Map) -> Result<Option<Map>, NbtError> {
+		if !matches!(slot, slot::HOTBAR_START..=slot::MAIN_END | slot::FEET..=slot::HEAD | slot::OFFHAND) {
+			return Err(invalid_data(&format!("{slot} is not an inventory slot.")));
+		}
+		set_slot(&mut self.data, "Inventory", slot, item)
+	}
+
+	/// Removes the item from the given `Inventory` slot, returning it.
+	pub fn remove_inventory_item(&mut self, slot: i8) -> Option<Map> {
+		remove_slot(&mut self.data, "Inventory", slot)
+	}
+
+	/// Returns the item in the given ender chest (`EnderItems`) slot.
+	pub fn ender_item(&self, slot: i8) -> Option<&Map> {
+		find_slot(&self.data, "EnderItems", slot)
+	}
+
+	/// Iterates over all items in the ender chest.
+	pub fn ender_items(&self) -> impl Iterator<Item = &Map> {
+		items(&self.data, "EnderItems")
+	}
+
+	/// Puts an item into the given ender chest slot, replacing the item that was there.<br>
+	/// The `Slot` byte of the item is set to `slot`, and the ender chest is kept ordered by slot.
+	/// Returns the replaced item. Fails if `slot` is not below [slot::ENDER_CHEST_SIZE], or if `EnderItems` is not
+	/// a List of Compounds, in which case it is left as it is.
+	pub fn set_ender_item(&mut self, slot: i8, item: Map) -> Result<Option<Map>, NbtError> {
+		if !(0..slot::ENDER_CHEST_SIZE).contains(&slot) {
+			return Err(invalid_data(&format!("{slot} is not an ender chest slot.")));
+		}
+		set_slot(&mut self.data, "EnderItems", slot, item)
+	}
+
+	/// Removes the item from the given ender chest slot, returning it.
+	pub fn remove_ender_item(&mut self, slot: i8) -> Option<Map> {
+		remove_slot(&mut self.data, "EnderItems", slot)
+	}
+
+	/// The selected hotbar slot (`SelectedItemSlot`).
+	pub fn selected_slot(&self) -> Option<i32> {
+		match self.data.get("SelectedItemSlot") {
+			Some(Tag::Int(slot)) => Some(*slot),
+			_ => None,
+		}
+	}
+
+	/// Sets the selected hotbar slot. The value is clamped to the hotbar (`0..=8`).
+	pub fn set_selected_slot(&mut self, slot: i32) {
+		let slot = slot.clamp(slot::HOTBAR_START as i32, slot::HOTBAR_END as i32);
+		self.data.insert("SelectedItemSlot".to_owned(), Tag::Int(slot));
+	}
+
+	/// The experience level (`XpLevel`).
+	pub fn xp_level(&self) -> Option<i32> {
+		match self.data.get("XpLevel") {
+			Some(Tag::Int(level)) => Some(*level),
+			_ => None,
+		}
+	}
+
+	/// Sets the experience level.
+	pub fn set_xp_level(&mut self, level: i32) {
+		self.data.insert("XpLevel".to_owned(), Tag::Int(level));
+	}
+
+	/// The progress towards the next level (`XpP`), from `0.0` to `1.0`.
+	pub fn xp_progress(&self) -> Option<f32> {
+		match self.data.get("XpP") {
+			Some(Tag::Float(progress)) => Some(*progress),
+			_ => None,
+		}
+	}
+
+	/// Sets the progress towards the next level. The value is clamped to `0.0..=1.0`.
+	pub fn set_xp_progress(&mut self, progress: f32) {
+		self.data.insert("XpP".to_owned(), Tag::Float(progress.clamp(0.0, 1.0)));
+	}
+
+	/// The total experience collected (`XpTotal`).
+	pub fn xp_total(&self) -> Option<i32> {
+		match self.data.get("XpTotal") {
+			Some(Tag::Int(total)) => Some(*total),
+			_ => None,
+		}
+	}
+
+	/// Sets the total experience collected.
+	pub fn set_xp_total(&mut self, total: i32) {
+		self.data.insert("XpTotal".to_owned(), Tag::Int(total));
+	}
+
+	/// The position of the player (`Pos`) as `[x, y, z]`.
+	pub fn position(&self) -> Option<[f64; 3]> {
+		match self.data.get("Pos") {
+			Some(Tag::List(ListTag::Double(pos))) if pos.len() == 3 => Some([pos[0], pos[1], pos[2]]),
+			_ => None,
+		}
+	}
+
+	/// Sets the position of the player.
+	pub fn set_position(&mut self, position: [f64; 3]) {
+		self.data.insert("Pos".to_owned(), Tag::List(ListTag::Double(position.to_vec())));
+	}
+
+	/// The dimension the player is in (`Dimension`), such as `minecraft:overworld`.<br>
+	/// Worlds from before 1.16 store the dimension as an integer, which is converted
+	/// to the equivalent namespaced ID.
+	pub fn dimension(&self) -> Option<&str> {
+		match self.data.get("Dimension") {
+			Some(Tag::String(dimension)) => Some(dimension),
+			Some(Tag::Int(-1)) => Some("minecraft:the_nether"),
+			Some(Tag::Int(0)) => Some("minecraft:overworld"),
+			Some(Tag::Int(1)) => Some("minecraft:the_end"),
+			_ => None,
+		}
+	}
+
+	/// Sets the dimension the player is in.
+	pub fn set_dimension<S: Into<String>>(&mut self, dimension: S) {
+		self.data.insert("Dimension".to_owned(), Tag::String(dimension.into()));
+	}
+}
+
+impl From<Map> for PlayerData {
+	fn from(data: Map) -> Self {
+		Self::new(data)
+	}
+}
+
+impl From<PlayerData> for Tag {
+	fn from(player: PlayerData) -> Self {
+		Tag::Compound(player.data)
+	}
+}
+
+impl TryFrom<Tag> for PlayerData {
+	type Error = ();
+	/// Tries to wrap a [Tag::Compound].
+	fn try_from(value: Tag) -> Result<Self, ()> {
+		if let Tag::Compound(data) = value {
+			return Ok(Self::new(data));
+		}
+		Err(())
+	}
+}
+
+fn item_slot(item: &Map) -> Option<i8> {
+	match item.get("Slot") {
+		Some(Tag::Byte(slot)) => Some(*slot),
+		_ => None,
+	}
+}
+
+fn items<'a>(data: &'a Map, key: &str) -> impl Iterator<Item = &'a Map> {
+	let items = match data.get(key) {
+		Some(Tag::List(ListTag::Compound(items))) => items.as_slice(),
+		_ => &[],
+	};
+	items.iter()
+}
+
+fn find_slot<'a>(data: &'a Map, key: &str, slot: i8) -> Option<&'a Map> {
+	items(data, key).find(|item| item_slot(item) == Some(slot))
+}
+
+/// Returns the item list for `key`, creating it if it does not exist. Empty Lists of other types are replaced, since
+/// they hold nothing, and anything else is an error.
+fn items_mut<'a>(data: &'a mut Map, key: &str) -> Result<&'a mut Vec<Map>, NbtError> {
+	let list = data.entry(key.to_owned()).or_insert(Tag::List(ListTag::Empty));
+	if matches!(list, Tag::List(items) if items.is_empty()) {
+		*list = Tag::List(ListTag::Compound(Vec::new()));
+	}
+	match list {
+		Tag::List(ListTag::Compound(items)) => Ok(items),
+		_ => Err(invalid_data(&format!("{key} is not a List of Compounds."))),
+	}
+}
+
+fn set_slot(data: &mut Map, key: &str, slot: i8, mut item: Map) -> Result<Option<Map>, NbtError> {
+	item.insert("Slot".to_owned(), Tag::Byte(slot));
+	let items = items_mut(data, key)?;
+	if let Some(existing) = items.iter_mut().find(|existing| item_slot(existing) == Some(slot)) {
+		return Ok(Some(std::mem::replace(existing, item)));
+	}
+	// Vanilla writes items in ascending slot order, so keep that order.
+	let index = items.iter()
+		.position(|existing| item_slot(existing).is_some_and(|existing| existing > slot))
+		.unwrap_or(items.len());
+	items.insert(index, item);
+	Ok(None)
+}
+
+fn remove_slot(data: &mut Map, key: &str, slot: i8) -> Option<Map> {
+	match data.get_mut(key) {
+		Some(Tag::List(ListTag::Compound(items))) => {
+			let index = items.iter().position(|item| item_slot(item) == Some(slot))?;
+			Some(items.remove(index))
+		}
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn item(id: &str, count: i8) -> Map {
+		Map::from_iter([
+			("id".to_owned(), Tag::from(id)),
+			("Count".to_owned(), Tag::Byte(count)),
+		])
+	}
+
+	#[test]
+	fn inventory_test() -> Result<(), NbtError> {
+		let mut player = PlayerData::default();
+		assert!(player.set_inventory_item(slot::HEAD, item("minecraft:diamond_helmet", 1))?.is_none());
+		assert!(player.set_inventory_item(0, item("minecraft:stone", 64))?.is_none());
+		assert!(player.set_inventory_item(slot::OFFHAND, item("minecraft:shield", 1))?.is_none());
+		let slots: Vec<i8> = player.inventory().filter_map(item_slot).collect();
+		assert_eq!(slots, [slot::OFFHAND, 0, slot::HEAD]);
+		let replaced = player.set_inventory_item(0, item("minecraft:dirt", 3))?.expect("Expected a replaced item.");
+		assert!(matches!(replaced.get("id"), Some(Tag::String(id)) if id == "minecraft:stone"));
+		assert!(matches!(player.inventory_item(0).and_then(|item| item.get("Count")), Some(Tag::Byte(3))));
+		assert!(player.remove_inventory_item(slot::HEAD).is_some());
+		assert!(player.inventory_item(slot::HEAD).is_none());
+		assert_eq!(player.inventory().count(), 2);
+		assert!(player.set_ender_item(26, item("minecraft:elytra", 1))?.is_none());
+		assert!(player.ender_item(26).is_some());
+		for slot in [-1, 36, 99, 104, -105, -107] {
+			assert!(player.set_inventory_item(slot, item("minecraft:stone", 1)).is_err());
+		}
+		for slot in [slot::MAIN_END, slot::FEET] {
+			assert!(player.set_inventory_item(slot, item("minecraft:stone", 1))?.is_none());
+		}
+		assert!(player.set_ender_item(slot::ENDER_CHEST_SIZE, item("minecraft:stone", 1)).is_err());
+		assert!(player.set_ender_item(-1, item("minecraft:stone", 1)).is_err());
+		assert!(player.set_ender_item(0, item("minecraft:stone", 1))?.is_none());
+		assert_eq!((player.inventory().count(), player.ender_items().count()), (4, 2));
+
+		// Empty Lists of another type hold nothing and are replaced, but other values are kept.
+		player.data_mut().insert("EnderItems".to_owned(), Tag::List(ListTag::Int(Vec::new())));
+		assert!(player.set_ender_item(0, item("minecraft:stone", 1))?.is_none());
+		player.data_mut().insert("Inventory".to_owned(), Tag::List(ListTag::Int(vec![1])));
+		assert!(player.set_inventory_item(0, item("minecraft:stone", 1)).is_err());
+		assert_eq!(player.data().get("Inventory"), Some(&Tag::List(ListTag::Int(vec![1]))));
+		Ok(())
+	}
+
+	#[test]
+	fn stats_test() {
+		let mut player = PlayerData::default();
+		player.set_selected_slot(12);
+		assert_eq!(player.selected_slot(), Some(8));
+		player.set_xp_level(30);
+		player.set_xp_progress(0.5);
+		assert_eq!(player.xp_level(), Some(30));
+		assert_eq!(player.xp_progress(), Some(0.5));
+		player.set_position([1.5, 64.0, -3.5]);
+		assert_eq!(player.position(), Some([1.5, 64.0, -3.5]));
+		player.data_mut().insert("Dimension".to_owned(), Tag::Int(-1));
+		assert_eq!(player.dimension(), Some("minecraft:the_nether"));
+		player.set_dimension("minecraft:the_end");
+		assert_eq!(player.dimension(), Some("minecraft:the_end"));
+	}
+}
@@ -0,0 +1,258 @@
+#![doc = r#"
+Coordinate types for blocks, chunk sections, chunks, and regions.
+
+A chunk is 16x16 blocks, a chunk section is a 16x16x16 cube of blocks within a chunk,
+and a region is 32x32 chunks. The conversions between them are floor divisions, which
+is what the shifts below do for negative coordinates as well.
+
+```
+# use rustnbt::pos::*;
+let block = BlockPos::new(-1, 70, 513);
+assert_eq!(block.chunk(), ChunkPos::new(-1, 32));
+assert_eq!(block.chunk().region(), RegionPos::new(-1, 1));
+assert_eq!(block.local(), (15, 6, 1));
+```
+"#]
+
+use crate::world::THE_NETHER;
+
+/// Position of a block.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct BlockPos {
+	pub x: i32,
+	pub y: i32,
+	pub z: i32,
+}
+
+/// Position of a chunk section, in units of 16 blocks on every axis.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct SectionPos {
+	pub x: i32,
+	pub y: i32,
+	pub z: i32,
+}
+
+/// Position of a chunk, in units of 16 blocks.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct ChunkPos {
+	pub x: i32,
+	pub z: i32,
+}
+
+/// Position of a region, in units of 32 chunks.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct RegionPos {
+	pub x: i32,
+	pub z: i32,
+}
+
+impl BlockPos {
+	pub const fn new(x: i32, y: i32, z: i32) -> Self {
+		Self { x, y, z }
+	}
+
+	/// The chunk that contains this block.
+	pub const fn chunk(self) -> ChunkPos {
+		ChunkPos::new(self.x >> 4, self.z >> 4)
+	}
+
+	/// The chunk section that contains this block.
+	pub const fn section(self) -> SectionPos {
+		SectionPos::new(self.x >> 4, self.y >> 4, self.z >> 4)
+	}
+
+	/// The position of this block within its chunk section, as `(x, y, z)` where each is in `0..16`.
+	pub const fn local(self) -> (usize, usize, usize) {
+		((self.x & 15) as usize, (self.y & 15) as usize, (self.z & 15) as usize)
+	}
+
+	/// The index of this block in the 4096 entry block array of its chunk section.
+	/// Blocks are ordered by Y, then Z, then X.
+	pub const fn section_index(self) -> usize {
+		let (x, y, z) = self.local();
+		y << 8 | z << 4 | x
+	}
+
+	/// The index of the 4x4x4 biome cell that contains this block, in the 64 entry biome array of its chunk section.
+	/// Cells are ordered by Y, then Z, then X.
+	pub const fn biome_index(self) -> usize {
+		let (x, y, z) = self.local();
+		(y >> 2) << 4 | (z >> 2) << 2 | x >> 2
+	}
+
+	/// Converts a position in `from` into the equivalent position in `to`, using the coordinate
+	/// scale of each dimension (the nether is 8 times smaller than the overworld). Y is left as-is.
+	pub fn to_dimension(self, from: &str, to: &str) -> Self {
+		let scale = coordinate_scale(from) / coordinate_scale(to);
+		let convert = |value: i32| (value as f64 * scale).floor() as i32;
+		Self::new(convert(self.x), self.y, convert(self.z))
+	}
+}
+
+impl SectionPos {
+	pub const fn new(x: i32, y: i32, z: i32) -> Self {
+		Self { x, y, z }
+	}
+
+	/// The chunk that contains this section.
+	pub const fn chunk(self) -> ChunkPos {
+		ChunkPos::new(self.x, self.z)
+	}
+
+	/// The block with the lowest coordinates in this section.
+	pub const fn min_block(self) -> BlockPos {
+		BlockPos::new(self.x << 4, self.y << 4, self.z << 4)
+	}
+}
+
+impl ChunkPos {
+	pub const fn new(x: i32, z: i32) -> Self {
+		Self { x, z }
+	}
+
+	/// The region that contains this chunk.
+	pub const fn region(self) -> RegionPos {
+		RegionPos::new(self.x >> 5, self.z >> 5)
+	}
+
+	/// The position of this chunk within its region, as `(x, z)` where each is in `0..32`.
+	pub const fn local(self) -> (usize, usize) {
+		((self.x & 31) as usize, (self.z & 31) as usize)
+	}
+
+	/// The index of this chunk in the header tables of its region file.
+	pub const fn region_index(self) -> usize {
+		let (x, z) = self.local();
+		z * 32 + x
+	}
+
+	/// The block with the lowest X and Z coordinates in this chunk, at the given Y coordinate.
+	pub const fn min_block(self, y: i32) -> BlockPos {
+		BlockPos::new(self.x << 4, y, self.z << 4)
+	}
+
+	/// The section of this chunk at the given section Y coordinate.
+	pub const fn section(self, y: i32) -> SectionPos {
+		SectionPos::new(self.x, y, self.z)
+	}
+
+	/// Packs this position into a long the way the game does, with X in the low 32 bits and Z in the high 32 bits.
+	pub const fn as_long(self) -> i64 {
+		(self.x as u32 as u64 | (self.z as u32 as u64) << 32) as i64
+	}
+
+	/// Unpacks a position packed by [as_long](Self::as_long).
+	pub const fn from_long(value: i64) -> Self {
+		Self::new(value as i32, (value >> 32) as i32)
+	}
+}
+
+impl RegionPos {
+	pub const fn new(x: i32, z: i32) -> Self {
+		Self { x, z }
+	}
+
+	/// The chunk at the given position within this region. Only the lower 5 bits of `x` and `z` are used.
+	pub const fn chunk(self, x: i32, z: i32) -> ChunkPos {
+		ChunkPos::new(self.x << 5 | (x & 31), self.z << 5 | (z & 31))
+	}
+
+	/// Iterates over all 1024 chunks in this region.
+	pub fn chunks(self) -> impl Iterator<Item = ChunkPos> {
+		(0..1024).map(move |index| self.chunk(index % 32, index / 32))
+	}
+
+	/// The file name of this region, such as `r.-1.2.mca`.
+	pub fn file_name(self) -> String {
+		format!("r.{}.{}.mca", self.x, self.z)
+	}
+
+	/// Parses the region position from a file name such as `r.-1.2.mca`.
+	pub fn from_file_name(name: &str) -> Option<Self> {
+		let mut parts = name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+		let x = parts.next()?.parse().ok()?;
+		let z = parts.next()?.parse().ok()?;
+		if parts.next().is_some() {
+			return None;
+		}
+		Some(Self::new(x, z))
+	}
+}
+
+/// The number of overworld blocks that one block of the dimension spans horizontally.
+/// This is `8.0` for the nether and `1.0` for every other vanilla dimension.
+pub fn coordinate_scale(dimension: &str) -> f64 {
+	match dimension {
+		THE_NETHER => 8.0,
+		_ => 1.0,
+	}
+}
+
+macro_rules! tuple_conversions {
+	($($type:ident($($field:ident),+);)+) => {
+		$(
+			impl From<($(tuple_conversions!(@i32 $field),)+)> for $type {
+				fn from(($($field,)+): ($(tuple_conversions!(@i32 $field),)+)) -> Self {
+					Self { $($field,)+ }
+				}
+			}
+
+			impl From<$type> for ($(tuple_conversions!(@i32 $field),)+) {
+				fn from(pos: $type) -> Self {
+					($(pos.$field,)+)
+				}
+			}
+		)+
+	};
+	(@i32 $field:ident) => { i32 };
+}
+
+tuple_conversions!{
+	BlockPos(x, y, z);
+	SectionPos(x, y, z);
+	ChunkPos(x, z);
+	RegionPos(x, z);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::world::{ OVERWORLD, THE_END };
+
+	#[test]
+	fn conversion_test() {
+		let block = BlockPos::new(-17, -64, 31);
+		assert_eq!(block.chunk(), ChunkPos::new(-2, 1));
+		assert_eq!(block.section(), SectionPos::new(-2, -4, 1));
+		assert_eq!(block.local(), (15, 0, 15));
+		assert_eq!(block.section_index(), 0xFF);
+		assert_eq!(block.biome_index(), 0x0F);
+		assert_eq!(block.section().min_block(), BlockPos::new(-32, -64, 16));
+		let chunk = ChunkPos::new(-33, 64);
+		assert_eq!(chunk.region(), RegionPos::new(-2, 2));
+		assert_eq!(chunk.local(), (31, 0));
+		assert_eq!(chunk.region_index(), 31);
+		assert_eq!(chunk.region().chunk(31, 0), chunk);
+		assert_eq!(chunk.as_long(), 64 << 32 | 0xFFFF_FFDF);
+		assert_eq!(ChunkPos::from_long(chunk.as_long()), chunk);
+		assert_eq!(RegionPos::new(1, -1).chunks().last(), Some(ChunkPos::new(63, -1)));
+		assert_eq!(ChunkPos::from((1, 2)), ChunkPos::new(1, 2));
+		assert_eq!(<(i32, i32, i32)>::from(block), (-17, -64, 31));
+	}
+
+	#[test]
+	fn file_name_test() {
+		assert_eq!(RegionPos::from_file_name("r.-1.2.mca"), Some(RegionPos::new(-1, 2)));
+		assert_eq!(RegionPos::from_file_name("r.1.2.mcr"), None);
+		assert_eq!(RegionPos::from_file_name("r.1.2.3.mca"), None);
+		assert_eq!(RegionPos::new(-3, 4).file_name(), "r.-3.4.mca");
+	}
+
+	#[test]
+	fn dimension_test() {
+		let block = BlockPos::new(-100, 64, 800);
+		assert_eq!(block.to_dimension(OVERWORLD, THE_NETHER), BlockPos::new(-13, 64, 100));
+		assert_eq!(BlockPos::new(-13, 64, 100).to_dimension(THE_NETHER, OVERWORLD), BlockPos::new(-104, 64, 800));
+		assert_eq!(block.to_dimension(OVERWORLD, THE_END), block);
+	}
+}
@@ -0,0 +1,437 @@
+#![doc = r#"
+Reflection over binary NBT, reporting where every tag is in the source bytes.
+
+[reflect] walks uncompressed binary NBT without building a [Tag](crate::tag::Tag), and reports a [TagSpan] for every tag:
+its [TagPath], the offset of its header (the type ID and name), the offset of its payload, and its length.
+This is what hex-editor style views and corruption diagnostics need. When the data is damaged, the [ReflectError]
+has the offset and path where reading stopped, and every tag that ended before that point has already been reported.
+[annotated_dump](crate::io::annotated_dump) prints the spans as a hexdump.
+
+[diff] compares two versions of binary NBT tag by tag and maps each difference to its path and byte ranges.
+Because it works from the spans, it still compares everything that could be read when one side is damaged.
+
+```
+# use rustnbt::{ compound, io::*, tag::NamedTag, reflect::spans };
+let mut bytes = Vec::new();
+bytes.write_nbt(&NamedTag::new(compound!(("Health", 20.0f32))))?;
+let spans = spans(&bytes).expect("Data is valid.");
+// The root, then `Health`.
+assert_eq!(spans[1].path.to_string(), "Health");
+assert_eq!((spans[1].header, spans[1].payload, spans[1].length), (3, 12, 13));
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	io::DEFAULT_MAX_DEPTH,
+	path::{ PathSegment, TagPath },
+	tag::TagID,
+};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Where a tag is in binary NBT. Offsets are from the start of the data that was reflected.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TagSpan {
+	pub id: TagID,
+	/// The path of the tag from the root. The root has an empty path.
+	pub path: TagPath,
+	/// The offset of the tag's type ID. List elements have no header, so this is the same as `payload` for them.
+	pub header: usize,
+	/// The offset of the tag's payload, after the type ID and name.
+	pub payload: usize,
+	/// The number of bytes from `header` to the end of the payload.
+	pub length: usize,
+}
+
+impl TagSpan {
+	/// The offset after the last byte of the tag.
+	pub fn end(&self) -> usize {
+		self.header + self.length
+	}
+
+	/// The bytes of the whole tag, including its header.
+	pub fn range(&self) -> Range<usize> {
+		self.header..self.end()
+	}
+
+	/// The bytes of the tag's payload.
+	pub fn payload_range(&self) -> Range<usize> {
+		self.payload..self.end()
+	}
+
+	/// The number of Compounds and Lists that contain the tag.
+	pub fn depth(&self) -> usize {
+		self.path.segments().len()
+	}
+}
+
+/// The reason that [reflect] stopped.
+#[derive(thiserror::Error, Clone, PartialEq, Eq, Debug)]
+#[error("{message} (at offset {offset}, in `{path}`)")]
+pub struct ReflectError {
+	/// The offset of the byte that could not be read.
+	pub offset: usize,
+	/// The path of the tag that was being read.
+	pub path: TagPath,
+	pub message: String,
+}
+
+/// Receives the span of every tag from [reflect].
+pub trait Visitor {
+	/// Called when a tag ends, so the tags inside of a Compound or List are visited before it.
+	fn visit(&mut self, span: &TagSpan);
+}
+
+impl<F: FnMut(&TagSpan)> Visitor for F {
+	fn visit(&mut self, span: &TagSpan) {
+		self(span)
+	}
+}
+
+/// A Compound or List whose end has not been reached.
+struct Open {
+	id: TagID,
+	header: usize,
+	payload: usize,
+	/// The element type, the number of elements left, and the index of the next element, for Lists.
+	list: Option<(TagID, usize, usize)>,
+}
+
+struct Reflector<'a, V> {
+	bytes: &'a [u8],
+	visitor: &'a mut V,
+	path: TagPath,
+	stack: Vec<Open>,
+}
+
+/// Walks the named tag at the start of `bytes`, calling `visitor` for every tag, and returns the length of the named tag.<br>
+/// Tags may be nested up to [DEFAULT_MAX_DEPTH](crate::io::DEFAULT_MAX_DEPTH) deep. Nesting is tracked on the heap,
+/// and lengths are checked against the data before they are used, so damaged data is safe to reflect.
+/// Names are decoded lossily, since only their position matters here.
+pub fn reflect<V: Visitor>(bytes: &[u8], visitor: &mut V) -> Result<usize, ReflectError> {
+	let mut reflector = Reflector { bytes, visitor, path: TagPath::new(), stack: Vec::new() };
+	reflector.run()
+}
+
+/// Reflects the named tag at the start of `bytes`, returning the spans of every tag in the order they start.
+pub fn spans(bytes: &[u8]) -> Result<Vec<TagSpan>, ReflectError> {
+	let mut spans = Vec::new();
+	reflect(bytes, &mut |span: &TagSpan| spans.push(span.clone()))?;
+	spans.sort_by_key(|span| span.header);
+	Ok(spans)
+}
+
+impl<V: Visitor> Reflector<'_, V> {
+	fn error<T>(&self, offset: usize, message: impl Into<String>) -> Result<T, ReflectError> {
+		Err(ReflectError { offset, path: self.path.clone(), message: message.into() })
+	}
+
+	fn take(&self, position: usize, count: usize) -> Result<&[u8], ReflectError> {
+		match position.checked_add(count).and_then(|end| self.bytes.get(position..end)) {
+			Some(bytes) => Ok(bytes),
+			None => self.error(self.bytes.len().min(position), "Unexpected end of data."),
+		}
+	}
+
+	fn id(&self, position: usize) -> Result<Option<TagID>, ReflectError> {
+		match self.take(position, 1)?[0] {
+			0 => Ok(None),
+			id => match TagID::try_from(id) {
+				Ok(id) => Ok(Some(id)),
+				Err(_) => self.error(position, format!("Unsupported tag ID {id}.")),
+			},
+		}
+	}
+
+	fn length(&self, position: usize) -> Result<usize, ReflectError> {
+		let length = i32::from_be_bytes(self.take(position, 4)?.try_into().unwrap());
+		if length < 0 {
+			return self.error(position, format!("Negative length {length}."));
+		}
+		Ok(length as usize)
+	}
+
+	/// Returns the name at `position` and the position after it.
+	fn string(&self, position: usize) -> Result<(String, usize), ReflectError> {
+		let length = u16::from_be_bytes(self.take(position, 2)?.try_into().unwrap()) as usize;
+		let name = String::from_utf8_lossy(self.take(position + 2, length)?).into_owned();
+		Ok((name, position + 2 + length))
+	}
+
+	fn run(&mut self) -> Result<usize, ReflectError> {
+		let Some(id) = self.id(0)? else {
+			return self.error(0, "The root is an End tag.");
+		};
+		let (_, payload) = self.string(1)?;
+		let mut position = self.begin(id, 0, payload)?;
+		while let Some(open) = self.stack.last_mut() {
+			let next = match &mut open.list {
+				Some((_, 0, _)) => None,
+				Some((element, remaining, index)) => {
+					*remaining -= 1;
+					*index += 1;
+					Some((*element, PathSegment::Index(*index - 1), position))
+				}
+				None => match self.id(position)? {
+					None => {
+						position += 1;
+						None
+					}
+					Some(id) => {
+						let (name, payload) = self.string(position + 1)?;
+						Some((id, PathSegment::Key(name), payload))
+					}
+				},
+			};
+			match next {
+				Some((id, segment, payload)) => {
+					self.path.push(segment);
+					position = self.begin(id, position, payload)?;
+				}
+				None => {
+					let open = self.stack.pop().expect("The stack is not empty.");
+					self.finish(open.id, open.header, open.payload, position);
+				}
+			}
+		}
+		Ok(position)
+	}
+
+	/// Starts a tag. Tags that hold no other tags are finished right away. Returns the position after what was read.
+	fn begin(&mut self, id: TagID, header: usize, payload: usize) -> Result<usize, ReflectError> {
+		let array = |reflector: &Self, size: usize| -> Result<usize, ReflectError> {
+			let length = reflector.length(payload)?;
+			let Some(bytes) = length.checked_mul(size) else {
+				return reflector.error(payload, "Array is too long.");
+			};
+			reflector.take(payload + 4, bytes)?;
+			Ok(payload + 4 + bytes)
+		};
+		let end = match id {
+			TagID::Byte => payload + self.take(payload, 1)?.len(),
+			TagID::Short => payload + self.take(payload, 2)?.len(),
+			TagID::Int | TagID::Float => payload + self.take(payload, 4)?.len(),
+			TagID::Long | TagID::Double => payload + self.take(payload, 8)?.len(),
+			TagID::ByteArray => array(self, 1)?,
+			TagID::IntArray => array(self, 4)?,
+			TagID::LongArray => array(self, 8)?,
+			TagID::String => self.string(payload)?.1,
+			TagID::List | TagID::Compound => {
+				if self.stack.len() >= DEFAULT_MAX_DEPTH {
+					return self.error(header, format!("Tags are nested deeper than {DEFAULT_MAX_DEPTH}."));
+				}
+				if id == TagID::Compound {
+					self.stack.push(Open { id, header, payload, list: None });
+					return Ok(payload);
+				}
+				let element = self.id(payload)?;
+				let length = self.length(payload + 1)?;
+				let element = match element {
+					Some(element) => element,
+					None if length == 0 => TagID::Byte,
+					None => return self.error(payload, "List of End tags has elements."),
+				};
+				self.stack.push(Open { id, header, payload, list: Some((element, length, 0)) });
+				return Ok(payload + 5);
+			}
+		};
+		self.finish(id, header, payload, end);
+		Ok(end)
+	}
+
+	fn finish(&mut self, id: TagID, header: usize, payload: usize, end: usize) {
+		self.visitor.visit(&TagSpan { id, path: self.path.clone(), header, payload, length: end - header });
+		self.path.pop();
+	}
+}
+
+/// How a tag differs between the old and new data in a [ByteChange].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ByteChangeKind {
+	/// Only the new data has the tag.
+	Added,
+	/// Only the old data has the tag.
+	Removed,
+	/// The tag has a different type, or a tag that holds no other tags has a different payload.
+	Changed,
+	/// One side has the tag, and the other side is damaged where the tag would be.
+	Unknown,
+}
+
+/// A difference found by [diff]. Tags inside of an added, removed, unknown, or retyped Compound or List are not listed on their own.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ByteChange {
+	pub path: TagPath,
+	pub kind: ByteChangeKind,
+	/// The bytes of the tag in the old data, if it has the tag.
+	pub old: Option<Range<usize>>,
+	/// The bytes of the tag in the new data, if it has the tag.
+	pub new: Option<Range<usize>>,
+}
+
+/// The result of [diff].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct BinaryDiff {
+	/// The differences, in the order of the old data, followed by tags that only the new data has.
+	pub changes: Vec<ByteChange>,
+	/// Where reflecting the old data stopped, if it is damaged.
+	pub old_error: Option<ReflectError>,
+	/// Where reflecting the new data stopped, if it is damaged.
+	pub new_error: Option<ReflectError>,
+}
+
+impl BinaryDiff {
+	/// Returns true if both sides could be read and no differences were found.
+	pub fn is_empty(&self) -> bool {
+		self.changes.is_empty() && self.old_error.is_none() && self.new_error.is_none()
+	}
+}
+
+/// The spans of every tag that ended before the data was found to be damaged, in the order they start.
+fn partial_spans(bytes: &[u8]) -> (Vec<TagSpan>, Option<ReflectError>) {
+	let mut spans = Vec::new();
+	let error = reflect(bytes, &mut |span: &TagSpan| spans.push(span.clone())).err();
+	spans.sort_by_key(|span| span.header);
+	(spans, error)
+}
+
+/// Why a tag is missing from one side of a [diff].
+enum Missing {
+	/// The tag is a Compound or List that was partly read before the damage, so its contents are compared instead.
+	Partial,
+	/// The tag's container was read completely, so the tag is really missing.
+	Known,
+	/// The tag's container was damaged.
+	Unknown,
+	/// The tag's container is missing or has another type, which is reported instead.
+	Covered,
+}
+
+/// Classifies a tag that `this` side has and the `other` side does not.
+fn missing(path: &TagPath, this: &HashMap<&TagPath, &TagSpan>, other: &HashMap<&TagPath, &TagSpan>, error: Option<&ReflectError>) -> Missing {
+	let damaged_under = |path: &TagPath| error.is_some_and(|error| error.path.segments().starts_with(path.segments()));
+	if damaged_under(path) {
+		// The other side stopped inside of this tag. A Compound or List may have been partly read.
+		let container = this.get(path).is_some_and(|span| matches!(span.id, TagID::Compound | TagID::List));
+		return if container { Missing::Partial } else { Missing::Unknown };
+	}
+	let mut parent = path.clone();
+	parent.pop();
+	match (other.get(&parent), this.get(&parent)) {
+		(Some(theirs), Some(ours)) if theirs.id == ours.id => Missing::Known,
+		(Some(_), _) => Missing::Covered,
+		(None, _) if damaged_under(&parent) => Missing::Unknown,
+		(None, _) => Missing::Covered,
+	}
+}
+
+/// Compares two named tags in binary NBT, by the paths of their tags rather than by position,
+/// so reordered Compound entries are not differences. Either side may be damaged; see [ByteChangeKind::Unknown].
+pub fn diff(old: &[u8], new: &[u8]) -> BinaryDiff {
+	let (old_spans, old_error) = partial_spans(old);
+	let (new_spans, new_error) = partial_spans(new);
+	let old_index: HashMap<&TagPath, &TagSpan> = old_spans.iter().map(|span| (&span.path, span)).collect();
+	let new_index: HashMap<&TagPath, &TagSpan> = new_spans.iter().map(|span| (&span.path, span)).collect();
+	let change = |span: &TagSpan, kind: ByteChangeKind, old: Option<Range<usize>>, new: Option<Range<usize>>| {
+		ByteChange { path: span.path.clone(), kind, old, new }
+	};
+	let mut changes = Vec::new();
+	for span in &old_spans {
+		match new_index.get(&span.path) {
+			Some(other) => {
+				let leaf = !matches!(span.id, TagID::Compound | TagID::List);
+				if other.id != span.id || (leaf && old[span.payload_range()] != new[other.payload_range()]) {
+					changes.push(change(span, ByteChangeKind::Changed, Some(span.range()), Some(other.range())));
+				}
+			}
+			None => match missing(&span.path, &old_index, &new_index, new_error.as_ref()) {
+				Missing::Known => changes.push(change(span, ByteChangeKind::Removed, Some(span.range()), None)),
+				Missing::Unknown => changes.push(change(span, ByteChangeKind::Unknown, Some(span.range()), None)),
+				Missing::Partial | Missing::Covered => {}
+			},
+		}
+	}
+	for span in new_spans.iter().filter(|span| !old_index.contains_key(&span.path)) {
+		match missing(&span.path, &new_index, &old_index, old_error.as_ref()) {
+			Missing::Known => changes.push(change(span, ByteChangeKind::Added, None, Some(span.range()))),
+			Missing::Unknown => changes.push(change(span, ByteChangeKind::Unknown, None, Some(span.range()))),
+			Missing::Partial | Missing::Covered => {}
+		}
+	}
+	BinaryDiff { changes, old_error, new_error }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ NbtError, compound, list, io::*, tag::{ Tag, NamedTag } };
+
+	#[test]
+	fn reflect_test() -> Result<(), NbtError> {
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&NamedTag::with_name("root", compound!(("Pos", list!(1.0, 2.0)))))?;
+		let spans = spans(&bytes).expect("Data is valid.");
+		let summary: Vec<(String, TagID, usize, usize, usize)> = spans.iter()
+			.map(|span| (span.path.to_string(), span.id, span.header, span.payload, span.length))
+			.collect();
+		assert_eq!(summary, [
+			("".to_owned(), TagID::Compound, 0, 7, bytes.len()),
+			("Pos".to_owned(), TagID::List, 7, 13, 6 + 5 + 16),
+			("Pos[0]".to_owned(), TagID::Double, 18, 18, 8),
+			("Pos[1]".to_owned(), TagID::Double, 26, 26, 8),
+		]);
+		assert_eq!(&bytes[spans[3].payload_range()], 2.0f64.to_be_bytes());
+
+		// Cutting off the second Double reports where reading stopped, after the first one was visited.
+		let mut visited = Vec::new();
+		let error = reflect(&bytes[..30], &mut |span: &TagSpan| visited.push(span.path.to_string())).unwrap_err();
+		assert_eq!((error.offset, error.path.to_string()), (26, "Pos[1]".to_owned()));
+		assert_eq!(visited, ["Pos[0]"]);
+
+		let mut corrupt = bytes.clone();
+		corrupt[7] = 13;
+		assert_eq!(reflect(&corrupt, &mut |_: &TagSpan| {}).unwrap_err().offset, 7);
+		Ok(())
+	}
+
+	fn summary(diff: &BinaryDiff) -> Vec<(String, ByteChangeKind)> {
+		let mut changes: Vec<_> = diff.changes.iter().map(|change| (change.path.to_string(), change.kind)).collect();
+		changes.sort_by(|a, b| a.0.cmp(&b.0));
+		changes
+	}
+
+	#[test]
+	fn diff_test() -> Result<(), NbtError> {
+		let encode = |tag: Tag| -> Result<Vec<u8>, NbtError> {
+			let mut bytes = Vec::new();
+			bytes.write_nbt(&NamedTag::new(tag))?;
+			Ok(bytes)
+		};
+		let old = encode(compound!(("a", 1), ("b", "x"), ("e", 5), ("Pos", list!(1.0, 2.0)), ("t", compound!(("u", 1)))))?;
+		let new = encode(compound!(("a", 2), ("c", compound!(("d", 1))), ("e", 5), ("Pos", list!(1.0, 2.0, 3.0)), ("t", list!(1))))?;
+		let diff = diff(&old, &new);
+		assert_eq!(summary(&diff), [
+			("Pos[2]".to_owned(), ByteChangeKind::Added),
+			("a".to_owned(), ByteChangeKind::Changed),
+			("b".to_owned(), ByteChangeKind::Removed),
+			("c".to_owned(), ByteChangeKind::Added),
+			("t".to_owned(), ByteChangeKind::Changed),
+		]);
+		let a = diff.changes.iter().find(|change| change.path.to_string() == "a").expect("a changed");
+		assert_eq!(old[a.old.clone().expect("old has a")].last(), Some(&1));
+		assert!(self::diff(&old, &old).is_empty());
+
+		// The new data is cut off in the middle of Pos, so its last element can not be compared.
+		let old = encode(compound!(("Pos", list!(1.0, 2.0))))?;
+		let mut new = encode(compound!(("Pos", list!(1.5, 2.0))))?;
+		new.truncate(new.len() - 5);
+		let diff = self::diff(&old, &new);
+		assert_eq!(summary(&diff), [
+			("Pos[0]".to_owned(), ByteChangeKind::Changed),
+			("Pos[1]".to_owned(), ByteChangeKind::Unknown),
+		]);
+		assert_eq!(diff.new_error.map(|error| error.path.to_string()).as_deref(), Some("Pos[1]"));
+		Ok(())
+	}
+}
@@ -0,0 +1,490 @@
+// https://minecraft.fandom.com/wiki/Region_file_format
+
+#![doc = r#"
+Reading and writing of Anvil region files (`r.<x>.<z>.mca`).
+
+A region file holds up to 32x32 chunks. The file starts with an 8KiB header made of
+a table of chunk locations followed by a table of chunk timestamps. The chunks are stored
+after the header in 4KiB sectors, each one prefixed with its length and compression scheme.
+
+```no_run
+# use rustnbt::{ region::Region, pos::ChunkPos };
+let mut region = Region::open("world/region/r.0.0.mca").expect("Failed to open region.");
+if let Some(chunk) = region.read_chunk(ChunkPos::new(3, 7)).expect("Failed to read chunk.") {
+	println!("{}", chunk.tag());
+}
+```
+"#]
+
+use crate::{
+	NbtError,
+	context::{ ErrorContext, WithContext },
+	invalid_data,
+	io::*,
+	pos::{ ChunkPos, RegionPos },
+	tag::NamedTag,
+};
+use std::fs::{ File, OpenOptions };
+use std::io::{ BufReader, Read, Seek, SeekFrom, Write };
+use std::path::{ Path, PathBuf };
+
+/// Size of a sector in bytes.
+pub const SECTOR_SIZE: usize = 4096;
+/// Number of sectors that the header takes up.
+const HEADER_SECTORS: u32 = 2;
+/// Chunks that need more sectors than this are stored in an external `.mcc` file.
+const MAX_CHUNK_SECTORS: usize = 255;
+/// Flag set on the compression byte when the chunk is stored in an external `.mcc` file.
+const EXTERNAL_FLAG: u8 = 128;
+
+/// The compression scheme of a chunk.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum ChunkCompression {
+	GZip = 1,
+	Zlib = 2,
+	Uncompressed = 3,
+	Lz4 = 4,
+}
+
+impl TryFrom<u8> for ChunkCompression {
+	type Error = NbtError;
+	/// Attempts to create a [ChunkCompression] from the compression byte of a chunk (without the external flag).
+	fn try_from(value: u8) -> Result<Self, NbtError> {
+		match value {
+			1 => Ok(ChunkCompression::GZip),
+			2 => Ok(ChunkCompression::Zlib),
+			3 => Ok(ChunkCompression::Uncompressed),
+			4 => Ok(ChunkCompression::Lz4),
+			_ => Err(invalid_data("Unknown chunk compression scheme.")),
+		}
+	}
+}
+
+/// The bytes of a chunk exactly as they are stored in the region file.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RawChunk {
+	/// The compression byte, without the external flag.
+	pub compression: u8,
+	/// The compressed chunk data.
+	pub data: Vec<u8>,
+}
+
+impl RawChunk {
+	/// Decompresses and reads the chunk NBT.
+	pub fn decode(&self) -> Result<NamedTag, NbtError> {
+		match ChunkCompression::try_from(self.compression)? {
+			ChunkCompression::GZip => flate2::read::GzDecoder::new(self.data.as_slice()).read_nbt(),
+			ChunkCompression::Zlib => flate2::read::ZlibDecoder::new(self.data.as_slice()).read_nbt(),
+			ChunkCompression::Uncompressed => self.data.as_slice().read_nbt(),
+			ChunkCompression::Lz4 => Err(invalid_data("LZ4 compressed chunks are not supported.")),
+		}
+	}
+
+	/// Writes chunk NBT and compresses it with zlib, which is what the game uses by default.
+	pub fn encode(chunk: &NamedTag) -> Result<Self, NbtError> {
+		let mut encoder = flate2::write::ZlibEncoder::new(Vec::with_capacity(chunk.nbt_size() / 4), flate2::Compression::default());
+		encoder.write_nbt(chunk)?;
+		Ok(Self {
+			compression: ChunkCompression::Zlib as u8,
+			data: encoder.finish()?,
+		})
+	}
+}
+
+/// An open region file.
+#[derive(Debug)]
+pub struct Region {
+	file: File,
+	path: PathBuf,
+	position: RegionPos,
+	/// Packed locations, `offset << 8 | sector_count`.
+	locations: [u32; 1024],
+	timestamps: [u32; 1024],
+}
+
+/// Seconds since the unix epoch, which is what the timestamp table holds.
+pub(crate) fn now_timestamp() -> u32 {
+	std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|time| time.as_secs() as u32)
+		.unwrap_or(0)
+}
+
+impl Region {
+	/// Opens an existing region file for reading and writing.
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, NbtError> {
+		let file = OpenOptions::new().read(true).write(true).open(path.as_ref())?;
+		Self::from_file(file, path.as_ref().to_path_buf())
+	}
+
+	/// Opens a region file for reading and writing, creating an empty region if the file does not exist.
+	pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, NbtError> {
+		let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path.as_ref())?;
+		Self::from_file(file, path.as_ref().to_path_buf())
+	}
+
+	fn from_file(mut file: File, path: PathBuf) -> Result<Self, NbtError> {
+		let length = file.metadata()?.len();
+		// Chunks are stored with local coordinates, so the absolute coordinates come from the file name.
+		let position = path.file_name()
+			.and_then(|name| name.to_str())
+			.and_then(RegionPos::from_file_name)
+			.unwrap_or_default();
+		let mut region = Self {
+			file,
+			path,
+			position,
+			locations: [0; 1024],
+			timestamps: [0; 1024],
+		};
+		if length == 0 {
+			// A new region file, so write an empty header.
+			region.file.write_all(&[0u8; SECTOR_SIZE * HEADER_SECTORS as usize])?;
+			return Ok(region);
+		}
+		if length < (SECTOR_SIZE * HEADER_SECTORS as usize) as u64 {
+			return Err(invalid_data("Region file is too small to contain a header."));
+		}
+		let mut reader = BufReader::new(&mut region.file);
+		for location in region.locations.iter_mut() {
+			*location = u32::nbt_read(&mut reader)?;
+		}
+		for timestamp in region.timestamps.iter_mut() {
+			*timestamp = u32::nbt_read(&mut reader)?;
+		}
+		Ok(region)
+	}
+
+	/// The path of the region file.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// The position of the region, taken from its file name.
+	/// Regions with non-standard file names are at `RegionPos::new(0, 0)`.
+	pub fn position(&self) -> RegionPos {
+		self.position
+	}
+
+	/// Returns `true` if the region contains the chunk.
+	/// Only the position of the chunk within its region is used.
+	pub fn contains_chunk(&self, chunk: ChunkPos) -> bool {
+		self.locations[chunk.region_index()] != 0
+	}
+
+	/// Iterates over the positions of all chunks present in the region.
+	pub fn chunks(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+		self.position.chunks().filter(|chunk| self.locations[chunk.region_index()] != 0)
+	}
+
+	/// Returns the time the chunk was last written, in seconds since the unix epoch.
+	/// Returns [None] if the chunk is not present in the region.
+	pub fn timestamp(&self, chunk: ChunkPos) -> Option<u32> {
+		let index = chunk.region_index();
+		if self.locations[index] == 0 {
+			return None;
+		}
+		Some(self.timestamps[index])
+	}
+
+	/// Sets the time the chunk was last written, in seconds since the unix epoch.
+	/// Returns `false` without changing anything if the chunk is not present in the region.
+	pub fn set_timestamp(&mut self, chunk: ChunkPos, timestamp: u32) -> Result<bool, NbtError> {
+		let index = chunk.region_index();
+		if self.locations[index] == 0 {
+			return Ok(false);
+		}
+		self.timestamps[index] = timestamp;
+		self.write_header_entry(index)?;
+		Ok(true)
+	}
+
+	/// Iterates over the positions of all chunks that were written at or after `since`
+	/// (in seconds since the unix epoch), according to the timestamp table.
+	/// This only reads the header, so it is cheap compared to reading the chunks themselves.
+	pub fn chunks_modified_since(&self, since: u32) -> impl Iterator<Item = ChunkPos> + '_ {
+		self.chunks().filter(move |chunk| self.timestamps[chunk.region_index()] >= since)
+	}
+
+	/// The last modification time of the region file itself.<br>
+	/// Scans can skip a whole region when this is older than the last scan.
+	pub fn file_modified(&self) -> Result<std::time::SystemTime, NbtError> {
+		Ok(self.file.metadata()?.modified()?)
+	}
+
+	/// Path of the external file used for chunks that are too big for the region file.
+	fn external_path(&self, chunk: ChunkPos) -> PathBuf {
+		let (x, z) = chunk.local();
+		let chunk = self.position.chunk(x as i32, z as i32);
+		self.path.with_file_name(format!("c.{}.{}.mcc", chunk.x, chunk.z))
+	}
+
+	/// The region and absolute position of a chunk, for the errors of reading and writing it.
+	fn chunk_context(&self, chunk: ChunkPos) -> ErrorContext {
+		let (x, z) = chunk.local();
+		ErrorContext::default().region(self.position).chunk(self.position.chunk(x as i32, z as i32))
+	}
+
+	/// Reads the bytes of a chunk without decompressing them.
+	/// Returns [None] if the chunk is not present in the region.
+	pub fn read_raw_chunk(&mut self, chunk: ChunkPos) -> Result<Option<RawChunk>, NbtError> {
+		self.read_raw(chunk).with_context(|| self.chunk_context(chunk))
+	}
+
+	fn read_raw(&mut self, chunk: ChunkPos) -> Result<Option<RawChunk>, NbtError> {
+		let location = self.locations[chunk.region_index()];
+		if location == 0 {
+			return Ok(None);
+		}
+		let offset = (location >> 8) as u64 * SECTOR_SIZE as u64;
+		let sectors = (location & 0xFF) as usize;
+		self.file.seek(SeekFrom::Start(offset))?;
+		let length = u32::nbt_read(&mut self.file)? as usize;
+		if length == 0 || length + 4 > sectors * SECTOR_SIZE {
+			return Err(invalid_data("Chunk length does not fit in its sectors."));
+		}
+		let compression = u8::nbt_read(&mut self.file)?;
+		if compression & EXTERNAL_FLAG != 0 {
+			let data = std::fs::read(self.external_path(chunk))?;
+			return Ok(Some(RawChunk { compression: compression & !EXTERNAL_FLAG, data }));
+		}
+		let mut data = vec![0u8; length - 1];
+		self.file.read_exact(&mut data)?;
+		Ok(Some(RawChunk { compression, data }))
+	}
+
+	/// Reads and decompresses a chunk. Returns [None] if the chunk is not present in the region.
+	pub fn read_chunk(&mut self, chunk: ChunkPos) -> Result<Option<NamedTag>, NbtError> {
+		match self.read_raw_chunk(chunk)? {
+			Some(raw) => raw.decode().map(Some).with_context(|| self.chunk_context(chunk)),
+			None => Ok(None),
+		}
+	}
+
+	/// Compresses and writes a chunk, updating its timestamp to the current time.
+	pub fn write_chunk(&mut self, chunk: ChunkPos, data: &NamedTag) -> Result<(), NbtError> {
+		let raw = RawChunk::encode(data).with_context(|| self.chunk_context(chunk))?;
+		self.write_raw_chunk(chunk, &raw, now_timestamp())
+	}
+
+	/// Writes the bytes of a chunk as-is, with the given timestamp.
+	/// Chunks that are too large to fit in the region file are written to an external `.mcc` file.
+	pub fn write_raw_chunk(&mut self, chunk: ChunkPos, raw: &RawChunk, timestamp: u32) -> Result<(), NbtError> {
+		self.write_raw(chunk, raw, timestamp).with_context(|| self.chunk_context(chunk))
+	}
+
+	fn write_raw(&mut self, chunk: ChunkPos, raw: &RawChunk, timestamp: u32) -> Result<(), NbtError> {
+		let index = chunk.region_index();
+		let external = raw.data.len() + 5 > MAX_CHUNK_SECTORS * SECTOR_SIZE;
+		let mut payload = Vec::with_capacity(raw.data.len() + 5);
+		if external {
+			std::fs::write(self.external_path(chunk), &raw.data)?;
+			payload.extend_from_slice(&1u32.to_be_bytes());
+			payload.push(raw.compression | EXTERNAL_FLAG);
+		} else {
+			payload.extend_from_slice(&(raw.data.len() as u32 + 1).to_be_bytes());
+			payload.push(raw.compression);
+			payload.extend_from_slice(&raw.data);
+		}
+		let sectors = payload.len().div_ceil(SECTOR_SIZE);
+		payload.resize(sectors * SECTOR_SIZE, 0);
+		let offset = self.allocate(index, sectors as u32);
+		self.file.seek(SeekFrom::Start(offset as u64 * SECTOR_SIZE as u64))?;
+		self.file.write_all(&payload)?;
+		self.locations[index] = offset << 8 | sectors as u32;
+		self.timestamps[index] = timestamp;
+		self.write_header_entry(index)?;
+		if !external {
+			// Clean up any external file from an older version of this chunk.
+			let external_path = self.external_path(chunk);
+			if external_path.exists() {
+				std::fs::remove_file(external_path)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Copies a chunk into another region without decompressing it, preserving its bytes and timestamp exactly.
+	/// The chunk keeps its local coordinates, so `dest` should be the same region of another world.
+	/// Returns `false` if the chunk is not present in this region.
+	pub fn copy_chunk_raw(&mut self, chunk: ChunkPos, dest: &mut Region) -> Result<bool, NbtError> {
+		match self.read_raw_chunk(chunk)? {
+			Some(raw) => {
+				let timestamp = self.timestamps[chunk.region_index()];
+				dest.write_raw_chunk(chunk, &raw, timestamp)?;
+				Ok(true)
+			}
+			None => Ok(false),
+		}
+	}
+
+	/// Removes a chunk from the region. Returns `false` if the chunk was not present.
+	/// The sectors that the chunk used are left in the file and will be reused by later writes.
+	pub fn delete_chunk(&mut self, chunk: ChunkPos) -> Result<bool, NbtError> {
+		let index = chunk.region_index();
+		if self.locations[index] == 0 {
+			return Ok(false);
+		}
+		self.locations[index] = 0;
+		self.timestamps[index] = 0;
+		self.write_header_entry(index)?;
+		let external_path = self.external_path(chunk);
+		if external_path.exists() {
+			std::fs::remove_file(external_path)?;
+		}
+		Ok(true)
+	}
+
+	/// Rewrites the region file with its chunks packed after the header in the order of their positions, which drops
+	/// the sectors left behind by deleted and moved chunks. Returns the number of bytes that the file shrank by.<br>
+	/// The new file is written next to the old one and then renamed over it, so the region is not damaged if this
+	/// fails part of the way through.
+	pub fn compact(&mut self) -> Result<u64, NbtError> {
+		let before = self.file.metadata()?.len();
+		let mut locations = [0; 1024];
+		let mut next = HEADER_SECTORS;
+		for (location, old) in locations.iter_mut().zip(self.locations) {
+			if old != 0 {
+				*location = next << 8 | (old & 0xFF);
+				next += old & 0xFF;
+			}
+		}
+		let temporary = self.path.with_extension("mca.tmp");
+		let mut writer = std::io::BufWriter::new(File::create(&temporary)?);
+		for location in locations.iter().chain(&self.timestamps) {
+			writer.write_all(&location.to_be_bytes())?;
+		}
+		for old in self.locations {
+			if old == 0 {
+				continue;
+			}
+			self.file.seek(SeekFrom::Start((old >> 8) as u64 * SECTOR_SIZE as u64))?;
+			let copied = std::io::copy(&mut (&mut self.file).take((old & 0xFF) as u64 * SECTOR_SIZE as u64), &mut writer)?;
+			// The last sector of a file may be cut short.
+			std::io::copy(&mut std::io::repeat(0).take((old & 0xFF) as u64 * SECTOR_SIZE as u64 - copied), &mut writer)?;
+		}
+		writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+		std::fs::rename(&temporary, &self.path)?;
+		self.file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+		self.locations = locations;
+		Ok(before.saturating_sub(self.file.metadata()?.len()))
+	}
+
+	/// Finds a place for `sectors` sectors for the chunk at `index`, returning the sector offset.
+	/// The chunk's current sectors are reused if they are large enough, otherwise the first
+	/// large enough gap is used, or the sectors are appended to the end of the file.
+	fn allocate(&self, index: usize, sectors: u32) -> u32 {
+		let current = self.locations[index];
+		if current != 0 && current & 0xFF >= sectors {
+			return current >> 8;
+		}
+		let mut used: Vec<(u32, u32)> = self.locations.iter()
+			.enumerate()
+			.filter(|&(other, &location)| other != index && location != 0)
+			.map(|(_, &location)| (location >> 8, (location >> 8) + (location & 0xFF)))
+			.collect();
+		used.sort_unstable();
+		let mut start = HEADER_SECTORS;
+		for (used_start, used_end) in used {
+			if used_start >= start + sectors {
+				return start;
+			}
+			start = start.max(used_end);
+		}
+		// Nothing is stored past `start`, so the chunk goes at the end.
+		start
+	}
+
+	fn write_header_entry(&mut self, index: usize) -> Result<(), NbtError> {
+		self.file.seek(SeekFrom::Start(index as u64 * 4))?;
+		self.file.write_all(&self.locations[index].to_be_bytes())?;
+		self.file.seek(SeekFrom::Start((SECTOR_SIZE + index * 4) as u64))?;
+		self.file.write_all(&self.timestamps[index].to_be_bytes())?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, tag::Tag, world::tests::temp_world };
+
+	fn chunk(pos: ChunkPos, filler: usize) -> NamedTag {
+		NamedTag::new(compound!(
+			("xPos", pos.x),
+			("zPos", pos.z),
+			("Status", "minecraft:full"),
+			("Filler", Tag::LongArray((0..filler as i64).map(|i| i.wrapping_mul(0x9E3779B97F4A7C15u64 as i64)).collect()))
+		))
+	}
+
+	#[test]
+	fn region_test() -> Result<(), NbtError> {
+		let dir = temp_world("region");
+		let first = ChunkPos::new(-1, 0);
+		let second = ChunkPos::new(-29, 4);
+		let missing = ChunkPos::new(-27, 5);
+		let mut region = Region::create(dir.join("r.-1.0.mca"))?;
+		assert_eq!(region.position(), RegionPos::new(-1, 0));
+		assert!(region.read_chunk(first)?.is_none());
+		region.write_chunk(first, &chunk(first, 16))?;
+		region.write_chunk(second, &chunk(second, 2000))?;
+		// Grow the first chunk so that it needs to be moved.
+		region.write_chunk(first, &chunk(first, 4000))?;
+		drop(region);
+
+		let mut region = Region::open(dir.join("r.-1.0.mca"))?;
+		assert_eq!(region.chunks().collect::<Vec<_>>(), [first, second]);
+		let read = region.read_chunk(first)?.expect("Chunk is missing.");
+		assert!(matches!(read.tag(), Tag::Compound(map) if matches!(map.get("Filler"), Some(Tag::LongArray(filler)) if filler.len() == 4000)));
+		assert!(region.read_chunk(second)?.is_some());
+
+		let mut dest = Region::create(dir.join("r.-1.0.copy.mca"))?;
+		assert!(region.copy_chunk_raw(second, &mut dest)?);
+		assert!(!region.copy_chunk_raw(missing, &mut dest)?);
+		assert_eq!(region.read_raw_chunk(second)?, dest.read_raw_chunk(second)?);
+
+		assert_eq!(region.timestamp(missing), None);
+		assert_eq!(region.timestamp(second), dest.timestamp(second));
+		assert!(region.set_timestamp(second, 100)?);
+		assert!(region.set_timestamp(first, 200)?);
+		assert!(!region.set_timestamp(missing, 300)?);
+		assert_eq!(region.chunks_modified_since(150).collect::<Vec<_>>(), [first]);
+		assert_eq!(region.chunks_modified_since(0).count(), 2);
+		drop(region);
+		let mut region = Region::open(dir.join("r.-1.0.mca"))?;
+		assert_eq!(region.timestamp(second), Some(100));
+		assert!(region.file_modified().is_ok());
+
+		assert!(region.delete_chunk(second)?);
+		assert!(!region.contains_chunk(second));
+		// The sectors of the deleted chunk and of the first chunk before it grew are dropped.
+		let raw = region.read_raw_chunk(first)?;
+		assert!(region.compact()? > 0);
+		assert_eq!(region.timestamp(first), Some(200));
+		assert_eq!(region.read_raw_chunk(first)?, raw);
+		let sectors = HEADER_SECTORS + (region.locations[first.region_index()] & 0xFF);
+		assert_eq!(std::fs::metadata(dir.join("r.-1.0.mca"))?.len(), sectors as u64 * SECTOR_SIZE as u64);
+		drop(region);
+		assert!(Region::open(dir.join("r.-1.0.mca"))?.read_chunk(first)?.is_some());
+		std::fs::remove_dir_all(dir)?;
+		Ok(())
+	}
+
+	#[test]
+	fn external_test() -> Result<(), NbtError> {
+		let dir = temp_world("region-external");
+		let mut region = Region::create(dir.join("r.1.1.mca"))?;
+		let data: Vec<u8> = (0..MAX_CHUNK_SECTORS * SECTOR_SIZE + 1).map(|i| i as u8).collect();
+		let raw = RawChunk { compression: ChunkCompression::Uncompressed as u8, data };
+		let pos = ChunkPos::new(34, 35);
+		region.write_raw_chunk(pos, &raw, 1234)?;
+		assert!(dir.join("c.34.35.mcc").exists());
+		assert_eq!(region.read_raw_chunk(pos)?.as_ref(), Some(&raw));
+		region.delete_chunk(pos)?;
+		assert!(!dir.join("c.34.35.mcc").exists());
+		std::fs::remove_dir_all(dir)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,438 @@
+#![doc = r#"
+Schemas that describe the expected shape of tags.
+
+A [Schema] describes the type of a tag and, for Compounds, the fields it has and their default values.
+[Tag::prune_defaults] removes every field whose value equals its default, which keeps item NBT small for
+network packet limits, and [Tag::expand_defaults] inserts the missing defaults back.
+
+```
+# use rustnbt::{ compound, tag::{ Tag, TagID }, schema::Schema };
+let item = Schema::compound()
+	.field("id", Schema::Type(TagID::String))
+	.field_with_default("Count", Schema::Type(TagID::Byte), Tag::Byte(1))
+	.field_with_default("Damage", Schema::Type(TagID::Int), Tag::Int(0));
+let mut tag = compound!(("id", "minecraft:stone"), ("Count", Tag::Byte(1)), ("Damage", 3));
+tag.prune_defaults(&item);
+assert_eq!(tag, compound!(("id", "minecraft:stone"), ("Damage", 3)));
+tag.expand_defaults(&item);
+assert_eq!(tag, compound!(("id", "minecraft:stone"), ("Count", Tag::Byte(1)), ("Damage", 3)));
+```
+
+With the `json` feature, [Schema::to_json_schema] exports a schema as [JSON Schema](https://json-schema.org) for the
+JSON form of the data (as written by the [serde](crate::serde) implementation of [Tag]), so other tools can validate it.
+Every type is also recorded in an `x-nbt-type` keyword, which [Schema::from_json_schema] reads back, so a schema survives
+a round trip. [Schema::from_json_schema] also imports a subset of schemas written by hand: the `type`, `items`,
+`properties`, `default`, `minimum` and `maximum` keywords are understood, and other keywords are ignored.
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+	},
+};
+use std::collections::BTreeMap;
+
+/// The expected shape of a tag.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Schema {
+	/// Any tag.
+	Any,
+	/// A tag of this type, with nothing known about its contents.
+	Type(TagID),
+	/// A List whose elements match the schema.
+	List(Box<Schema>),
+	/// A Compound with these fields. Other keys are allowed and left alone.
+	Compound(BTreeMap<String, Field>),
+}
+
+/// A field of a Compound [Schema].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Field {
+	pub schema: Schema,
+	/// The value that the field has when it is absent.
+	pub default: Option<Tag>,
+}
+
+impl Schema {
+	/// A Compound schema without fields.
+	pub fn compound() -> Self {
+		Schema::Compound(BTreeMap::new())
+	}
+
+	/// A List schema.
+	pub fn list(element: Schema) -> Self {
+		Schema::List(Box::new(element))
+	}
+
+	/// Adds a field without a default. A schema that is not a Compound becomes one.
+	pub fn field<S: Into<String>>(self, key: S, schema: Schema) -> Self {
+		self.with_field(key.into(), Field { schema, default: None })
+	}
+
+	/// Adds a field with a default value. A schema that is not a Compound becomes one.
+	pub fn field_with_default<S: Into<String>>(self, key: S, schema: Schema, default: Tag) -> Self {
+		self.with_field(key.into(), Field { schema, default: Some(default) })
+	}
+
+	fn with_field(self, key: String, field: Field) -> Self {
+		let mut fields = match self {
+			Schema::Compound(fields) => fields,
+			_ => BTreeMap::new(),
+		};
+		fields.insert(key, field);
+		Schema::Compound(fields)
+	}
+}
+
+/// The keyword that records the NBT type of a value in an exported JSON Schema.
+#[cfg(feature = "json")]
+pub const NBT_TYPE_KEYWORD: &str = "x-nbt-type";
+
+#[cfg(feature = "json")]
+impl Schema {
+	/// Exports the schema as a JSON Schema (draft 2020-12) document. See the [module](self) documentation.
+	pub fn to_json_schema(&self) -> serde_json::Value {
+		let mut schema = self.json_schema();
+		if let serde_json::Value::Object(object) = &mut schema {
+			object.insert("$schema".to_owned(), "https://json-schema.org/draft/2020-12/schema".into());
+		}
+		schema
+	}
+
+	fn json_schema(&self) -> serde_json::Value {
+		use serde_json::json;
+		let typed = |id: TagID, schema: serde_json::Value| {
+			let mut schema = schema;
+			schema[NBT_TYPE_KEYWORD] = id.title().into();
+			schema
+		};
+		let integer = |min: i64, max: i64| json!({ "type": "integer", "minimum": min, "maximum": max });
+		match self {
+			Schema::Any => json!({}),
+			Schema::Type(id) => typed(*id, match id {
+				TagID::Byte => integer(i8::MIN as i64, i8::MAX as i64),
+				TagID::Short => integer(i16::MIN as i64, i16::MAX as i64),
+				TagID::Int => integer(i32::MIN as i64, i32::MAX as i64),
+				TagID::Long => integer(i64::MIN, i64::MAX),
+				TagID::Float | TagID::Double => json!({ "type": "number" }),
+				TagID::String => json!({ "type": "string" }),
+				// ByteArrays are written as bytes, which JSON writes as unsigned numbers.
+				TagID::ByteArray => json!({ "type": "array", "items": integer(0, u8::MAX as i64) }),
+				TagID::IntArray => json!({ "type": "array", "items": integer(i32::MIN as i64, i32::MAX as i64) }),
+				TagID::LongArray => json!({ "type": "array", "items": integer(i64::MIN, i64::MAX) }),
+				TagID::List => json!({ "type": "array" }),
+				TagID::Compound => json!({ "type": "object" }),
+			}),
+			Schema::List(element) => typed(TagID::List, json!({ "type": "array", "items": element.json_schema() })),
+			Schema::Compound(fields) => {
+				let properties: serde_json::Map<String, serde_json::Value> = fields.iter().map(|(key, field)| {
+					let mut schema = field.schema.json_schema();
+					if let (Some(default), serde_json::Value::Object(object)) = (&field.default, &mut schema) {
+						object.insert("default".to_owned(), tag_to_json(default));
+					}
+					(key.clone(), schema)
+				}).collect();
+				typed(TagID::Compound, json!({ "type": "object", "properties": properties }))
+			}
+		}
+	}
+
+	/// Imports a JSON Schema. See the [module](self) documentation for what is understood.<br>
+	/// Without an `x-nbt-type`, integers become the smallest type that holds their `minimum` and `maximum`
+	/// (Long if they have none), numbers become Doubles, and booleans become Bytes.
+	pub fn from_json_schema(schema: &serde_json::Value) -> Result<Self, NbtError> {
+		use serde_json::Value;
+		let object = match schema {
+			Value::Bool(true) => return Ok(Schema::Any),
+			Value::Object(object) => object,
+			_ => return Err(invalid_data("JSON Schema must be an object or true.")),
+		};
+		let nbt_type = match object.get(NBT_TYPE_KEYWORD) {
+			Some(Value::String(title)) => Some(tag_id_from_title(title).ok_or_else(|| invalid_data(&format!("Unknown NBT type: {title}")))?),
+			Some(_) => return Err(invalid_data("x-nbt-type must be a string.")),
+			None => None,
+		};
+		let json_type = object.get("type").and_then(Value::as_str);
+		let id = match (nbt_type, json_type) {
+			(Some(id), _) => id,
+			(None, None) => return Ok(Schema::Any),
+			(None, Some("integer")) => {
+				let bound = |key: &str| object.get(key).and_then(Value::as_i64);
+				let (min, max) = (bound("minimum").unwrap_or(i64::MIN), bound("maximum").unwrap_or(i64::MAX));
+				if min >= i8::MIN as i64 && max <= i8::MAX as i64 {
+					TagID::Byte
+				} else if min >= i16::MIN as i64 && max <= i16::MAX as i64 {
+					TagID::Short
+				} else if min >= i32::MIN as i64 && max <= i32::MAX as i64 {
+					TagID::Int
+				} else {
+					TagID::Long
+				}
+			}
+			(None, Some("number")) => TagID::Double,
+			(None, Some("string")) => TagID::String,
+			(None, Some("boolean")) => TagID::Byte,
+			(None, Some("array")) => TagID::List,
+			(None, Some("object")) => TagID::Compound,
+			(None, Some(other)) => return Err(invalid_data(&format!("Unsupported JSON Schema type: {other}"))),
+		};
+		match (id, object.get("items"), object.get("properties")) {
+			(TagID::List, Some(items), _) => Ok(Schema::list(Schema::from_json_schema(items)?)),
+			(TagID::Compound, _, Some(Value::Object(properties))) => {
+				let mut fields = BTreeMap::new();
+				for (key, property) in properties {
+					let schema = Schema::from_json_schema(property)?;
+					let default = match property.get("default") {
+						Some(default) => Some(tag_from_json(default, &schema).ok_or_else(|| invalid_data(&format!("Default of {key} does not match its schema.")))?),
+						None => None,
+					};
+					fields.insert(key.clone(), Field { schema, default });
+				}
+				Ok(Schema::Compound(fields))
+			}
+			(id, _, _) => Ok(Schema::Type(id)),
+		}
+	}
+}
+
+#[cfg(feature = "json")]
+fn tag_id_from_title(title: &str) -> Option<TagID> {
+	(1..=12).filter_map(|id| TagID::try_from(id).ok()).find(|id| id.title() == title)
+}
+
+/// Converts a tag to JSON the same way as its serde implementation.
+#[cfg(feature = "json")]
+fn tag_to_json(tag: &Tag) -> serde_json::Value {
+	use serde_json::Value;
+	let float = |value: f64| serde_json::Number::from_f64(value).map(Value::Number).unwrap_or(Value::Null);
+	match tag {
+		Tag::Byte(value) => (*value).into(),
+		Tag::Short(value) => (*value).into(),
+		Tag::Int(value) => (*value).into(),
+		Tag::Long(value) => (*value).into(),
+		Tag::Float(value) => float(*value as f64),
+		Tag::Double(value) => float(*value),
+		Tag::ByteArray(values) => values.iter().map(|value| *value as u8).collect(),
+		Tag::String(value) => value.as_str().into(),
+		Tag::List(list) => Vec::<Tag>::from(list.clone()).iter().map(tag_to_json).collect(),
+		Tag::Compound(map) => Value::Object(map.iter().map(|(key, value)| (key.clone(), tag_to_json(value))).collect()),
+		Tag::IntArray(values) => values.iter().copied().collect(),
+		Tag::LongArray(values) => values.iter().copied().collect(),
+	}
+}
+
+/// Converts JSON to a tag of the type the schema expects. Values that the schema does not know the type of are
+/// given the type [Schema::from_json_schema] would give them.
+#[cfg(feature = "json")]
+fn tag_from_json(value: &serde_json::Value, schema: &Schema) -> Option<Tag> {
+	use serde_json::Value;
+	let inferred;
+	let schema = match (schema, value) {
+		(Schema::Any, _) | (Schema::Type(TagID::List), Value::Array(_)) | (Schema::Type(TagID::Compound), Value::Object(_)) => {
+			inferred = match value {
+				Value::Bool(_) => Schema::Type(TagID::Byte),
+				Value::Number(number) if number.is_i64() || number.is_u64() => Schema::Type(TagID::Long),
+				Value::Number(_) => Schema::Type(TagID::Double),
+				Value::String(_) => Schema::Type(TagID::String),
+				Value::Array(_) => Schema::list(Schema::Any),
+				Value::Object(_) => Schema::compound(),
+				Value::Null => return None,
+			};
+			&inferred
+		}
+		(schema, _) => schema,
+	};
+	let integer = |value: &Value| value.as_i64().or_else(|| value.as_bool().map(i64::from));
+	let array = |value: &Value| value.as_array().cloned();
+	Some(match schema {
+		Schema::Type(TagID::Byte) => Tag::Byte(integer(value)?.try_into().ok()?),
+		Schema::Type(TagID::Short) => Tag::Short(integer(value)?.try_into().ok()?),
+		Schema::Type(TagID::Int) => Tag::Int(integer(value)?.try_into().ok()?),
+		Schema::Type(TagID::Long) => Tag::Long(integer(value)?),
+		Schema::Type(TagID::Float) => Tag::Float(value.as_f64()? as f32),
+		Schema::Type(TagID::Double) => Tag::Double(value.as_f64()?),
+		Schema::Type(TagID::String) => Tag::String(value.as_str()?.to_owned()),
+		Schema::Type(TagID::ByteArray) => Tag::ByteArray(array(value)?.iter().map(|value| Some(u8::try_from(value.as_u64()?).ok()? as i8)).collect::<Option<_>>()?),
+		Schema::Type(TagID::IntArray) => Tag::IntArray(array(value)?.iter().map(|value| value.as_i64()?.try_into().ok()).collect::<Option<_>>()?),
+		Schema::Type(TagID::LongArray) => Tag::LongArray(array(value)?.iter().map(Value::as_i64).collect::<Option<_>>()?),
+		Schema::List(element) => {
+			let elements: Vec<Tag> = array(value)?.iter().map(|value| tag_from_json(value, element)).collect::<Option<_>>()?;
+			Tag::List(ListTag::try_from(elements).ok()?)
+		}
+		Schema::Compound(fields) => {
+			let mut map = Map::default();
+			for (key, value) in value.as_object()? {
+				let schema = fields.get(key).map(|field| &field.schema).unwrap_or(&Schema::Any);
+				map.insert(key.clone(), tag_from_json(value, schema)?);
+			}
+			Tag::Compound(map)
+		}
+		Schema::Any | Schema::Type(TagID::List | TagID::Compound) => return None,
+	})
+}
+
+impl Tag {
+	/// Removes Compound entries whose values equal their default in the schema, at any depth.<br>
+	/// Nested values are pruned first, so a Compound that only held defaults is removed if its own default is empty.
+	pub fn prune_defaults(&mut self, schema: &Schema) {
+		match (schema, self) {
+			(Schema::Compound(fields), Tag::Compound(map)) => prune_map(fields, map),
+			(Schema::List(element), Tag::List(list)) => prune_list(element, list),
+			_ => {}
+		}
+	}
+
+	/// Inserts the defaults of missing Compound entries from the schema, at any depth.
+	/// This reverses [prune_defaults](Tag::prune_defaults).
+	pub fn expand_defaults(&mut self, schema: &Schema) {
+		match (schema, self) {
+			(Schema::Compound(fields), Tag::Compound(map)) => expand_map(fields, map),
+			(Schema::List(element), Tag::List(list)) => expand_list(element, list),
+			_ => {}
+		}
+	}
+}
+
+fn prune_map(fields: &BTreeMap<String, Field>, map: &mut Map) {
+	for (key, value) in map.iter_mut() {
+		if let Some(field) = fields.get(key) {
+			value.prune_defaults(&field.schema);
+		}
+	}
+	map.retain(|key, value| {
+		let Some(Field { schema, default: Some(default) }) = fields.get(key) else {
+			return true;
+		};
+		// Defaults are compared in their pruned form, since the value has been pruned.
+		match default {
+			Tag::Compound(_) | Tag::List(_) => {
+				let mut default = default.clone();
+				default.prune_defaults(schema);
+				*value != default
+			}
+			default => value != default,
+		}
+	});
+}
+
+fn prune_list(element: &Schema, list: &mut ListTag) {
+	match (element, list) {
+		(Schema::Compound(fields), ListTag::Compound(maps)) => maps.iter_mut().for_each(|map| prune_map(fields, map)),
+		(Schema::List(element), ListTag::List(lists)) => lists.iter_mut().for_each(|list| prune_list(element, list)),
+		_ => {}
+	}
+}
+
+fn expand_map(fields: &BTreeMap<String, Field>, map: &mut Map) {
+	for (key, field) in fields {
+		match map.get_mut(key) {
+			Some(value) => value.expand_defaults(&field.schema),
+			None => if let Some(default) = &field.default {
+				let mut default = default.clone();
+				default.expand_defaults(&field.schema);
+				map.insert(key.clone(), default);
+			}
+		}
+	}
+}
+
+fn expand_list(element: &Schema, list: &mut ListTag) {
+	match (element, list) {
+		(Schema::Compound(fields), ListTag::Compound(maps)) => maps.iter_mut().for_each(|map| expand_map(fields, map)),
+		(Schema::List(element), ListTag::List(lists)) => lists.iter_mut().for_each(|list| expand_list(element, list)),
+		_ => {}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, snbt };
+
+	#[test]
+	fn defaults_test() {
+		let display = Schema::compound()
+			.field_with_default("Name", Schema::Type(TagID::String), Tag::from(""))
+			.field_with_default("Color", Schema::Type(TagID::Int), Tag::Int(0xA06540));
+		let item = Schema::compound()
+			.field("id", Schema::Type(TagID::String))
+			.field_with_default("Count", Schema::Type(TagID::Byte), Tag::Byte(1))
+			.field_with_default("display", display, Tag::Compound(Map::default()));
+		let inventory = Schema::compound().field("Items", Schema::list(item));
+
+		let slot = |id: &str, count: i8, display: Tag| Map::from_iter([
+			("id".to_owned(), Tag::from(id)),
+			("Count".to_owned(), Tag::Byte(count)),
+			("display".to_owned(), display),
+		]);
+		let full = compound!(("Items", Tag::List(ListTag::Compound(vec![
+			slot("minecraft:stone", 1, compound!(("Name", ""), ("Color", 0xA06540))),
+			slot("minecraft:dirt", 5, compound!(("Name", "Soil"), ("Color", 0xA06540))),
+		]))), ("Extra", Tag::Byte(1)));
+		let mut pruned = full.clone();
+		pruned.prune_defaults(&inventory);
+		let stone = Map::from_iter([("id".to_owned(), Tag::from("minecraft:stone"))]);
+		let dirt = Map::from_iter([
+			("id".to_owned(), Tag::from("minecraft:dirt")),
+			("Count".to_owned(), Tag::Byte(5)),
+			("display".to_owned(), compound!(("Name", "Soil"))),
+		]);
+		assert_eq!(pruned, compound!(("Items", Tag::List(ListTag::Compound(vec![stone, dirt]))), ("Extra", Tag::Byte(1))));
+
+		let mut expanded = pruned;
+		expanded.expand_defaults(&inventory);
+		assert_eq!(snbt::to_string_canonical(&expanded), snbt::to_string_canonical(&full));
+	}
+
+	#[cfg(feature = "json")]
+	#[test]
+	fn json_schema_test() -> Result<(), NbtError> {
+		use serde_json::json;
+		let item = Schema::compound()
+			.field("id", Schema::Type(TagID::String))
+			.field_with_default("Count", Schema::Type(TagID::Byte), Tag::Byte(1))
+			.field_with_default("Pos", Schema::list(Schema::Type(TagID::Double)), crate::list!(0.5, 64.0))
+			.field("UUID", Schema::Type(TagID::IntArray))
+			.field("tag", Schema::Any);
+		let exported = item.to_json_schema();
+		assert_eq!(exported["properties"]["Count"], json!({ "type": "integer", "minimum": -128, "maximum": 127, "x-nbt-type": "Byte", "default": 1 }));
+		assert_eq!(exported["properties"]["Pos"]["default"], json!([0.5, 64.0]));
+		assert_eq!(exported["properties"]["tag"], json!({}));
+		assert_eq!(exported["$schema"], "https://json-schema.org/draft/2020-12/schema");
+		assert_eq!(Schema::from_json_schema(&exported)?, item);
+		for schema in [Schema::compound(), Schema::Type(TagID::Compound), Schema::Type(TagID::List), Schema::Type(TagID::ByteArray)] {
+			assert_eq!(Schema::from_json_schema(&schema.to_json_schema())?, schema);
+		}
+
+		let written = json!({
+			"type": "object",
+			"properties": {
+				"name": { "type": "string", "default": "Steve" },
+				"level": { "type": "integer", "minimum": 0, "maximum": 1000 },
+				"score": { "type": "integer", "default": 3 },
+				"flying": { "type": "boolean", "default": false },
+				"tags": { "type": "array", "items": { "type": "string" } },
+				"extra": { "type": "object", "default": { "a": 1.5 } },
+				"anything": true,
+			},
+		});
+		let imported = Schema::from_json_schema(&written)?;
+		assert_eq!(imported, Schema::compound()
+			.field_with_default("name", Schema::Type(TagID::String), Tag::from("Steve"))
+			.field("level", Schema::Type(TagID::Short))
+			.field_with_default("score", Schema::Type(TagID::Long), Tag::Long(3))
+			.field_with_default("flying", Schema::Type(TagID::Byte), Tag::Byte(0))
+			.field("tags", Schema::list(Schema::Type(TagID::String)))
+			.field_with_default("extra", Schema::Type(TagID::Compound), compound!(("a", 1.5)))
+			.field("anything", Schema::Any));
+		assert!(Schema::from_json_schema(&json!({ "type": "null" })).is_err());
+		let mismatched = json!({ "type": "object", "properties": { "level": { "type": "integer", "default": "a" } } });
+		assert!(Schema::from_json_schema(&mismatched).is_err());
+		Ok(())
+	}
+}
@@ -0,0 +1,2213 @@
+#![doc = r#"
+[serde](https://serde.rs) support, enabled with the `serde` feature.
+
+Any type that implements `Serialize` can be converted into a [Tag] with [to_tag], and a [Tag]
+can be converted into any type that implements `Deserialize` with [from_tag].
+[Tag] and [ListTag] implement both traits themselves, so they can be used as fields that hold arbitrary NBT.
+
+Rust types are mapped to NBT like this:
+
+| Rust | NBT |
+|------|-----|
+| `bool` | Byte (`0` or `1`) |
+| `i8`, `i16`, `i32`, `i64` | Byte, Short, Int, Long |
+| `u8`, `u16`, `u32`, `u64` | Byte, Short, Int, Long (the bits are reinterpreted) |
+| `f32`, `f64` | Float, Double |
+| `char`, `String` | String |
+| byte buffers | ByteArray |
+| sequences and tuples | List (all elements must have the same type) |
+| maps and structs | Compound (map keys must be strings or integers) |
+| `None` | The key is left out of the compound |
+| `()` and unit structs | Empty Compound |
+| unit variants | String holding the variant name |
+| other variants | Compound with the variant name as the only key |
+
+Keys that a struct does not know about can be kept in a [Map] with `#[serde(flatten)]`,
+so that data from newer versions of the game or from mods survives a round trip:
+
+```
+# use rustnbt::{ Map, compound, tag::Tag };
+# use serde::{ Serialize, Deserialize };
+#[derive(Serialize, Deserialize)]
+struct Item {
+	id: String,
+	#[serde(rename = "Count")]
+	count: i8,
+	#[serde(flatten)]
+	other: Map,
+}
+
+let tag = compound!(
+	("id", "minecraft:diamond_sword"),
+	("Count", Tag::Byte(1)),
+	("tag", compound!(("Damage", 12)))
+);
+let item: Item = rustnbt::serde::from_tag(tag.clone()).unwrap();
+assert!(item.other.contains_key("tag"));
+assert_eq!(rustnbt::serde::to_tag(&item).unwrap(), tag);
+```
+
+Missing keys deserialize to `None` for `Option` fields, and to their default for fields with `#[serde(default)]`.
+
+## Untagged enums and flattened fields
+
+Untagged enums, internally tagged enums, and flattened fields are supported, but serde buffers
+their values through `deserialize_any` first, which only keeps the NBT type of each value:
+- Numbers keep their exact type, so within these, `bool` and unsigned fields can't be read from
+  Byte, Short, Int, or Long tags. Use signed fields of the same size instead.
+- IntArray and LongArray are reported as a map with a single reserved key, so that [Tag] and [Map]
+  fields restore them as arrays instead of lists. They can't be read into a plain `Vec` within these,
+  but fields using [int_array] or [long_array] accept them.
+- ByteArray is reported as bytes.
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	io::DEFAULT_MAX_DEPTH,
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+	},
+};
+use ::serde::{
+	de::{
+		self,
+		IntoDeserializer,
+		value::MapDeserializer,
+	},
+	ser::{
+		self,
+		Impossible,
+	},
+	Deserialize,
+	Serialize,
+};
+use std::fmt::Display;
+
+// Serde has no notion of typed arrays, so IntArray and LongArray are passed through serde
+// as newtype structs (when serializing) or single entry maps (when deserializing) with these names.
+// Formats that don't know about these names see a plain sequence or map.
+const INT_ARRAY_TOKEN: &str = "__rustnbt_int_array";
+const LONG_ARRAY_TOKEN: &str = "__rustnbt_long_array";
+
+/// Converts a value into a [Tag].
+pub fn to_tag<T: Serialize + ?Sized>(value: &T) -> Result<Tag, NbtError> {
+	value.serialize(Serializer)?.ok_or_else(|| ser::Error::custom("None can not be converted to a tag."))
+}
+
+/// Converts a [Tag] into a value.<br>
+/// To deserialize straight from NBT bytes and borrow strings from them, use [from_bytes].
+pub fn from_tag<T: de::DeserializeOwned>(tag: Tag) -> Result<T, NbtError> {
+	T::deserialize(Deserializer::new(tag))
+}
+
+impl ser::Error for NbtError {
+	fn custom<T: Display>(msg: T) -> Self {
+		NbtError::Message(msg.to_string())
+	}
+}
+
+impl de::Error for NbtError {
+	fn custom<T: Display>(msg: T) -> Self {
+		NbtError::Message(msg.to_string())
+	}
+}
+
+/// Applies `$action` to the vector inside of every [ListTag] variant, along with the [Tag] constructor for its elements.
+macro_rules! list_variants {
+	($list:expr, $empty:expr, |$values:ident, $tag:ident| $action:expr) => {
+		match $list {
+			ListTag::Empty => $empty,
+			ListTag::Byte($values) => { let $tag = Tag::Byte; $action }
+			ListTag::Short($values) => { let $tag = Tag::Short; $action }
+			ListTag::Int($values) => { let $tag = Tag::Int; $action }
+			ListTag::Long($values) => { let $tag = Tag::Long; $action }
+			ListTag::Float($values) => { let $tag = Tag::Float; $action }
+			ListTag::Double($values) => { let $tag = Tag::Double; $action }
+			ListTag::ByteArray($values) => { let $tag = Tag::ByteArray; $action }
+			ListTag::String($values) => { let $tag = Tag::String; $action }
+			ListTag::List($values) => { let $tag = Tag::List; $action }
+			ListTag::Compound($values) => { let $tag = Tag::Compound; $action }
+			ListTag::IntArray($values) => { let $tag = Tag::IntArray; $action }
+			ListTag::LongArray($values) => { let $tag = Tag::LongArray; $action }
+		}
+	};
+}
+
+impl Serialize for Tag {
+	fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			Tag::Byte(value) => serializer.serialize_i8(*value),
+			Tag::Short(value) => serializer.serialize_i16(*value),
+			Tag::Int(value) => serializer.serialize_i32(*value),
+			Tag::Long(value) => serializer.serialize_i64(*value),
+			Tag::Float(value) => serializer.serialize_f32(*value),
+			Tag::Double(value) => serializer.serialize_f64(*value),
+			Tag::ByteArray(value) => serializer.serialize_bytes(bytemuck::cast_slice(value)),
+			Tag::String(value) => serializer.serialize_str(value),
+			Tag::List(list) => list.serialize(serializer),
+			Tag::Compound(map) => map.serialize(serializer),
+			Tag::IntArray(value) => serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, value),
+			Tag::LongArray(value) => serializer.serialize_newtype_struct(LONG_ARRAY_TOKEN, value),
+		}
+	}
+}
+
+impl Serialize for ListTag {
+	fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		use ser::SerializeSeq;
+		let mut seq = serializer.serialize_seq(Some(self.len()))?;
+		list_variants!(self, (), |values, tag| {
+			for value in values.iter().cloned() {
+				seq.serialize_element(&tag(value))?;
+			}
+		});
+		seq.end()
+	}
+}
+
+impl<'de> Deserialize<'de> for Tag {
+	fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_any(TagVisitor)
+	}
+}
+
+impl<'de> Deserialize<'de> for ListTag {
+	fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		match Tag::deserialize(deserializer)? {
+			Tag::List(list) => Ok(list),
+			other => Err(de::Error::invalid_type(de::Unexpected::Other(other.name()), &"a list")),
+		}
+	}
+}
+
+struct TagVisitor;
+
+impl<'de> de::Visitor<'de> for TagVisitor {
+	type Value = Tag;
+
+	fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+		formatter.write_str("an NBT tag")
+	}
+
+	fn visit_bool<E: de::Error>(self, value: bool) -> Result<Tag, E> {
+		Ok(Tag::from(value))
+	}
+
+	fn visit_i8<E: de::Error>(self, value: i8) -> Result<Tag, E> {
+		Ok(Tag::Byte(value))
+	}
+
+	fn visit_i16<E: de::Error>(self, value: i16) -> Result<Tag, E> {
+		Ok(Tag::Short(value))
+	}
+
+	fn visit_i32<E: de::Error>(self, value: i32) -> Result<Tag, E> {
+		Ok(Tag::Int(value))
+	}
+
+	fn visit_i64<E: de::Error>(self, value: i64) -> Result<Tag, E> {
+		Ok(Tag::Long(value))
+	}
+
+	// Unsigned values go into the smallest signed type that can hold them.
+	fn visit_u8<E: de::Error>(self, value: u8) -> Result<Tag, E> {
+		Ok(Tag::Short(value as i16))
+	}
+
+	fn visit_u16<E: de::Error>(self, value: u16) -> Result<Tag, E> {
+		Ok(Tag::Int(value as i32))
+	}
+
+	fn visit_u32<E: de::Error>(self, value: u32) -> Result<Tag, E> {
+		Ok(Tag::Long(value as i64))
+	}
+
+	fn visit_u64<E: de::Error>(self, value: u64) -> Result<Tag, E> {
+		i64::try_from(value)
+			.map(Tag::Long)
+			.map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+	}
+
+	fn visit_f32<E: de::Error>(self, value: f32) -> Result<Tag, E> {
+		Ok(Tag::Float(value))
+	}
+
+	fn visit_f64<E: de::Error>(self, value: f64) -> Result<Tag, E> {
+		Ok(Tag::Double(value))
+	}
+
+	fn visit_str<E: de::Error>(self, value: &str) -> Result<Tag, E> {
+		Ok(Tag::from(value))
+	}
+
+	fn visit_string<E: de::Error>(self, value: String) -> Result<Tag, E> {
+		Ok(Tag::String(value))
+	}
+
+	fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Tag, E> {
+		Ok(Tag::ByteArray(bytemuck::cast_slice(value).to_vec()))
+	}
+
+	fn visit_unit<E: de::Error>(self) -> Result<Tag, E> {
+		Ok(Tag::Compound(Map::default()))
+	}
+
+	fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Tag, D::Error> {
+		deserializer.deserialize_any(self)
+	}
+
+	fn visit_newtype_struct<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Tag, D::Error> {
+		deserializer.deserialize_any(self)
+	}
+
+	fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Tag, A::Error> {
+		let mut tags = Vec::<Tag>::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+		while let Some(tag) = seq.next_element()? {
+			tags.push(tag);
+		}
+		ListTag::try_from(tags)
+			.map(Tag::List)
+			.map_err(|_| de::Error::custom("List elements must all have the same type."))
+	}
+
+	fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Tag, A::Error> {
+		let mut compound = Map::default();
+		let Some(key) = map.next_key::<String>()? else {
+			return Ok(Tag::Compound(compound));
+		};
+		match key.as_str() {
+			INT_ARRAY_TOKEN => return Ok(Tag::IntArray(map.next_value()?)),
+			LONG_ARRAY_TOKEN => return Ok(Tag::LongArray(map.next_value()?)),
+			_ => {}
+		}
+		compound.insert(key, map.next_value()?);
+		while let Some((key, value)) = map.next_entry()? {
+			compound.insert(key, value);
+		}
+		Ok(Tag::Compound(compound))
+	}
+}
+
+/// Serializer that produces a [Tag]. `None` produces `Ok(None)`, which compounds leave out.
+struct Serializer;
+
+/// Serializer for [Tag::List].
+struct SerializeList {
+	tags: Vec<Tag>,
+}
+
+/// Serializer for [Tag::Compound].
+struct SerializeCompound {
+	map: Map,
+	key: Option<String>,
+}
+
+/// Serializer for enum variants, which are wrapped in a compound with the variant name as the key.
+struct SerializeVariant<S> {
+	variant: &'static str,
+	inner: S,
+}
+
+fn wrap_variant(variant: &'static str, tag: Tag) -> Tag {
+	Tag::Compound(Map::from_iter([(variant.to_owned(), tag)]))
+}
+
+fn list_from_tags(tags: Vec<Tag>) -> Result<Tag, NbtError> {
+	ListTag::try_from(tags)
+		.map(Tag::List)
+		.map_err(|_| ser::Error::custom("List elements must all have the same type."))
+}
+
+fn element(tag: Option<Tag>) -> Result<Tag, NbtError> {
+	tag.ok_or_else(|| ser::Error::custom("None can not be stored in a list."))
+}
+
+impl ser::Serializer for Serializer {
+	type Ok = Option<Tag>;
+	type Error = NbtError;
+	type SerializeSeq = SerializeList;
+	type SerializeTuple = SerializeList;
+	type SerializeTupleStruct = SerializeList;
+	type SerializeTupleVariant = SerializeVariant<SerializeList>;
+	type SerializeMap = SerializeCompound;
+	type SerializeStruct = SerializeCompound;
+	type SerializeStructVariant = SerializeVariant<SerializeCompound>;
+
+	fn serialize_bool(self, v: bool) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::from(v)))
+	}
+
+	fn serialize_i8(self, v: i8) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Byte(v)))
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Short(v)))
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Int(v)))
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Long(v)))
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Byte(v as i8)))
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Short(v as i16)))
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Int(v as i32)))
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Long(v as i64)))
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Float(v)))
+	}
+
+	fn serialize_f64(self, v: f64) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Double(v)))
+	}
+
+	fn serialize_char(self, v: char) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::String(v.to_string())))
+	}
+
+	fn serialize_str(self, v: &str) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::from(v)))
+	}
+
+	fn serialize_bytes(self, v: &[u8]) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::bytes(v.iter().copied())))
+	}
+
+	fn serialize_none(self) -> Result<Option<Tag>, NbtError> {
+		Ok(None)
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Option<Tag>, NbtError> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Compound(Map::default())))
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<Tag>, NbtError> {
+		self.serialize_unit()
+	}
+
+	fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<Option<Tag>, NbtError> {
+		self.serialize_str(variant)
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<Option<Tag>, NbtError> {
+		let tag = value.serialize(self)?;
+		Ok(match (name, tag) {
+			(INT_ARRAY_TOKEN, Some(Tag::List(ListTag::Int(values)))) => Some(Tag::IntArray(values)),
+			(INT_ARRAY_TOKEN, Some(Tag::List(ListTag::Empty))) => Some(Tag::IntArray(Vec::new())),
+			(LONG_ARRAY_TOKEN, Some(Tag::List(ListTag::Long(values)))) => Some(Tag::LongArray(values)),
+			(LONG_ARRAY_TOKEN, Some(Tag::List(ListTag::Empty))) => Some(Tag::LongArray(Vec::new())),
+			(INT_ARRAY_TOKEN | LONG_ARRAY_TOKEN, _) => return Err(ser::Error::custom(format!("{name} must hold a sequence of integers."))),
+			(_, tag) => tag,
+		})
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _variant_index: u32, variant: &'static str, value: &T) -> Result<Option<Tag>, NbtError> {
+		let tag = element(value.serialize(self)?)?;
+		Ok(Some(wrap_variant(variant, tag)))
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, NbtError> {
+		Ok(SerializeList { tags: Vec::with_capacity(len.unwrap_or(0)) })
+	}
+
+	fn serialize_tuple(self, len: usize) -> Result<SerializeList, NbtError> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeList, NbtError> {
+		self.serialize_seq(Some(len))
+	}
+
+	fn serialize_tuple_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeTupleVariant, NbtError> {
+		Ok(SerializeVariant { variant, inner: self.serialize_seq(Some(len))? })
+	}
+
+	fn serialize_map(self, _len: Option<usize>) -> Result<SerializeCompound, NbtError> {
+		Ok(SerializeCompound { map: Map::default(), key: None })
+	}
+
+	fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeCompound, NbtError> {
+		self.serialize_map(Some(len))
+	}
+
+	fn serialize_struct_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeStructVariant, NbtError> {
+		Ok(SerializeVariant { variant, inner: self.serialize_map(Some(len))? })
+	}
+}
+
+impl ser::SerializeSeq for SerializeList {
+	type Ok = Option<Tag>;
+	type Error = NbtError;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+		self.tags.push(element(value.serialize(Serializer)?)?);
+		Ok(())
+	}
+
+	fn end(self) -> Result<Option<Tag>, NbtError> {
+		list_from_tags(self.tags).map(Some)
+	}
+}
+
+impl ser::SerializeTuple for SerializeList {
+	type Ok = Option<Tag>;
+	type Error = NbtError;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Option<Tag>, NbtError> {
+		ser::SerializeSeq::end(self)
+	}
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+	type Ok = Option<Tag>;
+	type Error = NbtError;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+		ser::SerializeSeq::serialize_element(self, value)
+	}
+
+	fn end(self) -> Result<Option<Tag>, NbtError> {
+		ser::SerializeSeq::end(self)
+	}
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeList> {
+	type Ok = Option<Tag>;
+	type Error = NbtError;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+		ser::SerializeSeq::serialize_element(&mut self.inner, value)
+	}
+
+	fn end(self) -> Result<Option<Tag>, NbtError> {
+		let tag = list_from_tags(self.inner.tags)?;
+		Ok(Some(wrap_variant(self.variant, tag)))
+	}
+}
+
+impl ser::SerializeMap for SerializeCompound {
+	type Ok = Option<Tag>;
+	type Error = NbtError;
+
+	fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), NbtError> {
+		self.key = Some(key.serialize(KeySerializer)?);
+		Ok(())
+	}
+
+	fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), NbtError> {
+		let key = self.key.take().ok_or_else(|| NbtError::Message("serialize_value was called before serialize_key.".to_owned()))?;
+		if let Some(tag) = value.serialize(Serializer)? {
+			self.map.insert(key, tag);
+		}
+		Ok(())
+	}
+
+	fn end(self) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Compound(self.map)))
+	}
+}
+
+impl ser::SerializeStruct for SerializeCompound {
+	type Ok = Option<Tag>;
+	type Error = NbtError;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), NbtError> {
+		if let Some(tag) = value.serialize(Serializer)? {
+			self.map.insert(key.to_owned(), tag);
+		}
+		Ok(())
+	}
+
+	fn end(self) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(Tag::Compound(self.map)))
+	}
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeCompound> {
+	type Ok = Option<Tag>;
+	type Error = NbtError;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), NbtError> {
+		ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+	}
+
+	fn end(self) -> Result<Option<Tag>, NbtError> {
+		Ok(Some(wrap_variant(self.variant, Tag::Compound(self.inner.map))))
+	}
+}
+
+/// Serializer for the keys of a compound. Strings, characters, integers, and unit variants are accepted.
+struct KeySerializer;
+
+fn key_error() -> NbtError {
+	ser::Error::custom("Compound keys must be strings.")
+}
+
+macro_rules! key_to_string {
+	($($method:ident($type:ty))+) => {
+		$(
+			fn $method(self, v: $type) -> Result<String, NbtError> {
+				Ok(v.to_string())
+			}
+		)+
+	};
+}
+
+macro_rules! key_unsupported {
+	($($method:ident($($type:ty)?))+) => {
+		$(
+			fn $method(self $(, _: $type)?) -> Result<String, NbtError> {
+				Err(key_error())
+			}
+		)+
+	};
+}
+
+impl ser::Serializer for KeySerializer {
+	type Ok = String;
+	type Error = NbtError;
+	type SerializeSeq = Impossible<String, NbtError>;
+	type SerializeTuple = Impossible<String, NbtError>;
+	type SerializeTupleStruct = Impossible<String, NbtError>;
+	type SerializeTupleVariant = Impossible<String, NbtError>;
+	type SerializeMap = Impossible<String, NbtError>;
+	type SerializeStruct = Impossible<String, NbtError>;
+	type SerializeStructVariant = Impossible<String, NbtError>;
+
+	key_to_string!{
+		serialize_i8(i8)
+		serialize_i16(i16)
+		serialize_i32(i32)
+		serialize_i64(i64)
+		serialize_u8(u8)
+		serialize_u16(u16)
+		serialize_u32(u32)
+		serialize_u64(u64)
+		serialize_char(char)
+		serialize_str(&str)
+	}
+
+	key_unsupported!{
+		serialize_bool(bool)
+		serialize_f32(f32)
+		serialize_f64(f64)
+		serialize_bytes(&[u8])
+		serialize_none()
+		serialize_unit()
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, NbtError> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<String, NbtError> {
+		Err(key_error())
+	}
+
+	fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<String, NbtError> {
+		Ok(variant.to_owned())
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<String, NbtError> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _variant_index: u32, _variant: &'static str, _value: &T) -> Result<String, NbtError> {
+		Err(key_error())
+	}
+
+	fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, NbtError> {
+		Err(key_error())
+	}
+
+	fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, NbtError> {
+		Err(key_error())
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, NbtError> {
+		Err(key_error())
+	}
+
+	fn serialize_tuple_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, NbtError> {
+		Err(key_error())
+	}
+
+	fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, NbtError> {
+		Err(key_error())
+	}
+
+	fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, NbtError> {
+		Err(key_error())
+	}
+
+	fn serialize_struct_variant(self, _name: &'static str, _variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, NbtError> {
+		Err(key_error())
+	}
+}
+
+/// Deserializer that takes its data from an owned [Tag].
+pub struct Deserializer {
+	tag: Tag,
+}
+
+impl Deserializer {
+	pub fn new(tag: Tag) -> Self {
+		Self { tag }
+	}
+}
+
+impl<'de> IntoDeserializer<'de, NbtError> for Tag {
+	type Deserializer = Deserializer;
+
+	fn into_deserializer(self) -> Deserializer {
+		Deserializer::new(self)
+	}
+}
+
+/// Access to the elements of a [ListTag] or an array tag.
+struct SeqAccess<I> {
+	iter: I,
+}
+
+impl<'de, I: ExactSizeIterator<Item = Tag>> de::SeqAccess<'de> for SeqAccess<I> {
+	type Error = NbtError;
+
+	fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, NbtError> {
+		match self.iter.next() {
+			Some(tag) => seed.deserialize(Deserializer::new(tag)).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.iter.len())
+	}
+}
+
+/// Access to the entries of a [Tag::Compound].
+struct MapAccess<I> {
+	iter: I,
+	value: Option<Tag>,
+}
+
+impl<'de, I: Iterator<Item = (String, Tag)>> de::MapAccess<'de> for MapAccess<I> {
+	type Error = NbtError;
+
+	fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, NbtError> {
+		match self.iter.next() {
+			Some((key, value)) => {
+				self.value = Some(value);
+				seed.deserialize(key.into_deserializer()).map(Some)
+			}
+			None => Ok(None),
+		}
+	}
+
+	fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, NbtError> {
+		let value = self.value.take().ok_or_else(|| NbtError::Message("next_value was called before next_key.".to_owned()))?;
+		seed.deserialize(Deserializer::new(value))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		self.iter.size_hint().1
+	}
+}
+
+/// Access to an enum variant stored as a String (unit variants) or a single entry Compound (other variants).
+struct EnumAccess {
+	variant: String,
+	value: Option<Tag>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+	type Error = NbtError;
+	type Variant = Self;
+
+	fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), NbtError> {
+		let variant: de::value::StrDeserializer<NbtError> = self.variant.as_str().into_deserializer();
+		let variant = seed.deserialize(variant)?;
+		Ok((variant, self))
+	}
+}
+
+impl<'de> de::VariantAccess<'de> for EnumAccess {
+	type Error = NbtError;
+
+	fn unit_variant(self) -> Result<(), NbtError> {
+		match self.value {
+			None => Ok(()),
+			Some(value) => Deserialize::deserialize(Deserializer::new(value)),
+		}
+	}
+
+	fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, NbtError> {
+		match self.value {
+			Some(value) => seed.deserialize(Deserializer::new(value)),
+			None => Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"newtype variant")),
+		}
+	}
+
+	fn tuple_variant<V: de::Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, NbtError> {
+		match self.value {
+			Some(value) => de::Deserializer::deserialize_seq(Deserializer::new(value), visitor),
+			None => Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"tuple variant")),
+		}
+	}
+
+	fn struct_variant<V: de::Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, NbtError> {
+		match self.value {
+			Some(value) => de::Deserializer::deserialize_map(Deserializer::new(value), visitor),
+			None => Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"struct variant")),
+		}
+	}
+}
+
+/// Creates a deserialize method that reinterprets the bits of a tag as an unsigned integer,
+/// matching how [to_tag] stores unsigned integers.
+macro_rules! deserialize_unsigned {
+	($($method:ident $visit:ident $title:ident $type:ty;)+) => {
+		$(
+			fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+				match self.tag {
+					Tag::$title(value) => visitor.$visit(value as $type),
+					_ => self.deserialize_any(visitor),
+				}
+			}
+		)+
+	};
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+	type Error = NbtError;
+
+	fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		match self.tag {
+			Tag::Byte(value) => visitor.visit_i8(value),
+			Tag::Short(value) => visitor.visit_i16(value),
+			Tag::Int(value) => visitor.visit_i32(value),
+			Tag::Long(value) => visitor.visit_i64(value),
+			Tag::Float(value) => visitor.visit_f32(value),
+			Tag::Double(value) => visitor.visit_f64(value),
+			Tag::ByteArray(value) => visitor.visit_byte_buf(bytemuck::cast_vec(value)),
+			Tag::String(value) => visitor.visit_string(value),
+			Tag::List(list) => list_variants!(list, visitor.visit_seq(SeqAccess { iter: std::iter::empty() }), |values, tag| {
+				visitor.visit_seq(SeqAccess { iter: values.into_iter().map(tag) })
+			}),
+			Tag::Compound(map) => visitor.visit_map(MapAccess { iter: map.into_iter(), value: None }),
+			Tag::IntArray(values) => visitor.visit_map(MapDeserializer::new(std::iter::once((INT_ARRAY_TOKEN, values)))),
+			Tag::LongArray(values) => visitor.visit_map(MapDeserializer::new(std::iter::once((LONG_ARRAY_TOKEN, values)))),
+		}
+	}
+
+	fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		match self.tag {
+			Tag::Byte(value) => visitor.visit_bool(value != 0),
+			_ => self.deserialize_any(visitor),
+		}
+	}
+
+	deserialize_unsigned!{
+		deserialize_u8 visit_u8 Byte u8;
+		deserialize_u16 visit_u16 Short u16;
+		deserialize_u32 visit_u32 Int u32;
+		deserialize_u64 visit_u64 Long u64;
+	}
+
+	fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		// Missing keys are the only way to represent None.
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		match self.tag {
+			Tag::Compound(map) if map.is_empty() => visitor.visit_unit(),
+			_ => self.deserialize_any(visitor),
+		}
+	}
+
+	fn deserialize_unit_struct<V: de::Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, NbtError> {
+		self.deserialize_unit(visitor)
+	}
+
+	fn deserialize_newtype_struct<V: de::Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, NbtError> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		match self.tag {
+			Tag::ByteArray(values) => visitor.visit_seq(SeqAccess { iter: values.into_iter().map(Tag::Byte) }),
+			Tag::IntArray(values) => visitor.visit_seq(SeqAccess { iter: values.into_iter().map(Tag::Int) }),
+			Tag::LongArray(values) => visitor.visit_seq(SeqAccess { iter: values.into_iter().map(Tag::Long) }),
+			_ => self.deserialize_any(visitor),
+		}
+	}
+
+	fn deserialize_tuple<V: de::Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, NbtError> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_tuple_struct<V: de::Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, NbtError> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		match self.tag {
+			Tag::List(ListTag::Byte(values)) => visitor.visit_byte_buf(bytemuck::cast_vec(values)),
+			_ => self.deserialize_any(visitor),
+		}
+	}
+
+	fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		self.deserialize_bytes(visitor)
+	}
+
+	fn deserialize_enum<V: de::Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, NbtError> {
+		match self.tag {
+			Tag::String(variant) => visitor.visit_enum(EnumAccess { variant, value: None }),
+			Tag::Compound(map) if map.len() == 1 => {
+				let (variant, value) = map.into_iter().next().expect("Compound has one entry.");
+				visitor.visit_enum(EnumAccess { variant, value: Some(value) })
+			}
+			other => Err(de::Error::invalid_type(de::Unexpected::Other(other.name()), &"a string or a compound with one entry")),
+		}
+	}
+
+	fn deserialize_ignored_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		visitor.visit_unit()
+	}
+
+	::serde::forward_to_deserialize_any!{
+		i8 i16 i32 i64 i128 u128 f32 f64 char str string map struct identifier
+	}
+}
+
+/// Deserializes a value straight from an uncompressed NBT buffer holding a named root tag,
+/// without building a [Tag] first.<br>
+/// Strings and byte arrays can be borrowed from the buffer, so `&'de str` and `&'de [u8]` fields
+/// do not allocate. Strings must be valid UTF-8 to be read, and tags may be nested up to
+/// [DEFAULT_MAX_DEPTH] deep, like [read_named_tag](crate::io::read_named_tag).
+/// ```
+/// # use rustnbt::{ compound, io::write_named_tag };
+/// #[derive(serde::Deserialize)]
+/// struct Item<'a> {
+///     id: &'a str,
+/// }
+/// let mut bytes = Vec::new();
+/// write_named_tag(&mut bytes, &compound!(("id", "minecraft:stone")), "").unwrap();
+/// let item: Item = rustnbt::serde::from_bytes(&bytes).unwrap();
+/// assert_eq!(item.id, "minecraft:stone");
+/// ```
+pub fn from_bytes<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T, NbtError> {
+	let mut reader = SliceReader { input: bytes, depth: 0 };
+	let id = reader.read_id()?;
+	reader.read_str()?;
+	T::deserialize(SliceDeserializer { reader: &mut reader, id })
+}
+
+/// Cursor over an NBT buffer that hands out slices that borrow from the buffer.
+struct SliceReader<'de> {
+	input: &'de [u8],
+	/// How many Compounds and Lists the tag being read is in.
+	depth: usize,
+}
+
+impl<'de> SliceReader<'de> {
+	fn take(&mut self, length: usize) -> Result<&'de [u8], NbtError> {
+		if length > self.input.len() {
+			return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+		}
+		let (taken, rest) = self.input.split_at(length);
+		self.input = rest;
+		Ok(taken)
+	}
+
+	fn read<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+		Ok(self.take(N)?.try_into().expect("Slice has the requested length."))
+	}
+
+	fn read_id(&mut self) -> Result<TagID, NbtError> {
+		TagID::try_from(self.read::<1>()?[0])
+	}
+
+	fn read_length(&mut self) -> Result<usize, NbtError> {
+		Ok(u32::from_be_bytes(self.read()?) as usize)
+	}
+
+	fn read_str(&mut self) -> Result<&'de str, NbtError> {
+		let length = u16::from_be_bytes(self.read()?) as usize;
+		std::str::from_utf8(self.take(length)?).map_err(|_| invalid_data("Failed to read UTF-8 string."))
+	}
+
+	/// Reads the element type and length of a list. Empty lists may have the End ID as their element type.
+	fn read_list_header(&mut self) -> Result<(Option<TagID>, usize), NbtError> {
+		let id = TagID::list_element(self.read::<1>()?[0])?;
+		let length = self.read_length()?;
+		if id.is_none() && length != 0 {
+			return Err(crate::invalid_data("List of End tags has elements."));
+		}
+		Ok((id, length))
+	}
+
+	/// Returns `true` and consumes the End ID if it is next.
+	fn at_end(&mut self) -> bool {
+		if self.input.first() == Some(&0) {
+			self.input = &self.input[1..];
+			return true;
+		}
+		false
+	}
+
+	/// The size of a payload with a fixed size, or [None] for variable sized payloads.
+	fn fixed_size(id: TagID) -> Option<usize> {
+		match id {
+			TagID::Byte => Some(1),
+			TagID::Short => Some(2),
+			TagID::Int | TagID::Float => Some(4),
+			TagID::Long | TagID::Double => Some(8),
+			_ => None,
+		}
+	}
+
+	/// Reads a Compound or List with `read`, one level deeper. Tags are read by recursion, so nesting deeper than
+	/// [DEFAULT_MAX_DEPTH] is an error instead of a stack overflow.
+	fn nested<T>(&mut self, read: impl FnOnce(&mut Self) -> Result<T, NbtError>) -> Result<T, NbtError> {
+		if self.depth >= DEFAULT_MAX_DEPTH {
+			return Err(invalid_data(&format!("Tags are nested deeper than {DEFAULT_MAX_DEPTH}.")));
+		}
+		self.depth += 1;
+		let result = read(self);
+		self.depth -= 1;
+		result
+	}
+
+	/// Skips over the payload of a tag.
+	fn skip(&mut self, id: TagID) -> Result<(), NbtError> {
+		if let Some(size) = Self::fixed_size(id) {
+			self.take(size)?;
+			return Ok(());
+		}
+		match id {
+			TagID::ByteArray => {
+				let length = self.read_length()?;
+				self.take(length)?;
+			}
+			TagID::String => {
+				self.read_str()?;
+			}
+			TagID::List => self.nested(|reader| {
+				let (id, length) = reader.read_list_header()?;
+				match id {
+					Some(id) => reader.skip_elements(id, length),
+					None => Ok(()),
+				}
+			})?,
+			TagID::Compound => self.nested(|reader| {
+				while !reader.at_end() {
+					let id = reader.read_id()?;
+					reader.read_str()?;
+					reader.skip(id)?;
+				}
+				Ok(())
+			})?,
+			TagID::IntArray => {
+				let length = self.read_length()?;
+				self.skip_elements(TagID::Int, length)?;
+			}
+			TagID::LongArray => {
+				let length = self.read_length()?;
+				self.skip_elements(TagID::Long, length)?;
+			}
+			_ => unreachable!(),
+		}
+		Ok(())
+	}
+
+	fn skip_elements(&mut self, id: TagID, length: usize) -> Result<(), NbtError> {
+		match Self::fixed_size(id) {
+			Some(size) => {
+				let total = size.checked_mul(length).ok_or_else(|| invalid_data("List is too large."))?;
+				self.take(total)?;
+			}
+			None => {
+				for _ in 0..length {
+					self.skip(id)?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Deserializer for the payload of a tag in an NBT buffer.
+struct SliceDeserializer<'a, 'de> {
+	reader: &'a mut SliceReader<'de>,
+	id: TagID,
+}
+
+/// Deserializer for the elements of an array tag, which are always read as a sequence.
+struct ArrayDeserializer<'a, 'de> {
+	reader: &'a mut SliceReader<'de>,
+	id: TagID,
+	length: usize,
+}
+
+/// Access to the elements of a list or array in an NBT buffer.
+struct SliceSeqAccess<'a, 'de> {
+	reader: &'a mut SliceReader<'de>,
+	id: TagID,
+	remaining: usize,
+}
+
+/// Access to the entries of a compound in an NBT buffer.
+struct SliceMapAccess<'a, 'de> {
+	reader: &'a mut SliceReader<'de>,
+	id: Option<TagID>,
+	done: bool,
+}
+
+/// Access to an IntArray or LongArray in an NBT buffer, presented as a single entry map like [Deserializer] does.
+struct SliceArrayAccess<'a, 'de> {
+	reader: &'a mut SliceReader<'de>,
+	token: Option<&'static str>,
+	id: TagID,
+	length: usize,
+}
+
+/// Access to an enum variant in an NBT buffer.
+struct SliceEnumAccess<'a, 'de> {
+	reader: &'a mut SliceReader<'de>,
+	/// The ID of the variant's value, or [None] for unit variants stored as a String.
+	id: Option<TagID>,
+}
+
+impl<'a, 'de> SliceDeserializer<'a, 'de> {
+	/// Visits a sequence, skipping any elements that the visitor did not read.
+	fn visit_elements<V: de::Visitor<'de>>(reader: &'a mut SliceReader<'de>, id: Option<TagID>, length: usize, visitor: V) -> Result<V::Value, NbtError> {
+		let Some(id) = id else {
+			return visitor.visit_seq(SeqAccess { iter: std::iter::empty() });
+		};
+		let mut access = SliceSeqAccess { reader, id, remaining: length };
+		let value = visitor.visit_seq(&mut access)?;
+		access.reader.skip_elements(id, access.remaining)?;
+		Ok(value)
+	}
+
+	fn visit_array<V: de::Visitor<'de>>(self, token: &'static str, id: TagID, visitor: V) -> Result<V::Value, NbtError> {
+		let length = self.reader.read_length()?;
+		let mut access = SliceArrayAccess { reader: self.reader, token: Some(token), id, length };
+		let value = visitor.visit_map(&mut access)?;
+		if access.token.is_some() {
+			access.reader.skip_elements(id, length)?;
+		}
+		Ok(value)
+	}
+}
+
+macro_rules! slice_deserialize_unsigned {
+	($($method:ident $visit:ident $title:ident $type:ty;)+) => {
+		$(
+			fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+				match self.id {
+					TagID::$title => visitor.$visit(<$type>::from_be_bytes(self.reader.read()?)),
+					_ => self.deserialize_any(visitor),
+				}
+			}
+		)+
+	};
+}
+
+impl<'a, 'de> de::Deserializer<'de> for SliceDeserializer<'a, 'de> {
+	type Error = NbtError;
+
+	fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		let reader = self.reader;
+		match self.id {
+			TagID::Byte => visitor.visit_i8(i8::from_be_bytes(reader.read()?)),
+			TagID::Short => visitor.visit_i16(i16::from_be_bytes(reader.read()?)),
+			TagID::Int => visitor.visit_i32(i32::from_be_bytes(reader.read()?)),
+			TagID::Long => visitor.visit_i64(i64::from_be_bytes(reader.read()?)),
+			TagID::Float => visitor.visit_f32(f32::from_be_bytes(reader.read()?)),
+			TagID::Double => visitor.visit_f64(f64::from_be_bytes(reader.read()?)),
+			TagID::ByteArray => {
+				let length = reader.read_length()?;
+				visitor.visit_borrowed_bytes(reader.take(length)?)
+			}
+			TagID::String => visitor.visit_borrowed_str(reader.read_str()?),
+			TagID::List => reader.nested(|reader| {
+				let (id, length) = reader.read_list_header()?;
+				SliceDeserializer::visit_elements(reader, id, length, visitor)
+			}),
+			TagID::Compound => reader.nested(|reader| {
+				let mut access = SliceMapAccess { reader, id: None, done: false };
+				let value = visitor.visit_map(&mut access)?;
+				access.finish()?;
+				Ok(value)
+			}),
+			TagID::IntArray => SliceDeserializer { reader, id: TagID::IntArray }.visit_array(INT_ARRAY_TOKEN, TagID::Int, visitor),
+			TagID::LongArray => SliceDeserializer { reader, id: TagID::LongArray }.visit_array(LONG_ARRAY_TOKEN, TagID::Long, visitor),
+		}
+	}
+
+	fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		match self.id {
+			TagID::Byte => visitor.visit_bool(self.reader.read::<1>()?[0] != 0),
+			_ => self.deserialize_any(visitor),
+		}
+	}
+
+	slice_deserialize_unsigned!{
+		deserialize_u8 visit_u8 Byte u8;
+		deserialize_u16 visit_u16 Short u16;
+		deserialize_u32 visit_u32 Int u32;
+		deserialize_u64 visit_u64 Long u64;
+	}
+
+	fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		match self.id {
+			TagID::Compound if self.reader.at_end() => visitor.visit_unit(),
+			_ => self.deserialize_any(visitor),
+		}
+	}
+
+	fn deserialize_unit_struct<V: de::Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, NbtError> {
+		self.deserialize_unit(visitor)
+	}
+
+	fn deserialize_newtype_struct<V: de::Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, NbtError> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		let element = match self.id {
+			TagID::ByteArray => TagID::Byte,
+			TagID::IntArray => TagID::Int,
+			TagID::LongArray => TagID::Long,
+			_ => return self.deserialize_any(visitor),
+		};
+		let length = self.reader.read_length()?;
+		Self::visit_elements(self.reader, Some(element), length, visitor)
+	}
+
+	fn deserialize_tuple<V: de::Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, NbtError> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_tuple_struct<V: de::Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, NbtError> {
+		self.deserialize_seq(visitor)
+	}
+
+	fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		if self.id != TagID::List || !self.reader.input.starts_with(&[TagID::Byte as u8]) {
+			return self.deserialize_any(visitor);
+		}
+		// A list of bytes is stored the same way as a byte array after its element type.
+		self.reader.take(1)?;
+		let length = self.reader.read_length()?;
+		visitor.visit_borrowed_bytes(self.reader.take(length)?)
+	}
+
+	fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		self.deserialize_bytes(visitor)
+	}
+
+	fn deserialize_enum<V: de::Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, NbtError> {
+		match self.id {
+			TagID::String => visitor.visit_enum(SliceEnumAccess { reader: self.reader, id: None }),
+			TagID::Compound => self.reader.nested(|reader| {
+				let id = reader.read_id()
+					.map_err(|_| NbtError::Message("Expected a compound with one entry for an enum.".to_owned()))?;
+				visitor.visit_enum(SliceEnumAccess { reader, id: Some(id) })
+			}),
+			other => Err(de::Error::invalid_type(de::Unexpected::Other(other.name()), &"a string or a compound with one entry")),
+		}
+	}
+
+	fn deserialize_ignored_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		self.reader.skip(self.id)?;
+		visitor.visit_unit()
+	}
+
+	::serde::forward_to_deserialize_any!{
+		i8 i16 i32 i64 i128 u128 f32 f64 char str string map struct identifier
+	}
+}
+
+impl<'a, 'de> de::Deserializer<'de> for ArrayDeserializer<'a, 'de> {
+	type Error = NbtError;
+
+	fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, NbtError> {
+		SliceDeserializer::visit_elements(self.reader, Some(self.id), self.length, visitor)
+	}
+
+	::serde::forward_to_deserialize_any!{
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+		unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+	}
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for SliceSeqAccess<'a, 'de> {
+	type Error = NbtError;
+
+	fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, NbtError> {
+		if self.remaining == 0 {
+			return Ok(None);
+		}
+		self.remaining -= 1;
+		seed.deserialize(SliceDeserializer { reader: self.reader, id: self.id }).map(Some)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.remaining)
+	}
+}
+
+impl<'a, 'de> SliceMapAccess<'a, 'de> {
+	/// Skips the entries that the visitor did not read, including the value of a key that was read without its value.
+	fn finish(&mut self) -> Result<(), NbtError> {
+		if let Some(id) = self.id.take() {
+			self.reader.skip(id)?;
+		}
+		if !self.done {
+			self.reader.skip(TagID::Compound)?;
+		}
+		Ok(())
+	}
+}
+
+impl<'a, 'de> de::MapAccess<'de> for SliceMapAccess<'a, 'de> {
+	type Error = NbtError;
+
+	fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, NbtError> {
+		if self.done || self.reader.at_end() {
+			self.done = true;
+			return Ok(None);
+		}
+		let id = self.reader.read_id()?;
+		let name = self.reader.read_str()?;
+		self.id = Some(id);
+		seed.deserialize(de::value::BorrowedStrDeserializer::<NbtError>::new(name)).map(Some)
+	}
+
+	fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, NbtError> {
+		let id = self.id.take().ok_or_else(|| NbtError::Message("next_value was called before next_key.".to_owned()))?;
+		seed.deserialize(SliceDeserializer { reader: self.reader, id })
+	}
+}
+
+impl<'a, 'de> de::MapAccess<'de> for SliceArrayAccess<'a, 'de> {
+	type Error = NbtError;
+
+	fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, NbtError> {
+		match self.token {
+			Some(token) => seed.deserialize(de::value::BorrowedStrDeserializer::<NbtError>::new(token)).map(Some),
+			None => Ok(None),
+		}
+	}
+
+	fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, NbtError> {
+		self.token = None;
+		seed.deserialize(ArrayDeserializer { reader: self.reader, id: self.id, length: self.length })
+	}
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for SliceEnumAccess<'a, 'de> {
+	type Error = NbtError;
+	type Variant = Self;
+
+	fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), NbtError> {
+		let variant = self.reader.read_str()?;
+		let variant = seed.deserialize(de::value::BorrowedStrDeserializer::<NbtError>::new(variant))?;
+		Ok((variant, self))
+	}
+}
+
+impl<'a, 'de> SliceEnumAccess<'a, 'de> {
+	/// Deserializes the value of the variant, and then the End ID of the enclosing compound.
+	fn value<T>(self, expected: &str, read: impl FnOnce(SliceDeserializer<'_, 'de>) -> Result<T, NbtError>) -> Result<T, NbtError> {
+		let Some(id) = self.id else {
+			return Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &expected));
+		};
+		let value = read(SliceDeserializer { reader: self.reader, id })?;
+		if !self.reader.at_end() {
+			return Err(NbtError::Message("Expected a compound with one entry for an enum.".to_owned()));
+		}
+		Ok(value)
+	}
+}
+
+impl<'a, 'de> de::VariantAccess<'de> for SliceEnumAccess<'a, 'de> {
+	type Error = NbtError;
+
+	fn unit_variant(self) -> Result<(), NbtError> {
+		match self.id {
+			None => Ok(()),
+			Some(_) => self.value("unit variant", |deserializer| Deserialize::deserialize(deserializer)),
+		}
+	}
+
+	fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, NbtError> {
+		self.value("newtype variant", |deserializer| seed.deserialize(deserializer))
+	}
+
+	fn tuple_variant<V: de::Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, NbtError> {
+		self.value("tuple variant", |deserializer| de::Deserializer::deserialize_seq(deserializer, visitor))
+	}
+
+	fn struct_variant<V: de::Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, NbtError> {
+		self.value("struct variant", |deserializer| de::Deserializer::deserialize_map(deserializer, visitor))
+	}
+}
+
+/// Byte buffers that can be used with the [byte_array] and [byte_list] modules.<br>
+/// NBT bytes are signed, so buffers of `u8` reinterpret the bits of each byte unless the `checked` variant of a module is used.
+pub trait ByteBuffer: Sized {
+	/// `true` if the elements are `i8`.
+	const SIGNED: bool;
+	/// The raw bytes of the buffer.
+	fn as_raw(&self) -> &[u8];
+	/// Creates the buffer from raw bytes.
+	fn from_raw(bytes: Vec<u8>) -> Self;
+}
+
+impl ByteBuffer for Vec<u8> {
+	const SIGNED: bool = false;
+
+	fn as_raw(&self) -> &[u8] {
+		self
+	}
+
+	fn from_raw(bytes: Vec<u8>) -> Self {
+		bytes
+	}
+}
+
+impl ByteBuffer for Vec<i8> {
+	const SIGNED: bool = true;
+
+	fn as_raw(&self) -> &[u8] {
+		bytemuck::cast_slice(self)
+	}
+
+	fn from_raw(bytes: Vec<u8>) -> Self {
+		bytemuck::cast_vec(bytes)
+	}
+}
+
+/// Checks that every byte of an unsigned buffer fits in an `i8` when reinterpretation is not allowed.
+fn check_unsigned<B: ByteBuffer, E: ser::Error>(buffer: &B, checked: bool) -> Result<(), E> {
+	if checked && !B::SIGNED && buffer.as_raw().iter().any(|&byte| byte > i8::MAX as u8) {
+		return Err(E::custom("Byte is too large to be stored without reinterpreting it as a negative number."));
+	}
+	Ok(())
+}
+
+/// Visitor that reads a [ByteBuffer] from a ByteArray, a List of bytes, or any sequence of integers.
+struct ByteBufferVisitor<B> {
+	checked: bool,
+	buffer: std::marker::PhantomData<B>,
+}
+
+impl<B: ByteBuffer> ByteBufferVisitor<B> {
+	fn new(checked: bool) -> Self {
+		Self { checked, buffer: std::marker::PhantomData }
+	}
+
+	/// Converts a value to its raw byte, failing if it does not fit.
+	fn raw<E: de::Error>(&self, value: i16) -> Result<u8, E> {
+		let range = match (self.checked, B::SIGNED) {
+			(false, _) => i8::MIN as i16..=u8::MAX as i16,
+			(true, true) => i8::MIN as i16..=i8::MAX as i16,
+			(true, false) => 0..=u8::MAX as i16,
+		};
+		if !range.contains(&value) {
+			return Err(E::invalid_value(de::Unexpected::Signed(value as i64), &"a byte"));
+		}
+		Ok(value as u8)
+	}
+}
+
+impl<'de, B: ByteBuffer> de::Visitor<'de> for ByteBufferVisitor<B> {
+	type Value = B;
+
+	fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+		formatter.write_str("a byte array or a list of bytes")
+	}
+
+	fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<B, E> {
+		self.visit_byte_buf(value.to_vec())
+	}
+
+	fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<B, E> {
+		// NBT bytes are signed, so bytes that are negative as an i8 need reinterpretation to become a u8.
+		if self.checked && !B::SIGNED && value.iter().any(|&byte| byte > i8::MAX as u8) {
+			return Err(E::custom("Negative byte can not be read as a u8 without reinterpreting it."));
+		}
+		Ok(B::from_raw(value))
+	}
+
+	fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<B, A::Error> {
+		let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+		while let Some(value) = seq.next_element::<i16>()? {
+			bytes.push(self.raw(value)?);
+		}
+		Ok(B::from_raw(bytes))
+	}
+}
+
+/// Stores a [ByteBuffer] (`Vec<u8>` or `Vec<i8>`) as a ByteArray instead of a List.
+/// Use with `#[serde(with = "rustnbt::serde::byte_array")]`.<br>
+/// Reading accepts a ByteArray or a List of bytes. The bits of negative bytes are reinterpreted for `Vec<u8>`;
+/// use [byte_array::checked] to reject bytes that don't fit in the element type instead.
+/// ```
+/// # use rustnbt::tag::Tag;
+/// #[derive(serde::Serialize)]
+/// struct Section {
+///     #[serde(rename = "SkyLight", with = "rustnbt::serde::byte_array")]
+///     sky_light: Vec<u8>,
+/// }
+/// let tag = rustnbt::serde::to_tag(&Section { sky_light: vec![255, 0] }).unwrap();
+/// assert!(matches!(tag, Tag::Compound(map) if map["SkyLight"] == Tag::ByteArray(vec![-1, 0])));
+/// ```
+pub mod byte_array {
+	use super::*;
+
+	pub fn serialize<B: ByteBuffer, S: ser::Serializer>(buffer: &B, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_bytes(buffer.as_raw())
+	}
+
+	pub fn deserialize<'de, B: ByteBuffer, D: de::Deserializer<'de>>(deserializer: D) -> Result<B, D::Error> {
+		deserializer.deserialize_byte_buf(ByteBufferVisitor::new(false))
+	}
+
+	/// Like [byte_array](super::byte_array), but fails instead of reinterpreting bytes that don't fit in the element type.
+	pub mod checked {
+		use super::*;
+
+		pub fn serialize<B: ByteBuffer, S: ser::Serializer>(buffer: &B, serializer: S) -> Result<S::Ok, S::Error> {
+			check_unsigned(buffer, true)?;
+			serializer.serialize_bytes(buffer.as_raw())
+		}
+
+		pub fn deserialize<'de, B: ByteBuffer, D: de::Deserializer<'de>>(deserializer: D) -> Result<B, D::Error> {
+			deserializer.deserialize_byte_buf(ByteBufferVisitor::new(true))
+		}
+	}
+}
+
+/// Stores a [ByteBuffer] (`Vec<u8>` or `Vec<i8>`) as a List of bytes.
+/// Use with `#[serde(with = "rustnbt::serde::byte_list")]`.<br>
+/// This is what a plain `Vec<i8>` or `Vec<u8>` does already, but reading also accepts a ByteArray.
+/// The bits of negative bytes are reinterpreted for `Vec<u8>`; use [byte_list::checked] to reject bytes
+/// that don't fit in the element type instead.
+pub mod byte_list {
+	use super::*;
+
+	fn serialize_list<B: ByteBuffer, S: ser::Serializer>(buffer: &B, serializer: S) -> Result<S::Ok, S::Error> {
+		use ser::SerializeSeq;
+		let bytes = buffer.as_raw();
+		let mut seq = serializer.serialize_seq(Some(bytes.len()))?;
+		for &byte in bytes {
+			seq.serialize_element(&(byte as i8))?;
+		}
+		seq.end()
+	}
+
+	pub fn serialize<B: ByteBuffer, S: ser::Serializer>(buffer: &B, serializer: S) -> Result<S::Ok, S::Error> {
+		serialize_list(buffer, serializer)
+	}
+
+	pub fn deserialize<'de, B: ByteBuffer, D: de::Deserializer<'de>>(deserializer: D) -> Result<B, D::Error> {
+		deserializer.deserialize_seq(ByteBufferVisitor::new(false))
+	}
+
+	/// Like [byte_list](super::byte_list), but fails instead of reinterpreting bytes that don't fit in the element type.
+	pub mod checked {
+		use super::*;
+
+		pub fn serialize<B: ByteBuffer, S: ser::Serializer>(buffer: &B, serializer: S) -> Result<S::Ok, S::Error> {
+			check_unsigned(buffer, true)?;
+			serialize_list(buffer, serializer)
+		}
+
+		pub fn deserialize<'de, B: ByteBuffer, D: de::Deserializer<'de>>(deserializer: D) -> Result<B, D::Error> {
+			deserializer.deserialize_seq(ByteBufferVisitor::new(true))
+		}
+	}
+}
+
+/// Buffers of integers that can be used with the [int_array] and [long_array] modules.
+pub trait ArrayBuffer<T>: Sized {
+	/// The elements of the buffer.
+	fn as_slice(&self) -> &[T];
+	/// Creates the buffer from its elements, or returns [None] if there is the wrong number of elements.
+	fn from_vec(values: Vec<T>) -> Option<Self>;
+}
+
+impl<T> ArrayBuffer<T> for Vec<T> {
+	fn as_slice(&self) -> &[T] {
+		self
+	}
+
+	fn from_vec(values: Vec<T>) -> Option<Self> {
+		Some(values)
+	}
+}
+
+impl<T, const N: usize> ArrayBuffer<T> for [T; N] {
+	fn as_slice(&self) -> &[T] {
+		self
+	}
+
+	fn from_vec(values: Vec<T>) -> Option<Self> {
+		values.try_into().ok()
+	}
+}
+
+/// Visitor that reads the elements of an IntArray, LongArray, or List.<br>
+/// Arrays are visited as a single entry map by `deserialize_any`, which is what untagged enums
+/// and flattened fields use, so that representation is accepted as well.
+struct ArrayVisitor<T, B> {
+	values: std::marker::PhantomData<(T, B)>,
+}
+
+impl<T, B> ArrayVisitor<T, B> {
+	fn new() -> Self {
+		Self { values: std::marker::PhantomData }
+	}
+}
+
+impl<'de, T: Deserialize<'de>, B: ArrayBuffer<T>> de::Visitor<'de> for ArrayVisitor<T, B> {
+	type Value = B;
+
+	fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+		formatter.write_str("an array or a list of integers")
+	}
+
+	fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<B, A::Error> {
+		let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+		while let Some(value) = seq.next_element()? {
+			values.push(value);
+		}
+		let length = values.len();
+		B::from_vec(values).ok_or_else(|| de::Error::invalid_length(length, &self))
+	}
+
+	fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<B, A::Error> {
+		match map.next_key::<String>()?.as_deref() {
+			Some(INT_ARRAY_TOKEN | LONG_ARRAY_TOKEN) => {
+				let values: Vec<T> = map.next_value()?;
+				let length = values.len();
+				B::from_vec(values).ok_or_else(|| de::Error::invalid_length(length, &self))
+			}
+			_ => Err(de::Error::invalid_type(de::Unexpected::Map, &self)),
+		}
+	}
+}
+
+/// Stores `Vec<i32>` or `[i32; N]` as an IntArray instead of a List.
+/// Use with `#[serde(with = "rustnbt::serde::int_array")]`.<br>
+/// Reading accepts an IntArray or a List of integers.
+pub mod int_array {
+	use super::*;
+
+	pub fn serialize<B: ArrayBuffer<i32>, S: ser::Serializer>(buffer: &B, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, buffer.as_slice())
+	}
+
+	pub fn deserialize<'de, B: ArrayBuffer<i32>, D: de::Deserializer<'de>>(deserializer: D) -> Result<B, D::Error> {
+		deserializer.deserialize_any(ArrayVisitor::new())
+	}
+}
+
+/// Stores `Vec<i64>` or `[i64; N]` as a LongArray instead of a List.
+/// Use with `#[serde(with = "rustnbt::serde::long_array")]`.<br>
+/// Reading accepts a LongArray or a List of integers.
+/// ```
+/// # use rustnbt::tag::Tag;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct BlockStates {
+///     #[serde(with = "rustnbt::serde::long_array")]
+///     data: Vec<i64>,
+/// }
+/// let tag = rustnbt::serde::to_tag(&BlockStates { data: vec![1, 2] }).unwrap();
+/// assert!(matches!(&tag, Tag::Compound(map) if map["data"] == Tag::LongArray(vec![1, 2])));
+/// ```
+pub mod long_array {
+	use super::*;
+
+	pub fn serialize<B: ArrayBuffer<i64>, S: ser::Serializer>(buffer: &B, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_newtype_struct(LONG_ARRAY_TOKEN, buffer.as_slice())
+	}
+
+	pub fn deserialize<'de, B: ArrayBuffer<i64>, D: de::Deserializer<'de>>(deserializer: D) -> Result<B, D::Error> {
+		deserializer.deserialize_any(ArrayVisitor::new())
+	}
+}
+
+/// UUID types that can be used with the [uuid] module.
+/// This is implemented for `u128`, and for `uuid::Uuid` with the `uuid` feature.
+pub trait UuidLike: Sized {
+	fn to_u128(&self) -> u128;
+	fn from_u128(value: u128) -> Self;
+}
+
+impl UuidLike for u128 {
+	fn to_u128(&self) -> u128 {
+		*self
+	}
+
+	fn from_u128(value: u128) -> Self {
+		value
+	}
+}
+
+#[cfg(feature = "uuid")]
+impl UuidLike for ::uuid::Uuid {
+	fn to_u128(&self) -> u128 {
+		self.as_u128()
+	}
+
+	fn from_u128(value: u128) -> Self {
+		::uuid::Uuid::from_u128(value)
+	}
+}
+
+/// Stores a UUID as an IntArray of 4 integers, most significant first, the way the game has since 1.16.
+/// Use with `#[serde(with = "rustnbt::serde::uuid")]` on a `u128` or `uuid::Uuid` field.<br>
+/// Reading also accepts the hexadecimal string form, with or without hyphens, which older versions use in places.
+pub mod uuid {
+	use super::*;
+
+	pub fn serialize<U: UuidLike, S: ser::Serializer>(uuid: &U, serializer: S) -> Result<S::Ok, S::Error> {
+		let value = uuid.to_u128();
+		let ints = [(value >> 96) as i32, (value >> 64) as i32, (value >> 32) as i32, value as i32];
+		serializer.serialize_newtype_struct(INT_ARRAY_TOKEN, &ints)
+	}
+
+	pub fn deserialize<'de, U: UuidLike, D: de::Deserializer<'de>>(deserializer: D) -> Result<U, D::Error> {
+		deserializer.deserialize_any(UuidVisitor(std::marker::PhantomData))
+	}
+
+	struct UuidVisitor<U>(std::marker::PhantomData<U>);
+
+	impl<'de, U: UuidLike> de::Visitor<'de> for UuidVisitor<U> {
+		type Value = U;
+
+		fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+			formatter.write_str("an IntArray of 4 integers or a UUID string")
+		}
+
+		fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<U, A::Error> {
+			let ints: [i32; 4] = ArrayVisitor::new().visit_seq(seq)?;
+			Ok(from_ints(ints))
+		}
+
+		fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<U, A::Error> {
+			let ints: [i32; 4] = ArrayVisitor::new().visit_map(map)?;
+			Ok(from_ints(ints))
+		}
+
+		fn visit_str<E: de::Error>(self, value: &str) -> Result<U, E> {
+			let hex: String = value.chars().filter(|&c| c != '-').collect();
+			if hex.len() != 32 {
+				return Err(E::invalid_value(de::Unexpected::Str(value), &self));
+			}
+			u128::from_str_radix(&hex, 16)
+				.map(U::from_u128)
+				.map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+		}
+	}
+
+	fn from_ints<U: UuidLike>(ints: [i32; 4]) -> U {
+		U::from_u128(ints.iter().fold(0u128, |value, &int| value << 32 | int as u32 as u128))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	enum Kind {
+		Plain,
+		Named(String),
+		Point { x: i32, z: i32 },
+	}
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	struct Entity {
+		id: String,
+		#[serde(rename = "Health")]
+		health: f32,
+		#[serde(rename = "Pos")]
+		pos: Vec<f64>,
+		glowing: bool,
+		color: u8,
+		custom_name: Option<String>,
+		kind: Kind,
+		other: Kind,
+		#[serde(rename = "Data", with = "serde_bytes_compat")]
+		data: Vec<u8>,
+	}
+
+	// Writes byte buffers with serialize_bytes, like the serde_bytes crate does.
+	mod serde_bytes_compat {
+		pub fn serialize<S: serde::Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+			serializer.serialize_bytes(value)
+		}
+
+		pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+			struct Visitor;
+			impl<'de> serde::de::Visitor<'de> for Visitor {
+				type Value = Vec<u8>;
+				fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+					formatter.write_str("bytes")
+				}
+				fn visit_bytes<E: serde::de::Error>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+					Ok(value.to_vec())
+				}
+			}
+			deserializer.deserialize_bytes(Visitor)
+		}
+	}
+
+	#[test]
+	fn roundtrip_test() -> Result<(), NbtError> {
+		let entity = Entity {
+			id: "minecraft:pig".to_owned(),
+			health: 10.0,
+			pos: vec![1.0, 2.0, 3.0],
+			glowing: true,
+			color: 200,
+			custom_name: None,
+			kind: Kind::Plain,
+			other: Kind::Point { x: 1, z: -1 },
+			data: vec![255, 0, 1],
+		};
+		let tag = to_tag(&entity)?;
+		let expected = compound!(
+			("id", "minecraft:pig"),
+			("Health", Tag::Float(10.0)),
+			("Pos", list!(1.0, 2.0, 3.0)),
+			("glowing", Tag::Byte(1)),
+			("color", Tag::Byte(-56)),
+			("kind", "Plain"),
+			("other", compound!(("Point", compound!(("x", 1), ("z", -1))))),
+			("Data", Tag::ByteArray(vec![-1, 0, 1]))
+		);
+		assert_eq!(tag, expected);
+		assert_eq!(from_tag::<Entity>(tag)?, entity);
+		assert_eq!(from_tag::<Kind>(compound!(("Named", "Bob")))?, Kind::Named("Bob".to_owned()));
+		Ok(())
+	}
+
+	#[test]
+	fn tag_roundtrip_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("bytes", Tag::ByteArray(vec![1, -2])),
+			("ints", Tag::IntArray(vec![1, 2, 3])),
+			("longs", Tag::LongArray(vec![])),
+			("empty", list!()),
+			("nested", Tag::List(ListTag::IntArray(vec![vec![4]]))),
+			("short", Tag::Short(7))
+		);
+		assert_eq!(to_tag(&tag)?, tag);
+		assert_eq!(from_tag::<Tag>(tag.clone())?, tag);
+		assert!(to_tag(&(1, "two")).is_err());
+		Ok(())
+	}
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	struct Section {
+		#[serde(rename = "Y")]
+		y: i8,
+		#[serde(flatten)]
+		other: Map,
+	}
+
+	#[test]
+	fn flatten_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("Y", Tag::Byte(-4)),
+			("BlockStates", Tag::LongArray(vec![1, 2])),
+			("SkyLight", Tag::ByteArray(vec![0; 4])),
+			("Palette", Tag::List(ListTag::Compound(vec![Map::from_iter([("Name".to_owned(), Tag::from("minecraft:air"))])]))),
+			("Scale", Tag::Float(0.5)),
+			("Extra", compound!(("Version", Tag::Short(2))))
+		);
+		let section: Section = from_tag(tag.clone())?;
+		assert_eq!(section.y, -4);
+		assert_eq!(section.other.len(), 5);
+		assert_eq!(section.other.get("BlockStates"), Some(&Tag::LongArray(vec![1, 2])));
+		assert_eq!(to_tag(&section)?, tag);
+		Ok(())
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	struct Borrowed<'a> {
+		name: &'a str,
+		data: &'a [u8],
+		heights: Vec<i64>,
+		kind: Kind,
+		missing: Option<i32>,
+		#[serde(rename = "Count")]
+		count: u8,
+	}
+
+	#[test]
+	fn from_bytes_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("name", "minecraft:chest"),
+			("data", Tag::ByteArray(vec![1, 2, 3])),
+			("heights", Tag::LongArray(vec![5, -6])),
+			("kind", compound!(("Point", compound!(("x", 1), ("z", 2), ("y", 3))))),
+			("ignored", Tag::List(ListTag::Compound(vec![Map::from_iter([("a".to_owned(), Tag::IntArray(vec![1]))])]))),
+			("Count", Tag::Byte(-1))
+		);
+		let mut bytes = Vec::new();
+		crate::io::write_named_tag(&mut bytes, &tag, "root")?;
+		let borrowed: Borrowed = from_bytes(&bytes)?;
+		assert_eq!(borrowed, Borrowed {
+			name: "minecraft:chest",
+			data: &[1, 2, 3],
+			heights: vec![5, -6],
+			kind: Kind::Point { x: 1, z: 2 },
+			missing: None,
+			count: 255,
+		});
+		// The string is borrowed from the buffer.
+		assert!(bytes.as_ptr_range().contains(&borrowed.name.as_ptr()));
+		assert_eq!(from_bytes::<Tag>(&bytes)?, tag);
+		assert!(from_bytes::<Tag>(&bytes[..bytes.len() - 1]).is_err());
+
+		// A List of Lists nested deep enough to overflow the stack without the depth limit.
+		let mut deep = vec![TagID::List as u8, 0, 0];
+		for _ in 0..10_000 {
+			deep.extend([TagID::List as u8, 0, 0, 0, 1]);
+		}
+		deep.extend([0, 0, 0, 0, 0]);
+		assert!(crate::io::read_named_tag(&mut deep.as_slice()).is_err());
+		// Deserializing takes more stack per level than the 2 MiB of a test thread holds for DEFAULT_MAX_DEPTH levels in
+		// debug builds, so it gets the 8 MiB of a main thread.
+		let deep = std::thread::Builder::new().stack_size(8 << 20).spawn(move || {
+			from_bytes::<::serde::de::IgnoredAny>(&deep).is_err() && from_bytes::<Borrowed>(&deep).is_err() && from_bytes::<Tag>(&deep).is_err()
+		});
+		assert!(deep?.join().expect("The thread does not panic."));
+		Ok(())
+	}
+
+	/// A 1.18+ chunk, trimmed down to a few sections and a block entity.
+	fn chunk_tag() -> Tag {
+		let palette = |names: &[&str]| Tag::List(ListTag::Compound(names.iter().map(|name| {
+			Map::from_iter([("Name".to_owned(), Tag::from(*name))])
+		}).collect()));
+		compound!(
+			("DataVersion", 3120),
+			("xPos", -3),
+			("zPos", 12),
+			("yPos", Tag::Int(-4)),
+			("Status", "minecraft:full"),
+			("LastUpdate", Tag::Long(123456)),
+			("InhabitedTime", Tag::Long(0)),
+			("isLightOn", Tag::Byte(1)),
+			("sections", Tag::List(ListTag::Compound(vec![
+				Map::from_iter([
+					("Y".to_owned(), Tag::Byte(-4)),
+					("block_states".to_owned(), compound!(
+						("palette", palette(&["minecraft:bedrock", "minecraft:deepslate"])),
+						("data", Tag::LongArray(vec![0x1111, 0x2222]))
+					)),
+					("biomes".to_owned(), compound!(("palette", list!("minecraft:plains")))),
+				]),
+				Map::from_iter([
+					("Y".to_owned(), Tag::Byte(0)),
+					("block_states".to_owned(), compound!(("palette", palette(&["minecraft:air"])))),
+					("SkyLight".to_owned(), Tag::ByteArray(vec![-1; 16])),
+				]),
+			]))),
+			("block_entities", Tag::List(ListTag::Compound(vec![
+				Map::from_iter([
+					("id".to_owned(), Tag::from("minecraft:sign")),
+					("x".to_owned(), Tag::Int(-40)),
+					("y".to_owned(), Tag::Int(64)),
+					("z".to_owned(), Tag::Int(200)),
+					("Text1".to_owned(), Tag::from("{\"text\":\"Hi\"}")),
+				]),
+				Map::from_iter([
+					("id".to_owned(), Tag::from("minecraft:chest")),
+					("x".to_owned(), Tag::Int(-41)),
+					("y".to_owned(), Tag::Int(64)),
+					("z".to_owned(), Tag::Int(200)),
+					("Items".to_owned(), Tag::List(ListTag::Compound(vec![
+						Map::from_iter([
+							("Slot".to_owned(), Tag::Byte(0)),
+							("id".to_owned(), Tag::from("minecraft:torch")),
+							("Count".to_owned(), Tag::Byte(12)),
+						]),
+					]))),
+				]),
+			]))),
+			("Heightmaps", compound!(("MOTION_BLOCKING", Tag::LongArray(vec![7; 37]))))
+		)
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	struct Chunk {
+		#[serde(rename = "DataVersion")]
+		data_version: i32,
+		#[serde(rename = "xPos")]
+		x: i32,
+		#[serde(rename = "zPos")]
+		z: i32,
+		#[serde(rename = "Status")]
+		status: String,
+		#[serde(rename = "isLightOn")]
+		light_on: Option<bool>,
+		#[serde(rename = "PostProcessing")]
+		post_processing: Option<Vec<Tag>>,
+		sections: Vec<ChunkSection>,
+		block_entities: Vec<BlockEntity>,
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	struct ChunkSection {
+		#[serde(rename = "Y")]
+		y: i8,
+		block_states: Option<Palette>,
+		#[serde(rename = "SkyLight")]
+		sky_light: Option<Vec<i8>>,
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	struct Palette {
+		palette: Vec<Map>,
+		data: Option<Vec<i64>>,
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	#[serde(untagged)]
+	enum BlockEntity {
+		Container {
+			id: String,
+			#[serde(rename = "Items")]
+			items: Vec<Slot>,
+		},
+		Other(Map),
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	struct Slot {
+		#[serde(rename = "Slot")]
+		slot: i8,
+		id: String,
+		#[serde(rename = "Count")]
+		count: i8,
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	#[serde(untagged)]
+	enum Value {
+		Int(i32),
+		Long(i64),
+		Text(String),
+		List(Vec<i64>),
+		Other(Tag),
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	#[serde(tag = "id")]
+	enum TaggedBlockEntity {
+		#[serde(rename = "minecraft:chest")]
+		Chest {
+			#[serde(rename = "Items")]
+			items: Vec<Slot>,
+			#[serde(rename = "CustomName")]
+			custom_name: Option<String>,
+		},
+		#[serde(other)]
+		Unknown,
+	}
+
+	#[test]
+	fn chunk_test() -> Result<(), NbtError> {
+		let tag = chunk_tag();
+		let chunk: Chunk = from_tag(tag.clone())?;
+		assert_eq!((chunk.data_version, chunk.x, chunk.z), (3120, -3, 12));
+		assert_eq!(chunk.status, "minecraft:full");
+		assert_eq!(chunk.light_on, Some(true));
+		assert_eq!(chunk.post_processing, None);
+		assert_eq!(chunk.sections[0].block_states.as_ref().and_then(|states| states.data.clone()), Some(vec![0x1111, 0x2222]));
+		assert!(chunk.sections[1].block_states.as_ref().is_some_and(|states| states.data.is_none()));
+		assert_eq!(chunk.sections[1].sky_light.as_ref().map(Vec::len), Some(16));
+		assert!(matches!(&chunk.block_entities[0], BlockEntity::Other(map) if map.get("Text1").is_some()));
+		assert!(matches!(&chunk.block_entities[1], BlockEntity::Container { id, items } if id == "minecraft:chest" && items[0].count == 12));
+
+		let mut bytes = Vec::new();
+		crate::io::write_named_tag(&mut bytes, &tag, "")?;
+		assert_eq!(from_bytes::<Chunk>(&bytes)?, chunk);
+
+		let Tag::Compound(root) = tag else { unreachable!() };
+		let Some(Tag::List(ListTag::Compound(block_entities))) = root.get("block_entities") else { unreachable!() };
+		let tagged: Vec<TaggedBlockEntity> = from_tag(Tag::List(ListTag::Compound(block_entities.clone())))?;
+		assert_eq!(tagged[0], TaggedBlockEntity::Unknown);
+		assert!(matches!(&tagged[1], TaggedBlockEntity::Chest { items, custom_name: None } if items.len() == 1));
+
+		assert_eq!(from_tag::<Value>(Tag::Int(3))?, Value::Int(3));
+		assert_eq!(from_tag::<Value>(Tag::Long(3))?, Value::Int(3));
+		assert_eq!(from_tag::<Value>(Tag::Long(i64::MAX))?, Value::Long(i64::MAX));
+		assert_eq!(from_tag::<Value>(Tag::from("3"))?, Value::Text("3".to_owned()));
+		assert_eq!(from_tag::<Value>(list!(1i64, 2i64))?, Value::List(vec![1, 2]));
+		// Arrays keep their type when buffered.
+		assert_eq!(from_tag::<Value>(Tag::LongArray(vec![1, 2]))?, Value::Other(Tag::LongArray(vec![1, 2])));
+		assert_eq!(from_tag::<Value>(Tag::Float(0.5))?, Value::Other(Tag::Float(0.5)));
+		Ok(())
+	}
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	struct Bytes {
+		#[serde(with = "byte_array")]
+		array: Vec<u8>,
+		#[serde(with = "byte_list")]
+		list: Vec<u8>,
+		#[serde(with = "byte_array::checked")]
+		signed: Vec<i8>,
+		plain: Vec<u8>,
+	}
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	struct CheckedBytes {
+		#[serde(with = "byte_list::checked")]
+		list: Vec<u8>,
+	}
+
+	#[test]
+	fn byte_buffer_test() -> Result<(), NbtError> {
+		let bytes = Bytes {
+			array: vec![255, 1],
+			list: vec![128],
+			signed: vec![-1],
+			plain: vec![200],
+		};
+		let tag = to_tag(&bytes)?;
+		assert_eq!(tag, compound!(
+			("array", Tag::ByteArray(vec![-1, 1])),
+			("list", list!(-128i8)),
+			("signed", Tag::ByteArray(vec![-1])),
+			("plain", list!(-56i8))
+		));
+		assert_eq!(from_tag::<Bytes>(tag)?, bytes);
+		// Each representation can be read as the other.
+		let swapped = compound!(
+			("array", list!(-1i8)),
+			("list", Tag::ByteArray(vec![-1])),
+			("signed", list!(-1i8)),
+			("plain", Tag::ByteArray(vec![-1]))
+		);
+		assert_eq!(from_tag::<Bytes>(swapped)?, Bytes { array: vec![255], list: vec![255], signed: vec![-1], plain: vec![255] });
+
+		assert!(to_tag(&CheckedBytes { list: vec![128] }).is_err());
+		assert_eq!(to_tag(&CheckedBytes { list: vec![127] })?, compound!(("list", list!(127i8))));
+		assert!(from_tag::<CheckedBytes>(compound!(("list", list!(-1i8)))).is_err());
+		assert!(from_tag::<CheckedBytes>(compound!(("list", Tag::ByteArray(vec![-1])))).is_err());
+		Ok(())
+	}
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	struct Arrays {
+		#[serde(with = "int_array")]
+		pos: [i32; 3],
+		#[serde(with = "long_array")]
+		data: Vec<i64>,
+		#[serde(rename = "UUID", with = "uuid")]
+		uuid: u128,
+		plain: Vec<i64>,
+	}
+
+	#[derive(Deserialize, PartialEq, Debug)]
+	#[serde(untagged)]
+	enum UntaggedArray {
+		Data {
+			#[serde(with = "long_array")]
+			data: Vec<i64>,
+		},
+	}
+
+	#[test]
+	fn array_adapter_test() -> Result<(), NbtError> {
+		let arrays = Arrays {
+			pos: [1, -2, 3],
+			data: vec![],
+			uuid: 0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210,
+			plain: vec![4],
+		};
+		let tag = to_tag(&arrays)?;
+		assert_eq!(tag, compound!(
+			("pos", Tag::IntArray(vec![1, -2, 3])),
+			("data", Tag::LongArray(vec![])),
+			("UUID", Tag::IntArray(vec![0x0123_4567, 0x89AB_CDEFu32 as i32, 0xFEDC_BA98u32 as i32, 0x7654_3210])),
+			("plain", list!(4i64))
+		));
+		assert_eq!(from_tag::<Arrays>(tag.clone())?, arrays);
+		let mut bytes = Vec::new();
+		crate::io::write_named_tag(&mut bytes, &tag, "")?;
+		assert_eq!(from_bytes::<Arrays>(&bytes)?, arrays);
+
+		let legacy = compound!(
+			("pos", list!(1, -2, 3)),
+			("data", list!()),
+			("UUID", "01234567-89ab-cdef-fedc-ba9876543210"),
+			("plain", Tag::LongArray(vec![4]))
+		);
+		assert_eq!(from_tag::<Arrays>(legacy)?, arrays);
+		assert!(from_tag::<Arrays>(compound!(
+			("pos", Tag::IntArray(vec![1])),
+			("data", Tag::LongArray(vec![])),
+			("UUID", Tag::IntArray(vec![0; 4])),
+			("plain", list!())
+		)).is_err());
+		assert_eq!(
+			from_tag::<UntaggedArray>(compound!(("data", Tag::LongArray(vec![5]))))?,
+			UntaggedArray::Data { data: vec![5] }
+		);
+		#[cfg(feature = "uuid")]
+		{
+			let uuid = ::uuid::Uuid::from_u128(arrays.uuid);
+			let tag = to_tag(&Tag::from(uuid.to_string()))?;
+			assert_eq!(uuid::deserialize::<::uuid::Uuid, _>(Deserializer::new(tag))?, uuid);
+		}
+		Ok(())
+	}
+}
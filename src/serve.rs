@@ -0,0 +1,287 @@
+#![doc = r#"
+A tiny local HTTP server for inspecting a [Tag] or a world from a browser or a web UI, enabled with the `serve` feature.
+
+The server only answers `GET` requests, one connection at a time, and sends every response with
+`Access-Control-Allow-Origin: *` so pages served from elsewhere can call it. Values are sent as JSON, converted with the
+[serde](crate::serde) implementation of [Tag], or as pretty SNBT with `format=snbt`. Every endpoint that returns a
+value takes a `path` query, such as `path=Inventory[0].id`, that selects part of it.
+
+| Endpoint | Returns |
+|----------|---------|
+| `/` | A summary of the source: the type of the tag, or the dimensions and players of the world. |
+| `/tag` | The tag, when serving a tag. |
+| `/file/<path>` | An NBT file of the world, such as `/file/level.dat` or `/file/playerdata/<uuid>.dat`. |
+| `/chunk/<dimension>/<x>/<z>` | A chunk of the world, such as `/chunk/minecraft:overworld/0/-1`. |
+
+Errors are sent as `{"error": "..."}` with status 400 for a bad request, 404 for a missing value, and 500 for a file
+that could not be read.
+
+```no_run
+# use rustnbt::{ serve::{ Server, Source }, world::World };
+let world = World::open("saves/New World").expect("Failed to open world.");
+let server = Server::bind("127.0.0.1:8080", Source::World(world)).expect("Failed to bind.");
+server.run().expect("Server failed.");
+```
+"#]
+
+use crate::{
+	NbtError,
+	clipboard,
+	path::TagPath,
+	pos::ChunkPos,
+	snbt,
+	tag::Tag,
+	world::World,
+};
+use std::io::{ BufRead, BufReader, Write };
+use std::net::{ SocketAddr, TcpListener, TcpStream, ToSocketAddrs };
+
+/// What a [Server] exposes.
+#[derive(Clone, Debug)]
+pub enum Source {
+	Tag(Tag),
+	World(World),
+}
+
+/// A response from a [Server].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Response {
+	pub status: u16,
+	pub content_type: &'static str,
+	pub body: Vec<u8>,
+}
+
+impl Response {
+	fn json(status: u16, value: &serde_json::Value) -> Self {
+		Self {
+			status,
+			content_type: "application/json",
+			body: serde_json::to_vec(value).expect("Serializing a JSON value does not fail."),
+		}
+	}
+
+	fn error(status: u16, message: &str) -> Self {
+		Self::json(status, &serde_json::json!({ "error": message }))
+	}
+
+	fn reason(&self) -> &'static str {
+		match self.status {
+			200 => "OK",
+			400 => "Bad Request",
+			404 => "Not Found",
+			405 => "Method Not Allowed",
+			_ => "Internal Server Error",
+		}
+	}
+}
+
+/// A local HTTP server over a [Source]. See the [module](self) documentation.
+#[derive(Debug)]
+pub struct Server {
+	listener: TcpListener,
+	source: Source,
+}
+
+impl Server {
+	/// Binds the server to an address. Use a loopback address such as `127.0.0.1:8080` unless the data may be seen
+	/// by anyone on the network.
+	pub fn bind<A: ToSocketAddrs>(address: A, source: Source) -> Result<Self, NbtError> {
+		Ok(Self { listener: TcpListener::bind(address)?, source })
+	}
+
+	/// The address the server is bound to, which is useful after binding to port 0.
+	pub fn local_addr(&self) -> Result<SocketAddr, NbtError> {
+		Ok(self.listener.local_addr()?)
+	}
+
+	pub fn source(&self) -> &Source {
+		&self.source
+	}
+
+	/// Answers requests until accepting a connection fails. Errors on a single connection are ignored.
+	pub fn run(&self) -> Result<(), NbtError> {
+		loop {
+			let (stream, _) = self.listener.accept()?;
+			let _ = self.handle(stream);
+		}
+	}
+
+	/// Accepts one connection and answers its request.
+	pub fn handle_next(&self) -> Result<(), NbtError> {
+		let (stream, _) = self.listener.accept()?;
+		self.handle(stream)
+	}
+
+	fn handle(&self, mut stream: TcpStream) -> Result<(), NbtError> {
+		let mut reader = BufReader::new(&stream);
+		let mut request = String::new();
+		reader.read_line(&mut request)?;
+		loop {
+			let mut header = String::new();
+			if reader.read_line(&mut header)? == 0 || header.trim_end().is_empty() {
+				break;
+			}
+		}
+		let mut parts = request.split_whitespace();
+		let response = match (parts.next(), parts.next()) {
+			(Some(method), Some(target)) => self.respond(method, target),
+			_ => Response::error(400, "Malformed request."),
+		};
+		write!(
+			stream,
+			"HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+			response.status,
+			response.reason(),
+			response.content_type,
+			response.body.len(),
+		)?;
+		stream.write_all(&response.body)?;
+		stream.flush()?;
+		Ok(())
+	}
+
+	/// Answers a request for `target`, which is the path and query of the URL.
+	pub fn respond(&self, method: &str, target: &str) -> Response {
+		if method != "GET" {
+			return Response::error(405, "Only GET requests are supported.");
+		}
+		let (route, query) = target.split_once('?').unwrap_or((target, ""));
+		let query: Vec<(String, String)> = query.split('&').filter(|pair| !pair.is_empty()).map(|pair| {
+			let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+			(percent_decode(key), percent_decode(value))
+		}).collect();
+		let parameter = |name: &str| query.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+		let segments: Vec<String> = route.split('/').filter(|segment| !segment.is_empty()).map(percent_decode).collect();
+		let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+		let tag = match (&self.source, segments.as_slice()) {
+			(_, []) => return Response::json(200, &self.summary()),
+			(Source::Tag(tag), ["tag"]) => Ok(Some(tag.clone())),
+			(Source::World(world), ["file", path @ ..]) => read_file(world, path),
+			(Source::World(world), ["chunk", dimension, x, z]) => match (x.parse(), z.parse()) {
+				(Ok(x), Ok(z)) => world.chunk(dimension, ChunkPos::new(x, z)).map(|chunk| chunk.map(|chunk| Tag::Compound(chunk.take_data()))),
+				_ => return Response::error(400, "Chunk coordinates must be integers."),
+			},
+			_ => return Response::error(404, "Unknown endpoint."),
+		};
+		let tag = match tag {
+			Ok(Some(tag)) => tag,
+			Ok(None) => return Response::error(404, "Not found."),
+			Err(err) if matches!(err.root(), NbtError::IoError(err) if err.kind() == std::io::ErrorKind::NotFound) => return Response::error(404, "Not found."),
+			Err(err) => return Response::error(500, &err.to_string()),
+		};
+		let path: TagPath = match parameter("path").unwrap_or("").parse() {
+			Ok(path) => path,
+			Err(err) => return Response::error(400, &format!("Invalid path: {err}")),
+		};
+		let Some(value) = path.get(&tag) else {
+			return Response::error(404, &format!("Nothing at {path}."));
+		};
+		match parameter("format") {
+			None | Some("json") => Response::json(200, &serde_json::to_value(&*value).unwrap_or(serde_json::Value::Null)),
+			Some("snbt") => Response { status: 200, content_type: "text/plain; charset=utf-8", body: snbt::to_string_pretty(&value).into_bytes() },
+			Some(format) => Response::error(400, &format!("Unknown format: {format}")),
+		}
+	}
+
+	fn summary(&self) -> serde_json::Value {
+		match &self.source {
+			Source::Tag(tag) => serde_json::json!({ "source": "tag", "type": tag.name() }),
+			Source::World(world) => serde_json::json!({
+				"source": "world",
+				"dimensions": world.dimensions().unwrap_or_default(),
+				"players": world.player_uuids().unwrap_or_default(),
+			}),
+		}
+	}
+}
+
+/// Reads an NBT file of a world, which may be compressed with gzip or zlib.
+fn read_file(world: &World, path: &[&str]) -> Result<Option<Tag>, NbtError> {
+	if path.is_empty() || path.iter().any(|segment| *segment == ".." || segment.contains('\\')) {
+		return Ok(None);
+	}
+	let file = path.iter().fold(world.root().to_path_buf(), |file, segment| file.join(segment));
+	if !file.is_file() {
+		return Ok(None);
+	}
+	Ok(Some(clipboard::from_binary(&std::fs::read(file)?)?.take_tag()))
+}
+
+/// Decodes `%XX` escapes and `+` in a URL component. Invalid escapes are kept as they are.
+fn percent_decode(text: &str) -> String {
+	let mut bytes = Vec::with_capacity(text.len());
+	let mut rest = text.as_bytes();
+	while let Some((&byte, tail)) = rest.split_first() {
+		let escaped = tail.get(..2).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+		match (byte, escaped) {
+			(b'%', Some(escaped)) => {
+				bytes.push(escaped);
+				rest = &tail[2..];
+				continue;
+			}
+			(b'+', _) => bytes.push(b' '),
+			(byte, _) => bytes.push(byte),
+		}
+		rest = tail;
+	}
+	String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, tag::NamedTag, world::{ OVERWORLD, tests::temp_world } };
+	use std::io::Read;
+
+	fn body(response: &Response) -> &str {
+		std::str::from_utf8(&response.body).expect("Body is UTF-8.")
+	}
+
+	#[test]
+	fn tag_test() -> Result<(), NbtError> {
+		let server = Server::bind("127.0.0.1:0", Source::Tag(compound!(("Pos", list!(1.0, 64.0)), ("Name", "a b"))))?;
+		assert_eq!(body(&server.respond("GET", "/")), r#"{"source":"tag","type":"TAG_Compound"}"#);
+		assert_eq!(body(&server.respond("GET", "/tag?path=Pos%5B1%5D")), "64.0");
+		assert_eq!(body(&server.respond("GET", "/tag?path=Name&format=snbt")), "\"a b\"");
+		assert_eq!(server.respond("GET", "/tag?path=Missing").status, 404);
+		assert_eq!(server.respond("GET", "/tag?path=Pos[").status, 400);
+		assert_eq!(server.respond("GET", "/chunk/a/0/0").status, 404);
+		assert_eq!(server.respond("POST", "/tag").status, 405);
+		assert_eq!(percent_decode("a+b%2Fc%zz"), "a b/c%zz");
+
+		let address = server.local_addr()?;
+		let client = std::thread::spawn(move || -> std::io::Result<String> {
+			let mut stream = TcpStream::connect(address)?;
+			stream.write_all(b"GET /tag?path=Name HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+			let mut response = String::new();
+			stream.read_to_string(&mut response)?;
+			Ok(response)
+		});
+		server.handle_next()?;
+		let response = client.join().expect("Client thread panicked.")?;
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+		assert!(response.contains("Access-Control-Allow-Origin: *\r\n"));
+		assert!(response.ends_with("\r\n\r\n\"a b\""));
+		Ok(())
+	}
+
+	#[test]
+	fn world_test() -> Result<(), NbtError> {
+		let dir = temp_world("serve");
+		std::fs::create_dir_all(&dir)?;
+		crate::world::write_gzip_nbt(dir.join("level.dat"), &NamedTag::new(compound!(("Data", compound!(("LevelName", "Test"))))))?;
+		let world = World::open(&dir)?;
+		let chunk = crate::chunk::Chunk::new(crate::Map::from_iter([("xPos".to_owned(), Tag::Int(0))]));
+		world.save_chunk(OVERWORLD, ChunkPos::new(0, -1), &chunk)?;
+		let server = Server::bind("127.0.0.1:0", Source::World(world))?;
+		assert_eq!(body(&server.respond("GET", "/file/level.dat?path=Data.LevelName")), "\"Test\"");
+		assert_eq!(server.respond("GET", "/file/missing.dat").status, 404);
+		assert_eq!(server.respond("GET", "/file/../secret.dat").status, 404);
+		assert_eq!(body(&server.respond("GET", "/chunk/minecraft:overworld/0/-1?path=xPos")), "0");
+		assert_eq!(server.respond("GET", "/chunk/minecraft:overworld/5/5").status, 404);
+		assert_eq!(server.respond("GET", "/chunk/minecraft:overworld/a/5").status, 400);
+		assert_eq!(server.respond("GET", "/tag").status, 404);
+		std::fs::remove_dir_all(dir)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,524 @@
+#![doc = r#"
+A single traversal of [Tag] trees that writes to any output format.
+
+[Tag::encode_to] walks a tag and calls the methods of an [NbtSink] for each value, Compound, and List it finds.
+The binary writer ([BinarySink](crate::io::BinarySink)), the SNBT writer, [JsonSink], and [CountingSink] are all sinks,
+and other output formats can be added by implementing [NbtSink].<br>
+Tags are walked with a stack on the heap instead of by recursion, so deeply nested tags can not overflow the thread stack.
+
+```
+# use rustnbt::{ NbtError, compound, list, sink::*, tag::TagID };
+/// Collects the keys of every Compound entry.
+#[derive(Default)]
+struct Keys(Vec<String>);
+
+impl NbtSink for Keys {
+	fn scalar(&mut self, _: Scalar) -> Result<(), NbtError> { Ok(()) }
+	fn begin_compound(&mut self, _: usize) -> Result<(), NbtError> { Ok(()) }
+	fn key(&mut self, key: &str, _: TagID) -> Result<(), NbtError> {
+		self.0.push(key.to_owned());
+		Ok(())
+	}
+	fn end_compound(&mut self) -> Result<(), NbtError> { Ok(()) }
+	fn begin_list(&mut self, _: Option<TagID>, _: usize) -> Result<(), NbtError> { Ok(()) }
+	fn end_list(&mut self) -> Result<(), NbtError> { Ok(()) }
+	fn sorted_keys(&self) -> bool { true }
+}
+
+let mut keys = Keys::default();
+compound!(("b", 1), ("a", compound!(("c", list!(1, 2))))).encode_to(&mut keys).unwrap();
+assert_eq!(keys.0, ["a", "c", "b"]);
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+	},
+};
+use std::fmt::Write;
+
+/// A value that is not a Compound or List, borrowed from a [Tag] or a List element.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Scalar<'a> {
+	Byte(i8),
+	Short(i16),
+	Int(i32),
+	Long(i64),
+	Float(f32),
+	Double(f64),
+	ByteArray(&'a [i8]),
+	String(&'a str),
+	IntArray(&'a [i32]),
+	LongArray(&'a [i64]),
+}
+
+impl<'a> Scalar<'a> {
+	/// Borrows a tag that is not a Compound or List.
+	pub fn of(tag: &'a Tag) -> Option<Self> {
+		Some(match tag {
+			Tag::Byte(value) => Scalar::Byte(*value),
+			Tag::Short(value) => Scalar::Short(*value),
+			Tag::Int(value) => Scalar::Int(*value),
+			Tag::Long(value) => Scalar::Long(*value),
+			Tag::Float(value) => Scalar::Float(*value),
+			Tag::Double(value) => Scalar::Double(*value),
+			Tag::ByteArray(values) => Scalar::ByteArray(values),
+			Tag::String(value) => Scalar::String(value),
+			Tag::IntArray(values) => Scalar::IntArray(values),
+			Tag::LongArray(values) => Scalar::LongArray(values),
+			Tag::List(_) | Tag::Compound(_) => return None,
+		})
+	}
+
+	pub fn id(&self) -> TagID {
+		match self {
+			Scalar::Byte(_) => TagID::Byte,
+			Scalar::Short(_) => TagID::Short,
+			Scalar::Int(_) => TagID::Int,
+			Scalar::Long(_) => TagID::Long,
+			Scalar::Float(_) => TagID::Float,
+			Scalar::Double(_) => TagID::Double,
+			Scalar::ByteArray(_) => TagID::ByteArray,
+			Scalar::String(_) => TagID::String,
+			Scalar::IntArray(_) => TagID::IntArray,
+			Scalar::LongArray(_) => TagID::LongArray,
+		}
+	}
+
+	pub fn to_tag(&self) -> Tag {
+		match *self {
+			Scalar::Byte(value) => Tag::Byte(value),
+			Scalar::Short(value) => Tag::Short(value),
+			Scalar::Int(value) => Tag::Int(value),
+			Scalar::Long(value) => Tag::Long(value),
+			Scalar::Float(value) => Tag::Float(value),
+			Scalar::Double(value) => Tag::Double(value),
+			Scalar::ByteArray(values) => Tag::ByteArray(values.to_vec()),
+			Scalar::String(value) => Tag::String(value.to_owned()),
+			Scalar::IntArray(values) => Tag::IntArray(values.to_vec()),
+			Scalar::LongArray(values) => Tag::LongArray(values.to_vec()),
+		}
+	}
+}
+
+/// An output format for [Tag::encode_to].<br>
+/// A Compound is written as [begin_compound](Self::begin_compound), then [key](Self::key) followed by the value of each
+/// entry, then [end_compound](Self::end_compound). A List is written as [begin_list](Self::begin_list), each element,
+/// then [end_list](Self::end_list).
+pub trait NbtSink {
+	/// Writes a value that is not a Compound or List.
+	fn scalar(&mut self, value: Scalar) -> Result<(), NbtError>;
+
+	/// Starts a Compound with `len` entries.
+	fn begin_compound(&mut self, len: usize) -> Result<(), NbtError>;
+
+	/// Starts an entry of the open Compound. The value of the entry, which has the type `id`, is written next.
+	fn key(&mut self, key: &str, id: TagID) -> Result<(), NbtError>;
+
+	fn end_compound(&mut self) -> Result<(), NbtError>;
+
+	/// Starts a List of `len` elements of type `element`, which is None for [ListTag::Empty].
+	fn begin_list(&mut self, element: Option<TagID>, len: usize) -> Result<(), NbtError>;
+
+	fn end_list(&mut self) -> Result<(), NbtError>;
+
+	/// Writes a List whose elements are not Compounds or Lists. By default, each element is written with
+	/// [scalar](Self::scalar), and sinks can write the whole List at once instead.
+	fn flat_list(&mut self, list: &ListTag) -> Result<(), NbtError> {
+		macro_rules! elements {
+			($($title:ident($value:ident => $scalar:expr))+) => {
+				match list {
+					ListTag::Empty => {
+						self.begin_list(None, 0)?;
+					}
+					ListTag::Compound(_) | ListTag::List(_) => return encode(Node::List(list), self),
+					$(
+						ListTag::$title(values) => {
+							self.begin_list(Some(TagID::$title), values.len())?;
+							for $value in values {
+								self.scalar(Scalar::$title($scalar))?;
+							}
+						}
+					)+
+				}
+			};
+		}
+		elements!(
+			Byte(value => *value)
+			Short(value => *value)
+			Int(value => *value)
+			Long(value => *value)
+			Float(value => *value)
+			Double(value => *value)
+			ByteArray(value => value)
+			String(value => value)
+			IntArray(value => value)
+			LongArray(value => value)
+		);
+		self.end_list()
+	}
+
+	/// Returns true if the entries of each Compound should be written in the order of their keys.
+	fn sorted_keys(&self) -> bool {
+		false
+	}
+}
+
+impl Tag {
+	/// Writes this tag to a sink. See the [module](crate::sink) documentation.
+	pub fn encode_to<S: NbtSink + ?Sized>(&self, sink: &mut S) -> Result<(), NbtError> {
+		encode(Node::Tag(self), sink)
+	}
+}
+
+impl ListTag {
+	/// Writes this List to a sink. See the [module](crate::sink) documentation.
+	pub fn encode_to<S: NbtSink + ?Sized>(&self, sink: &mut S) -> Result<(), NbtError> {
+		encode(Node::List(self), sink)
+	}
+}
+
+/// A value to encode. List elements are not stored as [Tag]s.
+pub(crate) enum Node<'a> {
+	Tag(&'a Tag),
+	Map(&'a Map),
+	List(&'a ListTag),
+}
+
+/// The entries of a Compound, in the order of the map or of their keys.
+enum Entries<'a> {
+	Map(<&'a Map as IntoIterator>::IntoIter),
+	Sorted(std::vec::IntoIter<(&'a String, &'a Tag)>),
+}
+
+impl<'a> Iterator for Entries<'a> {
+	type Item = (&'a String, &'a Tag);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self {
+			Entries::Map(entries) => entries.next(),
+			Entries::Sorted(entries) => entries.next(),
+		}
+	}
+}
+
+/// A Compound or List that is being encoded.
+enum Frame<'a> {
+	Compound(Entries<'a>),
+	Compounds(std::slice::Iter<'a, Map>),
+	Lists(std::slice::Iter<'a, ListTag>),
+}
+
+/// Encodes a value, keeping the Compounds and Lists that are being encoded on a stack.
+pub(crate) fn encode<S: NbtSink + ?Sized>(root: Node, sink: &mut S) -> Result<(), NbtError> {
+	let mut stack = Vec::new();
+	begin(root, sink, &mut stack)?;
+	while let Some(frame) = stack.last_mut() {
+		let next = match frame {
+			Frame::Compound(entries) => match entries.next() {
+				Some((key, tag)) => {
+					sink.key(key, tag.id())?;
+					Some(Node::Tag(tag))
+				}
+				None => {
+					sink.end_compound()?;
+					None
+				}
+			},
+			Frame::Compounds(maps) => maps.next().map(Node::Map),
+			Frame::Lists(lists) => lists.next().map(Node::List),
+		};
+		match next {
+			Some(node) => begin(node, sink, &mut stack)?,
+			None => {
+				if !matches!(stack.pop(), Some(Frame::Compound(_))) {
+					sink.end_list()?;
+				}
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Starts encoding a value. Compounds and Lists of Compounds or Lists are pushed onto the stack, and other values are
+/// encoded whole.
+fn begin<'a, S: NbtSink + ?Sized>(node: Node<'a>, sink: &mut S, stack: &mut Vec<Frame<'a>>) -> Result<(), NbtError> {
+	match node {
+		Node::Tag(Tag::Compound(map)) | Node::Map(map) => {
+			sink.begin_compound(map.len())?;
+			let entries = if sink.sorted_keys() {
+				let mut entries: Vec<_> = map.iter().collect();
+				entries.sort_unstable_by_key(|(key, _)| *key);
+				Entries::Sorted(entries.into_iter())
+			} else {
+				Entries::Map(map.iter())
+			};
+			stack.push(Frame::Compound(entries));
+		}
+		Node::Tag(Tag::List(list)) | Node::List(list) => match list {
+			ListTag::Compound(maps) => {
+				sink.begin_list(Some(TagID::Compound), maps.len())?;
+				stack.push(Frame::Compounds(maps.iter()));
+			}
+			ListTag::List(lists) => {
+				sink.begin_list(Some(TagID::List), lists.len())?;
+				stack.push(Frame::Lists(lists.iter()));
+			}
+			list => sink.flat_list(list)?,
+		},
+		Node::Tag(tag) => sink.scalar(Scalar::of(tag).expect("Compounds and Lists are handled above."))?,
+	}
+	Ok(())
+}
+
+/// Counts what [BinarySink](crate::io::BinarySink) would write, without writing anything.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CountingSink {
+	/// The number of bytes of the binary payload, which is the same as [NbtSize::nbt_size](crate::io::NbtSize::nbt_size).
+	pub bytes: usize,
+	/// The number of tags, counting Compounds, Lists, and every List element.
+	pub tags: usize,
+	/// The deepest nesting of Compounds and Lists.
+	pub max_depth: usize,
+	depth: usize,
+}
+
+impl CountingSink {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Counts a value in a List or Compound, or the root.
+	fn open(&mut self) {
+		self.tags += 1;
+		self.depth += 1;
+		self.max_depth = self.max_depth.max(self.depth);
+	}
+}
+
+impl NbtSink for CountingSink {
+	fn scalar(&mut self, value: Scalar) -> Result<(), NbtError> {
+		self.tags += 1;
+		self.bytes += match value {
+			Scalar::Byte(_) => 1,
+			Scalar::Short(_) => 2,
+			Scalar::Int(_) | Scalar::Float(_) => 4,
+			Scalar::Long(_) | Scalar::Double(_) => 8,
+			Scalar::ByteArray(values) => 4 + values.len(),
+			Scalar::String(value) => 2 + value.len(),
+			Scalar::IntArray(values) => 4 + values.len() * 4,
+			Scalar::LongArray(values) => 4 + values.len() * 8,
+		};
+		Ok(())
+	}
+
+	fn begin_compound(&mut self, _: usize) -> Result<(), NbtError> {
+		self.open();
+		Ok(())
+	}
+
+	fn key(&mut self, key: &str, _: TagID) -> Result<(), NbtError> {
+		self.bytes += 1 + 2 + key.len();
+		Ok(())
+	}
+
+	fn end_compound(&mut self) -> Result<(), NbtError> {
+		self.bytes += 1;
+		self.depth -= 1;
+		Ok(())
+	}
+
+	fn begin_list(&mut self, _: Option<TagID>, _: usize) -> Result<(), NbtError> {
+		self.open();
+		self.bytes += 5;
+		Ok(())
+	}
+
+	fn end_list(&mut self) -> Result<(), NbtError> {
+		self.depth -= 1;
+		Ok(())
+	}
+}
+
+/// Writes tags as compact JSON. Numbers become JSON numbers, with Float and Double values that are not finite written
+/// as `null`, arrays and Lists become JSON arrays, and Compounds become JSON objects.
+/// ```
+/// # use rustnbt::{ compound, list, sink::JsonSink, tag::Tag };
+/// let mut json = JsonSink::new();
+/// compound!(("Pos", list!(1.5, 64.0)), ("Ids", Tag::IntArray(vec![1, 2]))).encode_to(&mut json).unwrap();
+/// assert!(json.as_str() == r#"{"Pos":[1.5,64.0],"Ids":[1,2]}"# || json.as_str() == r#"{"Ids":[1,2],"Pos":[1.5,64.0]}"#);
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct JsonSink {
+	output: String,
+	/// For each open object or array, whether nothing has been written in it yet.
+	first: Vec<bool>,
+	sort_keys: bool,
+}
+
+impl JsonSink {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns this sink with the entries of each Compound written in the order of their keys.
+	pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+		self.sort_keys = sort_keys;
+		self
+	}
+
+	pub fn as_str(&self) -> &str {
+		&self.output
+	}
+
+	pub fn into_string(self) -> String {
+		self.output
+	}
+
+	/// Writes the separator before an element of the open array.
+	fn separate(&mut self) {
+		if let Some(first) = self.first.last_mut() {
+			if !std::mem::replace(first, false) {
+				self.output.push(',');
+			}
+		}
+	}
+
+	fn array<T: std::fmt::Display>(&mut self, values: &[T]) {
+		self.output.push('[');
+		for (i, value) in values.iter().enumerate() {
+			if i > 0 {
+				self.output.push(',');
+			}
+			write!(self.output, "{value}").unwrap();
+		}
+		self.output.push(']');
+	}
+
+	fn float<T: std::fmt::Debug>(&mut self, value: T, finite: bool) {
+		if finite {
+			write!(self.output, "{value:?}").unwrap();
+		} else {
+			self.output.push_str("null");
+		}
+	}
+
+	fn string(&mut self, value: &str) {
+		write_json_string(&mut self.output, value);
+	}
+}
+
+/// Writes a quoted JSON string.
+pub(crate) fn write_json_string(output: &mut String, value: &str) {
+	output.push('"');
+	for c in value.chars() {
+		match c {
+			'"' => output.push_str("\\\""),
+			'\\' => output.push_str("\\\\"),
+			'\n' => output.push_str("\\n"),
+			'\r' => output.push_str("\\r"),
+			'\t' => output.push_str("\\t"),
+			c if c < ' ' => write!(output, "\\u{:04x}", c as u32).unwrap(),
+			c => output.push(c),
+		}
+	}
+	output.push('"');
+}
+
+impl NbtSink for JsonSink {
+	fn scalar(&mut self, value: Scalar) -> Result<(), NbtError> {
+		self.separate();
+		match value {
+			Scalar::Byte(value) => write!(self.output, "{value}").unwrap(),
+			Scalar::Short(value) => write!(self.output, "{value}").unwrap(),
+			Scalar::Int(value) => write!(self.output, "{value}").unwrap(),
+			Scalar::Long(value) => write!(self.output, "{value}").unwrap(),
+			Scalar::Float(value) => self.float(value, value.is_finite()),
+			Scalar::Double(value) => self.float(value, value.is_finite()),
+			Scalar::ByteArray(values) => self.array(values),
+			Scalar::String(value) => self.string(value),
+			Scalar::IntArray(values) => self.array(values),
+			Scalar::LongArray(values) => self.array(values),
+		}
+		Ok(())
+	}
+
+	fn begin_compound(&mut self, _: usize) -> Result<(), NbtError> {
+		self.separate();
+		self.output.push('{');
+		self.first.push(true);
+		Ok(())
+	}
+
+	fn key(&mut self, key: &str, _: TagID) -> Result<(), NbtError> {
+		self.separate();
+		self.string(key);
+		self.output.push(':');
+		// The value of the entry follows the key without a separator.
+		if let Some(first) = self.first.last_mut() {
+			*first = true;
+		}
+		Ok(())
+	}
+
+	fn end_compound(&mut self) -> Result<(), NbtError> {
+		self.first.pop();
+		self.output.push('}');
+		Ok(())
+	}
+
+	fn begin_list(&mut self, _: Option<TagID>, _: usize) -> Result<(), NbtError> {
+		self.separate();
+		self.output.push('[');
+		self.first.push(true);
+		Ok(())
+	}
+
+	fn end_list(&mut self) -> Result<(), NbtError> {
+		self.first.pop();
+		self.output.push(']');
+		Ok(())
+	}
+
+	fn sorted_keys(&self) -> bool {
+		self.sort_keys
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, io::NbtSize };
+
+	#[test]
+	fn sink_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("name", "Steve \"the\" miner\n"),
+			("pos", list!(1.5, f64::NAN)),
+			("items", Tag::List(ListTag::Compound(vec![Map::from_iter([("id".to_owned(), Tag::Short(1))]), Map::default()]))),
+			("nested", Tag::List(ListTag::List(vec![ListTag::Empty, ListTag::from(vec![1i8, 2])]))),
+			("bytes", Tag::ByteArray(vec![-1, 2]))
+		);
+		let mut counting = CountingSink::new();
+		tag.encode_to(&mut counting)?;
+		assert_eq!(counting.bytes, tag.nbt_size());
+		assert_eq!(counting.tags, 15);
+		assert_eq!(counting.max_depth, 3);
+
+		let mut json = JsonSink::new().sort_keys(true);
+		tag.encode_to(&mut json)?;
+		assert_eq!(json.as_str(), concat!(
+			r#"{"bytes":[-1,2],"items":[{"id":1},{}],"name":"Steve \"the\" miner\n","#,
+			r#""nested":[[],[1,2]],"pos":[1.5,null]}"#,
+		));
+		let mut json = JsonSink::new();
+		list!(1, 2).encode_to(&mut json)?;
+		assert_eq!(json.into_string(), "[1,2]");
+		Ok(())
+	}
+}
@@ -17,23 +17,57 @@ Minecraft SNBT, and will not cover the Tag type extensions.
 |[Tag::IntArray]  | `[I; 0, 1, 2 3]`
 |[Tag::LongArray] | `[L; 0, 1, 2l, 3L]`
 
-Note: Identifiers can include the following characters: [a-zA-Z0-9+-._].
+Note: Identifiers can include the following characters: [a-zA-Z0-9+-._]. [parse_with] can also allow letters and digits
+of other scripts with [crate::parse::UnquotedText].
+Unquoted words are numbers only if they have the forms the game reads as numbers, which allow a leading `+`,
+decimals such as `1.` and `.5`, and exponents such as `1.5e3`. Other words are Strings, even if they start like a
+number, such as `1.5.pack`, `007`, and `1e5`. [word_kind] tells how a word is read.
 For [Tag::List], the tag type for the list is determined by the type of the first tag.
+
+[Tag::parse] and [parse_with] read SNBT with a hand-written parser. The `snbt-diagnostics` feature, which is on by
+default, adds the `Token` lexer and `parse_partial`, which recover from errors to report as many of them as they can.
+They use chumsky, so turning the feature off drops that dependency without changing how SNBT is parsed.
+[parse_spanned] reads the same syntax into a tree that keeps the span of every tag, for editors and validators.
+
+Floats and Doubles written with [FloatFormat::Shortest] or [FloatFormat::Java] read back with the same bits, including
+`-0.0` and subnormal values. NaN and infinities have no SNBT form: they are written like the game writes them, such as
+`NaNd` and `-Infinityf`, and are read back as Strings, as the game reads them. Binary NBT keeps every bit of every
+Float and Double, including the sign and payload of NaN.
 "#]
 
+// Chumsky combinators are cloned by convention when they are reused. Some of them
+// happen to be Copy, which clippy would otherwise complain about.
+#![allow(clippy::clone_on_copy, clippy::result_large_err)]
+
 use crate::*;
 use crate::tag::*;
-use chumsky::prelude::*;
-use chumsky::primitive::{
-	Container,
-	OneOf,
-	NoneOf,
+use crate::path::{ PathSegment, TagPath };
+use crate::parse::{ NumberRange, ParseOptions, ParseOutcome, Surrogates, UnquotedText, Warning };
+use crate::sink::{ NbtSink, Scalar };
+use crate::source::{ self, NbtSource };
+use crate::stream::Event;
+use crate::view::NumberView;
+#[cfg(feature = "snbt-diagnostics")]
+use chumsky::{
+	prelude::*,
+	primitive::{
+		Container,
+		OneOf,
+		NoneOf,
+	},
+	Error,
+	Stream,
+};
+#[cfg(feature = "snbt-diagnostics")]
+use std::{
+	collections::HashSet,
+	hash::Hash,
 };
-use chumsky::Error;
-use std::collections::HashSet;
 use std::fmt::{Write, Display};
+use std::ops::Range;
 use std::str::FromStr;
 
+#[cfg(feature = "snbt-diagnostics")]
 #[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Hash, Debug)]
 pub enum Token {
 	Comma,
@@ -57,6 +91,7 @@ pub enum ArrayType {
 	Long = 'L' as isize,
 }
 
+#[cfg(feature = "snbt-diagnostics")]
 #[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Hash, Debug)]
 pub enum IntegerType {
 	Byte,
@@ -65,12 +100,37 @@ pub enum IntegerType {
 	Long,
 }
 
+#[cfg(feature = "snbt-diagnostics")]
 #[derive(PartialEq, Eq,PartialOrd, Ord, Clone, Hash, Debug)]
 pub enum DecimalType {
 	Float,
 	Double,
 }
 
+#[cfg(feature = "snbt-diagnostics")]
+impl IntegerType {
+	/// The type of the tags that integers of this type are read as.
+	pub fn id(&self) -> TagID {
+		match self {
+			IntegerType::Byte => TagID::Byte,
+			IntegerType::Short => TagID::Short,
+			IntegerType::Int => TagID::Int,
+			IntegerType::Long => TagID::Long,
+		}
+	}
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+impl DecimalType {
+	/// The type of the tags that decimals of this type are read as.
+	pub fn id(&self) -> TagID {
+		match self {
+			DecimalType::Float => TagID::Float,
+			DecimalType::Double => TagID::Double,
+		}
+	}
+}
+
 // I made it easier to make the lexer. Since there is a lot of boilerplate involved, I wrote
 // a macro that allows me to bypass writing all the error-prone boilerplate.
 // It also allows me to generate a parse function that will parse in the order that I define
@@ -79,6 +139,7 @@ pub enum DecimalType {
 // First you have the name that you want to apply to the function, then "=>", then a block
 // for the parser:
 //     name => { /* parser initialization */ }
+#[cfg(feature = "snbt-diagnostics")]
 macro_rules! token_parse_functions {
 	($($name:ident => $block:block)+) => {
 		impl Token {
@@ -99,10 +160,58 @@ macro_rules! token_parse_functions {
 				.collect::<Vec<Token>>()
 				.parse(source.as_ref())
 			}
+
+			/// Reads tokens with [LexOptions], returning them with their spans in characters, along with the errors
+			/// that were found.
+			pub fn lex(source: &str, options: &LexOptions) -> Lexed {
+				let token = choice((
+					$(
+						Self::$name(),
+					)+
+				))
+				.map_with_span(|token, span| (token, span));
+				let end = source.chars().count();
+				let mut tokens = Vec::new();
+				let mut errors = Vec::new();
+				let (mut offset, mut rest) = (0, source);
+				// Every attempt reads from a stream of the remaining characters, so the spans stay absolute.
+				fn input(end: usize, offset: usize, rest: &str) -> Stream<'_, char, Range<usize>, impl Iterator<Item = (char, Range<usize>)> + '_> {
+					let chars = rest.chars().enumerate().map(move |(index, c)| (c, offset + index..offset + index + 1));
+					Stream::from_iter(end..end, chars)
+				}
+				loop {
+					// Read as many tokens as possible at once, then read the next one alone to find out why it failed.
+					let (read, _) = (&token).padded().repeated().parse_recovery(input(end, offset, rest));
+					if let Some((_, span)) = read.as_ref().and_then(|read| read.last()) {
+						rest = &rest[rest.char_indices().nth(span.end - offset).map_or(rest.len(), |(index, _)| index)..];
+						offset = span.end;
+					}
+					tokens.extend(read.into_iter().flatten());
+					let trimmed = rest.trim_start();
+					offset += rest[..rest.len() - trimmed.len()].chars().count();
+					rest = trimmed;
+					if rest.is_empty() {
+						break;
+					}
+					match token.parse(input(end, offset, rest)) {
+						Ok(read) => tokens.push(read),
+						Err(found) => {
+							errors.extend(found);
+							if !options.recover {
+								break;
+							}
+							offset += 1;
+							rest = &rest[rest.chars().next().map_or(0, char::len_utf8)..];
+						}
+					}
+				}
+				Lexed { tokens, errors: options.report(errors) }
+			}
 		}
 	};
 }
 
+#[cfg(feature = "snbt-diagnostics")]
 token_parse_functions!{
 	comma => { just(',').to(Token::Comma).labelled("Comma") }
 	colon => { just(':').to(Token::Colon).labelled("Colon") }
@@ -123,70 +232,35 @@ token_parse_functions!{
 	close_bracket => { just(']').to(Token::CloseBracket).labelled("Close Bracket") }
 	open_brace => { just('{').to(Token::OpenBrace).labelled("Open Brace") }
 	close_brace => { just('}').to(Token::CloseBrace).labelled("Close Brace") }
+	// Booleans and numbers are unquoted words that [word_kind] reads as them, so that a word such as `1.5.pack` is one
+	// Identifier rather than a number followed by more.
 	boolean => {
-		choice((
-			text::keyword("true").to(Token::Boolean(true)),
-			text::keyword("false").to(Token::Boolean(false)),
-		))
-		.labelled("Boolean")
+		identifier()
+			.try_map(|word: String, span| match word_kind(&word) {
+				WordKind::Boolean(value) => Ok(Token::Boolean(value)),
+				_ => Err(Simple::custom(span, "Expected a Boolean.")),
+			})
+			.labelled("Boolean")
 	}
 	// If I want, I can add binary and hex literals.
 	integer => {
-		just::<char, _, Simple<char>>('-')
-			.or_not()
-			.chain::<char, _, _>(text::int(10))
-			.collect::<String>()
-			.then(
-				choice((
-					keyword("b", true).to(IntegerType::Byte),
-					keyword("s", true).to(IntegerType::Short),
-					keyword("l", true).to(IntegerType::Long),
-				))
-				.or_not()
-				.map(|opt| opt.unwrap_or(IntegerType::Int))
-			)
-			.then_ignore(choice((
-				filter(|c: &char| {
-					!c.is_alphanumeric() && !['_', '+','-','.'].contains(c)
-				}),
-				end().to('\0')
-			)).rewind())
-			.map(|(int_text, int_type)| Token::Integer(int_text, int_type))
+		identifier()
+			.try_map(|word: String, span| match number_form(&word) {
+				Some((digits, TagID::Byte)) => Ok(Token::Integer(digits.to_owned(), IntegerType::Byte)),
+				Some((digits, TagID::Short)) => Ok(Token::Integer(digits.to_owned(), IntegerType::Short)),
+				Some((digits, TagID::Int)) => Ok(Token::Integer(digits.to_owned(), IntegerType::Int)),
+				Some((digits, TagID::Long)) => Ok(Token::Integer(digits.to_owned(), IntegerType::Long)),
+				_ => Err(Simple::custom(span, "Expected an Integer.")),
+			})
 			.labelled("Integer")
 	}
 	decimal => {
-		just::<char, _, Simple<char>>('-').or_not()
-			.chain::<char,_,_>(
-				choice((
-					text::int(10)
-						.chain::<char,_,_>(just('.'))
-						.chain::<char,_,_>(text::digits(10))
-						.collect::<String>(),
-					text::int(10)
-						.then_ignore(
-							choice((
-								keyword("d", true),
-								keyword("f", true),
-							)).rewind()
-						),
-				))
-			)
-			.collect::<String>()
-			.then(
-				choice((
-					keyword("d", true).to(DecimalType::Double),
-					keyword("f", true).to(DecimalType::Float),
-				))
-				.or_not()
-				.map(|opt| opt.unwrap_or(DecimalType::Double))
-			)
-			.then_ignore(choice((
-				filter(|c: &char| {
-					!c.is_alphanumeric() && !['_', '+','-','.'].contains(c)
-				}),
-				end().to('\0')
-			)).rewind())
-			.map(|(dec_str, dec_type)| Token::Decimal(dec_str, dec_type))
+		identifier()
+			.try_map(|word: String, span| match number_form(&word) {
+				Some((digits, TagID::Float)) => Ok(Token::Decimal(digits.to_owned(), DecimalType::Float)),
+				Some((digits, TagID::Double)) => Ok(Token::Decimal(digits.to_owned(), DecimalType::Double)),
+				_ => Err(Simple::custom(span, "Expected a Decimal.")),
+			})
 			.labelled("Decimal")
 	}
 	identifier => {
@@ -200,6 +274,20 @@ token_parse_functions!{
 		.labelled("Identifier")
 	}
 	string_literal => {
+		let code_unit = filter::<_,_,Simple<char>>(char::is_ascii_hexdigit)
+			.repeated().exactly(4)
+			.collect::<String>()
+			.map(|digits| u16::from_str_radix(&digits, 16).expect("Four hexadecimal digits are a u16."));
+		// A character outside of the Basic Multilingual Plane is a surrogate pair in two escapes. Unpaired surrogates can
+		// not be in a String.
+		let unicode = just('u').ignore_then(code_unit.clone()).then_ignore(just("\\u")).then(code_unit.clone())
+			.try_map(|(high, low), span| match char::decode_utf16([high, low]).next() {
+				Some(Ok(c)) if u32::from(c) > 0xFFFF => Ok(c),
+				_ => Err(Simple::custom(span, "Not a surrogate pair.")),
+			})
+			.or(just('u').ignore_then(code_unit).try_map(|unit, span| {
+				char::from_u32(unit.into()).ok_or_else(|| Simple::custom(span, "An unpaired surrogate can not be in a String."))
+			}));
 		let escape = just::<_,_,Simple<char>>('\\').ignore_then(
 			just('\\')
 				.or(just('/'))
@@ -210,6 +298,7 @@ token_parse_functions!{
 				.or(just('n').to('\n'))
 				.or(just('r').to('\r'))
 				.or(just('t').to('\t'))
+				.or(unicode)
 		);
 		Token::identifier().or(
 			choice::<_,Simple<char>>((
@@ -230,8 +319,84 @@ token_parse_functions!{
 	}
 }
 
-/// Returns a parser that takes [Token] as input and returns a [Tag].
-fn parser() -> impl Parser<Token, Tag, Error = Simple<Token>> {
+/// Options for [Token::lex] and [parse_partial].
+#[cfg(feature = "snbt-diagnostics")]
+#[derive(Clone, Debug, Default)]
+pub struct LexOptions {
+	/// The most errors to report. The errors after it are dropped, but lexing goes on.
+	pub max_errors: Option<usize>,
+	/// Whether errors whose spans touch or overlap are reported as one error, expecting any of what they expected.
+	/// Recovering from a run of invalid characters reports an error for each of them otherwise.
+	pub merge_adjacent: bool,
+	/// Whether to skip a character that does not start a token and go on lexing, rather than stop at the first error.
+	pub recover: bool,
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+impl LexOptions {
+	/// The default options, which stop at the first error.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Options for diagnostics, which recover from errors and merge adjacent ones.
+	pub fn diagnostics() -> Self {
+		Self::new().recover(true).merge_adjacent(true)
+	}
+
+	/// Returns these options with a different maximum error count.
+	pub fn max_errors(mut self, max_errors: Option<usize>) -> Self {
+		self.max_errors = max_errors;
+		self
+	}
+
+	/// Returns these options with adjacent errors merged or not.
+	pub fn merge_adjacent(mut self, merge_adjacent: bool) -> Self {
+		self.merge_adjacent = merge_adjacent;
+		self
+	}
+
+	/// Returns these options with recovery from errors or not.
+	pub fn recover(mut self, recover: bool) -> Self {
+		self.recover = recover;
+		self
+	}
+
+	/// Orders errors by where they start, then merges and caps them.
+	fn report<I: Hash + Eq + Clone>(&self, mut errors: Vec<Simple<I>>) -> Vec<Simple<I>> {
+		errors.sort_by_key(|error| error.span().start);
+		if self.merge_adjacent {
+			let mut merged: Vec<Simple<I>> = Vec::with_capacity(errors.len());
+			for error in errors {
+				match merged.last_mut() {
+					Some(last) if error.span().start <= last.span().end => {
+						let span = last.span().start..last.span().end.max(error.span().end);
+						let expected: Vec<_> = last.expected().chain(error.expected()).cloned().collect();
+						*last = Simple::expected_input_found(span, expected, last.found().cloned());
+					}
+					_ => merged.push(error),
+				}
+			}
+			errors = merged;
+		}
+		if let Some(max_errors) = self.max_errors {
+			errors.truncate(max_errors);
+		}
+		errors
+	}
+}
+
+/// The tokens read by [Token::lex], with their spans in characters, and the errors that were found.
+#[cfg(feature = "snbt-diagnostics")]
+#[derive(Clone, Debug, Default)]
+pub struct Lexed {
+	pub tokens: Vec<(Token, Range<usize>)>,
+	pub errors: Vec<Simple<char>>,
+}
+
+/// Returns a parser that takes [Token] as input and returns a [Node].
+#[cfg(feature = "snbt-diagnostics")]
+fn parser() -> impl Parser<Token, Node, Error = Simple<Token>> {
 	// Macros rule!
 	macro_rules! num_parsers {
 		($(let $name:ident = Token::$token_type:ident($subtype:path) => $type:ty;)+) => {
@@ -240,7 +405,10 @@ fn parser() -> impl Parser<Token, Tag, Error = Simple<Token>> {
 					.try_map(|token, span| {
 						match token {
 							Token::$token_type(digits, $subtype) => {
-								digits.parse::<$type>().map_err(|_| Simple::custom(span, concat!("Failed to parse.")))
+								digits.parse::<$type>().map_err(|_| {
+									let id = $subtype.id();
+									Simple::custom(span, out_of_range(&format!("{digits}{}", integer_suffix(id)), id))
+								})
 							},
 							_ => Err(Simple::custom(span, "Invalid token.")),
 						}
@@ -295,76 +463,569 @@ fn parser() -> impl Parser<Token, Tag, Error = Simple<Token>> {
 	let mut list = Recursive::declare();
 	let mut compound = Recursive::declare();
 
-	let tag_match = choice((
-		compound.clone().map(Tag::Compound),
-		list.clone().map(Tag::List),
-		byte.clone().map(Tag::Byte),
-		short.clone().map(Tag::Short),
-		int.clone().map(Tag::Int),
-		long.clone().map(Tag::Long),
-		float.clone().map(Tag::Float),
-		double.clone().map(Tag::Double),
-		bytearray.clone().map(Tag::ByteArray),
-		intarray.clone().map(Tag::IntArray),
-		longarray.clone().map(Tag::LongArray),
-		string.clone().map(Tag::String)
+	let node = choice((
+		compound.clone(),
+		list.clone(),
+		byte.clone().map(Tag::Byte).map(Node::Tag),
+		short.clone().map(Tag::Short).map(Node::Tag),
+		int.clone().map(Tag::Int).map(Node::Tag),
+		long.clone().map(Tag::Long).map(Node::Tag),
+		float.clone().map(Tag::Float).map(Node::Tag),
+		double.clone().map(Tag::Double).map(Node::Tag),
+		bytearray.clone().map(Tag::ByteArray).map(Node::Tag),
+		intarray.clone().map(Tag::IntArray).map(Node::Tag),
+		longarray.clone().map(Tag::LongArray).map(Node::Tag),
+		string.clone().map(Tag::String).map(Node::Tag),
 	));
 
-	macro_rules! list_maker {
-		($([$pattern:expr]),+) => {
-			choice::<_,Simple<Token>>((
-				$(
-					($pattern)
-						.separated_by(just(Token::Comma))
-						.allow_trailing()
-						.delimited_by(just(Token::OpenBracket), just(Token::CloseBracket))
-						.map(ListTag::from),
-				)+
-			))
-		};
-	}
-
 	list.define(
-		list_maker!{
-			[byte.clone()],
-			[short.clone()],
-			[int.clone()],
-			[long.clone()],
-			[float.clone()],
-			[double.clone()],
-			[bytearray.clone()],
-			[string.clone()],
-			[list.clone()],
-			[compound.clone()],
-			[intarray.clone()],
-			[longarray.clone()]
-		}
+		node.clone()
+			.separated_by(just(Token::Comma))
+			.allow_trailing()
+			.delimited_by(just(Token::OpenBracket), just(Token::CloseBracket))
+			.map(Node::List)
+			// A List with errors is read as empty, so that the rest of the tree can still be read by [parse_partial].
+			.recover_with(nested_delimiters(Token::OpenBracket, Token::CloseBracket, [(Token::OpenBrace, Token::CloseBrace)], |_| Node::List(Vec::new())))
 	);
 
 	compound.define(
 		string.clone()
 			.then_ignore(just(Token::Colon))
-			.then(tag_match.clone())
+			.then(node.clone())
 			.separated_by(just(Token::Comma))
 			.allow_trailing()
 			.delimited_by(just(Token::OpenBrace), just(Token::CloseBrace))
-			.map(crate::Map::from_iter)
+			.map(Node::Compound)
+			.recover_with(nested_delimiters(Token::OpenBrace, Token::CloseBrace, [(Token::OpenBracket, Token::CloseBracket)], |_| Node::Compound(Vec::new())))
 	);
 
-	choice((
-		compound.clone().map(Tag::Compound),
-		list.clone().map(Tag::List),
-		byte.clone().map(Tag::Byte),
-		short.clone().map(Tag::Short),
-		int.clone().map(Tag::Int),
-		long.clone().map(Tag::Long),
-		float.clone().map(Tag::Float),
-		double.clone().map(Tag::Double),
-		bytearray.clone().map(Tag::ByteArray),
-		intarray.clone().map(Tag::IntArray),
-		longarray.clone().map(Tag::LongArray),
-		string.clone().map(Tag::String)
-	))
+	node
+}
+
+/// Reads SNBT straight into [Node]s by recursive descent, in a single pass over the characters.<br>
+/// It reads the same syntax as [Token::lex] and [parser] together, except that Compound keys may be any unquoted word,
+/// but without materializing the tokens in between.
+struct Reader<'a> {
+	source: &'a str,
+	/// The byte offset of the next character.
+	position: usize,
+	/// What to do with integers that are out of the range of their type.
+	range: NumberRange,
+	/// Which characters unquoted words can have.
+	unquoted: UnquotedText,
+	/// What to do with unpaired surrogates in `\u` escapes.
+	surrogates: Surrogates,
+	/// Whether to read with the rules of the game. See [ParseOptions::vanilla_strict].
+	strict: bool,
+	/// How deeply Compounds, Lists, and arrays may be nested, and how deeply the node being read is.
+	max_depth: usize,
+	depth: usize,
+	/// The quirks that were tolerated while reading, such as integers that were out of range.
+	warnings: Vec<Warning>,
+	/// Where everything that was read is, for [parse_spanned].
+	spans: Option<Spans>,
+}
+
+/// The byte ranges of what a [Reader] read, each in the order it was read.
+#[derive(Default)]
+struct Spans {
+	/// Every node, before the nodes inside of it.
+	nodes: Vec<Range<usize>>,
+	keys: Vec<Range<usize>>,
+	/// The elements of arrays.
+	elements: Vec<Range<usize>>,
+}
+
+impl<'a> Reader<'a> {
+	fn new(source: &'a str, options: &ParseOptions, spans: Option<Spans>) -> Self {
+		Reader {
+			source,
+			position: 0,
+			range: options.number_range,
+			unquoted: options.unquoted_text,
+			surrogates: options.surrogates,
+			strict: options.vanilla_strict,
+			max_depth: options.max_depth,
+			depth: 0,
+			warnings: Vec::new(),
+			spans,
+		}
+	}
+
+	fn rest(&self) -> &'a str {
+		&self.source[self.position..]
+	}
+
+	/// Skips whitespace and returns the next character without reading it.
+	fn skip_whitespace(&mut self) -> Option<char> {
+		let rest = self.rest();
+		let trimmed = rest.trim_start();
+		self.position += rest.len() - trimmed.len();
+		trimmed.chars().next()
+	}
+
+	/// The offset of the next character, in characters.
+	fn offset(&self) -> usize {
+		self.source[..self.position].chars().count()
+	}
+
+	/// An error at a byte offset.
+	fn error(&self, position: usize, message: String) -> ParseError {
+		ParseError::Syntax { offset: self.source[..position].chars().count(), message }
+	}
+
+	fn unexpected(&self, position: usize, found: Option<char>, expected: &str) -> ParseError {
+		match found {
+			Some(c) => self.error(position, format!("Expected {expected}, found {c:?}.")),
+			None => self.error(position, format!("Expected {expected}, found the end of the input.")),
+		}
+	}
+
+	/// Reads a character after skipping whitespace, if it is the expected one.
+	fn eat(&mut self, expected: char) -> bool {
+		if self.skip_whitespace() == Some(expected) {
+			self.position += expected.len_utf8();
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Reads the separator after an element of a List, Compound, or array, and returns true at the closing delimiter.
+	/// A trailing separator before the closing delimiter is allowed if `trailing` is set.
+	fn separator(&mut self, close: char, trailing: bool) -> Result<bool, ParseError> {
+		if self.eat(',') {
+			Ok(trailing && self.eat(close))
+		} else if self.eat(close) {
+			Ok(true)
+		} else {
+			Err(self.unexpected(self.position, self.rest().chars().next(), &format!("',' or '{close}'")))
+		}
+	}
+
+	fn node(&mut self) -> Result<Node, ParseError> {
+		let next = self.skip_whitespace();
+		let position = self.position;
+		// The span is reserved before the nodes inside of this one are read, and finished after.
+		let index = self.spans.as_mut().map(|spans| {
+			spans.nodes.push(position..position);
+			spans.nodes.len() - 1
+		});
+		if matches!(next, Some('{' | '[')) && self.depth >= self.max_depth {
+			// Checked before recursing, so deep input is an error instead of a stack overflow.
+			return Err(self.error(position, format!("Tags are nested deeper than {}.", self.max_depth)));
+		}
+		let node = match next {
+			Some('{') => self.nested(Self::compound)?,
+			Some('[') => self.nested(Self::list)?,
+			Some(quote @ ('"' | '\'')) => Node::Tag(Tag::String(self.quoted(quote)?)),
+			_ => {
+				let word = self.word()?;
+				Node::Tag(self.word_tag(position, word)?)
+			}
+		};
+		if let (Some(spans), Some(index)) = (&mut self.spans, index) {
+			spans.nodes[index].end = self.position;
+		}
+		Ok(node)
+	}
+
+	/// Reads a Compound, List, or array one level deeper.
+	fn nested(&mut self, read: fn(&mut Self) -> Result<Node, ParseError>) -> Result<Node, ParseError> {
+		self.depth += 1;
+		let node = read(self);
+		self.depth -= 1;
+		node
+	}
+
+	/// Records the span of a key or array element that started at a byte offset and ends at the current position.
+	fn record(&mut self, start: usize, spans: fn(&mut Spans) -> &mut Vec<Range<usize>>) {
+		if let Some(all) = &mut self.spans {
+			spans(all).push(start..self.position);
+		}
+	}
+
+	/// Returns the tag for a word that starts at a byte offset, handling integers that are out of range with the
+	/// [NumberRange] of the options.
+	fn word_tag(&mut self, position: usize, word: &str) -> Result<Tag, ParseError> {
+		let id = match word_kind(word) {
+			WordKind::Number(tag) => return Ok(tag),
+			WordKind::Boolean(value) => return Ok(Tag::Byte(value.into())),
+			WordKind::String => return Ok(Tag::String(word.to_owned())),
+			WordKind::OutOfRange(id) => id,
+		};
+		// The game read integers that are out of range as Strings before 1.21.5.
+		if self.strict {
+			return Ok(Tag::String(word.to_owned()));
+		}
+		let offset = self.source[..position].chars().count();
+		let length = word.chars().count();
+		let tag = match self.range {
+			NumberRange::Error => return Err(ParseError::OutOfRange { offset, length, value: word.to_owned(), id }),
+			NumberRange::Wrap => wrapping(word, id),
+			NumberRange::String => Tag::String(word.to_owned()),
+		};
+		self.warnings.push(Warning::NumberOutOfRange { offset, length, id, range: self.range });
+		Ok(tag)
+	}
+
+	fn compound(&mut self) -> Result<Node, ParseError> {
+		self.position += 1;
+		let mut entries = Vec::new();
+		if self.eat('}') {
+			return Ok(Node::Compound(entries));
+		}
+		loop {
+			let next = self.skip_whitespace();
+			let start = self.position;
+			let key = match next {
+				Some(quote @ ('"' | '\'')) => self.quoted(quote)?,
+				_ => self.word()?.to_owned(),
+			};
+			self.record(start, |spans| &mut spans.keys);
+			if !self.eat(':') {
+				return Err(self.unexpected(self.position, self.rest().chars().next(), "':'"));
+			}
+			entries.push((key, self.node()?));
+			if self.separator('}', true)? {
+				return Ok(Node::Compound(entries));
+			}
+		}
+	}
+
+	/// Reads a List, or an array if the bracket is followed by its type and a semicolon.
+	fn list(&mut self) -> Result<Node, ParseError> {
+		self.position += 1;
+		let mut chars = self.rest().chars();
+		if let (Some(kind), Some(';')) = (chars.next(), chars.next()) {
+			// The game reads any character but a quote before a semicolon as the type of an array, in uppercase.
+			let kind = if self.strict { kind } else { kind.to_ascii_uppercase() };
+			let array = match kind {
+				'B' => Some(ArrayType::Byte),
+				'I' => Some(ArrayType::Int),
+				'L' => Some(ArrayType::Long),
+				_ => None,
+			};
+			if let Some(array) = array {
+				self.position += 2;
+				return self.array(array).map(Node::Tag);
+			}
+			if self.strict && kind != '"' && kind != '\'' {
+				return Err(self.error(self.position, format!("Invalid array type {kind:?}. Arrays are [B;, [I;, or [L;.")));
+			}
+		}
+		let mut nodes = Vec::new();
+		if self.eat(']') {
+			return Ok(Node::List(nodes));
+		}
+		loop {
+			nodes.push(self.node()?);
+			if self.separator(']', true)? {
+				return Ok(Node::List(nodes));
+			}
+		}
+	}
+
+	fn array(&mut self, array: ArrayType) -> Result<Tag, ParseError> {
+		macro_rules! elements {
+			($name:literal, $variant:ident) => {{
+				let mut values = Vec::new();
+				if !self.eat(']') {
+					loop {
+						self.skip_whitespace();
+						let position = self.position;
+						let word = self.word()?;
+						self.record(position, |spans| &mut spans.elements);
+						match self.word_tag(position, word)? {
+							Tag::$variant(value) => values.push(value),
+							_ => return Err(self.error(position, format!("Expected {} in {} Array.", $name, stringify!($variant)))),
+						}
+						if self.separator(']', self.strict)? {
+							break;
+						}
+					}
+				}
+				values
+			}};
+		}
+		Ok(match array {
+			ArrayType::Byte => Tag::ByteArray(elements!("a Byte", Byte)),
+			ArrayType::Int => Tag::IntArray(elements!("an Int", Int)),
+			ArrayType::Long => Tag::LongArray(elements!("a Long", Long)),
+		})
+	}
+
+	/// Reads an unquoted word, made of the characters in [is_ident_char], and with [UnquotedText::Unicode], letters and
+	/// digits of any script.
+	fn word(&mut self) -> Result<&'a str, ParseError> {
+		let found = self.skip_whitespace();
+		let rest = self.rest();
+		let mut length = rest.find(|c: char| !is_ident_char(&c)).unwrap_or(rest.len());
+		if let Some(c) = rest[length..].chars().next().filter(|c| !c.is_ascii() && c.is_alphanumeric()) {
+			if self.unquoted == UnquotedText::Ascii {
+				let message = format!("{c:?} can only be in a quoted String. Unquoted Strings can only have ASCII letters, digits, and '_', '-', '.', and '+'.");
+				return Err(self.error(self.position + length, message));
+			}
+			length = rest.find(|c: char| !is_ident_char(&c) && !c.is_alphanumeric()).unwrap_or(rest.len());
+			let offset = self.offset();
+			self.warnings.push(Warning::UnquotedUnicode { offset, length: rest[..length].chars().count() });
+		}
+		if length == 0 {
+			return Err(self.unexpected(self.position, found, "a value"));
+		}
+		self.position += length;
+		Ok(&rest[..length])
+	}
+
+	/// Reads a String in quotes, unescaping it.
+	fn quoted(&mut self, quote: char) -> Result<String, ParseError> {
+		let start = self.position;
+		self.position += 1;
+		let mut value = String::new();
+		loop {
+			let rest = self.rest();
+			let Some(length) = rest.find([quote, '\\']) else {
+				return Err(self.error(start, "The String is not closed.".to_owned()));
+			};
+			value.push_str(&rest[..length]);
+			self.position += length + 1;
+			if rest[length..].starts_with(quote) {
+				return Ok(value);
+			}
+			if !self.strict && self.rest().starts_with('u') {
+				value.push(self.unicode_escape()?);
+				continue;
+			}
+			let escaped = match self.rest().chars().next() {
+				Some(c @ ('\\' | '"' | '\'')) if !self.strict || c == '\\' || c == quote => c,
+				Some(c) if self.strict => return Err(self.error(self.position, format!("Invalid escape sequence \\{c}. Only '\\' and {quote} can be escaped."))),
+				Some('/') => '/',
+				Some('b') => '\x08',
+				Some('f') => '\x0C',
+				Some('n') => '\n',
+				Some('r') => '\r',
+				Some('t') => '\t',
+				found => return Err(self.unexpected(self.position, found, "an escape sequence")),
+			};
+			value.push(escaped);
+			self.position += 1;
+		}
+	}
+
+	/// Reads a `\u` escape, from the `u` after the backslash, along with the escape of the low surrogate after a high
+	/// one. Unpaired surrogates are handled with the [Surrogates] of the options.
+	fn unicode_escape(&mut self) -> Result<char, ParseError> {
+		let start = self.position - 1;
+		let unit = self.code_unit()?;
+		if (0xD800..0xDC00).contains(&unit) && self.rest().starts_with("\\u") {
+			let position = self.position;
+			self.position += 1;
+			let low = self.code_unit()?;
+			if let Some(Ok(c)) = char::decode_utf16([unit, low]).next() {
+				return Ok(c);
+			}
+			// The second escape is not the rest of a pair, so it is read on its own.
+			self.position = position;
+		}
+		if let Some(c) = char::from_u32(unit.into()) {
+			return Ok(c);
+		}
+		let offset = self.source[..start].chars().count();
+		match self.surrogates {
+			Surrogates::Error => Err(self.error(start, format!("{} is an unpaired surrogate, which can not be in a String.", &self.source[start..self.position]))),
+			Surrogates::Replace => {
+				self.warnings.push(Warning::UnpairedSurrogate { offset });
+				Ok(char::REPLACEMENT_CHARACTER)
+			}
+		}
+	}
+
+	/// Reads the `u` and the four hexadecimal digits of a `\u` escape.
+	fn code_unit(&mut self) -> Result<u16, ParseError> {
+		let digits = self.rest().get(1..5).filter(|digits| digits.bytes().all(|digit| digit.is_ascii_hexdigit()));
+		let Some(unit) = digits.and_then(|digits| u16::from_str_radix(digits, 16).ok()) else {
+			return Err(self.error(self.position, "Expected four hexadecimal digits after \\u.".to_owned()));
+		};
+		self.position += 5;
+		Ok(unit)
+	}
+}
+
+/// How an unquoted SNBT word is read, from [word_kind].
+#[derive(Clone, PartialEq, Debug)]
+pub enum WordKind {
+	/// A number, such as `5b`, `-.5`, or `1.5e3f`.
+	Number(Tag),
+	/// `true` or `false` in any case, which are read as the Bytes 1 and 0.
+	Boolean(bool),
+	/// An integer that has the form of a number of a type but is out of its range, such as `128b`. It is handled with
+	/// the [NumberRange] of the options, and read as a String with [ParseOptions::vanilla_strict].
+	OutOfRange(TagID),
+	/// Anything else, which is read as a String, such as `1.5.pack`, `007`, or `1e5`.
+	String,
+}
+
+/// Returns how an unquoted word is read. Words are read like the game reads them: a word is a number only if it has
+/// one of the forms of numbers of the game, and is a String otherwise, even if it starts like a number.
+pub fn word_kind(word: &str) -> WordKind {
+	if word.eq_ignore_ascii_case("true") {
+		return WordKind::Boolean(true);
+	}
+	if word.eq_ignore_ascii_case("false") {
+		return WordKind::Boolean(false);
+	}
+	let Some((number, id)) = number_form(word) else {
+		return WordKind::String;
+	};
+	macro_rules! number {
+		($variant:ident) => {
+			number.parse().map_or(WordKind::OutOfRange(TagID::$variant), |value| WordKind::Number(Tag::$variant(value)))
+		};
+	}
+	// Floats and Doubles that are too large parse as infinity, so only integers can be out of range.
+	match id {
+		TagID::Byte => number!(Byte),
+		TagID::Short => number!(Short),
+		TagID::Long => number!(Long),
+		TagID::Float => number!(Float),
+		TagID::Double => number!(Double),
+		_ => number!(Int),
+	}
+}
+
+/// Returns the sign and digits of an unquoted word without its suffix, and the type of number it is, if it has one of
+/// the forms of numbers of the game:
+/// - Integers are `[-+]?(0|[1-9][0-9]*)`, followed by `b`, `s`, or `l` for Bytes, Shorts, and Longs.
+/// - Decimals are `[-+]?([0-9]+[.]?|[0-9]*[.][0-9]+)(e[-+]?[0-9]+)?`, followed by `f` or `d` for Floats and Doubles.
+///   Doubles without a suffix must have a point.
+///
+/// Suffixes and exponents are in any case.
+fn number_form(word: &str) -> Option<(&str, TagID)> {
+	let unsigned = word.strip_prefix(['+', '-']).unwrap_or(word);
+	let (body, suffix) = match unsigned.as_bytes().last() {
+		Some(suffix) if suffix.is_ascii_alphabetic() => (&unsigned[..unsigned.len() - 1], Some(suffix.to_ascii_lowercase())),
+		_ => (unsigned, None),
+	};
+	let id = match suffix {
+		Some(b'f') if is_decimal(body, false) => TagID::Float,
+		Some(b'd') if is_decimal(body, false) => TagID::Double,
+		Some(b'b') if is_integer(body) => TagID::Byte,
+		Some(b's') if is_integer(body) => TagID::Short,
+		Some(b'l') if is_integer(body) => TagID::Long,
+		None if is_integer(body) => TagID::Int,
+		None if is_decimal(body, true) => TagID::Double,
+		_ => return None,
+	};
+	Some((&word[..word.len() - unsigned.len() + body.len()], id))
+}
+
+/// Whether text is the digits of an integer, `0|[1-9][0-9]*`.
+fn is_integer(text: &str) -> bool {
+	match text.as_bytes() {
+		[b'0'] => true,
+		[b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+		_ => false,
+	}
+}
+
+/// Whether text is the digits of a decimal, `[0-9]+[.]?|[0-9]*[.][0-9]+` followed by an optional exponent,
+/// `e[-+]?[0-9]+`.
+fn is_decimal(text: &str, point_required: bool) -> bool {
+	let digits = |text: &str| text.bytes().take_while(u8::is_ascii_digit).count();
+	let integer = digits(text);
+	let (length, point) = match text[integer..].strip_prefix('.') {
+		Some(fraction) => (integer + 1 + digits(fraction), true),
+		None => (integer, false),
+	};
+	if length == usize::from(point) || (point_required && !point) {
+		return false;
+	}
+	match text[length..].strip_prefix(['e', 'E']) {
+		Some(exponent) => {
+			let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+			!exponent.is_empty() && digits(exponent) == exponent.len()
+		}
+		None => length == text.len(),
+	}
+}
+
+/// Wraps an integer word that is out of the range of its type to the width of the type, like a narrowing cast in Java.
+fn wrapping(word: &str, id: TagID) -> Tag {
+	let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+	let (negative, digits) = match digits.strip_prefix('-') {
+		Some(digits) => (true, digits),
+		None => (false, digits.strip_prefix('+').unwrap_or(digits)),
+	};
+	// Only the low 64 bits matter, and arithmetic modulo 2^64 keeps them exact.
+	let value = digits.bytes().fold(0u64, |value, digit| value.wrapping_mul(10).wrapping_add(u64::from(digit - b'0')));
+	let value = if negative { value.wrapping_neg() } else { value };
+	match id {
+		TagID::Byte => Tag::Byte(value as i8),
+		TagID::Short => Tag::Short(value as i16),
+		TagID::Long => Tag::Long(value as i64),
+		_ => Tag::Int(value as i32),
+	}
+}
+
+/// A value read by the parser, before Compound keys and List element types are checked with [ParseOptions].
+#[derive(Clone, Debug)]
+enum Node {
+	Tag(Tag),
+	List(Vec<Node>),
+	Compound(Vec<(String, Node)>),
+}
+
+/// Reports the values read by the parser as [Event]s, so that tags are built like those of any other [NbtSource].
+struct NodeSource {
+	root: Option<Node>,
+	stack: Vec<NodeFrame>,
+}
+
+enum NodeFrame {
+	Compound(std::vec::IntoIter<(String, Node)>),
+	List(std::vec::IntoIter<Node>),
+}
+
+impl NbtSource for NodeSource {
+	fn next_event(&mut self) -> Result<Option<Event>, NbtError> {
+		let (name, node) = match self.root.take() {
+			Some(node) => (None, node),
+			None => match self.stack.last_mut() {
+				None => return Ok(None),
+				Some(NodeFrame::Compound(entries)) => match entries.next() {
+					Some((key, node)) => (Some(key), node),
+					None => {
+						self.stack.pop();
+						return Ok(Some(Event::CompoundEnd));
+					}
+				},
+				Some(NodeFrame::List(nodes)) => match nodes.next() {
+					Some(node) => (None, node),
+					None => {
+						self.stack.pop();
+						return Ok(Some(Event::ListEnd));
+					}
+				},
+			},
+		};
+		Ok(Some(match node {
+			Node::Tag(tag) => Event::Value { name, tag },
+			Node::Compound(entries) => {
+				self.stack.push(NodeFrame::Compound(entries.into_iter()));
+				Event::CompoundStart { name }
+			}
+			Node::List(nodes) => {
+				let id = nodes.first().map(|node| match node {
+					Node::Tag(tag) => tag.id(),
+					Node::List(_) => TagID::List,
+					Node::Compound(_) => TagID::Compound,
+				});
+				let length = nodes.len();
+				self.stack.push(NodeFrame::List(nodes.into_iter()));
+				Event::ListStart { name, id, length }
+			}
+		}))
+	}
 }
 
 impl Tag {
@@ -403,85 +1064,978 @@ impl Tag {
 	/// }
 	/// ```
 	pub fn parse<S: AsRef<str>>(source: S) -> Result<Tag, ParseError> {
-		match Token::parse(source) {
-			Ok(tokens) => {
-				match parser().parse(tokens) {
-					Ok(tag) => Ok(tag),
-					Err(errors) => Err(ParseError::ParseFailure(errors)),
-				}
-			},
-			Err(errors) => Err(ParseError::TokenizeError(errors)),
-		}
+		parse_with(source, &ParseOptions::default()).map(ParseOutcome::into_value)
 	}
-}
-
-impl FromStr for Tag {
-	type Err = ParseError;
 
-	fn from_str(source: &str) -> Result<Self, Self::Err> {
-		Tag::parse(source)
+	/// Writes this tag as SNBT with the given options, like [to_string_with]. [Tag::parse] reads the text back as an
+	/// equal tag with every option, except for Floats and Doubles written with [FloatFormat::Fixed], NaN and
+	/// infinities, which have no SNBT form, and empty Lists of a type, such as an empty List of Compounds, which are
+	/// written as `[]` and read back as [ListTag::Empty]. The [Display] implementation writes
+	/// [pretty](WriteOptions::pretty) SNBT.
+	/// ```
+	/// # use rustnbt::{ compound, list, tag::Tag, snbt::{ QuoteStyle, SuffixCase, WriteOptions } };
+	/// let tag = compound!(("Tags", list!("stone", "true", "minecraft:stone")));
+	/// let options = WriteOptions::compact().quotes(QuoteStyle::Bare);
+	/// assert_eq!(tag.to_snbt(&options), r#"{Tags:[stone,"true","minecraft:stone"]}"#);
+	/// assert_eq!(Tag::parse(tag.to_snbt(&options)).unwrap(), tag);
+	/// let options = WriteOptions::compact().quotes(QuoteStyle::Always).suffixes(SuffixCase::Upper);
+	/// assert_eq!(compound!(("Count", 1i8)).to_snbt(&options), r#"{"Count":1B}"#);
+	/// ```
+	pub fn to_snbt(&self, options: &WriteOptions) -> String {
+		to_string_with(self, options)
 	}
 }
 
-fn is_ident_char(c: &char) -> bool {
-	c.is_ascii_alphanumeric() || ['_','-','+','.'].contains(c)
+/// Parses SNBT leniently, with [ParseOptions].<br>
+/// Duplicate keys are handled by the key collision policy of the options, Lists with elements of different types are
+/// an error unless [ParseOptions::allow_mixed_lists] is set, and text after the root tag is an error unless
+/// [ParseOptions::allow_trailing_data] is set.
+/// ### Example
+/// ```
+/// # use rustnbt::{ compound, parse::*, snbt::parse_with };
+/// let options = ParseOptions::new().allow_trailing_data(true);
+/// let outcome = parse_with("{a: 1, a: 2} garbage", &options).unwrap();
+/// assert_eq!(outcome.value, compound!(("a", 2)));
+/// assert!(matches!(outcome.warnings[..], [Warning::KeyCollision { .. }, Warning::TrailingData { offset: 13, length: 7 }]));
+/// ```
+pub fn parse_with<S: AsRef<str>>(source: S, options: &ParseOptions) -> Result<ParseOutcome<Tag>, ParseError> {
+	let mut options = options.clone();
+	let (node, trailing, _) = read(source.as_ref(), &mut options, None)?;
+	let tag = build(node, &mut options)?;
+	if let Some(trailing) = trailing {
+		options.warn(trailing);
+	}
+	Ok(options.finish(tag))
 }
 
-fn identifier<E: chumsky::Error<char>>() -> impl Parser<char, String, Error = E> {
-	filter::<char,_,E>(is_ident_char)
-		.repeated()
-		.at_least(1)
-		.collect::<String>()
+/// Parses the first SNBT value in `source` and returns it with the text after it, which starts right after the value.
+/// Unlike [Tag::parse], anything may follow the value, so SNBT can be read from the middle of a command.
+/// ### Example
+/// ```
+/// # use rustnbt::{ compound, snbt::parse_prefix };
+/// let (tag, rest) = parse_prefix("{Count: 2b} 10 replace").unwrap();
+/// assert_eq!(tag, compound!(("Count", 2i8)));
+/// assert_eq!(rest, " 10 replace");
+/// ```
+pub fn parse_prefix(source: &str) -> Result<(Tag, &str), ParseError> {
+	let mut options = ParseOptions::new();
+	let mut reader = Reader::new(source, &options, None);
+	let node = reader.node()?;
+	Ok((build(node, &mut options)?, &source[reader.position..]))
 }
 
-fn strcmp(ignore_case: bool, lhs: &str, rhs: &str, ) -> bool {
-	if lhs.len() != rhs.len() {
-		return false;
-	}
-	if ignore_case {
-		lhs.to_lowercase() == rhs.to_lowercase()
-	} else {
-		lhs == rhs
+/// Reads the [Node] tree of some SNBT, moving the warnings of the [Reader] into the options, and returns it with the
+/// warning about trailing data, if there is any and it is allowed, and the spans if `spans` is given.
+fn read(source: &str, options: &mut ParseOptions, spans: Option<Spans>) -> Result<(Node, Option<Warning>, Option<Spans>), ParseError> {
+	let mut reader = Reader::new(source, options, spans);
+	let node = reader.node()?;
+	for warning in reader.warnings.drain(..) {
+		options.warn(warning);
 	}
+	let trailing = match reader.skip_whitespace() {
+		None => None,
+		Some(_) if options.allow_trailing_data => {
+			let offset = reader.offset();
+			Some(Warning::TrailingData { offset, length: source[reader.position..].chars().count() })
+		}
+		found => return Err(reader.unexpected(reader.position, found, "the end of the input")),
+	};
+	Ok((node, trailing, reader.spans))
 }
 
-fn keyword<S: AsRef<str>>(word: S, ignore_case: bool) -> impl Parser<char, (), Error = Simple<char>> {
-	identifier()
-		.try_map(move |text, span| {
-			if strcmp(ignore_case, word.as_ref(), &text) {
-				Ok(())
-			} else {
-				Err(Simple::custom(span, format!("Expected keyword: {}, found {}", word.as_ref(), text)))
-			}
-		})
-}
-
-fn no_case<C: Container<char>>(chars: C) -> HashSet<char> {
-	chars.get_iter().fold(HashSet::new(), |mut set, c| {
-		set.insert(c);
-		if c.is_lowercase() {
-			set.extend(c.to_uppercase());
-		} else {
-			set.extend(c.to_lowercase());
-		}
-		set
-	})
+/// Builds the tag of a [Node] tree with the options.
+fn build(node: Node, options: &mut ParseOptions) -> Result<Tag, ParseError> {
+	let mut source = NodeSource { root: Some(node), stack: Vec::new() };
+	let (_, tag) = source::build(&mut source, options).map_err(|err| ParseError::Invalid(err.to_string()))?;
+	Ok(tag)
 }
 
-fn one_of_nc<C: Container<char>, E: Error<char>>(chars: C) -> OneOf<char,HashSet<char>,E> {
-	one_of(no_case(chars))
+/// A value read from SNBT with the span of its text, in characters.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Spanned<T> {
+	pub value: T,
+	pub span: Range<usize>,
 }
 
-fn none_of_nc<C: Container<char>, E: Error<char>>(chars: C) -> NoneOf<char,HashSet<char>,E> {
-	none_of(no_case(chars))
+/// A tag read by [parse_spanned], as it was written: every element of a List and every entry of a Compound has its
+/// own span, and so does every element of an array.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SpannedTag {
+	/// A number or a String.
+	Value(Tag),
+	/// A ByteArray, IntArray, or LongArray, with the span of each element.
+	Array(Tag, Vec<Range<usize>>),
+	List(Vec<Spanned<SpannedTag>>),
+	/// The entries in the order they were written, with the spans of their keys. Duplicate keys are kept.
+	Compound(Vec<(Spanned<String>, Spanned<SpannedTag>)>),
 }
 
-#[derive(Debug, thiserror::Error)]
+impl Spanned<SpannedTag> {
+	/// The span of the value at a path, or of the element of an array at the end of it.<br>
+	/// If a Compound has the same key twice, the last one is used, since that is the value [Tag::parse] keeps.
+	pub fn span(&self, path: &TagPath) -> Option<Range<usize>> {
+		let mut node = self;
+		let mut segments = path.segments().iter();
+		while let Some(segment) = segments.next() {
+			node = match (&node.value, segment) {
+				(SpannedTag::Compound(entries), PathSegment::Key(key)) => {
+					&entries.iter().rev().find(|(name, _)| name.value == *key)?.1
+				}
+				(SpannedTag::List(nodes), PathSegment::Index(index)) => nodes.get(*index)?,
+				(SpannedTag::Array(_, elements), PathSegment::Index(index)) if segments.len() == 0 => {
+					return elements.get(*index).cloned();
+				}
+				_ => return None,
+			};
+		}
+		Some(node.span.clone())
+	}
+
+	/// The path of the innermost value whose span contains a character offset, such as the one under the cursor of an
+	/// editor. Offsets on a key give the path of its value.
+	pub fn path_at(&self, offset: usize) -> Option<TagPath> {
+		if !self.span.contains(&offset) {
+			return None;
+		}
+		let mut path = TagPath::new();
+		let mut node = self;
+		loop {
+			node = match &node.value {
+				SpannedTag::Compound(entries) => {
+					match entries.iter().find(|(key, value)| key.span.contains(&offset) || value.span.contains(&offset)) {
+						Some((key, value)) => {
+							path.push(PathSegment::Key(key.value.clone()));
+							value
+						}
+						None => return Some(path),
+					}
+				}
+				SpannedTag::List(nodes) => match nodes.iter().position(|node| node.span.contains(&offset)) {
+					Some(index) => {
+						path.push(PathSegment::Index(index));
+						&nodes[index]
+					}
+					None => return Some(path),
+				},
+				SpannedTag::Array(_, elements) => {
+					if let Some(index) = elements.iter().position(|element| element.contains(&offset)) {
+						path.push(PathSegment::Index(index));
+					}
+					return Some(path);
+				}
+				SpannedTag::Value(_) => return Some(path),
+			};
+		}
+	}
+
+	/// Builds the [Tag] without spans, the way [Tag::parse] would, except that Lists with elements of different types
+	/// are wrapped like [ParseOptions::allow_mixed_lists] does.
+	pub fn to_tag(&self) -> Tag {
+		fn node(spanned: &Spanned<SpannedTag>) -> Node {
+			match &spanned.value {
+				SpannedTag::Value(tag) | SpannedTag::Array(tag, _) => Node::Tag(tag.clone()),
+				SpannedTag::List(nodes) => Node::List(nodes.iter().map(node).collect()),
+				SpannedTag::Compound(entries) => Node::Compound(entries.iter().map(|(key, value)| (key.value.clone(), node(value))).collect()),
+			}
+		}
+		build(node(self), &mut ParseOptions::new().allow_mixed_lists(true)).expect("Every List can be built when mixed Lists are allowed.")
+	}
+}
+
+/// Parses SNBT with [ParseOptions] like [parse_with], but returns a tree where every tag has the span of its text, so
+/// that editors can map between the tree and the text, and validators can point at the text of a problem.
+/// ### Example
+/// ```
+/// # use rustnbt::{ compound, path::TagPath, parse::ParseOptions, snbt::parse_spanned };
+/// let source = "{id: 'minecraft:stone', tag: {Lore: ['a', 'b']}}";
+/// let tree = parse_spanned(source, &ParseOptions::new()).unwrap().value;
+/// let lore = tree.span(&TagPath::new().key("tag").key("Lore").index(1)).unwrap();
+/// assert_eq!(&source[lore.clone()], "'b'");
+/// assert_eq!(tree.path_at(lore.start).unwrap().to_string(), "tag.Lore[1]");
+/// assert_eq!(tree.to_tag(), rustnbt::tag::Tag::parse(source).unwrap());
+/// ```
+pub fn parse_spanned<S: AsRef<str>>(source: S, options: &ParseOptions) -> Result<ParseOutcome<Spanned<SpannedTag>>, ParseError> {
+	let source = source.as_ref();
+	let mut options = options.clone();
+	let (node, trailing, spans) = read(source, &mut options, Some(Spans::default()))?;
+	let spans = spans.expect("Spans were recorded.");
+	// Builds the tag only to check it and to report what the options tolerated.
+	build(node.clone(), &mut options)?;
+	if let Some(trailing) = trailing {
+		options.warn(trailing);
+	}
+	// Byte offsets are turned into character offsets by finding them among the starts of the characters.
+	let starts: Vec<usize> = source.char_indices().map(|(index, _)| index).collect();
+	let chars = |ranges: Vec<Range<usize>>| ranges.into_iter().map(|range| {
+		let offset = |byte: usize| starts.partition_point(|start| *start < byte);
+		offset(range.start)..offset(range.end)
+	}).collect::<Vec<_>>().into_iter();
+	let (mut nodes, mut keys, mut elements) = (chars(spans.nodes), chars(spans.keys), chars(spans.elements));
+	Ok(options.finish(spanned(node, &mut nodes, &mut keys, &mut elements)))
+}
+
+/// Pairs a [Node] tree with the spans that were recorded while reading it, which are in the same order.
+fn spanned<I: Iterator<Item = Range<usize>>>(node: Node, nodes: &mut I, keys: &mut I, elements: &mut I) -> Spanned<SpannedTag> {
+	let span = nodes.next().expect("Every node has a span.");
+	let value = match node {
+		Node::Tag(tag @ (Tag::ByteArray(_) | Tag::IntArray(_) | Tag::LongArray(_))) => {
+			let length = match &tag {
+				Tag::ByteArray(values) => values.len(),
+				Tag::IntArray(values) => values.len(),
+				Tag::LongArray(values) => values.len(),
+				_ => unreachable!(),
+			};
+			SpannedTag::Array(tag, elements.take(length).collect())
+		}
+		Node::Tag(tag) => SpannedTag::Value(tag),
+		Node::List(list) => SpannedTag::List(list.into_iter().map(|node| spanned(node, nodes, keys, elements)).collect()),
+		Node::Compound(entries) => SpannedTag::Compound(entries.into_iter().map(|(key, node)| {
+			let key = Spanned { value: key, span: keys.next().expect("Every key has a span.") };
+			(key, spanned(node, nodes, keys, elements))
+		}).collect()),
+	};
+	Spanned { value, span }
+}
+
+/// What [parse_partial] could read of some SNBT.
+#[cfg(feature = "snbt-diagnostics")]
+#[derive(Clone, Debug)]
+pub struct PartialParse {
+	/// The tag that was read, with every Compound and List that had errors read as empty, or None if the root could
+	/// not be read at all.
+	pub tag: Option<Tag>,
+	pub lex_errors: Vec<Simple<char>>,
+	/// The errors of the parser, with spans in characters.
+	pub parse_errors: Vec<Simple<Token>>,
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+impl PartialParse {
+	/// Returns true if there were no errors.
+	pub fn is_clean(&self) -> bool {
+		self.lex_errors.is_empty() && self.parse_errors.is_empty()
+	}
+}
+
+/// Reads as much of some SNBT as possible for diagnostics, reporting every error rather than stopping at the first.
+/// <br>
+/// Invalid characters are skipped if [LexOptions::recover] is set, and the options also cap and merge the errors of
+/// the parser. Lists with elements of different types are allowed in the partial tag.
+/// ### Example
+/// ```
+/// # use rustnbt::{ Map, compound, list, tag::Tag, snbt::* };
+/// let partial = parse_partial("{a: [1, 2], b: {c: }, d: 'x' @ }", &LexOptions::diagnostics());
+/// assert_eq!(partial.tag, Some(compound!(("a", list!(1, 2)), ("b", Tag::Compound(Map::default())), ("d", "x"))));
+/// assert_eq!(partial.lex_errors[0].span(), 29..30);
+/// assert_eq!(partial.parse_errors[0].span(), 19..20);
+/// ```
+#[cfg(feature = "snbt-diagnostics")]
+pub fn parse_partial<S: AsRef<str>>(source: S, options: &LexOptions) -> PartialParse {
+	let source = source.as_ref();
+	let lexed = Token::lex(source, options);
+	let end = source.chars().count();
+	let (node, parse_errors) = parser().then_ignore(chumsky::primitive::end()).parse_recovery(Stream::from_iter(end..end, lexed.tokens.into_iter()));
+	let tag = node.and_then(|node| {
+		let mut source = NodeSource { root: Some(node), stack: Vec::new() };
+		source::build(&mut source, &mut ParseOptions::new().allow_mixed_lists(true)).ok().map(|(_, tag)| tag)
+	});
+	PartialParse { tag, lex_errors: lexed.errors, parse_errors: options.report(parse_errors) }
+}
+
+impl FromStr for Tag {
+	type Err = ParseError;
+
+	fn from_str(source: &str) -> Result<Self, Self::Err> {
+		Tag::parse(source)
+	}
+}
+
+/// How [SnbtWriter] writes Float and Double values. NaN and infinities are written as `NaN`, `Infinity`, and
+/// `-Infinity` in every format. See the [module](self) documentation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FloatFormat {
+	/// The shortest digits that read back as the same value, such as `0.1f` and `64d`.
+	#[default]
+	Shortest,
+	/// This many digits after the decimal point, such as `0.100f` with 3 digits. Values may not read back exactly.
+	Fixed(usize),
+	/// The same text as Java's `Float.toString` and `Double.toString`, which the game uses when it writes SNBT,
+	/// such as `64.0d` and `1.0E-5f`.
+	Java,
+}
+
+impl FloatFormat {
+	/// Writes a Float without its suffix.
+	pub fn format_f32(self, value: f32) -> String {
+		let mut text = String::new();
+		self.write_float(&mut text, value, value as f64).unwrap();
+		text
+	}
+
+	/// Writes a Double without its suffix.
+	pub fn format_f64(self, value: f64) -> String {
+		let mut text = String::new();
+		self.write_float(&mut text, value, value).unwrap();
+		text
+	}
+
+	/// Writes a Float or Double without its suffix. `wide` is the same value as an [f64].
+	fn write_float<W: Write + ?Sized, F: Display + std::fmt::LowerExp>(self, output: &mut W, value: F, wide: f64) -> std::fmt::Result {
+		// SNBT has no form for these, so they are written like Java writes them in every format.
+		if wide.is_nan() {
+			return output.write_str("NaN");
+		}
+		if wide.is_infinite() {
+			return output.write_str(if wide < 0.0 { "-Infinity" } else { "Infinity" });
+		}
+		match self {
+			FloatFormat::Shortest => write!(output, "{value}"),
+			FloatFormat::Fixed(digits) => write!(output, "{value:.digits$}"),
+			FloatFormat::Java => write_java_float(output, value, wide),
+		}
+	}
+}
+
+/// Text that is formatted on the stack, for the parts of a number that are laid out before they are written.
+struct Digits {
+	bytes: [u8; 40],
+	length: usize,
+}
+
+impl Digits {
+	fn new() -> Self {
+		Self { bytes: [0; 40], length: 0 }
+	}
+
+	fn as_str(&self) -> &str {
+		std::str::from_utf8(&self.bytes[..self.length]).expect("Only strings are written.")
+	}
+}
+
+impl Write for Digits {
+	fn write_str(&mut self, text: &str) -> std::fmt::Result {
+		let end = self.length + text.len();
+		self.bytes.get_mut(self.length..end).ok_or(std::fmt::Error)?.copy_from_slice(text.as_bytes());
+		self.length = end;
+		Ok(())
+	}
+}
+
+/// Lays out the shortest digits of a value like Java does: plain notation with at least one digit after the point
+/// for magnitudes from 10^-3 up to 10^7, and `E` notation otherwise.
+fn write_java_float<W: Write + ?Sized, F: Display + std::fmt::LowerExp>(output: &mut W, value: F, wide: f64) -> std::fmt::Result {
+	let mut digits = Digits::new();
+	let magnitude = wide.abs();
+	if magnitude == 0.0 || (1e-3..1e7).contains(&magnitude) {
+		write!(digits, "{value}")?;
+		output.write_str(digits.as_str())?;
+		return if digits.as_str().contains('.') { Ok(()) } else { output.write_str(".0") };
+	}
+	write!(digits, "{value:e}")?;
+	let (mantissa, exponent) = digits.as_str().split_once('e').expect("Exponent notation has an exponent.");
+	let point = if mantissa.contains('.') { "" } else { ".0" };
+	write!(output, "{mantissa}{point}E{exponent}")
+}
+
+/// Writes a number as SNBT, with the suffix of its type, such as `5b`, `12`, or `0.1f`, using `floats` for Floats and
+/// Doubles.<br>
+/// This is what [SnbtWriter] writes numbers with. Numbers are formatted straight into `output`, without allocating,
+/// and never depend on the locale, so it is fast for arrays and Lists with millions of numbers.
+/// ```
+/// # use rustnbt::{ snbt::{ fmt_tag_number, FloatFormat }, view::NumberView };
+/// let mut text = String::new();
+/// fmt_tag_number(&mut text, NumberView::Long(-3), FloatFormat::Shortest).unwrap();
+/// fmt_tag_number(&mut text, NumberView::Double(1e-5), FloatFormat::Java).unwrap();
+/// assert_eq!(text, "-3L1.0E-5d");
+/// ```
+pub fn fmt_tag_number<W: Write + ?Sized>(output: &mut W, number: crate::view::NumberView, floats: FloatFormat) -> std::fmt::Result {
+	write_number(output, number, floats, SuffixCase::Mixed)
+}
+
+/// Writes a number like [fmt_tag_number], with its suffix in the given case.
+fn write_number<W: Write + ?Sized>(output: &mut W, number: NumberView, floats: FloatFormat, suffixes: SuffixCase) -> std::fmt::Result {
+	match number {
+		NumberView::Byte(value) => write!(output, "{value}{}", suffixes.apply('b')),
+		NumberView::Short(value) => write!(output, "{value}{}", suffixes.apply('s')),
+		NumberView::Int(value) => write!(output, "{value}"),
+		NumberView::Long(value) => write!(output, "{value}{}", suffixes.apply('L')),
+		NumberView::Float(value) => {
+			floats.write_float(output, value, value as f64)?;
+			output.write_char(suffixes.apply('f'))
+		}
+		NumberView::Double(value) => {
+			floats.write_float(output, value, value)?;
+			output.write_char(suffixes.apply('d'))
+		}
+	}
+}
+
+/// Options for writing SNBT with [to_string_with].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct WriteOptions {
+	/// The text to indent nested values with, or `None` to write everything on one line.
+	pub indent: Option<String>,
+	/// Writes the keys of every Compound in sorted order.
+	pub sort_keys: bool,
+	pub floats: FloatFormat,
+	pub escapes: Escapes,
+	pub quotes: QuoteStyle,
+	pub suffixes: SuffixCase,
+	/// Writes keys, Strings, and ByteArrays like the game does before 1.21.5: keys are only quoted if they have
+	/// characters that unquoted Strings can not have, Strings are quoted with the quote that they do not start with
+	/// and only backslashes and that quote are escaped, whatever the [Escapes] are, and Bytes in arrays end with `B`.
+	pub vanilla_strict: bool,
+}
+
+/// Which keys and Strings the SNBT writers write without quotes. Only words that read back as the same key or String
+/// are written without quotes, so `true`, `1b`, and `minecraft:stone` are always quoted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum QuoteStyle {
+	/// Keys are written without quotes when they can be, and Strings are always quoted, such as `{id:"stone"}`.
+	#[default]
+	BareKeys,
+	/// Every key and String is quoted, such as `{"id":"stone"}`.
+	Always,
+	/// Keys and Strings are both written without quotes when they can be, such as `{id:stone}`.
+	Bare,
+}
+
+/// The case of the suffixes of numbers and of the elements of ByteArrays and LongArrays. The parsers read suffixes
+/// in any case.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SuffixCase {
+	/// `b`, `s`, `f`, and `d`, and `L` for Longs, since `l` looks like `1`.
+	#[default]
+	Mixed,
+	/// `b`, `s`, `l`, `f`, and `d`.
+	Lower,
+	/// `B`, `S`, `L`, `F`, and `D`.
+	Upper,
+}
+
+impl SuffixCase {
+	/// Returns a suffix, which is given in the [Mixed](SuffixCase::Mixed) case, in this case.
+	fn apply(self, suffix: char) -> char {
+		match self {
+			SuffixCase::Mixed => suffix,
+			SuffixCase::Lower => suffix.to_ascii_lowercase(),
+			SuffixCase::Upper => suffix.to_ascii_uppercase(),
+		}
+	}
+}
+
+/// Which characters of Strings and keys the SNBT writers escape, besides quotes and backslashes. Every escape is read
+/// back by [Tag::parse], and by the game since 1.21.5.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Escapes {
+	/// Only newlines, carriage returns, and tabs, as `\n`, `\r`, and `\t`. Other characters are written as they are.
+	#[default]
+	Raw,
+	/// Also every other control character, as `\b`, `\f`, or `\uXXXX`, so the text has no invisible characters.
+	Control,
+	/// Also every character that is not ASCII, as `\uXXXX`, so the text is ASCII. Characters outside of the Basic
+	/// Multilingual Plane are written as the two escapes of a surrogate pair, like Java and JSON write them.
+	NonAscii,
+}
+
+impl WriteOptions {
+	/// The options of [to_string].
+	pub fn compact() -> Self {
+		Self::default()
+	}
+
+	/// The options of [to_string_pretty].
+	pub fn pretty() -> Self {
+		Self { indent: Some("    ".to_owned()), ..Self::default() }
+	}
+
+	/// The options of [to_string_canonical].
+	pub fn canonical() -> Self {
+		Self { sort_keys: true, ..Self::pretty() }
+	}
+
+	/// The options that write the same text as the game does before 1.21.5 with `toString`, such as
+	/// `{Items:[{Count:1b,id:"minecraft:stone"}],Pos:[0.5d,64.0d,0.5d],Tags:[B;1B]}`. The keys are sorted, like the
+	/// game sorts them, and Floats and Doubles are written like Java does. It can be read back with
+	/// [ParseOptions::vanilla_strict].
+	pub fn vanilla() -> Self {
+		Self { sort_keys: true, floats: FloatFormat::Java, vanilla_strict: true, ..Self::compact() }
+	}
+
+	/// Returns these options with a different [FloatFormat].
+	pub fn floats(mut self, floats: FloatFormat) -> Self {
+		self.floats = floats;
+		self
+	}
+
+	/// Returns these options with different [Escapes].
+	pub fn escapes(mut self, escapes: Escapes) -> Self {
+		self.escapes = escapes;
+		self
+	}
+
+	/// Returns these options with a different [QuoteStyle].
+	pub fn quotes(mut self, quotes: QuoteStyle) -> Self {
+		self.quotes = quotes;
+		self
+	}
+
+	/// Returns these options with a different [SuffixCase].
+	pub fn suffixes(mut self, suffixes: SuffixCase) -> Self {
+		self.suffixes = suffixes;
+		self
+	}
+}
+
+/// Writes a [Tag] as compact SNBT, such as `{Health:20f,Tags:["a","b"]}`.
+/// The output can be read back with [Tag::parse].
+pub fn to_string(tag: &Tag) -> String {
+	to_string_with(tag, &WriteOptions::compact())
+}
+
+/// Writes a [Tag] as SNBT with each Compound entry and List element on its own line, indented by four spaces.
+pub fn to_string_pretty(tag: &Tag) -> String {
+	to_string_with(tag, &WriteOptions::pretty())
+}
+
+/// Writes a [Tag] like [to_string_pretty], with the keys of every Compound in sorted order.
+/// Equal tags always give the same output, which makes it suitable for diffing.
+pub fn to_string_canonical(tag: &Tag) -> String {
+	to_string_with(tag, &WriteOptions::canonical())
+}
+
+/// Writes a [Tag] as SNBT with the given options.
+/// ```
+/// # use rustnbt::{ compound, list, snbt::{ self, FloatFormat, WriteOptions } };
+/// let options = WriteOptions::compact().floats(FloatFormat::Java);
+/// assert_eq!(snbt::to_string_with(&compound!(("Health", 20.0f32)), &options), "{Health:20.0f}");
+/// assert_eq!(snbt::to_string_with(&list!(0.0, 1e-5), &options), "[0.0d,1.0E-5d]");
+/// ```
+pub fn to_string_with(tag: &Tag, options: &WriteOptions) -> String {
+	let mut writer = SnbtWriter::new(options);
+	tag.encode_to(&mut writer).expect("Writing to a String can not fail.");
+	writer.output
+}
+
+/// Writes a [Tag] as SNBT with the given options to a writer, a piece at a time, so the whole text of a large tree is
+/// never in memory at once. The output is the same as that of [to_string_with].
+/// ```
+/// # use rustnbt::{ compound, snbt::{ self, WriteOptions } };
+/// let mut output = Vec::new();
+/// snbt::write_to(&compound!(("Health", 20.0f32)), &mut output, &WriteOptions::compact())?;
+/// assert_eq!(output, b"{Health:20f}");
+/// # Ok::<(), rustnbt::NbtError>(())
+/// ```
+pub fn write_to<W: std::io::Write>(tag: &Tag, writer: &mut W, options: &WriteOptions) -> Result<(), NbtError> {
+	let mut snbt = SnbtWriter::new(options);
+	snbt.stream = Some(writer);
+	tag.encode_to(&mut snbt)?;
+	snbt.spill(true)
+}
+
+/// A layer of quoting around SNBT that is embedded in other text, for [embed].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Quoting {
+	/// A quoted SNBT String, such as the `Command` of a command block that another command places.
+	Snbt,
+	/// A JSON string, such as a field of a JSON text component.
+	Json,
+}
+
+/// Writes a [Tag] as compact SNBT and quotes it in each of the `layers`, from the innermost outward, so it can be
+/// embedded in commands and JSON without escaping it by hand. Without layers, the SNBT is on one line and can be used
+/// as an argument of a command, such as `/give` or `/data merge`.
+/// ```
+/// # use rustnbt::{ compound, snbt::{ self, Quoting } };
+/// let item = compound!(("display", compound!(("Name", r#"{"text":"Sword"}"#))));
+/// let give = format!("give @p diamond_sword{}", snbt::embed(&item, &[]));
+/// assert_eq!(give, r#"give @p diamond_sword{display:{Name:'{"text":"Sword"}'}}"#);
+/// // The same item in a command that a command block runs, as placed by `/setblock`.
+/// let command = format!("setblock ~ ~ ~ command_block{{Command:{}}}", snbt::quote(&give, Quoting::Snbt));
+/// assert_eq!(command, r#"setblock ~ ~ ~ command_block{Command:"give @p diamond_sword{display:{Name:'{\"text\":\"Sword\"}'}}"}"#);
+/// ```
+pub fn embed(tag: &Tag, layers: &[Quoting]) -> String {
+	layers.iter().fold(to_string(tag), |text, layer| quote(&text, *layer))
+}
+
+/// Quotes text as a String of SNBT or JSON, escaping the characters that need it.
+pub fn quote(text: &str, quoting: Quoting) -> String {
+	let mut output = String::with_capacity(text.len() + 2);
+	match quoting {
+		Quoting::Snbt => write_quoted(&mut output, text, Escapes::Raw),
+		Quoting::Json => crate::sink::write_json_string(&mut output, text),
+	}
+	output
+}
+
+/// Writes a [ListTag] like [to_string_pretty], for its [Display] implementation.
+pub(crate) fn list_to_string_pretty(list: &ListTag) -> String {
+	let options = WriteOptions::pretty();
+	let mut writer = SnbtWriter::new(&options);
+	list.encode_to(&mut writer).expect("Writing to a String can not fail.");
+	writer.output
+}
+
+/// Writes a [NamedTag] as its name and its tag like [to_string_pretty], for its [Display] implementation.
+pub(crate) fn named_to_string_pretty(named: &NamedTag) -> String {
+	let options = WriteOptions::pretty();
+	let mut writer = SnbtWriter::new(&options);
+	writer.key(named.name(), named.tag().id()).expect("Writing to a String can not fail.");
+	named.tag().encode_to(&mut writer).expect("Writing to a String can not fail.");
+	writer.output
+}
+
+/// Returns `true` if a Compound key can be written without quotes.
+/// Keys that would be read as a number or boolean are quoted.
+fn is_bare_key(key: &str) -> bool {
+	key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+		&& key.chars().all(|c| is_ident_char(&c))
+		&& key != "true"
+		&& key != "false"
+}
+
+/// Returns `true` if a String can be written without quotes, which is when it is read back as the same String.
+fn is_bare_string(value: &str) -> bool {
+	!value.is_empty() && value.chars().all(|c| is_ident_char(&c)) && word_kind(value) == WordKind::String
+}
+
+/// Writes a quoted string like the game's `StringTag.quoteAndEscape`: it is quoted with single quotes if the first
+/// quote in it is a double quote, and only backslashes and the quote it is in are escaped.
+fn write_vanilla_quoted(output: &mut String, value: &str) {
+	let quote = match value.chars().find(|c| *c == '"' || *c == '\'') {
+		Some('"') => '\'',
+		_ => '"',
+	};
+	output.push(quote);
+	for c in value.chars() {
+		if c == '\\' || c == quote {
+			output.push('\\');
+		}
+		output.push(c);
+	}
+	output.push(quote);
+}
+
+/// Writes a quoted string, using single quotes if the string contains double quotes but no single quotes.
+fn write_quoted(output: &mut String, value: &str, escapes: Escapes) {
+	let quote = if value.contains('"') && !value.contains('\'') { '\'' } else { '"' };
+	output.push(quote);
+	for c in value.chars() {
+		match c {
+			'\\' => output.push_str("\\\\"),
+			'\n' => output.push_str("\\n"),
+			'\r' => output.push_str("\\r"),
+			'\t' => output.push_str("\\t"),
+			c if c == quote => {
+				output.push('\\');
+				output.push(c);
+			}
+			'\x08' if escapes != Escapes::Raw => output.push_str("\\b"),
+			'\x0C' if escapes != Escapes::Raw => output.push_str("\\f"),
+			c if (escapes != Escapes::Raw && c.is_control()) || (escapes == Escapes::NonAscii && !c.is_ascii()) => {
+				let mut units = [0; 2];
+				for unit in c.encode_utf16(&mut units) {
+					write!(output, "\\u{unit:04x}").unwrap();
+				}
+			}
+			c => output.push(c),
+		}
+	}
+	output.push(quote);
+}
+
+struct SnbtWriter<'a> {
+	output: String,
+	indent: Option<&'a str>,
+	sort_keys: bool,
+	floats: FloatFormat,
+	escapes: Escapes,
+	quotes: QuoteStyle,
+	suffixes: SuffixCase,
+	strict: bool,
+	depth: usize,
+	/// For each open Compound or List, whether it is a List and whether nothing has been written in it yet.
+	open: Vec<(bool, bool)>,
+	/// Where the output goes when it is streamed by [write_to]. Otherwise it is kept in `output`.
+	stream: Option<&'a mut dyn std::io::Write>,
+}
+
+/// How much output [write_to] buffers before writing it.
+const STREAM_BUFFER: usize = 64 * 1024;
+
+impl<'a> SnbtWriter<'a> {
+	fn new(options: &'a WriteOptions) -> Self {
+		Self {
+			output: String::new(),
+			indent: options.indent.as_deref(),
+			sort_keys: options.sort_keys,
+			floats: options.floats,
+			escapes: options.escapes,
+			quotes: options.quotes,
+			suffixes: options.suffixes,
+			strict: options.vanilla_strict,
+			depth: 0,
+			open: Vec::new(),
+			stream: None,
+		}
+	}
+
+	/// Writes the buffered output to the stream, if there is one and the buffer is full or `all` is set.
+	fn spill(&mut self, all: bool) -> Result<(), NbtError> {
+		if let Some(stream) = &mut self.stream {
+			if all || self.output.len() >= STREAM_BUFFER {
+				stream.write_all(self.output.as_bytes())?;
+				self.output.clear();
+			}
+		}
+		Ok(())
+	}
+
+	fn newline(&mut self) {
+		if let Some(indent) = self.indent {
+			self.output.push('\n');
+			for _ in 0..self.depth {
+				self.output.push_str(indent);
+			}
+		}
+	}
+
+	/// Writes the separator before an element of a List or an entry of a Compound.
+	fn separate(&mut self) {
+		let Some((_, first)) = self.open.last_mut() else {
+			return;
+		};
+		if !std::mem::replace(first, false) {
+			self.output.push(',');
+		}
+		self.newline();
+	}
+
+	/// Starts a value, which needs a separator if it is an element of a List.
+	fn element(&mut self) {
+		if matches!(self.open.last(), Some((true, _))) {
+			self.separate();
+		}
+	}
+
+	fn begin(&mut self, open: char, list: bool) {
+		self.element();
+		self.output.push(open);
+		self.depth += 1;
+		self.open.push((list, true));
+	}
+
+	fn end(&mut self, close: char) {
+		let (_, empty) = self.open.pop().expect("A Compound or List is open.");
+		self.depth -= 1;
+		if !empty {
+			self.newline();
+		}
+		self.output.push(close);
+	}
+
+	/// Writes a String, or a key if `key` is set, with the quotes of the options.
+	fn text(&mut self, value: &str, key: bool) {
+		let bare = match self.quotes {
+			QuoteStyle::Always => false,
+			QuoteStyle::BareKeys if !key => false,
+			// The game does not quote keys that only have characters of unquoted Strings.
+			_ if key && self.strict => !value.is_empty() && value.chars().all(|c| is_ident_char(&c)),
+			_ if key => is_bare_key(value),
+			_ => is_bare_string(value),
+		};
+		if bare {
+			self.output.push_str(value);
+		} else if self.strict {
+			write_vanilla_quoted(&mut self.output, value);
+		} else {
+			write_quoted(&mut self.output, value, self.escapes);
+		}
+	}
+
+	/// Writes an array on a single line.
+	fn array<T: Display>(&mut self, prefix: char, values: &[T], suffix: &str) {
+		let separator = if self.indent.is_some() { ", " } else { "," };
+		write!(self.output, "[{prefix};").unwrap();
+		if self.indent.is_some() && !values.is_empty() {
+			self.output.push(' ');
+		}
+		for (i, value) in values.iter().enumerate() {
+			if i > 0 {
+				self.output.push_str(separator);
+			}
+			write!(self.output, "{value}{suffix}").unwrap();
+		}
+		self.output.push(']');
+	}
+}
+
+impl NbtSink for SnbtWriter<'_> {
+	fn scalar(&mut self, value: Scalar) -> Result<(), NbtError> {
+		self.element();
+		match value {
+			Scalar::Byte(value) => write_number(&mut self.output, NumberView::Byte(value), self.floats, self.suffixes).unwrap(),
+			Scalar::Short(value) => write_number(&mut self.output, NumberView::Short(value), self.floats, self.suffixes).unwrap(),
+			Scalar::Int(value) => write_number(&mut self.output, NumberView::Int(value), self.floats, self.suffixes).unwrap(),
+			Scalar::Long(value) => write_number(&mut self.output, NumberView::Long(value), self.floats, self.suffixes).unwrap(),
+			Scalar::Float(value) => write_number(&mut self.output, NumberView::Float(value), self.floats, self.suffixes).unwrap(),
+			Scalar::Double(value) => write_number(&mut self.output, NumberView::Double(value), self.floats, self.suffixes).unwrap(),
+			Scalar::ByteArray(values) => {
+				let suffix = if self.strict { 'B' } else { self.suffixes.apply('b') };
+				self.array('B', values, suffix.encode_utf8(&mut [0; 4]))
+			}
+			Scalar::String(value) => self.text(value, false),
+			Scalar::IntArray(values) => self.array('I', values, ""),
+			Scalar::LongArray(values) => self.array('L', values, self.suffixes.apply('L').encode_utf8(&mut [0; 4])),
+		}
+		self.spill(false)
+	}
+
+	fn begin_compound(&mut self, _: usize) -> Result<(), NbtError> {
+		self.begin('{', false);
+		self.spill(false)
+	}
+
+	fn key(&mut self, key: &str, _: TagID) -> Result<(), NbtError> {
+		self.separate();
+		self.text(key, true);
+		self.output.push_str(if self.indent.is_some() { ": " } else { ":" });
+		self.spill(false)
+	}
+
+	fn end_compound(&mut self) -> Result<(), NbtError> {
+		self.end('}');
+		self.spill(false)
+	}
+
+	fn begin_list(&mut self, _: Option<TagID>, _: usize) -> Result<(), NbtError> {
+		self.begin('[', true);
+		self.spill(false)
+	}
+
+	fn end_list(&mut self) -> Result<(), NbtError> {
+		self.end(']');
+		self.spill(false)
+	}
+
+	fn sorted_keys(&self) -> bool {
+		self.sort_keys
+	}
+}
+
+fn is_ident_char(c: &char) -> bool {
+	c.is_ascii_alphanumeric() || ['_','-','+','.'].contains(c)
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+fn identifier<E: chumsky::Error<char>>() -> impl Parser<char, String, Error = E> {
+	filter::<char,_,E>(is_ident_char)
+		.repeated()
+		.at_least(1)
+		.collect::<String>()
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+fn strcmp(ignore_case: bool, lhs: &str, rhs: &str, ) -> bool {
+	if lhs.len() != rhs.len() {
+		return false;
+	}
+	if ignore_case {
+		lhs.to_lowercase() == rhs.to_lowercase()
+	} else {
+		lhs == rhs
+	}
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+fn keyword<S: AsRef<str>>(word: S, ignore_case: bool) -> impl Parser<char, (), Error = Simple<char>> {
+	identifier()
+		.try_map(move |text, span| {
+			if strcmp(ignore_case, word.as_ref(), &text) {
+				Ok(())
+			} else {
+				Err(Simple::custom(span, format!("Expected keyword: {}, found {}", word.as_ref(), text)))
+			}
+		})
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+fn no_case<C: Container<char>>(chars: C) -> HashSet<char> {
+	chars.get_iter().fold(HashSet::new(), |mut set, c| {
+		set.insert(c);
+		if c.is_lowercase() {
+			set.extend(c.to_uppercase());
+		} else {
+			set.extend(c.to_lowercase());
+		}
+		set
+	})
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+fn one_of_nc<C: Container<char>, E: Error<char>>(chars: C) -> OneOf<char,HashSet<char>,E> {
+	one_of(no_case(chars))
+}
+
+#[cfg(feature = "snbt-diagnostics")]
+fn none_of_nc<C: Container<char>, E: Error<char>>(chars: C) -> NoneOf<char,HashSet<char>,E> {
+	none_of(no_case(chars))
+}
+
+#[derive(Debug, thiserror::Error)]
 pub enum ParseError {
-	#[error("Found invalid token(s).")]
-	TokenizeError(Vec<Simple<char>>),
-	#[error("Failed to parse SNBT.")]
-	ParseFailure(Vec<Simple<Token>>),
+	/// The SNBT is not well-formed. The offset is in characters.
+	#[error("Invalid SNBT at character {offset}: {message}")]
+	Syntax { offset: usize, message: String },
+	/// An integer is out of the range of its type, and [ParseOptions::number_range] is [NumberRange::Error]. The offset
+	/// and length are in characters. The message suggests the suffix of a type that the integer fits, if there is one.
+	#[error("Invalid SNBT at character {offset}: {}", out_of_range(value, *id))]
+	OutOfRange { offset: usize, length: usize, value: String, id: TagID },
+	#[error("{0}")]
+	Invalid(String),
+}
+
+impl ParseError {
+	/// For an integer that is out of range, the integer with the suffix of the narrowest type that it fits, such as
+	/// `3000000000L` for `3000000000`.
+	pub fn suggestion(&self) -> Option<String> {
+		match self {
+			ParseError::OutOfRange { value, id, .. } => suggest_suffix(value, *id),
+			_ => None,
+		}
+	}
+}
+
+/// The suffix that integers of a type are written with.
+fn integer_suffix(id: TagID) -> &'static str {
+	match id {
+		TagID::Byte => "b",
+		TagID::Short => "s",
+		TagID::Long => "L",
+		_ => "",
+	}
+}
+
+/// Returns an integer word with the suffix of the narrowest type wider than `id` that it fits, if there is one.
+fn suggest_suffix(word: &str, id: TagID) -> Option<String> {
+	let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+	let value: i128 = digits.parse().ok()?;
+	let wider = [
+		(TagID::Short, i16::MIN.into(), i16::MAX.into()),
+		(TagID::Int, i32::MIN.into(), i32::MAX.into()),
+		(TagID::Long, i64::MIN.into(), i64::MAX.into()),
+	];
+	wider.into_iter()
+		.find(|&(wider, min, max)| wider > id && (min..=max).contains(&value))
+		.map(|(wider, ..)| format!("{digits}{}", integer_suffix(wider)))
+}
+
+/// The message for an integer word that is out of the range of its type.
+fn out_of_range(word: &str, id: TagID) -> String {
+	match suggest_suffix(word, id) {
+		Some(suggestion) => format!("{word} exceeds {}; did you mean {suggestion}?", id.title()),
+		None => format!("{word} exceeds {}, and no integer type is wider.", id.title()),
+	}
 }
 
 #[cfg(test)]
@@ -556,6 +2110,70 @@ mod tests {
 		}
 	}
 	
+	#[test]
+	fn to_string_test() {
+		use super::*;
+		let tag = compound!(
+			("byte", Tag::Byte(-1)),
+			("short", Tag::Short(2)),
+			("long", Tag::Long(i64::MIN)),
+			("float", Tag::Float(0.5)),
+			("double", Tag::Double(-3.0)),
+			("bytes", Tag::ByteArray(vec![1, 2])),
+			("ints", Tag::IntArray(vec![])),
+			("longs", Tag::LongArray(vec![3])),
+			("quote", "say \"hi\"\n"),
+			("true", list!("a", "b")),
+			("1 key", Tag::List(ListTag::Compound(vec![Map::default()]))),
+			("nested", list!(1i16))
+		);
+		assert_eq!(Tag::parse(to_string(&tag)).expect("Failed to parse compact SNBT."), tag);
+		assert_eq!(Tag::parse(to_string_pretty(&tag)).expect("Failed to parse pretty SNBT."), tag);
+		assert_eq!(Tag::parse(to_string_canonical(&tag)).expect("Failed to parse canonical SNBT."), tag);
+		assert_eq!(to_string_canonical(&compound!(("b", 1), ("a", 2))), "{\n    a: 2,\n    b: 1\n}");
+		assert_eq!(to_string(&list!("it's", "\\")), r#"["it's","\\"]"#);
+		let java = WriteOptions::compact().floats(FloatFormat::Java);
+		assert_eq!(to_string_with(&list!(64.0, 0.001, 1234567.0, 1e7, 1.5e-4, -0.0, 1e300, f64::NAN, f64::NEG_INFINITY), &java),
+			"[64.0d,0.001d,1234567.0d,1.0E7d,1.5E-4d,-0.0d,1.0E300d,NaNd,-Infinityd]");
+		assert_eq!(to_string_with(&list!(0.1f32, 3.4028235e38f32), &java), "[0.1f,3.4028235E38f]");
+		assert_eq!(to_string_with(&list!(0.1f32, 2.0f32), &WriteOptions::compact().floats(FloatFormat::Fixed(3))), "[0.100f,2.000f]");
+		assert_eq!(to_string_with(&list!(0.1f32), &WriteOptions::compact()), "[0.1f]");
+		let mut numbers = String::new();
+		for number in [NumberView::Double(-f64::MAX), NumberView::Double(f64::MIN_POSITIVE), NumberView::Short(-7), NumberView::Float(f32::MIN_POSITIVE)] {
+			fmt_tag_number(&mut numbers, number, FloatFormat::Java).unwrap();
+			numbers.push(' ');
+		}
+		assert_eq!(numbers, "-1.7976931348623157E308d 2.2250738585072014E-308d -7s 1.1754944E-38f ");
+		assert_eq!(to_string(&compound!(("key", "say \"hi\""))), r#"{key:'say "hi"'}"#);
+		let item = Map::from_iter([("b".to_owned(), Tag::IntArray(vec![1, 2]))]);
+		assert_eq!(to_string_pretty(&compound!(("a", Tag::List(ListTag::Compound(vec![item, Map::default()]))))), concat!(
+			"{\n",
+			"    a: [\n",
+			"        {\n",
+			"            b: [I; 1, 2]\n",
+			"        },\n",
+			"        {}\n",
+			"    ]\n",
+			"}",
+		));
+	}
+
+	#[test]
+	fn embed_test() -> Result<(), super::ParseError> {
+		use super::*;
+		let tag = compound!(("Text", "It's \"quoted\"\n"), ("Path", "C:\\items"));
+		let command = embed(&tag, &[]);
+		assert!(!command.contains('\n'));
+		assert_eq!(Tag::parse(&command)?, tag);
+		let nested = embed(&tag, &[Quoting::Snbt, Quoting::Snbt]);
+		let Tag::String(inner) = Tag::parse(&nested)? else { panic!("The outer layer is a String.") };
+		assert_eq!(Tag::parse(&inner)?, Tag::String(command.clone()));
+		assert_eq!(embed(&compound!(("a", "b")), &[Quoting::Json]), r#""{a:\"b\"}""#);
+		assert_eq!(quote("tab\there\u{1}", Quoting::Json), r#""tab\there\u0001""#);
+		assert_eq!(embed(&compound!(("a", "b")), &[Quoting::Snbt, Quoting::Json]), r#""'{a:\"b\"}'""#);
+		Ok(())
+	}
+
 	#[test]
 	fn tag_fromstr_test() {
 		use super::*;
@@ -626,8 +2244,443 @@ mod tests {
 			}
 		"#);
 	}
+	#[cfg(feature = "snbt-diagnostics")]
+	#[test]
+	fn lex_test() {
+		use super::*;
+		let source = "{a: @@@ 1b, b: \u{e9}}";
+		let lexed = Token::lex(source, &LexOptions::new());
+		assert_eq!(lexed.tokens.len(), 3);
+		assert_eq!(lexed.errors.len(), 1);
+		let lexed = Token::lex(source, &LexOptions::new().recover(true));
+		assert_eq!(lexed.tokens.iter().map(|(token, _)| token.clone()).collect::<Vec<_>>(), Token::parse("{a: 1b, b: }").unwrap());
+		assert_eq!(lexed.tokens[3].1, 8..10);
+		assert_eq!(lexed.errors.iter().map(Simple::span).collect::<Vec<_>>(), [4..5, 5..6, 6..7, 15..16]);
+		let lexed = Token::lex(source, &LexOptions::diagnostics());
+		assert_eq!(lexed.errors.iter().map(Simple::span).collect::<Vec<_>>(), [4..7, 15..16]);
+		let lexed = Token::lex(source, &LexOptions::diagnostics().max_errors(Some(1)));
+		assert_eq!(lexed.errors.len(), 1);
+		assert_eq!(lexed.tokens.len(), 8);
 
-}
+		let partial = parse_partial("[{a: [1, 2 3]}, {b: 2}]", &LexOptions::diagnostics());
+		assert_eq!(partial.tag, Some(Tag::List(ListTag::Compound(vec![
+			Map::from_iter([("a".to_owned(), Tag::List(ListTag::Empty))]),
+			Map::from_iter([("b".to_owned(), Tag::Int(2))]),
+		]))));
+		assert_eq!(partial.parse_errors.len(), 1);
+		assert!(!partial.is_clean());
+		assert!(parse_partial("{a: 1}", &LexOptions::new()).is_clean());
+	}
 
+	#[test]
+	fn single_pass_test() {
+		use super::*;
+		// The single-pass reader accepts the same syntax as the tokens and the parser over them.
+		#[cfg(feature = "snbt-diagnostics")]
+		let two_pass = |source: &str| -> Option<Tag> {
+			let node = parser().then_ignore(end()).parse(Token::parse(source).ok()?).ok()?;
+			source::build(&mut NodeSource { root: Some(node), stack: Vec::new() }, &mut ParseOptions::new()).ok().map(|(_, tag)| tag)
+		};
+		for source in [
+			"0", "-0", "007", "1b", "1B", "-128b", "128b", "1s", "1l", "1L", "2147483648", "1.5", "1.5f", "1f", "1d", "1.",
+			".5", "-.5", "1.5e3", "1.5.5", "0.5", "00.5", "1bx", "1b2", "-", "+1", "true", "false", "True", "true_", "a.b-c+d",
+			"'it''s'", r#""a\"b""#, r#"'a\'b\\c\n'"#, r#""\q""#, "'unclosed", "[]", "[,]", "[1, 2,]", "[1 2]", "[B;]",
+			"[B; 1b, true, false]", "[B; 1]", "[I; 1, 2]", "[I; 1,]", "[i;-1]", "[L; 1l]", "[L; 1]", "[B ;1b]", "[B]",
+			"[[], [1], ['a']]", "{}", "{,}", "{a: 1,}", "{a 1}", "{'a b': {c: [I; 1]}}", "{a: 1} x", "  {a:1}  ", "",
+			"{a: [1b, 2s]}", "[{}, {a: 1}]", "{a: @}", "x y", "é", "1.5.pack", "1e5", "+.5e-3f", "2147483648L", "[B; +1b]",
+			r#""\u00e9\ud83c\udf88""#, r#""\u0041\u0042""#, r#""\ud83c""#, r#""\ud83c\u0041""#, r#""\udf88\ud83c""#, r#""\u12""#,
+		] {
+			let single = Tag::parse(source).ok();
+			#[cfg(feature = "snbt-diagnostics")]
+			assert_eq!(single, two_pass(source), "{source:?}");
+		}
+		let Err(ParseError::Syntax { offset, .. }) = Tag::parse("{a: [1, 2 3]}") else { panic!("The List is missing a comma.") };
+		assert_eq!(offset, 10);
+		assert_eq!(Tag::parse("{1: 2, true: 3}").ok(), Some(compound!(("1", 2), ("true", 3))));
+	}
+
+	#[test]
+	fn snbt_macro_test() {
+		use super::*;
+		// The snbt! macro reads SNBT with its own copy of the reader, which must agree with this one.
+		macro_rules! same {
+			($($source:literal)+) => {
+				$(assert_eq!(crate::snbt!($source), Tag::parse($source).expect($source), "{}", $source);)+
+			};
+		}
+		same!{
+			"0" "-128b" "-32768s" "-2147483648" "-9223372036854775808l" "9223372036854775807L" "1.5f" "3.4028235E38f"
+			"1e5" "0.1" "-0.0d" "99999999999999999999999999999999999999999.0f" "true" "false" "007" "'it\\'s'" "\"a\\nb\""
+			"[]" "[1, 2,]" "[[], [1b], ['a']]" "[B; true, -1b]" "[I;]" "[L; 1l, -2L]" "[{}, {a: {b: [I; 1]}}]"
+			"{a: 1, a: 2, b: 'x'}" "{'a b': [1.5d], 1: {}}" "+5" ".5" "1." "1.5e3" "TRUE" "1.5.pack"
+			"'\\u00e9\\ud83c\\udf88'"
+		}
+	}
+
+	#[test]
+	fn parse_with_test() -> Result<(), super::ParseError> {
+		use super::*;
+		use crate::{ parse::KeyCollisionPolicy, path::TagPath };
+		assert!(Tag::parse("[1b, 2s]").is_err());
+		assert!(Tag::parse("{a: 1} {b: 2}").is_err());
+		assert_eq!(Tag::parse("{a: 1, a: 2}")?, compound!(("a", 2)));
+
+		let options = ParseOptions::new().allow_mixed_lists(true).allow_trailing_data(true);
+		let outcome = parse_with("{list: [[1b, {a: 2}, {'': 3}]]} {b: 2}", &options)?;
+		assert_eq!(outcome.value, compound!(("list", Tag::List(ListTag::List(vec![ListTag::Compound(vec![
+			Map::from_iter([(String::new(), Tag::Byte(1))]),
+			Map::from_iter([("a".to_owned(), Tag::Int(2))]),
+			Map::from_iter([(String::new(), Tag::Compound(Map::from_iter([(String::new(), Tag::Int(3))])))]),
+		])])))));
+		assert_eq!(outcome.warnings, [
+			Warning::MixedList { path: TagPath::new().key("list").index(0) },
+			Warning::TrailingData { offset: 32, length: 6 },
+		]);
+		let outcome = parse_with("{a: 1} @", &options)?;
+		assert_eq!(outcome.warnings, [Warning::TrailingData { offset: 7, length: 1 }]);
+		assert!(parse_with("{a: 1}  ", &options)?.is_clean());
+		assert!(parse_with("{a: 1, a: 2}", &ParseOptions::new().key_collisions(KeyCollisionPolicy::Error)).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn depth_test() -> Result<(), super::ParseError> {
+		use super::*;
+		use crate::io::DEFAULT_MAX_DEPTH;
+		let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+		assert!(Tag::parse(nested(DEFAULT_MAX_DEPTH)).is_ok());
+		let Err(ParseError::Syntax { offset, .. }) = Tag::parse(nested(DEFAULT_MAX_DEPTH + 1)) else { panic!("The Lists are too deep.") };
+		assert_eq!(offset, DEFAULT_MAX_DEPTH);
+		// Deep enough to overflow the stack if the depth was not limited.
+		let deep = nested(100_000);
+		assert!(Tag::parse(&deep).is_err());
+		assert!(parse_prefix(&deep).is_err());
+		assert!(parse_spanned(&deep, &ParseOptions::new()).is_err());
+		assert!(Tag::parse(format!("{}[I; 1]{}", "{a: ".repeat(100_000), "}".repeat(100_000))).is_err());
+
+		let options = ParseOptions::new().max_depth(2);
+		assert!(parse_with("{a: [I; 1]}", &options).is_ok());
+		assert!(parse_with("{a: {b: [I; 1]}}", &options).is_err());
+		assert!(parse_with("[[{}]]", &options).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn number_range_test() -> Result<(), super::ParseError> {
+		use super::*;
+		let read = |source: &str, range: NumberRange| parse_with(source, &ParseOptions::new().number_range(range));
+		// The edges of every type are in range in every mode.
+		for range in [NumberRange::Error, NumberRange::Wrap, NumberRange::String] {
+			assert_eq!(read("[B; -128b, 127b]", range)?.value, Tag::ByteArray(vec![-128, 127]));
+			assert_eq!(read("[-32768s, 32767s]", range)?.value, Tag::List(ListTag::Short(vec![-32768, 32767])));
+			assert_eq!(read("[I; -2147483648, 2147483647]", range)?.value, Tag::IntArray(vec![i32::MIN, i32::MAX]));
+			assert_eq!(read("[L; -9223372036854775808L, 9223372036854775807L]", range)?.value, Tag::LongArray(vec![i64::MIN, i64::MAX]));
+			assert!(read("[1e99d, 3.5e400d]", range)?.is_clean());
+			assert_eq!(read("1000000000000000000000000000000000000000.0f", range)?.value, Tag::Float(f32::INFINITY));
+		}
+		// One past each edge, per type.
+		let cases = [
+			("{a: 128b}", 4, TagID::Byte, Tag::Byte(-128)),
+			("{a: -129B}", 4, TagID::Byte, Tag::Byte(127)),
+			("{a: 32768s}", 4, TagID::Short, Tag::Short(-32768)),
+			("{a: -32769s}", 4, TagID::Short, Tag::Short(32767)),
+			("{a: 2147483648}", 4, TagID::Int, Tag::Int(i32::MIN)),
+			("{a: -2147483649}", 4, TagID::Int, Tag::Int(i32::MAX)),
+			("{a: 9223372036854775808l}", 4, TagID::Long, Tag::Long(i64::MIN)),
+			("{a: -9223372036854775809L}", 4, TagID::Long, Tag::Long(i64::MAX)),
+		];
+		for (source, offset, id, wrapped) in cases {
+			let value = &source[offset..source.len() - 1];
+			let length = value.len();
+			match read(source, NumberRange::Error) {
+				Err(ParseError::OutOfRange { offset: at, length: len, value: found, id: found_id }) => {
+					assert_eq!((at, len, found.as_str(), found_id), (offset, length, value, id));
+				}
+				other => panic!("{source} was read as {other:?}"),
+			}
+			let outcome = read(source, NumberRange::Wrap)?;
+			assert_eq!(outcome.value, compound!(("a", wrapped)));
+			assert_eq!(outcome.warnings, [Warning::NumberOutOfRange { offset, length, id, range: NumberRange::Wrap }]);
+			let outcome = read(source, NumberRange::String)?;
+			assert_eq!(outcome.value, compound!(("a", value)));
+			assert_eq!(outcome.warnings, [Warning::NumberOutOfRange { offset, length, id, range: NumberRange::String }]);
+		}
+		assert_eq!(read("300b", NumberRange::Wrap)?.value, Tag::Byte(44));
+		assert_eq!(read("99999999999999999999999l", NumberRange::Wrap)?.value, Tag::Long(99999999999999999999999u128 as i64));
+		// The offsets of array elements skip the whitespace before them.
+		assert!(matches!(read("[B; 1b,  200b]", NumberRange::Error), Err(ParseError::OutOfRange { offset: 9, length: 4, .. })));
+		assert_eq!(read("[B; 1b,  200b]", NumberRange::Wrap)?.value, Tag::ByteArray(vec![1, -56]));
+		assert!(read("[B; 1b, 200b]", NumberRange::String).is_err());
+		assert_eq!(
+			read("128b", NumberRange::Error).unwrap_err().to_string(),
+			"Invalid SNBT at character 0: 128b exceeds Byte; did you mean 128s?",
+		);
+		// The suggestion is the narrowest type that the integer fits.
+		let error = read("{a: 3000000000}", NumberRange::Error).unwrap_err();
+		assert!(matches!(error, ParseError::OutOfRange { offset: 4, length: 10, .. }));
+		assert_eq!(error.to_string(), "Invalid SNBT at character 4: 3000000000 exceeds Int; did you mean 3000000000L?");
+		assert_eq!(read("-40000s", NumberRange::Error).unwrap_err().suggestion().as_deref(), Some("-40000"));
+		let error = read("99999999999999999999L", NumberRange::Error).unwrap_err();
+		assert_eq!(error.suggestion(), None);
+		assert_eq!(error.to_string(), "Invalid SNBT at character 0: 99999999999999999999L exceeds Long, and no integer type is wider.");
+		#[cfg(feature = "snbt-diagnostics")]
+		{
+			let partial = parse_partial("[300b]", &LexOptions::diagnostics());
+			assert_eq!(partial.parse_errors.len(), 1);
+			assert_eq!(partial.parse_errors[0].span(), 1..5);
+			assert_eq!(partial.parse_errors[0].reason(), &chumsky::error::SimpleReason::Custom("300b exceeds Byte; did you mean 300s?".to_owned()));
+		}
+		Ok(())
+	}
 
+	#[test]
+	fn unquoted_text_test() -> Result<(), super::ParseError> {
+		use super::*;
+		let unicode = ParseOptions::new().unquoted_text(UnquotedText::Unicode);
+		// The game only reads these in quotes.
+		for source in ["{name: Grüße}", "{Größe: 1}", "[a, 名前]"] {
+			assert!(matches!(Tag::parse(source), Err(ParseError::Syntax { .. })), "{source}");
+			assert!(parse_with(source, &unicode).is_ok(), "{source}");
+		}
+		assert_eq!(
+			Tag::parse("{name: Grüße}").unwrap_err().to_string(),
+			"Invalid SNBT at character 9: 'ü' can only be in a quoted String. Unquoted Strings can only have ASCII letters, digits, and '_', '-', '.', and '+'.",
+		);
+		assert_eq!(Tag::parse("{name: 'Grüße'}")?, compound!(("name", "Grüße")));
 
+		let outcome = parse_with("{Größe: 1, name: Grüße-2, n: 5, 名前: 'x'}", &unicode)?;
+		assert_eq!(outcome.value, compound!(("Größe", 1), ("name", "Grüße-2"), ("n", 5), ("名前", "x")));
+		assert_eq!(outcome.warnings, [
+			Warning::UnquotedUnicode { offset: 1, length: 5 },
+			Warning::UnquotedUnicode { offset: 17, length: 7 },
+			Warning::UnquotedUnicode { offset: 32, length: 2 },
+		]);
+		// Only letters and digits are allowed, and the writers still quote them.
+		assert!(parse_with("{a: x🎈}", &unicode).is_err());
+		assert_eq!(Tag::parse(outcome.value.to_string())?, outcome.value);
+		Ok(())
+	}
+
+	#[test]
+	fn spanned_test() -> Result<(), super::ParseError> {
+		use super::*;
+		let source = "{ 'Größe' : [B; 1b, 2b], list: [{}, {a: 1, a: 2}], \"s\": 'x' }";
+		let outcome = parse_spanned(source, &ParseOptions::new())?;
+		let tree = &outcome.value;
+		// Spans are in characters, so they are compared by slicing the characters.
+		let chars: Vec<char> = source.chars().collect();
+		let text = |path: TagPath| tree.span(&path).map(|span| chars[span].iter().collect::<String>());
+		assert_eq!(text(TagPath::new()).as_deref(), Some(source));
+		assert_eq!(text(TagPath::new().key("Größe")).as_deref(), Some("[B; 1b, 2b]"));
+		assert_eq!(text(TagPath::new().key("Größe").index(1)).as_deref(), Some("2b"));
+		assert_eq!(text(TagPath::new().key("list").index(0)).as_deref(), Some("{}"));
+		assert_eq!(text(TagPath::new().key("list").index(1).key("a")).as_deref(), Some("2"));
+		assert_eq!(text(TagPath::new().key("s")).as_deref(), Some("'x'"));
+		assert_eq!(text(TagPath::new().key("list").index(2)), None);
+		assert_eq!(text(TagPath::new().key("Größe").index(0).index(0)), None);
+		let SpannedTag::Compound(entries) = &tree.value else { panic!() };
+		assert_eq!(chars[entries[0].0.span.clone()].iter().collect::<String>(), "'Größe'");
+
+		let path_at = |pattern: &str| tree.path_at(source.chars().count() - source[source.find(pattern).unwrap()..].chars().count()).map(|path| path.to_string());
+		assert_eq!(path_at("2b").as_deref(), Some("Größe[1]"));
+		assert_eq!(path_at("'Größe'").as_deref(), Some("Größe"));
+		assert_eq!(path_at("a: 2").as_deref(), Some("list[1].a"));
+		assert_eq!(path_at(", \"s\"").as_deref(), Some(""));
+		assert_eq!(tree.path_at(100), None);
+		assert_eq!(tree.to_tag(), Tag::parse(source)?);
+		assert!(matches!(outcome.warnings[..], [Warning::KeyCollision { .. }]));
+
+		// The options apply as they do for parse_with.
+		assert!(parse_spanned("[1b, 2s]", &ParseOptions::new()).is_err());
+		let mixed = parse_spanned("[1b, 2s] x", &ParseOptions::new().allow_mixed_lists(true).allow_trailing_data(true))?;
+		assert_eq!(mixed.warnings.len(), 2);
+		assert_eq!(mixed.value.span(&TagPath::new().index(1)), Some(5..7));
+		assert_eq!(mixed.value.to_tag(), parse_with("[1b, 2s]", &ParseOptions::new().allow_mixed_lists(true))?.value);
+		Ok(())
+	}
+
+	#[test]
+	fn parse_prefix_test() -> Result<(), super::ParseError> {
+		use super::*;
+		assert_eq!(parse_prefix("[1, 2]]")?, (Tag::List(ListTag::Int(vec![1, 2])), "]"));
+		assert_eq!(parse_prefix("  'a b' c")?, (Tag::from("a b"), " c"));
+		assert_eq!(parse_prefix("minecraft:stone")?, (Tag::from("minecraft"), ":stone"));
+		assert_eq!(parse_prefix("12b{")?, (Tag::Byte(12), "{"));
+		assert_eq!(parse_prefix("{a: 1}")?, (compound!(("a", 1)), ""));
+		assert!(parse_prefix("{a: 1").is_err());
+		assert!(parse_prefix("").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn write_to_test() {
+		use super::*;
+		/// Records the size of the largest write.
+		struct Chunks(Vec<u8>, usize);
+		impl std::io::Write for Chunks {
+			fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+				self.1 = self.1.max(bytes.len());
+				self.0.extend_from_slice(bytes);
+				Ok(bytes.len())
+			}
+			fn flush(&mut self) -> std::io::Result<()> {
+				Ok(())
+			}
+		}
+		let entity = compound!(("id", "minecraft:zombie"), ("Pos", list!(1.5, 64.0, -3.25)), ("Health", 20.0f32), ("UUID", Tag::IntArray(vec![1, 2, 3, 4])));
+		let tag = Tag::List(ListTag::Compound(vec![match entity { Tag::Compound(map) => map, _ => unreachable!() }; 5000]));
+		for options in [WriteOptions::compact(), WriteOptions::pretty(), WriteOptions::canonical()] {
+			let mut chunks = Chunks(Vec::new(), 0);
+			write_to(&tag, &mut chunks, &options).unwrap();
+			assert_eq!(String::from_utf8(chunks.0).unwrap(), to_string_with(&tag, &options));
+			assert!(chunks.1 < STREAM_BUFFER + 1024, "{}", chunks.1);
+		}
+		// Errors of the writer are returned.
+		let mut full = [0u8; 4];
+		assert!(matches!(write_to(&tag, &mut &mut full[..], &WriteOptions::compact()), Err(NbtError::IoError(_))));
+	}
+
+	#[test]
+	fn word_kind_test() {
+		use super::*;
+		assert_eq!(word_kind("1.5e3f"), WordKind::Number(Tag::Float(1500.0)));
+		assert_eq!(word_kind("+5"), WordKind::Number(Tag::Int(5)));
+		assert_eq!(word_kind("-.5"), WordKind::Number(Tag::Double(-0.5)));
+		assert_eq!(word_kind("TRUE"), WordKind::Boolean(true));
+		assert_eq!(word_kind("128b"), WordKind::OutOfRange(TagID::Byte));
+		for word in ["1.5.pack", "007", "1e5", "1.5b", "1.e", "-", "e5", "nan", "infinity", "1_000"] {
+			assert_eq!(word_kind(word), WordKind::String, "{word}");
+		}
+		assert_eq!(Tag::parse("[1.5.pack, 1.5]").unwrap_err().to_string(), Tag::parse("[a, 1.5]").unwrap_err().to_string());
+		#[cfg(feature = "snbt-diagnostics")]
+		assert_eq!(Token::parse("1.5.pack 1.5 +2b").unwrap(), [
+			Token::Identifier("1.5.pack".to_owned()),
+			Token::Decimal("1.5".to_owned(), DecimalType::Double),
+			Token::Integer("+2".to_owned(), IntegerType::Byte),
+		]);
+	}
+
+	#[test]
+	fn float_round_trip_test() {
+		use super::*;
+		let floats = [0.0, -0.0, f32::from_bits(1), f32::from_bits(0x807F_FFFF), f32::MIN_POSITIVE, f32::MAX, f32::MIN, 0.1, 16777217.0];
+		let doubles = [0.0, -0.0, f64::from_bits(1), f64::from_bits(0x800F_FFFF_FFFF_FFFF), f64::MIN_POSITIVE, f64::MAX, f64::MIN, 0.1, 1e23];
+		// A spread of every exponent and sign, and many mantissas.
+		let floats = floats.into_iter().chain((0..=u32::MAX).step_by(65_521).map(f32::from_bits).filter(|value| value.is_finite()));
+		let doubles = doubles.into_iter().chain((0..=u64::MAX).step_by(0x0004_1234_5678_9ABD).map(f64::from_bits).filter(|value| value.is_finite()));
+		for format in [FloatFormat::Shortest, FloatFormat::Java] {
+			let options = WriteOptions::compact().floats(format);
+			for value in floats.clone() {
+				let text = to_string_with(&Tag::Float(value), &options);
+				let Ok(Tag::Float(read)) = Tag::parse(&text) else { panic!("{text} is not a Float") };
+				assert_eq!(read.to_bits(), value.to_bits(), "{text}");
+			}
+			for value in doubles.clone() {
+				let text = to_string_with(&Tag::Double(value), &options);
+				let Ok(Tag::Double(read)) = Tag::parse(&text) else { panic!("{text} is not a Double") };
+				assert_eq!(read.to_bits(), value.to_bits(), "{text}");
+			}
+			let special = list!(f64::NAN, f64::INFINITY, f64::NEG_INFINITY);
+			assert_eq!(to_string_with(&special, &options), "[NaNd,Infinityd,-Infinityd]");
+			assert_eq!(Tag::parse(to_string_with(&special, &options)).unwrap(), list!("NaNd", "Infinityd", "-Infinityd"));
+		}
+		assert_eq!(to_string_with(&Tag::Float(f32::NAN), &WriteOptions::compact().floats(FloatFormat::Fixed(2))), "NaNf");
+	}
+
+	#[test]
+	fn escapes_test() -> Result<(), super::ParseError> {
+		use super::*;
+		let tag = compound!(("\u{e9}", "a\u{1}\u{e9}\u{1F388}\n\x08"));
+		let write = |escapes: Escapes| to_string_with(&tag, &WriteOptions::compact().escapes(escapes));
+		assert_eq!(write(Escapes::Raw), "{\"\u{e9}\":\"a\u{1}\u{e9}\u{1F388}\\n\x08\"}");
+		assert_eq!(write(Escapes::Control), "{\"\u{e9}\":\"a\\u0001\u{e9}\u{1F388}\\n\\b\"}");
+		assert_eq!(write(Escapes::NonAscii), r#"{"\u00e9":"a\u0001\u00e9\ud83c\udf88\n\b"}"#);
+		for escapes in [Escapes::Raw, Escapes::Control, Escapes::NonAscii] {
+			assert_eq!(Tag::parse(write(escapes))?, tag);
+		}
+		// The game has no escapes before 1.21.5.
+		assert_eq!(to_string_with(&tag, &WriteOptions::vanilla().escapes(Escapes::NonAscii)), "{\"\u{e9}\":\"a\u{1}\u{e9}\u{1F388}\n\x08\"}");
+
+		// Unpaired surrogates from Java are errors, or replaced with a warning.
+		assert!(matches!(Tag::parse(r#"["\ud83c"]"#), Err(ParseError::Syntax { offset: 2, .. })));
+		let outcome = parse_with(r#"["\ud83cx\udf88"]"#, &ParseOptions::new().surrogates(Surrogates::Replace))?;
+		assert_eq!(outcome.value, list!("\u{FFFD}x\u{FFFD}"));
+		assert_eq!(outcome.warnings, [Warning::UnpairedSurrogate { offset: 2 }, Warning::UnpairedSurrogate { offset: 9 }]);
+		assert!(parse_with(r#""\u00e9""#, &ParseOptions::new().vanilla_strict(true)).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn vanilla_strict_test() {
+		use super::*;
+		let strict = ParseOptions::new().vanilla_strict(true);
+		let read = |source: &str| parse_with(source, &strict).map(ParseOutcome::into_value);
+		// What the game reads each word as.
+		let vectors = [
+			("1b", Tag::Byte(1)), ("-128B", Tag::Byte(-128)), ("128b", Tag::from("128b")), ("+5", Tag::Int(5)),
+			("007", Tag::from("007")), ("2147483648", Tag::from("2147483648")), ("3000000000L", Tag::Long(3_000_000_000)),
+			("1.", Tag::Double(1.0)), (".5", Tag::Double(0.5)), ("1.5e3", Tag::Double(1500.0)), ("1e3", Tag::from("1e3")),
+			("1e3f", Tag::Float(1000.0)), ("2D", Tag::Double(2.0)), ("-.5E-1f", Tag::Float(-0.05)), ("1.e", Tag::from("1.e")),
+			("1.5b", Tag::from("1.5b")), ("TRUE", Tag::Byte(1)), ("False", Tag::Byte(0)), ("1.2.3", Tag::from("1.2.3")),
+			("a-b_c+d.e", Tag::from("a-b_c+d.e")), ("-", Tag::from("-")), ("e5", Tag::from("e5")),
+		];
+		for (word, tag) in vectors {
+			assert_eq!(read(&format!("[{word}]")).unwrap(), Tag::List(vec![tag].try_into().unwrap()), "{word}");
+		}
+		assert_eq!(read("[B; 1b, true, ]").unwrap(), Tag::ByteArray(vec![1, 1]));
+		assert!(read("[b; 1b]").is_err());
+		assert!(read("[x; 1]").is_err());
+		assert_eq!(read(r#"['it\'s', "a \\ b"]"#).unwrap(), list!("it's", "a \\ b"));
+		assert!(read(r#"["a\nb"]"#).is_err());
+		assert!(read(r#"["\'"]"#).is_err());
+		// The lenient parser reads these differently.
+		assert_eq!(Tag::parse("[b; 1b]").unwrap(), Tag::ByteArray(vec![1]));
+		assert!(Tag::parse("128b").is_err());
+		assert_eq!(Tag::parse(r#""a\nb""#).unwrap(), Tag::from("a\nb"));
+
+		let tag = Tag::parse(r#"{Pos: [0.5d, 64d], id: "minecraft:stone", Count: 1b, Bytes: [B; 1b, -2b], Longs: [L; 3L], "1st": 1, "": "a\"b", "x y": 'say "hi" it\'s', true: 0f}"#).unwrap();
+		let text = to_string_with(&tag, &WriteOptions::vanilla());
+		assert_eq!(text, r#"{"":'a"b',1st:1,Bytes:[B;1B,-2B],Count:1b,Longs:[L;3L],Pos:[0.5d,64.0d],id:"minecraft:stone",true:0.0f,"x y":'say "hi" it\'s'}"#);
+		assert_eq!(read(&text).unwrap(), tag);
+		assert_eq!(to_string_with(&Tag::from("line\nbreak"), &WriteOptions::vanilla()), "\"line\nbreak\"");
+	}
+
+	#[test]
+	fn to_snbt_test() {
+		use super::*;
+		use crate::generator::{ GeneratorConfig, random_tag };
+		let words = list!("stone", "true", "1b", "1.5.pack", "-", "", "minecraft:stone", "a b");
+		assert_eq!(words.to_snbt(&WriteOptions::compact().quotes(QuoteStyle::Bare)), r#"[stone,"true","1b",1.5.pack,-,"","minecraft:stone","a b"]"#);
+		assert_eq!(compound!(("a", "b")).to_snbt(&WriteOptions::vanilla().quotes(QuoteStyle::Always)), r#"{"a":"b"}"#);
+		let arrays = |suffixes| [Tag::ByteArray(vec![1]), Tag::LongArray(vec![2])].map(|tag| tag.to_snbt(&WriteOptions::compact().suffixes(suffixes)));
+		assert_eq!(arrays(SuffixCase::Mixed), ["[B;1b]", "[L;2L]"]);
+		assert_eq!(arrays(SuffixCase::Lower), ["[B;1b]", "[L;2l]"]);
+		assert_eq!(arrays(SuffixCase::Upper), ["[B;1B]", "[L;2L]"]);
+		assert_eq!(list!(1i16, 2i16).to_snbt(&WriteOptions::compact().suffixes(SuffixCase::Upper)), "[1S,2S]");
+		assert_eq!(list!(0.5f32).to_snbt(&WriteOptions::compact().suffixes(SuffixCase::Upper)), "[0.5F]");
+		// SNBT has no form for the type of an empty List.
+		let empty = Tag::List(ListTag::Compound(Vec::new()));
+		assert_eq!(empty.to_snbt(&WriteOptions::compact()), "[]");
+		assert_eq!(Tag::parse(empty.to_snbt(&WriteOptions::compact())).unwrap(), Tag::List(ListTag::Empty));
+
+		// Every combination of options reads back as the same tag.
+		let alphabet: Vec<char> = ('a'..='c').chain('0'..='2').chain(['_', '-', '+', '.', ' ', ':', '"', '\'', '\\', '\n', '\x01', 'é', '😀']).collect();
+		let config = GeneratorConfig { key_alphabet: alphabet.clone(), string_alphabet: alphabet, max_string_len: 4, ..Default::default() };
+		let tags: Vec<Tag> = (0..8).map(|seed| random_tag(&GeneratorConfig { seed, ..config.clone() })).collect();
+		for base in [WriteOptions::compact(), WriteOptions::pretty(), WriteOptions::canonical(), WriteOptions::vanilla()] {
+			for quotes in [QuoteStyle::BareKeys, QuoteStyle::Always, QuoteStyle::Bare] {
+				for suffixes in [SuffixCase::Mixed, SuffixCase::Lower, SuffixCase::Upper] {
+					for escapes in [Escapes::Raw, Escapes::Control, Escapes::NonAscii] {
+						for floats in [FloatFormat::Shortest, FloatFormat::Java] {
+							let options = base.clone().quotes(quotes).suffixes(suffixes).escapes(escapes).floats(floats);
+							for tag in &tags {
+								let text = tag.to_snbt(&options);
+								assert_eq!(Tag::parse(&text).as_ref().ok(), Some(tag), "{options:?}: {text}");
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,222 @@
+#![doc = r#"
+A single tree-building path for [Tag]s from any producer of [Event]s.
+
+An [NbtSource] produces the same [Event]s as the streaming [NbtReader](crate::stream::NbtReader), which is a source
+itself. [Tag::decode_from] and [decode_with] build a tag from any source. The SNBT parser builds its tags the same way,
+and other storage, such as a database cursor, can be read without an intermediate buffer by implementing [NbtSource]
+or by passing an iterator of events.
+
+```
+# use rustnbt::{ compound, stream::Event, tag::{ Tag, TagID } };
+let mut events = [
+	Event::CompoundStart { name: None },
+	Event::Value { name: Some("id".to_owned()), tag: Tag::from("minecraft:stone") },
+	Event::ListStart { name: Some("Pos".to_owned()), id: Some(TagID::Int), length: 2 },
+	Event::Value { name: None, tag: Tag::Int(1) },
+	Event::Value { name: None, tag: Tag::Int(2) },
+	Event::ListEnd,
+	Event::CompoundEnd,
+].into_iter().map(Ok);
+assert_eq!(Tag::decode_from(&mut events)?, compound!(("id", "minecraft:stone"), ("Pos", rustnbt::list!(1, 2))));
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	parse::{ ParseOptions, ParseOutcome, Warning },
+	path::{ PathSegment, TagPath },
+	stream::Event,
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+		NamedTag,
+	},
+};
+
+/// A producer of [Event]s that a [Tag] can be built from. See the [module](self) documentation.<br>
+/// A source produces the events of one root tag. Compound entries must have names, and List elements must not.
+pub trait NbtSource {
+	/// Returns the next event, or None after the root tag has ended.
+	fn next_event(&mut self) -> Result<Option<Event>, NbtError>;
+
+	/// Reads the elements of the array from the last [Event::Array].<br>
+	/// Sources that report arrays whole in [Event::Value] do not need to implement this.
+	fn read_array(&mut self) -> Result<Tag, NbtError> {
+		Err(invalid_data("This source does not report arrays before reading them."))
+	}
+}
+
+impl<I: Iterator<Item = Result<Event, NbtError>>> NbtSource for I {
+	fn next_event(&mut self) -> Result<Option<Event>, NbtError> {
+		self.next().transpose()
+	}
+}
+
+impl Tag {
+	/// Builds a tag from a source, discarding the name of the root tag.
+	pub fn decode_from<S: NbtSource + ?Sized>(source: &mut S) -> Result<Tag, NbtError> {
+		decode_with(source, &ParseOptions::default()).map(|outcome| outcome.value.take_tag())
+	}
+}
+
+impl NamedTag {
+	/// Builds a named tag from a source. The name is empty if the root tag has none.
+	pub fn decode_from<S: NbtSource + ?Sized>(source: &mut S) -> Result<NamedTag, NbtError> {
+		decode_with(source, &ParseOptions::default()).map(ParseOutcome::into_value)
+	}
+}
+
+/// Builds a named tag from a source with [ParseOptions].<br>
+/// Duplicate keys are handled by the key collision policy of the options, and Lists with elements of different types
+/// are an error unless [ParseOptions::allow_mixed_lists] is set.
+pub fn decode_with<S: NbtSource + ?Sized>(source: &mut S, options: &ParseOptions) -> Result<ParseOutcome<NamedTag>, NbtError> {
+	let mut options = options.clone();
+	let (name, tag) = build(source, &mut options)?;
+	Ok(options.finish(NamedTag::with_name(name.unwrap_or_default(), tag)))
+}
+
+/// A Compound or List that is being built, with its name in its parent Compound.
+enum Open {
+	Compound(Option<String>, Map),
+	/// A List with its name and the element type from [Event::ListStart].
+	List(Option<String>, Option<TagID>, Vec<Tag>),
+}
+
+/// Builds a tag from the events of a source, returning the name of the root tag.
+pub(crate) fn build<S: NbtSource + ?Sized>(source: &mut S, options: &mut ParseOptions) -> Result<(Option<String>, Tag), NbtError> {
+	let mut stack: Vec<Open> = Vec::new();
+	// The path of the innermost open Compound or List.
+	let mut path = TagPath::new();
+	loop {
+		let Some(event) = source.next_event()? else {
+			return Err(invalid_data("The source ended before the root tag was finished."));
+		};
+		let (name, tag) = match event {
+			Event::Value { name, tag } => (name, tag),
+			Event::Array { name, .. } => (name, source.read_array()?),
+			Event::CompoundStart { name } => {
+				enter(&stack, &mut path, &name)?;
+				stack.push(Open::Compound(name, Map::default()));
+				continue;
+			}
+			Event::ListStart { name, id, .. } => {
+				enter(&stack, &mut path, &name)?;
+				stack.push(Open::List(name, id, Vec::new()));
+				continue;
+			}
+			Event::CompoundEnd => match stack.pop() {
+				Some(Open::Compound(name, map)) => {
+					path.pop();
+					(name, Tag::Compound(map))
+				}
+				_ => return Err(invalid_data("A Compound ended that was not open.")),
+			},
+			Event::ListEnd => match stack.pop() {
+				Some(Open::List(name, id, tags)) => {
+					let list = list_from_tags(id, tags, options, &path)?;
+					path.pop();
+					(name, Tag::List(list))
+				}
+				_ => return Err(invalid_data("A List ended that was not open.")),
+			},
+		};
+		match stack.last_mut() {
+			None => return Ok((name, tag)),
+			Some(Open::Compound(_, map)) => {
+				let key = name.ok_or_else(|| invalid_data("Compound entries must have names."))?;
+				options.insert(map, || path.clone(), key, tag)?;
+			}
+			Some(Open::List(_, _, tags)) => {
+				if name.is_some() {
+					return Err(invalid_data("List elements must not have names."));
+				}
+				tags.push(tag);
+			}
+		}
+	}
+}
+
+/// Adds the segment of a Compound or List that is starting inside of the open ones to the path.
+fn enter(stack: &[Open], path: &mut TagPath, name: &Option<String>) -> Result<(), NbtError> {
+	match (stack.last(), name) {
+		(None, _) => {}
+		(Some(Open::Compound(..)), Some(name)) => path.push(PathSegment::Key(name.clone())),
+		(Some(Open::List(_, _, tags)), None) => path.push(PathSegment::Index(tags.len())),
+		(Some(Open::Compound(..)), None) => return Err(invalid_data("Compound entries must have names.")),
+		(Some(Open::List(..)), Some(_)) => return Err(invalid_data("List elements must not have names.")),
+	}
+	Ok(())
+}
+
+/// Converts the elements of the List at `path` to a [ListTag], wrapping the elements of a mixed List if the options
+/// allow it. An empty List keeps its element type `id`.
+fn list_from_tags(id: Option<TagID>, tags: Vec<Tag>, options: &mut ParseOptions, path: &TagPath) -> Result<ListTag, NbtError> {
+	if tags.is_empty() {
+		return Ok(ListTag::empty_of(id));
+	}
+	if tags.windows(2).all(|pair| pair[0].id() == pair[1].id()) {
+		return Ok(ListTag::try_from(tags).expect("List elements have the same type."));
+	}
+	if !options.allow_mixed_lists {
+		let place = if path.is_empty() { "the root".to_owned() } else { path.to_string() };
+		return Err(invalid_data(&format!("The elements of the List at {place} have different types.")));
+	}
+	options.warn(Warning::MixedList { path: path.clone() });
+	Ok(ListTag::Compound(tags.into_iter().map(wrap_element).collect()))
+}
+
+/// Wraps an element of a mixed List in a Compound with an empty key, the way the game does, unless it is a Compound
+/// that can not be mistaken for a wrapped element.
+fn wrap_element(tag: Tag) -> Map {
+	match tag {
+		Tag::Compound(map) if !(map.len() == 1 && map.contains_key("")) => map,
+		tag => Map::from_iter([(String::new(), tag)]),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, io::*, stream::NbtReader };
+
+	#[test]
+	fn decode_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("Name", "source"),
+			("Longs", Tag::LongArray(vec![1, 2, 3])),
+			("Items", Tag::List(ListTag::Compound(vec![Map::from_iter([("id".to_owned(), Tag::Short(1))])]))),
+			("Empty", Tag::List(ListTag::Empty))
+		);
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&NamedTag::with_name("Root", tag.clone()))?;
+		assert_eq!(NamedTag::decode_from(&mut NbtReader::new(bytes.as_slice()))?, NamedTag::with_name("Root", tag));
+
+		let events = |events: Vec<Event>| events.into_iter().map(Ok);
+		let mixed = vec![
+			Event::ListStart { name: None, id: Some(TagID::Int), length: 2 },
+			Event::Value { name: None, tag: Tag::Int(1) },
+			Event::Value { name: None, tag: Tag::from("two") },
+			Event::ListEnd,
+		];
+		assert!(Tag::decode_from(&mut events(mixed.clone())).is_err());
+		let outcome = decode_with(&mut events(mixed), &ParseOptions::new().allow_mixed_lists(true))?;
+		assert_eq!(outcome.value.tag(), &Tag::List(ListTag::Compound(vec![
+			Map::from_iter([(String::new(), Tag::Int(1))]),
+			Map::from_iter([(String::new(), Tag::from("two"))]),
+		])));
+		assert_eq!(outcome.warnings, [Warning::MixedList { path: TagPath::new() }]);
+
+		assert!(Tag::decode_from(&mut events(vec![Event::CompoundStart { name: None }])).is_err());
+		assert!(Tag::decode_from(&mut events(vec![Event::CompoundStart { name: None }, Event::ListEnd])).is_err());
+		assert!(Tag::decode_from(&mut events(vec![
+			Event::CompoundStart { name: None },
+			Event::Value { name: None, tag: Tag::Int(1) },
+			Event::CompoundEnd,
+		])).is_err());
+		Ok(())
+	}
+}
@@ -0,0 +1,289 @@
+#![doc = r#"
+Storage of [Tag] trees in a SQLite database, enabled with the `sqlite` feature.
+
+Every tag in a tree, including Compounds and Lists, is stored as one row of the `nbt` table:
+
+| Column | Contents |
+|--------|----------|
+| `tree` | name of the tree the row belongs to, so one database can hold many files |
+| `seq` | position of the row within its tree, in depth-first order |
+| `path` | [TagPath] of the tag, which is `''` for the root |
+| `type` | title of the tag type, such as `'Int'` or `'Compound'` |
+| `value` | INTEGER for Byte, Short, Int, and Long, REAL for Float and Double, TEXT for String, BLOB of big endian values for the arrays, and NULL for Compound and List |
+
+This allows ad-hoc queries over the stored trees, such as
+`SELECT tree, value FROM nbt WHERE path LIKE 'Inventory[%].id' AND value = 'minecraft:elytra'`.
+
+```
+# use rustnbt::{ compound, tag::Tag };
+let connection = rusqlite::Connection::open_in_memory().unwrap();
+rustnbt::sqlite::create_table(&connection).unwrap();
+let tag = compound!(("Health", Tag::Float(20.0)), ("Pos", Tag::IntArray(vec![1, 2, 3])));
+rustnbt::sqlite::store(&connection, "player", &tag).unwrap();
+assert_eq!(rustnbt::sqlite::load(&connection, "player").unwrap(), Some(tag));
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	invalid_data,
+	path::{ PathSegment, TagPath },
+	tag::{
+		Tag,
+		ListTag,
+	},
+};
+use rusqlite::{ Connection, Statement, params, types::Value };
+
+/// Creates the `nbt` table and its index if they do not exist yet.
+pub fn create_table(connection: &Connection) -> Result<(), NbtError> {
+	connection.execute_batch("
+		CREATE TABLE IF NOT EXISTS nbt (
+			tree TEXT NOT NULL,
+			seq INTEGER NOT NULL,
+			path TEXT NOT NULL,
+			type TEXT NOT NULL,
+			value,
+			PRIMARY KEY (tree, seq)
+		);
+		CREATE INDEX IF NOT EXISTS nbt_path ON nbt (path);
+	")?;
+	Ok(())
+}
+
+/// Stores a tree under the given name, replacing any tree that was stored with that name before.
+/// Returns the number of rows written.
+pub fn store(connection: &Connection, tree: &str, tag: &Tag) -> Result<usize, NbtError> {
+	let transaction = connection.unchecked_transaction()?;
+	transaction.execute("DELETE FROM nbt WHERE tree = ?1", [tree])?;
+	let mut writer = RowWriter {
+		statement: transaction.prepare("INSERT INTO nbt (tree, seq, path, type, value) VALUES (?1, ?2, ?3, ?4, ?5)")?,
+		tree,
+		seq: 0,
+		path: TagPath::new(),
+	};
+	writer.tag(tag)?;
+	let rows = writer.seq;
+	drop(writer);
+	transaction.commit()?;
+	Ok(rows)
+}
+
+/// Loads the tree stored under the given name, or returns `None` if there is no such tree.
+pub fn load(connection: &Connection, tree: &str) -> Result<Option<Tag>, NbtError> {
+	let mut statement = connection.prepare("SELECT path, type, value FROM nbt WHERE tree = ?1 ORDER BY seq")?;
+	let rows = statement.query_map([tree], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Value>(2)?)))?;
+	let mut nodes = Vec::new();
+	for row in rows {
+		let (path, kind, value) = row?;
+		let path: TagPath = path.parse().map_err(|_| invalid_data("Invalid path stored in SQLite database."))?;
+		nodes.push(Node { segments: path.into(), kind, value });
+	}
+	if nodes.is_empty() {
+		return Ok(None);
+	}
+	let mut position = 0;
+	let tag = build(&mut nodes, &mut position)?;
+	if position != nodes.len() {
+		return Err(invalid_data("Tree stored in SQLite database has rows outside of the root tag."));
+	}
+	Ok(Some(tag))
+}
+
+/// Deletes the tree stored under the given name. Returns `true` if it existed.
+pub fn delete(connection: &Connection, tree: &str) -> Result<bool, NbtError> {
+	Ok(connection.execute("DELETE FROM nbt WHERE tree = ?1", [tree])? > 0)
+}
+
+/// Returns the names of all stored trees, in sorted order.
+pub fn trees(connection: &Connection) -> Result<Vec<String>, NbtError> {
+	let mut statement = connection.prepare("SELECT DISTINCT tree FROM nbt ORDER BY tree")?;
+	let trees = statement.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?;
+	Ok(trees)
+}
+
+struct RowWriter<'a> {
+	statement: Statement<'a>,
+	tree: &'a str,
+	seq: usize,
+	path: TagPath,
+}
+
+impl RowWriter<'_> {
+	fn row(&mut self, title: &str, value: Value) -> Result<(), NbtError> {
+		self.statement.execute(params![self.tree, self.seq as i64, self.path.to_string(), title, value])?;
+		self.seq += 1;
+		Ok(())
+	}
+
+	fn tag(&mut self, tag: &Tag) -> Result<(), NbtError> {
+		let value = match tag {
+			Tag::Byte(value) => Value::Integer(*value as i64),
+			Tag::Short(value) => Value::Integer(*value as i64),
+			Tag::Int(value) => Value::Integer(*value as i64),
+			Tag::Long(value) => Value::Integer(*value),
+			Tag::Float(value) => Value::Real(*value as f64),
+			Tag::Double(value) => Value::Real(*value),
+			Tag::ByteArray(values) => Value::Blob(bytemuck::cast_slice(values).to_vec()),
+			Tag::String(value) => Value::Text(value.clone()),
+			Tag::IntArray(values) => Value::Blob(values.iter().flat_map(|value| value.to_be_bytes()).collect()),
+			Tag::LongArray(values) => Value::Blob(values.iter().flat_map(|value| value.to_be_bytes()).collect()),
+			Tag::List(list) => return self.list(list),
+			Tag::Compound(map) => return self.map(map),
+		};
+		self.row(tag.title(), value)
+	}
+
+	fn map(&mut self, map: &Map) -> Result<(), NbtError> {
+		self.row("Compound", Value::Null)?;
+		for (key, tag) in map.iter() {
+			self.path.push(PathSegment::Key(key.clone()));
+			self.tag(tag)?;
+			self.path.pop();
+		}
+		Ok(())
+	}
+
+	fn list(&mut self, list: &ListTag) -> Result<(), NbtError> {
+		self.row("List", Value::Null)?;
+		for index in 0..list.len() {
+			self.path.push(PathSegment::Index(index));
+			macro_rules! element {
+				($($title:ident)+) => {
+					match list {
+						ListTag::Empty => Ok(()),
+						ListTag::Compound(maps) => self.map(&maps[index]),
+						ListTag::List(lists) => self.list(&lists[index]),
+						$(
+							ListTag::$title(values) => self.tag(&Tag::$title(values[index].clone())),
+						)+
+					}
+				};
+			}
+			element!(Byte Short Int Long Float Double ByteArray String IntArray LongArray)?;
+			self.path.pop();
+		}
+		Ok(())
+	}
+}
+
+/// A row that was loaded from the database.
+struct Node {
+	segments: Vec<PathSegment>,
+	kind: String,
+	value: Value,
+}
+
+fn mismatch() -> NbtError {
+	invalid_data("Value stored in SQLite database does not match its type.")
+}
+
+fn integer<T: TryFrom<i64>>(value: &Value) -> Result<T, NbtError> {
+	match value {
+		Value::Integer(value) => T::try_from(*value).map_err(|_| mismatch()),
+		_ => Err(mismatch()),
+	}
+}
+
+fn real(value: &Value) -> Result<f64, NbtError> {
+	match value {
+		Value::Real(value) => Ok(*value),
+		Value::Integer(value) => Ok(*value as f64),
+		_ => Err(mismatch()),
+	}
+}
+
+fn blob<T, const N: usize>(value: &Value, from_be_bytes: fn([u8; N]) -> T) -> Result<Vec<T>, NbtError> {
+	match value {
+		Value::Blob(bytes) if bytes.len().is_multiple_of(N) => Ok(bytes.chunks_exact(N).map(|chunk| from_be_bytes(chunk.try_into().expect("Chunk has N bytes."))).collect()),
+		_ => Err(mismatch()),
+	}
+}
+
+/// Builds the tag at `nodes[*position]` and all of its descendants, which follow it in depth-first order.
+fn build(nodes: &mut [Node], position: &mut usize) -> Result<Tag, NbtError> {
+	let index = *position;
+	*position += 1;
+	let depth = nodes[index].segments.len();
+	let is_child = |nodes: &[Node], position: usize| nodes.get(position).is_some_and(|node| node.segments.len() == depth + 1);
+	let value = std::mem::replace(&mut nodes[index].value, Value::Null);
+	Ok(match nodes[index].kind.as_str() {
+		"Byte" => Tag::Byte(integer(&value)?),
+		"Short" => Tag::Short(integer(&value)?),
+		"Int" => Tag::Int(integer(&value)?),
+		"Long" => Tag::Long(integer(&value)?),
+		"Float" => Tag::Float(real(&value)? as f32),
+		"Double" => Tag::Double(real(&value)?),
+		"ByteArray" => Tag::ByteArray(blob(&value, i8::from_be_bytes)?),
+		"String" => match value {
+			Value::Text(value) => Tag::String(value),
+			_ => return Err(mismatch()),
+		},
+		"IntArray" => Tag::IntArray(blob(&value, i32::from_be_bytes)?),
+		"LongArray" => Tag::LongArray(blob(&value, i64::from_be_bytes)?),
+		"List" => {
+			let mut tags = Vec::new();
+			while is_child(nodes, *position) {
+				tags.push(build(nodes, position)?);
+			}
+			Tag::List(ListTag::try_from(tags).map_err(|_| mismatch())?)
+		}
+		"Compound" => {
+			let mut map = Map::default();
+			while is_child(nodes, *position) {
+				let Some(PathSegment::Key(key)) = nodes[*position].segments.last().cloned() else {
+					return Err(mismatch());
+				};
+				map.insert(key, build(nodes, position)?);
+			}
+			Tag::Compound(map)
+		}
+		_ => return Err(invalid_data("Unknown tag type stored in SQLite database.")),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn roundtrip_test() -> Result<(), NbtError> {
+		let connection = Connection::open_in_memory()?;
+		create_table(&connection)?;
+		let tag = compound!(
+			("byte", Tag::Byte(-3)),
+			("short", Tag::Short(300)),
+			("long", Tag::Long(i64::MIN)),
+			("float", Tag::Float(0.1)),
+			("double", Tag::Double(0.1)),
+			("bytes", Tag::ByteArray(vec![-1, 2])),
+			("ints", Tag::IntArray(vec![-1, i32::MAX])),
+			("longs", Tag::LongArray(vec![i64::MAX])),
+			("strings", list!("a", "b")),
+			("empty", list!()),
+			("empty compound", Tag::Compound(Map::default())),
+			("nested", Tag::List(ListTag::List(vec![ListTag::Int(vec![1]), ListTag::Empty]))),
+			("items", Tag::List(ListTag::Compound(vec![
+				Map::from_iter([("id".to_owned(), Tag::from("minecraft:elytra")), ("Count".to_owned(), Tag::Byte(1))]),
+			])))
+		);
+		assert_eq!(store(&connection, "player", &tag)?, 22);
+		store(&connection, "other", &Tag::Int(5))?;
+		assert_eq!(load(&connection, "player")?, Some(tag.clone()));
+		assert_eq!(load(&connection, "missing")?, None);
+		assert_eq!(trees(&connection)?, vec!["other".to_owned(), "player".to_owned()]);
+
+		let id: String = connection.query_row(
+			"SELECT value FROM nbt WHERE tree = 'player' AND path LIKE 'items[%].id'", [], |row| row.get(0)
+		)?;
+		assert_eq!(id, "minecraft:elytra");
+
+		store(&connection, "player", &Tag::Byte(1))?;
+		assert_eq!(load(&connection, "player")?, Some(Tag::Byte(1)));
+		assert!(delete(&connection, "player")?);
+		assert!(!delete(&connection, "player")?);
+		Ok(())
+	}
+}
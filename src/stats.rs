@@ -0,0 +1,233 @@
+#![doc = r#"
+Statistics over the numbers of arrays and numeric Lists, such as heightmaps and light levels.
+
+[Tag::numbers] borrows the elements of a ByteArray, IntArray, LongArray, or a List of numbers as [Numbers], which
+computes the [min](Numbers::min), [max](Numbers::max), [sum](Numbers::sum), [mean](Numbers::mean), and a
+[histogram](Numbers::histogram) of the elements in place, without copying them.
+
+Integer sums are exact. NaN Floats and Doubles are skipped by the minimum, maximum, and histogram, and make the sum
+and mean NaN.
+
+```
+# use rustnbt::{ list, tag::Tag, view::NumberView };
+let levels = Tag::ByteArray(vec![0, 15, 15, 7, 3]);
+let numbers = levels.numbers().unwrap();
+assert_eq!((numbers.min(), numbers.max()), (Some(NumberView::Byte(0)), Some(NumberView::Byte(15))));
+assert_eq!(numbers.sum(), 40.0);
+assert_eq!(numbers.mean(), Some(8.0));
+assert_eq!(numbers.histogram(3).unwrap().counts, [2, 1, 2]);
+assert!(list!("a", "b").numbers().is_none());
+```
+"#]
+
+use crate::{
+	tag::{ Tag, TagID, ListTag },
+	view::NumberView,
+};
+
+/// The borrowed elements of an array or numeric List. See the [module](self) documentation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Numbers<'a> {
+	Byte(&'a [i8]),
+	Short(&'a [i16]),
+	Int(&'a [i32]),
+	Long(&'a [i64]),
+	Float(&'a [f32]),
+	Double(&'a [f64]),
+}
+
+/// The number of values in equal ranges between the minimum and maximum of some [Numbers].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Histogram {
+	/// The start of the first range, which is the minimum.
+	pub start: f64,
+	/// The width of each range. The last range also holds the maximum.
+	pub width: f64,
+	/// The number of values in each range.
+	pub counts: Vec<usize>,
+}
+
+impl Histogram {
+	/// The start of the range of a bucket.
+	pub fn bucket_start(&self, index: usize) -> f64 {
+		self.start + self.width * index as f64
+	}
+}
+
+impl Tag {
+	/// Borrows the elements of a ByteArray, IntArray, LongArray, or List of numbers, or returns None for other tags.
+	/// An empty List has no element type, so it gives None too.
+	pub fn numbers(&self) -> Option<Numbers<'_>> {
+		match self {
+			Tag::ByteArray(values) => Some(Numbers::Byte(values)),
+			Tag::IntArray(values) => Some(Numbers::Int(values)),
+			Tag::LongArray(values) => Some(Numbers::Long(values)),
+			Tag::List(list) => list.numbers(),
+			_ => None,
+		}
+	}
+}
+
+impl ListTag {
+	/// Borrows the elements of a List of numbers, or returns None for Lists of other types and for empty Lists.
+	pub fn numbers(&self) -> Option<Numbers<'_>> {
+		match self {
+			ListTag::Byte(values) => Some(Numbers::Byte(values)),
+			ListTag::Short(values) => Some(Numbers::Short(values)),
+			ListTag::Int(values) => Some(Numbers::Int(values)),
+			ListTag::Long(values) => Some(Numbers::Long(values)),
+			ListTag::Float(values) => Some(Numbers::Float(values)),
+			ListTag::Double(values) => Some(Numbers::Double(values)),
+			_ => None,
+		}
+	}
+}
+
+/// Evaluates `$integer` with `$values` bound to the slice of an integer variant, or `$float` with `$floats` bound to the
+/// slice of a float variant.
+macro_rules! each {
+	($numbers:expr, $values:ident => $integer:expr, $floats:ident => $float:expr) => {
+		match $numbers {
+			Numbers::Byte($values) => $integer,
+			Numbers::Short($values) => $integer,
+			Numbers::Int($values) => $integer,
+			Numbers::Long($values) => $integer,
+			Numbers::Float($floats) => $float,
+			Numbers::Double($floats) => $float,
+		}
+	};
+}
+
+/// The least or greatest element of some [Numbers], with `$extreme` being `min` or `max`.
+macro_rules! extreme {
+	($numbers:expr, $extreme:ident) => {
+		match $numbers {
+			Numbers::Byte(values) => values.iter().copied().$extreme().map(NumberView::Byte),
+			Numbers::Short(values) => values.iter().copied().$extreme().map(NumberView::Short),
+			Numbers::Int(values) => values.iter().copied().$extreme().map(NumberView::Int),
+			Numbers::Long(values) => values.iter().copied().$extreme().map(NumberView::Long),
+			Numbers::Float(values) => values.iter().copied().filter(|value| !value.is_nan()).reduce(f32::$extreme).map(NumberView::Float),
+			Numbers::Double(values) => values.iter().copied().filter(|value| !value.is_nan()).reduce(f64::$extreme).map(NumberView::Double),
+		}
+	};
+}
+
+impl<'a> Numbers<'a> {
+	/// Returns the NBT type ID of the elements.
+	pub fn element_id(self) -> TagID {
+		match self {
+			Numbers::Byte(_) => TagID::Byte,
+			Numbers::Short(_) => TagID::Short,
+			Numbers::Int(_) => TagID::Int,
+			Numbers::Long(_) => TagID::Long,
+			Numbers::Float(_) => TagID::Float,
+			Numbers::Double(_) => TagID::Double,
+		}
+	}
+
+	pub fn len(self) -> usize {
+		each!(self, values => values.len(), values => values.len())
+	}
+
+	pub fn is_empty(self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the element at `index`, or None if it is out of bounds.
+	pub fn get(self, index: usize) -> Option<NumberView> {
+		match self {
+			Numbers::Byte(values) => values.get(index).copied().map(NumberView::Byte),
+			Numbers::Short(values) => values.get(index).copied().map(NumberView::Short),
+			Numbers::Int(values) => values.get(index).copied().map(NumberView::Int),
+			Numbers::Long(values) => values.get(index).copied().map(NumberView::Long),
+			Numbers::Float(values) => values.get(index).copied().map(NumberView::Float),
+			Numbers::Double(values) => values.get(index).copied().map(NumberView::Double),
+		}
+	}
+
+	/// Iterates over the elements.
+	pub fn iter(self) -> impl Iterator<Item = NumberView> + 'a {
+		(0..self.len()).map(move |index| self.get(index).expect("The index is in bounds."))
+	}
+
+	/// The least element, or None if there are none. NaNs are skipped.
+	pub fn min(self) -> Option<NumberView> {
+		extreme!(self, min)
+	}
+
+	/// The greatest element, or None if there are none. NaNs are skipped.
+	pub fn max(self) -> Option<NumberView> {
+		extreme!(self, max)
+	}
+
+	/// The sum of the elements as an [f64]. Integer sums are computed exactly and then converted.
+	pub fn sum(self) -> f64 {
+		match self {
+			Numbers::Float(values) => values.iter().map(|value| *value as f64).sum(),
+			Numbers::Double(values) => values.iter().sum(),
+			_ => self.integer_sum().expect("The elements are integers.") as f64,
+		}
+	}
+
+	/// The exact sum of the elements of an integer type, or None for Floats and Doubles.
+	pub fn integer_sum(self) -> Option<i128> {
+		each!(self, values => Some(values.iter().map(|value| *value as i128).sum()), _values => None)
+	}
+
+	/// The mean of the elements, or None if there are none.
+	pub fn mean(self) -> Option<f64> {
+		(!self.is_empty()).then(|| self.sum() / self.len() as f64)
+	}
+
+	/// Counts the elements in `buckets` ranges of equal width from the minimum to the maximum, or returns None if
+	/// there are no buckets or no elements other than NaN. If every element is the same, they are all in the first
+	/// bucket, and the width is 0.
+	pub fn histogram(self, buckets: usize) -> Option<Histogram> {
+		let (min, max) = (self.min()?.as_f64(), self.max()?.as_f64());
+		if buckets == 0 {
+			return None;
+		}
+		let width = (max - min) / buckets as f64;
+		let mut counts = vec![0; buckets];
+		let mut count = |value: f64| {
+			if !value.is_nan() {
+				let bucket = if width > 0.0 { ((value - min) / width) as usize } else { 0 };
+				counts[bucket.min(buckets - 1)] += 1;
+			}
+		};
+		self.iter().for_each(|value| count(value.as_f64()));
+		Some(Histogram { start: min, width, counts })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::list;
+
+	#[test]
+	fn stats_test() {
+		let heights = Tag::LongArray(vec![i64::MAX, i64::MAX, -4]);
+		let numbers = heights.numbers().unwrap();
+		assert_eq!(numbers.integer_sum(), Some(2 * i64::MAX as i128 - 4));
+		assert_eq!(numbers.min(), Some(NumberView::Long(-4)));
+		assert_eq!(numbers.element_id(), TagID::Long);
+
+		let list = list!(1.0f32, f32::NAN, -2.5f32, 4.0f32);
+		let numbers = list.numbers().unwrap();
+		assert_eq!((numbers.min(), numbers.max()), (Some(NumberView::Float(-2.5)), Some(NumberView::Float(4.0))));
+		assert_eq!(numbers.integer_sum(), None);
+		assert!(numbers.mean().unwrap().is_nan());
+		let histogram = numbers.histogram(2).unwrap();
+		assert_eq!((histogram.start, histogram.width, histogram.counts), (-2.5, 3.25, vec![1, 2]));
+
+		let same = Tag::IntArray(vec![7, 7]);
+		assert_eq!(same.numbers().unwrap().histogram(4).unwrap().counts, [2, 0, 0, 0]);
+		let empty = Tag::IntArray(vec![]);
+		let numbers = empty.numbers().unwrap();
+		assert_eq!((numbers.min(), numbers.mean(), numbers.histogram(2)), (None, None, None));
+		assert_eq!(numbers.sum(), 0.0);
+		assert!(Tag::List(ListTag::Empty).numbers().is_none());
+		assert!(same.numbers().unwrap().histogram(0).is_none());
+	}
+}
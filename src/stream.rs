@@ -0,0 +1,337 @@
+#![doc = r#"
+A streaming reader for binary NBT, which reads one tag at a time instead of building the whole tree.
+
+[NbtReader] turns a reader into a sequence of [Event]s. Compounds and Lists are reported as start and end events,
+and arrays are reported by their type and length before any element is read. An array can then be read in
+fixed-size chunks with [NbtReader::byte_chunks], [NbtReader::int_chunks], or [NbtReader::long_chunks], so that
+statistics can be computed over huge arrays with constant memory. Arrays that are not read are skipped.
+
+```
+# use rustnbt::{ compound, tag::{ Tag, TagID, NamedTag }, io::*, stream::* };
+let mut bytes = Vec::new();
+bytes.write_nbt(&NamedTag::new(compound!(("Data", Tag::LongArray((1..=10).collect())))))?;
+let mut reader = NbtReader::new(bytes.as_slice());
+let mut sum = 0;
+while let Some(event) = reader.next_event()? {
+	if let Event::Array { id: TagID::LongArray, .. } = event {
+		for chunk in reader.long_chunks(4)? {
+			sum += chunk?.iter().sum::<i64>();
+		}
+	}
+}
+assert_eq!(sum, 55);
+# Ok::<(), rustnbt::NbtError>(())
+```
+"#]
+
+use crate::{
+	NbtError,
+	io::NbtRead,
+	source::NbtSource,
+	tag::{
+		Tag,
+		TagID,
+	},
+};
+use std::{
+	io::Read,
+	marker::PhantomData,
+};
+
+/// A step through binary NBT, as returned by [NbtReader::next_event].<br>
+/// Names are `Some` for the root tag and for Compound entries, and `None` for List elements.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Event {
+	/// A Byte, Short, Int, Long, Float, Double, or String. Other [sources](crate::source::NbtSource) can also report
+	/// arrays whole with this event.
+	Value { name: Option<String>, tag: Tag },
+	/// A ByteArray, IntArray, or LongArray, whose elements have not been read yet.
+	Array { name: Option<String>, id: TagID, length: usize },
+	/// The start of a Compound. Its entries follow, then [Event::CompoundEnd].
+	CompoundStart { name: Option<String> },
+	CompoundEnd,
+	/// The start of a List. Its elements follow, then [Event::ListEnd]. `id` is None for Lists of End tags, which are
+	/// empty, like [ListTag::Empty](crate::tag::ListTag::Empty).
+	ListStart { name: Option<String>, id: Option<TagID>, length: usize },
+	ListEnd,
+}
+
+enum Frame {
+	Compound,
+	List { id: TagID, remaining: usize },
+}
+
+/// Reads binary NBT as a sequence of [Event]s. See the [module](self) documentation.
+pub struct NbtReader<R: Read> {
+	reader: R,
+	stack: Vec<Frame>,
+	/// The type and number of unread elements of the array from the last [Event::Array].
+	array: Option<(TagID, usize)>,
+	started: bool,
+}
+
+impl<R: Read> NbtReader<R> {
+	/// Creates a reader for a named root tag, such as the contents of an uncompressed NBT file.
+	pub fn new(reader: R) -> Self {
+		Self { reader, stack: Vec::new(), array: None, started: false }
+	}
+
+	/// Returns the underlying reader.
+	pub fn into_inner(self) -> R {
+		self.reader
+	}
+
+	/// How many Compounds and Lists the reader is inside of.
+	pub fn depth(&self) -> usize {
+		self.stack.len()
+	}
+
+	/// Reads the next event, or returns `None` after the root tag has ended.<br>
+	/// The unread elements of the last array are skipped.
+	pub fn next_event(&mut self) -> Result<Option<Event>, NbtError> {
+		self.skip_array()?;
+		match self.stack.last_mut() {
+			None if self.started => Ok(None),
+			None => {
+				self.started = true;
+				let id = TagID::nbt_read(&mut self.reader)?;
+				let name = String::nbt_read(&mut self.reader)?;
+				self.start(Some(name), id).map(Some)
+			}
+			Some(Frame::Compound) => match u8::nbt_read(&mut self.reader)? {
+				0 => {
+					self.stack.pop();
+					Ok(Some(Event::CompoundEnd))
+				}
+				id => {
+					let id = TagID::try_from(id)?;
+					let name = String::nbt_read(&mut self.reader)?;
+					self.start(Some(name), id).map(Some)
+				}
+			},
+			Some(Frame::List { remaining: 0, .. }) => {
+				self.stack.pop();
+				Ok(Some(Event::ListEnd))
+			}
+			Some(Frame::List { id, remaining }) => {
+				*remaining -= 1;
+				let id = *id;
+				self.start(None, id).map(Some)
+			}
+		}
+	}
+
+	fn start(&mut self, name: Option<String>, id: TagID) -> Result<Event, NbtError> {
+		Ok(match id {
+			TagID::Compound => {
+				self.stack.push(Frame::Compound);
+				Event::CompoundStart { name }
+			}
+			TagID::List => {
+				let element = u8::nbt_read(&mut self.reader)?;
+				let length = u32::nbt_read(&mut self.reader)? as usize;
+				// Lists of End tags are read as empty, like ListTag::Empty.
+				let id = TagID::list_element(element)?;
+				match id {
+					Some(element) => self.stack.push(Frame::List { id: element, remaining: length }),
+					None if length == 0 => self.stack.push(Frame::List { id: TagID::Byte, remaining: 0 }),
+					None => return Err(crate::invalid_data("List of End tags has elements.")),
+				}
+				Event::ListStart { name, id, length }
+			}
+			TagID::ByteArray | TagID::IntArray | TagID::LongArray => {
+				let length = u32::nbt_read(&mut self.reader)? as usize;
+				self.array = Some((id, length));
+				Event::Array { name, id, length }
+			}
+			TagID::Byte => Event::Value { name, tag: Tag::Byte(i8::nbt_read(&mut self.reader)?) },
+			TagID::Short => Event::Value { name, tag: Tag::Short(i16::nbt_read(&mut self.reader)?) },
+			TagID::Int => Event::Value { name, tag: Tag::Int(i32::nbt_read(&mut self.reader)?) },
+			TagID::Long => Event::Value { name, tag: Tag::Long(i64::nbt_read(&mut self.reader)?) },
+			TagID::Float => Event::Value { name, tag: Tag::Float(f32::nbt_read(&mut self.reader)?) },
+			TagID::Double => Event::Value { name, tag: Tag::Double(f64::nbt_read(&mut self.reader)?) },
+			TagID::String => Event::Value { name, tag: Tag::String(String::nbt_read(&mut self.reader)?) },
+		})
+	}
+
+	fn skip_array(&mut self) -> Result<(), NbtError> {
+		if let Some((id, remaining)) = self.array.take() {
+			let size = (remaining * element_size(id)) as u64;
+			let skipped = std::io::copy(&mut (&mut self.reader).take(size), &mut std::io::sink())?;
+			if skipped < size {
+				return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+			}
+		}
+		Ok(())
+	}
+
+	/// Reads the rest of the last array as a whole tag.
+	pub fn read_array(&mut self) -> Result<Tag, NbtError> {
+		Ok(match self.array {
+			Some((TagID::ByteArray, _)) => Tag::ByteArray(self.byte_chunks(usize::MAX)?.flatten_chunks()?),
+			Some((TagID::IntArray, _)) => Tag::IntArray(self.int_chunks(usize::MAX)?.flatten_chunks()?),
+			Some((TagID::LongArray, _)) => Tag::LongArray(self.long_chunks(usize::MAX)?.flatten_chunks()?),
+			_ => return Err(crate::invalid_data("No array to read.")),
+		})
+	}
+
+	/// Reads the rest of the last array, which must be a ByteArray, in chunks of up to `chunk_len` elements.
+	pub fn byte_chunks(&mut self, chunk_len: usize) -> Result<ArrayChunks<'_, R, i8>, NbtError> {
+		self.chunks(TagID::ByteArray, chunk_len)
+	}
+
+	/// Reads the rest of the last array, which must be an IntArray, in chunks of up to `chunk_len` elements.
+	pub fn int_chunks(&mut self, chunk_len: usize) -> Result<ArrayChunks<'_, R, i32>, NbtError> {
+		self.chunks(TagID::IntArray, chunk_len)
+	}
+
+	/// Reads the rest of the last array, which must be a LongArray, in chunks of up to `chunk_len` elements.
+	pub fn long_chunks(&mut self, chunk_len: usize) -> Result<ArrayChunks<'_, R, i64>, NbtError> {
+		self.chunks(TagID::LongArray, chunk_len)
+	}
+
+	fn chunks<T: NbtRead>(&mut self, id: TagID, chunk_len: usize) -> Result<ArrayChunks<'_, R, T>, NbtError> {
+		if chunk_len == 0 {
+			return Err(crate::invalid_data("Chunk length must not be zero."));
+		}
+		match self.array {
+			Some((found, _)) if found == id => Ok(ArrayChunks { reader: self, chunk_len, buffer: Vec::new(), element: PhantomData }),
+			Some((found, _)) => Err(crate::invalid_data(&format!("Expected {}, found {}.", id.title(), found.title()))),
+			None => Err(crate::invalid_data("No array to read.")),
+		}
+	}
+}
+
+impl<R: Read> NbtSource for NbtReader<R> {
+	fn next_event(&mut self) -> Result<Option<Event>, NbtError> {
+		NbtReader::next_event(self)
+	}
+
+	fn read_array(&mut self) -> Result<Tag, NbtError> {
+		NbtReader::read_array(self)
+	}
+}
+
+fn element_size(id: TagID) -> usize {
+	match id {
+		TagID::IntArray => 4,
+		TagID::LongArray => 8,
+		_ => 1,
+	}
+}
+
+/// An iterator over the elements of an array in chunks, from [NbtReader::byte_chunks],
+/// [NbtReader::int_chunks], or [NbtReader::long_chunks].
+pub struct ArrayChunks<'a, R: Read, T> {
+	reader: &'a mut NbtReader<R>,
+	chunk_len: usize,
+	/// Reused between chunks, so reading takes the memory of one chunk.
+	buffer: Vec<u8>,
+	element: PhantomData<T>,
+}
+
+impl<R: Read, T: NbtRead> ArrayChunks<'_, R, T> {
+	fn flatten_chunks(self) -> Result<Vec<T>, NbtError> {
+		let mut values = Vec::new();
+		for chunk in self {
+			values.extend(chunk?);
+		}
+		Ok(values)
+	}
+}
+
+impl<R: Read, T: NbtRead> Iterator for ArrayChunks<'_, R, T> {
+	type Item = Result<Vec<T>, NbtError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (id, remaining) = self.reader.array.as_mut()?;
+		if *remaining == 0 {
+			self.reader.array = None;
+			return None;
+		}
+		let count = self.chunk_len.min(*remaining);
+		*remaining -= count;
+		let size = element_size(*id);
+		// Grow the buffer as data arrives, so that a corrupt length can not allocate more than was read.
+		self.buffer.clear();
+		let read = (&mut self.reader.reader).take((count * size) as u64).read_to_end(&mut self.buffer);
+		match read {
+			Ok(read) if read == count * size => {
+				let mut bytes = self.buffer.as_slice();
+				Some((0..count).map(|_| T::nbt_read(&mut bytes)).collect())
+			}
+			Ok(_) => {
+				self.reader.array = None;
+				Some(Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()))
+			}
+			Err(err) => {
+				self.reader.array = None;
+				Some(Err(err.into()))
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, io::*, tag::{ ListTag, NamedTag } };
+
+	#[test]
+	fn events_test() -> Result<(), NbtError> {
+		let tag = compound!(
+			("Name", "stream"),
+			("Bytes", Tag::ByteArray(vec![1, 2, 3])),
+			("Values", list!(1, 2)),
+			("Empty", Tag::List(ListTag::Empty))
+		);
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&NamedTag::with_name("Root", tag))?;
+		let mut reader = NbtReader::new(bytes.as_slice());
+		let mut events = Vec::new();
+		while let Some(event) = reader.next_event()? {
+			events.push(event);
+		}
+		assert_eq!(events.first(), Some(&Event::CompoundStart { name: Some("Root".to_owned()) }));
+		assert_eq!(events.last(), Some(&Event::CompoundEnd));
+		assert_eq!(events.len(), 10);
+		assert!(events.contains(&Event::Value { name: Some("Name".to_owned()), tag: Tag::from("stream") }));
+		assert!(events.contains(&Event::Array { name: Some("Bytes".to_owned()), id: TagID::ByteArray, length: 3 }));
+		assert!(events.contains(&Event::ListStart { name: Some("Values".to_owned()), id: Some(TagID::Int), length: 2 }));
+		assert!(events.contains(&Event::Value { name: None, tag: Tag::Int(2) }));
+		assert!(events.contains(&Event::ListStart { name: Some("Empty".to_owned()), id: None, length: 0 }));
+		assert_eq!(events.iter().filter(|event| **event == Event::ListEnd).count(), 2);
+		Ok(())
+	}
+
+	#[test]
+	fn chunks_test() -> Result<(), NbtError> {
+		let values: Vec<i64> = (0..1000).collect();
+		let mut bytes = Vec::new();
+		bytes.write_nbt(&NamedTag::new(compound!(("Longs", Tag::LongArray(values.clone())))))?;
+		let mut reader = NbtReader::new(bytes.as_slice());
+		reader.next_event()?;
+		assert_eq!(reader.next_event()?, Some(Event::Array { name: Some("Longs".to_owned()), id: TagID::LongArray, length: 1000 }));
+		assert!(reader.int_chunks(16).is_err());
+		let mut chunks = reader.long_chunks(300)?;
+		let first = chunks.next().unwrap()?;
+		assert_eq!(first, values[..300]);
+		let lengths: Vec<usize> = chunks.map(|chunk| chunk.map(|chunk| chunk.len())).collect::<Result<_, _>>()?;
+		assert_eq!(lengths, [300, 300, 100]);
+		assert_eq!(reader.next_event()?, Some(Event::CompoundEnd));
+
+		// Unread arrays are skipped, and partly read arrays can be finished with read_array.
+		let mut reader = NbtReader::new(bytes.as_slice());
+		reader.next_event()?;
+		reader.next_event()?;
+		reader.long_chunks(10)?.next();
+		assert_eq!(reader.read_array()?, Tag::LongArray(values[10..].to_vec()));
+		assert_eq!(reader.next_event()?, Some(Event::CompoundEnd));
+
+		let mut truncated = NbtReader::new(&bytes[..100]);
+		truncated.next_event()?;
+		truncated.next_event()?;
+		assert!(truncated.long_chunks(8)?.any(|chunk| chunk.is_err()));
+		Ok(())
+	}
+}
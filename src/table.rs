@@ -7,7 +7,7 @@
 ///              NonByte: Marker that tells the compiler that this is a type that is not 8-bits wide.
 ///     NonBytePrimitive: Marker that combines the Primitive and NonByte marker.
 /// Usage:
-/// ```no_run
+/// ```ignore
 /// macro_rules! read_table {
 ///     //             $id: The tag ID that is written to file to mark a type. These are distinct integer values.
 ///     //                  Typically the ID will be a single 8-bit value, but as the extensions become more advanced, this may change.
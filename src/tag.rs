@@ -37,7 +37,12 @@ pub trait DecodeNbt: Sized {
 }
 /// The NBT Tag enum.<br>
 /// To see what types are supported, take a look at the table in [tag_info_table] located in [`/src/table.rs`].
-#[derive(Clone, Debug)]
+///
+/// Tags are stored in `Vec`s, `String`s, and [Map]s of the global allocator. For trees that are freed all at once, the
+/// `bumpalo` feature adds `arena::ArenaTag`, which is allocated in a bump arena. To avoid allocating for every message
+/// that is read, use an [io::Decoder](crate::io::Decoder), which reuses the allocations of tags that are given back to
+/// it.
+#[derive(Clone, PartialEq, Debug)]
 #[repr(isize)]
 pub enum Tag {
 	Byte(i8) = 1,
@@ -72,10 +77,11 @@ pub enum TagID {
 }
 
 #[doc = "Enum type for [Tag::List]."]
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[repr(isize)]
 pub enum ListTag {
-	/// Represents a ListTag without any elements. This would be represented as a List<Byte> with a size of 0.
+	/// Represents a ListTag without any elements. It is written as a List of End tags (type 0) with a length of 0, which
+	/// is how the game writes empty Lists. [ListTag::id] returns [TagID::Byte] for it, and [ListTag::element_id] returns None.
 	Empty,
 	Byte(Vec<i8>) = 1,
 	Short(Vec<i16>) = 2,
@@ -190,6 +196,73 @@ impl ListTag {
 			ListTag::Empty => 0,
 		}
 	}
+
+	#[doc = "Returns `true` if the list contains no elements."]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the type of the elements, or None for [ListTag::Empty], which is a List of End tags.
+	pub fn element_id(&self) -> Option<TagID> {
+		match self {
+			ListTag::Empty => None,
+			list => Some(list.id()),
+		}
+	}
+
+	/// Creates a List without elements that is written with the `element` type. None creates [ListTag::Empty].<br>
+	/// Empty Lists of other types keep their type when they are read back, so files from tools that write them
+	/// round-trip.
+	pub fn empty_of(element: Option<TagID>) -> ListTag {
+		match element {
+			None => ListTag::Empty,
+			Some(TagID::Byte) => ListTag::Byte(Vec::new()),
+			Some(TagID::Short) => ListTag::Short(Vec::new()),
+			Some(TagID::Int) => ListTag::Int(Vec::new()),
+			Some(TagID::Long) => ListTag::Long(Vec::new()),
+			Some(TagID::Float) => ListTag::Float(Vec::new()),
+			Some(TagID::Double) => ListTag::Double(Vec::new()),
+			Some(TagID::ByteArray) => ListTag::ByteArray(Vec::new()),
+			Some(TagID::String) => ListTag::String(Vec::new()),
+			Some(TagID::List) => ListTag::List(Vec::new()),
+			Some(TagID::Compound) => ListTag::Compound(Vec::new()),
+			Some(TagID::IntArray) => ListTag::IntArray(Vec::new()),
+			Some(TagID::LongArray) => ListTag::LongArray(Vec::new()),
+		}
+	}
+
+	/// The error for converting a List to an array of elements of another type.
+	fn wrong_elements(&self, array: TagID) -> crate::NbtError {
+		crate::invalid_data(&format!("Only a List of {}s can be converted to a {}, not a List of {}s.", array.title().trim_end_matches("Array"), array.title(), self.id().title()))
+	}
+
+	/// Returns the elements of a List of Bytes, for a ByteArray. Empty Lists of any type have no elements, and Lists of
+	/// other types are errors, since their elements would have to be converted.
+	pub fn to_byte_array(&self) -> Result<Vec<i8>, crate::NbtError> {
+		match self {
+			ListTag::Byte(values) => Ok(values.clone()),
+			list if list.is_empty() => Ok(Vec::new()),
+			list => Err(list.wrong_elements(TagID::ByteArray)),
+		}
+	}
+
+	/// Returns the elements of a List of Ints, for an IntArray, like [ListTag::to_byte_array].
+	pub fn to_int_array(&self) -> Result<Vec<i32>, crate::NbtError> {
+		match self {
+			ListTag::Int(values) => Ok(values.clone()),
+			list if list.is_empty() => Ok(Vec::new()),
+			list => Err(list.wrong_elements(TagID::IntArray)),
+		}
+	}
+
+	/// Returns the elements of a List of Longs, for a LongArray, like [ListTag::to_byte_array].
+	pub fn to_long_array(&self) -> Result<Vec<i64>, crate::NbtError> {
+		match self {
+			ListTag::Long(values) => Ok(values.clone()),
+			list if list.is_empty() => Ok(Vec::new()),
+			list => Err(list.wrong_elements(TagID::LongArray)),
+		}
+	}
 }
 
 impl TryFrom<u8> for TagID {
@@ -202,19 +275,19 @@ impl TryFrom<u8> for TagID {
 	"]
 	fn try_from(value: u8) -> Result<Self,Self::Error> {
 		match value {
-			01 => Ok(TagID::Byte),
-			02 => Ok(TagID::Short),
-			03 => Ok(TagID::Int),
-			04 => Ok(TagID::Long),
-			05 => Ok(TagID::Float),
-			06 => Ok(TagID::Double),
-			07 => Ok(TagID::ByteArray),
-			08 => Ok(TagID::String),
-			09 => Ok(TagID::List),
+			1 => Ok(TagID::Byte),
+			2 => Ok(TagID::Short),
+			3 => Ok(TagID::Int),
+			4 => Ok(TagID::Long),
+			5 => Ok(TagID::Float),
+			6 => Ok(TagID::Double),
+			7 => Ok(TagID::ByteArray),
+			8 => Ok(TagID::String),
+			9 => Ok(TagID::List),
 			10 => Ok(TagID::Compound),
 			11 => Ok(TagID::IntArray),
 			12 => Ok(TagID::LongArray),
-			00 => Err(crate::NbtError::End),
+			0 => Err(crate::NbtError::End),
 			other => {
 				// There was an unsupported ID passed to the try_from function.
 				Err(crate::NbtError::Unsupported {
@@ -335,7 +408,7 @@ tag_info_table!(tag_code);
 
 /// Represents a Named NBT Tag, often used as a Tag Root for an NBT file.
 /// This is also sometimes called a root tag.
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct NamedTag {
 	pub(crate) name: String,
 	pub(crate) tag: Tag,
@@ -422,14 +495,105 @@ where
 	
 }
 
+/// A [Tag] that is built the first time it is used, for fixed templates in `static` items.<br>
+/// Numbers can already be `const`, as in `const HEALTH: Tag = Tag::Float(20.0);`, but Strings, arrays,
+/// Lists, and Compounds allocate, so they are built from SNBT or a function when first dereferenced.
+/// ```
+/// # use rustnbt::tag::{ Tag, LazyTag };
+/// static SWORD: LazyTag = LazyTag::snbt(r#"{id: "minecraft:diamond_sword", Count: 1b}"#);
+/// let Tag::Compound(sword) = &*SWORD else { unreachable!() };
+/// assert_eq!(sword["Count"], Tag::Byte(1));
+/// assert_eq!(SWORD.instantiate(), *SWORD);
+/// ```
+pub struct LazyTag {
+	source: LazySource,
+	tag: std::sync::OnceLock<Tag>,
+}
+
+enum LazySource {
+	Snbt(&'static str),
+	Function(fn() -> Tag),
+}
+
+impl LazyTag {
+	/// A tag that is parsed from SNBT when first used.
+	/// Dereferencing it panics if the SNBT is invalid, so it should be covered by a test.
+	pub const fn snbt(text: &'static str) -> Self {
+		Self { source: LazySource::Snbt(text), tag: std::sync::OnceLock::new() }
+	}
+
+	/// A tag that is built by `build` when first used.
+	pub const fn new(build: fn() -> Tag) -> Self {
+		Self { source: LazySource::Function(build), tag: std::sync::OnceLock::new() }
+	}
+
+	/// The tag, building it if this is the first use.
+	pub fn get(&self) -> &Tag {
+		self.tag.get_or_init(|| match self.source {
+			LazySource::Snbt(text) => match Tag::parse(text) {
+				Ok(tag) => tag,
+				Err(err) => panic!("Invalid SNBT in LazyTag: {err}"),
+			},
+			LazySource::Function(build) => build(),
+		})
+	}
+
+	/// A copy of the tag that can be modified.
+	pub fn instantiate(&self) -> Tag {
+		self.get().clone()
+	}
+}
+
+impl std::ops::Deref for LazyTag {
+	type Target = Tag;
+
+	fn deref(&self) -> &Tag {
+		self.get()
+	}
+}
+
+impl std::fmt::Debug for LazyTag {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self.tag.get() {
+			Some(tag) => f.debug_tuple("LazyTag").field(tag).finish(),
+			None => f.write_str("LazyTag(<uninitialized>)"),
+		}
+	}
+}
+
 impl TagID {
+	/// The ID of TAG_End, which ends every Compound and is the element type of empty Lists written by the game.
+	/// It has no TagID because it never has a payload.
+	pub const END: u8 = 0;
+
 	/// Returns this TagID as an isize.
 	pub fn value(self) -> isize {
 		self as isize
 	}
+
+	/// Converts the element type of a List from a [u8], which is None for [TagID::END].
+	pub fn list_element(value: u8) -> Result<Option<TagID>, crate::NbtError> {
+		match value {
+			TagID::END => Ok(None),
+			value => TagID::try_from(value).map(Some),
+		}
+	}
 }
 
 impl Tag {
+	/// Returns a ByteArray, IntArray, or LongArray as a List of Bytes, Ints, or Longs, which is how some tools and older
+	/// versions of the game store the same data. A List is returned as it is, and other tags are errors. See
+	/// [ListTag::to_byte_array] for the other way.
+	pub fn to_list(&self) -> Result<ListTag, crate::NbtError> {
+		match self {
+			Tag::ByteArray(values) => Ok(ListTag::Byte(values.clone())),
+			Tag::IntArray(values) => Ok(ListTag::Int(values.clone())),
+			Tag::LongArray(values) => Ok(ListTag::Long(values.clone())),
+			Tag::List(list) => Ok(list.clone()),
+			tag => Err(crate::invalid_data(&format!("Only arrays can be converted to Lists, not a {}.", tag.title()))),
+		}
+	}
+
 	/// PascalCase title of this Tag.
 	pub fn title(&self) -> &'static str {
 		self.id().title()
@@ -527,12 +691,12 @@ impl Tag {
 
 	/// Create a [Tag::List].
 	pub fn list<T: NbtType, IT: IntoIterator<Item = T>>(it: IT) -> Tag where Vec<T>: Into<ListTag> {
-		Tag::List(ListTag::from(it.into_iter().collect::<Vec<T>>().into()))
+		Tag::List(it.into_iter().collect::<Vec<T>>().into())
 	}
 
 	/// Create a [Tag::Compound].
 	pub fn compound<T,IT,S>(items: IT) -> Tag where T: Into<Tag>, IT: IntoIterator<Item = (S, T)>, S: Into<String> {
-		let mut result = Map::new();
+		let mut result = Map::default();
 		items.into_iter().for_each(|(name, tag)| {
 			result.insert(name.into(), tag.into());
 		});
@@ -540,6 +704,121 @@ impl Tag {
 	}
 }
 
+/// A value whose allocations are counted by [heap_usage].
+enum Allocation<'a> {
+	Tag(&'a Tag),
+	Map(&'a Map),
+	List(&'a ListTag),
+}
+
+/// The bytes that a value and everything in it allocate, without recursion. Compounds are counted as a slot of a key
+/// and a tag, and a word of bookkeeping, for every entry they have room for.
+fn heap_usage(value: Allocation) -> usize {
+	use std::mem::size_of;
+	fn vec<T>(values: &Vec<T>) -> usize {
+		values.capacity() * size_of::<T>()
+	}
+	let mut stack = vec![value];
+	let mut bytes = 0;
+	while let Some(value) = stack.pop() {
+		bytes += match value {
+			Allocation::Tag(Tag::String(value)) => value.capacity(),
+			Allocation::Tag(Tag::ByteArray(values)) => vec(values),
+			Allocation::Tag(Tag::IntArray(values)) => vec(values),
+			Allocation::Tag(Tag::LongArray(values)) => vec(values),
+			Allocation::Tag(Tag::Compound(map)) | Allocation::Map(map) => {
+				stack.extend(map.values().map(Allocation::Tag));
+				map.capacity() * (size_of::<String>() + size_of::<Tag>() + size_of::<usize>()) + map.keys().map(String::capacity).sum::<usize>()
+			}
+			Allocation::Tag(Tag::List(list)) | Allocation::List(list) => match list {
+				ListTag::Empty => 0,
+				ListTag::Byte(values) => vec(values),
+				ListTag::Short(values) => vec(values),
+				ListTag::Int(values) => vec(values),
+				ListTag::Long(values) => vec(values),
+				ListTag::Float(values) => vec(values),
+				ListTag::Double(values) => vec(values),
+				ListTag::ByteArray(values) => vec(values) + values.iter().map(vec).sum::<usize>(),
+				ListTag::String(values) => vec(values) + values.iter().map(String::capacity).sum::<usize>(),
+				ListTag::IntArray(values) => vec(values) + values.iter().map(vec).sum::<usize>(),
+				ListTag::LongArray(values) => vec(values) + values.iter().map(vec).sum::<usize>(),
+				ListTag::List(lists) => {
+					stack.extend(lists.iter().map(Allocation::List));
+					vec(lists)
+				}
+				ListTag::Compound(maps) => {
+					stack.extend(maps.iter().map(Allocation::Map));
+					vec(maps)
+				}
+			},
+			Allocation::Tag(_) => 0,
+		};
+	}
+	bytes
+}
+
+impl Tag {
+	/// An estimate of the bytes of memory that this tag takes, including everything that it and the tags in it
+	/// allocate. Room that was allocated but is not used yet is counted too.
+	pub fn memory_usage(&self) -> usize {
+		std::mem::size_of::<Tag>() + heap_usage(Allocation::Tag(self))
+	}
+}
+
+/// An estimate of the bytes of memory that a Compound takes, like [Tag::memory_usage].
+pub(crate) fn map_memory_usage(map: &Map) -> usize {
+	std::mem::size_of::<Map>() + heap_usage(Allocation::Map(map))
+}
+
+/// Creates a [ListTag] from tags that all have the same type.
+impl TryFrom<Vec<Tag>> for ListTag {
+	type Error = ();
+	/// Tries to create a [ListTag] from tags that all have the same type. An empty vector creates [ListTag::Empty].
+	fn try_from(tags: Vec<Tag>) -> Result<Self, ()> {
+		macro_rules! list_from_tags {
+			($($id:literal $title:ident $type:path [$($impl:path)?])+) => {
+				match tags.first() {
+					None => Ok(ListTag::Empty),
+					$(
+						Some(Tag::$title(_)) => tags.into_iter()
+							.map(|tag| if let Tag::$title(value) = tag { Ok(value) } else { Err(()) })
+							.collect::<Result<Vec<_>, ()>>()
+							.map(ListTag::$title),
+					)+
+				}
+			};
+		}
+		tag_info_table!(list_from_tags)
+	}
+}
+
+impl From<ListTag> for Vec<Tag> {
+	/// Converts the elements of a list to tags. [ListTag::Empty] creates an empty vector.
+	fn from(list: ListTag) -> Self {
+		macro_rules! tags_from_list {
+			($($id:literal $title:ident $type:path [$($impl:path)?])+) => {
+				match list {
+					ListTag::Empty => Vec::new(),
+					$(
+						ListTag::$title(values) => values.into_iter().map(Tag::$title).collect(),
+					)+
+				}
+			};
+		}
+		tag_info_table!(tags_from_list)
+	}
+}
+
+/// Iterates over the elements of a list as tags, moving them out of the list.
+impl IntoIterator for ListTag {
+	type Item = Tag;
+	type IntoIter = std::vec::IntoIter<Tag>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		Vec::from(self).into_iter()
+	}
+}
+
 /// Creates a [Tag::Byte] from a boolean value.
 impl From<bool> for Tag {
 	/// Create a [Tag::Byte] from a boolean value.
@@ -583,29 +862,27 @@ impl Display for TagID {
 	}
 }
 
+// Tags are displayed as pretty SNBT, which is written without recursion.
 impl Display for Tag {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		// TODO: [ Display for Tag ] format into SNBT.
-		//       But I would like to be able to pretty-print SNBT as well.
-		//       So the solution I would like to go with is to create a formatter
-		//       that is configurable.
-		f.write_fmt(format_args!("{:#?}", self))
+		f.write_str(&crate::snbt::to_string_pretty(self))
 	}
 }
 
 impl Display for ListTag {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		f.write_fmt(format_args!("{:#?}", self))
+		f.write_str(&crate::snbt::list_to_string_pretty(self))
 	}
 }
 
 impl Display for NamedTag {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		f.write_fmt(format_args!("{:#?}", self))
+		f.write_str(&crate::snbt::named_to_string_pretty(self))
 	}
 }
 
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
 
 	#[test]
@@ -628,13 +905,26 @@ mod tests {
 		let list = Tag::List(ListTag::Empty);
 		let intarray = Tag::IntArray(vec![1,1,2,3,5,8,13,21,34,55,89,144]);
 		let longarray = Tag::LongArray(vec![1,3,3,7, 1337, 13,37, 1,3,37,1,337, 133,7, 1,33,7,13,3,7]);
-		let compound = Tag::Compound(Map::from([
+		let compound = Tag::Compound(Map::from_iter([
 			("Byte".to_owned(), byte.clone()),
 			("Short".to_owned(), short.clone()),
 			("Pi".to_owned(), double.clone()),
 		]));
 	}
 
+	#[test]
+	fn lazy_test() {
+		use crate::tag::*;
+		static EMPTY: LazyTag = LazyTag::new(|| Tag::Compound(Map::default()));
+		static ITEM: LazyTag = LazyTag::snbt("{id: \"minecraft:stone\", Count: 64b}");
+		const COUNT: Tag = Tag::Byte(64);
+		assert_eq!(format!("{EMPTY:?}"), "LazyTag(<uninitialized>)");
+		assert_eq!(*EMPTY, Tag::Compound(Map::default()));
+		let Tag::Compound(item) = ITEM.instantiate() else { panic!("Item is a Compound.") };
+		assert_eq!(item["Count"], COUNT);
+		assert!(std::ptr::eq(ITEM.get(), ITEM.get()));
+	}
+
 	#[test]
 	fn list_test() {
 		use crate::tag::*;
@@ -648,5 +938,22 @@ mod tests {
 		println!("{}", list);
 	}
 
+	#[test]
+	fn array_list_test() -> Result<(), crate::NbtError> {
+		use crate::tag::*;
+		let uuid = Tag::IntArray(vec![1, -2, 3, -4]);
+		let list = uuid.to_list()?;
+		assert_eq!(list, ListTag::Int(vec![1, -2, 3, -4]));
+		assert_eq!(Tag::IntArray(list.to_int_array()?), uuid);
+		assert_eq!(Tag::ByteArray(vec![1]).to_list()?.to_byte_array()?, [1]);
+		assert!(Tag::LongArray(vec![]).to_list()?.to_long_array()?.is_empty());
+		assert!(ListTag::Empty.to_int_array()?.is_empty());
+		assert!(ListTag::String(Vec::new()).to_long_array()?.is_empty());
+		assert_eq!(list.to_byte_array().unwrap_err().to_string(), crate::invalid_data("Only a List of Bytes can be converted to a ByteArray, not a List of Ints.").to_string());
+		assert!(ListTag::Short(vec![1]).to_int_array().is_err());
+		assert!(Tag::Int(1).to_list().is_err());
+		Ok(())
+	}
+
 }
 
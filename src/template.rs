@@ -0,0 +1,305 @@
+#![doc = r##"
+SNBT templates with placeholders, for generating many items or entities from data.
+
+A placeholder is written as `${name}` or `${name:type}`, where the type is the lowercase title of a tag type, such as `int`,
+`string`, or `intarray`. A placeholder can stand in for a whole value, either bare or quoted, and the value is then inserted
+as a tag of its own. A placeholder inside a longer quoted String or key is replaced with the value as text, which only
+works for numbers and Strings. Placeholders are Strings until they are filled, so they can not be used inside arrays,
+and a List that holds placeholders can only hold Strings besides them.
+
+When a placeholder has a type, the value must be that type. Numbers are also accepted if they can be converted to the type
+without changing their value, so an Int of 64 fills a `byte` placeholder, but an Int of 300 does not.
+
+```
+# use rustnbt::{ compound, tag::Tag, template::Template };
+let template = Template::parse(r#"{id: "minecraft:${item}", Count: ${count:byte}, tag: {display: {Name: '"${item} #${number}"'}}}"#).unwrap();
+let Tag::Compound(values) = compound!(("item", "diamond"), ("count", 64), ("number", 7)) else { unreachable!() };
+let item = template.instantiate(&values).unwrap();
+let Tag::Compound(item) = item else { unreachable!() };
+assert_eq!(item["id"], Tag::from("minecraft:diamond"));
+assert_eq!(item["Count"], Tag::Byte(64));
+```
+"##]
+
+use crate::{
+	Map,
+	snbt::ParseError,
+	tag::{
+		Tag,
+		TagID,
+		ListTag,
+	},
+};
+
+/// Error returned when parsing or instantiating a [Template] fails.
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+	#[error("{0}")]
+	Snbt(#[from] ParseError),
+	#[error("Unterminated placeholder starting at position {0}.")]
+	Unterminated(usize),
+	#[error("Invalid placeholder {0:?}.")]
+	InvalidPlaceholder(String),
+	#[error("No value for placeholder {0:?}.")]
+	Missing(String),
+	#[error("Value for placeholder {name:?} is a {found} but must be a {expected}.")]
+	Mismatch { name: String, expected: TagID, found: TagID },
+	#[error("Value for placeholder {0:?} does not fit in its type.")]
+	OutOfRange(String),
+	#[error("Value for placeholder {0:?} can not be inserted into text.")]
+	NotText(String),
+	#[error("Values in the List that holds placeholder {0:?} do not all have the same type.")]
+	MixedList(String),
+}
+
+/// A placeholder of a [Template].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Placeholder {
+	pub name: String,
+	/// The type that the value must have, or [None] if any value is accepted.
+	pub kind: Option<TagID>,
+}
+
+/// A parsed SNBT template. See the [module documentation](self).
+#[derive(Clone, PartialEq, Debug)]
+pub struct Template {
+	/// The template with every placeholder inside a String.
+	tag: Tag,
+	placeholders: Vec<Placeholder>,
+}
+
+impl Template {
+	/// Parses a template.
+	pub fn parse<S: AsRef<str>>(text: S) -> Result<Self, TemplateError> {
+		let text = text.as_ref();
+		let mut quoted = String::with_capacity(text.len());
+		let mut placeholders = Vec::new();
+		let mut quote = None;
+		let mut chars = text.char_indices().peekable();
+		while let Some((position, c)) = chars.next() {
+			match (quote, c) {
+				(Some(_), '\\') => {
+					quoted.push(c);
+					if let Some((_, escaped)) = chars.next() {
+						quoted.push(escaped);
+					}
+					continue;
+				}
+				(Some(open), c) if c == open => quote = None,
+				(None, '"' | '\'') => quote = Some(c),
+				_ => (),
+			}
+			if c != '$' || chars.peek().map(|(_, c)| *c) != Some('{') {
+				quoted.push(c);
+				continue;
+			}
+			let end = text[position..].find('}').ok_or(TemplateError::Unterminated(position))? + position;
+			let placeholder = parse_placeholder(&text[position..=end])?;
+			if !placeholders.contains(&placeholder) {
+				placeholders.push(placeholder);
+			}
+			// Bare placeholders are quoted so that the template parses as SNBT.
+			match quote {
+				Some(_) => quoted.push_str(&text[position..=end]),
+				None => {
+					quoted.push('"');
+					quoted.push_str(&text[position..=end]);
+					quoted.push('"');
+				}
+			}
+			while chars.next_if(|(index, _)| *index <= end).is_some() {}
+		}
+		Ok(Self { tag: Tag::parse(quoted)?, placeholders })
+	}
+
+	/// The placeholders of the template, in the order they first appear.
+	pub fn placeholders(&self) -> &[Placeholder] {
+		&self.placeholders
+	}
+
+	/// Creates a tag from the template, filling each placeholder with the value of the same name in `values`.
+	pub fn instantiate(&self, values: &Map) -> Result<Tag, TemplateError> {
+		fill(&self.tag, values)
+	}
+}
+
+impl std::str::FromStr for Template {
+	type Err = TemplateError;
+
+	fn from_str(text: &str) -> Result<Self, TemplateError> {
+		Self::parse(text)
+	}
+}
+
+fn parse_placeholder(text: &str) -> Result<Placeholder, TemplateError> {
+	let invalid = || TemplateError::InvalidPlaceholder(text.to_owned());
+	let inner = text.strip_prefix("${").and_then(|text| text.strip_suffix('}')).ok_or_else(invalid)?;
+	let (name, kind) = match inner.split_once(':') {
+		Some((name, kind)) => (name.trim(), Some(kind.trim())),
+		None => (inner.trim(), None),
+	};
+	if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+		return Err(invalid());
+	}
+	let kind = match kind {
+		Some(kind) => Some((1..=12u8)
+			.filter_map(|id| TagID::try_from(id).ok())
+			.find(|id| id.title().eq_ignore_ascii_case(kind))
+			.ok_or_else(invalid)?),
+		None => None,
+	};
+	Ok(Placeholder { name: name.to_owned(), kind })
+}
+
+fn fill(tag: &Tag, values: &Map) -> Result<Tag, TemplateError> {
+	Ok(match tag {
+		Tag::String(text) => fill_string(text, values)?,
+		Tag::List(list) => Tag::List(fill_list(list, values)?),
+		Tag::Compound(map) => Tag::Compound(fill_map(map, values)?),
+		other => other.clone(),
+	})
+}
+
+fn fill_map(map: &Map, values: &Map) -> Result<Map, TemplateError> {
+	map.iter()
+		.map(|(key, tag)| Ok((interpolate(key, values)?, fill(tag, values)?)))
+		.collect()
+}
+
+fn fill_list(list: &ListTag, values: &Map) -> Result<ListTag, TemplateError> {
+	Ok(match list {
+		ListTag::String(texts) => {
+			let tags = texts.iter().map(|text| fill_string(text, values)).collect::<Result<Vec<_>, _>>()?;
+			ListTag::try_from(tags).map_err(|_| {
+				let name = texts.iter().find_map(|text| whole_placeholder(text)).map_or_else(String::new, |placeholder| placeholder.name);
+				TemplateError::MixedList(name)
+			})?
+		}
+		ListTag::List(lists) => ListTag::List(lists.iter().map(|list| fill_list(list, values)).collect::<Result<_, _>>()?),
+		ListTag::Compound(maps) => ListTag::Compound(maps.iter().map(|map| fill_map(map, values)).collect::<Result<_, _>>()?),
+		other => other.clone(),
+	})
+}
+
+/// The placeholder that makes up all of `text`, if there is one.
+fn whole_placeholder(text: &str) -> Option<Placeholder> {
+	(text.starts_with("${") && text.find('}') == Some(text.len() - 1))
+		.then(|| parse_placeholder(text).ok())
+		.flatten()
+}
+
+fn fill_string(text: &str, values: &Map) -> Result<Tag, TemplateError> {
+	match whole_placeholder(text) {
+		Some(placeholder) => value(&placeholder, values),
+		None => Ok(Tag::String(interpolate(text, values)?)),
+	}
+}
+
+/// Replaces the placeholders in `text` with their values as text.
+fn interpolate(text: &str, values: &Map) -> Result<String, TemplateError> {
+	let mut output = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find("${") {
+		let end = rest[start..].find('}').ok_or(TemplateError::Unterminated(start))? + start;
+		let placeholder = parse_placeholder(&rest[start..=end])?;
+		output.push_str(&rest[..start]);
+		match value(&placeholder, values)? {
+			Tag::Byte(value) => output.push_str(&value.to_string()),
+			Tag::Short(value) => output.push_str(&value.to_string()),
+			Tag::Int(value) => output.push_str(&value.to_string()),
+			Tag::Long(value) => output.push_str(&value.to_string()),
+			Tag::Float(value) => output.push_str(&value.to_string()),
+			Tag::Double(value) => output.push_str(&value.to_string()),
+			Tag::String(value) => output.push_str(&value),
+			_ => return Err(TemplateError::NotText(placeholder.name)),
+		}
+		rest = &rest[end + 1..];
+	}
+	output.push_str(rest);
+	Ok(output)
+}
+
+/// Looks up the value of a placeholder and converts it to the placeholder's type.
+fn value(placeholder: &Placeholder, values: &Map) -> Result<Tag, TemplateError> {
+	let name = &placeholder.name;
+	let value = values.get(name).ok_or_else(|| TemplateError::Missing(name.clone()))?;
+	let Some(kind) = placeholder.kind else {
+		return Ok(value.clone());
+	};
+	if value.id() == kind {
+		return Ok(value.clone());
+	}
+	let mismatch = || TemplateError::Mismatch { name: name.clone(), expected: kind, found: value.id() };
+	let out_of_range = || TemplateError::OutOfRange(name.clone());
+	let integer = match value {
+		Tag::Byte(value) => Some(*value as i64),
+		Tag::Short(value) => Some(*value as i64),
+		Tag::Int(value) => Some(*value as i64),
+		Tag::Long(value) => Some(*value),
+		_ => None,
+	};
+	let real = match value {
+		Tag::Float(value) => Some(*value as f64),
+		Tag::Double(value) => Some(*value),
+		_ => integer.map(|value| value as f64),
+	};
+	Ok(match (kind, integer, real) {
+		(TagID::Byte, Some(value), _) => Tag::Byte(value.try_into().map_err(|_| out_of_range())?),
+		(TagID::Short, Some(value), _) => Tag::Short(value.try_into().map_err(|_| out_of_range())?),
+		(TagID::Int, Some(value), _) => Tag::Int(value.try_into().map_err(|_| out_of_range())?),
+		(TagID::Long, Some(value), _) => Tag::Long(value),
+		(TagID::Float, _, Some(value)) if value as f32 as f64 == value || value.is_nan() => Tag::Float(value as f32),
+		(TagID::Double, Some(value), _) if value as f64 as i64 == value => Tag::Double(value as f64),
+		(TagID::Double, None, Some(value)) => Tag::Double(value),
+		(TagID::Float | TagID::Double, _, Some(_)) => return Err(out_of_range()),
+		_ => return Err(mismatch()),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	fn values(tag: Tag) -> Map {
+		match tag {
+			Tag::Compound(map) => map,
+			_ => unreachable!(),
+		}
+	}
+
+	#[test]
+	fn template_test() -> Result<(), TemplateError> {
+		let template = Template::parse(r#"{Name: "${name}", "${key}": ${count:short}, Tags: [${first}, "${second:string}"], Text: 'a\'${name}\''}"#)?;
+		assert_eq!(template.placeholders().iter().map(|placeholder| placeholder.name.as_str()).collect::<Vec<_>>(), ["name", "key", "count", "first", "second"]);
+		assert_eq!(template.placeholders()[2].kind, Some(TagID::Short));
+		let filled = template.instantiate(&values(compound!(
+			("name", "Steve"),
+			("key", "Count"),
+			("count", Tag::Byte(3)),
+			("first", "x"),
+			("second", "y")
+		)))?;
+		assert_eq!(filled, compound!(
+			("Name", "Steve"),
+			("Count", Tag::Short(3)),
+			("Tags", list!("x", "y")),
+			("Text", "a'Steve'")
+		));
+		Ok(())
+	}
+
+	#[test]
+	fn error_test() -> Result<(), TemplateError> {
+		assert!(matches!(Template::parse("{a: ${a"), Err(TemplateError::Unterminated(4))));
+		assert!(matches!(Template::parse("{a: ${a:number}}"), Err(TemplateError::InvalidPlaceholder(_))));
+		let template = Template::parse("{a: ${a:byte}, b: [${b}, 'x']}")?;
+		let check = |a: Tag, b: Tag| template.instantiate(&values(compound!(("a", a), ("b", b))));
+		assert!(matches!(check(Tag::Int(300), Tag::from("y")), Err(TemplateError::OutOfRange(name)) if name == "a"));
+		assert!(matches!(check(Tag::from("x"), Tag::from("y")), Err(TemplateError::Mismatch { expected: TagID::Byte, found: TagID::String, .. })));
+		assert!(matches!(check(Tag::Byte(1), Tag::Long(0)), Err(TemplateError::MixedList(name)) if name == "b"));
+		assert!(matches!(template.instantiate(&Map::default()), Err(TemplateError::Missing(_))));
+		assert!(matches!(Template::parse("{a: 'x${a}'}")?.instantiate(&values(compound!(("a", list!(1))))), Err(TemplateError::NotText(_))));
+		Ok(())
+	}
+}
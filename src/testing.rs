@@ -0,0 +1,210 @@
+#![doc = r#"
+Assertions for testing code that produces NBT.
+
+[assert_nbt_eq!](crate::assert_nbt_eq) compares two tags, and when they differ, panics with the path of every
+difference instead of two large dumps.
+
+[assert_snbt_snapshot!](crate::assert_snbt_snapshot) compares a tag to a snapshot file stored as canonical SNBT in the
+`snapshots` folder of the crate being tested, named after the module and the snapshot. When the snapshot is missing or
+different, the new value is written next to it with a `.new` extension and the assertion fails, so it can be reviewed
+and renamed over the old snapshot. Set `RUSTNBT_UPDATE_SNAPSHOTS=1` to write snapshots directly instead.
+
+```
+# use rustnbt::{ compound, assert_nbt_eq };
+let written = compound!(("Health", 20.0f32), ("Tags", rustnbt::list!("a")));
+assert_nbt_eq!(written, compound!(("Tags", rustnbt::list!("a")), ("Health", 20.0f32)));
+```
+"#]
+
+use crate::{
+	Map,
+	snbt,
+	path::{ PathSegment, TagPath },
+	tag::{
+		Tag,
+		ListTag,
+	},
+};
+use std::path::{ Path, PathBuf };
+
+/// The environment variable that makes [assert_snapshot] write snapshots instead of comparing them.
+pub const UPDATE_VAR: &str = "RUSTNBT_UPDATE_SNAPSHOTS";
+
+/// Describes every difference between two tags, one line each, such as `Pos[1]: 64d != 65d`.
+/// Values are written as compact SNBT. Returns nothing if the tags are equal.
+pub fn differences(left: &Tag, right: &Tag) -> Vec<String> {
+	let mut lines = Vec::new();
+	compare(&mut TagPath::new(), left, right, &mut lines);
+	lines
+}
+
+fn compare(path: &mut TagPath, left: &Tag, right: &Tag, lines: &mut Vec<String>) {
+	match (left, right) {
+		_ if left == right => {}
+		(Tag::Compound(left), Tag::Compound(right)) => compare_maps(path, left, right, lines),
+		(Tag::List(left), Tag::List(right)) if left.id() == right.id() => {
+			let (left, right) = (Vec::<Tag>::from(left.clone()), Vec::<Tag>::from(right.clone()));
+			compare_elements(path, &left, &right, lines);
+		}
+		(Tag::ByteArray(left), Tag::ByteArray(right)) => compare_elements(path, &wrap(left, Tag::Byte), &wrap(right, Tag::Byte), lines),
+		(Tag::IntArray(left), Tag::IntArray(right)) => compare_elements(path, &wrap(left, Tag::Int), &wrap(right, Tag::Int), lines),
+		(Tag::LongArray(left), Tag::LongArray(right)) => compare_elements(path, &wrap(left, Tag::Long), &wrap(right, Tag::Long), lines),
+		_ => lines.push(format!("{}: {} != {}", display(path), snbt::to_string(left), snbt::to_string(right))),
+	}
+}
+
+fn wrap<T: Copy>(values: &[T], tag: fn(T) -> Tag) -> Vec<Tag> {
+	values.iter().map(|value| tag(*value)).collect()
+}
+
+fn compare_maps(path: &mut TagPath, left: &Map, right: &Map, lines: &mut Vec<String>) {
+	let mut keys: Vec<&String> = left.keys().chain(right.keys().filter(|key| !left.contains_key(*key))).collect();
+	keys.sort();
+	for key in keys {
+		path.push(PathSegment::Key(key.clone()));
+		match (left.get(key), right.get(key)) {
+			(Some(left), Some(right)) => compare(path, left, right, lines),
+			(Some(left), None) => lines.push(format!("{}: only on the left: {}", display(path), snbt::to_string(left))),
+			(None, Some(right)) => lines.push(format!("{}: only on the right: {}", display(path), snbt::to_string(right))),
+			(None, None) => {}
+		}
+		path.pop();
+	}
+}
+
+fn compare_elements(path: &mut TagPath, left: &[Tag], right: &[Tag], lines: &mut Vec<String>) {
+	if left.len() != right.len() {
+		lines.push(format!("{}: length {} != {}", display(path), left.len(), right.len()));
+	}
+	for (index, (left, right)) in left.iter().zip(right).enumerate() {
+		path.push(PathSegment::Index(index));
+		compare(path, left, right, lines);
+		path.pop();
+	}
+}
+
+fn display(path: &TagPath) -> String {
+	if path.is_empty() { "(root)".to_owned() } else { path.to_string() }
+}
+
+/// Panics with the [differences] of two tags if they are not equal. Used by [assert_nbt_eq!](crate::assert_nbt_eq).
+#[track_caller]
+pub fn assert_tags_eq(left: &Tag, right: &Tag, message: Option<String>) {
+	if left == right {
+		return;
+	}
+	let lines = differences(left, right);
+	let message = message.map(|message| format!("{message}\n")).unwrap_or_default();
+	panic!("{message}NBT is not equal:\n  {}", lines.join("\n  "));
+}
+
+/// Compares a tag to the snapshot at `path`. Used by [assert_snbt_snapshot!](crate::assert_snbt_snapshot),
+/// which picks the path. See the [module](self) documentation.
+#[track_caller]
+pub fn assert_snapshot<P: AsRef<Path>>(path: P, tag: &Tag) {
+	let path = path.as_ref();
+	let text = format!("{}\n", snbt::to_string_canonical(tag));
+	let update = std::env::var_os(UPDATE_VAR).is_some_and(|value| value != "0");
+	let snapshot = match std::fs::read_to_string(path) {
+		Ok(snapshot) => Some(Tag::parse(&snapshot).unwrap_or_else(|err| panic!("Snapshot {} is not valid SNBT: {err}", path.display()))),
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+		Err(err) => panic!("Failed to read snapshot {}: {err}", path.display()),
+	};
+	if snapshot.as_ref() == Some(tag) {
+		return;
+	}
+	let target = if update { path.to_path_buf() } else { new_path(path) };
+	if let Some(parent) = target.parent() {
+		std::fs::create_dir_all(parent).unwrap_or_else(|err| panic!("Failed to create {}: {err}", parent.display()));
+	}
+	std::fs::write(&target, text).unwrap_or_else(|err| panic!("Failed to write snapshot {}: {err}", target.display()));
+	if update {
+		return;
+	}
+	match snapshot {
+		Some(snapshot) => panic!(
+			"NBT does not match snapshot {}:\n  {}\nThe new value was written to {}.",
+			path.display(),
+			differences(&snapshot, tag).join("\n  "),
+			target.display(),
+		),
+		None => panic!("Snapshot {} does not exist. The new value was written to {}.", path.display(), target.display()),
+	}
+}
+
+/// The path that a snapshot that failed is written to, which is the snapshot's path with `.new` appended.
+pub fn new_path(path: &Path) -> PathBuf {
+	let mut name = path.file_name().unwrap_or_default().to_owned();
+	name.push(".new");
+	path.with_file_name(name)
+}
+
+/// Asserts that two tags are equal, printing the path of every difference if they are not.
+/// The tags may be [Tag]s or references to them. An extra format string and arguments can be given, like [assert_eq!].
+#[macro_export]
+macro_rules! assert_nbt_eq {
+	($left:expr, $right:expr $(,)?) => {
+		$crate::testing::assert_tags_eq(&$left, &$right, ::std::option::Option::None)
+	};
+	($left:expr, $right:expr, $($message:tt)+) => {
+		$crate::testing::assert_tags_eq(&$left, &$right, ::std::option::Option::Some(::std::format!($($message)+)))
+	};
+}
+
+/// Asserts that a tag matches the snapshot `snapshots/<module>__<name>.snbt` in the crate being tested.
+/// See the [testing](crate::testing) module.
+#[macro_export]
+macro_rules! assert_snbt_snapshot {
+	($name:expr, $tag:expr $(,)?) => {
+		$crate::testing::assert_snapshot(
+			::std::path::Path::new(::std::env!("CARGO_MANIFEST_DIR"))
+				.join("snapshots")
+				.join(::std::format!("{}__{}.snbt", ::std::module_path!().replace("::", "__"), $name)),
+			&$tag,
+		)
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, world::tests::temp_world };
+
+	#[test]
+	fn differences_test() {
+		let left = compound!(("Pos", list!(1.0, 64.0)), ("Name", "a"), ("Data", Tag::IntArray(vec![1, 2])));
+		let right = compound!(("Pos", list!(1.0, 65.0)), ("Id", 3), ("Data", Tag::IntArray(vec![1, 2, 3])));
+		assert_eq!(differences(&left, &right), [
+			"Data: length 2 != 3",
+			"Id: only on the right: 3",
+			"Name: only on the left: \"a\"",
+			"Pos[1]: 64d != 65d",
+		]);
+		assert!(differences(&left, &left).is_empty());
+		assert_eq!(differences(&Tag::Int(1), &Tag::Long(1)), ["(root): 1 != 1L"]);
+		assert_nbt_eq!(left, &left.clone(), "with a message {}", 1);
+	}
+
+	#[test]
+	#[should_panic(expected = "Pos[1]: 64d != 65d")]
+	fn assert_nbt_eq_test() {
+		assert_nbt_eq!(compound!(("Pos", list!(1.0, 64.0))), compound!(("Pos", list!(1.0, 65.0))));
+	}
+
+	#[test]
+	fn snapshot_test() {
+		let dir = temp_world("snapshot");
+		let path = dir.join("snapshots").join("player.snbt");
+		let tag = compound!(("Health", 20.0f32), ("Tags", list!("a", "b")));
+		let missing = std::panic::catch_unwind(|| assert_snapshot(&path, &tag));
+		assert!(missing.is_err());
+		std::fs::rename(new_path(&path), &path).expect("New snapshot was written.");
+		assert_snapshot(&path, &tag);
+
+		let changed = compound!(("Health", 19.0f32), ("Tags", list!("a", "b")));
+		let error = std::panic::catch_unwind(|| assert_snapshot(&path, &changed)).unwrap_err();
+		assert!(error.downcast_ref::<String>().is_some_and(|message| message.contains("Health: 20f != 19f")));
+		assert_eq!(std::fs::read_to_string(new_path(&path)).ok(), Some(format!("{}\n", snbt::to_string_canonical(&changed))));
+		std::fs::remove_dir_all(dir).expect("Failed to remove temp dir.");
+	}
+}
@@ -0,0 +1,168 @@
+// https://minecraft.wiki/w/Java_Edition_level_format
+
+#![doc = r#"
+Conversions for tick counts and timestamps.
+
+The game runs at 20 ticks per second, and a day is 24000 ticks long. The `Data` Compound of `level.dat` stores
+`LastPlayed`, the Unix time in milliseconds when the world was last saved, `Time`, the number of ticks the world has
+run, and `DayTime`, the time of day in ticks, which keeps counting up across days. Tick 0 of a day is 6:00 in the
+morning.
+
+With the `chrono` feature, Unix times in milliseconds can also be converted to and from `chrono::DateTime<Utc>`.
+
+```
+# use rustnbt::{ compound, tag::Tag, time::* };
+# use std::time::Duration;
+let Tag::Compound(data) = compound!(("Time", 72_000i64), ("DayTime", 42_000i64)) else { unreachable!() };
+assert_eq!(game_time(&data), Some(Duration::from_secs(3600)));
+let day_time = day_time(&data).unwrap();
+assert_eq!((day_time.day(), day_time.clock()), (1, (0, 0)));
+```
+"#]
+
+use crate::{
+	Map,
+	tag::Tag,
+};
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+
+pub const TICKS_PER_SECOND: i64 = 20;
+/// The length of a day, in ticks.
+pub const TICKS_PER_DAY: i64 = 24000;
+const MILLIS_PER_TICK: u64 = 1000 / TICKS_PER_SECOND as u64;
+
+/// Converts a number of ticks to a [Duration]. Negative numbers are zero.
+pub fn ticks_to_duration(ticks: i64) -> Duration {
+	Duration::from_millis((ticks.max(0) as u64).saturating_mul(MILLIS_PER_TICK))
+}
+
+/// Converts a [Duration] to a number of ticks, rounding down.
+pub fn duration_to_ticks(duration: Duration) -> i64 {
+	i64::try_from(duration.as_millis() / MILLIS_PER_TICK as u128).unwrap_or(i64::MAX)
+}
+
+/// Converts a Unix time in milliseconds, such as `LastPlayed`, to a [SystemTime].
+pub fn from_unix_millis(millis: i64) -> SystemTime {
+	let offset = Duration::from_millis(millis.unsigned_abs());
+	if millis < 0 { UNIX_EPOCH - offset } else { UNIX_EPOCH + offset }
+}
+
+/// Converts a [SystemTime] to a Unix time in milliseconds, saturating at the range of [i64].
+pub fn to_unix_millis(time: SystemTime) -> i64 {
+	match time.duration_since(UNIX_EPOCH) {
+		Ok(after) => i64::try_from(after.as_millis()).unwrap_or(i64::MAX),
+		Err(before) => i64::try_from(before.duration().as_millis()).map_or(i64::MIN, |millis| -millis),
+	}
+}
+
+/// Converts a Unix time in milliseconds to a [chrono::DateTime], or None if it is out of its range.
+#[cfg(feature = "chrono")]
+pub fn to_date_time(millis: i64) -> Option<chrono::DateTime<chrono::Utc>> {
+	chrono::DateTime::from_timestamp_millis(millis)
+}
+
+/// Converts a [chrono::DateTime] to a Unix time in milliseconds.
+#[cfg(feature = "chrono")]
+pub fn from_date_time<Tz: chrono::TimeZone>(time: &chrono::DateTime<Tz>) -> i64 {
+	time.timestamp_millis()
+}
+
+/// A `DayTime` value, which counts the ticks of every day that has passed and the ticks of the current day.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct DayTime(pub i64);
+
+impl DayTime {
+	/// Creates a day time from a day and a time on a 24-hour clock. Days start at 6:00, so the hours before 6:00 are
+	/// at the end of the day.
+	pub const fn from_clock(day: i64, hour: u8, minute: u8) -> Self {
+		let ticks = (hour as i64 * 1000 + minute as i64 * 1000 / 60 + TICKS_PER_DAY - 6000) % TICKS_PER_DAY;
+		Self(day * TICKS_PER_DAY + ticks)
+	}
+
+	/// The number of days that have passed.
+	pub const fn day(self) -> i64 {
+		self.0.div_euclid(TICKS_PER_DAY)
+	}
+
+	/// The ticks since the start of the day, from `0` to `23999`.
+	pub const fn time_of_day(self) -> i64 {
+		self.0.rem_euclid(TICKS_PER_DAY)
+	}
+
+	/// The hour and minute on a 24-hour clock. Tick 0 of a day is 6:00.
+	pub const fn clock(self) -> (u8, u8) {
+		let ticks = self.time_of_day();
+		(((ticks / 1000 + 6) % 24) as u8, (ticks % 1000 * 60 / 1000) as u8)
+	}
+}
+
+fn long(data: &Map, key: &str) -> Option<i64> {
+	match data.get(key) {
+		Some(Tag::Long(value)) => Some(*value),
+		_ => None,
+	}
+}
+
+/// Returns `LastPlayed` from the `Data` Compound of `level.dat`.
+pub fn last_played(data: &Map) -> Option<SystemTime> {
+	long(data, "LastPlayed").map(from_unix_millis)
+}
+
+/// Sets `LastPlayed` in the `Data` Compound of `level.dat`.
+pub fn set_last_played(data: &mut Map, time: SystemTime) {
+	data.insert("LastPlayed".to_owned(), Tag::Long(to_unix_millis(time)));
+}
+
+/// Returns `Time`, how long the world has run, from the `Data` Compound of `level.dat`.
+pub fn game_time(data: &Map) -> Option<Duration> {
+	long(data, "Time").map(ticks_to_duration)
+}
+
+/// Returns `DayTime` from the `Data` Compound of `level.dat`.
+pub fn day_time(data: &Map) -> Option<DayTime> {
+	long(data, "DayTime").map(DayTime)
+}
+
+/// Sets `DayTime` in the `Data` Compound of `level.dat`.
+pub fn set_day_time(data: &mut Map, day_time: DayTime) {
+	data.insert("DayTime".to_owned(), Tag::Long(day_time.0));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn time_test() {
+		assert_eq!(ticks_to_duration(30), Duration::from_millis(1500));
+		assert_eq!(ticks_to_duration(-5), Duration::ZERO);
+		assert_eq!(duration_to_ticks(Duration::from_millis(1549)), 30);
+		assert_eq!(duration_to_ticks(Duration::MAX), i64::MAX);
+		for millis in [0, 1_700_000_000_123, -86_400_000] {
+			assert_eq!(to_unix_millis(from_unix_millis(millis)), millis);
+		}
+
+		assert_eq!(DayTime(0).clock(), (6, 0));
+		assert_eq!(DayTime(18000).clock(), (0, 0));
+		assert_eq!(DayTime(24000 * 3 + 6500).clock(), (12, 30));
+		assert_eq!(DayTime(-1).day(), -1);
+		assert_eq!(DayTime(-1).time_of_day(), 23999);
+		assert_eq!(DayTime::from_clock(3, 12, 30), DayTime(24000 * 3 + 6500));
+		assert_eq!(DayTime::from_clock(0, 6, 0), DayTime(0));
+		assert_eq!(DayTime::from_clock(0, 5, 0), DayTime(23000));
+
+		let mut data = Map::default();
+		let now = from_unix_millis(1_700_000_000_000);
+		set_last_played(&mut data, now);
+		set_day_time(&mut data, DayTime(1000));
+		assert_eq!(last_played(&data), Some(now));
+		assert_eq!(day_time(&data), Some(DayTime(1000)));
+		assert_eq!(game_time(&data), None);
+		#[cfg(feature = "chrono")]
+		{
+			let date_time = to_date_time(1_700_000_000_000).expect("The time is in range.");
+			assert_eq!(date_time.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+			assert_eq!(from_date_time(&date_time), 1_700_000_000_000);
+		}
+	}
+}
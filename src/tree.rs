@@ -0,0 +1,305 @@
+#![doc = r#"
+A tree model over a [Tag] for NBT editor frontends.
+
+[TreeModel] gives every value in the tree a [NodeId] that stays the same while the tree is edited,
+so a UI can keep selection and expansion state by ID. Children only get IDs when they are first requested
+with [TreeModel::children], which keeps large arrays and lists cheap until they are expanded.
+Every edit made through the model is recorded as a [TreeChange], which the UI takes with [TreeModel::take_changes].
+
+```
+# use rustnbt::{ compound, tag::Tag, tree::* };
+let mut model = TreeModel::new(compound!(("Health", Tag::Float(20.0))));
+let root = model.root();
+assert_eq!(model.child_count(root), 1);
+let health = model.children(root)[0];
+model.set(health, Tag::Float(10.0)).unwrap();
+assert_eq!(model.take_changes(), vec![TreeChange::Changed(health)]);
+assert_eq!(model.get(health).as_deref(), Some(&Tag::Float(10.0)));
+```
+"#]
+
+use crate::{
+	path::{ PathSegment, TagPath },
+	tag::Tag,
+};
+use std::{
+	borrow::Cow,
+	collections::HashMap,
+};
+
+/// Identifies a node of a [TreeModel]. IDs are never reused within a model.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct NodeId(u64);
+
+/// An edit made to a [TreeModel].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TreeChange {
+	/// The value of the node was replaced. Its children were removed from the model and get new IDs when requested again.
+	Changed(NodeId),
+	/// A node was added at `index` in the children of `parent`.
+	Inserted { parent: NodeId, node: NodeId, index: usize },
+	/// A node was removed from `index` in the children of `parent`.
+	Removed { parent: NodeId, node: NodeId, index: usize },
+}
+
+struct Node {
+	parent: Option<NodeId>,
+	/// The key of a Compound entry, or `None` for List and array elements, whose index is their position in `parent`.
+	key: Option<String>,
+	/// The children that have IDs, or `None` if the node was not expanded yet.
+	children: Option<Vec<NodeId>>,
+}
+
+/// A [Tag] with stable node IDs, lazily expanded children, and a record of edits.
+pub struct TreeModel {
+	tag: Tag,
+	nodes: HashMap<NodeId, Node>,
+	next_id: u64,
+	changes: Vec<TreeChange>,
+}
+
+impl TreeModel {
+	const ROOT: NodeId = NodeId(0);
+
+	/// Creates a model for `tag`.
+	pub fn new(tag: Tag) -> Self {
+		let mut nodes = HashMap::new();
+		nodes.insert(Self::ROOT, Node { parent: None, key: None, children: None });
+		Self { tag, nodes, next_id: 1, changes: Vec::new() }
+	}
+
+	/// The root node.
+	pub fn root(&self) -> NodeId {
+		Self::ROOT
+	}
+
+	/// The tag that this model edits.
+	pub fn tag(&self) -> &Tag {
+		&self.tag
+	}
+
+	/// Returns the edited tag.
+	pub fn into_tag(self) -> Tag {
+		self.tag
+	}
+
+	/// Returns `true` if the node is part of the model.
+	pub fn contains(&self, id: NodeId) -> bool {
+		self.nodes.contains_key(&id)
+	}
+
+	/// The parent of a node, or `None` for the root.
+	pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+		self.nodes.get(&id)?.parent
+	}
+
+	/// The last segment of the node's path, which is the key or index that the UI shows as its label.
+	/// Returns `None` for the root.
+	pub fn segment(&self, id: NodeId) -> Option<PathSegment> {
+		let node = self.nodes.get(&id)?;
+		let parent = self.nodes.get(&node.parent?)?;
+		Some(match &node.key {
+			Some(key) => PathSegment::Key(key.clone()),
+			None => PathSegment::Index(parent.children.as_ref()?.iter().position(|child| *child == id)?),
+		})
+	}
+
+	/// The path from the root to a node.
+	pub fn path(&self, id: NodeId) -> Option<TagPath> {
+		let mut segments = Vec::new();
+		let mut current = id;
+		while current != Self::ROOT {
+			segments.push(self.segment(current)?);
+			current = self.parent(current)?;
+		}
+		segments.reverse();
+		Some(TagPath::from(segments))
+	}
+
+	/// The value of a node. See [TagPath::get] for when it is borrowed.
+	pub fn get(&self, id: NodeId) -> Option<Cow<'_, Tag>> {
+		self.path(id)?.get(&self.tag)
+	}
+
+	/// The number of children of a node, without expanding it.
+	/// Compounds, Lists, and arrays have children, and every other value has none.
+	pub fn child_count(&self, id: NodeId) -> usize {
+		self.get(id).map_or(0, |tag| child_count(&tag))
+	}
+
+	/// Returns `true` if the children of the node have IDs.
+	pub fn is_expanded(&self, id: NodeId) -> bool {
+		self.nodes.get(&id).is_some_and(|node| node.children.is_some())
+	}
+
+	/// The children of a node, giving them IDs if the node was not expanded yet.
+	/// Returns an empty slice for nodes that are not part of the model.
+	pub fn children(&mut self, id: NodeId) -> &[NodeId] {
+		self.expand(id);
+		self.nodes.get(&id).and_then(|node| node.children.as_deref()).unwrap_or(&[])
+	}
+
+	fn expand(&mut self, id: NodeId) {
+		if !self.contains(id) || self.is_expanded(id) {
+			return;
+		}
+		let keys: Vec<Option<String>> = match self.get(id).as_deref() {
+			Some(Tag::Compound(map)) => map.keys().cloned().map(Some).collect(),
+			Some(tag) => vec![None; child_count(tag)],
+			None => return,
+		};
+		let children = keys.into_iter().map(|key| self.add_node(id, key)).collect();
+		if let Some(node) = self.nodes.get_mut(&id) {
+			node.children = Some(children);
+		}
+	}
+
+	fn add_node(&mut self, parent: NodeId, key: Option<String>) -> NodeId {
+		let id = NodeId(self.next_id);
+		self.next_id += 1;
+		self.nodes.insert(id, Node { parent: Some(parent), key, children: None });
+		id
+	}
+
+	/// Removes the descendants of a node from the model, leaving the node unexpanded.
+	fn forget_children(&mut self, id: NodeId) {
+		let children = self.nodes.get_mut(&id).and_then(|node| node.children.take()).unwrap_or_default();
+		for child in children {
+			self.forget_children(child);
+			self.nodes.remove(&child);
+		}
+	}
+
+	/// Replaces the value of a node, returning the old value.
+	/// Fails with `value` if the node does not exist, or if it is a List or array element and `value` has a different type.
+	pub fn set(&mut self, id: NodeId, value: Tag) -> Result<Tag, Tag> {
+		let Some(path) = self.path(id) else {
+			return Err(value);
+		};
+		let old = path.set(&mut self.tag, value)?.expect("Existing nodes always have a value.");
+		self.forget_children(id);
+		self.changes.push(TreeChange::Changed(id));
+		Ok(old)
+	}
+
+	/// Inserts an entry into a Compound node, returning the ID of the entry.
+	/// If the key already exists, its value is replaced as with [TreeModel::set].
+	/// Fails with `value` if the node is not a Compound.
+	pub fn insert<S: Into<String>>(&mut self, parent: NodeId, key: S, value: Tag) -> Result<NodeId, Tag> {
+		let key = key.into();
+		if !matches!(self.get(parent).as_deref(), Some(Tag::Compound(_))) {
+			return Err(value);
+		}
+		self.expand(parent);
+		let existing = self.nodes[&parent].children.as_ref()
+			.and_then(|children| children.iter().copied().find(|child| self.nodes[child].key.as_deref() == Some(key.as_str())));
+		if let Some(existing) = existing {
+			self.set(existing, value)?;
+			return Ok(existing);
+		}
+		let mut path = self.path(parent).expect("Parent exists.");
+		path.push(PathSegment::Key(key.clone()));
+		path.set(&mut self.tag, value)?;
+		Ok(self.push_child(parent, Some(key)))
+	}
+
+	/// Appends an element to a List or array node, returning the ID of the element.
+	/// Fails with `value` if the node is not a List or array, or if `value` has a different type than the other elements.
+	pub fn push(&mut self, parent: NodeId, value: Tag) -> Result<NodeId, Tag> {
+		let Some(mut path) = self.path(parent) else {
+			return Err(value);
+		};
+		let index = match self.get(parent).as_deref() {
+			Some(Tag::Compound(_)) | None => return Err(value),
+			Some(tag) => child_count(tag),
+		};
+		self.expand(parent);
+		path.push(PathSegment::Index(index));
+		path.set(&mut self.tag, value)?;
+		Ok(self.push_child(parent, None))
+	}
+
+	fn push_child(&mut self, parent: NodeId, key: Option<String>) -> NodeId {
+		let node = self.add_node(parent, key);
+		let children = self.nodes.get_mut(&parent).and_then(|node| node.children.as_mut()).expect("Parent was expanded.");
+		children.push(node);
+		let index = children.len() - 1;
+		self.changes.push(TreeChange::Inserted { parent, node, index });
+		node
+	}
+
+	/// Removes a node and returns its value. The root can not be removed.
+	pub fn remove(&mut self, id: NodeId) -> Option<Tag> {
+		let path = self.path(id)?;
+		let parent = self.parent(id)?;
+		let value = path.remove(&mut self.tag)?;
+		self.forget_children(id);
+		self.nodes.remove(&id);
+		let children = self.nodes.get_mut(&parent).and_then(|node| node.children.as_mut()).expect("Parent was expanded.");
+		let index = children.iter().position(|child| *child == id).expect("Node is a child of its parent.");
+		children.remove(index);
+		self.changes.push(TreeChange::Removed { parent, node: id, index });
+		Some(value)
+	}
+
+	/// Takes the edits that were made since the last call.
+	pub fn take_changes(&mut self) -> Vec<TreeChange> {
+		std::mem::take(&mut self.changes)
+	}
+}
+
+fn child_count(tag: &Tag) -> usize {
+	match tag {
+		Tag::Compound(map) => map.len(),
+		Tag::List(list) => list.len(),
+		Tag::ByteArray(values) => values.len(),
+		Tag::IntArray(values) => values.len(),
+		Tag::LongArray(values) => values.len(),
+		_ => 0,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn tree_test() {
+		let mut model = TreeModel::new(compound!(("list", list!(1, 2, 3)), ("name", "root")));
+		let root = model.root();
+		assert!(!model.is_expanded(root));
+		let children = model.children(root).to_vec();
+		let list = children.into_iter().find(|child| model.segment(*child) == Some(PathSegment::Key("list".to_owned()))).unwrap();
+		assert_eq!(model.child_count(list), 3);
+		let elements = model.children(list).to_vec();
+		assert_eq!(model.path(elements[2]).unwrap().to_string(), "list[2]");
+
+		assert_eq!(model.remove(elements[0]), Some(Tag::Int(1)));
+		assert!(!model.contains(elements[0]));
+		assert_eq!(model.path(elements[2]).unwrap().to_string(), "list[1]");
+		assert_eq!(model.get(elements[2]).as_deref(), Some(&Tag::Int(3)));
+
+		assert_eq!(model.push(list, Tag::Byte(4)), Err(Tag::Byte(4)));
+		let pushed = model.push(list, Tag::Int(4)).unwrap();
+		assert_eq!(model.path(pushed).unwrap().to_string(), "list[2]");
+
+		let entry = model.insert(root, "new", Tag::Long(5)).unwrap();
+		assert_eq!(model.insert(root, "new", Tag::Long(6)), Ok(entry));
+		assert_eq!(model.insert(list, "key", Tag::Long(6)), Err(Tag::Long(6)));
+
+		assert_eq!(model.set(list, list!("a")), Ok(list!(2, 3, 4)));
+		assert!(!model.contains(elements[1]));
+		assert_eq!(model.remove(root), None);
+
+		assert_eq!(model.take_changes(), vec![
+			TreeChange::Removed { parent: list, node: elements[0], index: 0 },
+			TreeChange::Inserted { parent: list, node: pushed, index: 2 },
+			TreeChange::Inserted { parent: root, node: entry, index: 2 },
+			TreeChange::Changed(entry),
+			TreeChange::Changed(list),
+		]);
+		assert!(model.take_changes().is_empty());
+		assert_eq!(model.into_tag(), compound!(("list", list!("a")), ("name", "root"), ("new", Tag::Long(6))));
+	}
+}
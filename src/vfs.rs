@@ -0,0 +1,486 @@
+#![doc = r#"
+A read-only virtual file system over NBT data, for mounting with a FUSE or 9P frontend and browsing with ordinary
+shell tools.
+
+A [VirtualTree] answers the three questions such a frontend asks: what is at a path ([VirtualTree::metadata]), what is
+in a directory ([VirtualTree::read_dir]), and what is in a file ([VirtualTree::read]). Paths use `/` separators, and
+the root is `/` (or the empty path).
+
+[TagTree] exposes a single [Tag]. Compounds and lists are directories, whose entries are named after their keys and
+indices, and every other value is a file holding its SNBT. Every directory also has a `.snbt` file holding the SNBT of
+the whole compound or list. Keys are escaped with [escape_name] so they can be used as file names.
+
+[WorldTree] exposes a world folder. Folders and files are passed through, except that NBT files (`.dat`, `.dat_old`,
+and `.nbt`) are directories like a [TagTree], and region files are directories of chunks named `c.<x>.<z>`, each of
+which is a directory like a [TagTree].
+
+```
+# use rustnbt::{ compound, list, vfs::* };
+let tree = TagTree::new(compound!(("Pos", list!(1.0, 64.0, 2.0)), ("Name", "Steve")));
+let names: Vec<String> = tree.read_dir("/").unwrap().into_iter().map(|entry| entry.name).collect();
+assert_eq!(names, [".snbt", "Name", "Pos"]);
+assert_eq!(tree.read("/Pos/1").unwrap(), b"64d\n");
+assert_eq!(tree.metadata("/Pos").unwrap().kind, EntryKind::Directory);
+```
+"#]
+
+use crate::{
+	NbtError,
+	clipboard,
+	snbt,
+	pos::{ ChunkPos, RegionPos },
+	region::Region,
+	tag::Tag,
+	world::World,
+};
+use std::io::ErrorKind;
+use std::path::{ Path, PathBuf };
+
+/// The name of the file in every directory of a [TagTree] that holds the SNBT of the directory's value.
+pub const SNBT_FILE: &str = ".snbt";
+
+/// Whether an [Entry] is a directory or a file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntryKind {
+	Directory,
+	File,
+}
+
+/// An entry of a [VirtualTree].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+	/// The name of the entry in its directory. The root is named `/`.
+	pub name: String,
+	pub kind: EntryKind,
+	/// The length of a file's contents, or the number of entries in a directory.
+	pub size: u64,
+}
+
+impl Entry {
+	fn file<S: Into<String>>(name: S, size: usize) -> Self {
+		Self { name: name.into(), kind: EntryKind::File, size: size as u64 }
+	}
+
+	fn directory<S: Into<String>>(name: S, size: usize) -> Self {
+		Self { name: name.into(), kind: EntryKind::Directory, size: size as u64 }
+	}
+
+	pub fn is_dir(&self) -> bool {
+		self.kind == EntryKind::Directory
+	}
+}
+
+/// A read-only hierarchy of directories and files. See the [module](self) documentation.<br>
+/// Missing paths fail with [ErrorKind::NotFound], reading a directory fails with [ErrorKind::IsADirectory], and listing
+/// a file fails with [ErrorKind::NotADirectory], so frontends can map errors to error codes.
+pub trait VirtualTree {
+	/// Describes the entry at a path.
+	fn metadata(&self, path: &str) -> Result<Entry, NbtError>;
+	/// Lists a directory, in sorted order.
+	fn read_dir(&self, path: &str) -> Result<Vec<Entry>, NbtError>;
+	/// Reads a file.
+	fn read(&self, path: &str) -> Result<Vec<u8>, NbtError>;
+}
+
+/// Escapes a compound key for use as a file name. `%` and `/` are percent encoded, a leading `.` is encoded so keys
+/// cannot be `.`, `..`, or [SNBT_FILE], and the empty key is written as `%`.
+pub fn escape_name(key: &str) -> String {
+	if key.is_empty() {
+		return "%".to_owned();
+	}
+	let mut name = String::with_capacity(key.len());
+	for (index, char) in key.char_indices() {
+		match char {
+			'%' => name.push_str("%25"),
+			'/' => name.push_str("%2F"),
+			'\0' => name.push_str("%00"),
+			'.' if index == 0 => name.push_str("%2E"),
+			char => name.push(char),
+		}
+	}
+	name
+}
+
+/// Reverses [escape_name]. Returns `None` if the name has an invalid escape.
+pub fn unescape_name(name: &str) -> Option<String> {
+	if name == "%" {
+		return Some(String::new());
+	}
+	let mut bytes = Vec::with_capacity(name.len());
+	let mut rest = name.as_bytes();
+	while let Some((&byte, tail)) = rest.split_first() {
+		if byte == b'%' {
+			let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+			bytes.push(u8::from_str_radix(hex, 16).ok()?);
+			rest = &tail[2..];
+		} else {
+			bytes.push(byte);
+			rest = tail;
+		}
+	}
+	String::from_utf8(bytes).ok()
+}
+
+fn error(kind: ErrorKind, path: &str) -> NbtError {
+	let message = match kind {
+		ErrorKind::IsADirectory => "is a directory",
+		ErrorKind::NotADirectory => "is not a directory",
+		_ => "does not exist",
+	};
+	std::io::Error::new(kind, format!("{path} {message}.")).into()
+}
+
+/// Splits a path into its names, ignoring empty names and `.`.
+fn components(path: &str) -> Vec<&str> {
+	path.split('/').filter(|name| !name.is_empty() && *name != ".").collect()
+}
+
+fn file_contents(tag: &Tag) -> Vec<u8> {
+	let mut text = snbt::to_string(tag);
+	text.push('\n');
+	text.into_bytes()
+}
+
+/// The children of a compound or list, with their names, or `None` for other tags.
+fn children(tag: &Tag) -> Option<Vec<(String, Tag)>> {
+	match tag {
+		Tag::Compound(map) => Some(map.iter().map(|(key, value)| (escape_name(key), value.clone())).collect()),
+		Tag::List(list) => Some(Vec::<Tag>::from(list.clone()).into_iter().enumerate().map(|(index, value)| (index.to_string(), value)).collect()),
+		_ => None,
+	}
+}
+
+fn child(tag: &Tag, name: &str) -> Option<Tag> {
+	match tag {
+		Tag::Compound(map) => map.get(&unescape_name(name)?).cloned(),
+		Tag::List(list) => {
+			let index: usize = name.parse().ok()?;
+			if index.to_string() != name {
+				return None;
+			}
+			Vec::<Tag>::from(list.clone()).into_iter().nth(index)
+		}
+		_ => None,
+	}
+}
+
+/// What a path inside a tag refers to.
+enum TagNode {
+	Value(Tag),
+	/// The [SNBT_FILE] of a compound or list.
+	Snbt(Tag),
+}
+
+fn resolve_tag(tag: &Tag, names: &[&str], path: &str) -> Result<TagNode, NbtError> {
+	let mut current = tag.clone();
+	for (index, name) in names.iter().enumerate() {
+		if !matches!(current, Tag::Compound(_) | Tag::List(_)) {
+			return Err(error(ErrorKind::NotADirectory, path));
+		}
+		if *name == SNBT_FILE {
+			if index + 1 != names.len() {
+				return Err(error(ErrorKind::NotADirectory, path));
+			}
+			return Ok(TagNode::Snbt(current));
+		}
+		current = child(&current, name).ok_or_else(|| error(ErrorKind::NotFound, path))?;
+	}
+	Ok(TagNode::Value(current))
+}
+
+fn tag_entry(name: String, tag: &Tag) -> Entry {
+	match tag {
+		Tag::Compound(map) => Entry::directory(name, map.len() + 1),
+		Tag::List(list) => Entry::directory(name, list.len() + 1),
+		tag => Entry::file(name, file_contents(tag).len()),
+	}
+}
+
+fn tag_metadata(tag: &Tag, names: &[&str], name: &str, path: &str) -> Result<Entry, NbtError> {
+	Ok(match resolve_tag(tag, names, path)? {
+		TagNode::Value(value) => tag_entry(name.to_owned(), &value),
+		TagNode::Snbt(value) => Entry::file(SNBT_FILE, file_contents(&value).len()),
+	})
+}
+
+fn tag_read_dir(tag: &Tag, names: &[&str], path: &str) -> Result<Vec<Entry>, NbtError> {
+	let TagNode::Value(value) = resolve_tag(tag, names, path)? else {
+		return Err(error(ErrorKind::NotADirectory, path));
+	};
+	let Some(children) = children(&value) else {
+		return Err(error(ErrorKind::NotADirectory, path));
+	};
+	let mut entries = vec![Entry::file(SNBT_FILE, file_contents(&value).len())];
+	let mut children: Vec<Entry> = children.into_iter().map(|(name, tag)| tag_entry(name, &tag)).collect();
+	if matches!(value, Tag::Compound(_)) {
+		children.sort_by(|a, b| a.name.cmp(&b.name));
+	}
+	entries.extend(children);
+	Ok(entries)
+}
+
+fn tag_read(tag: &Tag, names: &[&str], path: &str) -> Result<Vec<u8>, NbtError> {
+	match resolve_tag(tag, names, path)? {
+		TagNode::Snbt(value) => Ok(file_contents(&value)),
+		TagNode::Value(value @ (Tag::Compound(_) | Tag::List(_))) => Err(error(ErrorKind::IsADirectory, path)),
+		TagNode::Value(value) => Ok(file_contents(&value)),
+	}
+}
+
+/// A [VirtualTree] over a single [Tag]. See the [module](self) documentation.
+#[derive(Clone, Debug)]
+pub struct TagTree {
+	root: Tag,
+}
+
+impl TagTree {
+	pub fn new(root: Tag) -> Self {
+		Self { root }
+	}
+
+	pub fn tag(&self) -> &Tag {
+		&self.root
+	}
+}
+
+impl VirtualTree for TagTree {
+	fn metadata(&self, path: &str) -> Result<Entry, NbtError> {
+		let names = components(path);
+		tag_metadata(&self.root, &names, names.last().copied().unwrap_or("/"), path)
+	}
+
+	fn read_dir(&self, path: &str) -> Result<Vec<Entry>, NbtError> {
+		tag_read_dir(&self.root, &components(path), path)
+	}
+
+	fn read(&self, path: &str) -> Result<Vec<u8>, NbtError> {
+		tag_read(&self.root, &components(path), path)
+	}
+}
+
+/// A [VirtualTree] over a world folder. See the [module](self) documentation.<br>
+/// Files are read again on every call, so the tree always shows the current contents of the world.
+#[derive(Clone, Debug)]
+pub struct WorldTree {
+	root: PathBuf,
+}
+
+/// What a path in a [WorldTree] refers to.
+enum WorldNode<'a> {
+	/// A folder or a file that is not NBT.
+	Path(PathBuf),
+	/// A path inside an NBT file.
+	Nbt(Tag, &'a [&'a str]),
+	/// A region file, and the path inside one of its chunks if there is one.
+	Region(PathBuf, Option<(ChunkPos, Tag, &'a [&'a str])>),
+}
+
+impl WorldTree {
+	pub fn new(world: &World) -> Self {
+		Self { root: world.root().to_path_buf() }
+	}
+
+	fn is_nbt_file(path: &Path) -> bool {
+		path.file_name().map(|name| name.to_string_lossy()).is_some_and(|name| {
+			name.ends_with(".dat") || name.ends_with(".dat_old") || name.ends_with(".nbt")
+		})
+	}
+
+	fn is_region_file(path: &Path) -> bool {
+		path.file_name().and_then(|name| RegionPos::from_file_name(&name.to_string_lossy())).is_some()
+	}
+
+	/// Reads an NBT file, or returns `None` if it is not valid NBT.
+	fn read_nbt_file(path: &Path) -> Result<Option<Tag>, NbtError> {
+		let bytes = std::fs::read(path)?;
+		Ok(clipboard::from_binary(&bytes).ok().map(|root| root.take_tag()))
+	}
+
+	fn chunk_name(chunk: ChunkPos) -> String {
+		format!("c.{}.{}", chunk.x, chunk.z)
+	}
+
+	fn parse_chunk_name(name: &str) -> Option<ChunkPos> {
+		let (x, z) = name.strip_prefix("c.")?.split_once('.')?;
+		Some(ChunkPos::new(x.parse().ok()?, z.parse().ok()?))
+	}
+
+	fn resolve<'a>(&self, names: &'a [&'a str], path: &str) -> Result<WorldNode<'a>, NbtError> {
+		let mut current = self.root.clone();
+		for (index, name) in names.iter().enumerate() {
+			if *name == ".." {
+				return Err(error(ErrorKind::NotFound, path));
+			}
+			if current.is_file() {
+				let rest = &names[index..];
+				if Self::is_region_file(&current) {
+					let chunk = Self::parse_chunk_name(rest[0]).ok_or_else(|| error(ErrorKind::NotFound, path))?;
+					let mut region = Region::open(&current)?;
+					if !region.contains_chunk(chunk) {
+						return Err(error(ErrorKind::NotFound, path));
+					}
+					let tag = region.read_chunk(chunk)?.ok_or_else(|| error(ErrorKind::NotFound, path))?.take_tag();
+					return Ok(WorldNode::Region(current, Some((chunk, tag, &rest[1..]))));
+				}
+				if Self::is_nbt_file(&current) {
+					if let Some(tag) = Self::read_nbt_file(&current)? {
+						return Ok(WorldNode::Nbt(tag, rest));
+					}
+				}
+				return Err(error(ErrorKind::NotADirectory, path));
+			}
+			current.push(name);
+			if !current.exists() {
+				return Err(error(ErrorKind::NotFound, path));
+			}
+		}
+		if current.is_file() {
+			if Self::is_region_file(&current) {
+				return Ok(WorldNode::Region(current, None));
+			}
+			if Self::is_nbt_file(&current) {
+				if let Some(tag) = Self::read_nbt_file(&current)? {
+					return Ok(WorldNode::Nbt(tag, &[]));
+				}
+			}
+		}
+		Ok(WorldNode::Path(current))
+	}
+
+	/// Describes a folder, a file, or an NBT or region file, which are directories.
+	fn path_entry(path: &Path, name: String) -> Result<Entry, NbtError> {
+		if path.is_dir() {
+			return Ok(Entry::directory(name, std::fs::read_dir(path)?.count()));
+		}
+		if Self::is_region_file(path) {
+			return Ok(Entry::directory(name, Region::open(path)?.chunks().count()));
+		}
+		if Self::is_nbt_file(path) {
+			if let Some(tag) = Self::read_nbt_file(path)? {
+				return Ok(tag_entry(name, &tag));
+			}
+		}
+		Ok(Entry::file(name, std::fs::metadata(path)?.len() as usize))
+	}
+}
+
+impl VirtualTree for WorldTree {
+	fn metadata(&self, path: &str) -> Result<Entry, NbtError> {
+		let names = components(path);
+		let name = names.last().copied().unwrap_or("/");
+		match self.resolve(&names, path)? {
+			WorldNode::Path(file) | WorldNode::Region(file, None) => Self::path_entry(&file, name.to_owned()),
+			WorldNode::Nbt(tag, rest) | WorldNode::Region(_, Some((_, tag, rest))) => tag_metadata(&tag, rest, name, path),
+		}
+	}
+
+	fn read_dir(&self, path: &str) -> Result<Vec<Entry>, NbtError> {
+		let names = components(path);
+		match self.resolve(&names, path)? {
+			WorldNode::Path(dir) => {
+				if !dir.is_dir() {
+					return Err(error(ErrorKind::NotADirectory, path));
+				}
+				let mut entries = Vec::new();
+				for entry in std::fs::read_dir(dir)? {
+					let entry = entry?;
+					entries.push(Self::path_entry(&entry.path(), entry.file_name().to_string_lossy().into_owned())?);
+				}
+				entries.sort_by(|a, b| a.name.cmp(&b.name));
+				Ok(entries)
+			}
+			WorldNode::Region(file, None) => {
+				let mut region = Region::open(file)?;
+				let mut chunks: Vec<ChunkPos> = region.chunks().collect();
+				chunks.sort_by_key(|chunk| (chunk.x, chunk.z));
+				let mut entries = Vec::with_capacity(chunks.len());
+				for chunk in chunks {
+					if let Some(root) = region.read_chunk(chunk)? {
+						entries.push(tag_entry(Self::chunk_name(chunk), root.tag()));
+					}
+				}
+				Ok(entries)
+			}
+			WorldNode::Nbt(tag, rest) | WorldNode::Region(_, Some((_, tag, rest))) => tag_read_dir(&tag, rest, path),
+		}
+	}
+
+	fn read(&self, path: &str) -> Result<Vec<u8>, NbtError> {
+		let names = components(path);
+		match self.resolve(&names, path)? {
+			WorldNode::Path(file) if file.is_file() => Ok(std::fs::read(file)?),
+			WorldNode::Path(_) | WorldNode::Region(_, None) => Err(error(ErrorKind::IsADirectory, path)),
+			WorldNode::Nbt(tag, rest) | WorldNode::Region(_, Some((_, tag, rest))) => tag_read(&tag, rest, path),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list, tag::{ ListTag, NamedTag }, world::{ OVERWORLD, RegionKind, tests::temp_world } };
+
+	fn names(entries: Vec<Entry>) -> Vec<String> {
+		entries.into_iter().map(|entry| entry.name).collect()
+	}
+
+	fn kind(result: Result<Vec<u8>, NbtError>) -> Option<ErrorKind> {
+		match result.as_ref().map_err(NbtError::root) {
+			Err(NbtError::IoError(err)) => Some(err.kind()),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn escape_test() {
+		for (key, name) in [("", "%"), ("a/b", "a%2Fb"), ("50%", "50%25"), (".snbt", "%2Esnbt"), ("..", "%2E."), ("a.b", "a.b")] {
+			assert_eq!(escape_name(key), name);
+			assert_eq!(unescape_name(name).as_deref(), Some(key));
+		}
+		assert_eq!(unescape_name("%2"), None);
+	}
+
+	#[test]
+	fn tag_tree_test() -> Result<(), NbtError> {
+		let tree = TagTree::new(compound!(("Pos", list!(1.0, 64.0)), ("a/b", 1), ("Inventory", Tag::List(ListTag::Compound(vec![])))));
+		assert_eq!(names(tree.read_dir("/")?), [".snbt", "Inventory", "Pos", "a%2Fb"]);
+		assert_eq!(names(tree.read_dir("Pos")?), [".snbt", "0", "1"]);
+		assert_eq!(tree.read("/a%2Fb")?, b"1\n");
+		assert_eq!(tree.read("/Pos/.snbt")?, b"[1d,64d]\n");
+		assert_eq!(tree.metadata("/")?, Entry::directory("/", 4));
+		assert_eq!(tree.metadata("/Pos/0")?, Entry::file("0", 3));
+		assert_eq!(kind(tree.read("/Pos")), Some(ErrorKind::IsADirectory));
+		assert_eq!(kind(tree.read("/Pos/2")), Some(ErrorKind::NotFound));
+		assert_eq!(kind(tree.read("/Pos/01")), Some(ErrorKind::NotFound));
+		assert_eq!(kind(tree.read("/Pos/0/x")), Some(ErrorKind::NotADirectory));
+		assert!(tree.read_dir("/a%2Fb").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn world_tree_test() -> Result<(), NbtError> {
+		let dir = temp_world("vfs");
+		std::fs::create_dir_all(&dir)?;
+		let world = World::open(&dir)?;
+		crate::world::write_gzip_nbt(dir.join("level.dat"), &NamedTag::new(compound!(("Data", compound!(("LevelName", "Test"))))))?;
+		std::fs::write(dir.join("icon.png"), b"png")?;
+		let chunk = ChunkPos::new(1, 2);
+		let region_path = world.region_path(OVERWORLD, RegionKind::Chunks, chunk.region());
+		std::fs::create_dir_all(region_path.parent().expect("Region has a folder."))?;
+		Region::create(&region_path)?.write_chunk(chunk, &NamedTag::new(compound!(("xPos", 1))))?;
+
+		let tree = WorldTree::new(&world);
+		assert_eq!(names(tree.read_dir("/")?), ["icon.png", "level.dat", "region"]);
+		assert!(tree.metadata("/level.dat")?.is_dir());
+		assert_eq!(tree.read("/level.dat/Data/LevelName")?, b"\"Test\"\n");
+		assert_eq!(tree.read("/icon.png")?, b"png");
+		assert_eq!(names(tree.read_dir("/region/r.0.0.mca")?), ["c.1.2"]);
+		assert_eq!(tree.read("/region/r.0.0.mca/c.1.2/xPos")?, b"1\n");
+		assert_eq!(kind(tree.read("/region/r.0.0.mca/c.0.0")), Some(ErrorKind::NotFound));
+		assert_eq!(kind(tree.read("/region")), Some(ErrorKind::IsADirectory));
+		assert_eq!(kind(tree.read("/icon.png/x")), Some(ErrorKind::NotADirectory));
+		assert_eq!(kind(tree.read("/../secret")), Some(ErrorKind::NotFound));
+		std::fs::remove_dir_all(dir)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,453 @@
+#![doc = r#"
+A borrowed view of a [Tag] that groups its types by category.
+
+[Tag::view] returns a [TagView], which has one variant per category instead of one per type: [NumberView] for the six
+numeric types and [ArrayView] for the three array types. Analysis code that treats every number or every array alike
+can match the categories with fewer arms, and still match the exact type inside a category when it matters.
+[Tag::as_array] returns the [ArrayView] of an array directly, and its elements can be read as [i64]s with
+[ArrayView::get_i64] and [ArrayView::iter_i64], so statistics and packing code needs one copy for every array type.
+
+NBT only has signed integers, but many of them hold unsigned data, such as colors, light levels, and packed block
+states. The unsigned accessors, like [Tag::as_u8_slice] and [Tag::as_u32], reinterpret the bits of a value as an
+unsigned integer of the same width, in two's complement: `Tag::Byte(-1).as_u8()` is `Some(255)`. They never clamp or
+convert between widths, and return None for tags of other types.
+
+```
+# use rustnbt::{ compound, tag::Tag, view::TagView };
+fn total(tag: &Tag) -> f64 {
+	match tag.view() {
+		TagView::Number(number) => number.as_f64(),
+		TagView::Array(array) => array.iter().map(|number| number.as_f64()).sum(),
+		TagView::Compound(map) => map.values().map(total).sum(),
+		TagView::String(_) | TagView::List(_) => 0.0,
+	}
+}
+let tag = compound!(("Health", 20.0f32), ("Levels", Tag::IntArray(vec![1, 2])), ("Name", "Steve"));
+assert_eq!(total(&tag), 23.0);
+```
+"#]
+
+use crate::{
+	Map,
+	tag::{ Tag, TagID, ListTag },
+};
+
+/// A [Tag] grouped by category. See the [module](self) documentation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TagView<'a> {
+	Number(NumberView),
+	Array(ArrayView<'a>),
+	String(&'a str),
+	List(&'a ListTag),
+	Compound(&'a Map),
+}
+
+/// The value of a numeric [Tag].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NumberView {
+	Byte(i8),
+	Short(i16),
+	Int(i32),
+	Long(i64),
+	Float(f32),
+	Double(f64),
+}
+
+/// The elements of an array [Tag].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ArrayView<'a> {
+	Byte(&'a [i8]),
+	Int(&'a [i32]),
+	Long(&'a [i64]),
+}
+
+impl Tag {
+	/// Returns a view of this tag grouped by category.
+	pub fn view(&self) -> TagView<'_> {
+		match self {
+			Tag::Byte(value) => TagView::Number(NumberView::Byte(*value)),
+			Tag::Short(value) => TagView::Number(NumberView::Short(*value)),
+			Tag::Int(value) => TagView::Number(NumberView::Int(*value)),
+			Tag::Long(value) => TagView::Number(NumberView::Long(*value)),
+			Tag::Float(value) => TagView::Number(NumberView::Float(*value)),
+			Tag::Double(value) => TagView::Number(NumberView::Double(*value)),
+			Tag::ByteArray(array) => TagView::Array(ArrayView::Byte(array)),
+			Tag::IntArray(array) => TagView::Array(ArrayView::Int(array)),
+			Tag::LongArray(array) => TagView::Array(ArrayView::Long(array)),
+			Tag::String(value) => TagView::String(value),
+			Tag::List(list) => TagView::List(list),
+			Tag::Compound(map) => TagView::Compound(map),
+		}
+	}
+
+	/// Returns a view of the elements of a ByteArray, IntArray, or LongArray, or None for other tags.
+	pub fn as_array(&self) -> Option<ArrayView<'_>> {
+		match self.view() {
+			TagView::Array(array) => Some(array),
+			_ => None,
+		}
+	}
+}
+
+/// Accessors that reinterpret signed integers as unsigned ones of the same width. See the [module](self) documentation.
+impl Tag {
+	/// Returns the value of a Byte as a [u8], so `-1` is `255`.
+	pub fn as_u8(&self) -> Option<u8> {
+		match self {
+			Tag::Byte(value) => Some(*value as u8),
+			_ => None,
+		}
+	}
+
+	/// Returns the value of a Short as a [u16], so `-1` is `65535`.
+	pub fn as_u16(&self) -> Option<u16> {
+		match self {
+			Tag::Short(value) => Some(*value as u16),
+			_ => None,
+		}
+	}
+
+	/// Returns the value of an Int as a [u32], so `-1` is [u32::MAX].
+	pub fn as_u32(&self) -> Option<u32> {
+		match self {
+			Tag::Int(value) => Some(*value as u32),
+			_ => None,
+		}
+	}
+
+	/// Returns the value of a Long as a [u64], so `-1` is [u64::MAX].
+	pub fn as_u64(&self) -> Option<u64> {
+		match self {
+			Tag::Long(value) => Some(*value as u64),
+			_ => None,
+		}
+	}
+
+	/// Returns the elements of a ByteArray as [u8]s without copying them.
+	pub fn as_u8_slice(&self) -> Option<&[u8]> {
+		match self {
+			Tag::ByteArray(values) => Some(bytemuck::cast_slice(values)),
+			_ => None,
+		}
+	}
+
+	/// Returns the elements of a ByteArray as mutable [u8]s.
+	pub fn as_u8_slice_mut(&mut self) -> Option<&mut [u8]> {
+		match self {
+			Tag::ByteArray(values) => Some(bytemuck::cast_slice_mut(values)),
+			_ => None,
+		}
+	}
+
+	/// Returns the elements of an IntArray as [u32]s without copying them.
+	pub fn as_u32_slice(&self) -> Option<&[u32]> {
+		match self {
+			Tag::IntArray(values) => Some(bytemuck::cast_slice(values)),
+			_ => None,
+		}
+	}
+
+	/// Returns the elements of an IntArray as mutable [u32]s.
+	pub fn as_u32_slice_mut(&mut self) -> Option<&mut [u32]> {
+		match self {
+			Tag::IntArray(values) => Some(bytemuck::cast_slice_mut(values)),
+			_ => None,
+		}
+	}
+
+	/// Returns the elements of a LongArray as [u64]s without copying them.
+	pub fn as_u64_slice(&self) -> Option<&[u64]> {
+		match self {
+			Tag::LongArray(values) => Some(bytemuck::cast_slice(values)),
+			_ => None,
+		}
+	}
+
+	/// Returns the elements of a LongArray as mutable [u64]s.
+	pub fn as_u64_slice_mut(&mut self) -> Option<&mut [u64]> {
+		match self {
+			Tag::LongArray(values) => Some(bytemuck::cast_slice_mut(values)),
+			_ => None,
+		}
+	}
+}
+
+impl<'a> TagView<'a> {
+	/// Returns the NBT type ID of the viewed tag.
+	pub fn id(self) -> TagID {
+		match self {
+			TagView::Number(number) => number.id(),
+			TagView::Array(array) => array.id(),
+			TagView::String(_) => TagID::String,
+			TagView::List(_) => TagID::List,
+			TagView::Compound(_) => TagID::Compound,
+		}
+	}
+
+	/// Returns true if the viewed tag is a List or Compound.
+	pub fn is_container(self) -> bool {
+		matches!(self, TagView::List(_) | TagView::Compound(_))
+	}
+
+	/// Returns the number of elements of an array, List, or Compound, or None for other tags.
+	pub fn element_count(self) -> Option<usize> {
+		match self {
+			TagView::Array(array) => Some(array.len()),
+			TagView::List(list) => Some(list.len()),
+			TagView::Compound(map) => Some(map.len()),
+			TagView::Number(_) | TagView::String(_) => None,
+		}
+	}
+
+	/// Copies the viewed tag into a new [Tag].
+	pub fn to_tag(self) -> Tag {
+		match self {
+			TagView::Number(number) => number.to_tag(),
+			TagView::Array(array) => array.to_tag(),
+			TagView::String(value) => Tag::String(value.to_owned()),
+			TagView::List(list) => Tag::List(list.clone()),
+			TagView::Compound(map) => Tag::Compound(map.clone()),
+		}
+	}
+}
+
+impl NumberView {
+	/// Returns the NBT type ID of the number.
+	pub fn id(self) -> TagID {
+		match self {
+			NumberView::Byte(_) => TagID::Byte,
+			NumberView::Short(_) => TagID::Short,
+			NumberView::Int(_) => TagID::Int,
+			NumberView::Long(_) => TagID::Long,
+			NumberView::Float(_) => TagID::Float,
+			NumberView::Double(_) => TagID::Double,
+		}
+	}
+
+	/// Returns true for Byte, Short, Int, and Long.
+	pub fn is_integer(self) -> bool {
+		!matches!(self, NumberView::Float(_) | NumberView::Double(_))
+	}
+
+	/// Returns the value of an integer, or None for Float and Double.
+	pub fn as_i64(self) -> Option<i64> {
+		match self {
+			NumberView::Byte(value) => Some(value as i64),
+			NumberView::Short(value) => Some(value as i64),
+			NumberView::Int(value) => Some(value as i64),
+			NumberView::Long(value) => Some(value),
+			NumberView::Float(_) | NumberView::Double(_) => None,
+		}
+	}
+
+	/// Returns the bits of an integer as an unsigned integer of the same width, zero-extended to a [u64], or None for
+	/// Float and Double. `Byte(-1)` is `255`.
+	pub fn as_unsigned(self) -> Option<u64> {
+		match self {
+			NumberView::Byte(value) => Some(value as u8 as u64),
+			NumberView::Short(value) => Some(value as u16 as u64),
+			NumberView::Int(value) => Some(value as u32 as u64),
+			NumberView::Long(value) => Some(value as u64),
+			NumberView::Float(_) | NumberView::Double(_) => None,
+		}
+	}
+
+	/// Returns the value as an [f64]. Longs beyond 2<sup>53</sup> lose precision.
+	pub fn as_f64(self) -> f64 {
+		match self {
+			NumberView::Byte(value) => value as f64,
+			NumberView::Short(value) => value as f64,
+			NumberView::Int(value) => value as f64,
+			NumberView::Long(value) => value as f64,
+			NumberView::Float(value) => value as f64,
+			NumberView::Double(value) => value,
+		}
+	}
+
+	/// Returns true if the value is zero, which the game reads as `false`.
+	pub fn is_zero(self) -> bool {
+		self.as_f64() == 0.0
+	}
+
+	pub fn to_tag(self) -> Tag {
+		match self {
+			NumberView::Byte(value) => Tag::Byte(value),
+			NumberView::Short(value) => Tag::Short(value),
+			NumberView::Int(value) => Tag::Int(value),
+			NumberView::Long(value) => Tag::Long(value),
+			NumberView::Float(value) => Tag::Float(value),
+			NumberView::Double(value) => Tag::Double(value),
+		}
+	}
+}
+
+impl<'a> ArrayView<'a> {
+	/// Returns the NBT type ID of the array.
+	pub fn id(self) -> TagID {
+		match self {
+			ArrayView::Byte(_) => TagID::ByteArray,
+			ArrayView::Int(_) => TagID::IntArray,
+			ArrayView::Long(_) => TagID::LongArray,
+		}
+	}
+
+	/// Returns the NBT type ID of the elements.
+	pub fn element_id(self) -> TagID {
+		match self {
+			ArrayView::Byte(_) => TagID::Byte,
+			ArrayView::Int(_) => TagID::Int,
+			ArrayView::Long(_) => TagID::Long,
+		}
+	}
+
+	pub fn len(self) -> usize {
+		match self {
+			ArrayView::Byte(array) => array.len(),
+			ArrayView::Int(array) => array.len(),
+			ArrayView::Long(array) => array.len(),
+		}
+	}
+
+	pub fn is_empty(self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the element at `index`, or None if it is out of bounds.
+	pub fn get(self, index: usize) -> Option<NumberView> {
+		match self {
+			ArrayView::Byte(array) => array.get(index).copied().map(NumberView::Byte),
+			ArrayView::Int(array) => array.get(index).copied().map(NumberView::Int),
+			ArrayView::Long(array) => array.get(index).copied().map(NumberView::Long),
+		}
+	}
+
+	/// Iterates over the elements.
+	pub fn iter(self) -> impl Iterator<Item = NumberView> + 'a {
+		(0..self.len()).map(move |index| self.get(index).expect("The index is in bounds."))
+	}
+
+	/// Returns the element at `index` widened to an [i64], or None if it is out of bounds.
+	pub fn get_i64(self, index: usize) -> Option<i64> {
+		match self {
+			ArrayView::Byte(array) => array.get(index).map(|value| i64::from(*value)),
+			ArrayView::Int(array) => array.get(index).map(|value| i64::from(*value)),
+			ArrayView::Long(array) => array.get(index).copied(),
+		}
+	}
+
+	/// Iterates over the elements widened to [i64]s.
+	pub fn iter_i64(self) -> impl Iterator<Item = i64> + 'a {
+		(0..self.len()).map(move |index| self.get_i64(index).expect("The index is in bounds."))
+	}
+
+	/// The size of an element in bytes: 1, 4, or 8.
+	pub fn element_size(self) -> usize {
+		match self {
+			ArrayView::Byte(_) => 1,
+			ArrayView::Int(_) => 4,
+			ArrayView::Long(_) => 8,
+		}
+	}
+
+	/// Returns the memory of the elements as bytes, in native byte order, without copying them. It is
+	/// [ArrayView::element_size] times [ArrayView::len] bytes long.
+	pub fn as_bytes(self) -> &'a [u8] {
+		match self {
+			ArrayView::Byte(array) => bytemuck::cast_slice(array),
+			ArrayView::Int(array) => bytemuck::cast_slice(array),
+			ArrayView::Long(array) => bytemuck::cast_slice(array),
+		}
+	}
+
+	/// Copies the elements into a new [Tag].
+	pub fn to_tag(self) -> Tag {
+		match self {
+			ArrayView::Byte(array) => Tag::ByteArray(array.to_vec()),
+			ArrayView::Int(array) => Tag::IntArray(array.to_vec()),
+			ArrayView::Long(array) => Tag::LongArray(array.to_vec()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ compound, list };
+
+	#[test]
+	fn view_test() {
+		let tags = [
+			Tag::Byte(1),
+			Tag::Short(2),
+			Tag::Int(3),
+			Tag::Long(4),
+			Tag::Float(0.5),
+			Tag::Double(-1.5),
+			Tag::ByteArray(vec![1, 2]),
+			Tag::String("text".to_owned()),
+			list!(1, 2, 3),
+			compound!(("a", 1)),
+			Tag::IntArray(vec![3, 4, 5]),
+			Tag::LongArray(vec![]),
+		];
+		for tag in &tags {
+			assert_eq!(tag.view().id(), tag.id());
+			assert_eq!(&tag.view().to_tag(), tag);
+		}
+		let numbers: Vec<NumberView> = tags.iter().filter_map(|tag| match tag.view() {
+			TagView::Number(number) => Some(number),
+			_ => None,
+		}).collect();
+		assert_eq!(numbers.len(), 6);
+		assert_eq!(numbers.iter().filter_map(|number| number.as_i64()).sum::<i64>(), 10);
+		assert_eq!(numbers[5].as_f64(), -1.5);
+		assert!(!numbers[4].is_integer() && !numbers[0].is_zero());
+
+		let TagView::Array(array) = tags[10].view() else { panic!("IntArray is an array.") };
+		assert_eq!((array.element_id(), array.len(), array.get(1), array.get(3)), (TagID::Int, 3, Some(NumberView::Int(4)), None));
+		assert_eq!(array.iter().map(NumberView::as_f64).sum::<f64>(), 12.0);
+		assert_eq!(tags[11].view().element_count(), Some(0));
+		assert_eq!(tags[8].view().element_count(), Some(3));
+		assert!(tags[9].view().is_container() && !tags[7].view().is_container());
+		assert_eq!(tags[7].view(), TagView::String("text"));
+	}
+
+	#[test]
+	fn array_view_test() {
+		// One function for every array type.
+		let range = |tag: &Tag| tag.as_array().and_then(|array| Some((array.iter_i64().min()?, array.iter_i64().max()?)));
+		assert_eq!(range(&Tag::ByteArray(vec![3, -7, 5])), Some((-7, 5)));
+		assert_eq!(range(&Tag::IntArray(vec![i32::MAX, 0])), Some((0, i32::MAX as i64)));
+		assert_eq!(range(&Tag::LongArray(vec![i64::MIN])), Some((i64::MIN, i64::MIN)));
+		assert_eq!(range(&Tag::LongArray(vec![])), None);
+		assert_eq!(range(&list!(1, 2)), None);
+
+		let tag = Tag::IntArray(vec![-1, 2]);
+		let array = tag.as_array().expect("IntArray is an array.");
+		assert_eq!((array.get_i64(0), array.get_i64(2)), (Some(-1), None));
+		assert_eq!(array.as_bytes().len(), array.element_size() * array.len());
+		assert_eq!(&array.as_bytes()[4..], 2i32.to_ne_bytes());
+	}
+
+	#[test]
+	fn unsigned_test() {
+		assert_eq!(Tag::Byte(-1).as_u8(), Some(255));
+		assert_eq!(Tag::Short(i16::MIN).as_u16(), Some(0x8000));
+		assert_eq!(Tag::Int(-2).as_u32(), Some(u32::MAX - 1));
+		assert_eq!(Tag::Long(-1).as_u64(), Some(u64::MAX));
+		assert_eq!(Tag::Int(1).as_u8(), None);
+		assert_eq!(NumberView::Byte(-128).as_unsigned(), Some(128));
+		assert_eq!(NumberView::Int(-1).as_unsigned(), Some(u32::MAX as u64));
+		assert_eq!(NumberView::Float(1.0).as_unsigned(), None);
+
+		let mut colors = Tag::ByteArray(vec![-1, 0, 127, -128]);
+		assert_eq!(colors.as_u8_slice(), Some([255, 0, 127, 128].as_slice()));
+		colors.as_u8_slice_mut().expect("ByteArray has bytes.")[1] = 200;
+		assert_eq!(colors, Tag::ByteArray(vec![-1, -56, 127, -128]));
+		let mut light = Tag::IntArray(vec![-1, 7]);
+		light.as_u32_slice_mut().expect("IntArray has ints.")[1] = 0xFFFF_FFF0;
+		assert_eq!(light.as_u32_slice(), Some([u32::MAX, 0xFFFF_FFF0].as_slice()));
+		assert_eq!(Tag::LongArray(vec![i64::MIN]).as_u64_slice(), Some([1u64 << 63].as_slice()));
+		assert_eq!(Tag::IntArray(vec![]).as_u64_slice(), None);
+	}
+}
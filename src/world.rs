@@ -0,0 +1,1344 @@
+#![doc = r#"
+Access to the files of a Minecraft world save folder.
+
+```no_run
+# use rustnbt::world::World;
+let world = World::open("saves/New World").expect("Failed to open world.");
+for uuid in world.player_uuids().expect("Failed to list players.") {
+	let player = world.player(&uuid).expect("Failed to read player.");
+	println!("{uuid}: {:?}", player.data.position());
+}
+```
+"#]
+
+use crate::{
+	Map,
+	NbtError,
+	bulk::{ ErrorPolicy, ItemError },
+	context::{ ErrorContext, WithContext },
+	invalid_data,
+	io::*,
+	player::PlayerData,
+	pos::{ BlockPos, ChunkPos, RegionPos },
+	region::{
+		Region,
+		RawChunk,
+	},
+	tag::{
+		Tag,
+		ListTag,
+		NamedTag,
+	},
+};
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::fs::File;
+use std::io::{ BufReader, BufWriter };
+use std::path::{ Path, PathBuf };
+
+/// A world save folder.
+#[derive(Clone, Debug)]
+pub struct World {
+	root: PathBuf,
+}
+
+/// Everything that a world stores about a single player.
+#[derive(Clone, Debug)]
+pub struct WorldPlayer {
+	/// The player's UUID, in the hyphenated form used for file names.
+	pub uuid: String,
+	/// The player's NBT data from `playerdata/<uuid>.dat`.
+	pub data: PlayerData,
+	/// The player's statistics from `stats/<uuid>.json`, if they were loaded and exist.
+	#[cfg(feature = "json")]
+	pub stats: Option<serde_json::Value>,
+	/// The player's advancements from `advancements/<uuid>.json`, if they were loaded and exist.
+	#[cfg(feature = "json")]
+	pub advancements: Option<serde_json::Value>,
+}
+
+impl World {
+	/// Opens the world save folder at `path`.
+	/// This will fail if the path is not a directory.
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, NbtError> {
+		let root = path.as_ref().to_path_buf();
+		if !root.is_dir() {
+			return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} is not a directory.", root.display())).into());
+		}
+		Ok(Self { root })
+	}
+
+	/// The path of the world save folder.
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Returns the UUIDs of all players that have a file in the `playerdata` folder.
+	pub fn player_uuids(&self) -> Result<Vec<String>, NbtError> {
+		let dir = self.root.join("playerdata");
+		if !dir.is_dir() {
+			return Ok(Vec::new());
+		}
+		let mut uuids = Vec::new();
+		for entry in std::fs::read_dir(dir)? {
+			let path = entry?.path();
+			if path.extension().is_some_and(|ext| ext == "dat") {
+				if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+					uuids.push(stem.to_owned());
+				}
+			}
+		}
+		uuids.sort();
+		Ok(uuids)
+	}
+
+	/// Reads the NBT data of a player.
+	/// The statistics and advancements of the player are not loaded.
+	pub fn player(&self, uuid: &str) -> Result<WorldPlayer, NbtError> {
+		let root = read_gzip_nbt(self.player_path(uuid))?;
+		let data = match root.take_tag() {
+			Tag::Compound(data) => PlayerData::new(data),
+			_ => return Err(invalid_data("The root of a player data file must be a compound.")),
+		};
+		Ok(WorldPlayer {
+			uuid: uuid.to_owned(),
+			data,
+			#[cfg(feature = "json")]
+			stats: None,
+			#[cfg(feature = "json")]
+			advancements: None,
+		})
+	}
+
+	/// Reads the NBT data of a player along with their statistics and advancements.
+	/// Missing JSON files are not an error, and leave the corresponding field as [None].
+	#[cfg(feature = "json")]
+	pub fn player_with_json(&self, uuid: &str) -> Result<WorldPlayer, NbtError> {
+		let mut player = self.player(uuid)?;
+		player.stats = read_json(self.stats_path(uuid))?;
+		player.advancements = read_json(self.advancements_path(uuid))?;
+		Ok(player)
+	}
+
+	/// Writes a player back to the world.
+	/// With the `json` feature, statistics and advancements are written too if they are present.
+	pub fn save_player(&self, player: &WorldPlayer) -> Result<(), NbtError> {
+		let root = NamedTag::new(Tag::Compound(player.data.data().clone()));
+		write_gzip_nbt(self.player_path(&player.uuid), &root)?;
+		#[cfg(feature = "json")]
+		{
+			if let Some(stats) = &player.stats {
+				write_json(self.stats_path(&player.uuid), stats)?;
+			}
+			if let Some(advancements) = &player.advancements {
+				write_json(self.advancements_path(&player.uuid), advancements)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Path to `playerdata/<uuid>.dat`.
+	pub fn player_path(&self, uuid: &str) -> PathBuf {
+		self.root.join("playerdata").join(format!("{uuid}.dat"))
+	}
+
+	/// Path to `stats/<uuid>.json`.
+	pub fn stats_path(&self, uuid: &str) -> PathBuf {
+		self.root.join("stats").join(format!("{uuid}.json"))
+	}
+
+	/// Path to `advancements/<uuid>.json`.
+	pub fn advancements_path(&self, uuid: &str) -> PathBuf {
+		self.root.join("advancements").join(format!("{uuid}.json"))
+	}
+
+	/// Path to the folder of a dimension, such as `DIM-1` for `minecraft:the_nether`.<br>
+	/// Dimensions added by datapacks and mods live in `dimensions/<namespace>/<path>`.
+	pub fn dimension_path(&self, dimension: &str) -> PathBuf {
+		match dimension {
+			OVERWORLD => self.root.clone(),
+			THE_NETHER => self.root.join("DIM-1"),
+			THE_END => self.root.join("DIM1"),
+			other => {
+				let (namespace, path) = other.split_once(':').unwrap_or(("minecraft", other));
+				self.root.join("dimensions").join(namespace).join(path)
+			}
+		}
+	}
+
+	/// Returns the IDs of all dimensions that have a folder in the world.
+	pub fn dimensions(&self) -> Result<Vec<String>, NbtError> {
+		let mut dimensions: Vec<String> = [OVERWORLD, THE_NETHER, THE_END].into_iter()
+			.filter(|dimension| self.dimension_path(dimension).join(RegionKind::Chunks.folder()).is_dir())
+			.map(str::to_owned)
+			.collect();
+		let custom = self.root.join("dimensions");
+		if custom.is_dir() {
+			for namespace in std::fs::read_dir(custom)? {
+				let namespace = namespace?;
+				if !namespace.file_type()?.is_dir() {
+					continue;
+				}
+				for path in std::fs::read_dir(namespace.path())? {
+					let path = path?;
+					let id = format!("{}:{}", namespace.file_name().to_string_lossy(), path.file_name().to_string_lossy());
+					if path.file_type()?.is_dir() && !dimensions.contains(&id) {
+						dimensions.push(id);
+					}
+				}
+			}
+		}
+		Ok(dimensions)
+	}
+
+	/// Path to the folder holding the region files of the given kind in a dimension.
+	pub fn region_dir(&self, dimension: &str, kind: RegionKind) -> PathBuf {
+		self.dimension_path(dimension).join(kind.folder())
+	}
+
+	/// Returns the positions of all region files of the given kind in a dimension.
+	pub fn regions(&self, dimension: &str, kind: RegionKind) -> Result<Vec<RegionPos>, NbtError> {
+		let dir = self.region_dir(dimension, kind);
+		if !dir.is_dir() {
+			return Ok(Vec::new());
+		}
+		let mut regions = Vec::new();
+		for entry in std::fs::read_dir(dir)? {
+			if let Some(region) = entry?.file_name().to_str().and_then(RegionPos::from_file_name) {
+				regions.push(region);
+			}
+		}
+		regions.sort_unstable();
+		Ok(regions)
+	}
+
+	/// Path to the region file of the given kind at the given position.
+	pub fn region_path(&self, dimension: &str, kind: RegionKind, region: RegionPos) -> PathBuf {
+		self.region_dir(dimension, kind).join(region.file_name())
+	}
+}
+
+/// ID of the overworld dimension.
+pub const OVERWORLD: &str = "minecraft:overworld";
+/// ID of the nether dimension.
+pub const THE_NETHER: &str = "minecraft:the_nether";
+/// ID of the end dimension.
+pub const THE_END: &str = "minecraft:the_end";
+
+/// The kinds of region files that a dimension has.
+/// Since 1.17, entities and points of interest are stored apart from the chunk terrain.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RegionKind {
+	/// Chunk terrain, in the `region` folder.
+	Chunks,
+	/// Entities, in the `entities` folder.
+	Entities,
+	/// Points of interest, in the `poi` folder.
+	Poi,
+}
+
+impl RegionKind {
+	/// All region kinds.
+	pub const ALL: [RegionKind; 3] = [RegionKind::Chunks, RegionKind::Entities, RegionKind::Poi];
+
+	/// The name of the folder that holds region files of this kind.
+	pub const fn folder(self) -> &'static str {
+		match self {
+			RegionKind::Chunks => "region",
+			RegionKind::Entities => "entities",
+			RegionKind::Poi => "poi",
+		}
+	}
+}
+
+/// Decides which chunk to keep when both worlds passed to [merge] contain the same chunk.
+pub enum MergePolicy<'a> {
+	/// Always take the chunk from the source world.
+	PreferSource,
+	/// Always keep the chunk in the destination world.
+	PreferDestination,
+	/// Keep the chunk that was written most recently, according to the region timestamps.
+	/// Ties keep the destination chunk.
+	Newest,
+	/// Keep the chunk that players spent the most time in (`InhabitedTime`).
+	/// Ties and chunks without `InhabitedTime` keep the destination chunk.
+	MostInhabited,
+	/// Ask a callback for every conflict.
+	Custom(&'a mut dyn FnMut(&MergeConflict) -> MergeChoice),
+}
+
+/// The choice made for a conflicting chunk during a [merge].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MergeChoice {
+	Source,
+	Destination,
+}
+
+/// A chunk that exists in both worlds passed to [merge].
+#[derive(Debug)]
+pub struct MergeConflict<'a> {
+	pub dimension: &'a str,
+	pub chunk: ChunkPos,
+	pub source: &'a RawChunk,
+	pub source_timestamp: u32,
+	pub destination: &'a RawChunk,
+	pub destination_timestamp: u32,
+}
+
+/// The number of chunks affected by a [merge].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct MergeReport {
+	/// Chunks that only existed in the source world.
+	pub added: usize,
+	/// Conflicting chunks where the source chunk replaced the destination chunk.
+	pub replaced: usize,
+	/// Conflicting chunks where the destination chunk was kept.
+	pub kept: usize,
+	/// Entities and points of interest chunks that were deleted from the destination world, because the source terrain
+	/// replaced their position and the source world has none.
+	pub removed: usize,
+}
+
+/// Reads `InhabitedTime` from a chunk, which is nested in `Level` before 1.18.
+fn inhabited_time(chunk: &RawChunk) -> Option<i64> {
+	let root = chunk.decode().ok()?.take_tag();
+	let Tag::Compound(root) = root else {
+		return None;
+	};
+	let level = match root.get("Level") {
+		Some(Tag::Compound(level)) => level,
+		_ => &root,
+	};
+	match level.get("InhabitedTime") {
+		Some(Tag::Long(time)) => Some(*time),
+		_ => None,
+	}
+}
+
+/// Copies the chunks of every dimension in `source` into `destination`, without decompressing them.<br>
+/// Chunks that only exist in `source` are copied, and chunks that exist in both worlds are resolved with `policy`.
+/// The choice for a chunk position is made on its terrain chunk, and the entities and points of interest
+/// of that position follow the same choice so that they stay consistent with the terrain.
+pub fn merge(source: &World, destination: &World, policy: MergePolicy) -> Result<MergeReport, NbtError> {
+	Ok(merge_with(source, destination, policy, ErrorPolicy::fail_fast())?.0)
+}
+
+/// Like [merge], with a policy for the region files and chunks that fail. Also returns the items that were skipped.<br>
+/// A skipped chunk is left as it was in `destination`. If its terrain chunk was skipped, its entities and points of
+/// interest follow the destination terrain, or are resolved on their own if the destination has no terrain there.
+pub fn merge_with(source: &World, destination: &World, mut policy: MergePolicy, on_error: ErrorPolicy) -> Result<(MergeReport, Vec<ItemError>), NbtError> {
+	let mut report = MergeReport::default();
+	let mut errors = Vec::new();
+	for dimension in source.dimensions()? {
+		merge_dimension(source, destination, &dimension, &mut policy, on_error, &mut report, &mut errors)
+			.with_context(|| ErrorContext::default().dimension(&dimension))?;
+	}
+	Ok((report, errors))
+}
+
+/// Merges the region files of one dimension, for [merge_with].
+fn merge_dimension(
+	source: &World,
+	destination: &World,
+	dimension: &str,
+	policy: &mut MergePolicy,
+	on_error: ErrorPolicy,
+	report: &mut MergeReport,
+	errors: &mut Vec<ItemError>,
+) -> Result<(), NbtError> {
+	let mut choices: HashMap<ChunkPos, MergeChoice> = HashMap::new();
+	for kind in RegionKind::ALL {
+		// The source chunks of this kind, and the source regions that could not be opened.
+		let mut sources = HashSet::new();
+		let mut skipped = HashSet::new();
+		for region in source.regions(dimension, kind)? {
+			let opened = on_error.run(errors, || ErrorContext::default().dimension(dimension).region(region), || {
+				let src = Region::open(source.region_path(dimension, kind, region))?;
+				std::fs::create_dir_all(destination.region_dir(dimension, kind))?;
+				Ok((src, Region::create(destination.region_path(dimension, kind, region))?))
+			})?;
+			let Some((mut src, mut dst)) = opened else {
+				skipped.insert(region);
+				continue;
+			};
+			let chunks: Vec<ChunkPos> = src.chunks().collect();
+			sources.extend(chunks.iter().copied());
+			for chunk in chunks {
+				on_error.run(errors, || ErrorContext::default().dimension(dimension).chunk(chunk), || {
+					merge_chunk(policy, dimension, kind, chunk, &mut src, &mut dst, &mut choices, report)
+				})?;
+			}
+		}
+		for region in destination.regions(dimension, kind)? {
+			if skipped.contains(&region) {
+				continue;
+			}
+			on_error.run(errors, || ErrorContext::default().dimension(dimension).region(region), || {
+				let mut dst = Region::open(destination.region_path(dimension, kind, region))?;
+				let chunks: Vec<ChunkPos> = dst.chunks().collect();
+				for chunk in chunks {
+					if kind == RegionKind::Chunks {
+						// Terrain that was not replaced keeps its entities and points of interest.
+						choices.entry(chunk).or_insert(MergeChoice::Destination);
+					} else if !sources.contains(&chunk) && choices.get(&chunk) == Some(&MergeChoice::Source) && dst.delete_chunk(chunk)? {
+						// The source terrain replaced this position, and the source has no chunk of this kind.
+						report.removed += 1;
+					}
+				}
+				Ok(())
+			})?;
+		}
+	}
+	Ok(())
+}
+
+/// Merges one chunk, for [merge_with]. The report is only changed once the chunk was merged.<br>
+/// Entities and points of interest follow the choice made on the terrain of their position.
+#[allow(clippy::too_many_arguments)]
+fn merge_chunk(
+	policy: &mut MergePolicy,
+	dimension: &str,
+	kind: RegionKind,
+	chunk: ChunkPos,
+	src: &mut Region,
+	dst: &mut Region,
+	choices: &mut HashMap<ChunkPos, MergeChoice>,
+	report: &mut MergeReport,
+) -> Result<(), NbtError> {
+	let exists = dst.contains_chunk(chunk);
+	let terrain = if kind == RegionKind::Chunks { None } else { choices.get(&chunk).copied() };
+	let choice = match terrain {
+		Some(choice) => choice,
+		None if !exists => MergeChoice::Source,
+		None => choose(policy, dimension, chunk, src, dst)?,
+	};
+	match (choice, exists) {
+		(MergeChoice::Source, false) => {
+			src.copy_chunk_raw(chunk, dst)?;
+			report.added += 1;
+		}
+		(MergeChoice::Source, true) => {
+			src.copy_chunk_raw(chunk, dst)?;
+			report.replaced += 1;
+		}
+		(MergeChoice::Destination, true) => report.kept += 1,
+		// The destination terrain was kept, so the source chunk of this position is left out.
+		(MergeChoice::Destination, false) => (),
+	}
+	choices.entry(chunk).or_insert(choice);
+	Ok(())
+}
+
+fn choose(policy: &mut MergePolicy, dimension: &str, chunk: ChunkPos, src: &mut Region, dst: &mut Region) -> Result<MergeChoice, NbtError> {
+	let source_timestamp = src.timestamp(chunk).unwrap_or(0);
+	let destination_timestamp = dst.timestamp(chunk).unwrap_or(0);
+	let prefer_source = |source_wins: bool| if source_wins { MergeChoice::Source } else { MergeChoice::Destination };
+	Ok(match policy {
+		MergePolicy::PreferSource => MergeChoice::Source,
+		MergePolicy::PreferDestination => MergeChoice::Destination,
+		MergePolicy::Newest => prefer_source(source_timestamp > destination_timestamp),
+		MergePolicy::MostInhabited => {
+			let source = src.read_raw_chunk(chunk)?.as_ref().and_then(inhabited_time);
+			let destination = dst.read_raw_chunk(chunk)?.as_ref().and_then(inhabited_time);
+			prefer_source(source.unwrap_or(i64::MIN) > destination.unwrap_or(i64::MIN))
+		}
+		MergePolicy::Custom(callback) => {
+			let (Some(source), Some(destination)) = (src.read_raw_chunk(chunk)?, dst.read_raw_chunk(chunk)?) else {
+				return Ok(MergeChoice::Destination);
+			};
+			callback(&MergeConflict {
+				dimension,
+				chunk,
+				source: &source,
+				source_timestamp,
+				destination: &destination,
+				destination_timestamp,
+			})
+		}
+	})
+}
+
+/// What [prune_chunks] knows about a terrain chunk when it decides whether to delete it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChunkMeta<'a> {
+	pub dimension: &'a str,
+	pub chunk: ChunkPos,
+	/// The time that the chunk was last written, from the region header, in seconds since the Unix epoch.
+	pub timestamp: u32,
+	/// The number of ticks that players spent in the chunk, or None if the chunk has none or could not be read.
+	pub inhabited_time: Option<i64>,
+	/// The `DataVersion` that the chunk was saved with, or None if it has none or could not be read.
+	pub data_version: Option<i32>,
+}
+
+/// Chunks that players spent less than `ticks` in. Chunks without an `InhabitedTime` are kept.
+pub fn inhabited_below(ticks: i64) -> impl Fn(&ChunkMeta) -> bool {
+	move |meta| meta.inhabited_time.is_some_and(|time| time < ticks)
+}
+
+/// Chunks of `dimension` outside of the area from `min` to `max`, inclusive. Chunks of other dimensions are kept.
+pub fn outside_area(dimension: &str, min: ChunkPos, max: ChunkPos) -> impl Fn(&ChunkMeta) -> bool + '_ {
+	move |meta| meta.dimension == dimension && !((min.x..=max.x).contains(&meta.chunk.x) && (min.z..=max.z).contains(&meta.chunk.z))
+}
+
+/// The result of [prune_chunks].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct PruneReport {
+	/// The number of terrain chunks that were deleted.
+	pub chunks: usize,
+	/// The number of bytes that region files shrank by, counting the region files that were removed.
+	pub bytes: u64,
+}
+
+/// Deletes the terrain chunks for which `delete` returns `true`, with their entities and points of interest, and then
+/// compacts the region files that changed. Region files left without chunks are removed.<br>
+/// Use the built-in predicates [inhabited_below] and [outside_area], or combine them in a closure. The world must not
+/// be open in the game while it is pruned.
+/// ```no_run
+/// # use rustnbt::{ pos::ChunkPos, world::* };
+/// let world = World::open("saves/New World").expect("Failed to open world.");
+/// // Chunks that were generated but barely visited, outside of the spawn area.
+/// let barely_visited = inhabited_below(20 * 60);
+/// let away_from_spawn = outside_area(OVERWORLD, ChunkPos::new(-32, -32), ChunkPos::new(31, 31));
+/// let report = prune_chunks(&world, |meta| barely_visited(meta) && away_from_spawn(meta)).expect("Failed to prune.");
+/// println!("Deleted {} chunks and freed {} bytes.", report.chunks, report.bytes);
+/// ```
+pub fn prune_chunks<F: FnMut(&ChunkMeta) -> bool>(world: &World, delete: F) -> Result<PruneReport, NbtError> {
+	Ok(prune_chunks_with(world, delete, ErrorPolicy::fail_fast())?.0)
+}
+
+/// Like [prune_chunks], with a policy for the region files that fail. Also returns the items that were skipped.<br>
+/// Chunks that can not be read are never an error, and are offered to `delete` without their `InhabitedTime` and
+/// `DataVersion`. A region file is skipped as a whole, so it may be left with some of its chunks deleted but not
+/// compacted.
+pub fn prune_chunks_with<F: FnMut(&ChunkMeta) -> bool>(world: &World, mut delete: F, on_error: ErrorPolicy) -> Result<(PruneReport, Vec<ItemError>), NbtError> {
+	let mut report = PruneReport::default();
+	let mut errors = Vec::new();
+	for dimension in world.dimensions()? {
+		for position in world.regions(&dimension, RegionKind::Chunks).with_context(|| ErrorContext::default().dimension(&dimension))? {
+			let context = || ErrorContext::default().dimension(&dimension).region(position);
+			if let Some(pruned) = on_error.run(&mut errors, context, || prune_region(world, &dimension, position, &mut delete))? {
+				report.chunks += pruned.chunks;
+				report.bytes += pruned.bytes;
+			}
+		}
+	}
+	Ok((report, errors))
+}
+
+/// Prunes the chunks of one region position, for [prune_chunks_with].
+fn prune_region<F: FnMut(&ChunkMeta) -> bool>(world: &World, dimension: &str, position: RegionPos, delete: &mut F) -> Result<PruneReport, NbtError> {
+	let mut report = PruneReport::default();
+	let mut region = Region::open(world.region_path(dimension, RegionKind::Chunks, position))?;
+	let mut deleted = Vec::new();
+	let chunks: Vec<ChunkPos> = region.chunks().collect();
+	for chunk in chunks {
+		let root = region.read_raw_chunk(chunk).ok().flatten().and_then(|raw| raw.decode().ok()).map(NamedTag::take_tag);
+		let (inhabited_time, data_version) = match &root {
+			Some(Tag::Compound(root)) => {
+				let level = match root.get("Level") {
+					Some(Tag::Compound(level)) => level,
+					_ => root,
+				};
+				let inhabited_time = match level.get("InhabitedTime") {
+					Some(Tag::Long(time)) => Some(*time),
+					_ => None,
+				};
+				let data_version = match root.get("DataVersion") {
+					Some(Tag::Int(version)) => Some(*version),
+					_ => None,
+				};
+				(inhabited_time, data_version)
+			}
+			_ => (None, None),
+		};
+		let meta = ChunkMeta { dimension, chunk, timestamp: region.timestamp(chunk).unwrap_or(0), inhabited_time, data_version };
+		if delete(&meta) {
+			deleted.push(chunk);
+		}
+	}
+	if deleted.is_empty() {
+		return Ok(report);
+	}
+	report.chunks += deleted.len();
+	drop(region);
+	for kind in RegionKind::ALL {
+		let path = world.region_path(dimension, kind, position);
+		if !path.is_file() {
+			continue;
+		}
+		let mut region = Region::open(&path)?;
+		for chunk in &deleted {
+			region.delete_chunk(*chunk)?;
+		}
+		if region.chunks().next().is_none() {
+			report.bytes += std::fs::metadata(&path)?.len();
+			drop(region);
+			std::fs::remove_file(path)?;
+		} else {
+			report.bytes += region.compact()?;
+		}
+	}
+	Ok(report)
+}
+
+/// An entity found by an [Index].
+#[derive(Clone, PartialEq, Debug)]
+pub struct IndexedEntity {
+	pub dimension: String,
+	/// The entity type, such as `minecraft:zombie`.
+	pub id: String,
+	pub position: [f64; 3],
+}
+
+/// A block entity found by an [Index].
+#[derive(Clone, PartialEq, Debug)]
+pub struct IndexedBlockEntity {
+	pub dimension: String,
+	/// The block entity type, such as `minecraft:chest`.
+	pub id: String,
+	pub position: BlockPos,
+}
+
+/// What an [Index] found in one region file, and when the file was last modified.
+#[derive(Clone, PartialEq, Default, Debug)]
+struct IndexedRegion {
+	modified: i64,
+	entities: Vec<IndexedEntity>,
+	block_entities: Vec<IndexedBlockEntity>,
+}
+
+/// Identifies a region file by dimension, [RegionKind::folder], and position.
+type RegionKey = (String, &'static str, RegionPos);
+
+/// A region file scanned by an [Index], or [None] if it was skipped.
+type ScannedRegion = (RegionKey, Option<IndexedRegion>);
+
+/// A queryable index of the entities, block entities, and players of a world.<br>
+/// Region files are scanned on all available threads. The index can be saved to disk and
+/// [refreshed](Index::refresh) later, which only scans region files whose modification time changed.
+/// Entities are found in entity region files and in the chunks of worlds from before 1.17.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct Index {
+	regions: BTreeMap<RegionKey, IndexedRegion>,
+	players: Vec<String>,
+}
+
+impl Index {
+	/// Scans a whole world.
+	pub fn build(world: &World) -> Result<Self, NbtError> {
+		Ok(Self::build_with(world, ErrorPolicy::fail_fast())?.0)
+	}
+
+	/// Scans a whole world, with a policy for the region files and chunks that fail. See [refresh_with](Index::refresh_with).
+	pub fn build_with(world: &World, on_error: ErrorPolicy) -> Result<(Self, Vec<ItemError>), NbtError> {
+		let mut index = Index::default();
+		let (_, errors) = index.refresh_with(world, on_error)?;
+		Ok((index, errors))
+	}
+
+	/// Scans the region files that were added or modified since the index was built, and forgets deleted ones.
+	/// Returns the number of region files that were scanned.
+	pub fn refresh(&mut self, world: &World) -> Result<usize, NbtError> {
+		Ok(self.refresh_with(world, ErrorPolicy::fail_fast())?.0)
+	}
+
+	/// Like [refresh](Index::refresh), with a policy for the region files and chunks that fail. Also returns the items
+	/// that were skipped, in no particular order.<br>
+	/// Region files that can not be opened are left out of the index, and region files with chunks that were skipped
+	/// are scanned again by the next refresh. Listing the region files and players is not an item, so it still fails.
+	pub fn refresh_with(&mut self, world: &World, on_error: ErrorPolicy) -> Result<(usize, Vec<ItemError>), NbtError> {
+		self.players = world.player_uuids()?;
+		let mut found = BTreeMap::new();
+		for dimension in world.dimensions()? {
+			for kind in [RegionKind::Chunks, RegionKind::Entities] {
+				for region in world.regions(&dimension, kind)? {
+					let path = world.region_path(&dimension, kind, region);
+					found.insert((dimension.clone(), kind.folder(), region), (path.clone(), modified_nanos(&path)?));
+				}
+			}
+		}
+		self.regions.retain(|key, _| found.contains_key(key));
+		let jobs: Vec<(RegionKey, PathBuf, i64)> = found.into_iter()
+			.filter(|(key, (_, modified))| self.regions.get(key).is_none_or(|region| region.modified != *modified))
+			.map(|(key, (path, modified))| (key, path, modified))
+			.collect();
+		let scanned = jobs.len();
+		let (regions, errors) = scan_regions(jobs, on_error)?;
+		for (key, region) in regions {
+			match region {
+				Some(region) => self.regions.insert(key, region),
+				None => self.regions.remove(&key),
+			};
+		}
+		Ok((scanned, errors))
+	}
+
+	/// Reads an index saved with [save](Index::save).
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, NbtError> {
+		let root = read_gzip_nbt(path)?;
+		let Tag::Compound(root) = root.tag() else {
+			return Err(invalid_data("The root of an index must be a compound."));
+		};
+		let strings = |tag: Option<&Tag>| -> Vec<String> {
+			match tag {
+				Some(Tag::List(ListTag::String(strings))) => strings.clone(),
+				_ => Vec::new(),
+			}
+		};
+		let mut index = Index { players: strings(root.get("Players")), ..Default::default() };
+		let Some(Tag::List(ListTag::Compound(regions))) = root.get("Regions") else {
+			return Ok(index);
+		};
+		for region in regions {
+			let invalid = || invalid_data("Invalid region in index.");
+			let (Some(Tag::String(dimension)), Some(Tag::String(folder)), Some(Tag::Int(x)), Some(Tag::Int(z)), Some(Tag::Long(modified))) =
+				(region.get("Dimension"), region.get("Kind"), region.get("X"), region.get("Z"), region.get("Modified")) else {
+				return Err(invalid());
+			};
+			let kind = RegionKind::ALL.into_iter().find(|kind| kind.folder() == folder).ok_or_else(invalid)?;
+			let maps = |key: &str| match region.get(key) {
+				Some(Tag::List(ListTag::Compound(maps))) => maps.as_slice(),
+				_ => &[],
+			};
+			let indexed = IndexedRegion {
+				modified: *modified,
+				entities: maps("Entities").iter().filter_map(|entity| read_entity(dimension, entity)).collect(),
+				block_entities: maps("BlockEntities").iter().filter_map(|entity| read_block_entity(dimension, entity)).collect(),
+			};
+			index.regions.insert((dimension.clone(), kind.folder(), RegionPos::new(*x, *z)), indexed);
+		}
+		Ok(index)
+	}
+
+	/// Writes the index to a gzip compressed NBT file.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), NbtError> {
+		let regions = self.regions.iter().map(|((dimension, folder, pos), region)| {
+			let entities = region.entities.iter().map(|entity| Map::from_iter([
+				("id".to_owned(), Tag::from(entity.id.as_str())),
+				("Pos".to_owned(), Tag::List(ListTag::Double(entity.position.to_vec()))),
+			])).collect();
+			let block_entities = region.block_entities.iter().map(|entity| Map::from_iter([
+				("id".to_owned(), Tag::from(entity.id.as_str())),
+				("x".to_owned(), Tag::Int(entity.position.x)),
+				("y".to_owned(), Tag::Int(entity.position.y)),
+				("z".to_owned(), Tag::Int(entity.position.z)),
+			])).collect();
+			Map::from_iter([
+				("Dimension".to_owned(), Tag::from(dimension.as_str())),
+				("Kind".to_owned(), Tag::from(*folder)),
+				("X".to_owned(), Tag::Int(pos.x)),
+				("Z".to_owned(), Tag::Int(pos.z)),
+				("Modified".to_owned(), Tag::Long(region.modified)),
+				("Entities".to_owned(), Tag::List(ListTag::Compound(entities))),
+				("BlockEntities".to_owned(), Tag::List(ListTag::Compound(block_entities))),
+			])
+		}).collect();
+		let root = Map::from_iter([
+			("Players".to_owned(), Tag::List(ListTag::String(self.players.clone()))),
+			("Regions".to_owned(), Tag::List(ListTag::Compound(regions))),
+		]);
+		write_gzip_nbt(path, &NamedTag::new(Tag::Compound(root)))?;
+		Ok(())
+	}
+
+	/// The UUIDs of the players that have a data file.
+	pub fn players(&self) -> &[String] {
+		&self.players
+	}
+
+	/// All indexed entities.
+	pub fn entities(&self) -> impl Iterator<Item = &IndexedEntity> {
+		self.regions.values().flat_map(|region| region.entities.iter())
+	}
+
+	/// The entities of one type.
+	pub fn entities_of<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a IndexedEntity> + 'a {
+		self.entities().filter(move |entity| entity.id == id)
+	}
+
+	/// The number of entities of each type.
+	pub fn entity_counts(&self) -> BTreeMap<&str, usize> {
+		let mut counts = BTreeMap::new();
+		for entity in self.entities() {
+			*counts.entry(entity.id.as_str()).or_default() += 1;
+		}
+		counts
+	}
+
+	/// All indexed block entities.
+	pub fn block_entities(&self) -> impl Iterator<Item = &IndexedBlockEntity> {
+		self.regions.values().flat_map(|region| region.block_entities.iter())
+	}
+
+	/// The block entities of one type.
+	pub fn block_entities_of<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a IndexedBlockEntity> + 'a {
+		self.block_entities().filter(move |entity| entity.id == id)
+	}
+
+	/// The number of block entities of each type.
+	pub fn block_entity_counts(&self) -> BTreeMap<&str, usize> {
+		let mut counts = BTreeMap::new();
+		for entity in self.block_entities() {
+			*counts.entry(entity.id.as_str()).or_default() += 1;
+		}
+		counts
+	}
+}
+
+/// The modification time of a file in nanoseconds since the Unix epoch.
+fn modified_nanos(path: &Path) -> Result<i64, NbtError> {
+	let modified = std::fs::metadata(path)?.modified()?;
+	Ok(modified.duration_since(std::time::UNIX_EPOCH).map_or(0, |duration| duration.as_nanos() as i64))
+}
+
+/// Scans region files on as many threads as are available. Regions that were skipped by the policy are [None].
+fn scan_regions(jobs: Vec<(RegionKey, PathBuf, i64)>, on_error: ErrorPolicy) -> Result<(Vec<ScannedRegion>, Vec<ItemError>), NbtError> {
+	let next = AtomicUsize::new(0);
+	let threads = std::thread::available_parallelism().map_or(1, |count| count.get()).min(jobs.len());
+	let (results, errors): (Vec<_>, Vec<_>) = std::thread::scope(|scope| {
+		let workers: Vec<_> = (0..threads).map(|_| scope.spawn(|| {
+			let mut scanned = Vec::new();
+			let mut errors = Vec::new();
+			while let Some((key, path, modified)) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+				let region = scan_region(key, path, *modified, on_error, &mut errors);
+				let failed = region.is_err();
+				scanned.push(region.map(|region| (key.clone(), region)));
+				if failed {
+					break;
+				}
+			}
+			(scanned, errors)
+		})).collect();
+		workers.into_iter().map(|worker| worker.join().expect("Index thread panicked.")).unzip()
+	});
+	Ok((results.into_iter().flatten().collect::<Result<_, _>>()?, errors.into_iter().flatten().collect()))
+}
+
+fn scan_region(key: &RegionKey, path: &Path, modified: i64, on_error: ErrorPolicy, errors: &mut Vec<ItemError>) -> Result<Option<IndexedRegion>, NbtError> {
+	let (dimension, _, position) = key;
+	let Some(mut region) = on_error.run(errors, || ErrorContext::default().dimension(dimension).region(*position), || Region::open(path))? else {
+		return Ok(None);
+	};
+	let mut indexed = IndexedRegion { modified, ..Default::default() };
+	let skipped = errors.len();
+	let chunks: Vec<ChunkPos> = region.chunks().collect();
+	for chunk in chunks {
+		let Some(Some(root)) = on_error.run(errors, || ErrorContext::default().dimension(dimension), || region.read_chunk(chunk))? else {
+			continue;
+		};
+		let Tag::Compound(root) = root.tag() else {
+			continue;
+		};
+		// Chunks from before 1.18 keep their data in a Level compound.
+		let level = match root.get("Level") {
+			Some(Tag::Compound(level)) => level,
+			_ => root,
+		};
+		if let Some(Tag::List(ListTag::Compound(entities))) = level.get("Entities") {
+			indexed.entities.extend(entities.iter().filter_map(|entity| read_entity(dimension, entity)));
+		}
+		if let Some(Tag::List(ListTag::Compound(entities))) = level.get("block_entities").or_else(|| level.get("TileEntities")) {
+			indexed.block_entities.extend(entities.iter().filter_map(|entity| read_block_entity(dimension, entity)));
+		}
+	}
+	if errors.len() > skipped {
+		// No modification time matches, so the region is scanned again by the next refresh.
+		indexed.modified = i64::MIN;
+	}
+	Ok(Some(indexed))
+}
+
+fn read_entity(dimension: &str, entity: &Map) -> Option<IndexedEntity> {
+	let (Some(Tag::String(id)), Some(Tag::List(ListTag::Double(position)))) = (entity.get("id"), entity.get("Pos")) else {
+		return None;
+	};
+	Some(IndexedEntity { dimension: dimension.to_owned(), id: id.clone(), position: position.as_slice().try_into().ok()? })
+}
+
+fn read_block_entity(dimension: &str, entity: &Map) -> Option<IndexedBlockEntity> {
+	let (Some(Tag::String(id)), Some(position)) = (entity.get("id"), crate::block_entity::position(entity)) else {
+		return None;
+	};
+	Some(IndexedBlockEntity { dimension: dimension.to_owned(), id: id.clone(), position })
+}
+
+/// The number of chunks in [WorldReport::largest_chunks].
+pub const REPORTED_LARGEST_CHUNKS: usize = 10;
+
+/// A chunk named in a [WorldReport].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ReportedChunk {
+	pub dimension: String,
+	pub chunk: ChunkPos,
+	/// The compressed size of the chunk in bytes.
+	pub size: usize,
+	/// Why the chunk could not be read, for [WorldReport::corrupt_chunks].
+	pub error: Option<String>,
+}
+
+/// A summary of the contents of a world, from [report].
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct WorldReport {
+	/// The number of terrain chunks in each dimension.
+	pub chunks: BTreeMap<String, usize>,
+	/// The number of terrain chunks saved with each `DataVersion`. Chunks without one are counted under 0.
+	pub data_versions: BTreeMap<i32, usize>,
+	/// The number of entities of each type, in entity region files and in the chunks of worlds from before 1.17.
+	pub entities: BTreeMap<String, usize>,
+	/// The largest terrain chunks by compressed size, largest first.
+	pub largest_chunks: Vec<ReportedChunk>,
+	/// The chunks of any kind that could not be read or decompressed.
+	pub corrupt_chunks: Vec<ReportedChunk>,
+}
+
+impl WorldReport {
+	/// The report as a Compound, with counts as Longs.
+	pub fn to_tag(&self) -> Tag {
+		fn counts<I: Iterator<Item = (String, usize)>>(counts: I) -> Tag {
+			Tag::Compound(counts.map(|(key, count)| (key, Tag::Long(count as i64))).collect())
+		}
+		let chunks = |chunks: &[ReportedChunk]| Tag::List(ListTag::Compound(chunks.iter().map(|chunk| {
+			let mut map = Map::from_iter([
+				("Dimension".to_owned(), Tag::from(chunk.dimension.as_str())),
+				("X".to_owned(), Tag::Int(chunk.chunk.x)),
+				("Z".to_owned(), Tag::Int(chunk.chunk.z)),
+				("Size".to_owned(), Tag::Long(chunk.size as i64)),
+			]);
+			if let Some(error) = &chunk.error {
+				map.insert("Error".to_owned(), Tag::from(error.as_str()));
+			}
+			map
+		}).collect()));
+		Tag::Compound(Map::from_iter([
+			("Chunks".to_owned(), counts(self.chunks.iter().map(|(dimension, count)| (dimension.clone(), *count)))),
+			("DataVersions".to_owned(), counts(self.data_versions.iter().map(|(version, count)| (version.to_string(), *count)))),
+			("Entities".to_owned(), counts(self.entities.iter().map(|(id, count)| (id.clone(), *count)))),
+			("LargestChunks".to_owned(), chunks(&self.largest_chunks)),
+			("CorruptChunks".to_owned(), chunks(&self.corrupt_chunks)),
+		]))
+	}
+
+	/// The report as JSON, with the same fields as [to_tag](WorldReport::to_tag).
+	#[cfg(feature = "json")]
+	pub fn to_json(&self) -> serde_json::Value {
+		let chunks = |chunks: &[ReportedChunk]| chunks.iter().map(|chunk| {
+			let mut value = serde_json::json!({ "Dimension": chunk.dimension, "X": chunk.chunk.x, "Z": chunk.chunk.z, "Size": chunk.size });
+			if let Some(error) = &chunk.error {
+				value["Error"] = serde_json::Value::from(error.as_str());
+			}
+			value
+		}).collect::<Vec<_>>();
+		serde_json::json!({
+			"Chunks": self.chunks,
+			"DataVersions": self.data_versions.iter().map(|(version, count)| (version.to_string(), *count)).collect::<BTreeMap<_, _>>(),
+			"Entities": self.entities,
+			"LargestChunks": chunks(&self.largest_chunks),
+			"CorruptChunks": chunks(&self.corrupt_chunks),
+		})
+	}
+}
+
+/// Reads every chunk of a world and summarizes it in a [WorldReport], for server health dashboards.<br>
+/// Chunks that can not be read are listed as corrupt instead of failing the report, but region files that can not be
+/// opened are an error.
+pub fn report(world: &World) -> Result<WorldReport, NbtError> {
+	let mut report = WorldReport::default();
+	for dimension in world.dimensions()? {
+		for kind in [RegionKind::Chunks, RegionKind::Entities] {
+			for region in world.regions(&dimension, kind)? {
+				let mut region = Region::open(world.region_path(&dimension, kind, region))
+					.with_context(|| ErrorContext::default().dimension(&dimension).region(region))?;
+				let chunks: Vec<ChunkPos> = region.chunks().collect();
+				for chunk in chunks {
+					let mut reported = ReportedChunk { dimension: dimension.clone(), chunk, size: 0, error: None };
+					let root = match region.read_raw_chunk(chunk) {
+						Ok(None) => continue,
+						Ok(Some(raw)) => {
+							reported.size = raw.data.len();
+							raw.decode()
+						}
+						Err(err) => Err(err),
+					};
+					let root = match root {
+						Ok(root) => root.take_tag(),
+						Err(err) => {
+							// The dimension and chunk are already in the report.
+							reported.error = Some(err.root().to_string());
+							report.corrupt_chunks.push(reported);
+							continue;
+						}
+					};
+					let Tag::Compound(root) = root else {
+						reported.error = Some("The root of the chunk is not a Compound.".to_owned());
+						report.corrupt_chunks.push(reported);
+						continue;
+					};
+					// Chunks from before 1.18 keep their data in a Level compound.
+					let level = match root.get("Level") {
+						Some(Tag::Compound(level)) => level,
+						_ => &root,
+					};
+					if let Some(Tag::List(ListTag::Compound(entities))) = level.get("Entities") {
+						for entity in entities {
+							if let Some(Tag::String(id)) = entity.get("id") {
+								*report.entities.entry(id.clone()).or_default() += 1;
+							}
+						}
+					}
+					if kind == RegionKind::Chunks {
+						*report.chunks.entry(dimension.clone()).or_default() += 1;
+						let version = match root.get("DataVersion") {
+							Some(Tag::Int(version)) => *version,
+							_ => 0,
+						};
+						*report.data_versions.entry(version).or_default() += 1;
+						report.largest_chunks.push(reported);
+						if report.largest_chunks.len() > REPORTED_LARGEST_CHUNKS * 2 {
+							shrink_largest(&mut report.largest_chunks);
+						}
+					}
+				}
+			}
+		}
+	}
+	shrink_largest(&mut report.largest_chunks);
+	Ok(report)
+}
+
+/// Sorts chunks by size, largest first, and keeps the largest [REPORTED_LARGEST_CHUNKS] of them.
+fn shrink_largest(chunks: &mut Vec<ReportedChunk>) {
+	chunks.sort_by_key(|chunk| std::cmp::Reverse(chunk.size));
+	chunks.truncate(REPORTED_LARGEST_CHUNKS);
+}
+
+/// Reads a gzip compressed NBT file, such as `level.dat` or a player data file.
+pub(crate) fn read_gzip_nbt<P: AsRef<Path>>(path: P) -> Result<NamedTag, NbtError> {
+	let file = File::open(path)?;
+	let mut reader = BufReader::new(flate2::read::GzDecoder::new(BufReader::new(file)));
+	reader.read_nbt()
+}
+
+/// Writes a gzip compressed NBT file, creating the parent directory if needed.
+pub(crate) fn write_gzip_nbt<P: AsRef<Path>>(path: P, root: &NamedTag) -> Result<usize, NbtError> {
+	if let Some(parent) = path.as_ref().parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	let file = File::create(path)?;
+	let mut writer = flate2::write::GzEncoder::new(BufWriter::new(file), flate2::Compression::default());
+	let written = writer.write_nbt(root)?;
+	writer.finish()?;
+	Ok(written)
+}
+
+/// Compresses NBT with gzip in memory, as [write_gzip_nbt] does for files.
+pub(crate) fn gzip_nbt(root: &NamedTag) -> Result<Vec<u8>, NbtError> {
+	let mut writer = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+	writer.write_nbt(root)?;
+	Ok(writer.finish()?)
+}
+
+/// Decompresses NBT written by [gzip_nbt].
+pub(crate) fn gunzip_nbt(bytes: &[u8]) -> Result<NamedTag, NbtError> {
+	flate2::read::GzDecoder::new(bytes).read_nbt()
+}
+
+#[cfg(feature = "json")]
+fn read_json(path: PathBuf) -> Result<Option<serde_json::Value>, NbtError> {
+	match File::open(path) {
+		Ok(file) => Ok(Some(serde_json::from_reader(BufReader::new(file))?)),
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+		Err(err) => Err(err.into()),
+	}
+}
+
+#[cfg(feature = "json")]
+fn write_json(path: PathBuf, value: &serde_json::Value) -> Result<(), NbtError> {
+	if let Some(parent) = path.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	let file = File::create(path)?;
+	// The game writes these files with two space indentation.
+	serde_json::to_writer_pretty(BufWriter::new(file), value)?;
+	Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use crate::{ Map, compound };
+
+	/// Creates an empty directory in the temp folder that is unique to this test.
+	pub(crate) fn temp_world(name: &str) -> PathBuf {
+		let path = std::env::temp_dir().join(format!("rustnbt-{}-{}", name, std::process::id()));
+		let _ = std::fs::remove_dir_all(&path);
+		std::fs::create_dir_all(&path).expect("Failed to create temp dir.");
+		path
+	}
+
+	#[test]
+	fn player_test() -> Result<(), NbtError> {
+		let path = temp_world("player");
+		let world = World::open(&path)?;
+		assert!(world.player_uuids()?.is_empty());
+		let uuid = "00000000-0000-0000-0000-000000000001";
+		let mut data = PlayerData::new(Map::default());
+		data.set_xp_level(7);
+		let player = WorldPlayer {
+			uuid: uuid.to_owned(),
+			data,
+			#[cfg(feature = "json")]
+			stats: Some(serde_json::json!({ "stats": { "minecraft:custom": { "minecraft:jump": 12 } }, "DataVersion": 3120 })),
+			#[cfg(feature = "json")]
+			advancements: None,
+		};
+		world.save_player(&player)?;
+		assert_eq!(world.player_uuids()?, [uuid]);
+		assert_eq!(world.player(uuid)?.data.xp_level(), Some(7));
+		#[cfg(feature = "json")]
+		{
+			let loaded = world.player_with_json(uuid)?;
+			assert_eq!(loaded.stats, player.stats);
+			assert!(loaded.advancements.is_none());
+		}
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+
+	fn chunk(pos: ChunkPos, inhabited: i64) -> NamedTag {
+		NamedTag::new(compound!(
+			("xPos", pos.x),
+			("zPos", pos.z),
+			("InhabitedTime", Tag::Long(inhabited))
+		))
+	}
+
+	fn inhabited(world: &World, dimension: &str, chunk: ChunkPos) -> Option<i64> {
+		let mut region = Region::open(world.region_path(dimension, RegionKind::Chunks, chunk.region())).ok()?;
+		inhabited_time(&region.read_raw_chunk(chunk).ok()??)
+	}
+
+	#[test]
+	fn merge_test() -> Result<(), NbtError> {
+		let path = temp_world("merge");
+		let src_path = path.join("src");
+		let dst_path = path.join("dst");
+		let overworld_chunk = ChunkPos::new(-1, 0);
+		let nether_chunk = ChunkPos::new(1, 2);
+		for (root, inhabited) in [(&src_path, 10), (&dst_path, 5)] {
+			std::fs::create_dir_all(root.join("region"))?;
+			std::fs::create_dir_all(root.join("DIM-1/region"))?;
+			let world = World::open(root)?;
+			let mut region = Region::create(world.region_path(OVERWORLD, RegionKind::Chunks, overworld_chunk.region()))?;
+			region.write_chunk(overworld_chunk, &chunk(overworld_chunk, inhabited))?;
+			region.set_timestamp(overworld_chunk, inhabited as u32)?;
+		}
+		let source = World::open(&src_path)?;
+		let destination = World::open(&dst_path)?;
+		let mut region = Region::create(source.region_path(THE_NETHER, RegionKind::Chunks, nether_chunk.region()))?;
+		region.write_chunk(nether_chunk, &chunk(nether_chunk, 0))?;
+		assert_eq!(source.dimensions()?, [OVERWORLD, THE_NETHER]);
+
+		let report = merge(&source, &destination, MergePolicy::PreferDestination)?;
+		assert_eq!(report, MergeReport { added: 1, replaced: 0, kept: 1, removed: 0 });
+		assert_eq!(inhabited(&destination, OVERWORLD, overworld_chunk), Some(5));
+		assert_eq!(inhabited(&destination, THE_NETHER, nether_chunk), Some(0));
+
+		let mut conflicts = Vec::new();
+		let mut callback = |conflict: &MergeConflict| {
+			conflicts.push((conflict.dimension.to_owned(), conflict.chunk));
+			MergeChoice::Destination
+		};
+		merge(&source, &destination, MergePolicy::Custom(&mut callback))?;
+		// The overworld chunk, and the nether chunk copied by the first merge.
+		assert_eq!(conflicts, [(OVERWORLD.to_owned(), overworld_chunk), (THE_NETHER.to_owned(), nether_chunk)]);
+
+		let report = merge(&source, &destination, MergePolicy::MostInhabited)?;
+		assert_eq!(report.replaced, 1);
+		assert_eq!(inhabited(&destination, OVERWORLD, overworld_chunk), Some(10));
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+
+	#[test]
+	fn merge_entities_test() -> Result<(), NbtError> {
+		let path = temp_world("merge-entities");
+		let (kept, replaced) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0));
+		let mut worlds = Vec::new();
+		for root in [path.join("src"), path.join("dst")] {
+			std::fs::create_dir_all(root.join("region"))?;
+			std::fs::create_dir_all(root.join("entities"))?;
+			let world = World::open(root)?;
+			let mut region = Region::create(world.region_path(OVERWORLD, RegionKind::Chunks, kept.region()))?;
+			region.write_chunk(kept, &chunk(kept, 0))?;
+			region.write_chunk(replaced, &chunk(replaced, 0))?;
+			worlds.push(world);
+		}
+		let (source, destination) = (&worlds[0], &worlds[1]);
+		// Entities are only in the source at the kept position, and only in the destination at the replaced one.
+		Region::create(source.region_path(OVERWORLD, RegionKind::Entities, kept.region()))?.write_chunk(kept, &chunk(kept, 1))?;
+		Region::create(destination.region_path(OVERWORLD, RegionKind::Entities, replaced.region()))?.write_chunk(replaced, &chunk(replaced, 2))?;
+
+		let mut callback = |conflict: &MergeConflict| if conflict.chunk == kept { MergeChoice::Destination } else { MergeChoice::Source };
+		let report = merge(source, destination, MergePolicy::Custom(&mut callback))?;
+		assert_eq!(report, MergeReport { added: 0, replaced: 1, kept: 1, removed: 1 });
+		let entities = Region::open(destination.region_path(OVERWORLD, RegionKind::Entities, kept.region()))?;
+		assert_eq!(entities.chunks().count(), 0);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+
+	#[test]
+	fn index_test() -> Result<(), NbtError> {
+		let path = temp_world("index");
+		std::fs::create_dir_all(path.join("region"))?;
+		std::fs::create_dir_all(path.join("entities"))?;
+		let world = World::open(&path)?;
+		let player = WorldPlayer {
+			uuid: "00000000-0000-0000-0000-000000000002".to_owned(),
+			data: PlayerData::new(Map::default()),
+			#[cfg(feature = "json")]
+			stats: None,
+			#[cfg(feature = "json")]
+			advancements: None,
+		};
+		world.save_player(&player)?;
+		let chunk = ChunkPos::new(0, 0);
+		let chest = Map::from_iter([
+			("id".to_owned(), Tag::from("minecraft:chest")),
+			("x".to_owned(), Tag::Int(1)),
+			("y".to_owned(), Tag::Int(64)),
+			("z".to_owned(), Tag::Int(2)),
+		]);
+		let mut terrain = Region::create(world.region_path(OVERWORLD, RegionKind::Chunks, chunk.region()))?;
+		terrain.write_chunk(chunk, &NamedTag::new(compound!(("block_entities", Tag::List(ListTag::Compound(vec![chest]))))))?;
+		let zombie = |x: f64| Map::from_iter([
+			("id".to_owned(), Tag::from("minecraft:zombie")),
+			("Pos".to_owned(), Tag::List(ListTag::Double(vec![x, 64.0, 0.5]))),
+		]);
+		let entities_path = world.region_path(OVERWORLD, RegionKind::Entities, chunk.region());
+		let mut entities = Region::create(&entities_path)?;
+		entities.write_chunk(chunk, &NamedTag::new(compound!(("Entities", Tag::List(ListTag::Compound(vec![zombie(0.5), zombie(3.5)]))))))?;
+
+		let mut index = Index::build(&world)?;
+		assert_eq!(index.players(), ["00000000-0000-0000-0000-000000000002"]);
+		assert_eq!(index.entity_counts(), BTreeMap::from([("minecraft:zombie", 2)]));
+		assert_eq!(index.entities_of("minecraft:zombie").nth(1).map(|zombie| zombie.position), Some([3.5, 64.0, 0.5]));
+		let chests: Vec<_> = index.block_entities_of("minecraft:chest").collect();
+		assert_eq!(chests, [&IndexedBlockEntity { dimension: OVERWORLD.to_owned(), id: "minecraft:chest".to_owned(), position: BlockPos::new(1, 64, 2) }]);
+
+		let saved = path.join("index.dat");
+		index.save(&saved)?;
+		assert_eq!(Index::load(&saved)?, index);
+
+		assert_eq!(index.refresh(&world)?, 0);
+		entities.write_chunk(chunk, &NamedTag::new(compound!(("Entities", Tag::List(ListTag::Compound(vec![zombie(0.5)]))))))?;
+		File::options().write(true).open(&entities_path)?.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(10))?;
+		assert_eq!(index.refresh(&world)?, 1);
+		assert_eq!(index.entity_counts(), BTreeMap::from([("minecraft:zombie", 1)]));
+		drop(entities);
+		std::fs::remove_file(&entities_path)?;
+		assert_eq!(index.refresh(&world)?, 0);
+		assert_eq!(index.entities().count(), 0);
+		assert_eq!(index.block_entities().count(), 1);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+
+	#[test]
+	fn report_test() -> Result<(), NbtError> {
+		let path = temp_world("report");
+		std::fs::create_dir_all(path.join("region"))?;
+		std::fs::create_dir_all(path.join("entities"))?;
+		let world = World::open(&path)?;
+		let mut terrain = Region::create(world.region_path(OVERWORLD, RegionKind::Chunks, RegionPos::new(0, 0)))?;
+		terrain.write_chunk(ChunkPos::new(0, 0), &NamedTag::new(compound!(("DataVersion", 3700), ("Status", "minecraft:full"))))?;
+		let cows = Tag::List(ListTag::Compound(vec![Map::from_iter([("id".to_owned(), Tag::from("minecraft:cow"))])]));
+		let padding = Tag::ByteArray((0..1000).map(|i| (i * 7 % 251) as i8).collect());
+		let old = compound!(("Level", compound!(("Entities", cows))), ("Padding", padding));
+		terrain.write_chunk(ChunkPos::new(1, 0), &NamedTag::new(old))?;
+		terrain.write_raw_chunk(ChunkPos::new(2, 0), &RawChunk { compression: 2, data: vec![1, 2, 3] }, 0)?;
+		let mut entities = Region::create(world.region_path(OVERWORLD, RegionKind::Entities, RegionPos::new(0, 0)))?;
+		entities.write_chunk(ChunkPos::new(0, 0), &NamedTag::new(compound!(("Entities", Tag::List(ListTag::Compound(vec![
+			Map::from_iter([("id".to_owned(), Tag::from("minecraft:cow"))]),
+			Map::from_iter([("id".to_owned(), Tag::from("minecraft:zombie"))]),
+		]))))))?;
+
+		let report = report(&world)?;
+		assert_eq!(report.chunks, BTreeMap::from([(OVERWORLD.to_owned(), 2)]));
+		assert_eq!(report.data_versions, BTreeMap::from([(0, 1), (3700, 1)]));
+		assert_eq!(report.entities, BTreeMap::from([("minecraft:cow".to_owned(), 2), ("minecraft:zombie".to_owned(), 1)]));
+		assert_eq!(report.largest_chunks.iter().map(|chunk| chunk.chunk).collect::<Vec<_>>(), [ChunkPos::new(1, 0), ChunkPos::new(0, 0)]);
+		assert_eq!(report.corrupt_chunks.len(), 1);
+		assert_eq!((report.corrupt_chunks[0].chunk, report.corrupt_chunks[0].size), (ChunkPos::new(2, 0), 3));
+		let tag = report.to_tag();
+		assert_eq!(crate::path::TagPath::new().key("Entities").key("minecraft:cow").get(&tag).as_deref(), Some(&Tag::Long(2)));
+		#[cfg(feature = "json")]
+		assert_eq!(report.to_json()["DataVersions"]["3700"], 1);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+
+	#[test]
+	fn prune_test() -> Result<(), NbtError> {
+		let path = temp_world("prune");
+		std::fs::create_dir_all(path.join("region"))?;
+		std::fs::create_dir_all(path.join("entities"))?;
+		let world = World::open(&path)?;
+		let (kept, visited, far) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0), ChunkPos::new(40, 0));
+		for (pos, inhabited) in [(kept, 1000), (visited, 5), (far, 0)] {
+			Region::create(world.region_path(OVERWORLD, RegionKind::Chunks, pos.region()))?.write_chunk(pos, &chunk(pos, inhabited))?;
+		}
+		let entities_path = world.region_path(OVERWORLD, RegionKind::Entities, visited.region());
+		Region::create(&entities_path)?.write_chunk(visited, &NamedTag::new(compound!(("Entities", Tag::List(ListTag::Empty)))))?;
+
+		let away = outside_area(OVERWORLD, ChunkPos::new(-8, -8), ChunkPos::new(8, 8));
+		let mut seen = Vec::new();
+		let report = prune_chunks(&world, |meta| {
+			seen.push(meta.chunk);
+			away(meta)
+		})?;
+		assert_eq!(seen, [kept, visited, far]);
+		assert_eq!(report.chunks, 1);
+		assert!(!world.region_path(OVERWORLD, RegionKind::Chunks, far.region()).exists());
+
+		let report = prune_chunks(&world, inhabited_below(100))?;
+		assert_eq!(report.chunks, 1);
+		assert!(report.bytes > 0);
+		assert!(!entities_path.exists());
+		assert_eq!(inhabited(&world, OVERWORLD, kept), Some(1000));
+		assert_eq!(inhabited(&world, OVERWORLD, visited), None);
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+
+	#[test]
+	fn error_policy_test() -> Result<(), NbtError> {
+		let path = temp_world("error-policy");
+		std::fs::create_dir_all(path.join("src/region"))?;
+		std::fs::create_dir_all(path.join("dst"))?;
+		let source = World::open(path.join("src"))?;
+		let destination = World::open(path.join("dst"))?;
+		let (good, corrupt) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0));
+		let mut region = Region::create(source.region_path(OVERWORLD, RegionKind::Chunks, good.region()))?;
+		region.write_chunk(good, &chunk(good, 5))?;
+		region.write_raw_chunk(corrupt, &RawChunk { compression: 2, data: vec![1, 2, 3] }, 0)?;
+		std::fs::write(source.region_path(OVERWORLD, RegionKind::Chunks, RegionPos::new(1, 0)), [0; 10])?;
+
+		assert!(Index::build(&source).is_err());
+		let (mut index, errors) = Index::build_with(&source, ErrorPolicy::skip_and_collect())?;
+		let skipped: Vec<_> = errors.iter().map(|error| (error.context.region, error.context.chunk)).collect();
+		assert_eq!(skipped, [(Some(RegionPos::new(0, 0)), Some(corrupt)), (Some(RegionPos::new(1, 0)), None)]);
+		// The region with a skipped chunk is scanned again.
+		assert_eq!(index.refresh_with(&source, ErrorPolicy::skip_and_collect())?.0, 2);
+
+		let (report, errors) = merge_with(&source, &destination, MergePolicy::PreferSource, ErrorPolicy::skip_and_collect())?;
+		// Chunks are copied without being decompressed, so only the corrupt region file fails.
+		assert_eq!((report.added, errors.len()), (2, 1));
+		assert_eq!(inhabited(&destination, OVERWORLD, good), Some(5));
+		let (report, errors) = prune_chunks_with(&source, |meta| meta.chunk == good, ErrorPolicy::skip_and_collect())?;
+		assert_eq!((report.chunks, errors.len()), (1, 1));
+		std::fs::remove_dir_all(path)?;
+		Ok(())
+	}
+}